@@ -1552,7 +1552,7 @@ open class MeshRepository(
 	                        )
 
 	                        val syncPeerIds = linkedSetOf(peerId.trim())
-	                        val isHeadless = agentVersion.contains("/headless/")
+	                        val isHeadless = uniffi.api.isRelayAgentVersion(agentVersion)
 	                        val transportIdentity = resolveTransportIdentity(peerId)
 	                        val shouldTreatAsHeadless = isBootstrapRelayPeer(peerId) || (isHeadless && transportIdentity == null)
 	                        if (shouldTreatAsHeadless) {
@@ -2350,6 +2350,42 @@ open class MeshRepository(
                         "[OK] Receipt processing complete: msg=$messageId status=$normalized"
                     )
                 }
+
+                override fun onBroadcastReceived(senderPublicKeyHex: String, topic: String, data: ByteArray) {
+                    Timber.i("Broadcast received on $topic from ${senderPublicKeyHex.take(12)} (${data.size} bytes)")
+                    repoScope.launch {
+                        com.scmessenger.android.service.MeshEventBus.emitMessageEvent(
+                            com.scmessenger.android.service.MessageEvent.BroadcastReceived(senderPublicKeyHex, topic, data)
+                        )
+                    }
+                }
+
+                override fun onInboxPruned(count: UInt) {
+                    Timber.i("Inbox sweeper removed $count expired message(s)")
+                    repoScope.launch {
+                        com.scmessenger.android.service.MeshEventBus.emitMessageEvent(
+                            com.scmessenger.android.service.MessageEvent.InboxPruned(count.toInt())
+                        )
+                    }
+                }
+
+                override fun onTyping(senderPublicKeyHex: String, isTyping: Boolean) {
+                    Timber.d("Typing from ${senderPublicKeyHex.take(12)}: $isTyping")
+                    repoScope.launch {
+                        com.scmessenger.android.service.MeshEventBus.emitMessageEvent(
+                            com.scmessenger.android.service.MessageEvent.Typing(senderPublicKeyHex, isTyping)
+                        )
+                    }
+                }
+
+                override fun onUnknownMessage(typeId: UInt, senderPublicKeyHex: String, payload: ByteArray) {
+                    Timber.w("Message of unsupported type $typeId from ${senderPublicKeyHex.take(12)} (${payload.size} bytes)")
+                    repoScope.launch {
+                        com.scmessenger.android.service.MeshEventBus.emitMessageEvent(
+                            com.scmessenger.android.service.MessageEvent.UnknownType(typeId.toInt(), senderPublicKeyHex, payload)
+                        )
+                    }
+                }
             }
             ironCore?.setDelegate(coreDelegate)
 
@@ -5822,7 +5858,9 @@ open class MeshRepository(
     }
 
     fun saveSettings(settings: uniffi.api.MeshSettings) {
-        settingsManager?.save(settings)
+        // Through the service when it exists so the running core picks the
+        // change up (e.g. the relay toggle) instead of waiting for a restart.
+        meshService?.updateSettings(settings) ?: settingsManager?.save(settings)
         Timber.i("Settings saved")
     }
 
@@ -128,6 +128,10 @@ sealed class MessageEvent {
     data class Sent(val messageId: String, val peerId: String) : MessageEvent()
     data class Delivered(val messageId: String) : MessageEvent()
     data class Failed(val messageId: String, val error: String) : MessageEvent()
+    data class BroadcastReceived(val senderPublicKeyHex: String, val topic: String, val data: ByteArray) : MessageEvent()
+    data class InboxPruned(val count: Int) : MessageEvent()
+    data class Typing(val senderPublicKeyHex: String, val isTyping: Boolean) : MessageEvent()
+    data class UnknownType(val typeId: Int, val senderPublicKeyHex: String, val payload: ByteArray) : MessageEvent()
 }
 
 /**
@@ -20,8 +20,36 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use tower_http::cors::{Any, CorsLayer};
 
+/// Default control API port.
 pub const API_PORT: u16 = 9876;
-pub const API_ADDR: &str = "127.0.0.1:9876";
+/// Environment variable overriding the control API port.
+pub const API_PORT_ENV: &str = "SCM_API_PORT";
+
+/// Port given with `--api-port`; wins over the environment.
+static API_PORT_OVERRIDE: std::sync::OnceLock<u16> = std::sync::OnceLock::new();
+
+/// Use `port` for the control API for the rest of the process
+/// (`--api-port`). Only the first call takes effect.
+pub fn set_api_port(port: u16) {
+    let _ = API_PORT_OVERRIDE.set(port);
+}
+
+/// Control API port: `--api-port`, then `SCM_API_PORT`, then `API_PORT`.
+/// Two nodes on one host need different ports as well as different data
+/// directories.
+pub fn api_port() -> u16 {
+    API_PORT_OVERRIDE.get().copied().unwrap_or_else(|| {
+        std::env::var(API_PORT_ENV)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(API_PORT)
+    })
+}
+
+/// Local address of the control API, as `host:port`.
+pub fn api_addr() -> String {
+    format!("127.0.0.1:{}", api_port())
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SendMessageRequest {
@@ -64,6 +92,9 @@ pub struct AddContactResponse {
 pub struct PeerEntry {
     pub peer_id: String,
     pub reputation: f64,
+    /// Rolling-average ping round trip, once at least one ping completed.
+    #[serde(default)]
+    pub rtt_ms: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -93,10 +124,22 @@ pub struct SwarmStatsResponse {
     pub stats: Vec<ApiConnectionStats>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NatStatusResponse {
+    pub nat_type: String,
+    pub hole_punch_enabled: bool,
+    pub upnp_enabled: bool,
+    /// `(peer_id, status)` of the latest hole punch with each peer
+    pub hole_punches: Vec<(String, String)>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GetHistoryRequest {
     pub peer_id: Option<String>,
     pub limit: Option<usize>,
+    /// `next_cursor` from the previous page; only used with `peer_id`.
+    #[serde(default)]
+    pub cursor: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -110,6 +153,9 @@ pub struct HistoryMessage {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GetHistoryResponse {
     pub messages: Vec<HistoryMessage>,
+    /// Pass back as `cursor` to fetch the next older page; absent at the end.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -122,11 +168,45 @@ pub struct GetListenersResponse {
     pub listeners: Vec<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RelayReservationInfo {
+    pub relay_peer_id: String,
+    /// Relay address the reservation was requested through, if known.
+    pub relay_addr: Option<String>,
+    /// False while the relay has not yet accepted the reservation.
+    pub accepted: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RelayReservationsResponse {
+    pub reservations: Vec<RelayReservationInfo>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConnectionPathStateResponse {
     pub state: String,
 }
 
+/// Node gauges served at `GET /api/status`. Fields are only ever added, never
+/// renamed or removed, so dashboards can scrape it across versions.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StatusResponse {
+    /// Always `"running"` while the API answers.
+    pub status: String,
+    /// Seconds since the control API started.
+    pub uptime_secs: u64,
+    /// Peers currently connected to the swarm.
+    pub connected_peers: usize,
+    /// Gossipsub topics this node is subscribed to.
+    pub subscribed_topics: usize,
+    /// Entries in the persistent connection ledger.
+    pub ledger_entries: usize,
+    /// Messages queued in the core outbox awaiting delivery.
+    pub outbox_count: u32,
+    /// Messages held in the core inbox.
+    pub inbox_count: u32,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DriftStatusResponse {
     pub state: String,
@@ -152,6 +232,63 @@ pub struct DiscoveryPeersResponse {
     pub peers: Vec<DiscoveredPeer>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PulledPeer {
+    pub peer_id: String,
+    pub addresses: Vec<String>,
+    /// Connected peer that reported it
+    pub source: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompactStorageResponse {
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    pub records_rewritten: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PeerExchangeResponse {
+    pub queried: usize,
+    pub responded: usize,
+    pub peers: Vec<PulledPeer>,
+    /// `(peer_id, error)` for peers that did not answer
+    pub errors: Vec<(String, String)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TopicPublishRequest {
+    pub topic: String,
+    pub data: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TopicPublishResponse {
+    /// Peers the payload was forwarded to; 0 means nobody subscribes
+    pub peers: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReconnectResponse {
+    /// Bootstrap dials (or DNS resolutions) the swarm started
+    pub bootstrap_dials: usize,
+    /// Ledger peers dialed: not connected, not blocked and out of backoff
+    pub ledger_dials: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PeerDetailResponse {
+    pub peer_id: String,
+    pub connected: bool,
+    pub rtt_ms: Option<u64>,
+    /// `None` until the peer has been identified
+    pub capabilities: Option<scmessenger_core::transport::PeerCapabilities>,
+    /// Ed25519 public key (hex) the peer proved over its live connection;
+    /// `None` if it is not connected or not yet identified
+    #[serde(default)]
+    pub public_key: Option<String>,
+}
+
 // Farm Test Harness Types
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -212,7 +349,7 @@ lazy_static! {
 
 // Check if API is available
 pub async fn is_api_available() -> bool {
-    tokio::net::TcpStream::connect(API_ADDR).await.is_ok()
+    tokio::net::TcpStream::connect(api_addr()).await.is_ok()
 }
 
 // Client functions for CLI commands
@@ -233,7 +370,7 @@ pub async fn send_message_via_api(recipient: &str, message: &str) -> Result<()>
     let json = serde_json::to_string(&req_body)?;
     let req = hyper::Request::builder()
         .method(Method::POST)
-        .uri(format!("http://{}/api/send", API_ADDR))
+        .uri(format!("http://{}/api/send", api_addr()))
         .header("content-type", "application/json")
         .body(Full::new(Bytes::from(json)))?;
 
@@ -274,7 +411,7 @@ pub async fn add_contact_via_api(
     let json = serde_json::to_string(&req_body)?;
     let req = hyper::Request::builder()
         .method(Method::POST)
-        .uri(format!("http://{}/api/contacts", API_ADDR))
+        .uri(format!("http://{}/api/contacts", api_addr()))
         .header("content-type", "application/json")
         .body(Full::new(Bytes::from(json)))?;
 
@@ -304,7 +441,7 @@ pub async fn get_peers_via_api() -> Result<Vec<PeerEntry>> {
 
     let req = hyper::Request::builder()
         .method(Method::GET)
-        .uri(format!("http://{}/api/peers", API_ADDR))
+        .uri(format!("http://{}/api/peers", api_addr()))
         .body(Empty::<Bytes>::new())?;
 
     let resp = client.request(req).await?;
@@ -324,7 +461,7 @@ pub async fn get_swarm_stats_via_api() -> Result<Vec<ApiConnectionStats>> {
 
     let req = hyper::Request::builder()
         .method(Method::GET)
-        .uri(format!("http://{}/api/swarm/stats", API_ADDR))
+        .uri(format!("http://{}/api/swarm/stats", api_addr()))
         .body(Empty::<Bytes>::new())?;
 
     let resp = client.request(req).await?;
@@ -349,12 +486,16 @@ pub async fn get_history_via_api(
 
     let client = Client::builder(TokioExecutor::new()).build_http();
 
-    let req_body = GetHistoryRequest { peer_id, limit };
+    let req_body = GetHistoryRequest {
+        peer_id,
+        limit,
+        cursor: None,
+    };
 
     let json = serde_json::to_string(&req_body)?;
     let req = hyper::Request::builder()
         .method(Method::POST)
-        .uri(format!("http://{}/api/history", API_ADDR))
+        .uri(format!("http://{}/api/history", api_addr()))
         .header("content-type", "application/json")
         .body(Full::new(Bytes::from(json)))?;
 
@@ -365,6 +506,27 @@ pub async fn get_history_via_api(
     Ok(response.messages)
 }
 
+pub async fn get_nat_status_via_api() -> Result<NatStatusResponse> {
+    use http_body_util::{BodyExt, Empty};
+    use hyper::body::Bytes;
+    use hyper_util::client::legacy::Client;
+    use hyper_util::rt::TokioExecutor;
+
+    let client = Client::builder(TokioExecutor::new()).build_http();
+
+    let req = hyper::Request::builder()
+        .method(Method::GET)
+        .uri(format!("http://{}/api/swarm/nat", api_addr()))
+        .body(Empty::<Bytes>::new())?;
+
+    let resp = client.request(req).await?;
+    if !resp.status().is_success() {
+        anyhow::bail!("Failed to get NAT status: {}", resp.status());
+    }
+    let body_bytes = resp.into_body().collect().await?.to_bytes();
+    Ok(serde_json::from_slice(&body_bytes)?)
+}
+
 pub async fn get_external_address_via_api() -> Result<Vec<String>> {
     use http_body_util::{BodyExt, Empty};
     use hyper::body::Bytes;
@@ -375,7 +537,7 @@ pub async fn get_external_address_via_api() -> Result<Vec<String>> {
 
     let req = hyper::Request::builder()
         .method(Method::GET)
-        .uri(format!("http://{}/api/external-address", API_ADDR))
+        .uri(format!("http://{}/api/external-address", api_addr()))
         .body(Empty::<Bytes>::new())?;
 
     let resp = client.request(req).await?;
@@ -405,7 +567,7 @@ pub async fn get_listeners_via_api() -> Result<Vec<String>> {
 
     let req = hyper::Request::builder()
         .method(Method::GET)
-        .uri(format!("http://{}/api/listeners", API_ADDR))
+        .uri(format!("http://{}/api/listeners", api_addr()))
         .body(Empty::<Bytes>::new())?;
 
     let resp = client.request(req).await?;
@@ -414,6 +576,25 @@ pub async fn get_listeners_via_api() -> Result<Vec<String>> {
     Ok(response.listeners)
 }
 
+pub async fn get_relay_reservations_via_api() -> Result<Vec<RelayReservationInfo>> {
+    use http_body_util::{BodyExt, Empty};
+    use hyper::body::Bytes;
+    use hyper_util::client::legacy::Client;
+    use hyper_util::rt::TokioExecutor;
+
+    let client = Client::builder(TokioExecutor::new()).build_http();
+
+    let req = hyper::Request::builder()
+        .method(Method::GET)
+        .uri(format!("http://{}/api/relay/reservations", api_addr()))
+        .body(Empty::<Bytes>::new())?;
+
+    let resp = client.request(req).await?;
+    let body_bytes = resp.into_body().collect().await?.to_bytes();
+    let response: RelayReservationsResponse = serde_json::from_slice(&body_bytes)?;
+    Ok(response.reservations)
+}
+
 pub async fn get_connection_path_state_via_api() -> Result<String> {
     use http_body_util::{BodyExt, Empty};
     use hyper::body::Bytes;
@@ -424,7 +605,7 @@ pub async fn get_connection_path_state_via_api() -> Result<String> {
 
     let req = hyper::Request::builder()
         .method(Method::GET)
-        .uri(format!("http://{}/api/connection-path-state", API_ADDR))
+        .uri(format!("http://{}/api/connection-path-state", api_addr()))
         .body(Empty::<Bytes>::new())?;
 
     let resp = client.request(req).await?;
@@ -432,6 +613,25 @@ pub async fn get_connection_path_state_via_api() -> Result<String> {
     let response: ConnectionPathStateResponse = serde_json::from_slice(&body_bytes)?;
     Ok(response.state)
 }
+pub async fn get_status_via_api() -> Result<StatusResponse> {
+    use http_body_util::{BodyExt, Empty};
+    use hyper::body::Bytes;
+    use hyper_util::client::legacy::Client;
+    use hyper_util::rt::TokioExecutor;
+
+    let client = Client::builder(TokioExecutor::new()).build_http();
+
+    let req = hyper::Request::builder()
+        .method(Method::GET)
+        .uri(format!("http://{}/api/status", api_addr()))
+        .body(Empty::<Bytes>::new())?;
+
+    let resp = client.request(req).await?;
+    let body_bytes = resp.into_body().collect().await?.to_bytes();
+    let response: StatusResponse = serde_json::from_slice(&body_bytes)?;
+    Ok(response)
+}
+
 pub async fn get_drift_state_via_api() -> Result<DriftStatusResponse> {
     use http_body_util::{BodyExt, Empty};
     use hyper::body::Bytes;
@@ -442,7 +642,7 @@ pub async fn get_drift_state_via_api() -> Result<DriftStatusResponse> {
 
     let req = hyper::Request::builder()
         .method(Method::GET)
-        .uri(format!("http://{}/api/drift-status", API_ADDR))
+        .uri(format!("http://{}/api/drift-status", api_addr()))
         .body(Empty::<Bytes>::new())?;
 
     let resp = client.request(req).await?;
@@ -461,7 +661,7 @@ pub async fn get_discovery_status() -> Result<DiscoveryStatusResponse> {
 
     let req = hyper::Request::builder()
         .method(Method::GET)
-        .uri(format!("http://{}/api/discovery/status", API_ADDR))
+        .uri(format!("http://{}/api/discovery/status", api_addr()))
         .body(Empty::<Bytes>::new())?;
 
     let resp = client.request(req).await?;
@@ -480,7 +680,7 @@ pub async fn trigger_discovery_scan() -> Result<()> {
 
     let req = hyper::Request::builder()
         .method(Method::POST)
-        .uri(format!("http://{}/api/discovery/scan", API_ADDR))
+        .uri(format!("http://{}/api/discovery/scan", api_addr()))
         .body(Empty::<Bytes>::new())?;
 
     let resp = client.request(req).await?;
@@ -500,7 +700,7 @@ pub async fn get_discovery_peers() -> Result<Vec<DiscoveredPeer>> {
 
     let req = hyper::Request::builder()
         .method(Method::GET)
-        .uri(format!("http://{}/api/discovery/peers", API_ADDR))
+        .uri(format!("http://{}/api/discovery/peers", api_addr()))
         .body(Empty::<Bytes>::new())?;
 
     let resp = client.request(req).await?;
@@ -519,7 +719,7 @@ pub async fn export_diagnostics_via_api() -> Result<String> {
 
     let req = hyper::Request::builder()
         .method(Method::GET)
-        .uri(format!("http://{}/api/diagnostics", API_ADDR))
+        .uri(format!("http://{}/api/diagnostics", api_addr()))
         .body(Empty::<Bytes>::new())?;
 
     let resp = client.request(req).await?;
@@ -527,12 +727,150 @@ pub async fn export_diagnostics_via_api() -> Result<String> {
     String::from_utf8(body_bytes.to_vec()).context("Diagnostics response was not UTF-8")
 }
 
+pub async fn export_support_bundle_via_api() -> Result<String> {
+    use http_body_util::{BodyExt, Empty};
+    use hyper::body::Bytes;
+    use hyper_util::client::legacy::Client;
+    use hyper_util::rt::TokioExecutor;
+
+    let client = Client::builder(TokioExecutor::new()).build_http();
+
+    let req = hyper::Request::builder()
+        .method(Method::GET)
+        .uri(format!("http://{}/api/support-bundle", api_addr()))
+        .body(Empty::<Bytes>::new())?;
+
+    let resp = client.request(req).await?;
+    let body_bytes = resp.into_body().collect().await?.to_bytes();
+    String::from_utf8(body_bytes.to_vec()).context("Support bundle response was not UTF-8")
+}
+
+pub async fn request_peers_via_api() -> Result<PeerExchangeResponse> {
+    use http_body_util::{BodyExt, Empty};
+    use hyper::body::Bytes;
+    use hyper_util::client::legacy::Client;
+    use hyper_util::rt::TokioExecutor;
+
+    let client = Client::builder(TokioExecutor::new()).build_http();
+
+    let req = hyper::Request::builder()
+        .method(Method::POST)
+        .uri(format!("http://{}/api/peers/discover", api_addr()))
+        .body(Empty::<Bytes>::new())?;
+
+    let resp = client.request(req).await?;
+    if !resp.status().is_success() {
+        anyhow::bail!("Failed to request peers: {}", resp.status());
+    }
+    let body_bytes = resp.into_body().collect().await?.to_bytes();
+    Ok(serde_json::from_slice(&body_bytes)?)
+}
+
+pub async fn publish_topic_via_api(topic: &str, data: &str) -> Result<TopicPublishResponse> {
+    use http_body_util::{BodyExt, Full};
+    use hyper::body::Bytes;
+    use hyper_util::client::legacy::Client;
+    use hyper_util::rt::TokioExecutor;
+
+    let client = Client::builder(TokioExecutor::new()).build_http();
+
+    let json = serde_json::to_string(&TopicPublishRequest {
+        topic: topic.to_string(),
+        data: data.to_string(),
+    })?;
+    let req = hyper::Request::builder()
+        .method(Method::POST)
+        .uri(format!("http://{}/api/topics/publish", api_addr()))
+        .header("content-type", "application/json")
+        .body(Full::new(Bytes::from(json)))?;
+
+    let resp = client.request(req).await?;
+    let status = resp.status();
+    let body_bytes = resp.into_body().collect().await?.to_bytes();
+    if !status.is_success() {
+        anyhow::bail!(
+            "Failed to publish to {}: {} {}",
+            topic,
+            status,
+            String::from_utf8_lossy(&body_bytes)
+        );
+    }
+    Ok(serde_json::from_slice(&body_bytes)?)
+}
+
+pub async fn reconnect_via_api() -> Result<ReconnectResponse> {
+    use http_body_util::{BodyExt, Empty};
+    use hyper::body::Bytes;
+    use hyper_util::client::legacy::Client;
+    use hyper_util::rt::TokioExecutor;
+
+    let client = Client::builder(TokioExecutor::new()).build_http();
+
+    let req = hyper::Request::builder()
+        .method(Method::POST)
+        .uri(format!("http://{}/api/reconnect", api_addr()))
+        .body(Empty::<Bytes>::new())?;
+
+    let resp = client.request(req).await?;
+    if !resp.status().is_success() {
+        anyhow::bail!("Failed to reconnect: {}", resp.status());
+    }
+    let body_bytes = resp.into_body().collect().await?.to_bytes();
+    Ok(serde_json::from_slice(&body_bytes)?)
+}
+
+pub async fn compact_storage_via_api() -> Result<CompactStorageResponse> {
+    use http_body_util::{BodyExt, Empty};
+    use hyper::body::Bytes;
+    use hyper_util::client::legacy::Client;
+    use hyper_util::rt::TokioExecutor;
+
+    let client = Client::builder(TokioExecutor::new()).build_http();
+
+    let req = hyper::Request::builder()
+        .method(Method::POST)
+        .uri(format!("http://{}/api/maintenance/compact", api_addr()))
+        .body(Empty::<Bytes>::new())?;
+
+    let resp = client.request(req).await?;
+    if !resp.status().is_success() {
+        anyhow::bail!("Failed to compact storage: {}", resp.status());
+    }
+    let body_bytes = resp.into_body().collect().await?.to_bytes();
+    Ok(serde_json::from_slice(&body_bytes)?)
+}
+
+pub async fn get_peer_detail_via_api(peer_id: &str) -> Result<PeerDetailResponse> {
+    use http_body_util::{BodyExt, Empty};
+    use hyper::body::Bytes;
+    use hyper_util::client::legacy::Client;
+    use hyper_util::rt::TokioExecutor;
+
+    let client = Client::builder(TokioExecutor::new()).build_http();
+
+    let req = hyper::Request::builder()
+        .method(Method::GET)
+        .uri(format!("http://{}/api/peers/{}", api_addr(), peer_id))
+        .body(Empty::<Bytes>::new())?;
+
+    let resp = client.request(req).await?;
+    if !resp.status().is_success() {
+        anyhow::bail!("Failed to get peer {}: {}", peer_id, resp.status());
+    }
+    let body_bytes = resp.into_body().collect().await?.to_bytes();
+    Ok(serde_json::from_slice(&body_bytes)?)
+}
+
 // Server implementation
 
 #[derive(Clone)]
 pub struct ApiContext {
     pub core: Arc<scmessenger_core::IronCore>,
     pub swarm_handle: Arc<scmessenger_core::transport::SwarmHandle>,
+    /// Connected peers as tracked by the node's event loop (shared with `WebContext`).
+    pub peers: Arc<tokio::sync::Mutex<HashMap<libp2p::PeerId, Option<String>>>>,
+    pub ledger: Arc<tokio::sync::Mutex<crate::ledger::ConnectionLedger>>,
+    pub start_time: std::time::Instant,
 }
 
 pub async fn stop_node_via_api() -> Result<()> {
@@ -545,7 +883,7 @@ pub async fn stop_node_via_api() -> Result<()> {
 
     let req = hyper::Request::builder()
         .method(Method::POST)
-        .uri(format!("http://{}/api/shutdown", API_ADDR))
+        .uri(format!("http://{}/api/shutdown", api_addr()))
         .body(Empty::<Bytes>::new())?;
 
     let _res = client.request(req).await?;
@@ -562,10 +900,24 @@ async fn handle_send_message(
     let contacts = core.contacts_store_manager();
 
     let list = contacts.list().unwrap_or_default();
-    let contact = list
-        .into_iter()
-        .find(|c| c.peer_id == request.recipient || c.nickname.as_ref() == Some(&request.recipient))
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Contact not found".to_string()))?;
+    let contact = match list.into_iter().find(|c| c.peer_id == request.recipient) {
+        Some(contact) => contact,
+        None => {
+            let mut matches = contacts
+                .find_all_by_nickname(&request.recipient)
+                .unwrap_or_default();
+            match matches.len() {
+                0 => return Err((StatusCode::NOT_FOUND, "Contact not found".to_string())),
+                1 => matches.remove(0),
+                n => {
+                    return Err((
+                        StatusCode::CONFLICT,
+                        format!("ambiguous nickname, {} matches — specify pubkey", n),
+                    ))
+                }
+            }
+        }
+    };
 
     // contact.public_key (and contact.peer_id — they hold the same value) stores
     // the Ed25519 public key as hex, not a libp2p 12D3Koo... base-58 peer ID.
@@ -679,23 +1031,192 @@ async fn handle_add_contact(
 async fn handle_get_peers(
     State(ctx): State<Arc<ApiContext>>,
 ) -> Result<AxumJson<GetPeersResponse>, (StatusCode, String)> {
-    let peers: Vec<PeerEntry> = ctx
+    let mut peers: Vec<PeerEntry> = Vec::new();
+    for p in ctx.swarm_handle.get_peers().await.unwrap_or_default() {
+        let pid = p.to_string();
+        let reputation = ctx.core.get_peer_reputation(pid.clone());
+        let rtt_ms = ctx
+            .swarm_handle
+            .get_peer_rtt(p)
+            .await
+            .ok()
+            .flatten()
+            .map(|rtt| rtt.as_millis() as u64);
+        peers.push(PeerEntry {
+            peer_id: pid,
+            reputation,
+            rtt_ms,
+        });
+    }
+
+    Ok(AxumJson(GetPeersResponse { peers }))
+}
+
+/// Fan a peer-list pull out to every connected peer. Learned peers reach the
+/// ledger through the swarm's `LedgerReceived` events; the swarm rate-limits
+/// repeat pulls per peer.
+async fn handle_request_peers(
+    State(ctx): State<Arc<ApiContext>>,
+) -> Result<AxumJson<PeerExchangeResponse>, (StatusCode, String)> {
+    let connected = ctx
         .swarm_handle
         .get_peers()
         .await
-        .unwrap_or_default()
-        .into_iter()
-        .map(|p| {
-            let pid = p.to_string();
-            let reputation = ctx.core.get_peer_reputation(pid.clone());
-            PeerEntry {
-                peer_id: pid,
-                reputation,
+        .map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, e.to_string()))?;
+
+    let results = futures::future::join_all(connected.iter().map(|peer| {
+        let handle = ctx.swarm_handle.clone();
+        let peer = *peer;
+        async move { (peer, handle.request_peers(peer).await) }
+    }))
+    .await;
+
+    let mut response = PeerExchangeResponse {
+        queried: connected.len(),
+        responded: 0,
+        peers: Vec::new(),
+        errors: Vec::new(),
+    };
+    for (source, result) in results {
+        match result {
+            Ok(peers) => {
+                response.responded += 1;
+                response.peers.extend(peers.into_iter().map(|p| PulledPeer {
+                    peer_id: p.peer_id,
+                    addresses: p.addresses,
+                    source: source.to_string(),
+                }));
             }
+            Err(e) => response.errors.push((source.to_string(), e.to_string())),
+        }
+    }
+
+    Ok(AxumJson(response))
+}
+
+async fn handle_publish_topic(
+    State(ctx): State<Arc<ApiContext>>,
+    AxumJson(req): AxumJson<TopicPublishRequest>,
+) -> Result<AxumJson<TopicPublishResponse>, (StatusCode, String)> {
+    let peers = ctx
+        .swarm_handle
+        .publish_topic_counted(req.topic, req.data.into_bytes())
+        .await
+        .map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, e.to_string()))?;
+    Ok(AxumJson(TopicPublishResponse { peers }))
+}
+
+async fn handle_reconnect(
+    State(ctx): State<Arc<ApiContext>>,
+) -> Result<AxumJson<ReconnectResponse>, (StatusCode, String)> {
+    let bootstrap_dials = ctx
+        .swarm_handle
+        .reconnect_bootstrap()
+        .await
+        .map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, e.to_string()))?;
+
+    let connected: std::collections::HashSet<String> = ctx
+        .peers
+        .lock()
+        .await
+        .keys()
+        .map(|peer| peer.to_string())
+        .collect();
+    let local_peer_id = ctx.core.get_identity_info().libp2p_peer_id;
+    // `dialable_addresses` already leaves out peers still backing off.
+    let targets: Vec<String> = ctx
+        .ledger
+        .lock()
+        .await
+        .dialable_addresses(local_peer_id.as_deref())
+        .into_iter()
+        .filter(|(_, peer)| {
+            peer.as_ref().is_none_or(|peer| {
+                !connected.contains(peer)
+                    && !ctx
+                        .core
+                        .is_peer_blocked(peer.clone(), None)
+                        .unwrap_or(false)
+            })
         })
+        .map(|(multiaddr, _)| multiaddr)
         .collect();
 
-    Ok(AxumJson(GetPeersResponse { peers }))
+    let mut ledger_dials = 0;
+    for multiaddr in targets {
+        let Ok(addr) = crate::ledger::strip_peer_id(&multiaddr).parse::<libp2p::Multiaddr>() else {
+            continue;
+        };
+        ledger_dials += 1;
+        let handle = ctx.swarm_handle.clone();
+        let ledger = ctx.ledger.clone();
+        tokio::spawn(async move {
+            if handle.dial(addr).await.is_err() {
+                ledger.lock().await.record_failure(&multiaddr);
+            }
+        });
+    }
+
+    Ok(AxumJson(ReconnectResponse {
+        bootstrap_dials,
+        ledger_dials,
+    }))
+}
+
+async fn handle_get_peer_detail(
+    State(ctx): State<Arc<ApiContext>>,
+    Path(peer_id): Path<String>,
+) -> Result<AxumJson<PeerDetailResponse>, (StatusCode, String)> {
+    let peer: libp2p::PeerId = peer_id
+        .parse()
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid peer ID: {}", e)))?;
+    let unavailable = |e: anyhow::Error| (StatusCode::SERVICE_UNAVAILABLE, e.to_string());
+
+    let connected = ctx
+        .swarm_handle
+        .get_peers()
+        .await
+        .map_err(unavailable)?
+        .contains(&peer);
+    let rtt = ctx
+        .swarm_handle
+        .get_peer_rtt(peer)
+        .await
+        .map_err(unavailable)?;
+    let capabilities = ctx
+        .swarm_handle
+        .get_peer_capabilities(peer)
+        .await
+        .map_err(unavailable)?;
+    let public_key = ctx.swarm_handle.confirm_peer_pubkey(peer).await.ok();
+
+    Ok(AxumJson(PeerDetailResponse {
+        peer_id: peer.to_string(),
+        connected,
+        rtt_ms: rtt.map(|d| d.as_millis() as u64),
+        capabilities,
+        public_key,
+    }))
+}
+
+async fn handle_get_nat_status(
+    State(ctx): State<Arc<ApiContext>>,
+) -> Result<AxumJson<NatStatusResponse>, (StatusCode, String)> {
+    let report = ctx
+        .swarm_handle
+        .get_nat_status()
+        .await
+        .map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, e.to_string()))?;
+    Ok(AxumJson(NatStatusResponse {
+        nat_type: format!("{:?}", report.nat_type),
+        hole_punch_enabled: report.hole_punch_enabled,
+        upnp_enabled: report.upnp_enabled,
+        hole_punches: report
+            .hole_punches
+            .into_iter()
+            .map(|(peer, status)| (peer.to_string(), format!("{:?}", status)))
+            .collect(),
+    }))
 }
 
 async fn handle_get_swarm_stats(
@@ -754,30 +1275,53 @@ async fn handle_get_listeners(
     Ok(AxumJson(GetListenersResponse { listeners }))
 }
 
+async fn handle_get_relay_reservations(
+    State(ctx): State<Arc<ApiContext>>,
+) -> Result<AxumJson<RelayReservationsResponse>, (StatusCode, String)> {
+    let reservations = ctx
+        .swarm_handle
+        .get_relay_reservations()
+        .await
+        .map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, e.to_string()))?
+        .into_iter()
+        .map(|r| RelayReservationInfo {
+            relay_peer_id: r.relay_peer_id.to_string(),
+            relay_addr: r.relay_addr.map(|addr| addr.to_string()),
+            accepted: r.accepted,
+        })
+        .collect();
+
+    Ok(AxumJson(RelayReservationsResponse { reservations }))
+}
+
 async fn handle_get_history(
     State(ctx): State<Arc<ApiContext>>,
     AxumJson(request): AxumJson<GetHistoryRequest>,
 ) -> Result<AxumJson<GetHistoryResponse>, (StatusCode, String)> {
     let history = ctx.core.history_store_manager();
 
-    let messages = if let Some(peer_id) = request.peer_id {
+    let limit = request.limit.unwrap_or(20) as u32;
+    let (messages, next_cursor) = if let Some(peer_id) = request.peer_id {
         history
-            .conversation(peer_id, request.limit.unwrap_or(20) as u32)
-            .map_err(|e| {
-                (
+            .conversation_page(peer_id, request.cursor, limit)
+            .map_err(|e| match e {
+                scmessenger_core::IronCoreError::InvalidInput => (
+                    StatusCode::BAD_REQUEST,
+                    "Invalid history cursor".to_string(),
+                ),
+                e => (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     format!("Failed to get history: {:?}", e),
-                )
+                ),
             })?
     } else {
-        history
-            .recent(None, request.limit.unwrap_or(20) as u32)
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Failed to get history: {:?}", e),
-                )
-            })?
+        let messages = history.recent(None, limit).map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to get history: {:?}", e),
+            )
+        })?;
+        (messages, None)
     };
 
     let history_messages: Vec<HistoryMessage> = messages
@@ -795,6 +1339,7 @@ async fn handle_get_history(
 
     Ok(AxumJson(GetHistoryResponse {
         messages: history_messages,
+        next_cursor,
     }))
 }
 
@@ -843,17 +1388,19 @@ fn export_diagnostics(
 ) -> String {
     let history = core.history_store_manager();
     let stats = history.stats().ok();
-    let mut payload = Map::new();
+    // Start from the core snapshot so CLI, mobile and WASM reports share keys.
+    let mut payload =
+        match serde_json::from_str::<Value>(&core.export_diagnostics(Some(peers.to_vec()), None)) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
     payload.insert("running".to_string(), true.into());
     payload.insert(
         "connection_path_state".to_string(),
         connection_path_state.into(),
     );
-    payload.insert("peers".to_string(), peers.into());
     payload.insert("listeners".to_string(), listeners.into());
     payload.insert("external_addrs".to_string(), external_addrs.into());
-    payload.insert("inbox_count".to_string(), core.inbox_count().into());
-    payload.insert("outbox_count".to_string(), core.outbox_count().into());
     payload.insert(
         "custody_audit_count".to_string(),
         core.custody_audit_count().into(),
@@ -957,6 +1504,32 @@ async fn handle_export_diagnostics(
     Ok(diagnostics)
 }
 
+async fn handle_export_support_bundle(State(ctx): State<Arc<ApiContext>>) -> String {
+    ctx.core.export_support_bundle()
+}
+
+async fn handle_get_status(
+    State(ctx): State<Arc<ApiContext>>,
+) -> Result<AxumJson<StatusResponse>, (StatusCode, String)> {
+    let subscribed_topics = ctx
+        .swarm_handle
+        .get_topics()
+        .await
+        .map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, e.to_string()))?
+        .len();
+    let connected_peers = ctx.peers.lock().await.len();
+    let ledger_entries = ctx.ledger.lock().await.entries.len();
+    Ok(AxumJson(StatusResponse {
+        status: "running".to_string(),
+        uptime_secs: ctx.start_time.elapsed().as_secs(),
+        connected_peers,
+        subscribed_topics,
+        ledger_entries,
+        outbox_count: ctx.core.outbox_count(),
+        inbox_count: ctx.core.inbox_count(),
+    }))
+}
+
 async fn handle_get_drift_status(
     State(ctx): State<Arc<ApiContext>>,
 ) -> Result<AxumJson<DriftStatusResponse>, (StatusCode, String)> {
@@ -966,6 +1539,41 @@ async fn handle_get_drift_status(
     }))
 }
 
+async fn handle_get_relay_stats(
+    State(ctx): State<Arc<ApiContext>>,
+) -> AxumJson<scmessenger_core::relay::RelayServerStats> {
+    AxumJson(ctx.swarm_handle.relay_server().stats())
+}
+
+async fn handle_reset_relay_stats(
+    State(ctx): State<Arc<ApiContext>>,
+) -> AxumJson<scmessenger_core::relay::RelayServerStats> {
+    let relay = ctx.swarm_handle.relay_server();
+    relay.reset_stats();
+    AxumJson(relay.stats())
+}
+
+async fn handle_compact_storage(
+    State(ctx): State<Arc<ApiContext>>,
+) -> Result<AxumJson<CompactStorageResponse>, (StatusCode, String)> {
+    // Rewrites every record; keep it off the async workers.
+    let core = ctx.core.clone();
+    let report = tokio::task::spawn_blocking(move || core.compact_storage())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to compact storage: {:?}", e),
+            )
+        })?;
+    Ok(AxumJson(CompactStorageResponse {
+        bytes_before: report.bytes_before,
+        bytes_after: report.bytes_after,
+        records_rewritten: report.records_rewritten,
+    }))
+}
+
 async fn handle_get_discovery_status(
 ) -> Result<AxumJson<DiscoveryStatusResponse>, (StatusCode, String)> {
     let cfg = crate::config::Config::load().unwrap_or_default();
@@ -1183,24 +1791,43 @@ pub async fn start_api_server(ctx: ApiContext, bind_addr: Option<String>) -> Res
                 tracing::warn!(
                     "Invalid http-bind address '{}', falling back to localhost: {}: {}",
                     bind_str,
-                    API_PORT,
+                    api_port(),
                     e
                 );
-                SocketAddr::from(([127, 0, 0, 1], API_PORT))
+                SocketAddr::from(([127, 0, 0, 1], api_port()))
             }
         }
     } else {
-        SocketAddr::from(([127, 0, 0, 1], API_PORT))
+        SocketAddr::from(([127, 0, 0, 1], api_port()))
     };
 
+    let app = router(ctx);
+
+    // Create TCP listener
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .context("Failed to bind API server")?;
+
+    tracing::info!("Control API listening on {}", addr);
+
+    // Serve with axum
+    axum::serve(listener, app)
+        .await
+        .context("API server error")?;
+
+    Ok(())
+}
+
+/// The control API routes, bound to `ctx`.
+#[allow(clippy::disallowed_methods)] // serde_json::json! expands to unwrap() calls internally
+pub fn router(ctx: Arc<ApiContext>) -> Router {
     // Create CORS layer
     let cors = CorsLayer::new()
         .allow_origin(tower_http::cors::Any)
         .allow_methods([Method::GET, Method::POST])
         .allow_headers(tower_http::cors::Any);
 
-    // Build router with all routes
-    let app = Router::new()
+    Router::new()
         .route(
             "/health",
             get(|| async { axum::Json(serde_json::json!({"status": "healthy"})) }),
@@ -1212,7 +1839,13 @@ pub async fn start_api_server(ctx: ApiContext, bind_addr: Option<String>) -> Res
             get(handle_get_contacts).post(handle_add_contact),
         )
         .route("/api/peers", get(handle_get_peers))
+        .route("/api/peers/discover", post(handle_request_peers))
+        .route("/api/reconnect", post(handle_reconnect))
+        .route("/api/topics/publish", post(handle_publish_topic))
+        .route("/api/peers/:peer_id", get(handle_get_peer_detail))
+        .route("/api/status", get(handle_get_status))
         .route("/api/swarm/stats", get(handle_get_swarm_stats))
+        .route("/api/swarm/nat", get(handle_get_nat_status))
         .route("/api/listeners", get(handle_get_listeners))
         .route("/api/history", post(handle_get_history))
         .route("/api/external-address", get(handle_get_external_address))
@@ -1221,7 +1854,15 @@ pub async fn start_api_server(ctx: ApiContext, bind_addr: Option<String>) -> Res
             get(handle_get_connection_path_state),
         )
         .route("/api/diagnostics", get(handle_export_diagnostics))
+        .route("/api/support-bundle", get(handle_export_support_bundle))
         .route("/api/drift-status", get(handle_get_drift_status))
+        .route("/api/relay/stats", get(handle_get_relay_stats))
+        .route(
+            "/api/relay/reservations",
+            get(handle_get_relay_reservations),
+        )
+        .route("/api/relay/stats/reset", post(handle_reset_relay_stats))
+        .route("/api/maintenance/compact", post(handle_compact_storage))
         .route("/api/discovery/status", get(handle_get_discovery_status))
         .route("/api/discovery/scan", post(handle_trigger_discovery_scan))
         .route("/api/discovery/peers", get(handle_get_discovery_peers))
@@ -1231,19 +1872,5 @@ pub async fn start_api_server(ctx: ApiContext, bind_addr: Option<String>) -> Res
         .route("/poll-status/:run_id", get(handle_poll_status))
         .route("/fetch-artifact/:run_id/:name", get(handle_fetch_artifact))
         .layer(cors)
-        .with_state(ctx);
-
-    // Create TCP listener
-    let listener = tokio::net::TcpListener::bind(addr)
-        .await
-        .context("Failed to bind API server")?;
-
-    tracing::info!("Control API listening on {}", addr);
-
-    // Serve with axum
-    axum::serve(listener, app)
-        .await
-        .context("API server error")?;
-
-    Ok(())
+        .with_state(ctx)
 }
@@ -18,10 +18,10 @@ use tokio::sync::RwLock;
 use tower_http::cors::{Any, CorsLayer};
 
 use super::api::{
-    AddContactRequest, AddContactResponse, ConnectionPathStateResponse, DiscoveredPeer,
+    api_port, AddContactRequest, AddContactResponse, ConnectionPathStateResponse, DiscoveredPeer,
     DiscoveryPeersResponse, DiscoveryStatusResponse, DriftStatusResponse,
     GetExternalAddressResponse, GetHistoryRequest, GetHistoryResponse, GetListenersResponse,
-    GetPeersResponse, HistoryMessage, PeerEntry, SendMessageRequest, SendMessageResponse, API_PORT,
+    GetPeersResponse, HistoryMessage, PeerEntry, SendMessageRequest, SendMessageResponse,
 };
 
 // Farm Test Harness Types
@@ -377,24 +377,28 @@ async fn handle_get_history(
 ) -> Result<AxumJson<GetHistoryResponse>, (StatusCode, String)> {
     let history = ctx.core.history_store_manager();
 
-    let messages = if let Some(peer_id) = request.peer_id {
+    let limit = request.limit.unwrap_or(20) as u32;
+    let (messages, next_cursor) = if let Some(peer_id) = request.peer_id {
         history
-            .conversation(peer_id, request.limit.unwrap_or(20) as u32)
-            .map_err(|e| {
-                (
+            .conversation_page(peer_id, request.cursor, limit)
+            .map_err(|e| match e {
+                scmessenger_core::IronCoreError::InvalidInput => (
+                    StatusCode::BAD_REQUEST,
+                    "Invalid history cursor".to_string(),
+                ),
+                e => (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     format!("Failed to get history: {:?}", e),
-                )
+                ),
             })?
     } else {
-        history
-            .recent(None, request.limit.unwrap_or(20) as u32)
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("Failed to get history: {:?}", e),
-                )
-            })?
+        let messages = history.recent(None, limit).map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to get history: {:?}", e),
+            )
+        })?;
+        (messages, None)
     };
 
     let history_messages: Vec<HistoryMessage> = messages
@@ -412,6 +416,7 @@ async fn handle_get_history(
 
     Ok(AxumJson(GetHistoryResponse {
         messages: history_messages,
+        next_cursor,
     }))
 }
 
@@ -617,7 +622,7 @@ fn export_diagnostics(
 
 pub async fn start_api_server(ctx: ApiContext) -> Result<()> {
     let ctx = Arc::new(ctx);
-    let addr = SocketAddr::from(([127, 0, 0, 1], API_PORT));
+    let addr = SocketAddr::from(([127, 0, 0, 1], api_port()));
 
     // Create CORS layer
     let cors = CorsLayer::new()
@@ -0,0 +1,143 @@
+// Local crypto throughput benchmark (`scm bench`)
+//
+// Runs the real message path between two in-memory identities: encrypt is
+// `prepare_message`, decrypt is `receive_message` (including dedup and inbox
+// bookkeeping), plus raw identity-key sign/verify. Timing uses `Instant`
+// only, so the shipped binary needs no benchmarking crates.
+
+use anyhow::{bail, Context, Result};
+use scmessenger_core::message::codec::MAX_PAYLOAD_SIZE;
+use scmessenger_core::{IronCore, MessageType};
+use std::time::{Duration, Instant};
+
+/// Timings for one operation across all iterations.
+pub struct BenchResult {
+    pub name: &'static str,
+    /// Bytes processed per operation (plaintext size)
+    pub bytes_per_op: usize,
+    samples: Vec<Duration>,
+}
+
+impl BenchResult {
+    fn new(name: &'static str, bytes_per_op: usize, capacity: usize) -> Self {
+        Self {
+            name,
+            bytes_per_op,
+            samples: Vec::with_capacity(capacity),
+        }
+    }
+
+    fn total(&self) -> Duration {
+        self.samples.iter().sum()
+    }
+
+    pub fn ops_per_sec(&self) -> f64 {
+        let secs = self.total().as_secs_f64();
+        if secs == 0.0 {
+            return 0.0;
+        }
+        self.samples.len() as f64 / secs
+    }
+
+    pub fn mb_per_sec(&self) -> f64 {
+        self.ops_per_sec() * self.bytes_per_op as f64 / (1024.0 * 1024.0)
+    }
+
+    /// Latency at percentile `p` (0-100), nearest-rank.
+    pub fn percentile(&self, p: f64) -> Duration {
+        let mut sorted = self.samples.clone();
+        sorted.sort_unstable();
+        percentile(&sorted, p)
+    }
+}
+
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn new_identity() -> Result<IronCore> {
+    let core = IronCore::new();
+    core.grant_consent();
+    core.initialize_identity()
+        .context("Failed to create benchmark identity")?;
+    Ok(core)
+}
+
+/// Run `messages` iterations of each operation on `size`-byte payloads.
+pub fn run_bench(messages: u32, size: usize) -> Result<Vec<BenchResult>> {
+    if messages == 0 {
+        bail!("--messages must be at least 1");
+    }
+    if size == 0 || size > MAX_PAYLOAD_SIZE {
+        bail!("--size must be between 1 and {} bytes", MAX_PAYLOAD_SIZE);
+    }
+
+    let alice = new_identity()?;
+    let bob = new_identity()?;
+    let bob_pk = bob
+        .get_identity_info()
+        .public_key_hex
+        .context("Benchmark identity has no public key")?;
+
+    let text = "x".repeat(size);
+    let n = messages as usize;
+    let mut encrypt = BenchResult::new("encrypt", size, n);
+    let mut decrypt = BenchResult::new("decrypt", size, n);
+    let mut sign = BenchResult::new("sign", size, n);
+    let mut verify = BenchResult::new("verify", size, n);
+
+    for _ in 0..messages {
+        let start = Instant::now();
+        let prepared =
+            alice.prepare_message(bob_pk.clone(), text.clone(), MessageType::Text, None)?;
+        encrypt.samples.push(start.elapsed());
+
+        let start = Instant::now();
+        bob.receive_message(prepared.envelope_data)?;
+        decrypt.samples.push(start.elapsed());
+    }
+
+    let data = text.into_bytes();
+    for _ in 0..messages {
+        let start = Instant::now();
+        let signed = alice.sign_data(data.clone())?;
+        sign.samples.push(start.elapsed());
+
+        let start = Instant::now();
+        let valid = bob.verify_signature(data.clone(), signed.signature, signed.public_key_hex)?;
+        verify.samples.push(start.elapsed());
+        if !valid {
+            bail!("Signature verification failed during benchmark");
+        }
+    }
+
+    Ok(vec![encrypt, decrypt, sign, verify])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let sorted: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&sorted, 50.0), Duration::from_millis(50));
+        assert_eq!(percentile(&sorted, 99.0), Duration::from_millis(99));
+        assert_eq!(percentile(&sorted, 0.0), Duration::from_millis(1));
+        assert_eq!(percentile(&[], 50.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_run_bench_covers_every_operation() {
+        let results = run_bench(3, 64).unwrap();
+        let names: Vec<_> = results.iter().map(|r| r.name).collect();
+        assert_eq!(names, ["encrypt", "decrypt", "sign", "verify"]);
+        assert!(results.iter().all(|r| r.samples.len() == 3));
+        assert!(run_bench(0, 64).is_err());
+        assert!(run_bench(1, MAX_PAYLOAD_SIZE + 1).is_err());
+    }
+}
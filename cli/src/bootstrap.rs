@@ -14,6 +14,8 @@
 // - Example: export SC_BOOTSTRAP_NODES="/ip4/1.2.3.4/tcp/9001/p2p/12D3Koo..."
 
 use crate::ledger;
+use anyhow::{Context, Result};
+use libp2p::multiaddr::{Multiaddr, Protocol};
 
 /// Default bootstrap nodes — can be overridden at build time
 ///
@@ -100,25 +102,111 @@ pub fn parse_bootstrap_addr(multiaddr: &str) -> (String, Option<String>) {
     (stripped, peer_id)
 }
 
+/// Normalized `host:port/transport` for a bootstrap multiaddr, so the same
+/// relay is recognised whatever PeerID (if any) is attached or how the
+/// address is spelled, while its TCP and QUIC (UDP) listeners on one port
+/// stay distinct. Fails if the multiaddr has no IP/DNS host or no TCP/UDP
+/// port.
+pub fn bootstrap_dial_key(addr: &Multiaddr) -> Result<String> {
+    let mut host = None;
+    let mut port = None;
+    for protocol in addr.iter() {
+        match protocol {
+            Protocol::Ip4(ip) if host.is_none() => host = Some(ip.to_string()),
+            Protocol::Ip6(ip) if host.is_none() => host = Some(format!("[{}]", ip)),
+            Protocol::Dns(name) | Protocol::Dns4(name) | Protocol::Dns6(name) if host.is_none() => {
+                host = Some(name.to_lowercase())
+            }
+            Protocol::Tcp(p) if port.is_none() => port = Some((p, "tcp")),
+            Protocol::Udp(p) if port.is_none() => port = Some((p, "udp")),
+            _ => {}
+        }
+    }
+    match (host, port) {
+        (Some(host), Some((port, transport))) => Ok(format!("{}:{}/{}", host, port, transport)),
+        _ => anyhow::bail!("{} has no IP/DNS host and TCP/UDP port to dial", addr),
+    }
+}
+
+/// Dedup key for an already-stored node. Only the address part is parsed,
+/// so entries saved before validation existed still dedup by IP:Port.
+fn stored_node_key(node: &str) -> Option<String> {
+    ledger::strip_peer_id(node)
+        .parse::<Multiaddr>()
+        .ok()
+        .and_then(|addr| bootstrap_dial_key(&addr).ok())
+}
+
+/// The address part of a stored node, without its PeerID.
+fn stored_node_addr(node: &str) -> Option<Multiaddr> {
+    ledger::strip_peer_id(node.trim()).parse().ok()
+}
+
+/// Remove every node in `nodes` at the same address as `multiaddr`,
+/// whatever PeerID either carries, and return how many were removed.
+/// Addresses are compared parsed, so spelling differences don't matter.
+pub fn remove_bootstrap_nodes(nodes: &mut Vec<String>, multiaddr: &str) -> Result<usize> {
+    let target = stored_node_addr(multiaddr)
+        .with_context(|| format!("Invalid bootstrap multiaddr '{}'", multiaddr.trim()))?;
+    let before = nodes.len();
+    nodes.retain(|n| stored_node_addr(n).as_ref() != Some(&target));
+    Ok(before - nodes.len())
+}
+
+/// How many nodes `add_bootstrap_nodes` added and skipped as duplicates.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BootstrapAddSummary {
+    pub added: usize,
+    pub skipped: usize,
+}
+
+/// Validate `candidates` and append the ones whose dial key (see
+/// `bootstrap_dial_key`) is not already in `nodes`. An unparseable multiaddr rejects the whole batch, so a typo
+/// never leaves the list half-updated.
+pub fn add_bootstrap_nodes(
+    nodes: &mut Vec<String>,
+    candidates: &[String],
+) -> Result<BootstrapAddSummary> {
+    let mut parsed = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        let candidate = candidate.trim();
+        let addr: Multiaddr = candidate
+            .parse()
+            .with_context(|| format!("Invalid bootstrap multiaddr '{}'", candidate))?;
+        let key = bootstrap_dial_key(&addr)
+            .with_context(|| format!("Invalid bootstrap multiaddr '{}'", candidate))?;
+        parsed.push((addr.to_string(), key));
+    }
+
+    let mut seen: std::collections::HashSet<String> =
+        nodes.iter().filter_map(|n| stored_node_key(n)).collect();
+    let mut summary = BootstrapAddSummary::default();
+    for (addr, key) in parsed {
+        if seen.insert(key) {
+            nodes.push(addr);
+            summary.added += 1;
+        } else {
+            summary.skipped += 1;
+        }
+    }
+    Ok(summary)
+}
+
 /// Merge user-provided bootstrap nodes with defaults.
 /// Ensures defaults are preserved unless explicitly removed.
-/// Deduplicates by IP:Port (ignoring PeerID differences).
+/// Deduplicates by normalized IP:Port (ignoring PeerID differences) and
+/// drops entries that cannot be dialed at all.
 pub fn merge_bootstrap_nodes(user_nodes: Vec<String>) -> Vec<String> {
     let mut seen_addrs = std::collections::HashSet::new();
     let mut merged = Vec::new();
 
-    // Add defaults first
-    for node in default_bootstrap_nodes() {
-        let stripped = ledger::strip_peer_id(&node);
-        if seen_addrs.insert(stripped) {
-            merged.push(node);
-        }
-    }
-
-    // Add user nodes that don't duplicate an existing address
-    for node in user_nodes {
-        let stripped = ledger::strip_peer_id(&node);
-        if seen_addrs.insert(stripped) {
+    // Defaults first, then user nodes that don't duplicate an existing address
+    for node in default_bootstrap_nodes().into_iter().chain(user_nodes) {
+        let Some(key) = stored_node_key(&node) else {
+            tracing::warn!("Ignoring invalid bootstrap node '{}'", node);
+            continue;
+        };
+        if seen_addrs.insert(key) {
             merged.push(node);
         }
     }
@@ -199,6 +287,72 @@ mod tests {
         assert!(merged.iter().any(|n| n.contains("10.0.0.1")));
     }
 
+    #[test]
+    fn test_add_bootstrap_nodes_validates_and_dedups() {
+        let mut nodes = vec![
+            "/ip4/1.2.3.4/tcp/9001/p2p/12D3KooWGGdvGNJb3JwkNpmYuapgk7SAZ4DsBmQsU989yhvnTB8W"
+                .to_string(),
+        ];
+
+        // Unparseable: rejected with the offending address named, nothing added.
+        let err = add_bootstrap_nodes(
+            &mut nodes,
+            &[
+                "/ip4/10.0.0.9/tcp/9001".into(),
+                "/ip4/not-an-ip/tcp/1".into(),
+            ],
+        )
+        .unwrap_err();
+        assert!(format!("{:#}", err).contains("/ip4/not-an-ip/tcp/1"));
+        assert!(add_bootstrap_nodes(&mut nodes, &["/ip4/10.0.0.9".into()]).is_err());
+        assert_eq!(nodes.len(), 1);
+
+        // Same IP:Port without (or with another) PeerID is skipped; a new one is added.
+        let summary = add_bootstrap_nodes(
+            &mut nodes,
+            &[
+                "/ip4/1.2.3.4/tcp/9001".into(),
+                "/ip4/10.0.0.1/tcp/9001".into(),
+                "/ip4/10.0.0.1/tcp/9001".into(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            summary,
+            BootstrapAddSummary {
+                added: 1,
+                skipped: 2
+            }
+        );
+        assert_eq!(nodes[1], "/ip4/10.0.0.1/tcp/9001");
+
+        // QUIC on the same port is a different listener, not a duplicate.
+        let summary =
+            add_bootstrap_nodes(&mut nodes, &["/ip4/10.0.0.1/udp/9001/quic-v1".into()]).unwrap();
+        assert_eq!(summary.added, 1);
+    }
+
+    #[test]
+    fn test_remove_bootstrap_nodes_matches_parsed_address() {
+        let mut nodes = vec![
+            "/ip6/0:0:0:0:0:0:0:1/tcp/9001/p2p/12D3KooWGGdvGNJb3JwkNpmYuapgk7SAZ4DsBmQsU989yhvnTB8W"
+                .to_string(),
+            "/ip6/::1/udp/9001/quic-v1".to_string(),
+        ];
+
+        assert!(remove_bootstrap_nodes(&mut nodes, "/ip6/not-an-ip/tcp/1").is_err());
+        assert_eq!(
+            remove_bootstrap_nodes(&mut nodes, "/ip6/::1/tcp/9002").unwrap(),
+            0
+        );
+        // Spelled differently and without the PeerID, still the same node.
+        assert_eq!(
+            remove_bootstrap_nodes(&mut nodes, "/ip6/::1/tcp/9001").unwrap(),
+            1
+        );
+        assert_eq!(nodes, vec!["/ip6/::1/udp/9001/quic-v1".to_string()]);
+    }
+
     #[test]
     fn test_default_topics() {
         let topics = default_topics();
@@ -8,6 +8,15 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Environment variable overriding the data directory.
+pub const DATA_DIR_ENV: &str = "SCM_DATA_DIR";
+/// Older name for `DATA_DIR_ENV`, still honored.
+const LEGACY_DATA_DIR_ENV: &str = "SCMESSENGER_DATA_DIR";
+
+/// Data directory given with `--data-dir`; wins over the environment.
+static DATA_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
@@ -63,6 +72,37 @@ pub struct NetworkConfig {
     /// Enable relay fallback
     #[serde(default)]
     pub enable_relay: bool,
+
+    /// Hold messages for offline peers while relaying (in memory, bounded)
+    #[serde(default)]
+    pub relay_mailbox: bool,
+
+    /// Refuse to relay envelopes that arrive without their sender's signature
+    pub require_signed_relay: bool,
+
+    /// Sustained inbound messages per second accepted from one sender (0 = no limit)
+    pub inbound_messages_per_sec: u32,
+
+    /// Messages a sender may send back-to-back before the rate applies
+    pub inbound_burst: u32,
+
+    /// Most peers kept in the connection ledger (0 = unbounded)
+    pub ledger_max_entries: usize,
+
+    /// Drop ledger peers not seen for this many seconds (0 = never)
+    pub ledger_max_age_secs: u64,
+
+    /// Mesh discovery mode; `paranoid` never dials peers learned from other
+    /// peers and skips ledger exchange
+    pub discovery_mode: scmessenger_core::DiscoveryMode,
+
+    /// Correct the local clock from signed timestamps served by the
+    /// bootstrap nodes
+    pub verify_clock: bool,
+
+    /// Which addresses are advertised and accepted into the DHT; unset
+    /// means LAN and WAN for a node and WAN only for a relay
+    pub advertise_scope: Option<scmessenger_core::AdvertiseScope>,
 }
 
 impl Default for Config {
@@ -87,13 +127,30 @@ impl Default for NetworkConfig {
             connection_timeout: 30,
             enable_nat_traversal: true,
             enable_relay: true,
+            relay_mailbox: false,
+            require_signed_relay: false,
+            inbound_messages_per_sec:
+                scmessenger_core::transport::rate_limit::DEFAULT_INBOUND_MESSAGES_PER_SEC,
+            inbound_burst: scmessenger_core::transport::rate_limit::DEFAULT_INBOUND_BURST,
+            ledger_max_entries: 1000,
+            ledger_max_age_secs: 30 * 24 * 60 * 60,
+            discovery_mode: scmessenger_core::DiscoveryMode::Normal,
+            verify_clock: false,
+            advertise_scope: None,
         }
     }
 }
 
 impl Config {
-    /// Get the config directory path (cross-platform)
+    /// Get the config directory path (cross-platform).
+    /// With a data directory override (see `data_dir`) the config lives in
+    /// that directory too, so isolated instances don't share a config.
     pub fn config_dir() -> Result<PathBuf> {
+        if let Some(path) = Self::data_dir_override() {
+            std::fs::create_dir_all(&path).context("Failed to create config directory")?;
+            return Ok(path);
+        }
+
         let config_dir = dirs::config_dir()
             .context("Failed to determine config directory")?
             .join("scmessenger");
@@ -104,12 +161,39 @@ impl Config {
         Ok(config_dir)
     }
 
+    /// Use `path` as the data directory for the rest of the process
+    /// (`--data-dir`). Only the first call takes effect.
+    pub fn set_data_dir_override(path: PathBuf) {
+        let _ = DATA_DIR_OVERRIDE.set(path);
+    }
+
+    /// Pick the data directory from, in order: the `--data-dir` flag,
+    /// `SCM_DATA_DIR`, and the legacy `SCMESSENGER_DATA_DIR`.
+    fn resolve_data_dir_override(
+        flag: Option<&PathBuf>,
+        env: Option<String>,
+        legacy_env: Option<String>,
+    ) -> Option<PathBuf> {
+        flag.cloned()
+            .or_else(|| env.filter(|v| !v.is_empty()).map(PathBuf::from))
+            .or_else(|| legacy_env.filter(|v| !v.is_empty()).map(PathBuf::from))
+    }
+
+    fn data_dir_override() -> Option<PathBuf> {
+        Self::resolve_data_dir_override(
+            DATA_DIR_OVERRIDE.get(),
+            std::env::var(DATA_DIR_ENV).ok(),
+            std::env::var(LEGACY_DATA_DIR_ENV).ok(),
+        )
+    }
+
     /// Get the data directory path (cross-platform).
-    /// Honors SCMESSENGER_DATA_DIR env var (absolute path) for running isolated
-    /// instances side by side, e.g. local multi-node discovery testing.
+    /// Honors `--data-dir` and the SCM_DATA_DIR env var for running isolated
+    /// instances side by side, e.g. local multi-node discovery testing or a
+    /// second identity. Storage, contacts, history, outbox and the ledger all
+    /// live under this directory.
     pub fn data_dir() -> Result<PathBuf> {
-        if let Ok(env_path) = std::env::var("SCMESSENGER_DATA_DIR") {
-            let path = PathBuf::from(env_path);
+        if let Some(path) = Self::data_dir_override() {
             std::fs::create_dir_all(&path).context("Failed to create data directory")?;
             return Ok(path);
         }
@@ -204,9 +288,53 @@ impl Config {
             "enable_relay" => {
                 self.network.enable_relay = value.parse().context("Invalid boolean value")?;
             }
+            "relay_mailbox" => {
+                self.network.relay_mailbox = value.parse().context("Invalid boolean value")?;
+            }
+            "require_signed_relay" => {
+                self.network.require_signed_relay =
+                    value.parse().context("Invalid boolean value")?;
+            }
+            "inbound_messages_per_sec" => {
+                self.network.inbound_messages_per_sec = value.parse().context("Invalid number")?;
+            }
+            "inbound_burst" => {
+                self.network.inbound_burst = value.parse().context("Invalid number")?;
+            }
+            "ledger_max_entries" => {
+                self.network.ledger_max_entries = value.parse().context("Invalid number")?;
+            }
+            "ledger_max_age_secs" => {
+                self.network.ledger_max_age_secs = value.parse().context("Invalid number")?;
+            }
+            "discovery_mode" => {
+                self.network.discovery_mode = match value.to_ascii_lowercase().as_str() {
+                    "normal" => scmessenger_core::DiscoveryMode::Normal,
+                    "cautious" => scmessenger_core::DiscoveryMode::Cautious,
+                    "paranoid" => scmessenger_core::DiscoveryMode::Paranoid,
+                    _ => anyhow::bail!("Invalid discovery mode (normal, cautious or paranoid)"),
+                };
+            }
+            "verify_clock" => {
+                self.network.verify_clock = value.parse().context("Invalid boolean value")?;
+            }
+            "advertise_scope" => {
+                self.network.advertise_scope = match value.to_ascii_lowercase().as_str() {
+                    "" | "auto" => None,
+                    "lan_and_wan" => Some(scmessenger_core::AdvertiseScope::LanAndWan),
+                    "wan_only" => Some(scmessenger_core::AdvertiseScope::WanOnly),
+                    "lan_only" => Some(scmessenger_core::AdvertiseScope::LanOnly),
+                    _ => anyhow::bail!(
+                        "Invalid advertise scope (auto, lan_and_wan, wan_only or lan_only)"
+                    ),
+                };
+            }
             "bootstrap_node_add" => {
                 if !value.is_empty() {
-                    self.bootstrap_nodes.push(value.to_string());
+                    crate::bootstrap::add_bootstrap_nodes(
+                        &mut self.bootstrap_nodes,
+                        &[value.to_string()],
+                    )?;
                 }
             }
             "bootstrap_node_remove" => {
@@ -216,10 +344,36 @@ impl Config {
             }
             _ => anyhow::bail!("Unknown config key: {}", key),
         }
+        if let Err(errors) = self.mesh_settings().validate() {
+            let list: Vec<String> = errors.iter().map(|e| format!("  - {}", e)).collect();
+            anyhow::bail!(
+                "Refusing to save contradictory settings:\n{}",
+                list.join("\n")
+            );
+        }
         self.save()?;
         Ok(())
     }
 
+    /// Core mesh settings implied by this config. The CLI always has an
+    /// Internet transport; BLE / WiFi Aware / relay follow the config flags.
+    pub fn mesh_settings(&self) -> scmessenger_core::MeshSettings {
+        scmessenger_core::MeshSettings {
+            relay_enabled: self.network.enable_relay,
+            relay_mailbox_enabled: self.network.relay_mailbox,
+            require_signed_relay: self.network.require_signed_relay,
+            inbound_messages_per_sec: self.network.inbound_messages_per_sec,
+            inbound_burst: self.network.inbound_burst,
+            ble_enabled: self.enable_ble,
+            wifi_aware_enabled: self.enable_wifi_aware,
+            internet_enabled: true,
+            discovery_mode: self.network.discovery_mode,
+            clock_sync_enabled: self.network.verify_clock,
+            advertise_scope: self.network.advertise_scope.unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
     /// Get a config value
     pub fn get(&self, key: &str) -> Option<String> {
         match key {
@@ -233,11 +387,34 @@ impl Config {
             "connection_timeout" => Some(self.network.connection_timeout.to_string()),
             "enable_nat_traversal" => Some(self.network.enable_nat_traversal.to_string()),
             "enable_relay" => Some(self.network.enable_relay.to_string()),
+            "relay_mailbox" => Some(self.network.relay_mailbox.to_string()),
+            "require_signed_relay" => Some(self.network.require_signed_relay.to_string()),
+            "inbound_messages_per_sec" => Some(self.network.inbound_messages_per_sec.to_string()),
+            "inbound_burst" => Some(self.network.inbound_burst.to_string()),
+            "ledger_max_entries" => Some(self.network.ledger_max_entries.to_string()),
+            "ledger_max_age_secs" => Some(self.network.ledger_max_age_secs.to_string()),
+            "discovery_mode" => Some(self.discovery_mode_name()),
+            "verify_clock" => Some(self.network.verify_clock.to_string()),
+            "advertise_scope" => Some(self.advertise_scope_name()),
             "bootstrap_nodes" => Some(self.bootstrap_nodes.join(",")),
             _ => None,
         }
     }
 
+    fn discovery_mode_name(&self) -> String {
+        format!("{:?}", self.network.discovery_mode).to_lowercase()
+    }
+
+    fn advertise_scope_name(&self) -> String {
+        match self.network.advertise_scope {
+            None => "auto",
+            Some(scmessenger_core::AdvertiseScope::LanAndWan) => "lan_and_wan",
+            Some(scmessenger_core::AdvertiseScope::WanOnly) => "wan_only",
+            Some(scmessenger_core::AdvertiseScope::LanOnly) => "lan_only",
+        }
+        .to_string()
+    }
+
     /// List all config values
     pub fn list(&self) -> Vec<(String, String)> {
         vec![
@@ -268,6 +445,36 @@ impl Config {
                 "enable_relay".to_string(),
                 self.network.enable_relay.to_string(),
             ),
+            (
+                "relay_mailbox".to_string(),
+                self.network.relay_mailbox.to_string(),
+            ),
+            (
+                "require_signed_relay".to_string(),
+                self.network.require_signed_relay.to_string(),
+            ),
+            (
+                "inbound_messages_per_sec".to_string(),
+                self.network.inbound_messages_per_sec.to_string(),
+            ),
+            (
+                "inbound_burst".to_string(),
+                self.network.inbound_burst.to_string(),
+            ),
+            (
+                "ledger_max_entries".to_string(),
+                self.network.ledger_max_entries.to_string(),
+            ),
+            (
+                "ledger_max_age_secs".to_string(),
+                format!("{}s", self.network.ledger_max_age_secs),
+            ),
+            ("discovery_mode".to_string(), self.discovery_mode_name()),
+            (
+                "verify_clock".to_string(),
+                self.network.verify_clock.to_string(),
+            ),
+            ("advertise_scope".to_string(), self.advertise_scope_name()),
             (
                 "bootstrap_nodes".to_string(),
                 self.bootstrap_nodes.join(","),
@@ -275,44 +482,32 @@ impl Config {
         ]
     }
 
-    /// Helper to strip /p2p/PeerID suffix from a multiaddr string
-    fn strip_peer_id(multiaddr: &str) -> String {
-        if let Some(idx) = multiaddr.find("/p2p/") {
-            multiaddr[..idx].to_string()
-        } else {
-            multiaddr.to_string()
-        }
-    }
-
     /// Add a bootstrap node to the config
     pub fn add_bootstrap_node(&mut self, multiaddr: String) -> Result<()> {
-        // Check for duplicates by IP:Port only (strip PeerID)
-        let stripped = Self::strip_peer_id(&multiaddr);
-        if self
-            .bootstrap_nodes
-            .iter()
-            .any(|n| Self::strip_peer_id(n) == stripped)
-        {
+        if self.add_bootstrap_nodes(&[multiaddr])?.added == 0 {
             anyhow::bail!("Bootstrap node already exists");
         }
-        self.bootstrap_nodes.push(multiaddr);
-        self.save()?;
         Ok(())
     }
 
+    /// Validate and add bootstrap nodes, skipping any whose IP:Port is
+    /// already configured. Saves only if something was added.
+    pub fn add_bootstrap_nodes(
+        &mut self,
+        multiaddrs: &[String],
+    ) -> Result<crate::bootstrap::BootstrapAddSummary> {
+        let summary = crate::bootstrap::add_bootstrap_nodes(&mut self.bootstrap_nodes, multiaddrs)?;
+        if summary.added > 0 {
+            self.save()?;
+        }
+        Ok(summary)
+    }
+
     /// Remove a bootstrap node from the config
     pub fn remove_bootstrap_node(&mut self, multiaddr: &str) -> Result<()> {
-        let stripped = Self::strip_peer_id(multiaddr);
-        let removed_count = self
-            .bootstrap_nodes
-            .iter()
-            .filter(|n| Self::strip_peer_id(n) == stripped)
-            .count();
-        if removed_count == 0 {
+        if crate::bootstrap::remove_bootstrap_nodes(&mut self.bootstrap_nodes, multiaddr)? == 0 {
             anyhow::bail!("Bootstrap node not found");
         }
-        self.bootstrap_nodes
-            .retain(|n| Self::strip_peer_id(n) != stripped);
         self.save()?;
         Ok(())
     }
@@ -322,6 +517,13 @@ impl Config {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_default_config_maps_to_valid_mesh_settings() {
+        let settings = Config::default().mesh_settings();
+        assert!(settings.internet_enabled);
+        assert_eq!(settings.validate(), Ok(()));
+    }
+
     #[test]
     fn test_default_config() {
         let config = Config::default();
@@ -330,6 +532,36 @@ mod tests {
         assert!(config.enable_dht);
     }
 
+    #[test]
+    fn test_data_dir_override_precedence() {
+        let flag = PathBuf::from("/tmp/flag");
+        assert_eq!(
+            Config::resolve_data_dir_override(
+                Some(&flag),
+                Some("/tmp/env".into()),
+                Some("/tmp/legacy".into())
+            ),
+            Some(flag)
+        );
+        assert_eq!(
+            Config::resolve_data_dir_override(
+                None,
+                Some("/tmp/env".into()),
+                Some("/tmp/legacy".into())
+            ),
+            Some(PathBuf::from("/tmp/env"))
+        );
+        assert_eq!(
+            Config::resolve_data_dir_override(
+                None,
+                Some(String::new()),
+                Some("/tmp/legacy".into())
+            ),
+            Some(PathBuf::from("/tmp/legacy"))
+        );
+        assert_eq!(Config::resolve_data_dir_override(None, None, None), None);
+    }
+
     #[test]
     fn test_config_serialization() {
         let config = Config::default();
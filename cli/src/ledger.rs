@@ -6,7 +6,8 @@
 // On startup, we load the ledger and attempt to reconnect to all known peers.
 // If a peer presents a different PeerID (e.g., after restart), we accept it,
 // update the ledger, and carry on. Unreachable peers enter exponential backoff
-// but are never deleted — they may come back.
+// and stay around so they may come back, until `prune` ages them out or they
+// fall below the size cap. Bootstrap nodes are never pruned.
 
 use anyhow::{Context, Result};
 use libp2p::PeerId;
@@ -299,6 +300,64 @@ impl ConnectionLedger {
         Ok(())
     }
 
+    /// Bound the ledger: drop entries not seen for `max_age_secs`, then the
+    /// lowest-ranked entries beyond `max_entries`. Bootstrap nodes and
+    /// verified peers whose last attempt succeeded are never dropped. A limit
+    /// of 0 disables that check. Returns the number of entries removed.
+    pub fn prune(&mut self, max_entries: usize, max_age_secs: u64) -> usize {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.prune_at(max_entries, max_age_secs, now)
+    }
+
+    fn prune_at(&mut self, max_entries: usize, max_age_secs: u64, now: u64) -> usize {
+        let before = self.entries.len();
+
+        if max_age_secs > 0 {
+            self.entries
+                .retain(|_, e| e.is_bootstrap || now.saturating_sub(e.last_seen) <= max_age_secs);
+        }
+
+        if max_entries > 0 && self.entries.len() > max_entries {
+            let mut candidates: Vec<(&String, &LedgerEntry)> = self
+                .entries
+                .iter()
+                .filter(|(_, e)| {
+                    !(e.is_bootstrap || (e.locally_verified && e.consecutive_failures == 0))
+                })
+                .collect();
+            // Worst first: unverified, then most failures, then stalest.
+            candidates.sort_by_key(|(_, e)| {
+                (
+                    e.locally_verified,
+                    std::cmp::Reverse(e.consecutive_failures),
+                    e.last_seen,
+                )
+            });
+            let excess = self.entries.len() - max_entries;
+            let evicted: Vec<String> = candidates
+                .into_iter()
+                .take(excess)
+                .map(|(key, _)| key.clone())
+                .collect();
+            for key in evicted {
+                self.entries.remove(&key);
+            }
+        }
+
+        let removed = before - self.entries.len();
+        if removed > 0 {
+            tracing::info!(
+                "[INFO] Pruned {} ledger entries ({} remain)",
+                removed,
+                self.entries.len()
+            );
+        }
+        removed
+    }
+
     /// Add or update a peer entry from a bootstrap multiaddr
     pub fn add_bootstrap(&mut self, multiaddr: &str, local_peer_id: Option<&str>) {
         if let Some(local) = local_peer_id {
@@ -418,6 +477,22 @@ impl ConnectionLedger {
             .collect()
     }
 
+    /// `dialable_addresses` for a node in `mode`. Paranoid discovery keeps
+    /// only bootstrap entries, never peers learned from other peers.
+    pub fn dialable_addresses_for_mode(
+        &self,
+        local_peer_id: Option<&str>,
+        mode: scmessenger_core::DiscoveryMode,
+    ) -> Vec<(String, Option<String>)> {
+        self.dialable_addresses(local_peer_id)
+            .into_iter()
+            .filter(|(multiaddr, _)| {
+                mode.dials_learned_peers()
+                    || self.entries.get(multiaddr).is_some_and(|e| e.is_bootstrap)
+            })
+            .collect()
+    }
+
     /// Get all known topics from connected peers
     pub fn all_known_topics(&self) -> Vec<String> {
         let mut topics: Vec<String> = self
@@ -805,6 +880,33 @@ pub fn is_dialable_for_this_node(multiaddr: &str, mode: NetworkMode, my_addrs: &
     true
 }
 
+/// Addresses to dial after a peer shares its ledger, skipping ones this
+/// node cannot route to. Paranoid discovery never dials peers it only heard
+/// about second-hand, so it gets none.
+pub fn learned_dial_targets(
+    entries: &[scmessenger_core::transport::SharedPeerEntry],
+    my_addrs: &[String],
+    mode: scmessenger_core::DiscoveryMode,
+) -> Vec<(String, Option<PeerId>)> {
+    if !mode.dials_learned_peers() {
+        return Vec::new();
+    }
+    entries
+        .iter()
+        .map(|e| strip_peer_id(&e.multiaddr))
+        .zip(entries.iter().map(|e| {
+            e.last_peer_id
+                .as_ref()
+                .and_then(|s| s.parse::<PeerId>().ok())
+        }))
+        // Skip non-routable addresses (loopback, link-local, site-local) a
+        // peer may advertise -- dialing them fails forever and storms the
+        // request_response handler.
+        .filter(|(addr, _)| is_dialable_multiaddr(addr, NetworkMode::Local))
+        .filter(|(addr, _)| is_dialable_for_this_node(addr, NetworkMode::Local, my_addrs))
+        .collect()
+}
+
 /// Strip the /p2p/PeerID suffix from a multiaddr string, leaving just the transport address.
 /// This is the core of "promiscuous" dialing — we dial the IP, not the identity.
 pub fn strip_peer_id(multiaddr: &str) -> String {
@@ -865,6 +967,88 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_paranoid_mode_does_not_dial_ledger_learned_peers() {
+        use scmessenger_core::DiscoveryMode;
+
+        let learned = vec![scmessenger_core::transport::SharedPeerEntry {
+            multiaddr: "/ip4/203.0.113.9/tcp/9001".to_string(),
+            last_peer_id: Some(PeerId::random().to_string()),
+            last_seen: 1_000,
+            known_topics: Vec::new(),
+        }];
+        assert_eq!(
+            learned_dial_targets(&learned, &[], DiscoveryMode::Normal).len(),
+            1
+        );
+        assert!(learned_dial_targets(&learned, &[], DiscoveryMode::Paranoid).is_empty());
+
+        // At startup a paranoid node still dials its bootstrap nodes.
+        let mut ledger = ConnectionLedger::default();
+        ledger.add_bootstrap("/ip4/198.51.100.1/tcp/9001", None);
+        ledger.merge_shared_entries(&learned);
+        assert_eq!(
+            ledger
+                .dialable_addresses_for_mode(None, DiscoveryMode::Normal)
+                .len(),
+            2
+        );
+        let paranoid = ledger.dialable_addresses_for_mode(None, DiscoveryMode::Paranoid);
+        assert_eq!(paranoid.len(), 1);
+        assert_eq!(paranoid[0].0, "/ip4/198.51.100.1/tcp/9001");
+    }
+
+    #[test]
+    fn test_prune_keeps_bootstrap_and_verified_peers() {
+        let day = 24 * 60 * 60;
+        let now = 100 * day;
+        let mut ledger = ConnectionLedger::default();
+        let mut seed = |addr: &str, age: u64, f: &dyn Fn(&mut LedgerEntry)| {
+            let mut entry = LedgerEntry::new(addr.to_string(), false);
+            entry.last_seen = now - age;
+            f(&mut entry);
+            ledger.entries.insert(addr.to_string(), entry);
+        };
+
+        seed("/ip4/10.0.0.1/tcp/9001", 90 * day, &|e| {
+            e.is_bootstrap = true
+        });
+        seed("/ip4/10.0.0.2/tcp/9001", 60, &|e| e.locally_verified = true);
+        seed("/ip4/10.0.0.3/tcp/9001", 40 * day, &|_| {});
+        seed("/ip4/10.0.0.4/tcp/9001", 45 * day, &|e| {
+            e.locally_verified = true;
+        });
+        for i in 0..10u64 {
+            seed(&format!("/ip4/10.0.1.{}/tcp/9001", i), i * 60, &|e| {
+                e.consecutive_failures = 3;
+            });
+        }
+        seed("/ip4/10.0.0.5/tcp/9001", 120, &|e| {
+            e.locally_verified = true;
+            e.consecutive_failures = 1;
+        });
+        assert_eq!(ledger.entries.len(), 15);
+
+        let removed = ledger.prune_at(5, 30 * day, now);
+        assert_eq!(removed, 10);
+        assert_eq!(ledger.entries.len(), 5);
+
+        // Bootstrap survives despite its age; the verified peer survives the cap.
+        assert!(ledger.entries.contains_key("/ip4/10.0.0.1/tcp/9001"));
+        assert!(ledger.entries.contains_key("/ip4/10.0.0.2/tcp/9001"));
+        assert!(ledger.entries.contains_key("/ip4/10.0.0.5/tcp/9001"));
+        // Stale entries are gone whatever their standing.
+        assert!(!ledger.entries.contains_key("/ip4/10.0.0.3/tcp/9001"));
+        assert!(!ledger.entries.contains_key("/ip4/10.0.0.4/tcp/9001"));
+        // Among the failing gossip entries, the freshest are kept.
+        assert!(ledger.entries.contains_key("/ip4/10.0.1.0/tcp/9001"));
+        assert!(ledger.entries.contains_key("/ip4/10.0.1.1/tcp/9001"));
+        assert!(!ledger.entries.contains_key("/ip4/10.0.1.9/tcp/9001"));
+
+        // Limits of 0 disable pruning.
+        assert_eq!(ledger.prune_at(0, 0, now + 365 * day), 0);
+    }
+
     #[test]
     fn test_ledger_entry_backoff() {
         let mut entry = LedgerEntry::new("/ip4/1.2.3.4/tcp/9001".to_string(), false);
@@ -5,6 +5,7 @@
 #![allow(dead_code, unused)]
 
 pub mod api;
+pub mod bench;
 pub mod ble_daemon;
 pub mod ble_mesh;
 pub mod bootstrap;
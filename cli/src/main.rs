@@ -5,6 +5,7 @@
 #![allow(dead_code, unused)]
 
 mod api;
+mod bench;
 mod ble_daemon;
 mod ble_mesh;
 mod bootstrap;
@@ -22,7 +23,9 @@ use clap::{Parser, Subcommand};
 use colored::*;
 use libp2p::{Multiaddr, PeerId};
 use scmessenger_core::message::{decode_envelope, MessageType};
-use scmessenger_core::store::{Contact, ContactManager, MessageDirection, Outbox, QueuedMessage};
+use scmessenger_core::store::{
+    Contact, ContactLookup, ContactManager, MessageDirection, Outbox, QueuedMessage,
+};
 use scmessenger_core::transport::abstraction::TransportType;
 use scmessenger_core::transport::{self, SwarmEvent};
 use scmessenger_core::wasm_support::rpc::{
@@ -42,6 +45,29 @@ fn path_to_string(path: &std::path::Path) -> Result<String> {
         .map(|s| s.to_string())
 }
 
+/// Open the persistent store after a writability / free-space preflight, so
+/// a bad data directory fails with a specific reason instead of a generic
+/// storage error later on.
+fn open_checked_storage(storage_path: &std::path::Path) -> Result<IronCore> {
+    use scmessenger_core::IronCoreError;
+
+    let path = path_to_string(storage_path)?;
+    IronCore::try_with_storage(path, scmessenger_core::store::DEFAULT_MIN_FREE_BYTES).map_err(|e| {
+        match e {
+            IronCoreError::StorageUnwritable => anyhow::anyhow!(
+                "Storage directory {} is not writable (check permissions or read-only mounts)",
+                storage_path.display()
+            ),
+            IronCoreError::StorageFull => anyhow::anyhow!(
+                "Disk full: less than {} MiB free at {}",
+                scmessenger_core::store::DEFAULT_MIN_FREE_BYTES / (1024 * 1024),
+                storage_path.display()
+            ),
+            other => anyhow::anyhow!("Failed to open storage: {}", other),
+        }
+    })
+}
+
 /// Try to replace the port in a multiaddr with a new port.
 /// This is used as a fallback mechanism when the stored port is stale.
 fn try_replace_port(addr: &Multiaddr, new_port: u16) -> Option<Multiaddr> {
@@ -137,6 +163,9 @@ fn load_or_create_headless_network_keypair(
     Ok(keypair)
 }
 
+/// Hard cap on `init --max-attempts`, so the vanity search always ends.
+const VANITY_MAX_ATTEMPTS: u64 = 1_000_000_000;
+
 const VERSION_INFO: &str = concat!(
     env!("CARGO_PKG_VERSION"),
     " (",
@@ -157,6 +186,16 @@ struct Cli {
     #[arg(long, value_name = "ADDR", global = true)]
     http_bind: Option<String>,
 
+    /// Directory for identity, contacts, history, outbox and ledger
+    /// (default: platform data dir, or $SCM_DATA_DIR). Use a separate one
+    /// per node to run several on one host.
+    #[arg(long, value_name = "PATH", global = true)]
+    data_dir: Option<std::path::PathBuf>,
+
+    /// Port for the local control API (default: 9876, or $SCM_API_PORT)
+    #[arg(long, value_name = "PORT", global = true)]
+    api_port: Option<u16>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -193,6 +232,18 @@ enum Commands {
     Init {
         #[arg(short, long)]
         name: Option<String>,
+        /// Search for an identity ID starting with this lowercase hex prefix
+        #[arg(long)]
+        vanity: Option<String>,
+        /// Give up the vanity search after this many attempts (at most
+        /// 1,000,000,000)
+        #[arg(
+            long,
+            default_value_t = 50_000_000,
+            requires = "vanity",
+            value_parser = clap::value_parser!(u64).range(1..=VANITY_MAX_ATTEMPTS)
+        )]
+        max_attempts: u64,
     },
     /// Show identity information
     Identity {
@@ -239,6 +290,12 @@ enum Commands {
     Send { recipient: String, message: String },
     /// Show network status
     Status,
+    /// Print a structured diagnostics snapshot (JSON) for bug reports
+    Diag,
+    /// Print the node's non-secret state (JSON) to attach to a support request
+    SupportBundle,
+    /// Re-dial bootstrap nodes and known peers now, e.g. after a network change
+    Reconnect,
     /// Mark an outbox message as delivered/sent
     MarkSent { message_id: String },
     /// Clear all local history records
@@ -274,6 +331,26 @@ enum Commands {
     HistoryDelete { id: String },
     /// Run self-tests
     Test,
+    /// Benchmark local encrypt/decrypt/sign/verify throughput
+    Bench {
+        /// Iterations per operation
+        #[arg(long, default_value_t = 1000)]
+        messages: u32,
+        /// Plaintext size in bytes
+        #[arg(long, default_value_t = 256)]
+        size: usize,
+    },
+    /// Securely erase the identity, contacts, messages and all local data
+    Wipe {
+        /// Required: confirms the wipe is intended (it cannot be undone)
+        #[arg(long)]
+        confirm: bool,
+    },
+    /// Storage upkeep for long-running nodes
+    Maintenance {
+        #[command(subcommand)]
+        action: MaintenanceAction,
+    },
     /// Manage audit log
     Audit {
         #[command(subcommand)]
@@ -289,6 +366,46 @@ enum Commands {
         #[command(subcommand)]
         action: DiscoveryAction,
     },
+    /// Bootstrap node tools
+    Bootstrap {
+        #[command(subcommand)]
+        action: BootstrapAction,
+    },
+    /// Peer exchange with connected peers (requires a running node)
+    Peer {
+        #[command(subcommand)]
+        action: PeerAction,
+    },
+    /// Gossipsub topics (requires a running node)
+    Topic {
+        #[command(subcommand)]
+        action: TopicAction,
+    },
+    /// Manage queued outbound messages
+    Outbox {
+        #[command(subcommand)]
+        action: OutboxAction,
+    },
+    /// Save, list and send message drafts
+    Draft {
+        #[command(subcommand)]
+        action: DraftAction,
+    },
+    /// Encrypt a file for a contact ("-" for stdin/stdout)
+    Encrypt {
+        /// Recipient contact (peer ID, nickname or public key)
+        #[arg(long)]
+        to: String,
+        infile: String,
+        outfile: String,
+    },
+    /// Decrypt a file encrypted for this identity ("-" for stdin/stdout)
+    Decrypt { infile: String, outfile: String },
+    /// Show who an envelope is from and addressed to, without decrypting it
+    Inspect {
+        /// Envelope file ("-" for stdin)
+        envelope_file: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -421,6 +538,34 @@ enum ContactAction {
         #[arg(long)]
         clear: bool,
     },
+    /// Set relays (PeerId or /p2p/ multiaddr) to try first when messaging a
+    /// contact; applied when the node starts
+    SetRelays {
+        contact: String,
+        relays: Vec<String>,
+        #[arg(long)]
+        clear: bool,
+    },
+    /// Export all contacts to a signed bundle file
+    Export {
+        file: String,
+    },
+    /// Import contacts from a bundle file, merging by public key
+    Import {
+        file: String,
+        /// Replace existing local nicknames when the bundle disagrees
+        #[arg(long)]
+        overwrite: bool,
+        /// Accept bundles whose signature does not verify
+        #[arg(long)]
+        no_verify: bool,
+    },
+    /// Merge contacts that share a public key
+    Dedup {
+        /// Only list the duplicates that would be merged
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -448,12 +593,30 @@ enum ConfigAction {
         #[arg(short, long)]
         timing: Option<bool>,
     },
+    /// Manage configured bootstrap nodes
+    Bootstrap {
+        #[command(subcommand)]
+        action: ConfigBootstrapAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigBootstrapAction {
+    /// Add bootstrap nodes; addresses already configured (same IP:Port) are skipped
+    Add {
+        #[arg(required = true)]
+        multiaddrs: Vec<String>,
+    },
+    /// Remove a bootstrap node (matched by IP:Port)
+    Remove { multiaddr: String },
 }
 
 #[derive(Subcommand)]
 enum SwarmAction {
     /// Show swarm connection statistics
     Stats,
+    /// Show the detected NAT type and hole-punch outcomes per peer
+    Nat,
 }
 
 #[derive(Subcommand)]
@@ -466,6 +629,56 @@ enum DiscoveryAction {
     Peers,
 }
 
+#[derive(Subcommand)]
+enum PeerAction {
+    /// Ask every connected peer for its known peers and merge them into the ledger
+    Discover,
+    /// Show connection state, latency and advertised capabilities of a peer
+    Show { peer_id: String },
+}
+
+#[derive(Subcommand)]
+enum TopicAction {
+    /// Publish a payload to a topic and report how many peers received it
+    Publish { topic: String, data: String },
+}
+
+#[derive(Subcommand)]
+enum MaintenanceAction {
+    /// Flush the store and reclaim space from deleted/overwritten records
+    Compact,
+}
+
+#[derive(Subcommand)]
+enum OutboxAction {
+    /// Drop every queued message for a contact (nickname, peer ID or public key)
+    Cancel { contact: String },
+    /// Show delivery status, attempts and paths tried for a sent message
+    Report { message_id: String },
+}
+
+#[derive(Subcommand)]
+enum DraftAction {
+    /// Save (or replace) the draft for a contact
+    Save { contact: String, text: String },
+    /// List saved drafts
+    List,
+    /// Send the draft for a contact and delete it
+    Send { contact: String },
+}
+
+#[derive(Subcommand)]
+enum BootstrapAction {
+    /// Dial bootstrap nodes, wait for Identify and report latency/agent version
+    Check {
+        /// Probe only this multiaddr (default: every configured bootstrap node)
+        multiaddr: Option<String>,
+        /// Seconds to wait for each node before reporting a timeout
+        #[arg(short, long, default_value_t = transport::DEFAULT_PROBE_TIMEOUT_SECS)]
+        timeout: u64,
+    },
+}
+
 /// Current Unix timestamp in seconds.
 fn now_secs() -> u64 {
     std::time::SystemTime::now()
@@ -619,6 +832,16 @@ mod dial_scheduler_tests {
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Parsed first: --data-dir and --api-port must be applied before
+    // anything resolves the data directory, logging included.
+    let cli = Cli::parse();
+    if let Some(dir) = cli.data_dir.clone() {
+        config::Config::set_data_dir_override(dir);
+    }
+    if let Some(port) = cli.api_port {
+        api::set_api_port(port);
+    }
+
     // 1. Determine data directory early for logging
     let data_dir = config::Config::data_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
     let log_dir = data_dir.join("logs");
@@ -641,6 +864,7 @@ async fn main() -> Result<()> {
                 .with_ansi(false)
                 .with_writer(non_blocking),
         )
+        .with(scmessenger_core::store::log_ring::LogRingLayer)
         .init();
 
     tracing::info!(
@@ -658,10 +882,12 @@ async fn main() -> Result<()> {
         tracing::warn!("Failed to prune old logs: {}", e);
     }
 
-    let cli = Cli::parse();
-
     match cli.command {
-        Commands::Init { name } => cmd_init(name).await,
+        Commands::Init {
+            name,
+            vanity,
+            max_attempts,
+        } => cmd_init(name, vanity, max_attempts).await,
         Commands::Identity { action } => cmd_identity(action).await,
         Commands::Contact { action } => cmd_contact(action).await,
         Commands::Config { action } => cmd_config(action).await,
@@ -679,6 +905,9 @@ async fn main() -> Result<()> {
         Commands::Stop => cmd_stop().await,
         Commands::Send { recipient, message } => cmd_send_offline(recipient, message).await,
         Commands::Status => cmd_status().await,
+        Commands::Diag => cmd_diag().await,
+        Commands::SupportBundle => cmd_support_bundle().await,
+        Commands::Reconnect => cmd_reconnect().await,
         Commands::MarkSent { message_id } => cmd_mark_sent(message_id).await,
         Commands::HistoryClear { yes } => cmd_history_clear(yes).await,
         Commands::HistoryEnforceRetention { max_messages } => {
@@ -698,9 +927,24 @@ async fn main() -> Result<()> {
         }
         Commands::HistoryDelete { id } => cmd_history_delete(id).await,
         Commands::Test => cmd_test().await,
+        Commands::Bench { messages, size } => cmd_bench(messages, size),
+        Commands::Wipe { confirm } => cmd_wipe(confirm),
+        Commands::Maintenance { action } => cmd_maintenance(action).await,
         Commands::Audit { action } => cmd_audit(action).await,
         Commands::Swarm { action } => cmd_swarm(action).await,
         Commands::Discovery { action } => cmd_discovery(action).await,
+        Commands::Bootstrap { action } => cmd_bootstrap(action).await,
+        Commands::Peer { action } => cmd_peer(action).await,
+        Commands::Topic { action } => cmd_topic(action).await,
+        Commands::Outbox { action } => cmd_outbox(action).await,
+        Commands::Draft { action } => cmd_draft(action).await,
+        Commands::Encrypt {
+            to,
+            infile,
+            outfile,
+        } => cmd_encrypt(to, infile, outfile),
+        Commands::Decrypt { infile, outfile } => cmd_decrypt(infile, outfile),
+        Commands::Inspect { envelope_file } => cmd_inspect(envelope_file),
     }
 }
 
@@ -718,10 +962,14 @@ async fn cmd_stop() -> Result<()> {
     Ok(())
 }
 
-async fn cmd_init(name: Option<String>) -> Result<()> {
+async fn cmd_init(name: Option<String>, vanity: Option<String>, max_attempts: u64) -> Result<()> {
     println!("{}", "Initializing SCMessenger...".bold());
     println!();
 
+    if let Some(prefix) = vanity.as_deref() {
+        scmessenger_core::identity::validate_vanity_prefix(prefix)?;
+    }
+
     let config = config::Config::load()?;
     println!("  {} Configuration", "[OK]".green());
 
@@ -733,10 +981,27 @@ async fn cmd_init(name: Option<String>) -> Result<()> {
     );
 
     let storage_path = data_dir.join("storage");
-    let core = IronCore::with_storage(path_to_string(&storage_path)?);
+    let core = open_checked_storage(&storage_path)?;
     core.grant_consent();
-    core.initialize_identity()
-        .context("Failed to initialize identity")?;
+    match vanity {
+        Some(prefix) if core.identity_id().is_none() => {
+            let keys = generate_vanity_keys(prefix, max_attempts).await?;
+            core.initialize_identity_with_keys(keys)
+                .context("Failed to initialize identity")?;
+        }
+        Some(_) => {
+            println!(
+                "  {} Identity already exists; --vanity ignored",
+                "[WARN]".yellow()
+            );
+            core.initialize_identity()
+                .context("Failed to initialize identity")?;
+        }
+        None => {
+            core.initialize_identity()
+                .context("Failed to initialize identity")?;
+        }
+    }
 
     // Set nickname if provided
     if let Some(nickname) = name {
@@ -761,6 +1026,46 @@ async fn cmd_init(name: Option<String>) -> Result<()> {
     Ok(())
 }
 
+/// Run the vanity search off the async runtime, printing attempt counts as
+/// it goes.
+async fn generate_vanity_keys(
+    prefix: String,
+    max_attempts: u64,
+) -> Result<scmessenger_core::identity::IdentityKeys> {
+    println!(
+        "  Searching for identity ID starting with {} (up to {} attempts)...",
+        prefix.bright_cyan(),
+        max_attempts
+    );
+    let search_prefix = prefix.clone();
+    let keys = tokio::task::spawn_blocking(move || {
+        scmessenger_core::identity::IdentityKeys::generate_vanity_with_progress(
+            &search_prefix,
+            max_attempts,
+            |attempts| {
+                use std::io::Write;
+                print!("\r    {} attempts", attempts);
+                let _ = std::io::stdout().flush();
+            },
+        )
+    })
+    .await
+    .context("Vanity search task failed")?;
+    println!("\r");
+
+    match keys {
+        Some(keys) => {
+            println!("  {} Found vanity identity", "[OK]".green());
+            Ok(keys)
+        }
+        None => anyhow::bail!(
+            "No identity ID starting with '{}' found in {} attempts; try a shorter prefix or raise --max-attempts",
+            prefix,
+            max_attempts
+        ),
+    }
+}
+
 async fn cmd_identity(action: Option<IdentityAction>) -> Result<()> {
     let config = config::Config::load()?;
     let data_dir = config::Config::data_dir()?;
@@ -929,6 +1234,10 @@ fn print_full_identity(core: &IronCore, config: &config::Config) -> Result<()> {
             .expect("Public key hex should be available")
             .bright_yellow()
     );
+    println!(
+        "  Fingerprint:            {}",
+        core.identity_fingerprint_words().join(" ").bright_cyan()
+    );
     println!();
 
     println!("{}", "Direct Connection Info".bold());
@@ -1051,6 +1360,25 @@ async fn cmd_contact(action: ContactAction) -> Result<()> {
 
             // Try to use API if a node is running
             if api::is_api_available().await {
+                // The checks above only show the key and ID are consistent
+                // with each other. If the node is connected to the peer, its
+                // Identify exchange proves which key the peer actually holds.
+                if let Some(live_pk) = confirm_pubkey_via_api(&peer_id, &resolved_pk).await {
+                    if live_pk.to_lowercase() != resolved_pk.to_lowercase() {
+                        eprintln!(
+                            "{} The connected peer's identity key does not match!",
+                            "[WARN] SECURITY:".red().bold()
+                        );
+                        eprintln!("  Peer proved public key: {}", live_pk.yellow());
+                        eprintln!("  You provided:           {}", resolved_pk.dimmed());
+                        eprintln!("  Messages encrypted to the provided key would not reach this peer. Contact NOT saved.");
+                        return Ok(());
+                    }
+                    println!(
+                        "{} Public key confirmed over a live connection",
+                        "[OK]".green()
+                    );
+                }
                 let _ = api::add_contact_via_api(&peer_id, &public_key, name.clone())
                     .await
                     .context("Failed to add contact via API");
@@ -1122,6 +1450,9 @@ async fn cmd_contact(action: ContactAction) -> Result<()> {
                     if let Some(ref notes) = contact.notes {
                         println!("  Notes:      {}", notes);
                     }
+                    if !contact.preferred_relays.is_empty() {
+                        println!("  Relays:     {}", contact.preferred_relays.join(", "));
+                    }
                 }
 
                 ContactAction::Remove { contact: query } => {
@@ -1192,6 +1523,88 @@ async fn cmd_contact(action: ContactAction) -> Result<()> {
                 }
 
                 ContactAction::Add { .. } => unreachable!(),
+                ContactAction::Export { file } => {
+                    let bundle = core
+                        .export_contacts_bundle()
+                        .context("Failed to export contacts (identity initialized?)")?;
+                    std::fs::write(&file, &bundle)
+                        .with_context(|| format!("Failed to write contact bundle: {}", file))?;
+                    println!(
+                        "{} Exported {} contact(s) to {}",
+                        "[OK]".green(),
+                        contacts.count(),
+                        file.bright_cyan()
+                    );
+                }
+                ContactAction::Import {
+                    file,
+                    overwrite,
+                    no_verify,
+                } => {
+                    let data = std::fs::read_to_string(&file)
+                        .with_context(|| format!("Failed to read contact bundle: {}", file))?;
+                    let report_json = core
+                        .import_contacts_bundle(data, !no_verify, overwrite)
+                        .context("Failed to import contact bundle")?;
+                    let report: scmessenger_core::store::ContactImportReport =
+                        serde_json::from_str(&report_json)?;
+                    println!(
+                        "{} Imported contacts: {} added, {} updated, {} unchanged",
+                        "[OK]".green(),
+                        report.added,
+                        report.updated,
+                        report.unchanged
+                    );
+                    if report.conflicts > 0 {
+                        let resolution = if overwrite {
+                            "bundle nickname applied"
+                        } else {
+                            "local nickname kept; use --overwrite to replace"
+                        };
+                        println!(
+                            "{} {} nickname conflict(s): {}",
+                            "[WARN]".yellow(),
+                            report.conflicts,
+                            resolution
+                        );
+                    }
+                    if report.rejected > 0 {
+                        println!(
+                            "{} {} bundle entries rejected: invalid public key",
+                            "[WARN]".yellow(),
+                            report.rejected
+                        );
+                    }
+                }
+                ContactAction::Dedup { dry_run } => {
+                    let groups = contacts
+                        .find_duplicates()
+                        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+                    if groups.is_empty() {
+                        println!("{}", "No duplicate contacts.".dimmed());
+                        return Ok(());
+                    }
+                    for group in &groups {
+                        println!(
+                            "{} {}",
+                            group[0].display_name().bright_cyan(),
+                            group[0].public_key.dimmed()
+                        );
+                        for (i, contact) in group.iter().enumerate() {
+                            let marker = if i == 0 { "keep " } else { "merge" };
+                            println!("  {} {}", marker, contact.peer_id);
+                        }
+                    }
+                    if dry_run {
+                        println!(
+                            "{} contact(s) would be merged (dry run)",
+                            groups.iter().map(|g| g.len() - 1).sum::<usize>()
+                        );
+                    } else {
+                        let merged = contacts.dedup().map_err(|e| anyhow::anyhow!("{:?}", e))?;
+                        println!("{} Merged {} duplicate contact(s)", "[OK]".green(), merged);
+                    }
+                }
                 ContactAction::SetNickname {
                     contact: query,
                     nickname,
@@ -1225,6 +1638,39 @@ async fn cmd_contact(action: ContactAction) -> Result<()> {
                         }
                     }
                 }
+
+                ContactAction::SetRelays {
+                    contact: query,
+                    relays,
+                    clear,
+                } => {
+                    if clear != relays.is_empty() {
+                        anyhow::bail!("Give one or more relays, or --clear");
+                    }
+
+                    let contact = find_contact(&contacts, &query)?;
+                    contacts
+                        .set_preferred_relays(contact.peer_id.clone(), relays.clone())
+                        .map_err(|_| {
+                            anyhow::anyhow!("Invalid relay: expected a PeerId or a /p2p/ multiaddr")
+                        })?;
+
+                    if relays.is_empty() {
+                        println!(
+                            "{} Preferred relays cleared for {}",
+                            "[OK]".green(),
+                            contact.peer_id.dimmed()
+                        );
+                    } else {
+                        println!(
+                            "{} Preferred relays set for {}: {}",
+                            "[OK]".green(),
+                            contact.peer_id.dimmed(),
+                            relays.join(", ").bright_cyan()
+                        );
+                        println!("  Takes effect the next time the node starts.");
+                    }
+                }
             }
         }
     }
@@ -1258,6 +1704,22 @@ async fn cmd_config(action: ConfigAction) -> Result<()> {
             }
         }
 
+        ConfigAction::Bootstrap { action } => match action {
+            ConfigBootstrapAction::Add { multiaddrs } => {
+                let summary = config.add_bootstrap_nodes(&multiaddrs)?;
+                println!(
+                    "{} Bootstrap nodes: {} added, {} skipped (already configured)",
+                    "[OK]".green(),
+                    summary.added,
+                    summary.skipped
+                );
+            }
+            ConfigBootstrapAction::Remove { multiaddr } => {
+                config.remove_bootstrap_node(&multiaddr)?;
+                println!("{} Removed {}", "[OK]".green(), multiaddr.bright_cyan());
+            }
+        },
+
         ConfigAction::Privacy {
             padding,
             onion,
@@ -1482,7 +1944,7 @@ async fn cmd_start(port: Option<u16>, http_bind: Option<String>) -> Result<()> {
 
     let data_dir = config::Config::data_dir()?;
     let storage_path = data_dir.join("storage");
-    let core = IronCore::with_storage(path_to_string(&storage_path)?);
+    let core = open_checked_storage(&storage_path)?;
     core.grant_consent();
     core.initialize_identity()
         .context("Failed to load identity")?;
@@ -1533,10 +1995,34 @@ async fn cmd_start(port: Option<u16>, http_bind: Option<String>) -> Result<()> {
         .context("Failed to get network keypair from identity")?;
     let local_peer_id = network_keypair.public().to_peer_id();
 
-    // NOTE: PeerId is now derived from identity keys. Existing installations that
-    // had a separate network_keypair.dat will see their PeerId change. This is
-    // intentional to unify identity and network IDs, but may require updating
-    // peer expectations/ledgers on migration.
+    // The PeerId is derived from the identity keys, so it changes when an
+    // identity is imported (or when migrating from the old separate
+    // network_keypair.dat). Remember the last one we ran as and say so when it
+    // moves: peers, ledgers and shared contact codes still expect the old ID.
+    let peer_id_path = data_dir.join("peer_id");
+    if let Ok(previous) = std::fs::read_to_string(&peer_id_path) {
+        let previous = previous.trim().to_string();
+        if !previous.is_empty() && !core.verify_peer_id_matches(previous.clone()) {
+            tracing::warn!(
+                "PeerId changed since last start: {} -> {}",
+                previous,
+                local_peer_id
+            );
+            println!(
+                "{} Peer ID changed since last start (was {}).",
+                "[WARN]".yellow(),
+                previous
+            );
+            println!(
+                "       Contacts and relays that knew the old ID must re-add this node; \
+                 share a fresh contact code with {}.",
+                "scm identity".bright_green()
+            );
+        }
+    }
+    if let Err(e) = std::fs::write(&peer_id_path, local_peer_id.to_string()) {
+        tracing::warn!("Failed to record PeerId in {:?}: {}", peer_id_path, e);
+    }
 
     println!("{} Peer ID: {}", "[OK]".green(), local_peer_id);
     println!();
@@ -1565,6 +2051,7 @@ async fn cmd_start(port: Option<u16>, http_bind: Option<String>) -> Result<()> {
         transport_bridge: transport_bridge.clone(),
         ui_port: ws_port,
         core: Some(Arc::clone(&core)),
+        relay_server: None,
     });
 
     // Start WebSocket + HTTP Server (serves landing page at /)
@@ -1574,12 +2061,15 @@ async fn cmd_start(port: Option<u16>, http_bind: Option<String>) -> Result<()> {
     let (event_tx, mut event_rx) = tokio::sync::mpsc::channel(256);
 
     // Build discovery config from CLI config
+    let discovery_mode = config.network.discovery_mode;
     let discovery_config =
         scmessenger_core::transport::DiscoveryConfig::new(if config.enable_mdns {
             scmessenger_core::transport::DiscoveryMode::Open
         } else {
             scmessenger_core::transport::DiscoveryMode::Manual
-        });
+        })
+        .with_advertise_scope(config.network.advertise_scope.unwrap_or_default())
+        .with_mesh_discovery_mode(discovery_mode);
 
     // Parse bootstrap node multiaddrs from merged list (relay also uses bootstrap nodes)
     let relay_bootstrap: Vec<libp2p::Multiaddr> = merged_bootstrap
@@ -1603,8 +2093,20 @@ async fn cmd_start(port: Option<u16>, http_bind: Option<String>) -> Result<()> {
         false,
         Some(discovery_config),
         transport::default_routing_engine_handle(),
+        None,
+        Some(transport::NatConfig {
+            enable_hole_punch: config.network.enable_nat_traversal,
+            enable_upnp: config.network.enable_nat_traversal,
+            ..Default::default()
+        }),
     )
     .await?;
+    swarm_handle
+        .relay_server()
+        .apply_mesh_settings(&config.mesh_settings());
+    swarm_handle
+        .apply_mesh_settings(&config.mesh_settings())
+        .await?;
 
     // ── WebSocket P2P Bridge for WASM ────────────────────────────────────
     // Redundant explicit bind removed; handled by MultiPortConfig.
@@ -1627,6 +2129,11 @@ async fn cmd_start(port: Option<u16>, http_bind: Option<String>) -> Result<()> {
         let _ = swarm_handle.subscribe_topic(topic.to_string()).await;
     }
 
+    // Route deliveries through each contact's preferred relays first
+    for (peer_id, relays) in contacts.preferred_relay_routes().unwrap_or_default() {
+        let _ = swarm_handle.set_preferred_relays(peer_id, relays).await;
+    }
+
     println!();
     println!("{}", "Commands:".bold());
     println!("  {} <contact> <message>", "send".bright_green());
@@ -1670,7 +2177,7 @@ async fn cmd_start(port: Option<u16>, http_bind: Option<String>) -> Result<()> {
         tokio::spawn(async move {
             let addrs = {
                 let l = ledger_clone.lock().await;
-                l.dialable_addresses(Some(&local_peer_id.to_string()))
+                l.dialable_addresses_for_mode(Some(&local_peer_id.to_string()), discovery_mode)
             };
             let my_addrs: Vec<String> = swarm_clone
                 .get_bound_addresses()
@@ -1718,13 +2225,18 @@ async fn cmd_start(port: Option<u16>, http_bind: Option<String>) -> Result<()> {
         }
     });
 
-    // Periodic ledger save (every 60 seconds)
+    // Periodic ledger prune + save (every 60 seconds)
     let ledger_save_clone = ledger.clone();
     let data_dir_save = data_dir.clone();
+    let ledger_limits = (
+        config.network.ledger_max_entries,
+        config.network.ledger_max_age_secs,
+    );
     tokio::spawn(async move {
         loop {
             tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
             let mut l = ledger_save_clone.lock().await;
+            l.prune(ledger_limits.0, ledger_limits.1);
             if let Err(e) = l.save(&data_dir_save) {
                 tracing::error!("Failed to save ledger: {}", e);
             }
@@ -1742,7 +2254,7 @@ async fn cmd_start(port: Option<u16>, http_bind: Option<String>) -> Result<()> {
 
             let addrs = {
                 let l = ledger_refresh_clone.lock().await;
-                l.dialable_addresses(Some(&local_peer_id.to_string()))
+                l.dialable_addresses_for_mode(Some(&local_peer_id.to_string()), discovery_mode)
             };
 
             // For each peer, try to refresh their address via Identify
@@ -1766,6 +2278,9 @@ async fn cmd_start(port: Option<u16>, http_bind: Option<String>) -> Result<()> {
     let api_ctx = api::ApiContext {
         core: core.clone(),
         swarm_handle: Arc::new(swarm_handle.clone()),
+        peers: Arc::clone(&peers),
+        ledger: Arc::clone(&ledger),
+        start_time: std::time::Instant::now(),
     };
 
     let http_bind_api = http_bind.clone();
@@ -1778,7 +2293,7 @@ async fn cmd_start(port: Option<u16>, http_bind: Option<String>) -> Result<()> {
     println!(
         "{} Control API: {}",
         "[OK]".green(),
-        format!("http://127.0.0.1:{}", api::API_PORT).dimmed()
+        format!("http://{}", api::api_addr()).dimmed()
     );
 
     let core_rx = core.clone();
@@ -1973,13 +2488,6 @@ async fn cmd_start(port: Option<u16>, http_bind: Option<String>) -> Result<()> {
                                         tracing::error!("Failed to save ledger: {}", e);
                                     }
 
-                                    // Dial newly discovered peers
-                                    let new_entries: Vec<(String, Option<String>)> = entries
-                                        .iter()
-                                        .map(|e| {
-                                            (ledger::strip_peer_id(&e.multiaddr), e.last_peer_id.clone())
-                                        })
-                                        .collect();
                                     drop(l); // release lock before dialing
 
                                     // Graceful-AF dial policy: know our own addresses
@@ -1996,27 +2504,13 @@ async fn cmd_start(port: Option<u16>, http_bind: Option<String>) -> Result<()> {
                                         .map(|a| a.to_string())
                                         .collect();
 
-                                    for (addr_str, peer_id_opt) in new_entries {
-                                        // Skip non-routable addresses (loopback,
-                                        // link-local, site-local) a peer may
-                                        // advertise -- dialing them fails forever
-                                        // and storms the request_response handler.
-                                        if !ledger::is_dialable_multiaddr(
-                                            &addr_str,
-                                            ledger::NetworkMode::Local,
-                                        ) {
-                                            continue;
-                                        }
-                                        if !ledger::is_dialable_for_this_node(
-                                            &addr_str,
-                                            ledger::NetworkMode::Local,
-                                            &my_addrs,
-                                        ) {
-                                            continue;
-                                        }
-                                        let peer_id = peer_id_opt
-                                            .as_ref()
-                                            .and_then(|s| s.parse::<PeerId>().ok());
+                                    // Dial newly discovered peers (none in paranoid
+                                    // discovery mode)
+                                    for (addr_str, peer_id) in ledger::learned_dial_targets(
+                                        &entries,
+                                        &my_addrs,
+                                        discovery_mode,
+                                    ) {
                                         // Fire-and-forget: scheduler spawns the actual dial
                                         // so this shared event loop never blocks on a
                                         // connection/timeout.
@@ -2026,7 +2520,13 @@ async fn cmd_start(port: Option<u16>, http_bind: Option<String>) -> Result<()> {
                             }
 
                             // IDENTIFY: Peer identity confirmed — update ledger
-                            SwarmEvent::PeerIdentified { peer_id, listen_addrs, .. } => {
+                            SwarmEvent::PeerIdentified { peer_id, agent_version, listen_addrs, .. } => {
+                                let is_relay = scmessenger_core::transport::is_relay_agent(&agent_version);
+                                core_rx.notify_peer_identified(
+                                    peer_id.to_string(),
+                                    agent_version,
+                                    listen_addrs.iter().map(|a| a.to_string()).collect(),
+                                );
                                 let entries = {
                                     let mut l = ledger_rx.lock().await;
                                     for addr in &listen_addrs {
@@ -2037,6 +2537,19 @@ async fn cmd_start(port: Option<u16>, http_bind: Option<String>) -> Result<()> {
                                 if let Err(e) = swarm_handle.share_ledger(peer_id, entries).await {
                                     tracing::warn!("Failed to share ledger with identified peer {}: {}", peer_id, e);
                                 }
+
+                                // RELAY FLUSH: queued messages for recipients we
+                                // never see directly go out via the new relay.
+                                if is_relay {
+                                    let connected: Vec<PeerId> =
+                                        peers_rx.lock().await.keys().copied().collect();
+                                    tokio::spawn(flush_outbox_via_relay(
+                                        swarm_handle.clone(),
+                                        outbox_rx.clone(),
+                                        peer_id,
+                                        connected,
+                                    ));
+                                }
                             }
 
                             // GOSSIPSUB: New topic discovered
@@ -2141,12 +2654,93 @@ async fn cmd_start(port: Option<u16>, http_bind: Option<String>) -> Result<()> {
                                                 }
                                             }
                                         }
+                                        MessageType::Broadcast => {
+                                            // Broadcasts travel over gossipsub (BroadcastReceived),
+                                            // never as a direct encrypted envelope.
+                                            tracing::debug!("Ignoring broadcast sent point-to-point by {}", peer_id);
+                                        }
+                                        MessageType::Typing => {
+                                            if msg.typing_state() == Some(true) {
+                                                tracing::debug!("{} is typing", peer_id);
+                                            }
+                                        }
                                     }
                                 }
                             }
                             SwarmEvent::ListeningOn(addr) => {
                                 println!("{} Listening on {}", "[OK]".green(), addr);
                             }
+                            SwarmEvent::ListenAddrExpired(addr) => {
+                                println!("{} No longer listening on {}", "[WARN]".yellow(), addr);
+                            }
+                            SwarmEvent::RelayCongested { relay_peer } => {
+                                tracing::warn!(
+                                    "Relay {} is out of forwarding budget; rerouting via other relays",
+                                    relay_peer
+                                );
+                            }
+                            SwarmEvent::PeerRateLimited { peer } => {
+                                tracing::warn!(
+                                    "Peer {} is over the inbound rate limit; rejecting its messages",
+                                    peer
+                                );
+                            }
+                            SwarmEvent::RelayRefused {
+                                peer_id,
+                                message_id,
+                                reason,
+                            } => {
+                                tracing::info!(
+                                    "Refused to relay {} for {} ({:?})",
+                                    message_id,
+                                    peer_id,
+                                    reason
+                                );
+                            }
+                            SwarmEvent::BroadcastReceived { topic, frame } => {
+                                match core_rx.receive_broadcast(&topic, &frame) {
+                                    Ok(Some(msg)) => {
+                                        println!(
+                                            "\n{} [{}] {}: {}",
+                                            "[BROADCAST]".bright_magenta(),
+                                            topic,
+                                            &msg.sender_id[..msg.sender_id.len().min(16)],
+                                            String::from_utf8_lossy(&msg.payload)
+                                        );
+                                        print!("> ");
+                                        let _ = std::io::Write::flush(&mut std::io::stdout());
+                                    }
+                                    Ok(None) => {}
+                                    Err(e) => {
+                                        tracing::warn!("Dropped broadcast on {}: {:?}", topic, e);
+                                    }
+                                }
+                            }
+                            SwarmEvent::ClockCorrected { relay, offset_secs } => {
+                                tracing::info!(
+                                    "Clock corrected by {}s from relay {}",
+                                    offset_secs,
+                                    relay
+                                );
+                                core_rx.set_clock_offset(offset_secs);
+                            }
+                            SwarmEvent::DeliveryGivenUp { message_id, target } => {
+                                println!(
+                                    "\n{} Gave up delivering {} to {}",
+                                    "[FAIL]".red(),
+                                    message_id.dimmed(),
+                                    target
+                                );
+                                print!("> ");
+                                let _ = std::io::Write::flush(&mut std::io::stdout());
+                                let _ = core_rx.mark_delivery_failed(
+                                    message_id,
+                                    format!("delivery to {} given up", target),
+                                );
+                            }
+                            SwarmEvent::DeliveryAttempted { message_id, path, error } => {
+                                let _ = core_rx.record_delivery_attempt(message_id, path, error);
+                            }
                             _ => {}
                         }
                     }
@@ -2265,8 +2859,20 @@ async fn cmd_start(port: Option<u16>, http_bind: Option<String>) -> Result<()> {
                                         continue;
                                     }
 
-                                    let contact = Contact::new(peer_id.clone(), pk)
-                                        .with_nickname(name.unwrap_or(peer_id));
+                                    let contact = Contact::new(peer_id, pk);
+                                    let contact = match name {
+                                        Some(name) => contact.with_nickname(name),
+                                        None => Ok(contact),
+                                    };
+                                    let contact = match contact {
+                                        Ok(contact) => contact,
+                                        Err(e) => {
+                                            let _ = ui_broadcast.send(server::UiOutbound::Legacy(server::UiEvent::Error {
+                                                message: format!("Invalid nickname: {}", e)
+                                            }));
+                                            continue;
+                                        }
+                                    };
                                     let _ = contacts_rx.add(contact);
                                     if let Ok(list) = contacts_rx.list() {
                                         let _ = ui_broadcast.send(server::UiOutbound::Legacy(server::UiEvent::ContactList { contacts: list.into_iter().map(|c| serde_json::to_value(c).unwrap_or_default()).collect() }));
@@ -2493,8 +3099,13 @@ async fn cmd_start(port: Option<u16>, http_bind: Option<String>) -> Result<()> {
                              println!("Peers: {}", c);
                         }
                         if line == "peers" {
-                             let p = peers_rx.lock().await;
-                             for k in p.keys() { println!("  {}", k); }
+                             let ids: Vec<libp2p::PeerId> = peers_rx.lock().await.keys().cloned().collect();
+                             for k in ids {
+                                 match swarm_handle.get_peer_rtt(k).await.ok().flatten() {
+                                     Some(rtt) => println!("  {} ({} ms)", k, rtt.as_millis()),
+                                     None => println!("  {}", k),
+                                 }
+                             }
                         }
                         if line == "contacts" {
                             if let Ok(l) = contacts_rx.list() {
@@ -2531,6 +3142,7 @@ async fn cmd_relay(
     let data_dir = config::Config::data_dir()?;
     let storage_path = data_dir.join("storage");
     let core = Arc::new(IronCore::with_storage(path_to_string(&storage_path)?));
+    core.set_relay_only(true);
     // Load existing identity (if any) so the relay can migrate its network key
     // from the IronCore identity, preserving the PeerId on first upgrade.
     let _ = core.initialize_identity();
@@ -2609,38 +3221,30 @@ async fn cmd_relay(
         transport_bridge::TransportBridge::new(),
     ));
 
-    // Web context for landing page + API
-    let web_ctx = Arc::new(server::WebContext {
-        node_peer_id: local_peer_id.to_string(),
-        node_public_key: String::new(),
-        bootstrap_nodes: all_bootstrap.clone(),
-        ledger: ledger.clone(),
-        peers: peers.clone(),
-        start_time: std::time::Instant::now(),
-        transport_bridge: transport_bridge.clone(),
-        ui_port: http_port,
-        core: Some(Arc::clone(&core)),
-    });
-
-    // Start HTTP server (landing page + WebSocket)
-    let (ui_broadcast, _ui_cmd_rx) = server::start(http_port, web_ctx.clone()).await?;
-    println!(
-        "{} HTTP server started on port {}",
-        "[OK]".green(),
-        http_port
-    );
-
     // Start swarm
     let listen_multiaddr: libp2p::Multiaddr =
         listen_addr.parse().context("Invalid listen multiaddr")?;
     let (event_tx, mut event_rx) = tokio::sync::mpsc::channel(256);
 
+    // Relay nodes are always on and publicly reachable, so they run the wide
+    // gossipsub mesh, serve DHT queries and, unless configured otherwise,
+    // advertise only public addresses.
+    let discovery_mode = config.network.discovery_mode;
     let discovery_config =
         scmessenger_core::transport::DiscoveryConfig::new(if config.enable_mdns {
             scmessenger_core::transport::DiscoveryMode::Open
         } else {
             scmessenger_core::transport::DiscoveryMode::Manual
-        });
+        })
+        .with_gossip_profile(scmessenger_core::GossipProfile::Relay)
+        .with_kad_mode(scmessenger_core::KadMode::Server)
+        .with_advertise_scope(
+            config
+                .network
+                .advertise_scope
+                .unwrap_or(scmessenger_core::AdvertiseScope::WanOnly),
+        )
+        .with_mesh_discovery_mode(discovery_mode);
 
     // Parse bootstrap node multiaddrs from config
     let bootstrap_multiaddrs: Vec<libp2p::Multiaddr> = all_bootstrap
@@ -2674,10 +3278,40 @@ async fn cmd_relay(
         true,
         Some(discovery_config),
         transport::default_routing_engine_handle(),
+        None,
+        Some(transport::NatConfig::relay()),
     )
     .await?;
+    swarm_handle
+        .relay_server()
+        .apply_mesh_settings(&config.mesh_settings());
+    swarm_handle
+        .apply_mesh_settings(&config.mesh_settings())
+        .await?;
     println!("{} P2P swarm started on {}", "[OK]".green(), listen_addr);
 
+    // Web context for landing page + API
+    let web_ctx = Arc::new(server::WebContext {
+        node_peer_id: local_peer_id.to_string(),
+        node_public_key: String::new(),
+        bootstrap_nodes: all_bootstrap.clone(),
+        ledger: ledger.clone(),
+        peers: peers.clone(),
+        start_time: std::time::Instant::now(),
+        transport_bridge: transport_bridge.clone(),
+        ui_port: http_port,
+        core: Some(Arc::clone(&core)),
+        relay_server: Some(swarm_handle.relay_server()),
+    });
+
+    // Start HTTP server (landing page + WebSocket)
+    let (ui_broadcast, _ui_cmd_rx) = server::start(http_port, web_ctx.clone()).await?;
+    println!(
+        "{} HTTP server started on port {}",
+        "[OK]".green(),
+        http_port
+    );
+
     // Subscribe to topics
     for topic in known_topics {
         let _ = swarm_handle.subscribe_topic(topic).await;
@@ -2700,6 +3334,9 @@ async fn cmd_relay(
     let api_ctx = api::ApiContext {
         core: core_arc.clone(),
         swarm_handle: Arc::new(swarm_handle.clone()),
+        peers: Arc::clone(&peers),
+        ledger: Arc::clone(&ledger),
+        start_time: std::time::Instant::now(),
     };
     let http_bind_api = http_bind.clone();
     tokio::spawn(async move {
@@ -2710,7 +3347,7 @@ async fn cmd_relay(
     println!(
         "{} Control API: {}",
         "[OK]".green(),
-        format!("http://127.0.0.1:{}", api::API_PORT).dimmed()
+        format!("http://{}", api::api_addr()).dimmed()
     );
 
     if config.enable_ble {
@@ -2739,7 +3376,7 @@ async fn cmd_relay(
         tokio::spawn(async move {
             let addrs = {
                 let l = ledger_clone.lock().await;
-                l.dialable_addresses(Some(&local_peer_id.to_string()))
+                l.dialable_addresses_for_mode(Some(&local_peer_id.to_string()), discovery_mode)
             };
             let my_addrs: Vec<String> = swarm_clone
                 .get_bound_addresses()
@@ -2777,7 +3414,7 @@ async fn cmd_relay(
                 tokio::time::sleep(tokio::time::Duration::from_secs(120)).await;
                 let addrs = {
                     let l = ledger_clone.lock().await;
-                    l.dialable_addresses(Some(&local_peer_id.to_string()))
+                    l.dialable_addresses_for_mode(Some(&local_peer_id.to_string()), discovery_mode)
                 };
                 let my_addrs: Vec<String> = swarm_clone
                     .get_bound_addresses()
@@ -2817,13 +3454,18 @@ async fn cmd_relay(
         }
     });
 
-    // ── Periodic ledger save (every 60 seconds) ─────────────────────────
+    // ── Periodic ledger prune + save (every 60 seconds) ─────────────────
     let ledger_save = ledger.clone();
     let data_dir_save = data_dir.clone();
+    let ledger_limits = (
+        config.network.ledger_max_entries,
+        config.network.ledger_max_age_secs,
+    );
     tokio::spawn(async move {
         loop {
             tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
             let mut l = ledger_save.lock().await;
+            l.prune(ledger_limits.0, ledger_limits.1);
             if let Err(e) = l.save(&data_dir_save) {
                 tracing::error!("Failed to save ledger: {}", e);
             }
@@ -2937,12 +3579,6 @@ async fn cmd_relay(
                             if let Err(e) = l.save(&data_dir) {
                                 tracing::error!("Failed to save ledger: {}", e);
                             }
-                            let new_entries: Vec<(String, Option<String>)> = entries
-                                .iter()
-                                .map(|e| {
-                                    (ledger::strip_peer_id(&e.multiaddr), e.last_peer_id.clone())
-                                })
-                                .collect();
                             drop(l);
 
                             // Graceful-AF dial policy: know our own addresses before
@@ -2957,19 +3593,9 @@ async fn cmd_relay(
                                 .map(|a| a.to_string())
                                 .collect();
 
-                            for (addr_str, peer_id_opt) in new_entries {
-                                // Skip non-routable addresses (loopback, link-local,
-                                // site-local) a peer may advertise -- dialing them
-                                // fails forever and storms the request_response handler.
-                                if !ledger::is_dialable_multiaddr(&addr_str, ledger::NetworkMode::Local) {
-                                    continue;
-                                }
-                                if !ledger::is_dialable_for_this_node(&addr_str, ledger::NetworkMode::Local, &my_addrs) {
-                                    continue;
-                                }
-                                let peer_id = peer_id_opt
-                                    .as_ref()
-                                    .and_then(|s| s.parse::<PeerId>().ok());
+                            for (addr_str, peer_id) in
+                                ledger::learned_dial_targets(&entries, &my_addrs, discovery_mode)
+                            {
                                 // Fire-and-forget: scheduler spawns the actual dial
                                 // so this shared event loop never blocks on a
                                 // connection/timeout.
@@ -2977,7 +3603,12 @@ async fn cmd_relay(
                             }
                         }
                     }
-                    SwarmEvent::PeerIdentified { peer_id, listen_addrs, .. } => {
+                    SwarmEvent::PeerIdentified { peer_id, agent_version, listen_addrs, .. } => {
+                        core_arc.notify_peer_identified(
+                            peer_id.to_string(),
+                            agent_version,
+                            listen_addrs.iter().map(|a| a.to_string()).collect(),
+                        );
                         let mut l = ledger_rx.lock().await;
                         for addr in &listen_addrs {
                             l.record_connection(&addr.to_string(), &peer_id.to_string());
@@ -2992,40 +3623,47 @@ async fn cmd_relay(
                         }
                     }
                     SwarmEvent::MessageReceived { peer_id, envelope_data } => {
-                        // In relay mode, we automatically peel and forward onion layers
-                        if let Ok(msg) = core_arc.receive_message(envelope_data.clone()) {
-                            if msg.message_type == scmessenger_core::MessageType::OnionRelay {
-                                let next_hop_hex = msg.recipient_id.clone();
-                                let payload = msg.payload.clone();
-
-                                if let Ok(next_hop_bytes) = hex::decode(&next_hop_hex) {
-                                    if let Ok(libp2p_kp) = libp2p::identity::ed25519::Keypair::try_from_bytes(&mut next_hop_bytes[..32].to_vec()) {
-                                        let next_peer_id = libp2p::PeerId::from_public_key(&libp2p::identity::PublicKey::from(libp2p_kp.public()));
-
-                                        tracing::info!("Relay node: forwarding onion packet to {}", next_peer_id);
-                                        let swarm_clone = swarm_handle.clone();
-                                        tokio::spawn(async move {
-                                            let _ = swarm_clone.send_message(next_peer_id, payload, None, None).await;
-                                        });
-                                    }
+                        // Onion layers wrapped for this relay are peeled and
+                        // forwarded to the next hop.
+                        if let Ok((next_hop_hex, payload)) = core_arc.peel_onion_relay(envelope_data.clone()) {
+                            match scmessenger_core::identity::peer_id_from_public_key_hex(&next_hop_hex)
+                                .ok()
+                                .and_then(|id| id.parse::<libp2p::PeerId>().ok())
+                            {
+                                Some(next_peer_id) => {
+                                    tracing::info!("Relay node: forwarding onion packet to {}", next_peer_id);
+                                    let swarm_clone = swarm_handle.clone();
+                                    tokio::spawn(async move {
+                                        let _ = swarm_clone.send_message(next_peer_id, payload, None, None).await;
+                                    });
                                 }
+                                None => tracing::debug!("Onion layer from {} names an invalid next hop", peer_id),
+                            }
+                        } else {
+                            // Relay-only core: other payloads are never decrypted
+                            // or stored here, only the cleartext header is read.
+                            match core_arc.relay_envelope_info(envelope_data) {
+                                Ok(info) => tracing::debug!(
+                                    "Relayed envelope from {} sender={} bytes={} v{}",
+                                    peer_id,
+                                    &info.sender_public_key_hex[..info.sender_public_key_hex.len().min(12)],
+                                    info.size_bytes,
+                                    info.wire_version
+                                ),
+                                Err(e) => tracing::debug!(
+                                    "Undecodable envelope from {}: {}",
+                                    peer_id,
+                                    e
+                                ),
                             }
-                        }
-
-                        // Also log standard envelopes for debugging
-                        if let Ok(env) = decode_envelope(&envelope_data) {
-                            let sender_key = hex::encode(&env.sender_public_key);
-                            tracing::debug!(
-                                "Relayed envelope from {} sender={} bytes={}",
-                                peer_id,
-                                &sender_key[..sender_key.len().min(12)],
-                                envelope_data.len()
-                            );
                         }
                     }
                     SwarmEvent::ListeningOn(addr) => {
                         tracing::info!("Listening on {}", addr);
                     }
+                    SwarmEvent::ListenAddrExpired(addr) => {
+                        tracing::info!("No longer listening on {}", addr);
+                    }
                     _ => {}
                 }
             }
@@ -3065,7 +3703,7 @@ async fn cmd_send_offline(recipient: String, message: String) -> Result<()> {
 
     let contacts = core.contacts_store_manager();
 
-    let contact = find_contact(&contacts, &recipient).context("Contact not found")?;
+    let contact = find_contact(&contacts, &recipient)?;
 
     // Get the network keypair from the core
     let network_keypair = core
@@ -3100,20 +3738,20 @@ async fn cmd_send_offline(recipient: String, message: String) -> Result<()> {
         Err(e) => {
             // If swarm startup fails, fall back to queuing
             tracing::warn!("Failed to start swarm: {}, falling back to queue", e);
-            return queue_message_for_later_delivery(&data_dir, &contact, &message).await;
+            return queue_message_for_later_delivery(&data_dir, &contact, &message, None).await;
         }
     };
 
     // Prepare the message envelope
-    let envelope_bytes = core
+    let prepared = core
         .prepare_message(
             contact.public_key.clone(),
             message.clone(),
             scmessenger_core::MessageType::Text,
             None,
         )
-        .map(|pm| pm.envelope_data)
         .context("Failed to encrypt message")?;
+    let envelope_bytes = prepared.envelope_data.clone();
 
     println!(
         "{} Message encrypted: {} bytes",
@@ -3143,6 +3781,11 @@ async fn cmd_send_offline(recipient: String, message: String) -> Result<()> {
             .await
         {
             Ok(_) => {
+                let _ = core.record_delivery_attempt(
+                    prepared.message_id.clone(),
+                    "direct".to_string(),
+                    None,
+                );
                 println!(
                     "{} Message sent successfully to {} (attempt {}/{})",
                     "[OK]".green(),
@@ -3154,6 +3797,11 @@ async fn cmd_send_offline(recipient: String, message: String) -> Result<()> {
             }
             Err(e) => {
                 last_error = Some(format!("{}", e));
+                let _ = core.record_delivery_attempt(
+                    prepared.message_id.clone(),
+                    "direct".to_string(),
+                    last_error.clone(),
+                );
                 tracing::warn!(
                     "Send attempt {}/{} failed: {}",
                     attempts,
@@ -3180,27 +3828,114 @@ async fn cmd_send_offline(recipient: String, message: String) -> Result<()> {
         "[WARN]".yellow(),
         last_error.unwrap_or("unknown error".to_string())
     );
-    queue_message_for_later_delivery(&data_dir, &contact, &message).await
+    queue_message_for_later_delivery(&data_dir, &contact, &message, Some(prepared)).await
+}
+
+/// Hand queued messages for recipients that are not connected to a newly
+/// identified relay. Messages the relay accepts leave the outbox; the rest
+/// are re-enqueued for the next direct connection or relay.
+async fn flush_outbox_via_relay(
+    swarm_handle: transport::SwarmHandle,
+    outbox: Arc<tokio::sync::Mutex<Outbox>>,
+    relay_peer: PeerId,
+    connected: Vec<PeerId>,
+) {
+    // Entries are keyed by Peer ID or, for contacts added by key, by their
+    // public key hex; both name the same libp2p peer.
+    let recipients: Vec<(String, PeerId)> = outbox
+        .lock()
+        .await
+        .pending_recipients()
+        .into_iter()
+        .filter_map(|key| {
+            let peer = key.parse::<PeerId>().ok().or_else(|| {
+                scmessenger_core::identity::peer_id_from_public_key_hex(&key)
+                    .ok()?
+                    .parse()
+                    .ok()
+            })?;
+            Some((key, peer))
+        })
+        .filter(|(_, r)| *r != relay_peer && !connected.contains(r))
+        .collect();
+
+    for (key, recipient) in recipients {
+        let queued = outbox.lock().await.drain_for_peer(&key);
+        if queued.is_empty() {
+            continue;
+        }
+        tracing::info!(
+            "Relaying {} queued message(s) for {} via {}",
+            queued.len(),
+            recipient,
+            relay_peer
+        );
+        for msg in queued {
+            let msg_id = msg.message_id.clone();
+            match swarm_handle
+                .send_via_relay(relay_peer, recipient, msg.envelope_data.clone())
+                .await
+            {
+                Ok(()) => {
+                    tracing::info!("Relay {} accepted queued message {}", relay_peer, msg_id);
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Relay {} did not take queued message {}: {} — re-enqueuing",
+                        relay_peer,
+                        msg_id,
+                        e
+                    );
+                    if let Err(eq_err) = outbox.lock().await.enqueue(msg) {
+                        tracing::error!("Failed to re-enqueue message {}: {}", msg_id, eq_err);
+                    }
+                }
+            }
+        }
+    }
 }
 
-/// Queue a message in the outbox for later delivery.
-/// Used when the swarm send fails or the API is unavailable.
+/// Public key the running node's connection to a peer proves, or `None` if
+/// that peer is not connected and identified right now. The peer is
+/// `peer_id` if it is a libp2p Peer ID, otherwise the one derived from
+/// `public_key_hex`.
+async fn confirm_pubkey_via_api(peer_id: &str, public_key_hex: &str) -> Option<String> {
+    let peer_id = if looks_like_libp2p_peer_id(peer_id) {
+        peer_id.parse::<libp2p::PeerId>().ok()?
+    } else {
+        let bytes = hex::decode(public_key_hex).ok()?;
+        let ed25519 = libp2p::identity::ed25519::PublicKey::try_from_bytes(&bytes).ok()?;
+        libp2p::PeerId::from_public_key(&ed25519.into())
+    };
+    api::get_peer_detail_via_api(&peer_id.to_string())
+        .await
+        .ok()?
+        .public_key
+}
+
+/// Queue a message in the outbox for later delivery, when the swarm send
+/// fails or the API is unavailable. `prepared` is the envelope from a failed
+/// direct send, queued under the same message ID so its delivery report
+/// carries on; without one the message is prepared here.
 async fn queue_message_for_later_delivery(
     data_dir: &std::path::Path,
     contact: &Contact,
     message: &str,
+    prepared: Option<scmessenger_core::PreparedMessage>,
 ) -> Result<()> {
-    let storage_path = data_dir.join("storage");
-    let core = IronCore::with_storage(path_to_string(&storage_path)?);
-
-    let envelope_bytes = core
-        .prepare_message(
-            contact.public_key.clone(),
-            message.to_string(),
-            scmessenger_core::MessageType::Text,
-            None,
-        )
-        .map(|pm| pm.envelope_data)?;
+    let prepared = match prepared {
+        Some(prepared) => prepared,
+        None => {
+            let storage_path = data_dir.join("storage");
+            let core = IronCore::with_storage(path_to_string(&storage_path)?);
+            core.prepare_message(
+                contact.public_key.clone(),
+                message.to_string(),
+                scmessenger_core::MessageType::Text,
+                None,
+            )?
+        }
+    };
 
     match Outbox::open_default(data_dir) {
         Ok(outbox_arc) => {
@@ -3211,9 +3946,9 @@ async fn queue_message_for_later_delivery(
                 .as_secs();
             let queued_msg = QueuedMessage {
                 version: 1,
-                message_id: uuid::Uuid::new_v4().to_string(),
+                message_id: prepared.message_id,
                 recipient_id: contact.peer_id.clone(),
-                envelope_data: envelope_bytes,
+                envelope_data: prepared.envelope_data,
                 queued_at: now,
                 attempts: 0,
                 next_retry_at: None,
@@ -3313,6 +4048,167 @@ async fn cmd_discovery(action: DiscoveryAction) -> Result<()> {
     Ok(())
 }
 
+async fn cmd_peer(action: PeerAction) -> Result<()> {
+    match action {
+        PeerAction::Discover => {
+            if !api::is_api_available().await {
+                anyhow::bail!("No running node found. Start one with `scm start` first.");
+            }
+            let result = api::request_peers_via_api().await?;
+            println!(
+                "{} Asked {} connected peer(s), {} answered",
+                "[OK]".green(),
+                result.queried,
+                result.responded
+            );
+            if result.peers.is_empty() {
+                println!("  {}", "No new peers reported.".dimmed());
+            }
+            for peer in &result.peers {
+                println!(
+                    "  • {} via {}",
+                    peer.peer_id.bright_cyan(),
+                    peer.source.dimmed()
+                );
+                for addr in &peer.addresses {
+                    println!("    {}", addr);
+                }
+            }
+            for (peer_id, error) in &result.errors {
+                println!("{} {}: {}", "[WARN]".yellow(), peer_id.dimmed(), error);
+            }
+        }
+        PeerAction::Show { peer_id } => {
+            if !api::is_api_available().await {
+                anyhow::bail!("No running node found. Start one with `scm start` first.");
+            }
+            let detail = api::get_peer_detail_via_api(&peer_id).await?;
+            println!("{} {}", "Peer".bold(), detail.peer_id.bright_cyan());
+            println!(
+                "  Connected:    {}",
+                if detail.connected {
+                    "yes".green()
+                } else {
+                    "no".yellow()
+                }
+            );
+            match detail.rtt_ms {
+                Some(ms) => println!("  RTT:          {}ms", ms),
+                None => println!("  RTT:          {}", "unknown".dimmed()),
+            }
+            match detail.capabilities {
+                Some(caps) => {
+                    println!("  Messages:     {}", caps.message_types.join(", "));
+                    if caps.features.is_empty() {
+                        println!("  Features:     {}", "none advertised".dimmed());
+                    } else {
+                        println!("  Features:     {}", caps.features.join(", "));
+                    }
+                }
+                None => println!(
+                    "  Capabilities: {}",
+                    "unknown (peer not identified yet)".dimmed()
+                ),
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn cmd_bootstrap(action: BootstrapAction) -> Result<()> {
+    match action {
+        BootstrapAction::Check { multiaddr, timeout } => {
+            cmd_bootstrap_check(multiaddr, timeout).await
+        }
+    }
+}
+
+async fn cmd_bootstrap_check(multiaddr: Option<String>, timeout_secs: u64) -> Result<()> {
+    let targets = match multiaddr {
+        Some(addr) => vec![addr],
+        None => {
+            let config = config::Config::load()?;
+            bootstrap::merge_bootstrap_nodes(config.bootstrap_nodes.clone())
+        }
+    };
+
+    println!("{}", "Bootstrap Health Check".bold());
+    if targets.is_empty() {
+        println!("  {}", "No bootstrap nodes configured.".yellow());
+        return Ok(());
+    }
+
+    // Probe from a throwaway key so the check never reuses (or reveals) the
+    // node's own PeerId, and never disturbs a running node's connections.
+    let keypair = libp2p::identity::Keypair::generate_ed25519();
+    let (event_tx, mut event_rx) = tokio::sync::mpsc::channel(256);
+    let swarm_handle = transport::start_swarm_with_config(
+        keypair,
+        None,
+        event_tx,
+        None,
+        Vec::new(),
+        None,
+        None,
+        true,
+        Some(scmessenger_core::transport::DiscoveryConfig::new(
+            scmessenger_core::transport::DiscoveryMode::Manual,
+        )),
+        transport::default_routing_engine_handle(),
+        None,
+        None,
+    )
+    .await?;
+    // Drain swarm events so the event loop never stalls on a full channel.
+    tokio::spawn(async move { while event_rx.recv().await.is_some() {} });
+
+    let timeout = std::time::Duration::from_secs(timeout_secs);
+    let mut reachable = 0usize;
+    for target in &targets {
+        let addr: Multiaddr = match target.parse() {
+            Ok(addr) => addr,
+            Err(e) => {
+                println!("  {} {} (invalid multiaddr: {})", "[FAIL]".red(), target, e);
+                continue;
+            }
+        };
+        match swarm_handle.probe_peer(addr, timeout).await {
+            Ok(result) if result.reachable => {
+                reachable += 1;
+                println!(
+                    "  {} {} {}ms {}",
+                    "[OK]".green(),
+                    target,
+                    result.latency_ms,
+                    result.agent_version.unwrap_or_default().dimmed()
+                );
+            }
+            Ok(result) => {
+                println!(
+                    "  {} {} {} ({}ms)",
+                    "[FAIL]".red(),
+                    target,
+                    result.error.unwrap_or_else(|| "unreachable".to_string()),
+                    result.latency_ms
+                );
+            }
+            Err(e) => {
+                println!("  {} {} {}", "[FAIL]".red(), target, e);
+            }
+        }
+    }
+
+    let _ = swarm_handle.shutdown().await;
+    println!();
+    println!(
+        "{} {}/{} bootstrap node(s) reachable",
+        "ℹ".dimmed(),
+        reachable,
+        targets.len()
+    );
+    Ok(())
+}
+
 async fn cmd_status() -> Result<()> {
     let data_dir = config::Config::data_dir()?;
     let storage_path = data_dir.join("storage");
@@ -3356,16 +4252,32 @@ async fn cmd_status() -> Result<()> {
                     } else {
                         "yellow"
                     };
+                    let rtt = peer
+                        .rtt_ms
+                        .map(|ms| format!("{} ms", ms))
+                        .unwrap_or_else(|| "-".to_string());
                     println!(
-                        "  - {} (reputation: {})",
+                        "  - {} (reputation: {}, rtt: {})",
                         peer.peer_id.dimmed(),
-                        format!("{:.1}", peer.reputation).color(rep_color)
+                        format!("{:.1}", peer.reputation).color(rep_color),
+                        rtt
                     );
                 }
             }
             Err(e) => println!("Peers: {} ({})", "unavailable".yellow(), e),
         }
 
+        match api::get_status_via_api().await {
+            Ok(status) => println!(
+                "Topics: {}  Ledger: {}  Outbox: {}  Inbox: {}",
+                status.subscribed_topics,
+                status.ledger_entries,
+                status.outbox_count,
+                status.inbox_count
+            ),
+            Err(e) => println!("Gauges: {} ({})", "unavailable".yellow(), e),
+        }
+
         match api::get_listeners_via_api().await {
             Ok(listeners) => println!("Listeners: {}", listeners.len()),
             Err(e) => println!("Listeners: {} ({})", "unavailable".yellow(), e),
@@ -3385,6 +4297,30 @@ async fn cmd_status() -> Result<()> {
             Err(e) => println!("External Addresses: {} ({})", "unavailable".yellow(), e),
         }
 
+        match api::get_relay_reservations_via_api().await {
+            Ok(reservations) => {
+                if reservations.is_empty() {
+                    println!("Relay Reservations: {}", "(none)".dimmed());
+                } else {
+                    println!("Relay Reservations:");
+                    for r in reservations {
+                        let state = if r.accepted {
+                            "accepted".green()
+                        } else {
+                            "requested".yellow()
+                        };
+                        println!(
+                            "  - {} via {} ({})",
+                            r.relay_peer_id.dimmed(),
+                            r.relay_addr.as_deref().unwrap_or("unknown address"),
+                            state
+                        );
+                    }
+                }
+            }
+            Err(e) => println!("Relay Reservations: {} ({})", "unavailable".yellow(), e),
+        }
+
         match api::get_connection_path_state_via_api().await {
             Ok(state) => println!("Connection Path State: {}", state.bright_cyan()),
             Err(e) => println!("Connection Path State: {} ({})", "unavailable".yellow(), e),
@@ -3416,6 +4352,131 @@ async fn cmd_status() -> Result<()> {
     Ok(())
 }
 
+async fn cmd_diag() -> Result<()> {
+    // Prefer the running node's snapshot: it can see live peers. Otherwise
+    // report what the on-disk core knows.
+    let diagnostics = if api::is_api_available().await {
+        api::export_diagnostics_via_api().await?
+    } else {
+        let data_dir = config::Config::data_dir()?;
+        let storage_path = data_dir.join("storage");
+        let core = IronCore::with_storage(path_to_string(&storage_path)?);
+        core.export_diagnostics(None, None)
+    };
+
+    let value: serde_json::Value =
+        serde_json::from_str(&diagnostics).context("Diagnostics output was not valid JSON")?;
+    println!("{}", serde_json::to_string_pretty(&value)?);
+    Ok(())
+}
+
+async fn cmd_support_bundle() -> Result<()> {
+    // The running node has the recent errors; a fresh core only has its own.
+    let bundle = if api::is_api_available().await {
+        api::export_support_bundle_via_api().await?
+    } else {
+        let data_dir = config::Config::data_dir()?;
+        let storage_path = data_dir.join("storage");
+        let core = IronCore::with_storage(path_to_string(&storage_path)?);
+        core.export_support_bundle()
+    };
+
+    let value: serde_json::Value =
+        serde_json::from_str(&bundle).context("Support bundle was not valid JSON")?;
+    println!("{}", serde_json::to_string_pretty(&value)?);
+    Ok(())
+}
+
+async fn cmd_topic(action: TopicAction) -> Result<()> {
+    match action {
+        TopicAction::Publish { topic, data } => {
+            if !api::is_api_available().await {
+                anyhow::bail!("No running node found. Start one with `scm start` first.");
+            }
+            let result = api::publish_topic_via_api(&topic, &data).await?;
+            if result.peers == 0 {
+                println!(
+                    "{} Published to {}, but no peer subscribes to it",
+                    "[WARN]".yellow(),
+                    topic.bright_cyan()
+                );
+            } else {
+                println!(
+                    "{} Published to {} ({} peer(s))",
+                    "[OK]".green(),
+                    topic.bright_cyan(),
+                    result.peers
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn cmd_reconnect() -> Result<()> {
+    if !api::is_api_available().await {
+        anyhow::bail!("No running node found. Start one with `scm start` first.");
+    }
+    let result = api::reconnect_via_api().await?;
+    println!(
+        "{} Re-dialing {} bootstrap node(s) and {} known peer(s)",
+        "[OK]".green(),
+        result.bootstrap_dials,
+        result.ledger_dials
+    );
+    if result.bootstrap_dials + result.ledger_dials == 0 {
+        println!(
+            "  {}",
+            "Nothing to dial: peers are connected or backing off.".dimmed()
+        );
+    }
+    Ok(())
+}
+
+async fn cmd_maintenance(action: MaintenanceAction) -> Result<()> {
+    match action {
+        MaintenanceAction::Compact => {
+            // A running node holds the store open; let it compact in place.
+            let report = if api::is_api_available().await {
+                api::compact_storage_via_api().await?
+            } else {
+                let data_dir = config::Config::data_dir()?;
+                let storage_str = path_to_string(&data_dir.join("storage"))?;
+                // IronCore silently falls back to memory when the store is
+                // locked, which would "compact" nothing; check for that first.
+                drop(
+                    scmessenger_core::store::backend::SledStorage::new(&storage_str).map_err(
+                        |e| anyhow::anyhow!("Storage is in use ({}). Retry in a moment.", e),
+                    )?,
+                );
+                let core = IronCore::with_storage(storage_str);
+                let report = core.compact_storage()?;
+                api::CompactStorageResponse {
+                    bytes_before: report.bytes_before,
+                    bytes_after: report.bytes_after,
+                    records_rewritten: report.records_rewritten,
+                }
+            };
+            println!(
+                "{} Storage compacted: {} -> {} ({} records rewritten)",
+                "[OK]".green(),
+                ble_daemon::format_bytes(report.bytes_before),
+                ble_daemon::format_bytes(report.bytes_after),
+                report.records_rewritten
+            );
+            if report.bytes_after >= report.bytes_before {
+                println!(
+                    "{}",
+                    "Freed segments are returned to the OS as the store rolls over; \
+                     the on-disk size may keep shrinking over the next few minutes."
+                        .dimmed()
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
 async fn cmd_mark_sent(message_id: String) -> Result<()> {
     let data_dir = config::Config::data_dir()?;
     let storage_path = data_dir.join("storage");
@@ -3437,6 +4498,247 @@ async fn cmd_mark_sent(message_id: String) -> Result<()> {
     Ok(())
 }
 
+async fn cmd_outbox(action: OutboxAction) -> Result<()> {
+    match action {
+        OutboxAction::Cancel { contact } => {
+            let data_dir = config::Config::data_dir()?;
+            let storage_path = data_dir.join("storage");
+            let core = IronCore::with_storage(path_to_string(&storage_path)?);
+            let contact = find_contact(&core.contacts_store_manager(), &contact)?;
+
+            // Offline sends queue under the peer ID; core-prepared messages
+            // queue under the recipient public key.
+            let outbox_arc = Outbox::open_default(&data_dir).map_err(|e| anyhow::anyhow!(e))?;
+            let mut outbox = outbox_arc.lock().await;
+            let mut removed = outbox.drain_for_peer(&contact.peer_id).len();
+            if contact.public_key != contact.peer_id {
+                removed += outbox.drain_for_peer(&contact.public_key).len();
+            }
+
+            println!(
+                "{} Canceled {} queued message(s) for {}",
+                "[OK]".green(),
+                removed,
+                contact.display_name().bright_cyan()
+            );
+        }
+        OutboxAction::Report { message_id } => {
+            let data_dir = config::Config::data_dir()?;
+            let storage_path = data_dir.join("storage");
+            let core = IronCore::with_storage(path_to_string(&storage_path)?);
+            let report = core
+                .delivery_report(message_id.clone())
+                .with_context(|| format!("No delivery report for message {}", message_id))?;
+            let recipient = find_contact(&core.contacts_store_manager(), &report.recipient)
+                .map(|c| c.display_name().to_string())
+                .unwrap_or_else(|_| report.recipient.clone());
+
+            println!("{} {}", "Message:".bold(), report.message_id);
+            println!("  Recipient:    {}", recipient.bright_cyan());
+            println!("  Status:       {}", report.status);
+            println!("  Created:      {}", format_timestamp(report.created_at));
+            println!("  Attempts:     {}", report.attempts);
+            if let Some(at) = report.last_attempt_at {
+                println!("  Last attempt: {}", format_timestamp(at));
+            }
+            if !report.paths_tried.is_empty() {
+                println!("  Paths tried:  {}", report.paths_tried.join(", "));
+            }
+            if let Some(err) = report.last_error {
+                println!("  Last error:   {}", err.red());
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn cmd_draft(action: DraftAction) -> Result<()> {
+    let data_dir = config::Config::data_dir()?;
+    let storage_path = data_dir.join("storage");
+
+    match action {
+        DraftAction::Save { contact, text } => {
+            let core = IronCore::with_storage(path_to_string(&storage_path)?);
+            let contact = find_contact(&core.contacts_store_manager(), &contact)?;
+            core.save_draft(contact.public_key.clone(), text)
+                .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+            println!(
+                "{} Draft saved for {}",
+                "[OK]".green(),
+                contact.display_name().bright_cyan()
+            );
+        }
+        DraftAction::List => {
+            let core = IronCore::with_storage(path_to_string(&storage_path)?);
+            let drafts = core.list_drafts();
+            if drafts.is_empty() {
+                println!("{}", "No drafts.".dimmed());
+                return Ok(());
+            }
+            let contacts = core.contacts_store_manager();
+            for draft in drafts {
+                let name = find_contact(&contacts, &draft.recipient)
+                    .map(|c| c.display_name().to_string())
+                    .unwrap_or_else(|_| draft.recipient.clone());
+                println!(
+                    "{} {} {}",
+                    format_timestamp(draft.updated_at).dimmed(),
+                    name.bright_cyan(),
+                    draft.text
+                );
+            }
+        }
+        DraftAction::Send { contact } => {
+            // The storage handle is released before sending, since the send
+            // path opens its own.
+            let (public_key, text) = {
+                let core = IronCore::with_storage(path_to_string(&storage_path)?);
+                let contact = find_contact(&core.contacts_store_manager(), &contact)?;
+                let draft = core
+                    .get_draft(contact.public_key.clone())
+                    .with_context(|| format!("No draft for {}", contact.display_name()))?;
+                (contact.public_key, draft.text)
+            };
+            cmd_send_offline(public_key.clone(), text).await?;
+
+            // Only dropped once the send went through; a failed send keeps it.
+            let core = IronCore::with_storage(path_to_string(&storage_path)?);
+            core.delete_draft(public_key)
+                .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Read a whole file, or stdin when `path` is "-".
+fn read_input(path: &str) -> Result<Vec<u8>> {
+    use std::io::Read;
+    let mut data = Vec::new();
+    if path == "-" {
+        std::io::stdin()
+            .read_to_end(&mut data)
+            .context("Failed to read stdin")?;
+    } else {
+        data = std::fs::read(path).with_context(|| format!("Failed to read {}", path))?;
+    }
+    Ok(data)
+}
+
+/// Write a whole file, or stdout when `path` is "-".
+fn write_output(path: &str, data: &[u8]) -> Result<()> {
+    use std::io::Write;
+    if path == "-" {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(data).context("Failed to write stdout")?;
+        stdout.flush()?;
+    } else {
+        std::fs::write(path, data).with_context(|| format!("Failed to write {}", path))?;
+    }
+    Ok(())
+}
+
+/// Open the local identity for file encryption/decryption.
+fn load_identity_keys() -> Result<(IronCore, scmessenger_core::identity::IdentityKeys)> {
+    let data_dir = config::Config::data_dir()?;
+    let core = IronCore::with_storage(path_to_string(&data_dir.join("storage"))?);
+    core.initialize_identity()
+        .context("Failed to load identity")?;
+    let keys = core
+        .get_identity_keys()
+        .context("No identity; run `scm init` first")?;
+    Ok((core, keys))
+}
+
+// Status lines go to stderr so "-" output can be piped.
+fn cmd_encrypt(to: String, infile: String, outfile: String) -> Result<()> {
+    let (core, keys) = load_identity_keys()?;
+    let contact = find_contact(&core.contacts_store_manager(), &to)?;
+    let recipient: [u8; 32] = hex::decode(&contact.public_key)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .context("Contact has an invalid public key")?;
+
+    let plaintext = read_input(&infile)?;
+    let sealed = scmessenger_core::crypto::seal_file(&keys.signing_key, &recipient, &plaintext)?;
+    write_output(&outfile, &sealed)?;
+    eprintln!(
+        "{} Encrypted {} bytes for {}",
+        "[OK]".green(),
+        plaintext.len(),
+        contact.display_name().bright_cyan()
+    );
+    Ok(())
+}
+
+fn cmd_decrypt(infile: String, outfile: String) -> Result<()> {
+    let (core, keys) = load_identity_keys()?;
+    let data = read_input(&infile)?;
+    let opened = scmessenger_core::crypto::open_file(&keys.signing_key, &data)
+        .with_context(|| format!("Cannot decrypt {}", infile))?;
+    write_output(&outfile, &opened.plaintext)?;
+
+    let sender_hex = hex::encode(opened.sender_public_key);
+    let sender = find_contact(&core.contacts_store_manager(), &sender_hex)
+        .map(|c| c.display_name().to_string())
+        .unwrap_or_else(|_| format!("{} (not a contact)", sender_hex));
+    eprintln!(
+        "{} Decrypted {} bytes, signed by {}",
+        "[OK]".green(),
+        opened.plaintext.len(),
+        sender.bright_cyan()
+    );
+    Ok(())
+}
+
+fn cmd_inspect(envelope_file: String) -> Result<()> {
+    let data = read_input(&envelope_file)?;
+    let data_dir = config::Config::data_dir()?;
+    let core = IronCore::with_storage(path_to_string(&data_dir.join("storage"))?);
+    let contacts = core.contacts_store_manager();
+
+    let sender = core
+        .envelope_sender(data.clone())
+        .context("Not a valid envelope")?;
+    let hint = core.envelope_recipient_hint(data).unwrap_or_default();
+    let sender_name = find_contact(&contacts, &sender)
+        .map(|c| c.display_name().to_string())
+        .unwrap_or_else(|_| "not a contact".to_string());
+
+    println!("{}", "Envelope".bold());
+    println!("  Sender:    {} ({})", sender.bright_cyan(), sender_name);
+    if hint.len() == 8 {
+        let hint_of = |public_key_hex: &str| -> Option<String> {
+            let bytes: [u8; 32] = hex::decode(public_key_hex).ok()?.try_into().ok()?;
+            Some(hex::encode(
+                scmessenger_core::drift::DriftEnvelope::hint_from_public_key(&bytes),
+            ))
+        };
+        let recipient = if core
+            .get_identity_info()
+            .public_key_hex
+            .and_then(|pk| hint_of(&pk))
+            .as_deref()
+            == Some(hint.as_str())
+        {
+            "this identity".to_string()
+        } else {
+            contacts
+                .list()
+                .unwrap_or_default()
+                .into_iter()
+                .find(|c| hint_of(&c.public_key).as_deref() == Some(hint.as_str()))
+                .map(|c| c.display_name().to_string())
+                .unwrap_or_else(|| "unknown key".to_string())
+        };
+        println!("  Recipient: hint {} ({})", hint.bright_yellow(), recipient);
+    } else {
+        // Legacy envelopes carry no recipient hint; only the sealed
+        // ephemeral key is visible.
+        println!("  Recipient: sealed (ephemeral key {})", hint.dimmed());
+    }
+    Ok(())
+}
+
 async fn cmd_history_clear(yes: bool) -> Result<()> {
     if !yes {
         anyhow::bail!("Refusing destructive clear without --yes");
@@ -3783,6 +5085,90 @@ async fn cmd_test() -> Result<()> {
     Ok(())
 }
 
+fn cmd_bench(messages: u32, size: usize) -> Result<()> {
+    println!(
+        "{} {} iterations, {}-byte payloads, two in-memory identities",
+        "Benchmarking:".bold(),
+        messages,
+        size
+    );
+    println!();
+
+    let results = bench::run_bench(messages, size)?;
+
+    println!(
+        "  {:<8} {:>12} {:>10} {:>10} {:>10} {:>10}",
+        "op", "ops/sec", "MB/sec", "p50", "p95", "p99"
+    );
+    for result in &results {
+        println!(
+            "  {:<8} {:>12.1} {:>10.2} {:>10} {:>10} {:>10}",
+            result.name,
+            result.ops_per_sec(),
+            result.mb_per_sec(),
+            format!("{:.1?}", result.percentile(50.0)),
+            format!("{:.1?}", result.percentile(95.0)),
+            format!("{:.1?}", result.percentile(99.0))
+        );
+    }
+    println!();
+    println!(
+        "{}",
+        "encrypt/decrypt run the full prepare_message/receive_message path.".dimmed()
+    );
+    Ok(())
+}
+
+/// Panic wipe: overwrite-then-delete every stored record and file. Unlike the
+/// UI's factory reset (a plain `remove_dir_all`), contents are overwritten
+/// with random bytes first.
+fn cmd_wipe(confirm: bool) -> Result<()> {
+    if !confirm {
+        anyhow::bail!(
+            "Refusing to wipe without --confirm. This permanently erases your identity, contacts and messages."
+        );
+    }
+    let data_dir = config::Config::data_dir()?;
+    let storage_path = data_dir.join("storage");
+
+    if storage_path.exists() {
+        // IronCore silently falls back to memory when the store is locked,
+        // which would leave the files untouched; check for that first.
+        let storage_str = path_to_string(&storage_path)?;
+        drop(
+            scmessenger_core::store::backend::SledStorage::new(&storage_str).map_err(|e| {
+                anyhow::anyhow!(
+                    "Storage is in use ({}). Stop any running `scm start`/`scm relay` and retry.",
+                    e
+                )
+            })?,
+        );
+        let core = IronCore::with_storage(storage_str);
+        core.panic_wipe()?;
+    }
+
+    let outbox_path = data_dir.join("outbox");
+    if outbox_path.exists() {
+        if let Ok(outbox) =
+            scmessenger_core::store::backend::SledStorage::new(&path_to_string(&outbox_path)?)
+        {
+            scmessenger_core::store::wipe::overwrite_backend(&outbox)
+                .map_err(|e| anyhow::anyhow!("Failed to erase outbox: {}", e))?;
+        }
+    }
+
+    scmessenger_core::store::wipe::shred_dir(&data_dir)
+        .with_context(|| format!("Failed to shred {}", data_dir.display()))?;
+
+    println!("{} Wiped {}", "[OK]".green(), data_dir.display());
+    println!(
+        "{}",
+        "Files were overwritten before deletion, but SSDs/flash and copy-on-write filesystems may keep older copies; only full-disk encryption guarantees they are unreadable."
+            .dimmed()
+    );
+    Ok(())
+}
+
 /// Returns true if `s` is exactly 64 hex characters — the shape of a
 /// Blake3 identity_id (32-byte hash → 64 hex chars).  A user who copies their
 /// `scm identity` "ID" field will get this format.
@@ -3816,17 +5202,58 @@ fn looks_like_libp2p_peer_id(s: &str) -> bool {
     s.parse::<libp2p::PeerId>().is_ok()
 }
 
+/// Resolve a contact by peer ID, public key or nickname. Exact IDs win;
+/// a nickname shared by several contacts is refused rather than guessed.
 fn find_contact(manager: &ContactManager, query: &str) -> Result<Contact> {
-    let list = manager.list().unwrap_or_default();
-    let query_lower = query.to_lowercase();
-
-    list.into_iter()
-        .find(|c| {
-            c.peer_id == query
-                || c.nickname.as_ref().map(|n| n.to_lowercase()).as_deref() == Some(&query_lower)
-                || c.public_key == query
-        })
-        .ok_or_else(|| anyhow::anyhow!("Contact not found: {}", query))
+    match manager.resolve(query)? {
+        ContactLookup::Found(contact) => Ok(*contact),
+        ContactLookup::NotFound => anyhow::bail!("Contact not found: {}", query),
+        ContactLookup::Ambiguous(n) => {
+            anyhow::bail!("ambiguous nickname, {} matches — specify pubkey", n)
+        }
+    }
+}
+
+#[cfg(test)]
+mod find_contact_tests {
+    use super::*;
+    use scmessenger_core::store::backend::MemoryStorage;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_shared_nickname_is_ambiguous() {
+        let manager = ContactManager::new(Arc::new(MemoryStorage::new()));
+        for (peer, key) in [("peer-a", "aa"), ("peer-b", "bb")] {
+            let mut contact = Contact::new(peer.to_string(), key.repeat(32));
+            contact.nickname = Some("alex".to_string());
+            manager.add(contact).unwrap();
+        }
+
+        let err = find_contact(&manager, "Alex").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ambiguous nickname, 2 matches — specify pubkey"
+        );
+        // The public key still picks one out unambiguously.
+        let by_key = find_contact(&manager, &"bb".repeat(32)).unwrap();
+        assert_eq!(by_key.peer_id, "peer-b");
+    }
+}
+
+#[cfg(test)]
+mod init_args_tests {
+    use super::*;
+
+    #[test]
+    fn test_max_attempts_is_capped() {
+        let parse = |attempts: &str| {
+            Cli::try_parse_from(["scm", "init", "--vanity", "ab", "--max-attempts", attempts])
+        };
+        assert!(parse(&VANITY_MAX_ATTEMPTS.to_string()).is_ok());
+        assert!(parse(&(VANITY_MAX_ATTEMPTS + 1).to_string()).is_err());
+        assert!(parse(&u64::MAX.to_string()).is_err());
+        assert!(parse("0").is_err());
+    }
 }
 
 fn format_timestamp(timestamp: u64) -> String {
@@ -3911,9 +5338,35 @@ async fn cmd_audit(action: AuditAction) -> Result<()> {
 async fn cmd_swarm(action: SwarmAction) -> Result<()> {
     match action {
         SwarmAction::Stats => cmd_swarm_stats().await,
+        SwarmAction::Nat => cmd_swarm_nat().await,
     }
 }
 
+async fn cmd_swarm_nat() -> Result<()> {
+    if !api::is_api_available().await {
+        anyhow::bail!("No running node found. Start one with `scm start` first.");
+    }
+    let status = api::get_nat_status_via_api().await?;
+    let on_off = |enabled: bool| {
+        if enabled {
+            "on".green()
+        } else {
+            "off".dimmed()
+        }
+    };
+    println!("{}", "NAT Traversal".bold());
+    println!("  NAT type:      {}", status.nat_type.bright_cyan());
+    println!("  Hole punching: {}", on_off(status.hole_punch_enabled));
+    println!("  UPnP:          {}", on_off(status.upnp_enabled));
+    if status.hole_punches.is_empty() {
+        println!("  {}", "No hole punches attempted yet.".dimmed());
+    }
+    for (peer_id, outcome) in &status.hole_punches {
+        println!("  • {} {}", peer_id.dimmed(), outcome);
+    }
+    Ok(())
+}
+
 async fn cmd_swarm_stats() -> Result<()> {
     println!("{}", "SCMessenger Swarm Connection Stats".bold());
     println!();
@@ -126,6 +126,8 @@ pub struct WebContext {
     /// (contacts, settings, history, blocking). None when core is not
     /// available (e.g. bootstrap-only CLI modes).
     pub core: Option<Arc<scmessenger_core::IronCore>>,
+    /// Relay statistics served at `/relay/stats` (`scm relay` only).
+    pub relay_server: Option<scmessenger_core::relay::RelayServer>,
 }
 
 impl Clone for WebContext {
@@ -140,6 +142,7 @@ impl Clone for WebContext {
             transport_bridge: Arc::clone(&self.transport_bridge),
             ui_port: self.ui_port,
             core: self.core.clone(),
+            relay_server: self.relay_server.clone(),
         }
     }
 }
@@ -182,17 +185,44 @@ pub async fn start(
     // Serve a minimal landing page at GET /
     let ctx_landing = ctx.clone();
     let landing = warp::path::end().map(move || {
+        let relay = ctx_landing
+            .relay_server
+            .as_ref()
+            .map(|relay| {
+                let stats = relay.stats();
+                format!(
+                    "<p>Relay: {} bytes relayed, {} circuits active, {} unique peers served \
+                     (<a href=\"/relay/stats\">JSON</a>)</p>",
+                    stats.bytes_relayed, stats.circuits_active, stats.unique_peers_served
+                )
+            })
+            .unwrap_or_default();
         let body = format!(
             "<!DOCTYPE html><html><head><title>SCMessenger</title></head>\
              <body><h1>SCMessenger</h1><p>Node: {}</p><p>Public Key: {}</p>\
-             <p>Uptime: {:?}</p></body></html>",
+             <p>Uptime: {:?}</p>{}</body></html>",
             ctx_landing.node_peer_id,
             ctx_landing.node_public_key,
             ctx_landing.start_time.elapsed(),
+            relay,
         );
         warp::reply::html(body)
     });
 
+    // Relay statistics as JSON at GET /relay/stats (404 when not a relay)
+    let ctx_relay = ctx.clone();
+    let relay_stats_route = warp::path!("relay" / "stats")
+        .and(warp::get())
+        .and_then(move || {
+            let relay = ctx_relay.relay_server.clone();
+            async move {
+                match relay {
+                    Some(relay) => Ok(warp::reply::json(&relay.stats())),
+                    None => Err(warp::reject::not_found()),
+                }
+            }
+        });
+
     // WebSocket route at GET /ws — upgrades to JSON-RPC bridge
     let ws_senders_filter = ws_senders.clone();
     let ctx_ws = ctx.clone();
@@ -216,7 +246,11 @@ pub async fn start(
     // Static route for /wasm — serves built WASM assets used by the UI
     let wasm_route = warp::path("wasm").and(warp::fs::dir("wasm"));
 
-    let routes = landing.or(ws_route).or(ui_route).or(wasm_route);
+    let routes = landing
+        .or(relay_stats_route)
+        .or(ws_route)
+        .or(ui_route)
+        .or(wasm_route);
 
     // Bind and serve on 127.0.0.1 only (local bridge, never exposed to network).
     let bound_addr: std::net::SocketAddr = ([127, 0, 0, 1], port).into();
@@ -525,7 +559,19 @@ pub async fn handle_jsonrpc_request(
                 let mut contact =
                     scmessenger_core::store::Contact::new(peer_id.clone(), String::new());
                 if let Some(n) = nickname {
-                    contact = contact.with_nickname(n);
+                    contact = match contact.with_nickname(n) {
+                        Ok(contact) => contact,
+                        Err(e) => {
+                            return rpc_error(
+                                id,
+                                JsonRpcErrorBody {
+                                    code: -32000,
+                                    message: format!("Invalid nickname: {}", e),
+                                    data: None,
+                                },
+                            )
+                        }
+                    };
                 }
                 match mgr.add(contact) {
                     Ok(()) => {
@@ -0,0 +1,70 @@
+// Two nodes on one host: separate data directories must give fully
+// isolated cores (identity, drafts, delivery state), even when opened
+// concurrently.
+
+use scmessenger_cli::config::Config;
+use scmessenger_core::IronCore;
+use std::path::{Path, PathBuf};
+
+fn open_core(data_dir: &Path) -> IronCore {
+    let storage = data_dir.join("storage");
+    IronCore::with_storage(storage.to_string_lossy().into_owned())
+}
+
+fn start_node(data_dir: PathBuf) -> (IronCore, String) {
+    let core = open_core(&data_dir);
+    core.grant_consent();
+    core.initialize_identity().unwrap();
+    let public_key = core.get_identity_info().public_key_hex.unwrap();
+    (core, public_key)
+}
+
+#[test]
+fn two_cores_in_separate_data_dirs_run_concurrently() {
+    let dir_a = tempfile::tempdir().unwrap();
+    let dir_b = tempfile::tempdir().unwrap();
+
+    let a = std::thread::spawn({
+        let path = dir_a.path().to_path_buf();
+        move || start_node(path)
+    });
+    let b = std::thread::spawn({
+        let path = dir_b.path().to_path_buf();
+        move || start_node(path)
+    });
+    let (core_a, pk_a) = a.join().unwrap();
+    let (core_b, pk_b) = b.join().unwrap();
+    assert_ne!(pk_a, pk_b);
+
+    // Both stores stay open at once; state written to one never shows up
+    // in the other.
+    core_a.save_draft(pk_b.clone(), "from a".into()).unwrap();
+    let prepared = core_b
+        .prepare_message(
+            pk_a.clone(),
+            "from b".into(),
+            scmessenger_core::MessageType::Text,
+            None,
+        )
+        .unwrap();
+    assert_eq!(core_a.list_drafts().len(), 1);
+    assert!(core_b.list_drafts().is_empty());
+    assert!(core_b
+        .delivery_report(prepared.message_id.clone())
+        .is_some());
+    assert!(core_a
+        .delivery_report(prepared.message_id.clone())
+        .is_none());
+
+    let received = core_a.receive_message(prepared.envelope_data).unwrap();
+    assert_eq!(received.text_content().unwrap(), "from b");
+}
+
+#[test]
+fn data_dir_flag_overrides_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let node_dir = dir.path().join("node-a");
+    Config::set_data_dir_override(node_dir.clone());
+    assert_eq!(Config::data_dir().unwrap(), node_dir);
+    assert!(node_dir.is_dir());
+}
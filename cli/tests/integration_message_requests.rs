@@ -53,6 +53,7 @@ fn make_ctx(core: Arc<IronCore>) -> WebContext {
         )),
         ui_port: 0,
         core: Some(core),
+        relay_server: None,
     }
 }
 
@@ -0,0 +1,122 @@
+//! Integration test: `GET /api/status` reports the shared peer map, ledger
+//! and swarm topics.
+//!
+//! Drives the real control API router against a running swarm. The peer map
+//! and ledger are filled in by hand, the way `scm start`'s event loop records
+//! a discovered peer; this does not exercise that loop itself.
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use libp2p::Multiaddr;
+use scmessenger_cli::api::{router, ApiContext, StatusResponse};
+use scmessenger_cli::ledger::ConnectionLedger;
+use scmessenger_core::identity::IdentityKeys;
+use scmessenger_core::transport::{start_swarm, SwarmEvent, SwarmHandle};
+use scmessenger_core::IronCore;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::{mpsc, Mutex};
+use tokio::time::{timeout, Duration};
+use tower::ServiceExt;
+
+async fn spawn_node() -> (SwarmHandle, mpsc::Receiver<SwarmEvent>, Multiaddr) {
+    let keypair = IdentityKeys::generate().to_libp2p_keypair().unwrap();
+    let (tx, mut rx) = mpsc::channel(256);
+    let handle = start_swarm(
+        keypair,
+        Some("/ip4/127.0.0.1/tcp/0".parse().unwrap()),
+        tx,
+        None,
+        false,
+        None, // discovery_config
+        scmessenger_core::transport::default_routing_engine_handle(),
+    )
+    .await
+    .expect("failed to start swarm");
+    let addr = timeout(Duration::from_secs(10), async {
+        loop {
+            match rx.recv().await {
+                Some(SwarmEvent::ListeningOn(addr)) if addr.to_string().contains("/tcp/") => {
+                    return addr;
+                }
+                Some(_) => {}
+                None => panic!("event channel closed while waiting for listener"),
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for listener");
+    (handle, rx, addr)
+}
+
+async fn get_status(ctx: &Arc<ApiContext>) -> StatusResponse {
+    let response = router(Arc::clone(ctx))
+        .oneshot(
+            Request::builder()
+                .uri("/api/status")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&body).expect("status response matches StatusResponse")
+}
+
+#[tokio::test]
+#[ignore = "requires real networking (TCP bind); run with --include-ignored"]
+async fn status_reports_recorded_peers_ledger_and_swarm_topics() {
+    let (handle, mut events, _addr) = spawn_node().await;
+    let (remote, _remote_events, remote_addr) = spawn_node().await;
+
+    let ledger_dir = tempfile::tempdir().unwrap();
+    let ctx = Arc::new(ApiContext {
+        core: Arc::new(IronCore::new()),
+        swarm_handle: Arc::new(handle.clone()),
+        peers: Arc::new(Mutex::new(HashMap::new())),
+        ledger: Arc::new(Mutex::new(
+            ConnectionLedger::load(ledger_dir.path()).unwrap(),
+        )),
+        start_time: Instant::now(),
+    });
+
+    let before = get_status(&ctx).await;
+    assert_eq!(before.status, "running");
+    assert_eq!(before.connected_peers, 0);
+    assert_eq!(before.ledger_entries, 0);
+    assert_eq!(before.outbox_count, 0);
+
+    handle
+        .subscribe_topic("sc-status-test".to_string())
+        .await
+        .unwrap();
+    handle.dial(remote_addr.clone()).await.unwrap();
+    let remote_peer = timeout(Duration::from_secs(15), async {
+        loop {
+            match events.recv().await {
+                Some(SwarmEvent::PeerDiscovered(peer_id)) => return peer_id,
+                Some(_) => {}
+                None => panic!("event channel closed while waiting for peer"),
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for peer");
+    // Recorded by hand, as the `scm start` event loop would.
+    ctx.peers.lock().await.insert(remote_peer, None);
+    ctx.ledger
+        .lock()
+        .await
+        .add_bootstrap(&remote_addr.to_string(), None);
+
+    let after = get_status(&ctx).await;
+    assert_eq!(after.connected_peers, 1);
+    assert_eq!(after.subscribed_topics, before.subscribed_topics + 1);
+    assert_eq!(after.ledger_entries, 1);
+
+    handle.shutdown().await.ok();
+    remote.shutdown().await.ok();
+}
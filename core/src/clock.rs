@@ -0,0 +1,96 @@
+// Time source for IronCore
+//
+// Message, receipt and inbox timestamps, ack timeouts and expiry sweeps read
+// the time through `Clock` instead of calling `SystemTime::now()` directly.
+// Production uses `SystemClock`, optionally shifted by a correction when the
+// device clock is known to be wrong (`IronCore::set_clock_offset`); tests use
+// `MockClock` to step time forward without sleeping.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Source of wall-clock time, as milliseconds since the Unix epoch.
+pub trait Clock: Send + Sync {
+    fn now_millis(&self) -> u64;
+
+    fn now_secs(&self) -> u64 {
+        self.now_millis() / 1000
+    }
+}
+
+/// The device clock, plus an optional correction in milliseconds.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock {
+    offset_millis: i64,
+}
+
+impl SystemClock {
+    /// A system clock shifted by `offset_secs` (positive = device is behind).
+    pub fn with_offset_secs(offset_secs: i64) -> Self {
+        Self {
+            offset_millis: offset_secs.saturating_mul(1000),
+        }
+    }
+
+    pub fn offset_secs(&self) -> i64 {
+        self.offset_millis / 1000
+    }
+}
+
+impl Clock for SystemClock {
+    fn now_millis(&self) -> u64 {
+        let device = web_time::SystemTime::now()
+            .duration_since(web_time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        device.saturating_add_signed(self.offset_millis)
+    }
+}
+
+/// Manually driven clock for tests. Clones share the same time.
+#[derive(Debug, Clone, Default)]
+pub struct MockClock {
+    millis: Arc<AtomicU64>,
+}
+
+impl MockClock {
+    pub fn at_secs(secs: u64) -> Self {
+        Self {
+            millis: Arc::new(AtomicU64::new(secs.saturating_mul(1000))),
+        }
+    }
+
+    pub fn set_secs(&self, secs: u64) {
+        self.millis
+            .store(secs.saturating_mul(1000), Ordering::SeqCst);
+    }
+
+    pub fn advance_secs(&self, secs: u64) {
+        self.millis
+            .fetch_add(secs.saturating_mul(1000), Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now_millis(&self) -> u64 {
+        self.millis.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock_is_shared_and_offset_applies() {
+        let clock = MockClock::at_secs(1_000);
+        let handle = clock.clone();
+        handle.advance_secs(30);
+        assert_eq!(clock.now_secs(), 1_030);
+        assert_eq!(clock.now_millis(), 1_030_000);
+
+        let device = SystemClock::default().now_secs();
+        let corrected = SystemClock::with_offset_secs(3_600).now_secs();
+        assert!(corrected >= device + 3_599 && corrected <= device + 3_601);
+    }
+}
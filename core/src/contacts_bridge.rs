@@ -9,7 +9,8 @@ use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use sled::Db;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, OnceLock, Weak};
 
 /// Public contact structure exposed via UniFFI
@@ -28,6 +29,10 @@ pub struct Contact {
     pub verified_at: Option<u64>,
     #[serde(default)]
     pub is_tombstone: bool,
+    /// Relays (PeerId or `/p2p/` multiaddr) to try first after a direct
+    /// connection when delivering to this contact.
+    #[serde(default)]
+    pub preferred_relays: Vec<String>,
 }
 
 impl Contact {
@@ -43,6 +48,7 @@ impl Contact {
             last_known_device_id: None,
             verified_at: None,
             is_tombstone: false,
+            preferred_relays: Vec::new(),
         }
     }
 
@@ -58,12 +64,22 @@ impl Contact {
             last_known_device_id: None,
             verified_at: None,
             is_tombstone: true,
+            preferred_relays: Vec::new(),
         }
     }
 
-    pub fn with_nickname(mut self, nickname: String) -> Self {
+    /// Set the federated nickname, rejecting over-long or display-unsafe
+    /// ones (see `identity::validate_nickname`).
+    pub fn with_nickname(
+        mut self,
+        nickname: String,
+    ) -> Result<Self, crate::identity::NicknameError> {
+        crate::identity::validate_nickname(
+            &nickname,
+            crate::identity::DEFAULT_MAX_NICKNAME_GRAPHEMES,
+        )?;
         self.nickname = Some(nickname);
-        self
+        Ok(self)
     }
 
     pub fn display_name(&self) -> &str {
@@ -78,6 +94,77 @@ impl Contact {
     }
 }
 
+impl crate::store::contacts::DuplicateContact for Contact {
+    fn peer_id(&self) -> &str {
+        &self.peer_id
+    }
+
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    fn display_name(&self) -> &str {
+        Contact::display_name(self)
+    }
+
+    fn added_at(&self) -> u64 {
+        self.added_at
+    }
+
+    fn last_seen(&self) -> Option<u64> {
+        self.last_seen
+    }
+
+    fn verified_at(&self) -> Option<u64> {
+        self.verified_at
+    }
+
+    fn absorb(&mut self, other: &Self) {
+        self.added_at = self.added_at.min(other.added_at);
+        self.last_seen = self.last_seen.max(other.last_seen);
+        self.verified_at = match (self.verified_at, other.verified_at) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        if self.nickname.is_none() {
+            self.nickname = other.nickname.clone();
+        }
+        if self.local_nickname.is_none() {
+            self.local_nickname = other.local_nickname.clone();
+        }
+        if self.notes.is_none() {
+            self.notes = other.notes.clone();
+        }
+        if self.last_known_device_id.is_none() {
+            self.last_known_device_id = other.last_known_device_id.clone();
+        }
+        for relay in &other.preferred_relays {
+            if !self.preferred_relays.contains(relay) {
+                self.preferred_relays.push(relay.clone());
+            }
+        }
+    }
+}
+
+/// An address-book name that is display-unsafe or too long is not worth
+/// losing the contact over; keep the contact without it.
+fn valid_or_dropped(nickname: Option<String>) -> Option<String> {
+    crate::identity::normalize_nickname(nickname).unwrap_or_else(|e| {
+        tracing::warn!("Dropping synced contact nickname: {}", e);
+        None
+    })
+}
+
+/// Outcome of `ContactManager::sync_contacts`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncResult {
+    pub added: u32,
+    pub updated: u32,
+    /// Public keys, as given, of contacts left out because the key is not a
+    /// valid Ed25519 key.
+    pub skipped: Vec<String>,
+}
+
 /// Contact manager with thread-safe sled database backend.
 ///
 /// Mobile clients hold a bridge manager for their UI while IronCore opens the
@@ -88,6 +175,9 @@ impl Contact {
 #[derive(uniffi::Object)]
 pub struct ContactManager {
     db: Arc<Mutex<Db>>,
+    /// `MeshSettings::max_contacts`, shared by every manager on the same
+    /// storage path; 0 = no cap.
+    max_contacts: Arc<AtomicU32>,
 }
 
 type SharedContactDatabase = Arc<Mutex<Db>>;
@@ -98,16 +188,44 @@ fn contact_database_registry() -> &'static Mutex<HashMap<PathBuf, WeakContactDat
     REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
 }
 
+/// Contact cap for the database at `path`, one per path for the process so
+/// a settings change reaches managers the UI already holds.
+fn shared_contact_cap(path: &Path) -> Arc<AtomicU32> {
+    static CAPS: OnceLock<Mutex<HashMap<PathBuf, Arc<AtomicU32>>>> = OnceLock::new();
+    CAPS.get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .entry(path.to_path_buf())
+        .or_default()
+        .clone()
+}
+
+/// Apply a new `MeshSettings::max_contacts` to every contact manager on
+/// `storage_path`, including ones opened before the change.
+pub(crate) fn set_max_contacts_for_storage(storage_path: &str, max_contacts: u32) {
+    shared_contact_cap(&PathBuf::from(storage_path).join("contacts.db"))
+        .store(max_contacts, Ordering::Relaxed);
+}
+
 #[uniffi::export]
 impl ContactManager {
     /// Create or open contact database at the given path
     #[uniffi::constructor]
     pub fn new(storage_path: String) -> Result<Self, crate::IronCoreError> {
+        // The cap lives in the mesh settings stored alongside contacts.db.
+        let max_contacts = crate::mobile_bridge::MeshSettingsManager::new(storage_path.clone())
+            .load()
+            .map(|settings| settings.max_contacts)
+            .unwrap_or(0);
         let path = PathBuf::from(storage_path).join("contacts.db");
+        let shared_cap = shared_contact_cap(&path);
+        shared_cap.store(max_contacts, Ordering::Relaxed);
         let mut registry = contact_database_registry().lock();
 
         if let Some(existing) = registry.get(&path).and_then(Weak::upgrade) {
-            return Ok(Self { db: existing });
+            return Ok(Self {
+                db: existing,
+                max_contacts: shared_cap,
+            });
         }
 
         // A previous manager may have been released after an app lifecycle
@@ -124,22 +242,42 @@ impl ContactManager {
         let db: SharedContactDatabase = Arc::new(Mutex::new(db));
         registry.insert(path, Arc::downgrade(&db));
 
-        Ok(Self { db })
+        Ok(Self {
+            db,
+            max_contacts: shared_cap,
+        })
     }
 
-    /// Add a contact to the database
-    pub fn add(&self, contact: Contact) -> Result<(), crate::IronCoreError> {
+    /// Add or update a contact. When a new contact takes the store past
+    /// `max_contacts`, the least recently seen unverified contact is evicted;
+    /// verified contacts are never evicted. Returns whether one was.
+    pub fn add(&self, contact: Contact) -> Result<bool, crate::IronCoreError> {
         let db = self.db.lock();
         let key = contact.peer_id.as_bytes();
         let value = serde_json::to_vec(&contact)
             .context("Failed to serialize contact")
             .map_err(|_| crate::IronCoreError::Internal)?;
 
-        db.insert(key, value)
+        let previous = db
+            .insert(key, value)
             .context("Failed to insert contact")
             .map_err(|_| crate::IronCoreError::StorageError)?;
 
-        Ok(())
+        if previous.is_some() || contact.is_tombstone {
+            return Ok(false);
+        }
+        Self::evict_over_cap(&db, self.max_contacts(), &contact.peer_id)
+    }
+
+    /// Current contact cap (0 = none).
+    pub fn max_contacts(&self) -> u32 {
+        self.max_contacts.load(Ordering::Relaxed)
+    }
+
+    /// Change the contact cap for every manager on this storage path. Takes
+    /// effect on the next `add`; existing contacts are not evicted until then.
+    pub fn set_max_contacts(&self, max_contacts: u32) {
+        self.max_contacts.store(max_contacts, Ordering::Relaxed);
     }
 
     /// Get a contact by peer ID
@@ -214,6 +352,15 @@ impl ContactManager {
         Ok(results)
     }
 
+    /// Resolve a libp2p PeerId to the contact holding the Ed25519 key it
+    /// embeds. `None` for unparsable or non-Ed25519 PeerIds and unknown keys.
+    pub fn find_by_peer_id(&self, peer_id: String) -> Option<Contact> {
+        let public_key = crate::iron_core::public_key_hex_from_peer_id(&peer_id).ok()?;
+        self.list().ok()?.into_iter().find(|contact| {
+            !contact.is_tombstone && contact.public_key.trim().eq_ignore_ascii_case(&public_key)
+        })
+    }
+
     /// Set or update contact federated nickname
     pub fn set_nickname(
         &self,
@@ -221,9 +368,10 @@ impl ContactManager {
         nickname: Option<String>,
     ) -> Result<(), crate::IronCoreError> {
         if let Some(mut contact) = self.get(peer_id.clone())? {
-            contact.nickname = nickname
-                .map(|value| value.trim().to_string())
-                .filter(|value| !value.is_empty());
+            contact.nickname = crate::identity::normalize_nickname(nickname).map_err(|e| {
+                tracing::warn!("Rejected contact nickname: {}", e);
+                crate::IronCoreError::InvalidNickname
+            })?;
             self.add(contact)?;
             Ok(())
         } else {
@@ -238,9 +386,29 @@ impl ContactManager {
         nickname: Option<String>,
     ) -> Result<(), crate::IronCoreError> {
         if let Some(mut contact) = self.get(peer_id.clone())? {
-            contact.local_nickname = nickname
-                .map(|value| value.trim().to_string())
-                .filter(|value| !value.is_empty());
+            contact.local_nickname =
+                crate::identity::normalize_nickname(nickname).map_err(|e| {
+                    tracing::warn!("Rejected contact nickname: {}", e);
+                    crate::IronCoreError::InvalidNickname
+                })?;
+            self.add(contact)?;
+            Ok(())
+        } else {
+            Err(crate::IronCoreError::InvalidInput)
+        }
+    }
+
+    /// Replace the contact's preferred relays; an empty list clears them.
+    /// Push the result to a running swarm with
+    /// `SwarmBridge::set_preferred_relays`.
+    pub fn set_preferred_relays(
+        &self,
+        peer_id: String,
+        relays: Vec<String>,
+    ) -> Result<(), crate::IronCoreError> {
+        let relays = crate::store::contacts::normalize_preferred_relays(relays)?;
+        if let Some(mut contact) = self.get(peer_id.clone())? {
+            contact.preferred_relays = relays;
             self.add(contact)?;
             Ok(())
         } else {
@@ -301,6 +469,90 @@ impl ContactManager {
         Ok(recovered)
     }
 
+    /// Upsert a batch of contacts from the platform address book in one
+    /// write, instead of one `add` per contact. Contacts match on their
+    /// canonical (lowercase hex) public key: a match keeps its peer ID,
+    /// verification, first-added time and device, and takes any names, notes
+    /// and relays the platform supplies. Invalid keys are reported in
+    /// `skipped` rather than failing the batch; names that fail
+    /// `identity::validate_nickname` are dropped and the contact kept.
+    pub fn sync_contacts(
+        &self,
+        contacts: Vec<Contact>,
+    ) -> Result<SyncResult, crate::IronCoreError> {
+        let db = self.db.lock();
+        let mut by_key: HashMap<String, Contact> = HashMap::new();
+        for item in db.iter() {
+            let (_, value) = item.map_err(|_| crate::IronCoreError::StorageError)?;
+            if let Ok(contact) = serde_json::from_slice::<Contact>(&value) {
+                if !contact.is_tombstone {
+                    by_key
+                        .entry(contact.public_key.trim().to_lowercase())
+                        .or_insert(contact);
+                }
+            }
+        }
+
+        let mut result = SyncResult::default();
+        let mut batch = sled::Batch::default();
+        for mut incoming in contacts {
+            let key = incoming.public_key.trim().to_lowercase();
+            if incoming.is_tombstone
+                || crate::crypto::encrypt::validate_ed25519_public_key(&key).is_err()
+            {
+                result.skipped.push(incoming.public_key);
+                continue;
+            }
+            incoming.nickname = valid_or_dropped(incoming.nickname);
+            incoming.local_nickname = valid_or_dropped(incoming.local_nickname);
+
+            let merged = match by_key.get(&key) {
+                Some(existing) => {
+                    let mut merged = existing.clone();
+                    merged.nickname = incoming.nickname.or(merged.nickname);
+                    merged.local_nickname = incoming.local_nickname.or(merged.local_nickname);
+                    merged.notes = incoming.notes.or(merged.notes);
+                    merged.last_seen = merged.last_seen.max(incoming.last_seen);
+                    if merged.last_known_device_id.is_none() {
+                        merged.last_known_device_id = incoming.last_known_device_id;
+                    }
+                    if !incoming.preferred_relays.is_empty() {
+                        merged.preferred_relays = incoming.preferred_relays;
+                    }
+                    let unchanged =
+                        serde_json::to_vec(&merged).ok() == serde_json::to_vec(existing).ok();
+                    if unchanged {
+                        continue;
+                    }
+                    result.updated += 1;
+                    merged
+                }
+                None => {
+                    let mut added = incoming;
+                    added.public_key = key.clone();
+                    if added.peer_id.trim().is_empty() {
+                        added.peer_id = key.clone();
+                    }
+                    result.added += 1;
+                    added
+                }
+            };
+            let value = serde_json::to_vec(&merged)
+                .context("Failed to serialize contact")
+                .map_err(|_| crate::IronCoreError::Internal)?;
+            batch.insert(merged.peer_id.as_bytes(), value);
+            by_key.insert(key, merged);
+        }
+
+        db.apply_batch(batch)
+            .context("Failed to apply contact sync")
+            .map_err(|_| crate::IronCoreError::StorageError)?;
+        if result.added > 0 {
+            Self::evict_over_cap(&db, self.max_contacts(), "")?;
+        }
+        Ok(result)
+    }
+
     /// Merge contacts from another device using LWW-register CRDT semantics.
     /// Higher `added_at` timestamp wins. For blocks, block always wins over unblock.
     /// Returns the number of contacts updated.
@@ -351,6 +603,40 @@ impl ContactManager {
         Ok(())
     }
 
+    /// Groups of live contacts that share a public key, as `dedup` would
+    /// merge them (see `store::contacts::duplicate_contact_groups`). Nothing
+    /// is modified.
+    pub fn find_duplicates(&self) -> Result<Vec<Vec<Contact>>, crate::IronCoreError> {
+        let live = self
+            .list()?
+            .into_iter()
+            .filter(|c| !c.is_tombstone)
+            .collect();
+        Ok(crate::store::contacts::duplicate_contact_groups(live))
+    }
+
+    /// Collapse contacts that share a public key into one record (see
+    /// `store::contacts::merge_duplicate_contacts`) and return how many
+    /// were removed.
+    pub fn dedup(&self) -> Result<u32, crate::IronCoreError> {
+        let mut removed = 0;
+        for group in self.find_duplicates()? {
+            let Some((keep, duplicates)) = crate::store::contacts::merge_duplicate_contacts(group)
+            else {
+                continue;
+            };
+            self.add(keep)?;
+            for peer_id in duplicates {
+                self.remove(peer_id)?;
+                removed += 1;
+            }
+        }
+        if removed > 0 {
+            self.flush();
+        }
+        Ok(removed)
+    }
+
     /// Count total contacts
     pub fn count(&self) -> u32 {
         let db = self.db.lock();
@@ -396,6 +682,53 @@ impl ContactManager {
     }
 }
 
+impl ContactManager {
+    /// Evict least recently seen unverified contacts, never `keep`, until at
+    /// most `cap` live contacts remain or only protected ones are left.
+    /// Tombstones neither count toward the cap nor get evicted.
+    fn evict_over_cap(db: &Db, cap: u32, keep: &str) -> Result<bool, crate::IronCoreError> {
+        if cap == 0 {
+            return Ok(false);
+        }
+        let mut live = Vec::new();
+        for item in db.iter() {
+            let (_, value) = item.map_err(|_| crate::IronCoreError::StorageError)?;
+            if let Ok(contact) = serde_json::from_slice::<Contact>(&value) {
+                if !contact.is_tombstone {
+                    live.push(contact);
+                }
+            }
+        }
+        let excess = live.len().saturating_sub(cap as usize);
+        if excess == 0 {
+            return Ok(false);
+        }
+
+        let mut candidates: Vec<&Contact> = live
+            .iter()
+            .filter(|c| c.verified_at.is_none() && c.peer_id != keep)
+            .collect();
+        candidates.sort_by(|a, b| {
+            a.last_seen
+                .unwrap_or(a.added_at)
+                .cmp(&b.last_seen.unwrap_or(b.added_at))
+                .then_with(|| a.peer_id.cmp(&b.peer_id))
+        });
+        let mut evicted = false;
+        for contact in candidates.into_iter().take(excess) {
+            db.remove(contact.peer_id.as_bytes())
+                .map_err(|_| crate::IronCoreError::StorageError)?;
+            tracing::info!(
+                "Evicted contact {} to stay within the {} contact cap",
+                contact.peer_id,
+                cap
+            );
+            evicted = true;
+        }
+        Ok(evicted)
+    }
+}
+
 fn current_timestamp() -> u64 {
     web_time::SystemTime::now()
         .duration_since(web_time::UNIX_EPOCH)
@@ -410,12 +743,71 @@ mod tests {
     #[test]
     fn test_contact_creation() {
         let contact = Contact::new("12D3KooTest".to_string(), "abcd1234".to_string())
-            .with_nickname("Alice".to_string());
+            .with_nickname("Alice".to_string())
+            .unwrap();
 
         assert_eq!(contact.display_name(), "Alice");
         assert_eq!(contact.peer_id, "12D3KooTest");
     }
 
+    #[test]
+    fn test_sync_contacts_upserts_batch_and_skips_invalid() -> Result<(), crate::IronCoreError> {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let storage_path = temp_dir.path().to_str().unwrap_or_default().to_string();
+        let manager = ContactManager::new(storage_path)?;
+
+        let key_of = || {
+            hex::encode(
+                crate::identity::IdentityKeys::generate()
+                    .signing_key
+                    .verifying_key()
+                    .to_bytes(),
+            )
+        };
+        let existing_key = key_of();
+        let mut existing = Contact::new("peer-existing".to_string(), existing_key.clone());
+        existing.verified_at = Some(42);
+        existing.notes = Some("met at the park".to_string());
+        manager.add(existing)?;
+
+        // The platform knows the existing contact by an upper-case key and
+        // without a peer ID or verification.
+        let mut renamed = Contact::new(String::new(), existing_key.to_uppercase());
+        renamed.local_nickname = Some("Bob (work)".to_string());
+        let new_key = key_of();
+        let mut fresh = Contact::new(String::new(), new_key.clone());
+        fresh.nickname = Some("admin\u{202E}txt.exe".to_string());
+        let invalid = Contact::new("peer-bad".to_string(), "not-a-key".to_string());
+
+        let result = manager.sync_contacts(vec![renamed, fresh, invalid])?;
+        assert_eq!(
+            result,
+            SyncResult {
+                added: 1,
+                updated: 1,
+                skipped: vec!["not-a-key".to_string()],
+            }
+        );
+
+        let kept = manager.get("peer-existing".to_string())?.unwrap();
+        assert_eq!(kept.verified_at, Some(42));
+        assert_eq!(kept.local_nickname.as_deref(), Some("Bob (work)"));
+        assert_eq!(kept.notes.as_deref(), Some("met at the park"));
+        // Added, but without the display-unsafe name.
+        assert_eq!(manager.get(new_key)?.unwrap().nickname, None);
+        assert!(manager.get("peer-bad".to_string())?.is_none());
+        assert!(matches!(
+            manager.set_nickname("peer-existing".to_string(), Some("x".repeat(100))),
+            Err(crate::IronCoreError::InvalidNickname)
+        ));
+        assert_eq!(manager.count(), 2);
+
+        // Syncing the same book again changes nothing.
+        let again = manager.sync_contacts(vec![Contact::new(String::new(), existing_key)])?;
+        assert_eq!(again, SyncResult::default());
+        Ok(())
+    }
+
     #[test]
     fn test_contact_manager() -> Result<(), crate::IronCoreError> {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -425,7 +817,8 @@ mod tests {
 
         // Add contact
         let contact = Contact::new("12D3KooTest1".to_string(), "pubkey1".to_string())
-            .with_nickname("Alice".to_string());
+            .with_nickname("Alice".to_string())
+            .unwrap();
 
         manager.add(contact)?;
 
@@ -448,6 +841,100 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_find_by_peer_id_resolves_derived_peer_id() -> Result<(), crate::IronCoreError> {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let storage_path = temp_dir.path().to_str().unwrap_or_default().to_string();
+        let manager = ContactManager::new(storage_path)?;
+
+        let keys = crate::identity::IdentityKeys::generate();
+        let public_key = hex::encode(keys.signing_key.verifying_key().to_bytes());
+        let libp2p_peer_id = keys
+            .to_libp2p_keypair()
+            .unwrap()
+            .public()
+            .to_peer_id()
+            .to_string();
+        manager.add(
+            Contact::new("identity-alice".to_string(), public_key.to_uppercase())
+                .with_nickname("Alice".to_string())
+                .unwrap(),
+        )?;
+
+        let found = manager.find_by_peer_id(libp2p_peer_id).unwrap();
+        assert_eq!(found.peer_id, "identity-alice");
+
+        // Hash-based (non-Ed25519) and malformed PeerIds resolve to nothing.
+        assert!(manager
+            .find_by_peer_id(libp2p::PeerId::random().to_string())
+            .is_none());
+        assert!(manager
+            .find_by_peer_id("not-a-peer-id".to_string())
+            .is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dedup_keeps_verified_contact() -> Result<(), crate::IronCoreError> {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let storage_path = temp_dir.path().to_str().unwrap_or_default().to_string();
+        let manager = ContactManager::new(storage_path)?;
+
+        let mut verified = Contact::new("peer-verified".to_string(), "AA11".to_string());
+        verified.verified_at = Some(50);
+        verified.last_seen = Some(10);
+        let mut recent = Contact::new("peer-recent".to_string(), "aa11".to_string());
+        recent.last_seen = Some(999);
+        recent.notes = Some("met at the meetup".to_string());
+        manager.add(verified)?;
+        manager.add(recent)?;
+
+        assert_eq!(manager.find_duplicates()?[0][0].peer_id, "peer-verified");
+        assert_eq!(manager.dedup()?, 1);
+        let kept = manager
+            .get("peer-verified".to_string())?
+            .expect("verified contact kept");
+        assert_eq!(kept.verified_at, Some(50));
+        assert_eq!(kept.last_seen, Some(999));
+        assert_eq!(kept.notes.as_deref(), Some("met at the meetup"));
+        assert!(manager.get("peer-recent".to_string())?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_contacts_evicts_least_recently_seen_unverified() -> Result<(), crate::IronCoreError>
+    {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let storage_path = temp_dir.path().to_str().unwrap_or_default().to_string();
+        let manager = ContactManager::new(storage_path)?;
+        manager.set_max_contacts(3);
+
+        let seen_at = |peer_id: &str, last_seen: u64| {
+            let mut contact = Contact::new(peer_id.to_string(), format!("pk-{}", peer_id));
+            contact.last_seen = Some(last_seen);
+            contact
+        };
+        // The oldest contact is verified, so it is protected.
+        let mut verified = seen_at("peer-verified", 1);
+        verified.verified_at = Some(5);
+        assert!(!manager.add(verified)?);
+        assert!(!manager.add(seen_at("peer-old", 10))?);
+        assert!(!manager.add(seen_at("peer-recent", 20))?);
+        // Updating an existing contact never evicts.
+        assert!(!manager.add(seen_at("peer-old", 11))?);
+
+        assert!(manager.add(seen_at("peer-new", 30))?);
+        assert_eq!(manager.count(), 3);
+        assert!(manager.get("peer-old".to_string())?.is_none());
+        assert!(manager.get("peer-verified".to_string())?.is_some());
+        assert!(manager.get("peer-new".to_string())?.is_some());
+
+        assert!(manager.add(seen_at("peer-newest", 40))?);
+        assert!(manager.get("peer-recent".to_string())?.is_none());
+        assert!(manager.get("peer-verified".to_string())?.is_some());
+        Ok(())
+    }
+
     #[test]
     fn test_contact_persistence_across_manager_restart() -> Result<(), crate::IronCoreError> {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -456,7 +943,8 @@ mod tests {
         {
             let manager = ContactManager::new(storage_path.clone())?;
             let contact = Contact::new("peer-alpha".to_string(), "pubkey-alpha".to_string())
-                .with_nickname("FederatedName".to_string());
+                .with_nickname("FederatedName".to_string())
+                .unwrap();
             manager.add(contact)?;
             manager.set_local_nickname("peer-alpha".to_string(), Some("LocalAlias".to_string()))?;
         }
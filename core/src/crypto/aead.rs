@@ -0,0 +1,171 @@
+// AEAD cipher suites for per-message (static-ECDH) encryption
+//
+// Every envelope carries a one-byte AEAD suite identifier so the symmetric
+// cipher can change without a flag day: senders pick a suite, receivers
+// dispatch on the byte. Envelopes from before the byte existed decode as
+// XChaCha20-Poly1305, the original scheme.
+//
+// The envelope nonce slot is always 24 bytes. Suites with a shorter nonce
+// use its leading bytes, so the wire layout is identical across suites.
+// Non-default suites also bind their id into the AAD, so a relay flipping
+// the suite byte makes decryption fail instead of silently reinterpreting
+// the ciphertext.
+
+use anyhow::Result;
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Nonce, XChaCha20Poly1305, XNonce,
+};
+use serde::{Deserialize, Serialize};
+
+/// Size of the nonce slot carried in every envelope.
+pub const ENVELOPE_NONCE_LEN: usize = 24;
+
+/// Suite byte assumed for envelopes that do not carry one.
+pub const DEFAULT_AEAD_SUITE: u8 = AeadSuite::XChaCha20Poly1305 as u8;
+
+/// Symmetric cipher used to seal an envelope's ciphertext.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum AeadSuite {
+    /// XChaCha20-Poly1305, 192-bit random nonce (0x01). The original scheme.
+    #[default]
+    XChaCha20Poly1305 = 0x01,
+    /// IETF ChaCha20-Poly1305, 96-bit nonce (0x02).
+    ChaCha20Poly1305 = 0x02,
+}
+
+/// An envelope named a suite this build does not implement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Unknown AEAD cipher suite 0x{0:02x}")]
+pub struct UnknownAeadSuite(pub u8);
+
+impl AeadSuite {
+    pub const ALL: [AeadSuite; 2] = [AeadSuite::XChaCha20Poly1305, AeadSuite::ChaCha20Poly1305];
+
+    pub fn from_u8(value: u8) -> Result<Self, UnknownAeadSuite> {
+        match value {
+            0x01 => Ok(AeadSuite::XChaCha20Poly1305),
+            0x02 => Ok(AeadSuite::ChaCha20Poly1305),
+            other => Err(UnknownAeadSuite(other)),
+        }
+    }
+
+    pub fn as_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Stable name used by settings and the FFI.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AeadSuite::XChaCha20Poly1305 => "xchacha20-poly1305",
+            AeadSuite::ChaCha20Poly1305 => "chacha20-poly1305",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|suite| suite.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// AAD for this suite: the sender key, plus the suite id for every suite
+    /// but the original one (whose AAD must stay unchanged for old peers).
+    fn aad(self, sender_public_key: &[u8]) -> Vec<u8> {
+        let mut aad = sender_public_key.to_vec();
+        if self != AeadSuite::XChaCha20Poly1305 {
+            aad.push(self.as_u8());
+        }
+        aad
+    }
+
+    pub fn seal(
+        self,
+        key: &[u8; 32],
+        nonce: &[u8; ENVELOPE_NONCE_LEN],
+        plaintext: &[u8],
+        sender_public_key: &[u8],
+    ) -> Result<Vec<u8>> {
+        let aad = self.aad(sender_public_key);
+        let payload = Payload {
+            msg: plaintext,
+            aad: &aad,
+        };
+        let sealed = match self {
+            AeadSuite::XChaCha20Poly1305 => XChaCha20Poly1305::new_from_slice(key)
+                .map_err(|e| anyhow::anyhow!("Failed to create cipher: {}", e))?
+                .encrypt(XNonce::from_slice(nonce), payload),
+            AeadSuite::ChaCha20Poly1305 => ChaCha20Poly1305::new_from_slice(key)
+                .map_err(|e| anyhow::anyhow!("Failed to create cipher: {}", e))?
+                .encrypt(Nonce::from_slice(&nonce[..12]), payload),
+        };
+        sealed.map_err(|e| anyhow::anyhow!("Encryption failed: {}", e))
+    }
+
+    pub fn open(
+        self,
+        key: &[u8; 32],
+        nonce: &[u8; ENVELOPE_NONCE_LEN],
+        ciphertext: &[u8],
+        sender_public_key: &[u8],
+    ) -> Result<Vec<u8>> {
+        let aad = self.aad(sender_public_key);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: &aad,
+        };
+        let opened = match self {
+            AeadSuite::XChaCha20Poly1305 => XChaCha20Poly1305::new_from_slice(key)
+                .map_err(|e| anyhow::anyhow!("Failed to create cipher: {}", e))?
+                .decrypt(XNonce::from_slice(nonce), payload),
+            AeadSuite::ChaCha20Poly1305 => ChaCha20Poly1305::new_from_slice(key)
+                .map_err(|e| anyhow::anyhow!("Failed to create cipher: {}", e))?
+                .decrypt(Nonce::from_slice(&nonce[..12]), payload),
+        };
+        opened.map_err(|_| {
+            anyhow::anyhow!(
+                "Decryption failed: invalid ciphertext, wrong key, or tampered sender public key"
+            )
+        })
+    }
+}
+
+impl std::fmt::Display for AeadSuite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suite_byte_and_name_roundtrip() {
+        for suite in AeadSuite::ALL {
+            assert_eq!(AeadSuite::from_u8(suite.as_u8()).unwrap(), suite);
+            assert_eq!(AeadSuite::from_name(suite.as_str()), Some(suite));
+        }
+        assert_eq!(AeadSuite::default().as_u8(), DEFAULT_AEAD_SUITE);
+        assert_eq!(AeadSuite::from_u8(0x00), Err(UnknownAeadSuite(0x00)));
+        assert_eq!(AeadSuite::from_u8(0x7f), Err(UnknownAeadSuite(0x7f)));
+        assert_eq!(AeadSuite::from_name("aes-128-ecb"), None);
+    }
+
+    #[test]
+    fn test_suites_are_not_interchangeable() {
+        let key = [7u8; 32];
+        let nonce = [9u8; ENVELOPE_NONCE_LEN];
+        let sender = [1u8; 32];
+        for suite in AeadSuite::ALL {
+            let sealed = suite.seal(&key, &nonce, b"hello", &sender).unwrap();
+            assert_eq!(
+                suite.open(&key, &nonce, &sealed, &sender).unwrap(),
+                b"hello"
+            );
+            for other in AeadSuite::ALL.into_iter().filter(|s| *s != suite) {
+                assert!(other.open(&key, &nonce, &sealed, &sender).is_err());
+            }
+        }
+    }
+}
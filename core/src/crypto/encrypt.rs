@@ -6,24 +6,31 @@
 // 3. ECDH: ephemeral_secret × recipient_x25519_public → shared_secret
 // 4. KDF: Blake3::derive_key(shared_secret) → symmetric_key
 // 5. Encrypt: XChaCha20-Poly1305(symmetric_key, random_nonce, plaintext)
-// 6. Output: Envelope { sender_pub, ephemeral_pub, nonce, ciphertext }
+// 6. Output: Envelope { sender_pub, ephemeral_pub, nonce, ciphertext, aead_suite }
 //
 // Recipient reverses:
 // 1. Convert recipient's Ed25519 key → X25519 static secret
 // 2. ECDH: recipient_secret × ephemeral_public → shared_secret
 // 3. KDF: same derivation → symmetric_key
 // 4. Decrypt: XChaCha20-Poly1305(symmetric_key, nonce, ciphertext)
+//
+// XChaCha20-Poly1305 is the default AEAD; the envelope's `aead_suite` byte
+// selects the cipher (see `crypto::aead`).
 
+use super::aead::{AeadSuite, ENVELOPE_NONCE_LEN};
 use anyhow::{bail, Result};
-use chacha20poly1305::{
-    aead::{Aead, KeyInit, Payload},
-    XChaCha20Poly1305, XNonce,
-};
 use ed25519_dalek::{Signature as Ed25519Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use rand::RngCore;
 use x25519_dalek::{EphemeralSecret, PublicKey as X25519PublicKey, StaticSecret};
 use zeroize::Zeroize;
 
+/// An envelope's fields are the wrong shape to attempt decryption at all
+/// (bad key or nonce length, missing ratchet header), as opposed to a
+/// ciphertext that fails authentication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Malformed envelope: {0}")]
+pub struct MalformedEnvelope(pub &'static str);
+
 /// KDF context string for deriving encryption keys from ECDH shared secrets.
 /// Changing this breaks compatibility with all existing messages.
 pub const KDF_CONTEXT: &str = "iron-core v2 message encryption 2026-02-05";
@@ -124,6 +131,22 @@ pub fn encrypt_message(
     sender_signing_key: &SigningKey,
     recipient_public_key: &[u8; 32],
     plaintext: &[u8],
+) -> Result<crate::message::Envelope> {
+    encrypt_message_with_suite(
+        sender_signing_key,
+        recipient_public_key,
+        plaintext,
+        AeadSuite::default(),
+    )
+}
+
+/// Encrypt like `encrypt_message`, sealing with the given AEAD suite. The
+/// suite byte travels in the envelope so the recipient can dispatch on it.
+pub fn encrypt_message_with_suite(
+    sender_signing_key: &SigningKey,
+    recipient_public_key: &[u8; 32],
+    plaintext: &[u8],
+    suite: AeadSuite,
 ) -> Result<crate::message::Envelope> {
     // Convert recipient's Ed25519 public key to X25519
     let recipient_x25519 = ed25519_public_to_x25519(recipient_public_key)?;
@@ -135,32 +158,74 @@ pub fn encrypt_message(
     // ECDH: ephemeral_secret × recipient_public → shared_secret
     let shared_secret = ephemeral_secret.diffie_hellman(&recipient_x25519);
 
-    // KDF: derive symmetric key
-    let mut symmetric_key = derive_key(shared_secret.as_bytes());
-
-    // Generate random nonce (24 bytes for XChaCha20)
-    let mut nonce_bytes = [0u8; 24];
+    // Generate random nonce (fills the 24-byte slot; shorter-nonce suites
+    // use its prefix)
+    let mut nonce_bytes = [0u8; ENVELOPE_NONCE_LEN];
     rand::rngs::OsRng.fill_bytes(&mut nonce_bytes);
-    let nonce = XNonce::from_slice(&nonce_bytes);
+
+    seal_envelope(
+        sender_signing_key,
+        plaintext,
+        suite,
+        ephemeral_public,
+        shared_secret.as_bytes(),
+        nonce_bytes,
+    )
+}
+
+/// Encrypt like `encrypt_message_with_suite`, but with a caller-chosen
+/// ephemeral secret and nonce so the envelope is reproducible. Only for
+/// test vectors: reusing either value breaks confidentiality.
+#[cfg(any(test, feature = "test-vectors"))]
+pub(crate) fn encrypt_message_with_ephemeral(
+    sender_signing_key: &SigningKey,
+    recipient_public_key: &[u8; 32],
+    plaintext: &[u8],
+    suite: AeadSuite,
+    ephemeral_secret: [u8; 32],
+    nonce_bytes: [u8; ENVELOPE_NONCE_LEN],
+) -> Result<crate::message::Envelope> {
+    let recipient_x25519 = ed25519_public_to_x25519(recipient_public_key)?;
+    let ephemeral_secret = StaticSecret::from(ephemeral_secret);
+    let ephemeral_public = X25519PublicKey::from(&ephemeral_secret);
+    let shared_secret = ephemeral_secret.diffie_hellman(&recipient_x25519);
+
+    seal_envelope(
+        sender_signing_key,
+        plaintext,
+        suite,
+        ephemeral_public,
+        shared_secret.as_bytes(),
+        nonce_bytes,
+    )
+}
+
+/// Derive the message key from an ECDH shared secret and seal `plaintext`
+/// into a per-message envelope.
+fn seal_envelope(
+    sender_signing_key: &SigningKey,
+    plaintext: &[u8],
+    suite: AeadSuite,
+    ephemeral_public: X25519PublicKey,
+    shared_secret: &[u8; 32],
+    nonce_bytes: [u8; ENVELOPE_NONCE_LEN],
+) -> Result<crate::message::Envelope> {
+    // KDF: derive symmetric key
+    let mut symmetric_key = derive_key(shared_secret);
 
     // Encrypt with AAD (Additional Authenticated Data)
     // Bind sender public key as AAD to prevent sender spoofing
     let sender_public_bytes = sender_signing_key.verifying_key().to_bytes();
-    let cipher = XChaCha20Poly1305::new_from_slice(&symmetric_key)
-        .map_err(|e| anyhow::anyhow!("Failed to create cipher: {}", e))?;
-
-    let ciphertext = cipher
-        .encrypt(
-            nonce,
-            Payload {
-                msg: plaintext,
-                aad: &sender_public_bytes,
-            },
-        )
-        .map_err(|e| anyhow::anyhow!("Encryption failed: {}", e))?;
+    let ciphertext = suite.seal(
+        &symmetric_key,
+        &nonce_bytes,
+        plaintext,
+        &sender_public_bytes,
+    );
 
     // Zeroize key material
     symmetric_key.zeroize();
+    let ciphertext = ciphertext?;
 
     Ok(crate::message::Envelope {
         sender_public_key: sender_signing_key.verifying_key().to_bytes().to_vec(),
@@ -169,6 +234,7 @@ pub fn encrypt_message(
         ciphertext,
         ratchet_dh_public: None,
         ratchet_message_number: None,
+        aead_suite: suite.as_u8(),
     })
 }
 
@@ -179,18 +245,22 @@ pub fn encrypt_message(
 /// * `envelope` - The encrypted envelope
 ///
 /// # Returns
-/// The decrypted plaintext bytes.
+/// The decrypted plaintext bytes. The cipher is chosen by the envelope's
+/// `aead_suite` byte; an unknown byte fails with `UnknownAeadSuite`.
 pub fn decrypt_message(
     recipient_signing_key: &SigningKey,
     envelope: &crate::message::Envelope,
 ) -> Result<Vec<u8>> {
+    let suite = AeadSuite::from_u8(envelope.aead_suite).map_err(anyhow::Error::new)?;
+
     // Validate envelope fields
     if envelope.ephemeral_public_key.len() != 32 {
-        bail!("Invalid ephemeral public key length");
-    }
-    if envelope.nonce.len() != 24 {
-        bail!("Invalid nonce length");
+        return Err(MalformedEnvelope("invalid ephemeral public key length").into());
     }
+    let nonce: [u8; ENVELOPE_NONCE_LEN] = match envelope.nonce.as_slice().try_into() {
+        Ok(nonce) => nonce,
+        Err(_) => return Err(MalformedEnvelope("invalid nonce length").into()),
+    };
 
     // Convert recipient's Ed25519 signing key to X25519 static secret
     let recipient_x25519_secret = ed25519_to_x25519_secret(recipient_signing_key);
@@ -206,35 +276,22 @@ pub fn decrypt_message(
     // KDF: same derivation as encryption
     let mut symmetric_key = derive_key(shared_secret.as_bytes());
 
-    // Reconstruct nonce
-    let nonce = XNonce::from_slice(&envelope.nonce);
-
     // Decrypt with AAD (must match the sender public key used during encryption)
     // This prevents sender spoofing attacks
     if envelope.sender_public_key.len() != 32 {
-        bail!("Invalid sender public key length");
+        return Err(MalformedEnvelope("invalid sender public key length").into());
     }
-    let cipher = XChaCha20Poly1305::new_from_slice(&symmetric_key)
-        .map_err(|e| anyhow::anyhow!("Failed to create cipher: {}", e))?;
-
-    let plaintext = cipher
-        .decrypt(
-            nonce,
-            Payload {
-                msg: envelope.ciphertext.as_ref(),
-                aad: envelope.sender_public_key.as_ref(),
-            },
-        )
-        .map_err(|_| {
-            anyhow::anyhow!(
-                "Decryption failed: invalid ciphertext, wrong key, or tampered sender public key"
-            )
-        })?;
+    let plaintext = suite.open(
+        &symmetric_key,
+        &nonce,
+        &envelope.ciphertext,
+        &envelope.sender_public_key,
+    );
 
     // Zeroize key material
     symmetric_key.zeroize();
 
-    Ok(plaintext)
+    plaintext
 }
 
 /// Decrypt a ratcheted envelope using a RatchetSession.
@@ -249,13 +306,15 @@ pub fn decrypt_message_ratcheted(
     let dh_public = envelope
         .ratchet_dh_public
         .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("Ratcheted envelope missing ratchet_dh_public field"))?;
-    let message_number = envelope.ratchet_message_number.ok_or_else(|| {
-        anyhow::anyhow!("Ratcheted envelope missing ratchet_message_number field")
-    })?;
+        .ok_or(MalformedEnvelope(
+            "ratcheted envelope missing ratchet_dh_public",
+        ))?;
+    let message_number = envelope.ratchet_message_number.ok_or(MalformedEnvelope(
+        "ratcheted envelope missing ratchet_message_number",
+    ))?;
 
     if envelope.nonce.len() != 24 {
-        bail!("Invalid nonce length in ratcheted envelope");
+        return Err(MalformedEnvelope("invalid nonce length in ratcheted envelope").into());
     }
 
     // Use sender public key as AAD (same binding as legacy path)
@@ -280,13 +339,15 @@ pub fn decrypt_message_ratcheted_v2(
     let dh_public = envelope
         .ratchet_dh_public
         .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("Ratcheted V2 envelope missing ratchet_dh_public field"))?;
-    let message_number = envelope.ratchet_message_number.ok_or_else(|| {
-        anyhow::anyhow!("Ratcheted V2 envelope missing ratchet_message_number field")
-    })?;
+        .ok_or(MalformedEnvelope(
+            "ratcheted V2 envelope missing ratchet_dh_public",
+        ))?;
+    let message_number = envelope.ratchet_message_number.ok_or(MalformedEnvelope(
+        "ratcheted V2 envelope missing ratchet_message_number",
+    ))?;
 
     if envelope.nonce.len() != 24 {
-        bail!("Invalid nonce length in ratcheted V2 envelope");
+        return Err(MalformedEnvelope("invalid nonce length in ratcheted V2 envelope").into());
     }
 
     let aad = envelope.sender_public_key.as_slice();
@@ -424,6 +485,7 @@ pub fn encrypt_message_ratcheted(
             ciphertext: result.ciphertext,
             ratchet_dh_public: Some(result.our_dh_public.to_vec()),
             ratchet_message_number: Some(result.message_number),
+            aead_suite: crate::crypto::aead::DEFAULT_AEAD_SUITE,
         }))
     }
 }
@@ -514,6 +576,35 @@ pub fn encrypt_with_ratchet_fallback(
     our_bundle: Option<&crate::identity::PublicKeyBundle>,
     require_pq: bool,
     audit_log: Option<&mut crate::observability::AuditLog>,
+) -> Result<crate::message::WireEnvelope> {
+    encrypt_with_ratchet_fallback_suite(
+        sender_signing_key,
+        recipient_bundle,
+        recipient_public_key_fallback,
+        plaintext,
+        session_manager,
+        peer_id,
+        our_bundle,
+        require_pq,
+        audit_log,
+        AeadSuite::default(),
+    )
+}
+
+/// `encrypt_with_ratchet_fallback` with an explicit AEAD suite for the legacy
+/// static-ECDH path. Ratcheted sends use the session cipher and ignore it.
+#[allow(clippy::too_many_arguments)]
+pub fn encrypt_with_ratchet_fallback_suite(
+    sender_signing_key: &SigningKey,
+    recipient_bundle: Option<&crate::identity::PublicKeyBundle>,
+    recipient_public_key_fallback: &[u8; 32],
+    plaintext: &[u8],
+    session_manager: Option<&mut crate::crypto::RatchetSessionManager>,
+    peer_id: &str,
+    our_bundle: Option<&crate::identity::PublicKeyBundle>,
+    require_pq: bool,
+    audit_log: Option<&mut crate::observability::AuditLog>,
+    aead_suite: AeadSuite,
 ) -> Result<crate::message::WireEnvelope> {
     let session_exists = session_manager
         .as_ref()
@@ -560,11 +651,14 @@ pub fn encrypt_with_ratchet_fallback(
                 );
             }
 
-            Ok(crate::message::WireEnvelope::V1(encrypt_message(
-                sender_signing_key,
-                recipient_public_key_fallback,
-                plaintext,
-            )?))
+            Ok(crate::message::WireEnvelope::V1(
+                encrypt_message_with_suite(
+                    sender_signing_key,
+                    recipient_public_key_fallback,
+                    plaintext,
+                    aead_suite,
+                )?,
+            ))
         }
     }
 }
@@ -813,9 +907,58 @@ pub fn verify_envelope_v2(signed_envelope: &crate::message::SignedEnvelopeV2) ->
     Ok(())
 }
 
+/// Detached signature over the envelope carried in `envelope_data` (any
+/// encoding `decode_wire_envelope` accepts), for `RelayRequest::envelope_signature`.
+///
+/// Signs exactly what `sign_envelope` / `sign_envelope_v2` sign, so relays can
+/// check the frame without decrypting it. Fails if the envelope's sender key
+/// is not `sender_signing_key`: a node only vouches for envelopes it sealed.
+pub fn sign_relay_envelope(
+    envelope_data: &[u8],
+    sender_signing_key: &SigningKey,
+) -> Result<Vec<u8>> {
+    let sender_public = sender_signing_key.verifying_key().to_bytes();
+    let signature = match crate::message::decode_wire_envelope(envelope_data)? {
+        crate::message::WireEnvelope::V1(envelope) => {
+            if envelope.sender_public_key != sender_public {
+                bail!("Envelope was not sealed by this signing key");
+            }
+            sign_envelope(envelope, sender_signing_key)?.signature
+        }
+        crate::message::WireEnvelope::V2(envelope) => {
+            if envelope.sender_public_key != sender_public {
+                bail!("Envelope was not sealed by this signing key");
+            }
+            sign_envelope_v2(envelope, sender_signing_key)?.signature
+        }
+    };
+    Ok(signature)
+}
+
+/// Verify a detached relay signature against the sender key inside
+/// `envelope_data`. Onion-wrapped or otherwise opaque payloads do not decode
+/// as envelopes and fail here, so they cannot pass a signed-relay policy.
+pub fn verify_envelope_signature(envelope_data: &[u8], signature: &[u8]) -> Result<()> {
+    match crate::message::decode_wire_envelope(envelope_data)? {
+        crate::message::WireEnvelope::V1(envelope) => {
+            verify_envelope(&crate::message::SignedEnvelope {
+                envelope,
+                signature: signature.to_vec(),
+            })
+        }
+        crate::message::WireEnvelope::V2(envelope) => {
+            verify_envelope_v2(&crate::message::SignedEnvelopeV2 {
+                envelope,
+                signature: signature.to_vec(),
+            })
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::crypto::aead::UnknownAeadSuite;
     use crate::observability::{AuditEventType, AuditLog};
     use ed25519_dalek::SigningKey;
 
@@ -1049,12 +1192,69 @@ mod tests {
             ciphertext: vec![0u8; 32],
             ratchet_dh_public: None,
             ratchet_message_number: None,
+            aead_suite: crate::crypto::aead::DEFAULT_AEAD_SUITE,
         };
 
         let result = decrypt_message(&recipient_key, &envelope);
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_encrypt_decrypt_roundtrip_each_suite() {
+        let sender_key = generate_keypair();
+        let recipient_key = generate_keypair();
+        let recipient_public = recipient_key.verifying_key().to_bytes();
+
+        for suite in AeadSuite::ALL {
+            let envelope =
+                encrypt_message_with_suite(&sender_key, &recipient_public, b"suite test", suite)
+                    .unwrap();
+            assert_eq!(envelope.aead_suite, suite.as_u8());
+            assert_eq!(envelope.nonce.len(), ENVELOPE_NONCE_LEN);
+            let decrypted = decrypt_message(&recipient_key, &envelope).unwrap();
+            assert_eq!(decrypted, b"suite test");
+        }
+    }
+
+    #[test]
+    fn test_wrong_recipient_fails_across_suites() {
+        let sender_key = generate_keypair();
+        let recipient_key = generate_keypair();
+        let wrong_key = generate_keypair();
+        let recipient_public = recipient_key.verifying_key().to_bytes();
+
+        for suite in AeadSuite::ALL {
+            let envelope =
+                encrypt_message_with_suite(&sender_key, &recipient_public, b"not for you", suite)
+                    .unwrap();
+            assert!(decrypt_message(&wrong_key, &envelope).is_err());
+
+            // Relabelling the suite byte must not let the intended recipient
+            // (or anyone else) open it under a different cipher.
+            for other in AeadSuite::ALL.into_iter().filter(|s| *s != suite) {
+                let mut relabelled = envelope.clone();
+                relabelled.aead_suite = other.as_u8();
+                assert!(decrypt_message(&recipient_key, &relabelled).is_err());
+                assert!(decrypt_message(&wrong_key, &relabelled).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn test_unknown_suite_byte_rejected() {
+        let sender_key = generate_keypair();
+        let recipient_key = generate_keypair();
+        let recipient_public = recipient_key.verifying_key().to_bytes();
+
+        let mut envelope = encrypt_message(&sender_key, &recipient_public, b"hi").unwrap();
+        envelope.aead_suite = 0xEE;
+        let err = decrypt_message(&recipient_key, &envelope).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<UnknownAeadSuite>(),
+            Some(&UnknownAeadSuite(0xEE))
+        );
+    }
+
     #[test]
     fn test_aad_binding_prevents_sender_spoofing() {
         let sender_key = generate_keypair();
@@ -1093,6 +1293,25 @@ mod tests {
         assert!(result.is_ok(), "Valid signature should verify successfully");
     }
 
+    #[test]
+    fn test_relay_envelope_signature_roundtrip() {
+        let sender_key = generate_keypair();
+        let recipient_public = generate_keypair().verifying_key().to_bytes();
+        let envelope = encrypt_message(&sender_key, &recipient_public, b"relay me").unwrap();
+        let data = crate::message::encode_envelope(&envelope).unwrap();
+
+        let signature = sign_relay_envelope(&data, &sender_key).unwrap();
+        assert!(verify_envelope_signature(&data, &signature).is_ok());
+
+        // Nobody else can vouch for the envelope, and a signature by another
+        // key does not verify against the sender inside it.
+        let other_key = generate_keypair();
+        assert!(sign_relay_envelope(&data, &other_key).is_err());
+        let forged = other_key.sign(&data).to_bytes().to_vec();
+        assert!(verify_envelope_signature(&data, &forged).is_err());
+        assert!(verify_envelope_signature(b"opaque onion layer", &signature).is_err());
+    }
+
     #[test]
     fn test_tampered_envelope_fails_verification() {
         let sender_key = generate_keypair();
@@ -0,0 +1,372 @@
+//! Chunked file transfer with streaming reassembly
+//!
+//! A file is split into fixed-size chunks and each chunk is sealed on its own
+//! with `seal_file`, so every chunk carries the sender's signature and can be
+//! verified and decrypted the moment it arrives. The sealed plaintext starts
+//! with a header binding the chunk to its file and position:
+//!
+//! `version || file_id_len (u16) || file_id || index (u32) || total (u32)
+//! || chunk_size (u32) || bytes`
+//!
+//! (integers big-endian). `FileReassembler` writes each chunk straight to its
+//! offset in a caller-provided sink, so a receiver holds at most one chunk in
+//! memory however large the file is.
+
+use super::file::{open_file, seal_file, FileCryptoError};
+use ed25519_dalek::SigningKey;
+use std::io::{Seek, SeekFrom, Write};
+use thiserror::Error;
+
+/// Current chunk header version.
+pub const FILE_CHUNK_VERSION: u8 = 1;
+/// Chunk size used when the sender does not pick one.
+pub const DEFAULT_FILE_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FileChunkError {
+    #[error(transparent)]
+    Crypto(#[from] FileCryptoError),
+    #[error("Malformed file chunk header")]
+    MalformedHeader,
+    #[error("Chunk belongs to file {0}")]
+    WrongFile(String),
+    #[error("Chunk {index} is outside the file's {total} chunks")]
+    IndexOutOfRange { index: u32, total: u32 },
+    #[error("Chunk layout does not match earlier chunks of the file")]
+    InconsistentLayout,
+    #[error("Chunk was sealed by a different sender than earlier chunks")]
+    SenderMismatch,
+    #[error("Chunk of {size} bytes exceeds the {budget} byte memory budget")]
+    OverBudget { size: usize, budget: usize },
+    #[error("File of {total} chunks exceeds the {max} chunk limit")]
+    TooManyChunks { total: u32, max: usize },
+    #[error("Failed to write chunk: {0}")]
+    Io(String),
+}
+
+/// One decrypted chunk and the identity that sealed it.
+#[derive(Debug, Clone)]
+pub struct OpenedFileChunk {
+    pub sender_public_key: [u8; 32],
+    pub file_id: String,
+    pub index: u32,
+    pub total: u32,
+    pub chunk_size: u32,
+    pub bytes: Vec<u8>,
+}
+
+/// Split `plaintext` into `chunk_size` pieces and seal each one for
+/// `recipient_public_key`. An empty file still produces one (empty) chunk.
+pub fn seal_file_chunks(
+    sender_signing_key: &SigningKey,
+    recipient_public_key: &[u8; 32],
+    file_id: &str,
+    plaintext: &[u8],
+    chunk_size: usize,
+) -> Result<Vec<Vec<u8>>, FileChunkError> {
+    let chunk_size = chunk_size.clamp(1, u32::MAX as usize);
+    if file_id.len() > u16::MAX as usize {
+        return Err(FileChunkError::MalformedHeader);
+    }
+    let pieces: Vec<&[u8]> = if plaintext.is_empty() {
+        vec![&[]]
+    } else {
+        plaintext.chunks(chunk_size).collect()
+    };
+    let total = u32::try_from(pieces.len()).map_err(|_| FileChunkError::MalformedHeader)?;
+
+    pieces
+        .into_iter()
+        .enumerate()
+        .map(|(index, bytes)| {
+            let mut chunk = Vec::with_capacity(15 + file_id.len() + bytes.len());
+            chunk.push(FILE_CHUNK_VERSION);
+            chunk.extend_from_slice(&(file_id.len() as u16).to_be_bytes());
+            chunk.extend_from_slice(file_id.as_bytes());
+            chunk.extend_from_slice(&(index as u32).to_be_bytes());
+            chunk.extend_from_slice(&total.to_be_bytes());
+            chunk.extend_from_slice(&(chunk_size as u32).to_be_bytes());
+            chunk.extend_from_slice(bytes);
+            Ok(seal_file(sender_signing_key, recipient_public_key, &chunk)?)
+        })
+        .collect()
+}
+
+/// Verify and decrypt one sealed chunk, then parse its header.
+pub fn open_file_chunk(
+    recipient_signing_key: &SigningKey,
+    data: &[u8],
+) -> Result<OpenedFileChunk, FileChunkError> {
+    fn take<'a>(buf: &'a [u8], pos: &mut usize, n: usize) -> Result<&'a [u8], FileChunkError> {
+        let field = buf
+            .get(*pos..*pos + n)
+            .ok_or(FileChunkError::MalformedHeader)?;
+        *pos += n;
+        Ok(field)
+    }
+    fn take_u32(buf: &[u8], pos: &mut usize) -> Result<u32, FileChunkError> {
+        let bytes = take(buf, pos, 4)?
+            .try_into()
+            .map_err(|_| FileChunkError::MalformedHeader)?;
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    let opened = open_file(recipient_signing_key, data)?;
+    let mut plaintext = opened.plaintext;
+
+    let mut pos = 0usize;
+    if take(&plaintext, &mut pos, 1)?[0] != FILE_CHUNK_VERSION {
+        return Err(FileChunkError::MalformedHeader);
+    }
+    let id_len = take(&plaintext, &mut pos, 2)?
+        .try_into()
+        .map(u16::from_be_bytes)
+        .map_err(|_| FileChunkError::MalformedHeader)? as usize;
+    let file_id = String::from_utf8(take(&plaintext, &mut pos, id_len)?.to_vec())
+        .map_err(|_| FileChunkError::MalformedHeader)?;
+    let index = take_u32(&plaintext, &mut pos)?;
+    let total = take_u32(&plaintext, &mut pos)?;
+    let chunk_size = take_u32(&plaintext, &mut pos)?;
+    let bytes = plaintext.split_off(pos);
+
+    if total == 0 || chunk_size == 0 || bytes.len() > chunk_size as usize {
+        return Err(FileChunkError::MalformedHeader);
+    }
+    if index >= total {
+        return Err(FileChunkError::IndexOutOfRange { index, total });
+    }
+    Ok(OpenedFileChunk {
+        sender_public_key: opened.sender_public_key,
+        file_id,
+        index,
+        total,
+        chunk_size,
+        bytes,
+    })
+}
+
+/// Where a streaming receive stands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileReassemblyStatus {
+    /// 0 until the first chunk arrives.
+    pub total_chunks: u32,
+    pub received_chunks: u32,
+    /// Indices not yet received, ascending.
+    pub missing_chunks: Vec<u32>,
+    pub bytes_written: u64,
+    pub complete: bool,
+}
+
+/// Writes the chunks of one file to `sink` as they arrive, in any order.
+///
+/// Only the chunk being written is ever held in memory: chunks whose sealed
+/// size exceeds `memory_budget` are refused before decryption. The first
+/// chunk fixes the sender, chunk count and chunk size; later chunks that
+/// disagree are refused. Received chunks are tracked with one byte each, so
+/// a file may have at most `memory_budget` chunks; a first chunk claiming
+/// more is refused before the table is allocated.
+pub struct FileReassembler<W: Write + Seek> {
+    file_id: String,
+    sink: W,
+    memory_budget: usize,
+    sender: Option<[u8; 32]>,
+    chunk_size: u32,
+    received: Vec<bool>,
+    bytes_written: u64,
+}
+
+impl<W: Write + Seek> FileReassembler<W> {
+    pub fn new(file_id: impl Into<String>, sink: W, memory_budget: usize) -> Self {
+        Self {
+            file_id: file_id.into(),
+            sink,
+            memory_budget,
+            sender: None,
+            chunk_size: 0,
+            received: Vec::new(),
+            bytes_written: 0,
+        }
+    }
+
+    pub fn file_id(&self) -> &str {
+        &self.file_id
+    }
+
+    /// Sender of the chunks accepted so far.
+    pub fn sender_public_key(&self) -> Option<[u8; 32]> {
+        self.sender
+    }
+
+    /// Verify, decrypt and write one sealed chunk. Returns false for a
+    /// duplicate of a chunk already written.
+    pub fn accept_sealed(
+        &mut self,
+        recipient_signing_key: &SigningKey,
+        data: &[u8],
+    ) -> Result<bool, FileChunkError> {
+        if data.len() > self.memory_budget {
+            return Err(FileChunkError::OverBudget {
+                size: data.len(),
+                budget: self.memory_budget,
+            });
+        }
+        let chunk = open_file_chunk(recipient_signing_key, data)?;
+        self.accept(chunk)
+    }
+
+    fn accept(&mut self, chunk: OpenedFileChunk) -> Result<bool, FileChunkError> {
+        if chunk.file_id != self.file_id {
+            return Err(FileChunkError::WrongFile(chunk.file_id));
+        }
+        match self.sender {
+            Some(sender) if sender != chunk.sender_public_key => {
+                return Err(FileChunkError::SenderMismatch)
+            }
+            Some(_) => {
+                if chunk.total as usize != self.received.len()
+                    || chunk.chunk_size != self.chunk_size
+                {
+                    return Err(FileChunkError::InconsistentLayout);
+                }
+            }
+            None => {
+                if chunk.total as usize > self.memory_budget {
+                    return Err(FileChunkError::TooManyChunks {
+                        total: chunk.total,
+                        max: self.memory_budget,
+                    });
+                }
+                self.sender = Some(chunk.sender_public_key);
+                self.chunk_size = chunk.chunk_size;
+                self.received = vec![false; chunk.total as usize];
+            }
+        }
+        // Every chunk but the last is full, so offsets never overlap.
+        let is_last = chunk.index + 1 == chunk.total;
+        if !is_last && chunk.bytes.len() != chunk.chunk_size as usize {
+            return Err(FileChunkError::InconsistentLayout);
+        }
+        if self.received[chunk.index as usize] {
+            return Ok(false);
+        }
+
+        let offset = u64::from(chunk.index) * u64::from(chunk.chunk_size);
+        self.sink
+            .seek(SeekFrom::Start(offset))
+            .and_then(|_| self.sink.write_all(&chunk.bytes))
+            .map_err(|e| FileChunkError::Io(e.to_string()))?;
+        self.received[chunk.index as usize] = true;
+        self.bytes_written += chunk.bytes.len() as u64;
+        Ok(true)
+    }
+
+    pub fn status(&self) -> FileReassemblyStatus {
+        let missing_chunks: Vec<u32> = self
+            .received
+            .iter()
+            .enumerate()
+            .filter(|(_, received)| !**received)
+            .map(|(index, _)| index as u32)
+            .collect();
+        FileReassemblyStatus {
+            total_chunks: self.received.len() as u32,
+            received_chunks: (self.received.len() - missing_chunks.len()) as u32,
+            complete: !self.received.is_empty() && missing_chunks.is_empty(),
+            missing_chunks,
+            bytes_written: self.bytes_written,
+        }
+    }
+
+    /// Flush the sink and hand it back with the final status. An incomplete
+    /// file is left as written; the status lists the absent chunks.
+    pub fn finish(mut self) -> Result<(W, FileReassemblyStatus), FileChunkError> {
+        self.sink
+            .flush()
+            .map_err(|e| FileChunkError::Io(e.to_string()))?;
+        let status = self.status();
+        Ok((self.sink, status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityKeys;
+
+    #[test]
+    fn test_reassembles_file_larger_than_memory_budget() {
+        let alice = IdentityKeys::generate();
+        let bob = IdentityKeys::generate();
+        let bob_pk = bob.signing_key.verifying_key().to_bytes();
+        let data: Vec<u8> = (0..1_000_000u32).map(|i| (i % 251) as u8).collect();
+        let budget = 64 * 1024;
+
+        let mut chunks =
+            seal_file_chunks(&alice.signing_key, &bob_pk, "file-1", &data, 16 * 1024).unwrap();
+        assert_eq!(chunks.len(), 62);
+        assert!(chunks.iter().all(|c| c.len() <= budget));
+        chunks.reverse();
+        let held_back = chunks.remove(10);
+
+        let output = tempfile::NamedTempFile::new().unwrap();
+        let mut reassembler = FileReassembler::new("file-1", output.reopen().unwrap(), budget);
+        for chunk in &chunks {
+            assert!(reassembler.accept_sealed(&bob.signing_key, chunk).unwrap());
+        }
+        assert!(!reassembler
+            .accept_sealed(&bob.signing_key, &chunks[0])
+            .unwrap());
+        let status = reassembler.status();
+        assert!(!status.complete);
+        assert_eq!(status.missing_chunks, vec![51]);
+
+        // A chunk sealed by someone else, or for another file, is refused.
+        let mallory = IdentityKeys::generate();
+        let forged =
+            seal_file_chunks(&mallory.signing_key, &bob_pk, "file-1", b"evil", 16 * 1024).unwrap();
+        assert!(reassembler
+            .accept_sealed(&bob.signing_key, &forged[0])
+            .is_err());
+        let other = seal_file_chunks(&alice.signing_key, &bob_pk, "file-2", b"x", 16).unwrap();
+        assert_eq!(
+            reassembler.accept_sealed(&bob.signing_key, &other[0]),
+            Err(FileChunkError::WrongFile("file-2".to_string()))
+        );
+        let oversized = seal_file_chunks(&alice.signing_key, &bob_pk, "file-1", &data, budget)
+            .unwrap()
+            .remove(0);
+        assert!(matches!(
+            reassembler.accept_sealed(&bob.signing_key, &oversized),
+            Err(FileChunkError::OverBudget { .. })
+        ));
+
+        reassembler
+            .accept_sealed(&bob.signing_key, &held_back)
+            .unwrap();
+        let (_, status) = reassembler.finish().unwrap();
+        assert!(status.complete);
+        assert_eq!(status.bytes_written, data.len() as u64);
+        assert_eq!(std::fs::read(output.path()).unwrap(), data);
+    }
+
+    #[test]
+    fn test_chunk_count_above_budget_refused_before_allocation() {
+        let alice = IdentityKeys::generate();
+        let bob = IdentityKeys::generate();
+        let bob_pk = bob.signing_key.verifying_key().to_bytes();
+        // Tiny chunks: each sealed chunk fits the budget, but there are
+        // more of them than the budget allows tracking.
+        let chunks =
+            seal_file_chunks(&alice.signing_key, &bob_pk, "file-1", &[7u8; 2048], 1).unwrap();
+        let mut reassembler =
+            FileReassembler::new("file-1", std::io::Cursor::new(Vec::new()), 1024);
+        assert_eq!(
+            reassembler.accept_sealed(&bob.signing_key, &chunks[0]),
+            Err(FileChunkError::TooManyChunks {
+                total: 2048,
+                max: 1024
+            })
+        );
+        assert_eq!(reassembler.status().total_chunks, 0);
+    }
+}
@@ -1,7 +1,10 @@
 // Cryptography module — message encryption and key exchange
 
+pub mod aead;
 pub mod backup;
 pub mod encrypt;
+pub mod file;
+pub mod file_chunks;
 pub mod negotiation;
 pub mod pq;
 pub mod ratchet;
@@ -13,11 +16,19 @@ mod proptest_harness;
 #[cfg(feature = "kani-proofs")]
 mod kani_proofs;
 
+pub use aead::{AeadSuite, UnknownAeadSuite, DEFAULT_AEAD_SUITE};
 pub use encrypt::{
     decrypt_message, decrypt_message_ratcheted, decrypt_with_ratchet_fallback,
     ed25519_public_to_x25519, ed25519_to_x25519_secret, encrypt_message, encrypt_message_ratcheted,
-    encrypt_with_ratchet_fallback, is_ratcheted_envelope, sign_envelope, sign_envelope_v2,
-    validate_ed25519_public_key, verify_envelope, verify_envelope_v2,
+    encrypt_message_with_suite, encrypt_with_ratchet_fallback, encrypt_with_ratchet_fallback_suite,
+    is_ratcheted_envelope, sign_envelope, sign_envelope_v2, sign_relay_envelope,
+    validate_ed25519_public_key, verify_envelope, verify_envelope_signature, verify_envelope_v2,
+    MalformedEnvelope,
+};
+pub use file::{open_file, seal_file, FileCryptoError, OpenedFile};
+pub use file_chunks::{
+    open_file_chunk, seal_file_chunks, FileChunkError, FileReassembler, FileReassemblyStatus,
+    OpenedFileChunk, DEFAULT_FILE_CHUNK_SIZE,
 };
 pub use ratchet::{RatchetEncryptResult, RatchetKey, RatchetSession};
 pub use session_manager::{RatchetSessionManager, SerializableRatchetSession};
@@ -18,7 +18,11 @@
 /// [64] signature
 /// [2]  ciphertext_len (LE u16)
 /// [N]  ciphertext
+///
+/// Optional trailing extensions: ratchet, PQ, then a one-byte AEAD suite id
+/// (present only for non-default suites).
 use super::{DriftError, DRIFT_VERSION};
+use crate::crypto::aead::DEFAULT_AEAD_SUITE;
 use crate::message::Envelope;
 use ed25519_dalek::Signer;
 use uuid::Uuid;
@@ -63,7 +67,7 @@ pub struct DriftEnvelope {
     pub sender_public_key: [u8; 32],
     /// Ephemeral X25519 public key for ECDH (32 bytes)
     pub ephemeral_public_key: [u8; 32],
-    /// XChaCha20-Poly1305 nonce (24 bytes)
+    /// AEAD nonce (24 bytes)
     pub nonce: [u8; 24],
     /// Ed25519 signature over everything except signature itself (64 bytes)
     pub signature: [u8; 64],
@@ -87,6 +91,11 @@ pub struct DriftEnvelope {
     pub pq_encaps_key: Option<Vec<u8>>,
     /// Transcript hash for post-quantum verification
     pub transcript_hash: Option<Vec<u8>>,
+
+    // AEAD suite extension
+    /// AEAD cipher suite of the ciphertext (see `crypto::aead`). Only
+    /// serialized when it differs from the default.
+    pub aead_suite: u8,
 }
 
 /// Message type enumeration for Drift Envelopes
@@ -216,6 +225,15 @@ impl DriftEnvelope {
             append_blob(&mut buf, &self.pq_kem_ciphertext);
             append_blob(&mut buf, &self.pq_encaps_key);
             append_blob(&mut buf, &self.transcript_hash);
+        } else if self.aead_suite != DEFAULT_AEAD_SUITE {
+            buf.push(0x00); // no PQ extension, AEAD suite byte follows
+        }
+
+        // AEAD suite extension (optional, backward compatible)
+        // Omitted for the default suite so those envelopes are byte-identical
+        // to what older nodes produce.
+        if self.aead_suite != DEFAULT_AEAD_SUITE {
+            buf.push(self.aead_suite);
         }
 
         Ok(buf)
@@ -406,6 +424,9 @@ impl DriftEnvelope {
             (None, None, None, None)
         };
 
+        // AEAD suite extension (absent = default suite)
+        let aead_suite = data.get(offset).copied().unwrap_or(DEFAULT_AEAD_SUITE);
+
         Ok(DriftEnvelope {
             version,
             envelope_type,
@@ -427,6 +448,7 @@ impl DriftEnvelope {
             pq_kem_ciphertext,
             pq_encaps_key,
             transcript_hash,
+            aead_suite,
         })
     }
 
@@ -470,6 +492,7 @@ impl DriftEnvelope {
             ciphertext: self.ciphertext.clone(),
             ratchet_dh_public: self.ratchet_dh_public.map(|k| k.to_vec()),
             ratchet_message_number: self.ratchet_message_number,
+            aead_suite: self.aead_suite,
         }
     }
 }
@@ -548,6 +571,7 @@ impl DriftEnvelope {
             pq_kem_ciphertext: None,
             pq_encaps_key: None,
             transcript_hash: None,
+            aead_suite: legacy.aead_suite,
         };
 
         // Sign the envelope
@@ -628,6 +652,7 @@ impl DriftEnvelope {
             pq_kem_ciphertext: v2.pq_kem_ciphertext,
             pq_encaps_key: v2.pq_encaps_key,
             transcript_hash: v2.transcript_hash,
+            aead_suite: DEFAULT_AEAD_SUITE,
         };
 
         // Sign the envelope
@@ -712,6 +737,13 @@ impl DriftEnvelope {
             hash_blob(&mut hasher, &self.pq_kem_ciphertext);
             hash_blob(&mut hasher, &self.pq_encaps_key);
             hash_blob(&mut hasher, &self.transcript_hash);
+        } else if self.aead_suite != DEFAULT_AEAD_SUITE {
+            hasher.update(&[0x00]);
+        }
+
+        // AEAD suite extension (if non-default)
+        if self.aead_suite != DEFAULT_AEAD_SUITE {
+            hasher.update(&[self.aead_suite]);
         }
 
         let hash = hasher.finalize();
@@ -748,6 +780,7 @@ mod tests {
             pq_kem_ciphertext: None,
             pq_encaps_key: None,
             transcript_hash: None,
+            aead_suite: DEFAULT_AEAD_SUITE,
         }
     }
 
@@ -1009,4 +1042,23 @@ mod tests {
         let restored = DriftEnvelope::from_bytes(&bytes).unwrap();
         assert!(restored.compressed);
     }
+
+    #[test]
+    fn test_aead_suite_extension_roundtrip() {
+        let default_env = make_test_envelope();
+        let default_len = default_env.to_bytes().unwrap().len();
+
+        let mut env = make_test_envelope();
+        env.aead_suite = crate::crypto::AeadSuite::ChaCha20Poly1305.as_u8();
+        let bytes = env.to_bytes().unwrap();
+        // Empty PQ flag plus the suite byte
+        assert_eq!(bytes.len(), default_len + 2);
+        assert_eq!(DriftEnvelope::from_bytes(&bytes).unwrap(), env);
+
+        // Alongside a PQ extension the suite byte follows it directly
+        env.suite = Some(0x02);
+        env.transcript_hash = Some(vec![7u8; 32]);
+        let restored = DriftEnvelope::from_bytes(&env.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored, env);
+    }
 }
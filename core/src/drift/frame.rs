@@ -4,9 +4,14 @@ use crc32fast::Hasher;
 
 /// Maximum time to wait for a complete frame to arrive (Slow Loris mitigation).
 /// If a sender trickles bytes slower than this deadline, the connection is dropped
-/// to prevent transport slot exhaustion.
+/// to prevent transport slot exhaustion. This is the default for fast stream
+/// transports (TCP, WiFi); see `TransportType::frame_read_timeout`.
 pub const FRAME_READ_TIMEOUT: web_time::Duration = web_time::Duration::from_secs(5);
 
+/// Frame read deadline for BLE links, whose throughput is low enough that a
+/// full-size frame legitimately takes several seconds to arrive.
+pub const BLE_FRAME_READ_TIMEOUT: web_time::Duration = web_time::Duration::from_secs(30);
+
 /// Maximum allowed frame payload size (64 KB). Prevents memory exhaustion
 /// from a malicious length field claiming enormous payloads.
 pub const FRAME_MAX_PAYLOAD: usize = 65_535;
@@ -168,12 +173,17 @@ impl DriftFrame {
 
     /// Read a complete frame from an async byte stream with timeout.
     ///
-    /// Enforces `FRAME_READ_TIMEOUT` on the entire read operation to prevent
-    /// Slow Loris attacks where a malicious peer trickles bytes to tie up
-    /// transport slots indefinitely.
+    /// Enforces `read_timeout` on the entire read operation, header and body
+    /// together, to prevent Slow Loris attacks where a malicious peer trickles
+    /// bytes to tie up transport slots indefinitely.
+    ///
+    /// On `Timeout` the stream is left part-way through a frame and can no
+    /// longer be resynchronised; the caller must drop the connection.
     ///
     /// # Arguments
     /// * `reader` - Any async reader (socket, BLE stream, etc.)
+    /// * `read_timeout` - Deadline for the whole frame, usually
+    ///   `TransportType::frame_read_timeout()` for the link being read
     ///
     /// # Returns
     /// * `Ok(DriftFrame)` if a valid frame was read within the deadline
@@ -182,10 +192,11 @@ impl DriftFrame {
     #[cfg(not(target_arch = "wasm32"))]
     pub async fn read_with_timeout<R: tokio::io::AsyncReadExt + Unpin>(
         reader: &mut R,
+        read_timeout: web_time::Duration,
     ) -> Result<Self, DriftError> {
         use tokio::time::timeout;
 
-        timeout(FRAME_READ_TIMEOUT, async {
+        timeout(read_timeout, async {
             // Read length header (2 bytes)
             let mut len_buf = [0u8; 2];
             reader
@@ -234,6 +245,35 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_read_with_timeout_trips_on_stalled_reader() {
+        use tokio::io::AsyncWriteExt;
+
+        let bytes = make_test_frame().to_bytes().unwrap();
+        let (mut writer, mut reader) = tokio::io::duplex(1024);
+        // Send the header and part of the body, then stall with the link open.
+        writer.write_all(&bytes[..5]).await.unwrap();
+
+        let result =
+            DriftFrame::read_with_timeout(&mut reader, web_time::Duration::from_millis(50)).await;
+        assert!(matches!(result, Err(DriftError::Timeout)));
+        drop(writer);
+    }
+
+    #[tokio::test]
+    async fn test_read_with_timeout_completes_for_prompt_reader() {
+        use tokio::io::AsyncWriteExt;
+
+        let frame = make_test_frame();
+        let (mut writer, mut reader) = tokio::io::duplex(1024);
+        writer.write_all(&frame.to_bytes().unwrap()).await.unwrap();
+
+        let read = DriftFrame::read_with_timeout(&mut reader, FRAME_READ_TIMEOUT)
+            .await
+            .unwrap();
+        assert_eq!(read, frame);
+    }
+
     #[test]
     fn test_frame_type_conversion() {
         assert_eq!(FrameType::Data.as_u8(), 0x01);
@@ -20,6 +20,7 @@ pub mod compress;
 pub mod envelope;
 pub mod frame;
 pub mod policy;
+pub mod portable;
 pub mod rate_limit;
 pub mod relay;
 pub mod sketch;
@@ -27,17 +28,21 @@ pub mod store;
 pub mod sync;
 
 pub use envelope::{DriftEnvelope, EnvelopeType};
-pub use frame::{DriftFrame, FrameType, FRAME_MAX_PAYLOAD, FRAME_READ_TIMEOUT};
+pub use frame::{
+    DriftFrame, FrameType, BLE_FRAME_READ_TIMEOUT, FRAME_MAX_PAYLOAD, FRAME_READ_TIMEOUT,
+};
 
 /// Read a drift frame from an async stream with timeout protection.
 /// Wraps `DriftFrame::read_with_timeout` for convenient import.
 #[cfg(not(target_arch = "wasm32"))]
 pub async fn read_frame_with_timeout<R: tokio::io::AsyncReadExt + Unpin>(
     reader: &mut R,
+    read_timeout: web_time::Duration,
 ) -> Result<DriftFrame, DriftError> {
-    DriftFrame::read_with_timeout(reader).await
+    DriftFrame::read_with_timeout(reader, read_timeout).await
 }
 pub use policy::{DeviceState, PolicyEngine, PolicyError, RelayProfile};
+pub use portable::{decode_portable, encode_portable, PORTABLE_MAX_CHARS};
 pub use rate_limit::SyncRateLimiter;
 pub use relay::{
     DropReason, MaintenanceReport, NetworkState, RelayConfig, RelayDecision, RelayEngine,
@@ -78,6 +83,12 @@ pub enum DriftError {
     #[error("Frame read timeout — possible Slow Loris attack")]
     Timeout,
 
+    #[error("Portable envelope is {chars} characters (max {max})")]
+    PortableTooLarge { chars: usize, max: usize },
+
+    #[error("Invalid Base45 text")]
+    InvalidBase45,
+
     #[error("IO error: {0}")]
     IoError(String),
 }
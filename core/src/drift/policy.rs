@@ -1,6 +1,6 @@
 //! Smart auto-adjust system for relay aggressiveness based on device state
 
-use super::relay::RelayConfig;
+use super::relay::{DropReason, RelayConfig};
 use thiserror::Error;
 
 /// Device state information (provided by platform layer)
@@ -44,6 +44,8 @@ pub struct PolicyEngine {
     battery_floor_override: Option<u8>,
     /// Current computed profile
     current_profile: RelayProfile,
+    /// Most recent state reported by the platform (None until the first update)
+    device_state: Option<DeviceState>,
 }
 
 impl PolicyEngine {
@@ -54,15 +56,33 @@ impl PolicyEngine {
             relay_budget_override: None,
             battery_floor_override: None,
             current_profile: RelayProfile::Standard,
+            device_state: None,
         }
     }
 
     /// Update device state and recompute profile
     pub fn update_device_state(&mut self, state: &DeviceState) -> RelayProfile {
         self.current_profile = self.compute_profile(state);
+        self.device_state = Some(state.clone());
         self.current_profile
     }
 
+    /// Whether relaying for others should be refused right now.
+    ///
+    /// Relaying is refused only on cellular (no WiFi), off charge and below
+    /// the profile's battery floor; every other state relays within budget.
+    /// Before any device state is reported nothing is refused.
+    pub fn relay_refusal(&self) -> Option<DropReason> {
+        let state = self.device_state.as_ref()?;
+        let on_cellular = !state.has_wifi;
+        let below_floor = state.battery_percent < self.to_relay_config().battery_floor_percent;
+        if on_cellular && !state.is_charging && below_floor {
+            Some(DropReason::LowBattery)
+        } else {
+            None
+        }
+    }
+
     /// Get current scan interval in milliseconds
     pub fn scan_interval_ms(&self) -> u64 {
         if let Some(override_val) = self.scan_interval_override {
@@ -190,6 +210,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_relay_refused_on_low_battery_cellular() {
+        let mut engine = PolicyEngine::new();
+        assert_eq!(engine.relay_refusal(), None);
+
+        engine.update_device_state(&make_device_state(10, false, false, false));
+        assert_eq!(engine.relay_refusal(), Some(DropReason::LowBattery));
+
+        // Plugged in on WiFi relays regardless of charge level.
+        engine.update_device_state(&make_device_state(10, true, true, false));
+        assert_eq!(engine.relay_refusal(), None);
+
+        // A healthy battery on cellular still relays.
+        engine.update_device_state(&make_device_state(80, false, false, false));
+        assert_eq!(engine.relay_refusal(), None);
+    }
+
     #[test]
     fn test_profile_maximum() {
         let mut engine = PolicyEngine::new();
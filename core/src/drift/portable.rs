@@ -0,0 +1,146 @@
+//! Portable envelopes — a DriftFrame encoded as Base45 text for sneakernet
+//! transfer (QR code, file, paper) with no network involved.
+//!
+//! Base45 (RFC 9285) maps onto the QR alphanumeric character set, so the
+//! encoded string packs into a QR code at 5.5 bits per character instead of
+//! the 8 bits byte mode would cost.
+
+use super::{DriftError, DriftFrame, FrameType};
+
+/// Largest alphanumeric payload a single QR code can hold (version 40,
+/// error correction level L).
+pub const PORTABLE_MAX_CHARS: usize = 4296;
+
+const BASE45_ALPHABET: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+/// Encode bytes as Base45 (RFC 9285).
+pub fn base45_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(2) * 3);
+    for chunk in data.chunks(2) {
+        let (mut n, digits) = match *chunk {
+            [a, b] => ((a as u32) * 256 + b as u32, 3),
+            [a] => (a as u32, 2),
+            _ => unreachable!(),
+        };
+        for _ in 0..digits {
+            out.push(BASE45_ALPHABET[(n % 45) as usize] as char);
+            n /= 45;
+        }
+    }
+    out
+}
+
+/// Decode a Base45 (RFC 9285) string.
+pub fn base45_decode(s: &str) -> Result<Vec<u8>, DriftError> {
+    let values = s
+        .bytes()
+        .map(|c| {
+            BASE45_ALPHABET
+                .iter()
+                .position(|&a| a == c)
+                .map(|v| v as u32)
+                .ok_or(DriftError::InvalidBase45)
+        })
+        .collect::<Result<Vec<u32>, _>>()?;
+
+    let mut out = Vec::with_capacity(values.len() / 3 * 2 + 1);
+    for chunk in values.chunks(3) {
+        match *chunk {
+            [c, d, e] => {
+                let n = c + d * 45 + e * 45 * 45;
+                if n > u16::MAX as u32 {
+                    return Err(DriftError::InvalidBase45);
+                }
+                out.extend_from_slice(&(n as u16).to_be_bytes());
+            }
+            [c, d] => {
+                let n = c + d * 45;
+                if n > u8::MAX as u32 {
+                    return Err(DriftError::InvalidBase45);
+                }
+                out.push(n as u8);
+            }
+            _ => return Err(DriftError::InvalidBase45),
+        }
+    }
+    Ok(out)
+}
+
+/// Wrap envelope bytes in a data DriftFrame and Base45-encode it. Fails with
+/// `PortableTooLarge` if the result would not fit in a single QR code.
+pub fn encode_portable(envelope_data: &[u8]) -> Result<String, DriftError> {
+    let frame = DriftFrame {
+        frame_type: FrameType::Data,
+        payload: envelope_data.to_vec(),
+    }
+    .to_bytes()?;
+    let encoded = base45_encode(&frame);
+    if encoded.len() > PORTABLE_MAX_CHARS {
+        return Err(DriftError::PortableTooLarge {
+            chars: encoded.len(),
+            max: PORTABLE_MAX_CHARS,
+        });
+    }
+    Ok(encoded)
+}
+
+/// Reverse of `encode_portable`: decode, check the frame CRC and return the
+/// envelope bytes. Line breaks around the text (e.g. from a file) are ignored;
+/// spaces are not, as space is part of the Base45 alphabet.
+pub fn decode_portable(s: &str) -> Result<Vec<u8>, DriftError> {
+    let frame = DriftFrame::from_bytes(&base45_decode(s.trim_matches(['\r', '\n']))?)?;
+    if frame.frame_type != FrameType::Data {
+        return Err(DriftError::InvalidFrameType(frame.frame_type.as_u8()));
+    }
+    Ok(frame.payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base45_rfc9285_vectors() {
+        assert_eq!(base45_encode(b"AB"), "BB8");
+        assert_eq!(base45_encode(b"Hello!!"), "%69 VD92EX0");
+        assert_eq!(base45_encode(b"base-45"), "UJCLQE7W581");
+        assert_eq!(base45_decode("QED8WEX0").unwrap(), b"ietf!");
+        assert_eq!(base45_decode("").unwrap(), b"");
+    }
+
+    #[test]
+    fn test_base45_rejects_invalid_input() {
+        assert!(base45_decode("abc").is_err()); // lowercase is not in the alphabet
+        assert!(base45_decode("GGW").is_err()); // decodes past u16::MAX
+        assert!(base45_decode("A").is_err()); // dangling single character
+    }
+
+    #[test]
+    fn test_portable_roundtrip_and_size_limit() {
+        let envelope = vec![0xA5u8; 512];
+        let encoded = encode_portable(&envelope).unwrap();
+        assert!(encoded.len() <= PORTABLE_MAX_CHARS);
+        assert_eq!(
+            decode_portable(&format!("{}\n", encoded)).unwrap(),
+            envelope
+        );
+
+        let too_big = vec![0u8; PORTABLE_MAX_CHARS];
+        assert!(matches!(
+            encode_portable(&too_big),
+            Err(DriftError::PortableTooLarge {
+                max: PORTABLE_MAX_CHARS,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_portable_detects_corruption() {
+        let encoded = encode_portable(b"sealed envelope").unwrap();
+        let mut corrupted = encoded.into_bytes();
+        corrupted[6] = if corrupted[6] == b'0' { b'1' } else { b'0' };
+        let corrupted = String::from_utf8(corrupted).unwrap();
+        assert!(decode_portable(&corrupted).is_err());
+    }
+}
@@ -76,6 +76,9 @@ pub enum DropReason {
     NetworkDormant,
     LowPriority,
     StoreFull,
+    /// Refused by the device policy: on cellular, not charging and below
+    /// the profile's battery floor.
+    LowBattery,
 }
 
 /// Relay engine errors
@@ -173,6 +176,8 @@ impl RelayEngine {
                 enabled: true,
                 message_size: 512,
                 rate_per_minute: rate_per_minute.max(1),
+                size_distribution: crate::privacy::SizeDistribution::chat(),
+                real_activity_cooldown_ms: crate::privacy::cover::DEFAULT_REAL_ACTIVITY_COOLDOWN_MS,
             };
             self.cover_scheduler = CoverTrafficScheduler::new(config).ok();
         } else {
@@ -180,6 +185,13 @@ impl RelayEngine {
         }
     }
 
+    /// Hold back cover traffic while real messages are flowing.
+    pub fn note_real_activity(&mut self) {
+        if let Some(scheduler) = self.cover_scheduler.as_mut() {
+            scheduler.note_real_activity();
+        }
+    }
+
     /// Set the reputation manager for abuse detection
     pub fn set_reputation_manager(
         &mut self,
@@ -504,6 +516,7 @@ mod tests {
             pq_kem_ciphertext: None,
             pq_encaps_key: None,
             transcript_hash: None,
+            aead_suite: crate::crypto::aead::DEFAULT_AEAD_SUITE,
         }
     }
 
@@ -0,0 +1,161 @@
+// Contact QR payloads
+//
+// Two encodings are in the field: the web UI shows `SCM:<peer_id>:<public_key>`
+// (optionally followed by `:<nickname>`), and the mobile apps show a JSON
+// object with `peer_id`, `public_key` and `nickname` fields. Either way the
+// public key is the identity; the PeerId is re-derived from it here so every
+// client dials exactly what the Rust transport will accept, and a payload
+// whose PeerId does not match its key is rejected.
+
+use super::nickname::{validate_nickname, NicknameError, DEFAULT_MAX_NICKNAME_GRAPHEMES};
+use thiserror::Error;
+
+/// Prefix of the compact text form.
+pub const CONTACT_QR_PREFIX: &str = "SCM:";
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ContactQrError {
+    #[error("Not an SCMessenger contact code")]
+    ForeignPayload,
+    #[error("Contact code is missing its public key")]
+    MissingPublicKey,
+    #[error("Contact code carries an invalid Ed25519 public key")]
+    InvalidPublicKey,
+    #[error("Contact code PeerId does not match its public key")]
+    PeerIdMismatch,
+    #[error("Contact code nickname is invalid: {0}")]
+    InvalidNickname(#[from] NicknameError),
+}
+
+/// A contact read from a QR code, with its PeerId derived locally.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContactQr {
+    pub public_key_hex: String,
+    pub peer_id: String,
+    pub nickname: Option<String>,
+}
+
+impl ContactQr {
+    /// Build the payload for `public_key_hex`, deriving its PeerId.
+    pub fn new(public_key_hex: &str, nickname: Option<String>) -> Result<Self, ContactQrError> {
+        let public_key_hex = public_key_hex.to_ascii_lowercase();
+        let peer_id = peer_id_from_public_key_hex(&public_key_hex)?;
+        let nickname = nickname.filter(|n| !n.is_empty());
+        if let Some(nickname) = &nickname {
+            validate_nickname(nickname, DEFAULT_MAX_NICKNAME_GRAPHEMES)?;
+        }
+        Ok(Self {
+            public_key_hex,
+            peer_id,
+            nickname,
+        })
+    }
+
+    /// The compact `SCM:` text form, as shown by the web UI.
+    pub fn to_payload(&self) -> String {
+        match &self.nickname {
+            Some(nickname) => format!(
+                "{}{}:{}:{}",
+                CONTACT_QR_PREFIX, self.peer_id, self.public_key_hex, nickname
+            ),
+            None => format!(
+                "{}{}:{}",
+                CONTACT_QR_PREFIX, self.peer_id, self.public_key_hex
+            ),
+        }
+    }
+}
+
+/// libp2p PeerId for a hex-encoded Ed25519 identity key.
+pub fn peer_id_from_public_key_hex(public_key_hex: &str) -> Result<String, ContactQrError> {
+    crate::crypto::validate_ed25519_public_key(public_key_hex)
+        .map_err(|_| ContactQrError::InvalidPublicKey)?;
+    let bytes = hex::decode(public_key_hex).map_err(|_| ContactQrError::InvalidPublicKey)?;
+    let key = libp2p::identity::ed25519::PublicKey::try_from_bytes(&bytes)
+        .map_err(|_| ContactQrError::InvalidPublicKey)?;
+    Ok(libp2p::identity::PublicKey::from(key)
+        .to_peer_id()
+        .to_string())
+}
+
+/// Parse a scanned contact code in either the `SCM:` or the mobile JSON form.
+pub fn parse_contact_qr(payload: &str) -> Result<ContactQr, ContactQrError> {
+    let payload = payload.trim();
+    let (claimed_peer_id, public_key_hex, nickname) =
+        if let Some(rest) = payload.strip_prefix(CONTACT_QR_PREFIX) {
+            let mut parts = rest.splitn(3, ':');
+            let peer_id = parts.next().unwrap_or_default().to_string();
+            let public_key = parts.next().unwrap_or_default().to_string();
+            let nickname = parts.next().map(str::to_string);
+            (peer_id, public_key, nickname)
+        } else if payload.starts_with('{') {
+            let json: serde_json::Value =
+                serde_json::from_str(payload).map_err(|_| ContactQrError::ForeignPayload)?;
+            let field = |name: &str| {
+                json.get(name)
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+                    .filter(|v| !v.is_empty())
+            };
+            let public_key = field("public_key").ok_or(ContactQrError::ForeignPayload)?;
+            let peer_id = field("peer_id")
+                .or_else(|| field("libp2p_peer_id"))
+                .unwrap_or_default();
+            (peer_id, public_key, field("nickname"))
+        } else {
+            return Err(ContactQrError::ForeignPayload);
+        };
+
+    if public_key_hex.is_empty() {
+        return Err(ContactQrError::MissingPublicKey);
+    }
+    let contact = ContactQr::new(&public_key_hex, nickname)?;
+    if !claimed_peer_id.is_empty() && claimed_peer_id != contact.peer_id {
+        return Err(ContactQrError::PeerIdMismatch);
+    }
+    Ok(contact)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityKeys;
+
+    #[test]
+    fn test_contact_qr_roundtrip_and_rejections() {
+        let keys = IdentityKeys::generate();
+        let pk = hex::encode(keys.signing_key.verifying_key().to_bytes());
+        let contact = ContactQr::new(&pk, Some("Ada: the first".into())).unwrap();
+        assert_eq!(contact.peer_id, keys.to_libp2p_peer_id().unwrap());
+        assert_eq!(parse_contact_qr(&contact.to_payload()).unwrap(), contact);
+
+        let json = serde_json::json!({
+            "version": "1.0",
+            "peer_id": contact.peer_id,
+            "public_key": pk,
+            "nickname": "",
+        })
+        .to_string();
+        let from_json = parse_contact_qr(&json).unwrap();
+        assert_eq!(from_json.peer_id, contact.peer_id);
+        assert_eq!(from_json.nickname, None);
+
+        assert_eq!(
+            parse_contact_qr("https://example.com/x"),
+            Err(ContactQrError::ForeignPayload)
+        );
+        assert_eq!(
+            parse_contact_qr("SCM:abc:"),
+            Err(ContactQrError::MissingPublicKey)
+        );
+        assert_eq!(
+            parse_contact_qr("SCM::zz"),
+            Err(ContactQrError::InvalidPublicKey)
+        );
+        let other = IdentityKeys::generate().to_libp2p_peer_id().unwrap();
+        assert_eq!(
+            parse_contact_qr(&format!("SCM:{}:{}", other, pk)),
+            Err(ContactQrError::PeerIdMismatch)
+        );
+    }
+}
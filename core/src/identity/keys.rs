@@ -3,7 +3,7 @@
 use anyhow::Result;
 use ed25519_dalek::{Signature as Ed25519Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use serde::{Deserialize, Serialize};
-use zeroize::Zeroize;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 /// Key pair for signing and verification
 #[derive(Clone)]
@@ -49,6 +49,16 @@ struct IdentityKeysV3Raw {
 }
 
 /// Identity keys (signing + dedicated encryption + hybrid post-quantum key agreement + ML-DSA)
+///
+/// Zeroized on drop (see the `Zeroize` impl below).
+///
+/// REVIEW CHECKLIST — every field here is secret. When adding a field, wipe
+/// it in `Zeroize::zeroize` and make sure any copy handed out (`to_bytes`,
+/// raw structs, temporaries) is zeroized by its holder too:
+/// - `signing_key`: Ed25519 seed
+/// - `x25519_encryption_secret`: X25519 static secret
+/// - `mlkem_keypair`: ML-KEM-768 private key and seed
+/// - `mldsa_keypair`: ML-DSA-65 private key
 #[derive(Clone)]
 pub struct IdentityKeys {
     pub signing_key: SigningKey,
@@ -57,15 +67,104 @@ pub struct IdentityKeys {
     pub mldsa_keypair: Option<crate::crypto::pq::mldsa::MlDsa65KeyPair>,
 }
 
+impl Zeroize for IdentityKeys {
+    fn zeroize(&mut self) {
+        // SigningKey has no in-place zeroize; replacing it drops the old key,
+        // which ed25519-dalek wipes.
+        self.signing_key = SigningKey::from_bytes(&[0u8; 32]);
+        self.x25519_encryption_secret.zeroize();
+        self.mlkem_keypair.zeroize();
+        if let Some(mldsa) = self.mldsa_keypair.as_mut() {
+            mldsa.zeroize();
+        }
+    }
+}
+
+impl Drop for IdentityKeys {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for IdentityKeys {}
+
+/// How often `generate_vanity_with_progress` reports progress.
+pub const VANITY_PROGRESS_INTERVAL: u64 = 100_000;
+
+/// Longest vanity prefix accepted. Each extra hex character multiplies the
+/// expected search by 16; 6 characters is ~16M attempts on average.
+pub const MAX_VANITY_PREFIX_LEN: usize = 6;
+
+/// Check that `prefix` is non-empty lowercase hex no longer than
+/// `MAX_VANITY_PREFIX_LEN`.
+pub fn validate_vanity_prefix(prefix: &str) -> Result<()> {
+    if prefix.is_empty() {
+        anyhow::bail!("Vanity prefix must not be empty");
+    }
+    if prefix.len() > MAX_VANITY_PREFIX_LEN {
+        anyhow::bail!(
+            "Vanity prefix is {} characters; at most {} are allowed",
+            prefix.len(),
+            MAX_VANITY_PREFIX_LEN
+        );
+    }
+    if !prefix
+        .bytes()
+        .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+    {
+        anyhow::bail!("Vanity prefix must be lowercase hex (0-9, a-f)");
+    }
+    Ok(())
+}
+
+fn random_signing_key() -> SigningKey {
+    use rand::RngCore;
+    let mut secret_key_bytes = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut secret_key_bytes);
+    let signing_key = SigningKey::from_bytes(&secret_key_bytes);
+    secret_key_bytes.zeroize();
+    signing_key
+}
+
 impl IdentityKeys {
     /// Generate new identity keys
     pub fn generate() -> Self {
-        use rand::RngCore;
-        let mut secret_key_bytes = [0u8; 32];
-        rand::rngs::OsRng.fill_bytes(&mut secret_key_bytes);
-        let signing_key = SigningKey::from_bytes(&secret_key_bytes);
-        secret_key_bytes.zeroize();
+        Self::with_signing_key(random_signing_key())
+    }
+
+    /// Generate identity keys whose `identity_id` starts with `prefix`.
+    ///
+    /// Returns `None` if the prefix is invalid (see `validate_vanity_prefix`)
+    /// or no match is found within `max_attempts`.
+    pub fn generate_vanity(prefix: &str, max_attempts: u64) -> Option<Self> {
+        Self::generate_vanity_with_progress(prefix, max_attempts, |_| {})
+    }
 
+    /// Like `generate_vanity`, calling `on_progress` with the attempt count
+    /// every `VANITY_PROGRESS_INTERVAL` attempts.
+    pub fn generate_vanity_with_progress(
+        prefix: &str,
+        max_attempts: u64,
+        mut on_progress: impl FnMut(u64),
+    ) -> Option<Self> {
+        validate_vanity_prefix(prefix).ok()?;
+        // identity_id depends only on the Ed25519 key, so search over that
+        // and derive the (expensive) X25519 / PQ material once at the end.
+        for attempt in 1..=max_attempts {
+            let signing_key = random_signing_key();
+            let hash = blake3::hash(&signing_key.verifying_key().to_bytes());
+            if hex::encode(hash.as_bytes()).starts_with(prefix) {
+                return Some(Self::with_signing_key(signing_key));
+            }
+            if attempt % VANITY_PROGRESS_INTERVAL == 0 {
+                on_progress(attempt);
+            }
+        }
+        None
+    }
+
+    fn with_signing_key(signing_key: SigningKey) -> Self {
+        use rand::RngCore;
         let mut x25519_bytes = [0u8; 32];
         rand::rngs::OsRng.fill_bytes(&mut x25519_bytes);
         let x25519_encryption_secret = x25519_dalek::StaticSecret::from(x25519_bytes);
@@ -94,6 +193,12 @@ impl IdentityKeys {
         hex::encode(hash.as_bytes())
     }
 
+    /// Six PGP words derived from the public key, for reading aloud when
+    /// verifying a contact (see `fingerprint_words`).
+    pub fn fingerprint_words(&self) -> Vec<String> {
+        fingerprint_words(&self.signing_key.verifying_key().to_bytes())
+    }
+
     /// Sign data with Ed25519
     pub fn sign(&self, data: &[u8]) -> Result<Vec<u8>> {
         let signature = self.signing_key.sign(data);
@@ -455,6 +560,27 @@ pub fn verify_bundle(bundle: &PublicKeyBundle) -> Result<()> {
     }
 }
 
+/// Number of words in an identity fingerprint (48 bits of the key hash).
+pub const FINGERPRINT_WORD_COUNT: usize = 6;
+
+/// Map an Ed25519 public key to `FINGERPRINT_WORD_COUNT` words from the PGP
+/// word list: Blake3 of the key, first bytes, alternating even/odd lists.
+pub fn fingerprint_words(public_key: &[u8]) -> Vec<String> {
+    let hash = blake3::hash(public_key);
+    hash.as_bytes()[..FINGERPRINT_WORD_COUNT]
+        .iter()
+        .enumerate()
+        .map(|(i, byte)| {
+            let list = if i % 2 == 0 {
+                &super::wordlist::EVEN_WORDS
+            } else {
+                &super::wordlist::ODD_WORDS
+            };
+            list[*byte as usize].to_string()
+        })
+        .collect()
+}
+
 /// Generate a Signal-style safety number from two public keys.
 ///
 /// Returns a 60-digit numeric string (12 groups of 5 digits, space-separated).
@@ -514,6 +640,47 @@ mod tests {
         assert!(keys.mldsa_keypair.is_some());
     }
 
+    #[test]
+    fn test_generate_vanity() {
+        let keys = IdentityKeys::generate_vanity("a", 10_000).expect("1/16 odds per attempt");
+        assert!(keys.identity_id().starts_with('a'));
+        assert!(keys.mldsa_keypair.is_some());
+
+        assert!(IdentityKeys::generate_vanity("ABC", 10).is_none());
+        assert!(IdentityKeys::generate_vanity("xyz", 10).is_none());
+        assert!(IdentityKeys::generate_vanity("", 10).is_none());
+        assert!(validate_vanity_prefix("0123456").is_err());
+        assert!(validate_vanity_prefix("c0ffee").is_ok());
+        // Practically unreachable within a couple of attempts.
+        assert!(IdentityKeys::generate_vanity("ffffff", 2).is_none());
+    }
+
+    #[test]
+    fn test_fingerprint_words_stable_and_distinct() {
+        let keys = IdentityKeys::generate();
+        let words = keys.fingerprint_words();
+        assert_eq!(words.len(), FINGERPRINT_WORD_COUNT);
+        assert_eq!(words, keys.fingerprint_words());
+        let restored = IdentityKeys::from_bytes(&keys.to_bytes()).unwrap();
+        assert_eq!(words, restored.fingerprint_words());
+
+        let other = IdentityKeys::generate();
+        assert_ne!(words, other.fingerprint_words());
+
+        // Position decides the list, so a repeated byte still reads as two
+        // different words.
+        let hash = blake3::hash(&[0u8; 32]);
+        let zero = fingerprint_words(&[0u8; 32]);
+        assert_eq!(
+            zero[0],
+            super::super::wordlist::EVEN_WORDS[hash.as_bytes()[0] as usize]
+        );
+        assert_eq!(
+            zero[1],
+            super::super::wordlist::ODD_WORDS[hash.as_bytes()[1] as usize]
+        );
+    }
+
     #[test]
     fn test_signing() {
         let keys = IdentityKeys::generate();
@@ -542,6 +709,20 @@ mod tests {
         assert!(!invalid);
     }
 
+    #[test]
+    fn test_zeroize_wipes_secret_fields() {
+        let mut keys = IdentityKeys::generate();
+        let copy = keys.clone();
+        keys.zeroize();
+
+        assert_eq!(keys.signing_key.to_bytes(), [0u8; 32]);
+        assert_eq!(keys.x25519_encryption_secret.to_bytes(), [0u8; 32]);
+        assert_eq!(keys.mlkem_keypair.seed, [0u8; 64]);
+        // Clones are independent; wiping one leaves the other usable.
+        assert_ne!(copy.signing_key.to_bytes(), [0u8; 32]);
+        assert!(copy.sign(b"still works").is_ok());
+    }
+
     #[test]
     fn test_serialization() {
         let keys = IdentityKeys::generate();
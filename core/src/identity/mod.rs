@@ -1,21 +1,49 @@
 // Identity & Crypto - Foundation for KERI support (Phase 4)
 
+pub mod contact_qr;
 pub mod keys;
+pub mod nickname;
 mod store;
-
-pub use keys::{sign_bundle, verify_bundle, IdentityKeys, KeyPair, PublicKeyBundle};
+mod wordlist;
+
+pub use contact_qr::{
+    parse_contact_qr, peer_id_from_public_key_hex, ContactQr, ContactQrError, CONTACT_QR_PREFIX,
+};
+pub use keys::{
+    fingerprint_words, sign_bundle, validate_vanity_prefix, verify_bundle, IdentityKeys, KeyPair,
+    PublicKeyBundle, FINGERPRINT_WORD_COUNT, MAX_VANITY_PREFIX_LEN, VANITY_PROGRESS_INTERVAL,
+};
+pub use nickname::{
+    normalize_nickname, validate_nickname, NicknameError, DEFAULT_MAX_NICKNAME_GRAPHEMES,
+};
 pub use store::{DeviceMetadata, IdentityStore};
 
 use crate::dspy::signatures::{blake3_hash, get_signature, signature_fingerprint};
 use crate::store::backend::StorageBackend;
 use anyhow::Result;
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use zeroize::Zeroize;
+
+/// An identity held in the keyring but not currently active.
+struct InactiveIdentity {
+    keys: IdentityKeys,
+    nickname: Option<String>,
+}
+
 /// Manages node identity and cryptographic keys
+///
+/// One identity is active at a time; everything that signs, encrypts or
+/// decrypts uses it. Further identities (personas) live in a keyring keyed
+/// by public key hex and can be made active with `switch_identity`. Device
+/// metadata belongs to the installation and is shared by all of them.
 pub struct IdentityManager {
     store: IdentityStore,
     keys: Option<IdentityKeys>,
     nickname: Option<String>,
     device_metadata: Option<DeviceMetadata>,
+    max_nickname_graphemes: usize,
+    inactive: BTreeMap<String, InactiveIdentity>,
 }
 
 impl IdentityManager {
@@ -26,6 +54,8 @@ impl IdentityManager {
             keys: None,
             nickname: None,
             device_metadata: None,
+            max_nickname_graphemes: DEFAULT_MAX_NICKNAME_GRAPHEMES,
+            inactive: BTreeMap::new(),
         }
     }
 
@@ -36,6 +66,8 @@ impl IdentityManager {
             keys: None,
             nickname: None,
             device_metadata: None,
+            max_nickname_graphemes: DEFAULT_MAX_NICKNAME_GRAPHEMES,
+            inactive: BTreeMap::new(),
         };
 
         tracing::debug!("IdentityManager::with_backend: Initializing with persistent storage");
@@ -63,6 +95,14 @@ impl IdentityManager {
         } else {
             tracing::debug!("IdentityManager::hydrate_from_store: No keys found in store");
         }
+        let active = self.public_key_hex();
+        for (keys, nickname) in self.store.load_keyring()? {
+            let public_key = keys.public_key_hex();
+            if Some(&public_key) != active.as_ref() {
+                self.inactive
+                    .insert(public_key, InactiveIdentity { keys, nickname });
+            }
+        }
         self.device_metadata = self.store.load_device_metadata()?;
         tracing::debug!(
             "IdentityManager::hydrate_from_store: Loaded device_metadata={:?}",
@@ -98,6 +138,100 @@ impl IdentityManager {
         Ok(())
     }
 
+    /// Adopt caller-supplied keys (e.g. a vanity identity) as the identity.
+    /// Refuses to replace an identity that already exists in the store.
+    pub fn initialize_with_keys(&mut self, keys: IdentityKeys) -> Result<()> {
+        self.hydrate_from_store()?;
+        if self.keys.is_some() {
+            anyhow::bail!("Identity already exists");
+        }
+        self.store.save_keys(&keys)?;
+        self.keys = Some(keys);
+        self.ensure_device_metadata()?;
+        Ok(())
+    }
+
+    /// Generate another identity into the keyring without activating it.
+    /// Returns its public key hex.
+    pub fn add_identity(&mut self) -> Result<String> {
+        let Some(active) = self.keys.as_ref() else {
+            anyhow::bail!("Identity not initialized");
+        };
+        // Keyring the active identity too, so it can be switched back to
+        // after a restart.
+        self.store
+            .save_keyring_entry(active, self.nickname.as_deref())?;
+        let keys = IdentityKeys::generate();
+        let public_key = keys.public_key_hex();
+        self.store.save_keyring_entry(&keys, None)?;
+        self.inactive.insert(
+            public_key.clone(),
+            InactiveIdentity {
+                keys,
+                nickname: None,
+            },
+        );
+        Ok(public_key)
+    }
+
+    /// Public keys of all identities, the active one first.
+    pub fn list_identities(&self) -> Vec<String> {
+        self.public_key_hex()
+            .into_iter()
+            .chain(self.inactive.keys().cloned())
+            .collect()
+    }
+
+    /// Make the keyring identity with `public_key_hex` active. The previously
+    /// active identity moves into the keyring.
+    pub fn switch_identity(&mut self, public_key_hex: &str) -> Result<()> {
+        let public_key_hex = public_key_hex.to_lowercase();
+        if self.public_key_hex().as_deref() == Some(public_key_hex.as_str()) {
+            return Ok(());
+        }
+        let Some(target) = self.inactive.remove(&public_key_hex) else {
+            anyhow::bail!("Unknown identity: {}", public_key_hex);
+        };
+        let persisted =
+            self.store
+                .save_keys(&target.keys)
+                .and_then(|_| match target.nickname.as_deref() {
+                    Some(nickname) => self.store.save_nickname(nickname),
+                    None => self.store.remove_nickname(),
+                });
+        if let Err(e) = persisted {
+            self.inactive.insert(public_key_hex, target);
+            return Err(e);
+        }
+        let InactiveIdentity { keys, nickname } = target;
+        let previous_nickname = std::mem::replace(&mut self.nickname, nickname);
+        if let Some(previous) = self.keys.replace(keys) {
+            let saved = self
+                .store
+                .save_keyring_entry(&previous, previous_nickname.as_deref());
+            // Keep the previous identity reachable in memory even if the
+            // keyring write failed.
+            self.inactive.insert(
+                previous.public_key_hex(),
+                InactiveIdentity {
+                    keys: previous,
+                    nickname: previous_nickname,
+                },
+            );
+            saved?;
+        }
+        Ok(())
+    }
+
+    /// Drop every identity held in memory (keys are zeroized on drop). The
+    /// persisted copies are left to the caller (see `IronCore::panic_wipe`).
+    pub fn wipe_in_memory(&mut self) {
+        self.keys = None;
+        self.inactive.clear();
+        self.nickname = None;
+        self.device_metadata = None;
+    }
+
     /// Get identity keys (if initialized)
     pub fn keys(&self) -> Option<&IdentityKeys> {
         self.keys.as_ref()
@@ -126,9 +260,14 @@ impl IdentityManager {
         IdentityKeys::verify(data, signature, public_key)
     }
 
-    /// Set nickname
+    /// Set nickname. Fails with `NicknameError` if it is too long or
+    /// contains control or bidi-override characters.
     pub fn set_nickname(&mut self, nickname: String) -> Result<()> {
+        validate_nickname(&nickname, self.max_nickname_graphemes)?;
         self.store.save_nickname(&nickname)?;
+        if let Some(keys) = self.keys.as_ref().filter(|_| !self.inactive.is_empty()) {
+            self.store.save_keyring_entry(keys, Some(&nickname))?;
+        }
         self.nickname = Some(nickname);
         Ok(())
     }
@@ -138,6 +277,15 @@ impl IdentityManager {
         self.nickname.clone()
     }
 
+    /// Maximum nickname length, in grapheme clusters, accepted by `set_nickname`.
+    pub fn max_nickname_graphemes(&self) -> usize {
+        self.max_nickname_graphemes
+    }
+
+    pub fn set_max_nickname_graphemes(&mut self, max: usize) {
+        self.max_nickname_graphemes = max;
+    }
+
     /// Get installation-local device metadata for tight-pair routing.
     pub fn device_id(&self) -> Option<String> {
         self.device_metadata
@@ -158,10 +306,13 @@ impl IdentityManager {
     }
 
     /// Import raw identity key bytes and persist them in the configured store.
+    /// Any previously loaded keys are zeroized before being replaced.
     pub fn import_key_bytes(&mut self, bytes: &[u8]) -> Result<()> {
         let keys = IdentityKeys::from_bytes(bytes)?;
         self.store.save_keys(&keys)?;
-        self.keys = Some(keys);
+        if let Some(mut previous) = self.keys.replace(keys) {
+            previous.zeroize();
+        }
         self.ensure_device_metadata()?;
         Ok(())
     }
@@ -301,6 +452,35 @@ mod tests {
         assert_eq!(seniority2, seniority1);
     }
 
+    #[test]
+    fn test_keyring_survives_restart_and_switch() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keyring").to_str().unwrap().to_string();
+
+        let (personal, work) = {
+            let backend = Arc::new(crate::store::backend::SledStorage::new(&path).unwrap());
+            let mut manager = IdentityManager::with_backend(backend).unwrap();
+            manager.initialize().unwrap();
+            manager.set_nickname("Alice".to_string()).unwrap();
+            let personal = manager.public_key_hex().unwrap();
+            let work = manager.add_identity().unwrap();
+            manager.switch_identity(&work).unwrap();
+            manager.set_nickname("Alice (work)".to_string()).unwrap();
+            (personal, work)
+        };
+
+        let backend = Arc::new(crate::store::backend::SledStorage::new(&path).unwrap());
+        let mut manager = IdentityManager::with_backend(backend).unwrap();
+        manager.initialize().unwrap();
+        assert_eq!(manager.public_key_hex(), Some(work.clone()));
+        assert_eq!(manager.nickname().as_deref(), Some("Alice (work)"));
+        assert_eq!(manager.list_identities(), vec![work, personal.clone()]);
+
+        manager.switch_identity(&personal).unwrap();
+        assert_eq!(manager.nickname().as_deref(), Some("Alice"));
+        assert!(manager.switch_identity("00").is_err());
+    }
+
     #[test]
     fn test_identity_import_export_roundtrip() {
         let mut manager1 = IdentityManager::new();
@@ -319,6 +499,26 @@ mod tests {
         assert!(manager2.seniority_timestamp().is_some());
     }
 
+    #[test]
+    fn test_import_replaces_existing_keys() {
+        let mut other = IdentityManager::new();
+        other.initialize().unwrap();
+        let other_id = other.identity_id();
+        let exported = other.export_key_bytes().unwrap();
+
+        let mut manager = IdentityManager::new();
+        manager.initialize().unwrap();
+        let original_id = manager.identity_id();
+
+        // Replacing loaded keys (twice, to also drop a previously imported
+        // set) wipes the old ones without disturbing the new identity.
+        manager.import_key_bytes(&exported).unwrap();
+        manager.import_key_bytes(&exported).unwrap();
+        assert_eq!(manager.identity_id(), other_id);
+        assert_ne!(manager.identity_id(), original_id);
+        assert!(manager.keys().unwrap().sign(b"after import").is_ok());
+    }
+
     #[test]
     fn test_with_path_hydrates_existing_identity_without_initialize() {
         use tempfile::tempdir;
@@ -420,4 +620,22 @@ mod tests {
         let fp = manager.signature_fingerprint(&signature);
         assert_eq!(fp.len(), 64);
     }
+
+    #[test]
+    fn test_set_nickname_validation() {
+        let mut manager = IdentityManager::new();
+        manager.set_nickname("🌻 Sunny".to_string()).unwrap();
+
+        let err = manager
+            .set_nickname("right\u{202E}left".to_string())
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<NicknameError>(),
+            Some(&NicknameError::BidiOverride(0x202E))
+        );
+
+        manager.set_max_nickname_graphemes(4);
+        assert!(manager.set_nickname("five!".to_string()).is_err());
+        assert_eq!(manager.nickname().as_deref(), Some("🌻 Sunny"));
+    }
 }
@@ -0,0 +1,108 @@
+// Nickname validation
+//
+// Nicknames are rendered in UIs, logs and CLI tables on every peer that sees
+// them, so they are length-limited in user-perceived characters (grapheme
+// clusters, so an emoji with modifiers counts once) and may not carry control
+// characters or bidi embedding/override/isolate codepoints, which can reorder
+// the surrounding text when displayed.
+
+use thiserror::Error;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Default maximum nickname length, in grapheme clusters.
+pub const DEFAULT_MAX_NICKNAME_GRAPHEMES: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum NicknameError {
+    #[error("Nickname is {graphemes} characters long (max {max})")]
+    TooLong { graphemes: usize, max: usize },
+    #[error("Nickname contains control character U+{0:04X}")]
+    ControlCharacter(u32),
+    #[error("Nickname contains bidirectional override U+{0:04X}")]
+    BidiOverride(u32),
+}
+
+/// Bidi embeddings and overrides (LRE, RLE, PDF, LRO, RLO) and isolates
+/// (LRI, RLI, FSI, PDI).
+fn is_bidi_control(c: char) -> bool {
+    matches!(c, '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+}
+
+/// Check a nickname against the display-safety rules, allowing at most
+/// `max_graphemes` grapheme clusters.
+pub fn validate_nickname(nickname: &str, max_graphemes: usize) -> Result<(), NicknameError> {
+    if let Some(c) = nickname.chars().find(|c| c.is_control()) {
+        return Err(NicknameError::ControlCharacter(c as u32));
+    }
+    if let Some(c) = nickname.chars().find(|c| is_bidi_control(*c)) {
+        return Err(NicknameError::BidiOverride(c as u32));
+    }
+    let graphemes = nickname.graphemes(true).count();
+    if graphemes > max_graphemes {
+        return Err(NicknameError::TooLong {
+            graphemes,
+            max: max_graphemes,
+        });
+    }
+    Ok(())
+}
+
+/// Trim a nickname and check it against the default limit. A blank name
+/// becomes `None` ("no nickname").
+pub fn normalize_nickname(nickname: Option<String>) -> Result<Option<String>, NicknameError> {
+    let Some(nickname) = nickname
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+    else {
+        return Ok(None);
+    };
+    validate_nickname(&nickname, DEFAULT_MAX_NICKNAME_GRAPHEMES)?;
+    Ok(Some(nickname))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emoji_nickname_counts_graphemes() {
+        // Family emoji (ZWJ sequence) and a skin-toned thumbs-up are one
+        // grapheme each, despite being several codepoints.
+        let nick = "Ana 👨‍👩‍👧 👍🏽";
+        assert_eq!(nick.graphemes(true).count(), 7);
+        assert!(validate_nickname(nick, 7).is_ok());
+        assert!(validate_nickname(nick, DEFAULT_MAX_NICKNAME_GRAPHEMES).is_ok());
+    }
+
+    #[test]
+    fn test_over_long_nickname_rejected() {
+        let nick = "x".repeat(DEFAULT_MAX_NICKNAME_GRAPHEMES + 1);
+        assert_eq!(
+            validate_nickname(&nick, DEFAULT_MAX_NICKNAME_GRAPHEMES),
+            Err(NicknameError::TooLong {
+                graphemes: DEFAULT_MAX_NICKNAME_GRAPHEMES + 1,
+                max: DEFAULT_MAX_NICKNAME_GRAPHEMES,
+            })
+        );
+    }
+
+    #[test]
+    fn test_control_and_bidi_codepoints_rejected() {
+        assert_eq!(
+            validate_nickname("evil\u{202E}gnp.exe", 32),
+            Err(NicknameError::BidiOverride(0x202E))
+        );
+        assert_eq!(
+            validate_nickname("a\u{2067}b", 32),
+            Err(NicknameError::BidiOverride(0x2067))
+        );
+        assert_eq!(
+            validate_nickname("line\nbreak", 32),
+            Err(NicknameError::ControlCharacter(0x0A))
+        );
+        assert_eq!(
+            validate_nickname("bell\u{7}", 32),
+            Err(NicknameError::ControlCharacter(0x07))
+        );
+    }
+}
@@ -11,6 +11,10 @@ const IDENTITY_KEY: &[u8] = b"identity_keys";
 const NICKNAME_KEY: &[u8] = b"identity_nickname";
 const DEVICE_ID_KEY: &[u8] = b"identity_device_id";
 const SENIORITY_TIMESTAMP_KEY: &[u8] = b"identity_seniority_timestamp";
+/// Every identity held by this installation, keyed by public key hex. The
+/// active identity is additionally stored under `IDENTITY_KEY`.
+const KEYRING_PREFIX: &str = "identity_keyring:";
+const KEYRING_NICKNAME_PREFIX: &str = "identity_keyring_nickname:";
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct DeviceMetadata {
@@ -240,11 +244,77 @@ impl IdentityStore {
         }
     }
 
+    /// Remove the active identity's nickname.
+    pub fn remove_nickname(&self) -> Result<()> {
+        match self {
+            Self::Memory => Ok(()),
+            Self::Persistent(db) => {
+                db.remove(NICKNAME_KEY).map_err(|e| anyhow::anyhow!(e))?;
+                db.flush().map_err(|e| anyhow::anyhow!(e))?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Save an identity into the keyring under its public key.
+    pub fn save_keyring_entry(&self, keys: &IdentityKeys, nickname: Option<&str>) -> Result<()> {
+        match self {
+            Self::Memory => Ok(()),
+            Self::Persistent(db) => {
+                let public_key = keys.public_key_hex();
+                let mut bytes = keys.to_bytes();
+                db.put(format!("{KEYRING_PREFIX}{public_key}").as_bytes(), &bytes)
+                    .map_err(|e| anyhow::anyhow!(e))?;
+                use zeroize::Zeroize;
+                bytes.zeroize();
+                let nickname_key = format!("{KEYRING_NICKNAME_PREFIX}{public_key}");
+                match nickname {
+                    Some(nickname) => db.put(nickname_key.as_bytes(), nickname.as_bytes()),
+                    None => db.remove(nickname_key.as_bytes()),
+                }
+                .map_err(|e| anyhow::anyhow!(e))?;
+                db.flush().map_err(|e| anyhow::anyhow!(e))?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Load every keyring identity with its nickname.
+    pub fn load_keyring(&self) -> Result<Vec<(IdentityKeys, Option<String>)>> {
+        match self {
+            Self::Memory => Ok(Vec::new()),
+            Self::Persistent(db) => {
+                let entries = db
+                    .scan_prefix(KEYRING_PREFIX.as_bytes())
+                    .map_err(|e| anyhow::anyhow!(e))?;
+                let mut identities = Vec::with_capacity(entries.len());
+                for (key, bytes) in entries {
+                    let public_key = String::from_utf8_lossy(&key[KEYRING_PREFIX.len()..]);
+                    let nickname = db
+                        .get(format!("{KEYRING_NICKNAME_PREFIX}{public_key}").as_bytes())
+                        .map_err(|e| anyhow::anyhow!(e))?
+                        .map(String::from_utf8)
+                        .transpose()?;
+                    identities.push((IdentityKeys::from_bytes(&bytes)?, nickname));
+                }
+                Ok(identities)
+            }
+        }
+    }
+
     /// Clear stored keys
     pub fn clear(&self) -> Result<()> {
         match self {
             Self::Memory => Ok(()),
             Self::Persistent(db) => {
+                for prefix in [KEYRING_PREFIX, KEYRING_NICKNAME_PREFIX] {
+                    for (key, _) in db
+                        .scan_prefix(prefix.as_bytes())
+                        .map_err(|e| anyhow::anyhow!(e))?
+                    {
+                        db.remove(&key).map_err(|e| anyhow::anyhow!(e))?;
+                    }
+                }
                 db.remove(IDENTITY_KEY).map_err(|e| anyhow::anyhow!(e))?;
                 db.remove(NICKNAME_KEY).map_err(|e| anyhow::anyhow!(e))?;
                 db.remove(DEVICE_ID_KEY).map_err(|e| anyhow::anyhow!(e))?;
@@ -18,7 +18,10 @@ use crate::abuse::auto_block::{AutoBlockConfig, AutoBlockEngine};
 use crate::abuse::spam_detection::{SpamDetectionConfig, SpamDetectionEngine};
 use crate::abuse::EnhancedAbuseReputationManager;
 use crate::crypto::encrypt::{ed25519_public_to_x25519, ed25519_to_x25519_secret};
-use crate::crypto::{decrypt_message, encrypt_message, session_manager::RatchetSessionManager};
+use crate::crypto::{
+    decrypt_message, encrypt_message_with_suite, session_manager::RatchetSessionManager,
+    MalformedEnvelope, UnknownAeadSuite,
+};
 use crate::drift::{MeshStore, NetworkState, RelayConfig, RelayEngine};
 use crate::identity::IdentityManager;
 use crate::message::{decode_envelope, decode_message, Message};
@@ -30,15 +33,15 @@ use crate::privacy::{
 #[cfg(not(target_arch = "wasm32"))]
 use crate::relay::{BootstrapManager, PeerExchangeManager};
 use crate::routing::optimized_engine::OptimizedRoutingEngine;
-use crate::store::backend::MemoryStorage;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::store::backend::SledStorage;
+use crate::store::backend::{MemoryStorage, PrefixedStorage};
 use crate::store::blocked::BlockedManager as CoreBlockedManager;
 use crate::store::logs::LogManager;
 use crate::store::{
-    Contact, ContactManager as CoreContactManager, HistoryManager as CoreHistoryManager, Inbox,
-    MessageDirection, MessageRecord, Outbox, QueuedMessage, ReceivedMessage, RelayCustodyStore,
-    StorageBackend, StorageManager,
+    Contact, ContactLookup, ContactManager as CoreContactManager,
+    HistoryManager as CoreHistoryManager, Inbox, MessageDirection, MessageRecord, Outbox,
+    QueuedMessage, ReceivedMessage, RelayCustodyStore, StorageBackend, StorageManager,
 };
 use crate::transport::behaviour::RegistrationRequest;
 use crate::transport::manager::TransportManager;
@@ -96,6 +99,15 @@ pub trait CoreDelegate: Send + Sync {
         data: Vec<u8>,
     );
     fn on_receipt_received(&self, message_id: String, status: String);
+    fn on_broadcast_received(&self, sender_public_key_hex: String, topic: String, data: Vec<u8>);
+    /// Expired messages were removed by the inbox sweeper.
+    fn on_inbox_pruned(&self, count: u32);
+    /// A contact started or stopped typing (`MessageType::Typing`).
+    fn on_typing(&self, sender_public_key_hex: String, is_typing: bool);
+    /// A message whose type this build does not know (see
+    /// `supported_message_types`). `payload` is passed through undecoded so
+    /// the app can show a fallback such as "update to view this message".
+    fn on_unknown_message(&self, type_id: u32, sender_public_key_hex: String, payload: Vec<u8>);
 }
 
 /// Consent state for identity initialization.
@@ -124,6 +136,192 @@ fn ratchet_disabled() -> bool {
     f.load(std::sync::atomic::Ordering::Relaxed)
 }
 
+/// Map a decryption failure to its FFI error. A cipher suite this build
+/// lacks and a malformed envelope are typed errors from `crypto`; an AEAD
+/// failure on a Drift envelope whose recipient hint is not ours means the
+/// message was sealed for someone else, otherwise it was corrupted or forged.
+fn decrypt_error(
+    e: &anyhow::Error,
+    envelope_data: &[u8],
+    local_public_key: &[u8; 32],
+) -> IronCoreError {
+    if e.downcast_ref::<UnknownAeadSuite>().is_some() {
+        return IronCoreError::UnsupportedCipherSuite;
+    }
+    if e.downcast_ref::<MalformedEnvelope>().is_some() {
+        return IronCoreError::MalformedEnvelope;
+    }
+    let addressed_elsewhere = envelope_data.first() == Some(&crate::drift::DRIFT_VERSION)
+        && crate::drift::DriftEnvelope::from_bytes(envelope_data)
+            .map(|env| {
+                env.recipient_hint
+                    != crate::drift::DriftEnvelope::hint_from_public_key(local_public_key)
+            })
+            .unwrap_or(false);
+    if addressed_elsewhere {
+        IronCoreError::NotIntendedRecipient
+    } else {
+        IronCoreError::AuthenticationFailed
+    }
+}
+
+/// One inbox expiry pass, shared by `IronCore::sweep_inbox` and the sweeper
+/// thread (which holds only these two handles, not the core).
+fn sweep_inbox_once(
+    inbox: &RwLock<crate::store::Inbox>,
+    delegate: &RwLock<Option<Box<dyn CoreDelegate>>>,
+    max_age_secs: u64,
+    now: u64,
+) -> u32 {
+    let pruned = inbox
+        .write()
+        .prune_older_than(now.saturating_sub(max_age_secs)) as u32;
+    if pruned > 0 {
+        tracing::info!("Inbox sweeper removed {} expired message(s)", pruned);
+        if let Some(delegate) = delegate.read().as_ref() {
+            delegate.on_inbox_pruned(pruned);
+        }
+    }
+    pruned
+}
+
+/// A delegate callback produced while receiving, dispatched once the
+/// identity lock is released (see `IronCore::receive_messages`).
+enum InboundNotification {
+    Receipt {
+        message_id: String,
+        status: String,
+    },
+    Message(Message),
+    Typing {
+        sender_public_key_hex: String,
+        is_typing: bool,
+    },
+    Unknown {
+        type_id: u32,
+        sender_public_key_hex: String,
+        payload: Vec<u8>,
+    },
+}
+
+/// How many received receipt ids `IronCore` remembers so it can refuse to
+/// acknowledge them (see `prepare_receipt`).
+const MAX_TRACKED_RECEIPT_IDS: usize = 4096;
+
+/// Most outgoing messages tracked in `pending_acks`; past it the one armed
+/// longest ago is dropped, as if given up with `clear_pending_ack`.
+const MAX_PENDING_ACKS: usize = 4096;
+
+/// Store key naming the identity whose contacts and history sit at the top
+/// of the store; every other identity's sit under a prefix of their own
+/// (see `identity_store`).
+const ROOT_IDENTITY_KEY: &[u8] = b"identity_store_root";
+
+/// Key, within an identity's part of the store, of the ratchet sessions it
+/// had when another identity was made active.
+const PARKED_RATCHET_SESSIONS_KEY: &[u8] = b"parked_ratchet_sessions";
+
+/// Ids of the most recently received receipt messages, oldest evicted first.
+#[derive(Default)]
+struct ReceivedReceiptIds {
+    ids: std::collections::HashSet<String>,
+    order: std::collections::VecDeque<String>,
+}
+
+impl ReceivedReceiptIds {
+    fn insert(&mut self, id: &str) {
+        if !self.ids.insert(id.to_string()) {
+            return;
+        }
+        self.order.push_back(id.to_string());
+        while self.order.len() > MAX_TRACKED_RECEIPT_IDS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+    }
+
+    fn contains(&self, id: &str) -> bool {
+        self.ids.contains(id)
+    }
+}
+
+/// Start tracking `message_id` for acknowledgment, evicting the entries
+/// armed longest ago once more than `MAX_PENDING_ACKS` are tracked.
+fn track_pending_ack(
+    pending: &mut std::collections::HashMap<String, u64>,
+    message_id: String,
+    now: u64,
+) {
+    pending.insert(message_id, now);
+    while pending.len() > MAX_PENDING_ACKS {
+        let Some(oldest) = pending
+            .iter()
+            .min_by_key(|(id, sent_at)| (**sent_at, (*id).clone()))
+            .map(|(id, _)| id.clone())
+        else {
+            break;
+        };
+        pending.remove(&oldest);
+    }
+}
+
+/// How an `IronCore::await_delivery` call ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryOutcome {
+    /// The recipient's delivery receipt arrived.
+    Delivered,
+    /// No receipt arrived before the timeout.
+    Timeout,
+    /// The transport gave up, or the recipient reported a failure.
+    Failed,
+}
+
+/// Callers waiting in `await_delivery`, keyed by message id.
+type DeliveryWaiters =
+    std::collections::HashMap<String, Vec<tokio::sync::oneshot::Sender<DeliveryOutcome>>>;
+
+/// Ed25519 public key hex embedded in a libp2p PeerId string.
+/// `InvalidInput` if it does not parse, `Internal` if it is not Ed25519.
+pub(crate) fn public_key_hex_from_peer_id(peer_id: &str) -> Result<String, IronCoreError> {
+    let peer_id: libp2p::PeerId = peer_id.parse().map_err(|_| IronCoreError::InvalidInput)?;
+    // Ed25519 PeerIds use identity multihash (code 0) where the digest
+    // contains the protobuf-encoded public key.
+    let mh = peer_id.as_ref();
+    if mh.code() != 0 {
+        return Err(IronCoreError::Internal);
+    }
+    let pk = libp2p::identity::PublicKey::try_decode_protobuf(mh.digest())
+        .map_err(|_| IronCoreError::Internal)?;
+    let ed25519_pk = pk.try_into_ed25519().map_err(|_| IronCoreError::Internal)?;
+    Ok(hex::encode(ed25519_pk.to_bytes()))
+}
+
+/// Map an envelope decode failure to its FFI error: an envelope from a newer
+/// client is reported distinctly from a malformed one.
+fn envelope_decode_error(e: &anyhow::Error) -> IronCoreError {
+    if e.downcast_ref::<crate::message::codec::UnsupportedEnvelopeVersion>()
+        .is_some()
+    {
+        IronCoreError::UnsupportedEnvelopeVersion
+    } else {
+        IronCoreError::EnvelopeDecodeFailed
+    }
+}
+
+/// Map a chunked file receive failure to its FFI error: a chunk that fails
+/// authentication or comes from another sender is a crypto error, a write
+/// failure is I/O, anything else is a bad chunk.
+fn file_chunk_error(e: crate::crypto::FileChunkError) -> IronCoreError {
+    use crate::crypto::FileChunkError;
+    tracing::warn!("File chunk rejected: {}", e);
+    match e {
+        FileChunkError::Crypto(_) | FileChunkError::SenderMismatch => IronCoreError::CryptoError,
+        FileChunkError::Io(_) => IronCoreError::IoError,
+        _ => IronCoreError::InvalidInput,
+    }
+}
+
 /// The main entry point for the SCMessenger core.
 ///
 /// Wraps all subsystems behind `Arc<RwLock<…>>` for safe concurrent access.
@@ -132,8 +330,12 @@ pub struct IronCore {
     pub(crate) identity: Arc<RwLock<IdentityManager>>,
     pub(crate) outbox: Arc<RwLock<Outbox>>,
     pub(crate) inbox: Arc<RwLock<Inbox>>,
+    /// Inboxes of the identities that are not active, keyed by public key
+    /// hex, so switching back keeps received messages and the dedup set.
+    parked_inboxes: Arc<RwLock<std::collections::HashMap<String, Inbox>>>,
     pub(crate) contact_manager: Arc<RwLock<CoreContactManager>>,
-    pub(crate) history_manager: Arc<CoreHistoryManager>,
+    /// History of the active identity (see `switch_identity`).
+    pub(crate) history_manager: Arc<RwLock<Arc<CoreHistoryManager>>>,
     pub(crate) storage_manager: Arc<RwLock<StorageManager>>,
     pub(crate) log_manager: Arc<LogManager>,
     pub(crate) blocked_manager: Arc<RwLock<CoreBlockedManager>>,
@@ -242,14 +444,65 @@ pub struct IronCore {
     /// Drift policy engine — adapts relay aggressiveness from device state.
     pub policy_engine: Arc<RwLock<crate::drift::PolicyEngine>>,
     pub transport_memory: Arc<RwLock<crate::store::transport_memory::TransportMemoryStore>>,
+
+    /// Unsent message drafts, plaintext and local only.
+    drafts: crate::store::Drafts,
+
+    /// Per-message delivery reports for outgoing messages.
+    delivery_log: crate::store::DeliveryLog,
+
+    /// Transport escalation (BLE → WiFi → Internet) policy and per-peer state.
+    escalation_engine: Arc<RwLock<crate::transport::escalation::EscalationEngine>>,
+
+    /// Outgoing messages still waiting for a delivery receipt:
+    /// message_id → sent_at (unix seconds), at most `MAX_PENDING_ACKS`.
+    /// See `check_ack_timeouts`.
+    pending_acks: Arc<RwLock<std::collections::HashMap<String, u64>>>,
+
+    /// Relay-only posture: never decrypt or store payloads (see `set_relay_only`).
+    relay_only: Arc<RwLock<bool>>,
+    /// Active mesh settings, loaded from `mesh_settings.json` under the
+    /// storage path (see `get_settings` / `update_settings`).
+    settings: Arc<RwLock<crate::settings::MeshSettings>>,
+    /// AEAD suite for outbound static-ECDH envelopes (see `set_preferred_cipher`).
+    preferred_cipher: Arc<RwLock<crate::crypto::AeadSuite>>,
+    /// Own presence heartbeat schedule (see `start_presence_heartbeat`).
+    presence_heartbeat: Arc<RwLock<crate::transport::presence::PresenceHeartbeat>>,
+    /// Stop signal for the running inbox sweeper thread, if any; dropping
+    /// the sender ends the thread (see `start_inbox_sweeper`).
+    inbox_sweeper: Arc<RwLock<Option<std::sync::mpsc::Sender<()>>>>,
+    /// The key-value store every persistent subsystem above shares; kept so
+    /// `panic_wipe` can erase all of it.
+    storage_backend: Arc<dyn StorageBackend>,
+    /// Time source for message, receipt and inbox timestamps (see
+    /// `crate::clock`); swapped by `set_clock` / `set_clock_offset`.
+    clock: Arc<RwLock<Arc<dyn crate::clock::Clock>>>,
+    /// Chunked file receives in progress, keyed by file id (see
+    /// `start_file_receive`).
+    file_receives: Arc<
+        RwLock<std::collections::HashMap<String, crate::crypto::FileReassembler<std::fs::File>>>,
+    >,
+    /// Receipt messages seen recently; never acknowledged in turn.
+    received_receipt_ids: Arc<RwLock<ReceivedReceiptIds>>,
+    /// Callers waiting for a delivery receipt (see `await_delivery`).
+    delivery_waiters: Arc<RwLock<DeliveryWaiters>>,
 }
 
+/// Largest sealed file chunk `receive_file_chunk` will decrypt. Chunks are
+/// written straight to the output file, so this bounds memory per receive
+/// regardless of file size.
+pub const FILE_RECEIVE_MEMORY_BUDGET: usize = 256 * 1024;
+
 /// Current version of the structured identity-backup payload (the plaintext
 /// encrypted by `export_identity_backup*`). Bumping this is safe: older
 /// payload shapes stay decodable as long as `import_identity_backup` keeps a
 /// fallback for them.
 const IDENTITY_BACKUP_PAYLOAD_VERSION: u32 = 2;
 
+/// Version of the `export_support_bundle` JSON layout. Bump it when fields
+/// are renamed or removed so support tooling can tell the shapes apart.
+pub const SUPPORT_BUNDLE_SCHEMA_VERSION: u32 = 1;
+
 /// Plaintext payload encrypted inside an identity backup blob: the identity
 /// keypair plus enough conversational state (ratchet sessions, contacts) to
 /// keep messaging without interruption after a restore on a fresh device.
@@ -285,278 +538,40 @@ impl IronCore {
     /// Create an in-memory IronCore with no persistent storage.
     #[cfg_attr(not(target_arch = "wasm32"), uniffi::constructor)]
     pub fn new() -> Self {
-        let backend: Arc<dyn StorageBackend> = Arc::new(MemoryStorage::new());
-        let contact_manager = CoreContactManager::new(backend.clone());
-        let history_manager = Arc::new(CoreHistoryManager::new(backend.clone()));
-        let log_mgr = Arc::new(LogManager::new(backend.clone()));
-        let blocked_manager = CoreBlockedManager::new(backend.clone());
-        let blocked_for_auto_block = CoreBlockedManager::new(backend.clone());
-        let inbox = Inbox::new();
-        let outbox = Outbox::new();
-        let storage_manager =
-            StorageManager::new(backend.clone(), history_manager.clone(), log_mgr.clone());
-        let spam_detector =
-            SpamDetectionEngine::new_heuristics_only(SpamDetectionConfig::default());
-        let abuse_mgr = EnhancedAbuseReputationManager::new(1000, spam_detector);
-        let auto_block_spam =
-            SpamDetectionEngine::new_heuristics_only(SpamDetectionConfig::default());
-        let auto_block_reputation = EnhancedAbuseReputationManager::new(1000, auto_block_spam);
-        let auto_block = AutoBlockEngine::new(
-            AutoBlockConfig::default(),
-            Arc::new(blocked_for_auto_block),
-            Arc::new(auto_block_reputation),
-        );
-        let ratchet_sessions = Arc::new(RwLock::new(RatchetSessionManager::new()));
-        let security_audit_pipeline =
-            Arc::new(crate::dspy::modules::ModuleFactory::build_security_audit_pipeline());
-
-        let transport_memory =
-            crate::store::transport_memory::TransportMemoryStore::new(backend.clone());
-
-        Self {
-            identity: Arc::new(RwLock::new(IdentityManager::new())),
-            outbox: Arc::new(RwLock::new(outbox)),
-            inbox: Arc::new(RwLock::new(inbox)),
-            contact_manager: Arc::new(RwLock::new(contact_manager)),
-            history_manager,
-            storage_manager: Arc::new(RwLock::new(storage_manager)),
-            log_manager: log_mgr,
-            blocked_manager: Arc::new(RwLock::new(blocked_manager)),
-            audit_log: Arc::new(RwLock::new(AuditLogType::new())),
-            relay_custody_store: Arc::new(RwLock::new(RelayCustodyStore::persistent(
-                backend.clone(),
-            ))),
-            delegate: Arc::new(RwLock::new(None)),
-            consent: Arc::new(RwLock::new(ConsentState::NotGranted)),
-            drift_active: Arc::new(RwLock::new(false)),
-            drift_store: Arc::new(RwLock::new(MeshStore::persistent(backend.clone()))),
-            drift_engine: Arc::new(RwLock::new(None)),
-            abuse_manager: Arc::new(RwLock::new(abuse_mgr)),
-            auto_block_engine: Arc::new(RwLock::new(auto_block)),
-            storage_path: None,
-            log_directory: None,
-            #[cfg(not(target_arch = "wasm32"))]
-            ledger_manager: crate::store::LedgerManager::new(
-                std::env::temp_dir().to_str().unwrap_or("/tmp").to_string(),
-            ),
-            running: Arc::new(RwLock::new(false)),
-            routing_engine: Arc::new(RwLock::new(None)),
-            cover_traffic_generator: Arc::new(RwLock::new(None)),
-            timing_jitter: Arc::new(RwLock::new(None)),
-            circuit_builder: Arc::new(RwLock::new(None)),
-            notification_endpoint_registry: Arc::new(RwLock::new(
-                NotificationEndpointRegistry::new(),
-            )),
-            transport_manager: Arc::new(RwLock::new(TransportManager::new())),
-            #[cfg(not(target_arch = "wasm32"))]
-            bootstrap_manager: Arc::new(RwLock::new(None)),
-            #[cfg(not(target_arch = "wasm32"))]
-            relay_bootstrap_manager: Arc::new(RwLock::new(Some(
-                crate::transport::bootstrap::BootstrapManager::with_defaults(),
-            ))),
-            #[cfg(not(target_arch = "wasm32"))]
-            peer_exchange_manager: Arc::new(RwLock::new(PeerExchangeManager::new())),
-            ratchet_sessions,
-            security_audit_pipeline,
-            privacy_config: Arc::new(RwLock::new(crate::privacy::PrivacyConfig::default())),
-            policy_engine: Arc::new(RwLock::new(crate::drift::PolicyEngine::new())),
-            transport_memory: Arc::new(RwLock::new(transport_memory)),
-        }
+        Self::open(
+            Arc::new(MemoryStorage::new()),
+            IdentityManager::new(),
+            crate::settings::MeshSettings::default(),
+            None,
+            None,
+        )
     }
 
     /// Create IronCore with persistent sled-backed storage at `path`.
     #[cfg(not(target_arch = "wasm32"))]
     #[cfg_attr(not(target_arch = "wasm32"), uniffi::constructor)]
     pub fn with_storage(path: String) -> Self {
-        let backend: Arc<dyn StorageBackend> = match SledStorage::new(&path) {
-            Ok(s) => Arc::new(s),
-            Err(_) => Arc::new(MemoryStorage::new()),
-        };
-        let p = path.clone();
-        let contact_manager = CoreContactManager::new(backend.clone());
-        let history_manager = Arc::new(CoreHistoryManager::new(backend.clone()));
-        let log_mgr = Arc::new(LogManager::new(backend.clone()));
-        let blocked_manager = CoreBlockedManager::new(backend.clone());
-        let blocked_for_auto_block = CoreBlockedManager::new(backend.clone());
-        let inbox = Inbox::new();
-        let outbox = Outbox::new();
-        let storage_manager =
-            StorageManager::new(backend.clone(), history_manager.clone(), log_mgr.clone());
-        let spam_detector =
-            SpamDetectionEngine::new_heuristics_only(SpamDetectionConfig::default());
-        let abuse_mgr = EnhancedAbuseReputationManager::new(1000, spam_detector);
-        let auto_block_spam =
-            SpamDetectionEngine::new_heuristics_only(SpamDetectionConfig::default());
-        let auto_block_reputation = EnhancedAbuseReputationManager::new(1000, auto_block_spam);
-        let auto_block = AutoBlockEngine::new(
-            AutoBlockConfig::default(),
-            Arc::new(blocked_for_auto_block),
-            Arc::new(auto_block_reputation),
-        );
-        let security_audit_pipeline =
-            Arc::new(crate::dspy::modules::ModuleFactory::build_security_audit_pipeline());
-
-        let transport_memory =
-            crate::store::transport_memory::TransportMemoryStore::new(backend.clone());
-
-        Self {
-            identity: Arc::new(RwLock::new(
-                IdentityManager::with_backend(backend.clone()).unwrap_or_else(|_| {
-                    tracing::error!(
-                        "Failed to hydrate identity from persistent store, falling back to memory"
-                    );
-                    IdentityManager::new()
-                }),
-            )),
-            outbox: Arc::new(RwLock::new(outbox)),
-            inbox: Arc::new(RwLock::new(inbox)),
-            contact_manager: Arc::new(RwLock::new(contact_manager)),
-            history_manager,
-            storage_manager: Arc::new(RwLock::new(storage_manager)),
-            log_manager: log_mgr,
-            blocked_manager: Arc::new(RwLock::new(blocked_manager)),
-            audit_log: Arc::new(RwLock::new(AuditLogType::new())),
-            relay_custody_store: Arc::new(RwLock::new(RelayCustodyStore::persistent(
-                backend.clone(),
-            ))),
-            delegate: Arc::new(RwLock::new(None)),
-            consent: Arc::new(RwLock::new(ConsentState::NotGranted)),
-            drift_active: Arc::new(RwLock::new(false)),
-            drift_store: Arc::new(RwLock::new(MeshStore::persistent(backend.clone()))),
-            drift_engine: Arc::new(RwLock::new(None)),
-            abuse_manager: Arc::new(RwLock::new(abuse_mgr)),
-            auto_block_engine: Arc::new(RwLock::new(auto_block)),
-            storage_path: Some(path),
-            log_directory: None,
-            #[cfg(not(target_arch = "wasm32"))]
-            ledger_manager: crate::store::LedgerManager::new(p),
-            running: Arc::new(RwLock::new(false)),
-            routing_engine: Arc::new(RwLock::new(None)),
-            cover_traffic_generator: Arc::new(RwLock::new(None)),
-            timing_jitter: Arc::new(RwLock::new(None)),
-            circuit_builder: Arc::new(RwLock::new(None)),
-            notification_endpoint_registry: Arc::new(RwLock::new(
-                NotificationEndpointRegistry::new(),
-            )),
-            transport_manager: Arc::new(RwLock::new(TransportManager::new())),
-            #[cfg(not(target_arch = "wasm32"))]
-            bootstrap_manager: Arc::new(RwLock::new(None)),
-            #[cfg(not(target_arch = "wasm32"))]
-            relay_bootstrap_manager: Arc::new(RwLock::new(Some(
-                crate::transport::bootstrap::BootstrapManager::with_defaults(),
-            ))),
-            #[cfg(not(target_arch = "wasm32"))]
-            peer_exchange_manager: Arc::new(RwLock::new(PeerExchangeManager::new())),
-            ratchet_sessions: Arc::new(RwLock::new(RatchetSessionManager::new())),
-            security_audit_pipeline,
-            privacy_config: Arc::new(RwLock::new(crate::privacy::PrivacyConfig::default())),
-            policy_engine: Arc::new(RwLock::new(crate::drift::PolicyEngine::new())),
-            transport_memory: Arc::new(RwLock::new(transport_memory)),
-        }
+        Self::open_persistent(path, None)
     }
 
     /// Create IronCore with persistent storage and a log directory.
     #[cfg(not(target_arch = "wasm32"))]
     #[cfg_attr(not(target_arch = "wasm32"), uniffi::constructor)]
     pub fn with_storage_and_logs(path: String, log_dir: String) -> Self {
-        let backend: Arc<dyn StorageBackend> = match SledStorage::new(&path) {
-            Ok(s) => Arc::new(s),
-            Err(_) => Arc::new(MemoryStorage::new()),
-        };
-        let p = path.clone();
-        let contact_manager = CoreContactManager::new(backend.clone());
-        let history_manager = Arc::new(CoreHistoryManager::new(backend.clone()));
-        let log_mgr = Arc::new(LogManager::new(backend.clone()));
-        let blocked_manager = CoreBlockedManager::new(backend.clone());
-        let blocked_for_auto_block = CoreBlockedManager::new(backend.clone());
-        let inbox = Inbox::new();
-        let outbox = Outbox::new();
-        let storage_manager =
-            StorageManager::new(backend.clone(), history_manager.clone(), log_mgr.clone());
-        let spam_detector =
-            SpamDetectionEngine::new_heuristics_only(SpamDetectionConfig::default());
-        let abuse_mgr = EnhancedAbuseReputationManager::new(1000, spam_detector);
-        let auto_block_spam =
-            SpamDetectionEngine::new_heuristics_only(SpamDetectionConfig::default());
-        let auto_block_reputation = EnhancedAbuseReputationManager::new(1000, auto_block_spam);
-        let auto_block = AutoBlockEngine::new(
-            AutoBlockConfig::default(),
-            Arc::new(blocked_for_auto_block),
-            Arc::new(auto_block_reputation),
-        );
-        let security_audit_pipeline =
-            Arc::new(crate::dspy::modules::ModuleFactory::build_security_audit_pipeline());
+        Self::open_persistent(path, Some(log_dir))
+    }
 
-        let transport_memory =
-            crate::store::transport_memory::TransportMemoryStore::new(backend.clone());
-
-        Self {
-            identity: Arc::new(RwLock::new(
-                IdentityManager::with_backend(backend.clone()).unwrap_or_else(|_| {
-                    tracing::error!(
-                        "Failed to hydrate identity from persistent store, falling back to memory"
-                    );
-                    IdentityManager::new()
-                }),
-            )),
-            outbox: Arc::new(RwLock::new(outbox)),
-            inbox: Arc::new(RwLock::new(inbox)),
-            contact_manager: Arc::new(RwLock::new(contact_manager)),
-            history_manager,
-            storage_manager: Arc::new(RwLock::new(storage_manager)),
-            log_manager: log_mgr,
-            blocked_manager: Arc::new(RwLock::new(blocked_manager)),
-            audit_log: Arc::new(RwLock::new(AuditLogType::new())),
-            relay_custody_store: Arc::new(RwLock::new(RelayCustodyStore::persistent(
-                backend.clone(),
-            ))),
-            delegate: Arc::new(RwLock::new(None)),
-            consent: Arc::new(RwLock::new(ConsentState::NotGranted)),
-            drift_active: Arc::new(RwLock::new(false)),
-            drift_store: Arc::new(RwLock::new(MeshStore::persistent(backend.clone()))),
-            drift_engine: Arc::new(RwLock::new(None)),
-            abuse_manager: Arc::new(RwLock::new(abuse_mgr)),
-            auto_block_engine: Arc::new(RwLock::new(auto_block)),
-            storage_path: Some(path),
-            log_directory: Some(log_dir),
-            #[cfg(not(target_arch = "wasm32"))]
-            ledger_manager: crate::store::LedgerManager::new(p),
-            running: Arc::new(RwLock::new(false)),
-            routing_engine: Arc::new(RwLock::new(None)),
-            cover_traffic_generator: Arc::new(RwLock::new(None)),
-            timing_jitter: Arc::new(RwLock::new(None)),
-            circuit_builder: Arc::new(RwLock::new(None)),
-            notification_endpoint_registry: Arc::new(RwLock::new(
-                NotificationEndpointRegistry::new(),
-            )),
-            transport_manager: Arc::new(RwLock::new(TransportManager::new())),
-            #[cfg(not(target_arch = "wasm32"))]
-            bootstrap_manager: Arc::new(RwLock::new(None)),
-            #[cfg(not(target_arch = "wasm32"))]
-            relay_bootstrap_manager: Arc::new(RwLock::new(Some(
-                crate::transport::bootstrap::BootstrapManager::with_defaults(),
-            ))),
-            #[cfg(not(target_arch = "wasm32"))]
-            peer_exchange_manager: Arc::new(RwLock::new(PeerExchangeManager::new())),
-            ratchet_sessions: Arc::new(RwLock::new(RatchetSessionManager::new())),
-            security_audit_pipeline,
-            privacy_config: Arc::new(RwLock::new(crate::privacy::PrivacyConfig::default())),
-            policy_engine: Arc::new(RwLock::new(crate::drift::PolicyEngine::new())),
-            transport_memory: Arc::new(RwLock::new(transport_memory)),
-        }
-    }
-
-    /// Start the core. Must be called before any messaging operations.
-    pub fn start(&self) -> Result<(), IronCoreError> {
-        let mut running = self.running.write();
-        if *running {
-            return Err(IronCoreError::AlreadyRunning);
-        }
-        *running = true;
-        self.drift_activate();
-        tracing::info!("IronCore started");
-        Ok(())
-    }
+    /// Start the core. Must be called before any messaging operations.
+    pub fn start(&self) -> Result<(), IronCoreError> {
+        let mut running = self.running.write();
+        if *running {
+            return Err(IronCoreError::AlreadyRunning);
+        }
+        *running = true;
+        self.drift_activate();
+        tracing::info!("IronCore started");
+        Ok(())
+    }
 
     /// Stop the core gracefully.
     pub fn stop(&self) {
@@ -623,6 +638,237 @@ impl IronCore {
         Ok(())
     }
 
+    /// Generate an additional identity (e.g. a work persona) without
+    /// switching to it. Returns its public key hex. Requires an initialized
+    /// identity.
+    pub fn add_identity(&self) -> Result<String, IronCoreError> {
+        if *self.consent.read() != ConsentState::Granted {
+            return Err(IronCoreError::ConsentRequired);
+        }
+        let mut identity = self.identity.write();
+        let active = identity
+            .public_key_hex()
+            .ok_or(IronCoreError::NotInitialized)?;
+        // The data stored so far is the active identity's.
+        self.root_identity(&active)?;
+        let public_key = identity.add_identity().map_err(|e| {
+            tracing::error!("Adding identity failed: {:?}", e);
+            IronCoreError::StorageError
+        })?;
+        let identity_id = hex::decode(&public_key)
+            .map(|pk| hex::encode(blake3::hash(&pk).as_bytes()))
+            .ok();
+        self.audit_log
+            .write()
+            .append(AuditEventType::IdentityCreated, identity_id, None, None);
+        Ok(public_key)
+    }
+
+    /// Public keys of every identity held by this core, the active one first.
+    pub fn list_identities(&self) -> Vec<String> {
+        self.identity.read().list_identities()
+    }
+
+    /// Make another identity from `list_identities` active. Messages are
+    /// prepared, signed and decrypted with the active identity, and each
+    /// identity has its own contacts, history, inbox and ratchet sessions:
+    /// the first identity's stay at the top of the store, later ones' sit
+    /// under their public key. Blocked peers and
+    /// settings belong to the installation. Fetch `contacts_manager` and
+    /// `history_manager` again afterwards. A running swarm keeps its
+    /// transport peer ID until it is restarted
+    /// (`MeshService::switch_identity` does that).
+    pub fn switch_identity(&self, public_key_hex: String) -> Result<(), IronCoreError> {
+        let mut identity = self.identity.write();
+        let previous = identity
+            .public_key_hex()
+            .ok_or(IronCoreError::NotInitialized)?;
+        if !identity
+            .list_identities()
+            .iter()
+            .any(|pk| pk.eq_ignore_ascii_case(&public_key_hex))
+        {
+            return Err(IronCoreError::InvalidInput);
+        }
+        if previous.eq_ignore_ascii_case(&public_key_hex) {
+            return Ok(());
+        }
+        let root = self.root_identity(&previous)?;
+
+        // identity-first lock order, as in receive_message. The sessions
+        // are parked before anything changes, so a failed write leaves the
+        // previous identity active with its sessions intact.
+        let mut sessions = self.ratchet_sessions.write();
+        let parked = sessions.serialize_sessions().map_err(|e| {
+            tracing::error!("Serializing ratchet sessions failed: {:?}", e);
+            IronCoreError::Internal
+        })?;
+        self.identity_store(&previous, &root)
+            .put(PARKED_RATCHET_SESSIONS_KEY, parked.as_bytes())
+            .map_err(|e| {
+                tracing::error!("Parking ratchet sessions failed: {}", e);
+                IronCoreError::StorageError
+            })?;
+        identity.switch_identity(&public_key_hex).map_err(|e| {
+            tracing::error!("Switching identity failed: {:?}", e);
+            IronCoreError::StorageError
+        })?;
+        let keys = identity.keys().ok_or(IronCoreError::Internal)?;
+        let current = keys.public_key_hex();
+        let store = self.identity_store(&current, &root);
+
+        let mut restored = RatchetSessionManager::new();
+        if let Ok(Some(json)) = store.get(PARKED_RATCHET_SESSIONS_KEY) {
+            if let Err(e) = restored.deserialize_sessions_strict(&String::from_utf8_lossy(&json)) {
+                tracing::warn!("Discarding unreadable parked ratchet sessions: {:?}", e);
+                restored = RatchetSessionManager::new();
+            }
+            // Once the sessions advance the parked copy is stale.
+            let _ = store.remove(PARKED_RATCHET_SESSIONS_KEY);
+        }
+        *sessions = restored;
+        drop(sessions);
+        {
+            let mut inbox = self.inbox.write();
+            let mut parked = self.parked_inboxes.write();
+            let active_inbox = parked.remove(&current).unwrap_or_default();
+            parked.insert(previous, std::mem::replace(&mut *inbox, active_inbox));
+        }
+        self.use_identity_store(store);
+
+        let pk_bytes = keys.signing_key.verifying_key().to_bytes();
+        *self.drift_engine.write() = Some(RelayEngine::new(&pk_bytes, RelayConfig::default()));
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            *self.bootstrap_manager.write() = Some(BootstrapManager::new(
+                keys.identity_id(),
+                pk_bytes.to_vec(),
+                Vec::new(),
+            ));
+        }
+
+        tracing::info!("Switched to identity {:?}", identity.identity_id());
+        Ok(())
+    }
+
+    /// Six-word fingerprint of the local public key, for reading aloud
+    /// during contact verification. Empty if no identity is initialized.
+    pub fn identity_fingerprint_words(&self) -> Vec<String> {
+        self.identity
+            .read()
+            .keys()
+            .map(|keys| keys.fingerprint_words())
+            .unwrap_or_default()
+    }
+
+    /// Switch the relay-only posture. While enabled, `receive_message`
+    /// refuses every envelope with `RelayOnly` before attempting decryption
+    /// or touching the inbox/history; relays forward using
+    /// `relay_envelope_info` instead, and open only onion layers wrapped for
+    /// them with `peel_onion_relay`. An identity is optional in this mode.
+    pub fn set_relay_only(&self, enabled: bool) {
+        *self.relay_only.write() = enabled;
+        tracing::info!(
+            "Relay-only mode {}",
+            if enabled { "enabled" } else { "disabled" }
+        );
+    }
+
+    pub fn is_relay_only(&self) -> bool {
+        *self.relay_only.read()
+    }
+
+    /// The mesh settings currently in force.
+    pub fn get_settings(&self) -> crate::MeshSettings {
+        self.settings.read().clone()
+    }
+
+    /// Validate and apply `settings`, persisting them when the core has a
+    /// storage path. With `relay_enabled` off, preparing and receiving
+    /// messages fail with `MeshParticipationDisabled`: relaying and
+    /// messaging are one switch.
+    pub fn update_settings(&self, settings: crate::MeshSettings) -> Result<(), IronCoreError> {
+        settings.validate().map_err(|errors| {
+            for error in &errors {
+                tracing::warn!("Rejected mesh settings: {}", error);
+            }
+            IronCoreError::InvalidInput
+        })?;
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = self.storage_path.as_ref() {
+            crate::mobile_bridge::MeshSettingsManager::new(path.clone()).save(settings.clone())?;
+            crate::contacts_bridge::set_max_contacts_for_storage(path, settings.max_contacts);
+            crate::contacts_bridge::set_max_contacts_for_storage(
+                &self.identity_storage_path(),
+                settings.max_contacts,
+            );
+        }
+        self.contact_manager
+            .read()
+            .set_max_contacts(settings.max_contacts);
+        self.presence_heartbeat
+            .write()
+            .set_suppressed(settings.discovery_mode == crate::settings::DiscoveryMode::Paranoid);
+        *self.settings.write() = settings;
+        Ok(())
+    }
+
+    /// Choose the AEAD suite for outbound envelopes by name
+    /// ("xchacha20-poly1305" or "chacha20-poly1305"). Applies to the
+    /// static-ECDH path; ratcheted sessions keep their own cipher. Inbound
+    /// envelopes are always decrypted with the suite they declare.
+    pub fn set_preferred_cipher(&self, suite: String) -> Result<(), IronCoreError> {
+        let suite =
+            crate::crypto::AeadSuite::from_name(&suite).ok_or(IronCoreError::InvalidInput)?;
+        *self.preferred_cipher.write() = suite;
+        tracing::info!("Preferred AEAD suite set to {}", suite);
+        Ok(())
+    }
+
+    pub fn preferred_cipher(&self) -> String {
+        self.preferred_cipher.read().as_str().to_string()
+    }
+
+    /// Read the cleartext envelope header (sender key, size, wire version)
+    /// without decrypting. Works with or without a local identity; this is
+    /// the ingest path for relay-only nodes, where `receive_message` fails
+    /// with `RelayOnly`.
+    pub fn relay_envelope_info(
+        &self,
+        envelope_data: Vec<u8>,
+    ) -> Result<crate::RelayEnvelopeInfo, IronCoreError> {
+        let wire = crate::message::codec::decode_wire_envelope(&envelope_data)
+            .map_err(|_| IronCoreError::InvalidInput)?;
+        let (sender_public_key, wire_version) = match &wire {
+            crate::message::WireEnvelope::V1(e) => (&e.sender_public_key, 1),
+            crate::message::WireEnvelope::V2(e2) => (&e2.sender_public_key, 2),
+        };
+        Ok(crate::RelayEnvelopeInfo {
+            sender_public_key_hex: hex::encode(sender_public_key),
+            size_bytes: envelope_data.len() as u64,
+            wire_version,
+        })
+    }
+
+    /// Connection-path state ("Bootstrapping", "RelayFallback",
+    /// "DirectPreferred") for a running transport, from its peer and
+    /// listener counts and the swarm's NAT status string. Same
+    /// classification as `MeshService::get_connection_path_state` and the
+    /// WASM `getConnectionPathState`.
+    pub fn connection_path_state(
+        &self,
+        peer_count: u32,
+        listener_count: u32,
+        nat_status: String,
+    ) -> String {
+        crate::transport::connection_path(
+            peer_count as usize,
+            listener_count as usize,
+            crate::transport::NatReachability::from_status_str(&nat_status),
+        )
+        .to_string()
+    }
+
     /// Return the identity ID (Blake3 hash of public key), if initialized.
     pub fn identity_id(&self) -> Option<String> {
         self.identity.read().identity_id()
@@ -659,15 +905,23 @@ impl IronCore {
         _msg_type: crate::MessageType,
         _ttl: Option<crate::TtlConfig>,
     ) -> Result<crate::PreparedMessage, IronCoreError> {
+        self.ensure_mesh_participation_enabled()?;
         let identity = self.identity.read();
         let keys = identity.keys().ok_or(IronCoreError::NotInitialized)?;
 
-        let recipient_bytes = hex::decode(recipient_id).map_err(|_| IronCoreError::InvalidInput)?;
+        let recipient_bytes = hex::decode(recipient_id).map_err(|_| IronCoreError::MalformedKey)?;
         let recipient_pk: [u8; 32] = recipient_bytes
             .try_into()
-            .map_err(|_| IronCoreError::InvalidInput)?;
+            .map_err(|_| IronCoreError::KeyWrongLength)?;
+        crate::crypto::validate_ed25519_public_key(recipient_id)
+            .map_err(|_| IronCoreError::MalformedKey)?;
 
         let message_id = uuid::Uuid::new_v4().to_string();
+        // Everything logged while sealing and queueing is tagged with the id,
+        // matching the spans opened by the swarm send and `receive_message`.
+        let _span =
+            tracing::info_span!("message", message_id = %message_id, direction = "outbound")
+                .entered();
         let sender_id = identity.identity_id().unwrap_or_default();
         let message = crate::Message {
             id: message_id.clone(),
@@ -675,78 +929,10 @@ impl IronCore {
             recipient_id: recipient_id.to_string(),
             message_type: _msg_type,
             payload: content.as_bytes().to_vec(),
-            timestamp: web_time::SystemTime::now()
-                .duration_since(web_time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs(),
-        };
-        let message_bytes =
-            crate::message::encode_message(&message).map_err(|_| IronCoreError::Internal)?;
-
-        let (mut envelope_data, drift_env) = if ratchet_disabled() {
-            // LEGACY PATH (kill switch) -- verbatim current behavior
-            let envelope = encrypt_message(&keys.signing_key, &recipient_pk, &message_bytes)
-                .map_err(|_| IronCoreError::CryptoError)?;
-            let drift_env = crate::drift::DriftEnvelope::from_legacy_envelope(
-                envelope,
-                message_id.clone(),
-                recipient_pk,
-                &keys.signing_key,
-            )
-            .map_err(|_| IronCoreError::Internal)?;
-            let envelope_data = drift_env.to_bytes().map_err(|_| IronCoreError::Internal)?;
-            (envelope_data, drift_env)
-        } else {
-            // RATCHET PATH -- identity.read() already held; take
-            // ratchet_sessions.write() AFTER (identity-first, proven-safe).
-            // TODO cache our_bundle per identity_id (per-send Ed25519+ML-DSA
-            // signing is wasteful) -- E-00 ships correct-first.
-            let our_bundle = crate::identity::sign_bundle(keys).ok();
-            let recipient_bundle = self
-                .contact_manager
-                .read()
-                .get_contact_bundle(recipient_id)
-                .ok()
-                .flatten();
-            let peer_id = recipient_id.to_string();
-            let signing_key = keys.signing_key.clone();
-            let mut sessions = self.ratchet_sessions.write();
-            let mut audit = self.audit_log.write();
-            let wire = crate::crypto::encrypt::encrypt_with_ratchet_fallback(
-                &signing_key,
-                recipient_bundle.as_ref(),
-                &recipient_pk,
-                &message_bytes,
-                Some(&mut *sessions),
-                &peer_id,
-                our_bundle.as_ref(),
-                false,
-                Some(&mut *audit),
-            )
-            .map_err(|_| IronCoreError::CryptoError)?;
-            let drift_env = match wire {
-                crate::message::WireEnvelope::V1(env) => {
-                    crate::drift::DriftEnvelope::from_legacy_envelope(
-                        env,
-                        message_id.clone(),
-                        recipient_pk,
-                        &signing_key,
-                    )
-                    .map_err(|_| IronCoreError::Internal)?
-                }
-                crate::message::WireEnvelope::V2(env2) => {
-                    crate::drift::DriftEnvelope::from_v2_envelope(
-                        env2,
-                        message_id.clone(),
-                        recipient_pk,
-                        &signing_key,
-                    )
-                    .map_err(|_| IronCoreError::Internal)?
-                }
-            };
-            let envelope_data = drift_env.to_bytes().map_err(|_| IronCoreError::Internal)?;
-            (envelope_data, drift_env)
+            timestamp: self.now_secs(),
         };
+        let (mut envelope_data, drift_env) =
+            self.seal_envelope(keys, recipient_id, recipient_pk, &message)?;
 
         if self.privacy_config().onion_routing_enabled {
             let relays = self.swarm_get_best_relays(3);
@@ -768,10 +954,7 @@ impl IronCore {
         let msg_id_bytes: [u8; 16] = *uuid::Uuid::parse_str(&message_id)
             .unwrap_or_else(|_| uuid::Uuid::nil())
             .as_bytes();
-        let now = web_time::SystemTime::now()
-            .duration_since(web_time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
+        let now = self.now_secs();
 
         let decision = self.make_routing_decision(hint, msg_id_bytes, 128, now);
 
@@ -791,10 +974,7 @@ impl IronCore {
                 ttl_expiry: drift_env.ttl_expiry,
                 hop_count: drift_env.hop_count,
                 priority: drift_env.priority,
-                received_at: web_time::SystemTime::now()
-                    .duration_since(web_time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs(),
+                received_at: self.now_secs(),
             };
             self.drift_store.write().insert(stored_env);
             tracing::info!("StoreAndCarry route resolved for {}. Handoff to Drift custody and bypassed active outbox.", message_id);
@@ -809,10 +989,7 @@ impl IronCore {
                     message_id: message_id.clone(),
                     recipient_id: recipient_id.to_string(),
                     envelope_data: envelope_data.clone(),
-                    queued_at: web_time::SystemTime::now()
-                        .duration_since(web_time::UNIX_EPOCH)
-                        .unwrap_or_default()
-                        .as_millis() as u64,
+                    queued_at: self.now_millis(),
                     attempts: 0,
                     next_retry_at: None,
                     in_custody: false,
@@ -829,6 +1006,19 @@ impl IronCore {
             None,
         );
 
+        track_pending_ack(
+            &mut self.pending_acks.write(),
+            message_id.clone(),
+            self.now_secs(),
+        );
+        if let Err(e) = self.delivery_log.record_queued(&message_id, recipient_id) {
+            tracing::warn!(
+                "Failed to start delivery report for {}: {:?}",
+                message_id,
+                e
+            );
+        }
+
         Ok(crate::PreparedMessage {
             message_id,
             envelope_data,
@@ -859,10 +1049,90 @@ impl IronCore {
         self.prepare_message_internal(&recipient_public_key_hex, &text, msg_type, ttl)
     }
 
+    /// Seal a text message as Base45 text small enough for a single QR code,
+    /// for sneakernet delivery with no network at all. Unlike
+    /// `prepare_message`, nothing is queued in the outbox or onion-wrapped.
+    /// Returns `PortableTooLarge` if the text does not fit.
+    pub fn prepare_message_portable(
+        &self,
+        recipient_public_key_hex: String,
+        text: String,
+    ) -> Result<String, IronCoreError> {
+        self.ensure_mesh_participation_enabled()?;
+        let identity = self.identity.read();
+        let keys = identity.keys().ok_or(IronCoreError::NotInitialized)?;
+
+        let recipient_bytes =
+            hex::decode(&recipient_public_key_hex).map_err(|_| IronCoreError::MalformedKey)?;
+        let recipient_pk: [u8; 32] = recipient_bytes
+            .try_into()
+            .map_err(|_| IronCoreError::KeyWrongLength)?;
+        crate::crypto::validate_ed25519_public_key(&recipient_public_key_hex)
+            .map_err(|_| IronCoreError::MalformedKey)?;
+
+        let message = crate::Message {
+            id: uuid::Uuid::new_v4().to_string(),
+            sender_id: identity.identity_id().unwrap_or_default(),
+            recipient_id: recipient_public_key_hex.clone(),
+            message_type: crate::MessageType::Text,
+            payload: text.into_bytes(),
+            timestamp: self.now_secs(),
+        };
+        let (envelope_data, _) =
+            self.seal_envelope(keys, &recipient_public_key_hex, recipient_pk, &message)?;
+
+        crate::drift::encode_portable(&envelope_data).map_err(|e| match e {
+            crate::drift::DriftError::PortableTooLarge { .. } => IronCoreError::PortableTooLarge,
+            _ => IronCoreError::Internal,
+        })
+    }
+
+    /// Wire names of the message types this build understands (e.g.
+    /// `"text"`, `"typing"`). Anything else arrives through
+    /// `CoreDelegate::on_unknown_message`.
+    pub fn supported_message_types(&self) -> Vec<String> {
+        crate::transport::capability::PeerCapabilities::local().message_types
+    }
+
+    /// Seal a typing indicator for `recipient_public_key_hex`. It is
+    /// encrypted like any message, but nothing is queued, tracked for a
+    /// receipt or onion-wrapped: send it now or drop it.
+    pub fn prepare_typing(
+        &self,
+        recipient_public_key_hex: String,
+        is_typing: bool,
+    ) -> Result<Vec<u8>, IronCoreError> {
+        self.ensure_mesh_participation_enabled()?;
+        let identity = self.identity.read();
+        let keys = identity.keys().ok_or(IronCoreError::NotInitialized)?;
+
+        let recipient_bytes =
+            hex::decode(&recipient_public_key_hex).map_err(|_| IronCoreError::MalformedKey)?;
+        let recipient_pk: [u8; 32] = recipient_bytes
+            .try_into()
+            .map_err(|_| IronCoreError::KeyWrongLength)?;
+        crate::crypto::validate_ed25519_public_key(&recipient_public_key_hex)
+            .map_err(|_| IronCoreError::MalformedKey)?;
+
+        let message = crate::Message {
+            timestamp: self.now_secs(),
+            ..crate::Message::typing(
+                identity.identity_id().unwrap_or_default(),
+                recipient_public_key_hex.clone(),
+                is_typing,
+            )
+        };
+        let (envelope_data, _) =
+            self.seal_envelope(keys, &recipient_public_key_hex, recipient_pk, &message)?;
+        Ok(envelope_data)
+    }
+
     /// Receive and decrypt an incoming envelope.
 
     /// Mark a message as sent (remove from outbox after transport confirms delivery).
     pub fn mark_message_sent(&self, message_id: String) -> bool {
+        self.pending_acks.write().remove(&message_id);
+        let _ = self.delivery_log.mark_sent(&message_id);
         let outbox_removed = self.outbox.write().remove(&message_id);
         let mut parsed_id = [0u8; 16];
         let parsed = if let Ok(uuid) = uuid::Uuid::parse_str(&message_id) {
@@ -879,6 +1149,38 @@ impl IronCore {
         outbox_removed || drift_removed
     }
 
+    /// Outgoing messages still awaiting a delivery receipt, as
+    /// message_id → sent_at (unix seconds).
+    pub fn pending_acks(&self) -> std::collections::HashMap<String, u64> {
+        self.pending_acks.read().clone()
+    }
+
+    /// Message IDs that have gone `timeout_secs` without a receipt, oldest
+    /// first. Each returned entry is re-armed with the current time, so a
+    /// background task can re-send it and will only see it again if the next
+    /// window also passes silently. Use `clear_pending_ack` to give up on a
+    /// message instead.
+    pub fn check_ack_timeouts(&self, timeout_secs: u64) -> Vec<String> {
+        let now = self.now_secs();
+        let mut pending = self.pending_acks.write();
+        let mut expired: Vec<(u64, String)> = pending
+            .iter()
+            .filter(|(_, sent_at)| now.saturating_sub(**sent_at) >= timeout_secs)
+            .map(|(id, sent_at)| (*sent_at, id.clone()))
+            .collect();
+        expired.sort();
+        for (_, id) in &expired {
+            pending.insert(id.clone(), now);
+        }
+        expired.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// Stop tracking `message_id` for acknowledgment (e.g. after marking it
+    /// failed). Returns whether it was being tracked.
+    pub fn clear_pending_ack(&self, message_id: String) -> bool {
+        self.pending_acks.write().remove(&message_id).is_some()
+    }
+
     /// Send a message status report for a given peer.
     /// Returns `None` on success, or `Some(error_string)` on failure.
     /// This method provides the same interface as the mobile bridge's
@@ -995,6 +1297,10 @@ impl IronCore {
     // -----------------------------------------------------------------------
 
     /// Notify the core that a peer was discovered.
+    ///
+    /// "Discovered" means the transport saw the peer (a connection was
+    /// established or mDNS/Kademlia surfaced it); its identity has not yet
+    /// been confirmed. See [`IronCore::notify_peer_identified`].
     /// Blocked peers (peer-level or any known device) are silently ignored.
     pub fn notify_peer_discovered(&self, peer_id: String) {
         // Suppress discovery notifications for blocked peers
@@ -1018,6 +1324,33 @@ impl IronCore {
         }
     }
 
+    /// Notify the core that a peer's identity was confirmed.
+    ///
+    /// "Identified" means the libp2p Identify protocol completed for the peer,
+    /// so its public key, agent version and advertised listen addresses are
+    /// known. This always follows a "discovered" notification for the same
+    /// peer. The delegate is called even when the peer advertised no listen
+    /// addresses, in which case `listen_addrs` is empty.
+    /// Blocked peers (peer-level or any known device) are silently ignored.
+    pub fn notify_peer_identified(
+        &self,
+        peer_id: String,
+        agent_version: String,
+        listen_addrs: Vec<String>,
+    ) {
+        if self
+            .blocked_manager
+            .read()
+            .is_blocked(&peer_id, None)
+            .unwrap_or(false)
+        {
+            return;
+        }
+        if let Some(delegate) = self.delegate.read().as_ref() {
+            delegate.on_peer_identified(peer_id, agent_version, listen_addrs);
+        }
+    }
+
     /// Record an abuse signal from the transport layer.
     pub fn record_abuse_signal(&self, peer_id: String, signal: String) {
         let abuse = self.abuse_manager.read();
@@ -1167,14 +1500,37 @@ impl IronCore {
             .and_then(|k| k.to_libp2p_peer_id().ok())
     }
 
+    /// Whether `expected` is the PeerId derived from the current identity.
+    /// Importing a different identity changes the PeerId, so callers that
+    /// persisted one (ledgers, contact exports) can detect the drift here.
+    /// False when no identity is initialized.
+    pub fn verify_peer_id_matches(&self, expected: String) -> bool {
+        self.get_libp2p_peer_id()
+            .is_some_and(|derived| derived == expected.trim())
+    }
+
     pub fn get_seniority_timestamp(&self) -> Option<u64> {
         self.identity.read().seniority_timestamp()
     }
 
-    /// Set the nickname for the local identity.
+    /// Maximum nickname length, in user-perceived characters, accepted by
+    /// `set_nickname` (default 32).
+    pub fn set_max_nickname_length(&self, max: u32) {
+        self.identity
+            .write()
+            .set_max_nickname_graphemes(max as usize);
+    }
+
+    /// Set the nickname for the local identity. Returns `InvalidNickname`
+    /// if it is longer than the configured limit or contains control or
+    /// bidi-override characters.
     pub fn set_nickname(&self, nickname: String) -> Result<(), IronCoreError> {
         let mut identity = self.identity.write();
         identity.set_nickname(nickname.clone()).map_err(|e| {
+            if let Some(invalid) = e.downcast_ref::<crate::identity::NicknameError>() {
+                tracing::warn!("Rejected nickname: {}", invalid);
+                return IronCoreError::InvalidNickname;
+            }
             tracing::error!("Failed to persist nickname to store: {:?}", e);
             IronCoreError::Internal
         })?;
@@ -1213,15 +1569,447 @@ impl IronCore {
     }
 
     // -----------------------------------------------------------------------
-    // Outbox / Inbox counts
+    // Contact bundle export / import
     // -----------------------------------------------------------------------
 
-    pub fn outbox_count(&self) -> u32 {
-        self.outbox.read().total_count() as u32
+    /// Export all contacts as a signed JSON bundle
+    /// (`{version, contacts: [{peer_id, public_key, nickname, tags}], signer_public_key, signature}`).
+    pub fn export_contacts_bundle(&self) -> Result<String, IronCoreError> {
+        let contacts = self.contact_manager.read().export_entries()?;
+        let payload = serde_json::to_vec(&contacts).map_err(|_| IronCoreError::Internal)?;
+        let signed = self.sign_data(payload)?;
+        let bundle = crate::store::SignedContactBundle {
+            version: crate::store::CONTACT_BUNDLE_VERSION,
+            contacts,
+            signer_public_key: signed.public_key_hex,
+            signature: hex::encode(signed.signature),
+        };
+        serde_json::to_string(&bundle).map_err(|_| IronCoreError::Internal)
     }
 
-    pub fn inbox_count(&self) -> u32 {
-        self.inbox.read().total_count() as u32
+    /// Import a bundle produced by `export_contacts_bundle`, merging by public
+    /// key. Existing local nicknames win on conflict unless `overwrite` is set.
+    /// Returns the import report as JSON.
+    pub fn import_contacts_bundle(
+        &self,
+        data: String,
+        verify_signature: bool,
+        overwrite: bool,
+    ) -> Result<String, IronCoreError> {
+        let bundle: crate::store::SignedContactBundle =
+            serde_json::from_str(&data).map_err(|_| IronCoreError::InvalidInput)?;
+        if bundle.version > crate::store::CONTACT_BUNDLE_VERSION {
+            return Err(IronCoreError::InvalidInput);
+        }
+        if verify_signature {
+            let payload =
+                serde_json::to_vec(&bundle.contacts).map_err(|_| IronCoreError::Internal)?;
+            let signature =
+                hex::decode(&bundle.signature).map_err(|_| IronCoreError::InvalidInput)?;
+            if !self.verify_signature(payload, signature, bundle.signer_public_key.clone())? {
+                tracing::warn!(
+                    signer = %bundle.signer_public_key,
+                    "Rejected contact bundle with invalid signature"
+                );
+                return Err(IronCoreError::CryptoError);
+            }
+        }
+        let report = self
+            .contact_manager
+            .read()
+            .merge_bundle_entries(bundle.contacts, overwrite)?;
+        tracing::info!(
+            added = report.added,
+            updated = report.updated,
+            conflicts = report.conflicts,
+            rejected = report.rejected,
+            "Imported contact bundle"
+        );
+        serde_json::to_string(&report).map_err(|_| IronCoreError::Internal)
+    }
+
+    // -----------------------------------------------------------------------
+    // Outbox / Inbox counts
+    // -----------------------------------------------------------------------
+
+    pub fn outbox_count(&self) -> u32 {
+        self.outbox.read().total_count() as u32
+    }
+
+    /// Irreversibly erase this installation: zeroize the in-memory keys,
+    /// overwrite every stored record (identity, contacts, history, queued
+    /// and received messages, settings) with random bytes and delete it,
+    /// and shred the side files next to the store (network key, mesh
+    /// settings, ledger, bridge contacts). Afterwards the core has no
+    /// identity; drop it rather than keep using it.
+    ///
+    /// Best effort by nature: flash wear levelling, copy-on-write
+    /// filesystems and sled's log-structured segments can keep older copies
+    /// beyond the app's reach (see `store::wipe`). Only full-disk
+    /// encryption guarantees those are unreadable.
+    pub fn panic_wipe(&self) -> Result<(), IronCoreError> {
+        self.stop_inbox_sweeper();
+        self.stop_presence_heartbeat();
+        self.identity.write().wipe_in_memory();
+        *self.ratchet_sessions.write() = RatchetSessionManager::new();
+        *self.inbox.write() = Inbox::new();
+        self.parked_inboxes.write().clear();
+        *self.outbox.write() = Outbox::new();
+        *self.audit_log.write() = AuditLogType::new();
+        self.pending_acks.write().clear();
+
+        let erased =
+            crate::store::wipe::overwrite_backend(self.storage_backend.as_ref()).map_err(|e| {
+                tracing::error!("Panic wipe could not erase the store: {}", e);
+                IronCoreError::StorageError
+            })?;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = &self.storage_path {
+            let dir = std::path::Path::new(path);
+            for name in ["relay_network_key.pb", "mesh_settings.json", "ledger.json"] {
+                if let Err(e) = crate::store::wipe::shred_file(&dir.join(name)) {
+                    tracing::warn!("Panic wipe could not shred {}: {}", name, e);
+                }
+            }
+            // The bridge contacts store is a second sled database; erase its
+            // records while it can still be opened, then its files.
+            let contacts_db = dir.join("contacts.db");
+            if contacts_db.exists() {
+                if let Some(db) = contacts_db.to_str().and_then(|p| SledStorage::new(p).ok()) {
+                    let _ = crate::store::wipe::overwrite_backend(&db);
+                }
+                if let Err(e) = crate::store::wipe::shred_dir(&contacts_db) {
+                    tracing::warn!("Panic wipe could not shred contacts.db: {}", e);
+                }
+            }
+            // Bridge stores of the identities added with `add_identity`.
+            if let Err(e) = crate::store::wipe::shred_dir(&dir.join("identities")) {
+                tracing::warn!("Panic wipe could not shred identities: {}", e);
+            }
+        }
+
+        tracing::warn!("Panic wipe erased {} stored records", erased);
+        Ok(())
+    }
+
+    /// Correct the time used for timestamps and expiry when the device
+    /// clock is known to be wrong: `offset_secs` is added to the device
+    /// time (positive when the device is behind). 0 restores the raw clock.
+    pub fn set_clock_offset(&self, offset_secs: i64) {
+        *self.clock.write() = Arc::new(crate::clock::SystemClock::with_offset_secs(offset_secs));
+    }
+
+    /// Delete received messages older than `max_age_secs` from the inbox now.
+    /// Returns the number removed; the delegate's `on_inbox_pruned` fires
+    /// when any were.
+    pub fn sweep_inbox(&self, max_age_secs: u64) -> u32 {
+        sweep_inbox_once(&self.inbox, &self.delegate, max_age_secs, self.now_secs())
+    }
+
+    /// Auto-delete received messages older than `max_age_secs`, sweeping
+    /// once now and then every `interval_secs` on a background thread.
+    /// Replaces any sweeper already running; stop it with
+    /// `stop_inbox_sweeper` (it also stops when the core is dropped).
+    pub fn start_inbox_sweeper(
+        &self,
+        max_age_secs: u64,
+        interval_secs: u64,
+    ) -> Result<(), IronCoreError> {
+        if max_age_secs == 0 || interval_secs == 0 {
+            return Err(IronCoreError::InvalidInput);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let (stop_tx, stop_rx) = std::sync::mpsc::channel::<()>();
+            let inbox = self.inbox.clone();
+            let delegate = self.delegate.clone();
+            let clock = self.clock.clone();
+            let interval = web_time::Duration::from_secs(interval_secs);
+            std::thread::Builder::new()
+                .name("scm-inbox-sweeper".to_string())
+                .spawn(move || loop {
+                    let now = clock.read().now_secs();
+                    sweep_inbox_once(&inbox, &delegate, max_age_secs, now);
+                    match stop_rx.recv_timeout(interval) {
+                        Err(std::sync::mpsc::RecvTimeoutError::Timeout) => continue,
+                        _ => break,
+                    }
+                })
+                .map_err(|_| IronCoreError::Internal)?;
+            // Dropping the previous sender stops the previous thread.
+            *self.inbox_sweeper.write() = Some(stop_tx);
+            tracing::info!(
+                "Inbox sweeper started (max age {}s, every {}s)",
+                max_age_secs,
+                interval_secs
+            );
+            Ok(())
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            // No threads in the browser; hosts call `sweep_inbox` on a timer.
+            Err(IronCoreError::Internal)
+        }
+    }
+
+    pub fn stop_inbox_sweeper(&self) {
+        self.inbox_sweeper.write().take();
+    }
+
+    /// Start publishing a signed presence beacon (public key + timestamp) on
+    /// the lobby topic every `interval_secs` (at least 10), so contacts see
+    /// an accurate "last seen". Has no effect while the mesh settings are in
+    /// `DiscoveryMode::Paranoid`.
+    pub fn start_presence_heartbeat(&self, interval_secs: u64) -> Result<(), IronCoreError> {
+        if self.identity.read().keys().is_none() {
+            return Err(IronCoreError::NotInitialized);
+        }
+        let paranoid = self.presence_suppressed_by_settings();
+        let mut heartbeat = self.presence_heartbeat.write();
+        if paranoid {
+            heartbeat.set_suppressed(true);
+        }
+        heartbeat.start(interval_secs);
+        if heartbeat.is_running() {
+            tracing::info!("Presence heartbeat started ({}s)", interval_secs);
+        } else {
+            tracing::info!("Presence heartbeat disabled by Paranoid discovery mode");
+        }
+        Ok(())
+    }
+
+    pub fn stop_presence_heartbeat(&self) {
+        self.presence_heartbeat.write().stop();
+    }
+
+    /// The next presence beacon to publish on the lobby topic, or `None` if
+    /// the heartbeat is stopped, suppressed, or not yet due. Polled by the
+    /// swarm loop.
+    pub fn presence_beacon_if_due(&self) -> Option<Vec<u8>> {
+        if self.presence_suppressed_by_settings() {
+            return None;
+        }
+        let now = self.now_secs();
+        if !self.presence_heartbeat.write().take_due(now) {
+            return None;
+        }
+        let identity = self.identity.read();
+        let keys = identity.keys()?;
+        crate::transport::presence::PresenceBeacon::sign(keys, now)
+            .ok()
+            .and_then(|beacon| beacon.encode())
+    }
+
+    /// Apply a presence beacon received from the mesh: updates the sending
+    /// contact's `last_seen`. Returns false for our own beacon or a sender
+    /// that is not a contact; errors if the beacon is malformed, badly
+    /// signed, or outside the accepted time window.
+    pub fn receive_presence_beacon(&self, data: Vec<u8>) -> Result<bool, IronCoreError> {
+        let now = self.now_secs();
+        let beacon = crate::transport::presence::PresenceBeacon::open(&data, now)
+            .ok_or(IronCoreError::InvalidInput)?;
+        let contact_manager = self.contact_manager.read();
+        let Some(contact) = contact_manager
+            .list()?
+            .into_iter()
+            .find(|c| c.public_key.eq_ignore_ascii_case(&beacon.public_key))
+        else {
+            return Ok(false);
+        };
+        contact_manager.record_seen_at(contact.peer_id, beacon.timestamp.min(now))
+    }
+
+    /// Messages still queued for `recipient_public_key_hex`, oldest first.
+    /// Messages handed off to custody are not listed; they can no longer be
+    /// canceled locally.
+    pub fn pending_for(&self, recipient_public_key_hex: String) -> Vec<crate::PreparedMessage> {
+        self.outbox
+            .read()
+            .peek_for_peer(&recipient_public_key_hex)
+            .into_iter()
+            .filter(|m| !m.in_custody)
+            .map(|m| crate::PreparedMessage {
+                message_id: m.message_id,
+                envelope_data: m.envelope_data,
+            })
+            .collect()
+    }
+
+    /// Drop every queued (non-custody) message for `recipient_public_key_hex`
+    /// and return how many were removed. Other recipients' queues are left
+    /// untouched.
+    pub fn cancel_pending(&self, recipient_public_key_hex: String) -> u32 {
+        let removed = self
+            .outbox
+            .write()
+            .drain_for_peer(&recipient_public_key_hex);
+        {
+            let mut pending_acks = self.pending_acks.write();
+            for msg in &removed {
+                pending_acks.remove(&msg.message_id);
+            }
+        }
+        tracing::info!(
+            event = "outbox_cancel",
+            recipient = %recipient_public_key_hex,
+            removed = removed.len(),
+            "Canceled pending outbound messages"
+        );
+        removed.len() as u32
+    }
+
+    // -----------------------------------------------------------------------
+    // Drafts
+    // -----------------------------------------------------------------------
+
+    /// Save (or overwrite) the draft for a recipient. Drafts stay plaintext
+    /// on this device until sent with `send_draft`.
+    pub fn save_draft(
+        &self,
+        recipient_public_key_hex: String,
+        text: String,
+    ) -> Result<(), IronCoreError> {
+        crate::crypto::validate_ed25519_public_key(&recipient_public_key_hex)
+            .map_err(|_| IronCoreError::MalformedKey)?;
+        self.drafts.save(&recipient_public_key_hex, &text)?;
+        Ok(())
+    }
+
+    pub fn get_draft(&self, recipient_public_key_hex: String) -> Option<crate::store::Draft> {
+        self.drafts.get(&recipient_public_key_hex).ok().flatten()
+    }
+
+    /// All drafts, most recently saved first.
+    pub fn list_drafts(&self) -> Vec<crate::store::Draft> {
+        self.drafts.list().unwrap_or_default()
+    }
+
+    pub fn delete_draft(&self, recipient_public_key_hex: String) -> Result<bool, IronCoreError> {
+        self.drafts.delete(&recipient_public_key_hex)
+    }
+
+    /// Prepare a text message for a saved contact named by nickname (local
+    /// or federated, case-insensitive), public key, peer ID or identity ID,
+    /// so platforms don't each resolve recipients themselves. Fails with
+    /// `InvalidInput` if no contact matches or a nickname matches several.
+    pub fn prepare_message_to_contact(
+        &self,
+        nickname_or_id: String,
+        text: String,
+    ) -> Result<crate::PreparedMessage, IronCoreError> {
+        let public_key = self.resolve_contact_public_key(&nickname_or_id)?;
+        self.prepare_message(public_key, text, crate::MessageType::Text, None)
+    }
+
+    /// Everything known about the delivery of an outgoing message: status,
+    /// attempts, paths tried and the last error. `None` for messages not
+    /// prepared by this identity's store.
+    pub fn delivery_report(&self, message_id: String) -> Option<crate::store::DeliveryReport> {
+        self.delivery_log.get(&message_id).ok().flatten()
+    }
+
+    /// Record a transport send attempt for a prepared message. `path`
+    /// names the route (e.g. "direct", "relay:<peer_id>"); `error` is `None`
+    /// when the path accepted the message.
+    pub fn record_delivery_attempt(
+        &self,
+        message_id: String,
+        path: String,
+        error: Option<String>,
+    ) -> Result<(), IronCoreError> {
+        self.delivery_log
+            .record_attempt(&message_id, &path, error.as_deref())?;
+        Ok(())
+    }
+
+    /// Record that the transport gave up on a message after exhausting its
+    /// paths (`SwarmEvent2::DeliveryGivenUp`).
+    pub fn mark_delivery_failed(
+        &self,
+        message_id: String,
+        reason: String,
+    ) -> Result<(), IronCoreError> {
+        self.delivery_log.mark_failed(&message_id, &reason)?;
+        self.resolve_delivery_waiters(&message_id, DeliveryOutcome::Failed);
+        Ok(())
+    }
+
+    /// Send the draft for a recipient through the normal `prepare_message`
+    /// path and delete it. The draft is taken out of the store first and put
+    /// back if preparing fails, so it ends up either sent or still saved,
+    /// never both. Fails with `InvalidInput` if there is no draft.
+    pub fn send_draft(
+        &self,
+        recipient_public_key_hex: String,
+    ) -> Result<crate::PreparedMessage, IronCoreError> {
+        let draft = self
+            .drafts
+            .take(&recipient_public_key_hex)?
+            .ok_or(IronCoreError::InvalidInput)?;
+        match self.prepare_message(
+            recipient_public_key_hex,
+            draft.text.clone(),
+            crate::MessageType::Text,
+            None,
+        ) {
+            Ok(prepared) => Ok(prepared),
+            Err(e) => {
+                self.drafts.restore(&draft)?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Build a broadcast for `topic`: a signed but unencrypted message for
+    /// every subscriber, such as an announcement or presence update. Publish
+    /// the returned bytes with `SwarmHandle::publish_signed_topic` on the same
+    /// topic; the signed gossip frame is what authenticates it.
+    pub fn prepare_broadcast(
+        &self,
+        topic: String,
+        data: Vec<u8>,
+    ) -> Result<Vec<u8>, IronCoreError> {
+        let sender_id = self
+            .identity
+            .read()
+            .identity_id()
+            .ok_or(IronCoreError::NotInitialized)?;
+        crate::message::codec::validate_payload_size(&data)
+            .map_err(|_| IronCoreError::InvalidInput)?;
+        let message = Message::broadcast(sender_id, &topic, data);
+        crate::message::encode_message(&message).map_err(|_| IronCoreError::Internal)
+    }
+
+    /// Who an envelope is addressed to, without decrypting it. Returns the
+    /// 4-byte recipient hint (8 hex chars, the first bytes of
+    /// `blake3(recipient_public_key)`) for Drift envelopes, or the ephemeral
+    /// public key (64 hex chars) for envelopes that carry no hint. `None` if
+    /// the bytes are not an envelope.
+    pub fn envelope_recipient_hint(&self, envelope_bytes: Vec<u8>) -> Option<String> {
+        if envelope_bytes.first() == Some(&crate::drift::DRIFT_VERSION) {
+            if let Ok(drift_env) = crate::drift::DriftEnvelope::from_bytes(&envelope_bytes) {
+                if drift_env.recipient_hint != [0u8; 4] {
+                    return Some(hex::encode(drift_env.recipient_hint));
+                }
+            }
+        }
+        decode_envelope(&envelope_bytes)
+            .ok()
+            .map(|envelope| hex::encode(envelope.ephemeral_public_key))
+    }
+
+    /// Sender public key (hex) claimed by an envelope. This is not
+    /// authenticated until the envelope is decrypted. `None` if the bytes
+    /// are not an envelope.
+    pub fn envelope_sender(&self, envelope_bytes: Vec<u8>) -> Option<String> {
+        decode_envelope(&envelope_bytes)
+            .ok()
+            .map(|envelope| hex::encode(envelope.sender_public_key))
+    }
+
+    pub fn inbox_count(&self) -> u32 {
+        self.inbox.read().total_count() as u32
     }
 
     /// Drain all received messages from the inbox, clearing the buffer while
@@ -1239,6 +2027,33 @@ impl IronCore {
         self.inbox.read().all_messages()
     }
 
+    /// Case-insensitive substring search over received text messages, newest
+    /// first, returning at most `limit` results (0 = no limit). A linear scan
+    /// of the inbox, meant for modest inbox sizes rather than as an index;
+    /// receipts and other non-text payloads never match.
+    pub fn search_inbox(&self, query: String, limit: u32) -> Vec<ReceivedMessage> {
+        self.inbox.read().search(&query, limit as usize)
+    }
+
+    /// Received messages from one sender and/or of one type, newest first,
+    /// skipping `offset` and returning at most `limit` (0 = no limit), so
+    /// conversation views need not pull the whole inbox. Payloads that do not
+    /// decode as a known type are skipped when filtering by type.
+    pub fn list_inbox_filtered(
+        &self,
+        sender_public_key_hex: Option<String>,
+        message_type: Option<crate::MessageType>,
+        limit: u32,
+        offset: u32,
+    ) -> Vec<ReceivedMessage> {
+        self.inbox.read().filtered(
+            sender_public_key_hex.as_deref(),
+            message_type,
+            limit as usize,
+            offset as usize,
+        )
+    }
+
     // -----------------------------------------------------------------------
     // Store managers (returned to WASM for bridging)
     // -----------------------------------------------------------------------
@@ -1303,7 +2118,7 @@ impl IronCore {
         self.blocked_manager
             .write()
             .unblock(peer_id.clone(), device_id)?;
-        let _ = self.history_manager.unhide_messages_for_peer(&peer_id);
+        let _ = self.history().unhide_messages_for_peer(&peer_id);
         Ok(())
     }
 
@@ -1333,7 +2148,7 @@ impl IronCore {
             .write()
             .block_and_delete(peer_id.clone(), reason)?;
         // Purge messages from this peer
-        let _ = self.history_manager.remove_conversation(peer_id.clone());
+        let _ = self.history().remove_conversation(peer_id.clone());
         let _ = self.outbox.write().drain_for_peer(&peer_id);
         Ok(())
     }
@@ -1357,7 +2172,7 @@ impl IronCore {
 
     /// Clear all message history.
     pub fn clear_history(&self) -> Result<(), IronCoreError> {
-        self.history_manager.clear()
+        self.history().clear()
     }
 
     /// Get the list of all blocked identities (non-WASM version).
@@ -1397,7 +2212,8 @@ impl IronCore {
         let bridge_contacts_json = if self.storage_path.is_none() {
             None
         } else {
-            let bridge_contacts = self.contacts_manager()?.list()?;
+            let path = self.storage_path_for_identity(identity.public_key_hex());
+            let bridge_contacts = self.contacts_manager_at(path)?.list()?;
             if bridge_contacts.is_empty() {
                 None
             } else {
@@ -1632,7 +2448,8 @@ impl IronCore {
         // restore into. WASM has no contacts_bridge (UniFFI-only) at all.
         #[cfg(not(target_arch = "wasm32"))]
         if self.storage_path.is_some() {
-            let bridge = self.contacts_manager()?;
+            let path = self.storage_path_for_identity(identity.public_key_hex());
+            let bridge = self.contacts_manager_at(path)?;
             for contact in bridge_contacts {
                 bridge.add(contact)?;
             }
@@ -1655,25 +2472,93 @@ impl IronCore {
             None,
             None,
         );
+        drop(identity);
+
+        // Anything queued under the previous identity would now fail the
+        // recipient's sender-signature check.
+        self.reconcile_outbox_after_import()?;
         Ok(())
     }
 
+    /// Bring the outbox in line with the active identity after an identity
+    /// import. Entries sealed by a different identity are re-encrypted and
+    /// re-signed when their plaintext is still in sent history, and dropped
+    /// otherwise. Returns an `OutboxReconcileReport` as JSON.
+    pub fn reconcile_outbox_after_import(&self) -> Result<String, IronCoreError> {
+        let identity = self.identity.read();
+        let keys = identity.keys().ok_or(IronCoreError::NotInitialized)?;
+        let active_pk = keys.signing_key.verifying_key().to_bytes();
+        let sender_id = identity.identity_id().unwrap_or_default();
+
+        let mut report = crate::store::OutboxReconcileReport::default();
+        let pending = self.outbox.read().pending();
+        for queued in pending {
+            let sender = match crate::message::codec::decode_wire_envelope(&queued.envelope_data) {
+                Ok(crate::message::WireEnvelope::V1(e)) => e.sender_public_key,
+                Ok(crate::message::WireEnvelope::V2(e2)) => e2.sender_public_key,
+                Err(_) => {
+                    report.unreadable += 1;
+                    continue;
+                }
+            };
+            if sender.as_slice() == active_pk.as_slice() {
+                report.kept += 1;
+                continue;
+            }
+
+            let resealed = self
+                .history()
+                .get(queued.message_id.clone())
+                .ok()
+                .flatten()
+                .filter(|record| record.direction == MessageDirection::Sent)
+                .and_then(|record| {
+                    let recipient_pk: [u8; 32] =
+                        hex::decode(&queued.recipient_id).ok()?.try_into().ok()?;
+                    let message = crate::Message {
+                        id: queued.message_id.clone(),
+                        sender_id: sender_id.clone(),
+                        recipient_id: queued.recipient_id.clone(),
+                        message_type: crate::MessageType::Text,
+                        payload: record.content.into_bytes(),
+                        timestamp: record.timestamp,
+                    };
+                    self.seal_envelope(keys, &queued.recipient_id, recipient_pk, &message)
+                        .ok()
+                });
+
+            let mut outbox = self.outbox.write();
+            outbox.remove(&queued.message_id);
+            match resealed {
+                Some((envelope_data, _)) => {
+                    let _ = outbox.enqueue(QueuedMessage {
+                        envelope_data,
+                        attempts: 0,
+                        next_retry_at: None,
+                        ..queued
+                    });
+                    report.resealed += 1;
+                }
+                None => report.dropped += 1,
+            }
+        }
+
+        tracing::info!(
+            kept = report.kept,
+            resealed = report.resealed,
+            dropped = report.dropped,
+            unreadable = report.unreadable,
+            "Outbox reconciled with active identity"
+        );
+        serde_json::to_string(&report).map_err(|_| IronCoreError::Internal)
+    }
+
     /// Derive the Ed25519 public key hex from a libp2p PeerId string.
     pub fn extract_public_key_from_peer_id(
         &self,
         peer_id: String,
     ) -> Result<String, IronCoreError> {
-        let peer_id: libp2p::PeerId = peer_id.parse().map_err(|_| IronCoreError::InvalidInput)?;
-        // Ed25519 PeerIds use identity multihash (code 0) where the digest
-        // contains the protobuf-encoded public key.
-        let mh = peer_id.as_ref();
-        if mh.code() != 0 {
-            return Err(IronCoreError::Internal);
-        }
-        let pk = libp2p::identity::PublicKey::try_decode_protobuf(mh.digest())
-            .map_err(|_| IronCoreError::Internal)?;
-        let ed25519_pk = pk.try_into_ed25519().map_err(|_| IronCoreError::Internal)?;
-        Ok(hex::encode(ed25519_pk.to_bytes()))
+        public_key_hex_from_peer_id(&peer_id)
     }
 
     // -----------------------------------------------------------------------
@@ -1681,18 +2566,23 @@ impl IronCore {
     // -----------------------------------------------------------------------
 
     /// Prepare a delivery receipt envelope for the given message.
+    ///
+    /// Receipts are never acknowledged: `InvalidInput` if `message_id` is a
+    /// receipt this core received, so two clients cannot ACK each other's
+    /// ACKs forever.
     pub fn prepare_receipt(
         &self,
         _recipient_public_key_hex: String,
         message_id: String,
     ) -> Result<Vec<u8>, IronCoreError> {
+        if self.received_receipt_ids.read().contains(&message_id) {
+            tracing::warn!("Refusing to acknowledge receipt {}", message_id);
+            return Err(IronCoreError::InvalidInput);
+        }
         let receipt = crate::Receipt {
             message_id,
             status: crate::DeliveryStatus::Delivered,
-            timestamp: web_time::SystemTime::now()
-                .duration_since(web_time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs(),
+            timestamp: self.now_secs(),
         };
         crate::message::types::encode_receipt(&receipt).map_err(|_| IronCoreError::Internal)
     }
@@ -1878,6 +2768,111 @@ impl IronCore {
         Ok(())
     }
 
+    /// Flush the store and let the storage engine reclaim space left behind
+    /// by deleted and overwritten records (identity, contacts, history,
+    /// queues and logs all share it). Safe while running: records written
+    /// concurrently are never rolled back.
+    pub fn compact_storage(&self) -> Result<crate::CompactionReport, IronCoreError> {
+        let bytes_before = self.storage_backend.approximate_size().unwrap_or(0);
+        let records_rewritten = self.storage_backend.compact().map_err(|e| {
+            tracing::warn!("Storage compaction failed: {}", e);
+            IronCoreError::StorageError
+        })?;
+        let bytes_after = self.storage_backend.approximate_size().unwrap_or(0);
+        tracing::info!(
+            "Storage compacted: {} -> {} bytes ({} records rewritten)",
+            bytes_before,
+            bytes_after,
+            records_rewritten
+        );
+
+        let identity_id = self.identity.read().identity_id();
+        self.audit_log.write().append(
+            AuditEventType::StorageCompacted,
+            identity_id,
+            None,
+            Some(format!(
+                "bytes_before={} bytes_after={} rewritten={}",
+                bytes_before, bytes_after, records_rewritten
+            )),
+        );
+
+        Ok(crate::CompactionReport {
+            bytes_before,
+            bytes_after,
+            records_rewritten,
+        })
+    }
+
+    /// Start receiving chunked file `file_id` (sealed with
+    /// `crypto::seal_file_chunks`) into `output_path`. Chunks are fed in with
+    /// `receive_file_chunk` in any order and decrypted straight to disk.
+    pub fn start_file_receive(
+        &self,
+        file_id: String,
+        output_path: String,
+    ) -> Result<(), IronCoreError> {
+        let mut receives = self.file_receives.write();
+        if receives.contains_key(&file_id) {
+            return Err(IronCoreError::InvalidInput);
+        }
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&output_path)
+            .map_err(|e| {
+                tracing::warn!("Cannot open {} for file receive: {}", output_path, e);
+                IronCoreError::IoError
+            })?;
+        receives.insert(
+            file_id.clone(),
+            crate::crypto::FileReassembler::new(file_id, file, FILE_RECEIVE_MEMORY_BUDGET),
+        );
+        Ok(())
+    }
+
+    /// Verify, decrypt and write one sealed chunk of a receive started with
+    /// `start_file_receive`. Duplicate chunks are ignored.
+    pub fn receive_file_chunk(
+        &self,
+        file_id: String,
+        chunk: Vec<u8>,
+    ) -> Result<crate::FileReceiveProgress, IronCoreError> {
+        let identity = self.identity.read();
+        let keys = identity.keys().ok_or(IronCoreError::NotInitialized)?;
+        let mut receives = self.file_receives.write();
+        let receive = receives
+            .get_mut(&file_id)
+            .ok_or(IronCoreError::InvalidInput)?;
+        receive
+            .accept_sealed(&keys.signing_key, &chunk)
+            .map_err(file_chunk_error)?;
+        Ok(crate::FileReceiveProgress::new(file_id, receive.status()))
+    }
+
+    /// End a file receive. An incomplete file is left on disk as written;
+    /// the returned progress lists the chunk indices that never arrived.
+    pub fn finish_file_receive(
+        &self,
+        file_id: String,
+    ) -> Result<crate::FileReceiveProgress, IronCoreError> {
+        let receive = self
+            .file_receives
+            .write()
+            .remove(&file_id)
+            .ok_or(IronCoreError::InvalidInput)?;
+        let (_, status) = receive.finish().map_err(file_chunk_error)?;
+        if !status.complete {
+            tracing::warn!(
+                "File {} finished incomplete: missing chunks {:?}",
+                file_id,
+                status.missing_chunks
+            );
+        }
+        Ok(crate::FileReceiveProgress::new(file_id, status))
+    }
+
     pub fn update_disk_stats(&self, total_bytes: u64, free_bytes: u64) {
         self.storage_manager
             .read()
@@ -1914,6 +2909,76 @@ impl IronCore {
         })
     }
 
+    /// Export a structured diagnostics snapshot as a JSON string.
+    ///
+    /// Core-side parity with the WASM `exportDiagnostics`. `IronCore` does not
+    /// own the swarm, so callers with a running swarm pass its connected
+    /// peers and NAT status; when `None`, those keys are omitted.
+    pub fn export_diagnostics(
+        &self,
+        peers: Option<Vec<String>>,
+        nat_status: Option<String>,
+    ) -> String {
+        let mut payload = serde_json::Map::new();
+        payload.insert("running".to_string(), self.is_running().into());
+        payload.insert(
+            "identity_id".to_string(),
+            self.identity.read().identity_id().into(),
+        );
+        payload.insert("inbox_count".to_string(), self.inbox_count().into());
+        payload.insert("outbox_count".to_string(), self.outbox_count().into());
+        payload.insert(
+            "relay_enabled".to_string(),
+            self.settings.read().relay_enabled.into(),
+        );
+        if let Some(peers) = peers {
+            payload.insert("peer_count".to_string(), peers.len().into());
+            payload.insert("peers".to_string(), peers.into());
+        }
+        if let Some(nat_status) = nat_status {
+            payload.insert("nat_status".to_string(), nat_status.into());
+        }
+        let timestamp_ms = self.now_millis();
+        payload.insert("timestamp_ms".to_string(), timestamp_ms.into());
+        serde_json::Value::Object(payload).to_string()
+    }
+
+    /// Export the node's non-secret state as one JSON document for bug
+    /// reports: build, public identity, queue sizes, settings and the
+    /// recent warnings and errors captured by `store::log_ring`.
+    ///
+    /// Never includes key material, contacts or message content.
+    pub fn export_support_bundle(&self) -> String {
+        let info = self.get_identity_info();
+        let mut identity = serde_json::Map::new();
+        identity.insert("initialized".to_string(), info.initialized.into());
+        identity.insert("public_key_hex".to_string(), info.public_key_hex.into());
+        identity.insert("identity_id".to_string(), info.identity_id.into());
+        identity.insert("device_id".to_string(), info.device_id.into());
+        identity.insert("libp2p_peer_id".to_string(), info.libp2p_peer_id.into());
+        identity.insert("nickname".to_string(), info.nickname.into());
+
+        let mut payload = serde_json::Map::new();
+        payload.insert(
+            "schema_version".to_string(),
+            SUPPORT_BUNDLE_SCHEMA_VERSION.into(),
+        );
+        payload.insert("generated_at_ms".to_string(), self.now_millis().into());
+        payload.insert("build".to_string(), crate::get_build_provenance().into());
+        payload.insert("identity".to_string(), identity.into());
+        payload.insert("inbox_count".to_string(), self.inbox_count().into());
+        payload.insert("outbox_count".to_string(), self.outbox_count().into());
+        payload.insert(
+            "settings".to_string(),
+            serde_json::to_value(self.get_settings()).unwrap_or_default(),
+        );
+        payload.insert(
+            "recent_errors".to_string(),
+            serde_json::to_value(crate::store::log_ring::recent_log_entries()).unwrap_or_default(),
+        );
+        serde_json::Value::Object(payload).to_string()
+    }
+
     /// Get privacy config as a JSON string.
     pub fn get_privacy_config(&self) -> String {
         let config = self.privacy_config();
@@ -1989,13 +3054,7 @@ impl IronCore {
     pub fn contacts_manager(
         &self,
     ) -> Result<crate::contacts_bridge::ContactManager, crate::IronCoreError> {
-        let path = self.storage_path.clone().unwrap_or_default();
-        crate::contacts_bridge::ContactManager::new(path.clone())
-            .or_else(|_| crate::contacts_bridge::ContactManager::new(path))
-            .or_else(|e| {
-                tracing::error!("Failed to create contact manager: {:?}", e);
-                crate::contacts_bridge::ContactManager::new("".to_string())
-            })
+        self.contacts_manager_at(self.identity_storage_path())
     }
 
     /// Return the federated nickname for a contact (the nickname advertised by the peer).
@@ -2080,7 +3139,7 @@ impl IronCore {
     pub fn history_manager(
         &self,
     ) -> Result<crate::mobile_bridge::HistoryManager, crate::IronCoreError> {
-        let path = self.storage_path.clone().unwrap_or_default();
+        let path = self.identity_storage_path();
         crate::mobile_bridge::HistoryManager::new(path.clone())
             .or_else(|_| crate::mobile_bridge::HistoryManager::new(path))
             .or_else(|e| {
@@ -2348,10 +3407,7 @@ impl IronCore {
 
     /// Advance the routing engine by one tick. Returns state snapshot as JSON.
     pub fn routing_tick(&self) -> String {
-        let now = web_time::SystemTime::now()
-            .duration_since(web_time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
+        let now = self.now_secs();
         let mut guard = self.routing_engine.write();
         if let Some(engine) = guard.as_mut() {
             let maintenance = engine.tick(now);
@@ -2600,10 +3656,7 @@ impl IronCore {
                     .try_into()
                     .unwrap_or([0u8; 4]);
                 let msg_id: [u8; 16] = *uuid::Uuid::new_v4().as_bytes();
-                let now = web_time::SystemTime::now()
-                    .duration_since(web_time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs();
+                let now = self.now_secs();
                 let decision = engine.route_message_optimized(&hint, &msg_id, 128, now);
                 let format_hop = |hop: &crate::routing::NextHop| -> Vec<String> {
                     match hop {
@@ -2731,10 +3784,7 @@ impl IronCore {
                                     msg.state = crate::store::outbox::MessageState::Enqueued;
                                     let backoff_secs =
                                         2u64.saturating_pow(current_attempt.min(12)).min(3600);
-                                    let now_secs = web_time::SystemTime::now()
-                                        .duration_since(web_time::UNIX_EPOCH)
-                                        .unwrap_or_default()
-                                        .as_secs();
+                                    let now_secs = self.now_secs();
                                     msg.next_retry_at = Some(now_secs + backoff_secs);
 
                                     tracing::debug!(
@@ -2822,16 +3872,412 @@ pub fn decode_receipt(data: Vec<u8>) -> Result<crate::Receipt, IronCoreError> {
 
 // Non-FFI-safe methods moved to plain impl block to avoid uniffi::export compilation errors.
 impl IronCore {
-    /// Test-only: true if `message_id` is currently queued in the live
-    /// outbox for `recipient_id`. Used to assert single-ownership between
-    /// the active outbox and drift custody (see T2.5).
-    pub fn outbox_contains_for_recipient(&self, recipient_id: &str, message_id: &str) -> bool {
-        self.outbox
-            .read()
-            .peek_for_peer(recipient_id)
-            .iter()
-            .any(|m| m.message_id == message_id)
-    }
+    /// Encrypt and sign `message` for `recipient_pk` with the given identity
+    /// keys, producing the Drift wire bytes. Shared by `prepare_message` and
+    /// `reconcile_outbox_after_import`, which re-seals queued messages under
+    /// a newly imported identity.
+    fn seal_envelope(
+        &self,
+        keys: &crate::identity::IdentityKeys,
+        recipient_id: &str,
+        recipient_pk: [u8; 32],
+        message: &crate::Message,
+    ) -> Result<(Vec<u8>, crate::drift::DriftEnvelope), IronCoreError> {
+        let message_bytes =
+            crate::message::encode_message(message).map_err(|_| IronCoreError::Internal)?;
+
+        let aead_suite = *self.preferred_cipher.read();
+        let sealed = if ratchet_disabled() {
+            // LEGACY PATH (kill switch)
+            let envelope = encrypt_message_with_suite(
+                &keys.signing_key,
+                &recipient_pk,
+                &message_bytes,
+                aead_suite,
+            )
+            .map_err(|_| IronCoreError::CryptoError)?;
+            let drift_env = crate::drift::DriftEnvelope::from_legacy_envelope(
+                envelope,
+                message.id.clone(),
+                recipient_pk,
+                &keys.signing_key,
+            )
+            .map_err(|_| IronCoreError::Internal)?;
+            let envelope_data = drift_env.to_bytes().map_err(|_| IronCoreError::Internal)?;
+            (envelope_data, drift_env)
+        } else {
+            // RATCHET PATH -- identity.read() already held; take
+            // ratchet_sessions.write() AFTER (identity-first, proven-safe).
+            // TODO cache our_bundle per identity_id (per-send Ed25519+ML-DSA
+            // signing is wasteful) -- E-00 ships correct-first.
+            let our_bundle = crate::identity::sign_bundle(keys).ok();
+            let recipient_bundle = self
+                .contact_manager
+                .read()
+                .get_contact_bundle(recipient_id)
+                .ok()
+                .flatten();
+            let peer_id = recipient_id.to_string();
+            let signing_key = keys.signing_key.clone();
+            let mut sessions = self.ratchet_sessions.write();
+            let mut audit = self.audit_log.write();
+            let wire = crate::crypto::encrypt::encrypt_with_ratchet_fallback_suite(
+                &signing_key,
+                recipient_bundle.as_ref(),
+                &recipient_pk,
+                &message_bytes,
+                Some(&mut *sessions),
+                &peer_id,
+                our_bundle.as_ref(),
+                false,
+                Some(&mut *audit),
+                aead_suite,
+            )
+            .map_err(|_| IronCoreError::CryptoError)?;
+            let drift_env = match wire {
+                crate::message::WireEnvelope::V1(env) => {
+                    crate::drift::DriftEnvelope::from_legacy_envelope(
+                        env,
+                        message.id.clone(),
+                        recipient_pk,
+                        &signing_key,
+                    )
+                    .map_err(|_| IronCoreError::Internal)?
+                }
+                crate::message::WireEnvelope::V2(env2) => {
+                    crate::drift::DriftEnvelope::from_v2_envelope(
+                        env2,
+                        message.id.clone(),
+                        recipient_pk,
+                        &signing_key,
+                    )
+                    .map_err(|_| IronCoreError::Internal)?
+                }
+            };
+            let envelope_data = drift_env.to_bytes().map_err(|_| IronCoreError::Internal)?;
+            (envelope_data, drift_env)
+        };
+        if let Some(generator) = self.cover_traffic_generator.read().as_ref() {
+            generator.observe_real_size(sealed.0.len());
+        }
+        if let Some(engine) = self.drift_engine.write().as_mut() {
+            engine.note_real_activity();
+        }
+        Ok(sealed)
+    }
+
+    /// History store of the active identity.
+    fn history(&self) -> Arc<CoreHistoryManager> {
+        self.history_manager.read().clone()
+    }
+
+    /// Public key hex of the identity whose contacts and history sit at the
+    /// top of the store. Recorded for `active` the first time it is asked,
+    /// since everything stored until then is the active identity's.
+    fn root_identity(&self, active: &str) -> Result<String, IronCoreError> {
+        if let Some(root) = self
+            .storage_backend
+            .get(ROOT_IDENTITY_KEY)
+            .ok()
+            .flatten()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+        {
+            return Ok(root);
+        }
+        self.storage_backend
+            .put(ROOT_IDENTITY_KEY, active.as_bytes())
+            .map_err(|e| {
+                tracing::error!("Recording the root identity failed: {}", e);
+                IronCoreError::StorageError
+            })?;
+        Ok(active.to_string())
+    }
+
+    /// Sled store, identity and settings at `path` for the persistent
+    /// constructors, falling back to memory if the store cannot be opened.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn open_persistent(path: String, log_directory: Option<String>) -> Self {
+        let backend: Arc<dyn StorageBackend> = match SledStorage::new(&path) {
+            Ok(s) => Arc::new(s),
+            Err(_) => Arc::new(MemoryStorage::new()),
+        };
+        let settings = crate::mobile_bridge::MeshSettingsManager::new(path.clone())
+            .load()
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to load mesh settings, using defaults: {:?}", e);
+                crate::settings::MeshSettings::default()
+            });
+        let identity = IdentityManager::with_backend(backend.clone()).unwrap_or_else(|_| {
+            tracing::error!(
+                "Failed to hydrate identity from persistent store, falling back to memory"
+            );
+            IdentityManager::new()
+        });
+        Self::open(backend, identity, settings, Some(path), log_directory)
+    }
+
+    /// Build a core over `backend`; every constructor goes through here so
+    /// the active identity's part of the store is always the one in use.
+    fn open(
+        backend: Arc<dyn StorageBackend>,
+        identity: IdentityManager,
+        settings: crate::settings::MeshSettings,
+        storage_path: Option<String>,
+        log_directory: Option<String>,
+    ) -> Self {
+        let contact_manager = CoreContactManager::new(backend.clone());
+        contact_manager.set_max_contacts(settings.max_contacts);
+        let history_manager = Arc::new(CoreHistoryManager::new(backend.clone()));
+        let log_mgr = Arc::new(LogManager::new(backend.clone()));
+        let blocked_manager = CoreBlockedManager::new(backend.clone());
+        let blocked_for_auto_block = CoreBlockedManager::new(backend.clone());
+        let inbox = Inbox::new();
+        let outbox = Outbox::new();
+        let storage_manager =
+            StorageManager::new(backend.clone(), history_manager.clone(), log_mgr.clone());
+        let spam_detector =
+            SpamDetectionEngine::new_heuristics_only(SpamDetectionConfig::default());
+        let abuse_mgr = EnhancedAbuseReputationManager::new(1000, spam_detector);
+        let auto_block_spam =
+            SpamDetectionEngine::new_heuristics_only(SpamDetectionConfig::default());
+        let auto_block_reputation = EnhancedAbuseReputationManager::new(1000, auto_block_spam);
+        let auto_block = AutoBlockEngine::new(
+            AutoBlockConfig::default(),
+            Arc::new(blocked_for_auto_block),
+            Arc::new(auto_block_reputation),
+        );
+        let security_audit_pipeline =
+            Arc::new(crate::dspy::modules::ModuleFactory::build_security_audit_pipeline());
+
+        let transport_memory =
+            crate::store::transport_memory::TransportMemoryStore::new(backend.clone());
+
+        let core = Self {
+            identity: Arc::new(RwLock::new(identity)),
+            outbox: Arc::new(RwLock::new(outbox)),
+            inbox: Arc::new(RwLock::new(inbox)),
+            parked_inboxes: Arc::new(RwLock::new(std::collections::HashMap::new())),
+            contact_manager: Arc::new(RwLock::new(contact_manager)),
+            history_manager: Arc::new(RwLock::new(history_manager)),
+            storage_manager: Arc::new(RwLock::new(storage_manager)),
+            log_manager: log_mgr,
+            blocked_manager: Arc::new(RwLock::new(blocked_manager)),
+            audit_log: Arc::new(RwLock::new(AuditLogType::new())),
+            relay_custody_store: Arc::new(RwLock::new(RelayCustodyStore::persistent(
+                backend.clone(),
+            ))),
+            delegate: Arc::new(RwLock::new(None)),
+            consent: Arc::new(RwLock::new(ConsentState::NotGranted)),
+            drift_active: Arc::new(RwLock::new(false)),
+            drift_store: Arc::new(RwLock::new(MeshStore::persistent(backend.clone()))),
+            drift_engine: Arc::new(RwLock::new(None)),
+            abuse_manager: Arc::new(RwLock::new(abuse_mgr)),
+            auto_block_engine: Arc::new(RwLock::new(auto_block)),
+            #[cfg(not(target_arch = "wasm32"))]
+            ledger_manager: crate::store::LedgerManager::new(
+                storage_path
+                    .clone()
+                    .unwrap_or_else(|| std::env::temp_dir().to_str().unwrap_or("/tmp").to_string()),
+            ),
+            storage_path,
+            log_directory,
+            running: Arc::new(RwLock::new(false)),
+            routing_engine: Arc::new(RwLock::new(None)),
+            cover_traffic_generator: Arc::new(RwLock::new(None)),
+            timing_jitter: Arc::new(RwLock::new(None)),
+            circuit_builder: Arc::new(RwLock::new(None)),
+            notification_endpoint_registry: Arc::new(RwLock::new(
+                NotificationEndpointRegistry::new(),
+            )),
+            transport_manager: Arc::new(RwLock::new(TransportManager::new())),
+            #[cfg(not(target_arch = "wasm32"))]
+            bootstrap_manager: Arc::new(RwLock::new(None)),
+            #[cfg(not(target_arch = "wasm32"))]
+            relay_bootstrap_manager: Arc::new(RwLock::new(Some(
+                crate::transport::bootstrap::BootstrapManager::with_defaults(),
+            ))),
+            #[cfg(not(target_arch = "wasm32"))]
+            peer_exchange_manager: Arc::new(RwLock::new(PeerExchangeManager::new())),
+            ratchet_sessions: Arc::new(RwLock::new(RatchetSessionManager::new())),
+            security_audit_pipeline,
+            privacy_config: Arc::new(RwLock::new(crate::privacy::PrivacyConfig::default())),
+            policy_engine: Arc::new(RwLock::new(crate::drift::PolicyEngine::new())),
+            transport_memory: Arc::new(RwLock::new(transport_memory)),
+            drafts: crate::store::Drafts::new(backend.clone()),
+            delivery_log: crate::store::DeliveryLog::open(backend.clone()),
+            escalation_engine: Arc::new(RwLock::new(
+                crate::transport::escalation::EscalationEngine::default(),
+            )),
+            pending_acks: Arc::new(RwLock::new(std::collections::HashMap::new())),
+            relay_only: Arc::new(RwLock::new(false)),
+            settings: Arc::new(RwLock::new(settings)),
+            preferred_cipher: Arc::new(RwLock::new(crate::crypto::AeadSuite::default())),
+            presence_heartbeat: Arc::new(RwLock::new(
+                crate::transport::presence::PresenceHeartbeat::default(),
+            )),
+            inbox_sweeper: Arc::new(RwLock::new(None)),
+            storage_backend: backend.clone(),
+            clock: Arc::new(RwLock::new(Arc::new(crate::clock::SystemClock::default()))),
+            file_receives: Arc::new(RwLock::new(std::collections::HashMap::new())),
+            received_receipt_ids: Arc::new(RwLock::new(ReceivedReceiptIds::default())),
+            delivery_waiters: Arc::new(RwLock::new(DeliveryWaiters::new())),
+        };
+        core.open_active_identity_store();
+        core
+    }
+
+    /// The part of the store holding `public_key_hex`'s contacts, history
+    /// and parked ratchet sessions.
+    fn identity_store(&self, public_key_hex: &str, root: &str) -> Arc<dyn StorageBackend> {
+        if public_key_hex == root {
+            self.storage_backend.clone()
+        } else {
+            Arc::new(PrefixedStorage::new(
+                self.storage_backend.clone(),
+                format!("identity_store:{}:", public_key_hex),
+            ))
+        }
+    }
+
+    /// Point contacts and history at the active identity's part of the
+    /// store, for a core reopened while an added identity is active.
+    fn open_active_identity_store(&self) {
+        let Some(active) = self.identity.read().public_key_hex() else {
+            return;
+        };
+        if let Ok(Some(root)) = self.storage_backend.get(ROOT_IDENTITY_KEY) {
+            if root != active.as_bytes() {
+                let root = String::from_utf8_lossy(&root).into_owned();
+                self.use_identity_store(self.identity_store(&active, &root));
+            }
+        }
+    }
+
+    /// Move contacts and history over to `store`.
+    fn use_identity_store(&self, store: Arc<dyn StorageBackend>) {
+        let contact_manager = CoreContactManager::new(store.clone());
+        contact_manager.set_max_contacts(self.settings.read().max_contacts);
+        *self.contact_manager.write() = contact_manager;
+        let history = Arc::new(CoreHistoryManager::new(store));
+        {
+            let mut storage_manager = self.storage_manager.write();
+            let retention = storage_manager.retention.clone();
+            *storage_manager = StorageManager::with_retention(
+                self.storage_backend.clone(),
+                history.clone(),
+                self.log_manager.clone(),
+                retention,
+            );
+        }
+        *self.history_manager.write() = history;
+    }
+
+    /// Directory of the active identity's bridge stores (`contacts_manager`,
+    /// `history_manager`): the storage path for the root identity,
+    /// `identities/<public key hex>` under it for the others.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn identity_storage_path(&self) -> String {
+        let active = self.identity.read().public_key_hex();
+        self.storage_path_for_identity(active)
+    }
+
+    /// `identity_storage_path` for a caller already holding the identity
+    /// lock, which must not be taken again.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn storage_path_for_identity(&self, active: Option<String>) -> String {
+        let path = self.storage_path.clone().unwrap_or_default();
+        let Some(active) = active else {
+            return path;
+        };
+        match self.storage_backend.get(ROOT_IDENTITY_KEY) {
+            Ok(Some(root)) if !path.is_empty() && root != active.as_bytes() => {
+                std::path::Path::new(&path)
+                    .join("identities")
+                    .join(active)
+                    .to_string_lossy()
+                    .into_owned()
+            }
+            _ => path,
+        }
+    }
+
+    /// Bridge contact store at `path` (see `identity_storage_path`), with the
+    /// mesh settings' contact cap applied.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn contacts_manager_at(
+        &self,
+        path: String,
+    ) -> Result<crate::contacts_bridge::ContactManager, crate::IronCoreError> {
+        let manager = crate::contacts_bridge::ContactManager::new(path.clone())
+            .or_else(|_| crate::contacts_bridge::ContactManager::new(path.clone()))
+            .or_else(|e| {
+                tracing::error!("Failed to create contact manager: {:?}", e);
+                crate::contacts_bridge::ContactManager::new("".to_string())
+            })?;
+        // Another identity's directory has no settings file of its own.
+        crate::contacts_bridge::set_max_contacts_for_storage(
+            &path,
+            self.settings.read().max_contacts,
+        );
+        Ok(manager)
+    }
+
+    /// `MeshParticipationDisabled` while the relay toggle is off, matching
+    /// the WASM client's enforcement.
+    fn ensure_mesh_participation_enabled(&self) -> Result<(), IronCoreError> {
+        if self.settings.read().relay_enabled {
+            Ok(())
+        } else {
+            Err(IronCoreError::MeshParticipationDisabled)
+        }
+    }
+
+    /// Whether the live mesh settings put discovery in Paranoid mode, which
+    /// disables presence beacons.
+    fn presence_suppressed_by_settings(&self) -> bool {
+        self.settings.read().discovery_mode == crate::settings::DiscoveryMode::Paranoid
+    }
+
+    /// Public key of the saved contact `query` names. Exact identifiers
+    /// (public key, peer ID, identity ID) win over nicknames; a nickname
+    /// shared by several contacts is ambiguous and rejected.
+    fn resolve_contact_public_key(&self, query: &str) -> Result<String, IronCoreError> {
+        match self.contact_manager.read().resolve(query)? {
+            ContactLookup::Found(contact) => Ok(contact.public_key),
+            ContactLookup::NotFound => {
+                tracing::warn!("Contact not found: {}", query.trim());
+                Err(IronCoreError::InvalidInput)
+            }
+            ContactLookup::Ambiguous(n) => {
+                tracing::warn!(
+                    "Nickname {} matches {} contacts; use a public key or peer ID",
+                    query.trim(),
+                    n
+                );
+                Err(IronCoreError::InvalidInput)
+            }
+        }
+    }
+
+    /// Like `with_storage`, but first runs the storage preflight so a
+    /// read-only or full disk surfaces as `StorageUnwritable` / `StorageFull`
+    /// instead of a silent fallback to in-memory storage.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn try_with_storage(path: String, min_free_bytes: u64) -> Result<Self, IronCoreError> {
+        crate::store::preflight_storage(std::path::Path::new(&path), min_free_bytes)?;
+        Ok(Self::with_storage(path))
+    }
+
+    /// Test-only: true if `message_id` is currently queued in the live
+    /// outbox for `recipient_id`. Used to assert single-ownership between
+    /// the active outbox and drift custody (see T2.5).
+    pub fn outbox_contains_for_recipient(&self, recipient_id: &str, message_id: &str) -> bool {
+        self.outbox
+            .read()
+            .peek_for_peer(recipient_id)
+            .iter()
+            .any(|m| m.message_id == message_id)
+    }
 
     /// Test-only: direct access to the ratchet session manager, so
     /// integration tests can establish a session and verify it survives
@@ -2940,53 +4386,234 @@ impl IronCore {
         keys.to_libp2p_keypair()
             .map_err(|_| IronCoreError::CryptoError)
     }
-    pub fn receive_message(&self, envelope_data: Vec<u8>) -> Result<Message, IronCoreError> {
-        // Hoist sender public key and local identity id out of the legacy /
-        // ratchet branches so they remain in scope for downstream inbox / audit
-        // handling.
-        let sender_pubkey: Vec<u8>;
-        let local_identity_id: Option<String>;
+    /// Seal delivery receipts for many `(recipient_public_key_hex, message_id)`
+    /// pairs at once, e.g. when catching up after being offline. The identity
+    /// lock is taken once for the whole batch. Items with an invalid recipient
+    /// key are skipped and reported by message id instead of failing the
+    /// batch, as are ids of received receipts (see `prepare_receipt`). Each
+    /// returned `PreparedMessage` carries the acknowledged message id
+    /// alongside the receipt envelope.
+    pub fn prepare_receipts_bulk(
+        &self,
+        items: Vec<(String, String)>,
+    ) -> Result<crate::BulkReceipts, IronCoreError> {
+        let identity = self.identity.read();
+        let keys = identity.keys().ok_or(IronCoreError::NotInitialized)?;
+        let sender_id = identity.identity_id().unwrap_or_default();
+        let now = self.now_secs();
+
+        let mut result = crate::BulkReceipts::default();
+        let received_receipts = self.received_receipt_ids.read();
+        for (recipient_id, message_id) in items {
+            if received_receipts.contains(&message_id) {
+                result.skipped.push(message_id);
+                continue;
+            }
+            let recipient_pk: Option<[u8; 32]> = hex::decode(&recipient_id)
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .filter(|_| crate::crypto::validate_ed25519_public_key(&recipient_id).is_ok());
+            let Some(recipient_pk) = recipient_pk else {
+                result.skipped.push(message_id);
+                continue;
+            };
 
-        let plaintext = if ratchet_disabled() {
-            // LEGACY PATH (kill switch)
-            let envelope = decode_envelope(&envelope_data).map_err(|e| {
-                tracing::warn!("Failed to decode envelope: {:?}", e);
-                IronCoreError::CryptoError
-            })?;
-            let identity = self.identity.read();
-            let keys = identity.keys().ok_or(IronCoreError::NotInitialized)?;
-            local_identity_id = identity.identity_id();
-            sender_pubkey = envelope.sender_public_key.clone();
-            let signing_key = keys.signing_key.clone();
-            decrypt_message(&signing_key, &envelope).map_err(|e| {
-                tracing::warn!("Failed to decrypt message: {:?}", e);
-                IronCoreError::CryptoError
-            })?
-        } else {
-            // RATCHET PATH -- identity.read() then ratchet_sessions.write().
-            // identity and ratchet_sessions are disjoint fields of self, so
-            // both guards can be held simultaneously while preserving the
-            // identity-first lock order.
-            let wire =
-                crate::message::codec::decode_wire_envelope(&envelope_data).map_err(|e| {
-                    tracing::warn!("Failed to decode wire envelope: {:?}", e);
-                    IronCoreError::CryptoError
-                })?;
-            let identity = self.identity.read();
-            let keys = identity.keys().ok_or(IronCoreError::NotInitialized)?;
-            local_identity_id = identity.identity_id();
-            sender_pubkey = match &wire {
-                crate::message::WireEnvelope::V1(e) => e.sender_public_key.clone(),
-                crate::message::WireEnvelope::V2(e2) => e2.sender_public_key.clone(),
+            let receipt = crate::Receipt {
+                message_id: message_id.clone(),
+                status: crate::DeliveryStatus::Delivered,
+                timestamp: now,
             };
-            let sender_bundle = self
-                .contact_manager
-                .read()
-                .get_contact_bundle(&hex::encode(&sender_pubkey))
-                .ok()
-                .flatten();
+            let sealed = crate::Message::receipt(sender_id.clone(), recipient_id.clone(), &receipt)
+                .map_err(|_| IronCoreError::Internal)
+                .and_then(|message| {
+                    self.seal_envelope(keys, &recipient_id, recipient_pk, &message)
+                });
+            match sealed {
+                Ok((envelope_data, _)) => result.envelopes.push(crate::PreparedMessage {
+                    message_id,
+                    envelope_data,
+                }),
+                Err(_) => result.skipped.push(message_id),
+            }
+        }
+
+        if !result.skipped.is_empty() {
+            tracing::warn!(
+                "Bulk receipts: {} sealed, {} skipped",
+                result.envelopes.len(),
+                result.skipped.len()
+            );
+        }
+        Ok(result)
+    }
+
+    /// Decode a message produced by `prepare_message_portable` (scanned QR
+    /// code or file contents) and process it like any received envelope.
+    pub fn receive_portable(&self, portable: String) -> Result<Message, IronCoreError> {
+        let envelope_data = crate::drift::decode_portable(&portable)
+            .map_err(|_| IronCoreError::EnvelopeDecodeFailed)?;
+        self.receive_message(envelope_data)
+    }
+
+    /// Replace the time source (e.g. a `MockClock` in tests).
+    pub fn set_clock(&self, clock: Arc<dyn crate::clock::Clock>) {
+        *self.clock.write() = clock;
+    }
+
+    pub(crate) fn now_secs(&self) -> u64 {
+        self.clock.read().now_secs()
+    }
+
+    pub(crate) fn now_millis(&self) -> u64 {
+        self.clock.read().now_millis()
+    }
+
+    /// Decrypt, verify and store an inbound envelope, notifying the delegate.
+    /// In relay-only mode every envelope fails with `RelayOnly`; relays read
+    /// the sender key and size for forwarding with `relay_envelope_info`.
+    pub fn receive_message(&self, envelope_data: Vec<u8>) -> Result<Message, IronCoreError> {
+        if *self.relay_only.read() {
+            // Checked before any key material or store is touched, so a relay
+            // can never turn into a content sink.
+            return Err(IronCoreError::RelayOnly);
+        }
+        self.ensure_mesh_participation_enabled()?;
+        let mut notifications = Vec::new();
+        let result = {
+            let identity = self.identity.read();
+            self.receive_with_identity(&identity, envelope_data, &mut notifications)
+        };
+        self.dispatch_inbound_notifications(notifications);
+        result
+    }
+
+    /// Batch form of `receive_message` for draining a backlog: the identity
+    /// lock is taken once for the whole batch instead of once per envelope.
+    /// Results line up with `envelopes`, and a bad envelope only fails its
+    /// own slot. An envelope repeated within the batch yields the same
+    /// message without being stored or reported to the delegate twice.
+    /// Delegate callbacks run after the batch, outside the identity lock.
+    pub fn receive_messages(&self, envelopes: Vec<Vec<u8>>) -> Vec<Result<Message, IronCoreError>> {
+        if *self.relay_only.read() {
+            return envelopes
+                .iter()
+                .map(|_| Err(IronCoreError::RelayOnly))
+                .collect();
+        }
+        if self.ensure_mesh_participation_enabled().is_err() {
+            return envelopes
+                .iter()
+                .map(|_| Err(IronCoreError::MeshParticipationDisabled))
+                .collect();
+        }
+        let mut notifications = Vec::new();
+        let mut results: Vec<Result<Message, IronCoreError>> = Vec::with_capacity(envelopes.len());
+        {
+            let identity = self.identity.read();
+            let mut first_seen: std::collections::HashMap<[u8; 32], usize> =
+                std::collections::HashMap::new();
+            for envelope_data in envelopes {
+                let digest = *blake3::hash(&envelope_data).as_bytes();
+                let earlier = first_seen
+                    .get(&digest)
+                    .and_then(|&index| results[index].as_ref().ok())
+                    .cloned();
+                let result = match earlier {
+                    Some(message) => Ok(message),
+                    None => {
+                        first_seen.insert(digest, results.len());
+                        self.receive_with_identity(&identity, envelope_data, &mut notifications)
+                    }
+                };
+                results.push(result);
+            }
+        }
+        self.dispatch_inbound_notifications(notifications);
+        results
+    }
+
+    fn dispatch_inbound_notifications(&self, notifications: Vec<InboundNotification>) {
+        if notifications.is_empty() {
+            return;
+        }
+        let delegate = self.delegate.read();
+        let Some(delegate) = delegate.as_ref() else {
+            return;
+        };
+        for notification in notifications {
+            match notification {
+                InboundNotification::Receipt { message_id, status } => {
+                    delegate.on_receipt_received(message_id, status)
+                }
+                InboundNotification::Message(message) => delegate.on_message_received(
+                    message.sender_id.clone(),
+                    message.sender_id,
+                    message.id,
+                    message.timestamp,
+                    message.payload,
+                ),
+                InboundNotification::Typing {
+                    sender_public_key_hex,
+                    is_typing,
+                } => delegate.on_typing(sender_public_key_hex, is_typing),
+                InboundNotification::Unknown {
+                    type_id,
+                    sender_public_key_hex,
+                    payload,
+                } => delegate.on_unknown_message(type_id, sender_public_key_hex, payload),
+            }
+        }
+    }
+
+    /// Decrypt an inbound envelope with the local identity. Returns the
+    /// plaintext, the sender's public key and the local identity id.
+    #[allow(clippy::type_complexity)]
+    fn open_envelope(
+        &self,
+        identity: &IdentityManager,
+        envelope_data: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>, Option<String>), IronCoreError> {
+        let sender_pubkey: Vec<u8>;
+        let local_identity_id: Option<String>;
+
+        let plaintext = if ratchet_disabled() {
+            // LEGACY PATH (kill switch)
+            let envelope = decode_envelope(envelope_data).map_err(|e| {
+                tracing::warn!("Failed to decode envelope: {:?}", e);
+                envelope_decode_error(&e)
+            })?;
+            let keys = identity.keys().ok_or(IronCoreError::NotInitialized)?;
+            local_identity_id = identity.identity_id();
+            sender_pubkey = envelope.sender_public_key.clone();
+            let signing_key = keys.signing_key.clone();
+            let local_public_key = signing_key.verifying_key().to_bytes();
+            decrypt_message(&signing_key, &envelope).map_err(|e| {
+                tracing::warn!("Failed to decrypt message: {:?}", e);
+                decrypt_error(&e, envelope_data, &local_public_key)
+            })?
+        } else {
+            // RATCHET PATH -- identity (held by the caller) then
+            // ratchet_sessions.write(), preserving the identity-first lock
+            // order.
+            let wire = crate::message::codec::decode_wire_envelope(envelope_data).map_err(|e| {
+                tracing::warn!("Failed to decode wire envelope: {:?}", e);
+                envelope_decode_error(&e)
+            })?;
+            let keys = identity.keys().ok_or(IronCoreError::NotInitialized)?;
+            local_identity_id = identity.identity_id();
+            sender_pubkey = match &wire {
+                crate::message::WireEnvelope::V1(e) => e.sender_public_key.clone(),
+                crate::message::WireEnvelope::V2(e2) => e2.sender_public_key.clone(),
+            };
+            let sender_bundle = self
+                .contact_manager
+                .read()
+                .get_contact_bundle(&hex::encode(&sender_pubkey))
+                .ok()
+                .flatten();
             let our_bundle = crate::identity::sign_bundle(keys).ok();
             let signing_key = keys.signing_key.clone();
+            let local_public_key = signing_key.verifying_key().to_bytes();
             let mut sessions = self.ratchet_sessions.write();
             crate::crypto::encrypt::decrypt_with_ratchet_fallback(
                 &signing_key,
@@ -2999,14 +4626,81 @@ impl IronCore {
             )
             .map_err(|e| {
                 tracing::warn!("Failed to decrypt ratchet message: {:?}", e);
-                IronCoreError::CryptoError
+                decrypt_error(&e, envelope_data, &local_public_key)
             })?
         };
+        Ok((plaintext, sender_pubkey, local_identity_id))
+    }
 
-        let message = decode_message(&plaintext).map_err(|e| {
-            tracing::warn!("Failed to decode message: {:?}", e);
-            IronCoreError::Internal
-        })?;
+    /// Open an onion-relay layer wrapped for this node and return the next
+    /// hop (public key hex) and the envelope to forward to it. Allowed in
+    /// relay-only mode: nothing is stored, recorded or reported to the
+    /// delegate, and any other message type fails with `RelayOnly`.
+    pub fn peel_onion_relay(
+        &self,
+        envelope_data: Vec<u8>,
+    ) -> Result<(String, Vec<u8>), IronCoreError> {
+        let identity = self.identity.read();
+        let (plaintext, _, _) = self.open_envelope(&identity, &envelope_data)?;
+        match decode_message(&plaintext) {
+            Ok(message) if message.message_type == crate::MessageType::OnionRelay => {
+                Ok((message.recipient_id, message.payload))
+            }
+            _ => Err(IronCoreError::RelayOnly),
+        }
+    }
+
+    /// Open, record and audit one inbound envelope with the active identity
+    /// already locked. Delegate callbacks are queued on `notifications` for
+    /// the caller to dispatch once the lock is released.
+    fn receive_with_identity(
+        &self,
+        identity: &IdentityManager,
+        envelope_data: Vec<u8>,
+        notifications: &mut Vec<InboundNotification>,
+    ) -> Result<Message, IronCoreError> {
+        // The id is only known once the envelope is opened; it is recorded
+        // onto the span then.
+        let span = tracing::info_span!(
+            "message",
+            message_id = tracing::field::Empty,
+            direction = "inbound"
+        );
+        let _enter = span.enter();
+
+        let (plaintext, sender_pubkey, local_identity_id) =
+            self.open_envelope(identity, &envelope_data)?;
+
+        let message = match decode_message(&plaintext) {
+            Ok(message) => message,
+            Err(e) => {
+                // A type from a newer sender still reaches the app, undecoded,
+                // once per id. The caller gets a text stand-in so it can
+                // acknowledge it and the sender stops retrying.
+                let Some(unknown) = crate::message::decode_unknown_message(&plaintext) else {
+                    tracing::warn!("Failed to decode message: {:?}", e);
+                    return Err(IronCoreError::Internal);
+                };
+                span.record("message_id", unknown.id.as_str());
+                tracing::info!("Received message of unknown type {}", unknown.type_id);
+                let is_blocked = self
+                    .blocked_manager
+                    .read()
+                    .is_blocked(&unknown.sender_id, None)
+                    .unwrap_or(false);
+                let first_seen = self.inbox.write().mark_seen(&unknown.id);
+                let fallback = unknown.fallback();
+                if first_seen && !is_blocked {
+                    notifications.push(InboundNotification::Unknown {
+                        type_id: unknown.type_id,
+                        sender_public_key_hex: hex::encode(&sender_pubkey),
+                        payload: unknown.payload,
+                    });
+                }
+                return Ok(fallback);
+            }
+        };
+        span.record("message_id", message.id.as_str());
 
         // Check blocked status (peer-level and device-specific)
         let is_blocked_and_deleted = self
@@ -3033,17 +4727,52 @@ impl IronCore {
             .is_blocked(&message.sender_id, sender_device_id.as_deref())
             .unwrap_or(false);
 
-        // Handle receipt classification AFTER blocked-peer check to prevent metadata leaks/spam bypass
+        // Typing indicators are live-only: no inbox, history, dedup or audit.
+        if message.message_type == crate::MessageType::Typing {
+            if !is_blocked {
+                notifications.push(InboundNotification::Typing {
+                    sender_public_key_hex: hex::encode(&sender_pubkey),
+                    is_typing: message.typing_state().unwrap_or(false),
+                });
+            }
+            return Ok(message);
+        }
+
+        // Handle receipt classification AFTER blocked-peer check to prevent metadata leaks/spam bypass.
+        // Receipts are terminal: they are never acknowledged (see
+        // `prepare_receipt`), and a repeated one is not reported again.
         if message.message_type == crate::MessageType::Receipt {
+            self.received_receipt_ids.write().insert(&message.id);
+            if self.inbox.read().is_duplicate(&message.id) {
+                tracing::debug!("Ignoring duplicate receipt {}", message.id);
+                return Ok(message);
+            }
             if let Ok(receipt) = crate::message::types::decode_receipt(&message.payload) {
-                if let Some(delegate) = self.delegate.read().as_ref() {
-                    let status_str = match receipt.status {
-                        crate::DeliveryStatus::Sent => "Sent".to_string(),
-                        crate::DeliveryStatus::Delivered => "Delivered".to_string(),
-                        _ => "Delivered".to_string(),
-                    };
-                    delegate.on_receipt_received(receipt.message_id, status_str);
-                }
+                self.pending_acks.write().remove(&receipt.message_id);
+                let _ = match receipt.status {
+                    crate::DeliveryStatus::Sent => self.delivery_log.mark_sent(&receipt.message_id),
+                    crate::DeliveryStatus::Failed => {
+                        self.resolve_delivery_waiters(&receipt.message_id, DeliveryOutcome::Failed);
+                        self.delivery_log
+                            .mark_failed(&receipt.message_id, "recipient reported failure")
+                    }
+                    _ => {
+                        self.resolve_delivery_waiters(
+                            &receipt.message_id,
+                            DeliveryOutcome::Delivered,
+                        );
+                        self.delivery_log.mark_delivered(&receipt.message_id)
+                    }
+                };
+                let status = match receipt.status {
+                    crate::DeliveryStatus::Sent => "Sent".to_string(),
+                    crate::DeliveryStatus::Failed => "Failed".to_string(),
+                    _ => "Delivered".to_string(),
+                };
+                notifications.push(InboundNotification::Receipt {
+                    message_id: receipt.message_id,
+                    status,
+                });
             } else {
                 tracing::warn!(
                     "Failed to parse receipt payload from sender {}: malformed JSON",
@@ -3055,10 +4784,7 @@ impl IronCore {
         }
 
         // Record in inbox and history (single lock acquisition prevents TOCTOU)
-        let now = web_time::SystemTime::now()
-            .duration_since(web_time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis() as u64;
+        let now = self.now_millis();
         {
             let mut inbox = self.inbox.write();
             if !inbox.is_duplicate(&message.id) {
@@ -3069,12 +4795,13 @@ impl IronCore {
                     payload: message.payload.clone(),
                     received_at: now,
                     sender_public_key_hex: Some(hex::encode(&sender_pubkey)),
+                    message_type: Some(message.message_type.clone()),
                 });
             }
         }
 
         let content = String::from_utf8(message.payload.clone()).unwrap_or_default();
-        let _ = self.history_manager.add(MessageRecord {
+        let _ = self.history().add(MessageRecord {
             id: message.id.clone(),
             direction: MessageDirection::Received,
             peer_id: message.sender_id.clone(),
@@ -3093,17 +4820,62 @@ impl IronCore {
         );
 
         // Notify delegate
+        notifications.push(InboundNotification::Message(message.clone()));
+
+        Ok(message)
+    }
+    /// Handle a gossipsub frame received on `topic` (the raw
+    /// `SignedGossip` bytes). The frame signature is verified here, the
+    /// inner message must be a broadcast for this topic from the signing
+    /// key, and each broadcast id is delivered to
+    /// `CoreDelegate::on_broadcast_received` at most once. Returns the
+    /// delivered broadcast, or `None` for a duplicate or a blocked sender;
+    /// tampered frames fail with `CryptoError`.
+    pub fn receive_broadcast(
+        &self,
+        topic: &str,
+        frame: &[u8],
+    ) -> Result<Option<Message>, IronCoreError> {
+        let Some(signed) = crate::transport::SignedGossip::open(frame, topic) else {
+            tracing::warn!("Dropping broadcast on {} with a bad signature", topic);
+            return Err(IronCoreError::CryptoError);
+        };
+        let message = decode_message(&signed.payload).map_err(|_| IronCoreError::InvalidInput)?;
+        let sender_pubkey =
+            hex::decode(&signed.sender_pubkey).map_err(|_| IronCoreError::MalformedKey)?;
+        let signer_id = hex::encode(blake3::hash(&sender_pubkey).as_bytes());
+        if message.message_type != crate::MessageType::Broadcast
+            || message.recipient_id != topic
+            || message.sender_id != signer_id
+        {
+            tracing::warn!(
+                "Dropping broadcast {} on {}: not signed by its sender for this topic",
+                message.id,
+                topic
+            );
+            return Err(IronCoreError::InvalidInput);
+        }
+
+        if self
+            .blocked_manager
+            .read()
+            .is_blocked(&message.sender_id, None)
+            .unwrap_or(false)
+        {
+            return Ok(None);
+        }
+        if !self.inbox.write().mark_seen(&message.id) {
+            return Ok(None);
+        }
+
         if let Some(delegate) = self.delegate.read().as_ref() {
-            delegate.on_message_received(
-                message.sender_id.clone(),
-                message.sender_id.clone(),
-                message.id.clone(),
-                message.timestamp,
+            delegate.on_broadcast_received(
+                signed.sender_pubkey,
+                topic.to_string(),
                 message.payload.clone(),
             );
         }
-
-        Ok(message)
+        Ok(Some(message))
     }
     pub fn build_registration_request(&self) -> Result<RegistrationRequest, IronCoreError> {
         let identity = self.identity.read();
@@ -3114,17 +4886,65 @@ impl IronCore {
         RegistrationRequest::new_signed(keys, device_id, seniority)
             .map_err(|_| IronCoreError::Internal)
     }
+    /// Initialize the identity from pre-generated keys (e.g. a vanity
+    /// identity). Fails with `InvalidInput` if an identity already exists.
+    pub fn initialize_identity_with_keys(
+        &self,
+        keys: crate::identity::IdentityKeys,
+    ) -> Result<(), IronCoreError> {
+        if *self.consent.read() != ConsentState::Granted {
+            return Err(IronCoreError::ConsentRequired);
+        }
+        self.identity
+            .write()
+            .initialize_with_keys(keys)
+            .map_err(|e| {
+                tracing::warn!("Rejected supplied identity keys: {:?}", e);
+                IronCoreError::InvalidInput
+            })?;
+        // Keys are now persisted; the regular path loads them and wires up
+        // the drift, routing and bootstrap engines.
+        self.initialize_identity()
+    }
     pub fn get_identity_keys(&self) -> Option<crate::identity::IdentityKeys> {
         self.identity.read().keys().cloned()
     }
     pub fn flush_outbox_for_peer(&self, peer_id: &str) -> Vec<QueuedMessage> {
         self.outbox.write().drain_for_peer(peer_id)
     }
+    /// Replace the transport escalation policy. Per-peer state is kept.
+    pub fn set_escalation_policy(&self, policy: crate::transport::escalation::EscalationPolicy) {
+        self.escalation_engine.write().set_policy(policy);
+    }
+    /// Override the size/latency/battery thresholds. `apply_policy_config`
+    /// resets these from `MeshSettings`.
+    pub fn set_escalation_thresholds(
+        &self,
+        thresholds: crate::transport::escalation::EscalationThresholds,
+    ) {
+        self.escalation_engine.write().set_thresholds(thresholds);
+    }
+    /// Shared handle to the escalation engine, for transports that feed it
+    /// peer availability and link conditions.
+    pub fn escalation_engine(&self) -> Arc<RwLock<crate::transport::escalation::EscalationEngine>> {
+        self.escalation_engine.clone()
+    }
+    /// Current escalation state per peer, keyed by hex-encoded peer id.
+    pub fn escalation_state(
+        &self,
+    ) -> std::collections::HashMap<String, crate::transport::escalation::EscalationState> {
+        self.escalation_engine
+            .read()
+            .all_states()
+            .into_iter()
+            .map(|(peer, state)| (hex::encode(peer), state))
+            .collect()
+    }
     pub fn contacts_store_manager(&self) -> CoreContactManager {
         self.contact_manager.read().clone()
     }
     pub fn history_store_manager(&self) -> CoreHistoryManager {
-        (*self.history_manager).clone()
+        (*self.history()).clone()
     }
     pub fn list_blocked_peers_raw(
         &self,
@@ -3167,6 +4987,25 @@ impl IronCore {
             }
         }
     }
+    /// A cover message from the generator set by `set_cover_traffic_generator`,
+    /// or `None` when none is configured (the swarm then uses its default).
+    pub fn generate_configured_cover_message(
+        &self,
+    ) -> Option<Result<crate::privacy::cover::CoverMessage, crate::privacy::cover::CoverTrafficError>>
+    {
+        self.cover_traffic_generator
+            .read()
+            .as_ref()
+            .map(|generator| generator.generate_cover_message())
+    }
+    /// How long cover traffic pauses after a real send, from the generator
+    /// set by `set_cover_traffic_generator` (`None` when none is configured).
+    pub fn cover_real_activity_cooldown_ms(&self) -> Option<u64> {
+        self.cover_traffic_generator
+            .read()
+            .as_ref()
+            .map(|generator| generator.config().real_activity_cooldown_ms)
+    }
     pub fn set_timing_jitter(&self, config: JitterConfig) {
         match TimingJitter::new(config) {
             Ok(jitter) => {
@@ -3477,6 +5316,65 @@ impl IronCore {
             .disable_transport(transport_type);
     }
 
+    /// Wait until the delivery receipt for `message_id` arrives (through
+    /// `receive_message`), delivery fails (`mark_delivery_failed` or a
+    /// failure receipt) or `timeout` elapses. Resolves at once if the
+    /// outcome is already known.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn await_delivery(
+        &self,
+        message_id: &str,
+        timeout: std::time::Duration,
+    ) -> DeliveryOutcome {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        // Register before checking the log so a receipt arriving in between
+        // is not missed.
+        self.delivery_waiters
+            .write()
+            .entry(message_id.to_string())
+            .or_default()
+            .push(tx);
+
+        let settled = match self.delivery_log.get(message_id) {
+            Ok(Some(report)) if report.status == crate::store::DeliveryState::Delivered => {
+                Some(DeliveryOutcome::Delivered)
+            }
+            Ok(Some(report)) if report.status == crate::store::DeliveryState::Failed => {
+                Some(DeliveryOutcome::Failed)
+            }
+            _ => None,
+        };
+        let outcome = if let Some(outcome) = settled {
+            drop(rx);
+            outcome
+        } else {
+            tokio::time::timeout(timeout, rx)
+                .await
+                .ok()
+                .and_then(Result::ok)
+                .unwrap_or(DeliveryOutcome::Timeout)
+        };
+
+        // Our sender is closed now; drop it and any other abandoned ones.
+        let mut waiters = self.delivery_waiters.write();
+        if let Some(pending) = waiters.get_mut(message_id) {
+            pending.retain(|tx| !tx.is_closed());
+            if pending.is_empty() {
+                waiters.remove(message_id);
+            }
+        }
+        outcome
+    }
+
+    /// Wake every `await_delivery` caller waiting on `message_id`.
+    fn resolve_delivery_waiters(&self, message_id: &str, outcome: DeliveryOutcome) {
+        if let Some(waiters) = self.delivery_waiters.write().remove(message_id) {
+            for tx in waiters {
+                let _ = tx.send(outcome);
+            }
+        }
+    }
+
     /// Initiate a NAT hole-punch attempt to a remote peer.
     /// Delegates to `NatTraversal::start_hole_punch`.
     /// Returns the created attempt key on success, or an error description on failure.
@@ -3555,10 +5453,7 @@ impl IronCore {
     ) -> Option<crate::routing::RoutingDecision> {
         let mut guard = self.routing_engine.write();
         if let Some(ref mut engine) = guard.as_mut() {
-            let now = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_millis() as u64;
+            let now = self.now_millis();
             Some(engine.route_message_optimized(recipient_hint, message_id, priority, now))
         } else {
             None
@@ -3741,10 +5636,7 @@ impl IronCore {
             is_charging,
             has_wifi,
             is_moving,
-            timestamp: web_time::SystemTime::now()
-                .duration_since(web_time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs(),
+            timestamp: self.now_secs(),
         };
         let mut engine = self.policy_engine.write();
         let profile = engine.update_device_state(&state);
@@ -3766,6 +5658,12 @@ impl IronCore {
         self.policy_engine.read().current_profile()
     }
 
+    /// Why the swarm should refuse to relay for others given the last
+    /// `update_device_state`, or `None` to relay within budget.
+    pub fn relay_policy_refusal(&self) -> Option<crate::drift::DropReason> {
+        self.policy_engine.read().relay_refusal()
+    }
+
     /// Set cover traffic parameters on the drift relay engine.
     /// When enabled, generates dummy traffic at the specified rate
     /// to mask real traffic patterns from traffic analysis.
@@ -3885,6 +5783,11 @@ impl IronCore {
             }
         }
 
+        // Paranoid discovery never announces presence.
+        self.presence_heartbeat
+            .write()
+            .set_suppressed(settings.discovery_mode == crate::settings::DiscoveryMode::Paranoid);
+
         // Propagate onion routing to circuit builder.
         if settings.onion_routing {
             let mut circuit_builder = self.circuit_builder.write();
@@ -3897,6 +5800,11 @@ impl IronCore {
             }
         }
 
+        // Refresh escalation thresholds (transport toggles, limits, battery floor).
+        self.escalation_engine.write().set_thresholds(
+            crate::transport::escalation::EscalationThresholds::from_mesh_settings(&settings),
+        );
+
         tracing::info!(
             relay_budget = settings.max_relay_budget,
             battery_floor = settings.battery_floor,
@@ -3918,7 +5826,7 @@ impl IronCore {
     pub fn emergency_recover(&self) -> Result<u32, IronCoreError> {
         self.contact_manager
             .read()
-            .reconcile_from_history(&self.history_manager)
+            .reconcile_from_history(&self.history())
             .map_err(|_| IronCoreError::StorageError)
     }
 
@@ -4147,6 +6055,24 @@ mod tests {
         assert_eq!(exported, "[]", "empty log store should export []");
     }
 
+    #[test]
+    fn test_verify_peer_id_matches_detects_identity_change() {
+        let core = IronCore::new();
+        let other = IronCore::new();
+        assert!(!core.verify_peer_id_matches("12D3KooWanything".into()));
+
+        for c in [&core, &other] {
+            c.grant_consent();
+            c.initialize_identity().unwrap();
+        }
+        let own = core.get_libp2p_peer_id().unwrap();
+        let foreign = other.get_libp2p_peer_id().unwrap();
+        assert!(core.verify_peer_id_matches(own.clone()));
+        assert!(core.verify_peer_id_matches(format!("{}\n", own)));
+        assert!(!core.verify_peer_id_matches(foreign));
+        assert!(!core.verify_peer_id_matches(String::new()));
+    }
+
     #[test]
     fn test_update_disk_stats_with_app_data() {
         let core = IronCore::new();
@@ -4182,6 +6108,1905 @@ mod tests {
         assert_eq!(logs[0]["content"], "persistent entry");
     }
 
+    /// Delegate that records every callback, for tests. Clones share the
+    /// recordings, so a test keeps one and hands a clone to `set_delegate`.
+    #[derive(Clone, Default)]
+    #[allow(clippy::type_complexity)]
+    struct RecordingDelegate {
+        identified: Arc<parking_lot::Mutex<Vec<(String, String, Vec<String>)>>>,
+        received: Arc<parking_lot::Mutex<Vec<String>>>,
+        receipts: Arc<parking_lot::Mutex<Vec<String>>>,
+        broadcasts: Arc<parking_lot::Mutex<Vec<(String, String, Vec<u8>)>>>,
+        pruned: Arc<parking_lot::Mutex<Vec<u32>>>,
+        typing: Arc<parking_lot::Mutex<Vec<(String, bool)>>>,
+        unknown: Arc<parking_lot::Mutex<Vec<(u32, String, Vec<u8>)>>>,
+    }
+
+    impl CoreDelegate for RecordingDelegate {
+        fn on_peer_discovered(&self, _peer_id: String) {}
+        fn on_peer_disconnected(&self, _peer_id: String) {}
+        fn on_peer_identified(
+            &self,
+            peer_id: String,
+            agent_version: String,
+            listen_addrs: Vec<String>,
+        ) {
+            self.identified
+                .lock()
+                .push((peer_id, agent_version, listen_addrs));
+        }
+        fn on_message_received(
+            &self,
+            _sender_id: String,
+            _sender_public_key_hex: String,
+            message_id: String,
+            _sender_timestamp: u64,
+            _data: Vec<u8>,
+        ) {
+            self.received.lock().push(message_id);
+        }
+        fn on_receipt_received(&self, message_id: String, _status: String) {
+            self.receipts.lock().push(message_id);
+        }
+        fn on_broadcast_received(
+            &self,
+            sender_public_key_hex: String,
+            topic: String,
+            data: Vec<u8>,
+        ) {
+            self.broadcasts
+                .lock()
+                .push((sender_public_key_hex, topic, data));
+        }
+        fn on_inbox_pruned(&self, count: u32) {
+            self.pruned.lock().push(count);
+        }
+        fn on_typing(&self, sender_public_key_hex: String, is_typing: bool) {
+            self.typing.lock().push((sender_public_key_hex, is_typing));
+        }
+        fn on_unknown_message(
+            &self,
+            type_id: u32,
+            sender_public_key_hex: String,
+            payload: Vec<u8>,
+        ) {
+            self.unknown
+                .lock()
+                .push((type_id, sender_public_key_hex, payload));
+        }
+    }
+
+    #[test]
+    fn test_notify_peer_identified_reaches_delegate() {
+        let core = IronCore::new();
+        let delegate = RecordingDelegate::default();
+        core.set_delegate(Some(Box::new(delegate.clone())));
+
+        core.notify_peer_identified(
+            "peer-a".to_string(),
+            "scmessenger/test".to_string(),
+            vec!["/ip4/10.0.0.1/tcp/4001".to_string()],
+        );
+        // Identity confirmed without any advertised addresses still fires.
+        core.notify_peer_identified(
+            "peer-b".to_string(),
+            "scmessenger/test".to_string(),
+            Vec::new(),
+        );
+
+        let calls = delegate.identified.lock();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].0, "peer-a");
+        assert_eq!(calls[0].2, vec!["/ip4/10.0.0.1/tcp/4001".to_string()]);
+        assert_eq!(calls[1].0, "peer-b");
+        assert!(calls[1].2.is_empty());
+    }
+
+    #[test]
+    fn test_broadcast_tampered_dropped_valid_delivered_once() {
+        use crate::transport::{seal_gossip, SignedGossip};
+
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let delegate = RecordingDelegate::default();
+        bob.set_delegate(Some(Box::new(delegate.clone())));
+
+        let topic = "sc-announce";
+        let payload = alice
+            .prepare_broadcast(topic.to_string(), b"node maintenance at 02:00".to_vec())
+            .unwrap();
+        let keypair = alice.get_libp2p_keypair().unwrap();
+        let frame = seal_gossip(&keypair, topic, payload).unwrap();
+
+        // Payload swapped under the original signature.
+        let mut tampered = SignedGossip::from_bytes(&frame).unwrap();
+        tampered.payload = alice
+            .prepare_broadcast(topic.to_string(), b"forged".to_vec())
+            .unwrap();
+        assert!(matches!(
+            bob.receive_broadcast(topic, &tampered.to_bytes().unwrap()),
+            Err(IronCoreError::CryptoError)
+        ));
+        // Replayed onto another topic.
+        assert!(matches!(
+            bob.receive_broadcast("sc-other", &frame),
+            Err(IronCoreError::CryptoError)
+        ));
+        assert!(delegate.broadcasts.lock().is_empty());
+
+        let delivered = bob.receive_broadcast(topic, &frame).unwrap();
+        assert!(delivered.is_some());
+        assert!(bob.receive_broadcast(topic, &frame).unwrap().is_none());
+
+        let received = delegate.broadcasts.lock();
+        assert_eq!(received.len(), 1);
+        assert_eq!(
+            received[0].0,
+            alice.get_identity_info().public_key_hex.unwrap()
+        );
+        assert_eq!(received[0].1, topic);
+        assert_eq!(received[0].2, b"node maintenance at 02:00");
+        // Broadcasts share the dedup window but never land in the inbox.
+        assert_eq!(bob.inbox_count(), 0);
+    }
+
+    #[test]
+    fn test_export_diagnostics_snapshot() {
+        let core = IronCore::new();
+
+        let offline: serde_json::Value =
+            serde_json::from_str(&core.export_diagnostics(None, None)).unwrap();
+        assert_eq!(offline["running"], false);
+        assert_eq!(offline["inbox_count"], 0);
+        assert_eq!(offline["outbox_count"], 0);
+        assert_eq!(offline["relay_enabled"], true);
+        assert!(offline.get("peers").is_none());
+        assert!(offline.get("nat_status").is_none());
+
+        let online: serde_json::Value = serde_json::from_str(&core.export_diagnostics(
+            Some(vec!["peer-a".to_string(), "peer-b".to_string()]),
+            Some("public".to_string()),
+        ))
+        .unwrap();
+        assert_eq!(online["peer_count"], 2);
+        assert_eq!(online["peers"][1], "peer-b");
+        assert_eq!(online["nat_status"], "public");
+
+        // Reported from the relay setting, as the WASM client does.
+        core.update_settings(crate::MeshSettings {
+            relay_enabled: false,
+            ..Default::default()
+        })
+        .unwrap();
+        let relay_off: serde_json::Value =
+            serde_json::from_str(&core.export_diagnostics(None, None)).unwrap();
+        assert_eq!(relay_off["relay_enabled"], false);
+    }
+
+    #[test]
+    fn test_support_bundle_has_pubkey_but_no_secrets_or_content() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+        let prepared = alice
+            .prepare_message_with_id(
+                bob_pk.clone(),
+                "support-bundle plaintext".into(),
+                crate::MessageType::Text,
+                None,
+            )
+            .unwrap();
+        bob.receive_message(prepared.envelope_data).unwrap();
+
+        let subscriber = tracing_subscriber::registry().with(crate::store::log_ring::LogRingLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!("support-bundle-test warning");
+        });
+
+        let bundle = bob.export_support_bundle();
+        let value: serde_json::Value = serde_json::from_str(&bundle).unwrap();
+        assert_eq!(value["schema_version"], SUPPORT_BUNDLE_SCHEMA_VERSION);
+        assert_eq!(value["identity"]["public_key_hex"], bob_pk.as_str());
+        assert_eq!(value["inbox_count"], 1);
+        assert!(value["settings"].is_object());
+        assert!(value["recent_errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|e| e["message"] == "support-bundle-test warning"));
+
+        let identity = bob.identity.read();
+        let keys = identity.keys().unwrap();
+        assert!(!bundle.contains(&hex::encode(keys.signing_key.to_bytes())));
+        assert!(!bundle.contains(&hex::encode(keys.x25519_encryption_secret.to_bytes())));
+        assert!(!bundle.contains("support-bundle plaintext"));
+    }
+
+    #[test]
+    fn test_initialize_identity_with_vanity_keys() {
+        let core = IronCore::new();
+        let keys = crate::identity::IdentityKeys::generate_vanity("b", 10_000).unwrap();
+        let expected_id = keys.identity_id();
+
+        assert!(matches!(
+            core.initialize_identity_with_keys(keys.clone()),
+            Err(IronCoreError::ConsentRequired)
+        ));
+        core.grant_consent();
+        core.initialize_identity_with_keys(keys).unwrap();
+        assert_eq!(core.identity_id(), Some(expected_id));
+
+        // An existing identity is never silently replaced.
+        let other = crate::identity::IdentityKeys::generate();
+        assert!(core.initialize_identity_with_keys(other).is_err());
+    }
+
+    #[test]
+    fn test_prepare_receipts_bulk_skips_invalid_recipients() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+
+        let batch = alice
+            .prepare_receipts_bulk(vec![
+                (bob_pk.clone(), "m1".into()),
+                ("not-hex".into(), "m2".into()),
+                ("ab".repeat(16), "m3".into()),
+                (bob_pk, "m4".into()),
+            ])
+            .unwrap();
+        let ids: Vec<_> = batch
+            .envelopes
+            .iter()
+            .map(|p| p.message_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["m1", "m4"]);
+        assert_eq!(batch.skipped, vec!["m2".to_string(), "m3".to_string()]);
+
+        for prepared in batch.envelopes {
+            let received = bob.receive_message(prepared.envelope_data).unwrap();
+            assert_eq!(received.message_type, crate::MessageType::Receipt);
+            let receipt = crate::message::types::decode_receipt(&received.payload).unwrap();
+            assert_eq!(receipt.message_id, prepared.message_id);
+        }
+
+        let uninitialized = IronCore::new();
+        assert!(matches!(
+            uninitialized.prepare_receipts_bulk(Vec::new()),
+            Err(IronCoreError::NotInitialized)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_await_delivery_resolves_on_receipt() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let alice_pk = alice.get_identity_info().public_key_hex.unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+
+        let prepared = alice
+            .prepare_message(bob_pk, "hi".into(), crate::MessageType::Text, None)
+            .unwrap();
+        let message = bob.receive_message(prepared.envelope_data).unwrap();
+        let receipt = bob
+            .prepare_receipts_bulk(vec![(alice_pk, message.id)])
+            .unwrap()
+            .envelopes
+            .remove(0);
+
+        let (outcome, _) = tokio::join!(
+            alice.await_delivery(&prepared.message_id, std::time::Duration::from_secs(5)),
+            async {
+                tokio::task::yield_now().await;
+                alice.receive_message(receipt.envelope_data).unwrap();
+            }
+        );
+        assert_eq!(outcome, DeliveryOutcome::Delivered);
+        assert!(alice.delivery_waiters.read().is_empty());
+
+        // Once delivered, later callers resolve without waiting.
+        assert_eq!(
+            alice
+                .await_delivery(&prepared.message_id, std::time::Duration::ZERO)
+                .await,
+            DeliveryOutcome::Delivered
+        );
+    }
+
+    #[tokio::test]
+    async fn test_await_delivery_times_out_or_fails_without_receipt() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+
+        let prepared = alice
+            .prepare_message(
+                bob.get_identity_info().public_key_hex.unwrap(),
+                "anyone there?".into(),
+                crate::MessageType::Text,
+                None,
+            )
+            .unwrap();
+        let outcome = alice
+            .await_delivery(&prepared.message_id, std::time::Duration::from_millis(20))
+            .await;
+        assert_eq!(outcome, DeliveryOutcome::Timeout);
+        assert!(alice.delivery_waiters.read().is_empty());
+
+        // Giving up resolves a waiter, and a later caller, as failed.
+        let (outcome, _) = tokio::join!(
+            alice.await_delivery(&prepared.message_id, std::time::Duration::from_secs(5)),
+            async {
+                tokio::task::yield_now().await;
+                alice
+                    .mark_delivery_failed(prepared.message_id.clone(), "delivery given up".into())
+                    .unwrap();
+            }
+        );
+        assert_eq!(outcome, DeliveryOutcome::Failed);
+        assert_eq!(
+            alice
+                .await_delivery(&prepared.message_id, std::time::Duration::from_millis(20))
+                .await,
+            DeliveryOutcome::Failed
+        );
+    }
+
+    #[test]
+    fn test_receipts_are_idempotent_and_never_acknowledged() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let alice_pk = alice.get_identity_info().public_key_hex.unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+        let delegate = RecordingDelegate::default();
+        alice.set_delegate(Some(Box::new(delegate.clone())));
+
+        // Both sides auto-ACK everything they receive, the way a
+        // misconfigured client would. The exchange must still die out.
+        let mut in_flight = vec![(
+            true,
+            alice
+                .prepare_message(bob_pk.clone(), "hi".into(), crate::MessageType::Text, None)
+                .unwrap()
+                .envelope_data,
+        )];
+        let mut deliveries = 0;
+        while let Some((to_bob, envelope)) = in_flight.pop() {
+            deliveries += 1;
+            assert!(deliveries <= 4, "receipt storm");
+            let (receiver, sender_pk) = if to_bob {
+                (&bob, alice_pk.clone())
+            } else {
+                (&alice, bob_pk.clone())
+            };
+            let message = receiver.receive_message(envelope.clone()).unwrap();
+            // Redelivery of the same envelope changes nothing.
+            receiver.receive_message(envelope).unwrap();
+            let acks = receiver
+                .prepare_receipts_bulk(vec![(sender_pk, message.id)])
+                .unwrap();
+            for ack in acks.envelopes {
+                in_flight.push((!to_bob, ack.envelope_data));
+            }
+        }
+        assert_eq!(deliveries, 2);
+        assert_eq!(delegate.receipts.lock().len(), 1);
+
+        let receipt_id = alice.inbox.read().all_messages()[0].message_id.clone();
+        assert!(matches!(
+            alice.prepare_receipt(bob_pk, receipt_id),
+            Err(IronCoreError::InvalidInput)
+        ));
+    }
+
+    /// Records the `message_id` of every `message` span, whether set at
+    /// creation or recorded later.
+    #[derive(Clone, Default)]
+    struct MessageSpanCapture(Arc<parking_lot::Mutex<Vec<(String, String)>>>);
+
+    struct SpanFieldVisitor<'a>(&'a mut Vec<(String, String)>);
+
+    impl tracing::field::Visit for SpanFieldVisitor<'_> {
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            self.0.push((field.name().to_string(), value.to_string()));
+        }
+
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .push((field.name().to_string(), format!("{:?}", value)));
+        }
+    }
+
+    impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for MessageSpanCapture {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            _id: &tracing::span::Id,
+            _ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            if attrs.metadata().name() == "message" {
+                attrs.record(&mut SpanFieldVisitor(&mut self.0.lock()));
+            }
+        }
+
+        fn on_record(
+            &self,
+            _id: &tracing::span::Id,
+            values: &tracing::span::Record<'_>,
+            _ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            values.record(&mut SpanFieldVisitor(&mut self.0.lock()));
+        }
+    }
+
+    #[test]
+    fn test_message_span_carries_message_id() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+
+        let capture = MessageSpanCapture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let prepared = tracing::subscriber::with_default(subscriber, || {
+            let prepared = alice
+                .prepare_message_with_id(bob_pk, "traced".into(), crate::MessageType::Text, None)
+                .unwrap();
+            bob.receive_message(prepared.envelope_data.clone()).unwrap();
+            prepared
+        });
+
+        let fields = capture.0.lock();
+        let ids: Vec<_> = fields
+            .iter()
+            .filter(|(name, _)| name == "message_id")
+            .map(|(_, value)| value.as_str())
+            .collect();
+        assert_eq!(
+            ids,
+            vec![prepared.message_id.as_str(), prepared.message_id.as_str()]
+        );
+        assert!(fields.contains(&("direction".to_string(), "outbound".to_string())));
+        assert!(fields.contains(&("direction".to_string(), "inbound".to_string())));
+    }
+
+    #[test]
+    fn test_portable_message_roundtrip() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+
+        let portable = alice
+            .prepare_message_portable(bob_pk.clone(), "hand-carried".into())
+            .unwrap();
+        assert!(portable.len() <= crate::drift::PORTABLE_MAX_CHARS);
+        assert!(alice.outbox.read().peek_for_peer(&bob_pk).is_empty());
+
+        let received = bob.receive_portable(portable).unwrap();
+        assert_eq!(received.text_content().unwrap(), "hand-carried");
+
+        let too_long = alice.prepare_message_portable(bob_pk, "x".repeat(4000));
+        assert!(matches!(too_long, Err(IronCoreError::PortableTooLarge)));
+        assert!(matches!(
+            bob.receive_portable("not base45".into()),
+            Err(IronCoreError::EnvelopeDecodeFailed)
+        ));
+    }
+
+    #[test]
+    fn test_envelope_inspectors() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let alice_pk = alice.get_identity_info().public_key_hex.unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+
+        let envelope = alice
+            .prepare_message(bob_pk.clone(), "hi".into(), crate::MessageType::Text, None)
+            .unwrap()
+            .envelope_data;
+        let bob_pk_bytes: [u8; 32] = hex::decode(&bob_pk).unwrap().try_into().unwrap();
+        let expected_hint = hex::encode(crate::drift::DriftEnvelope::hint_from_public_key(
+            &bob_pk_bytes,
+        ));
+
+        // Anyone can inspect, not just the recipient.
+        let carol = IronCore::new();
+        assert_eq!(
+            carol.envelope_recipient_hint(envelope.clone()),
+            Some(expected_hint)
+        );
+        assert_eq!(carol.envelope_sender(envelope), Some(alice_pk));
+
+        assert_eq!(carol.envelope_recipient_hint(b"garbage".to_vec()), None);
+        assert_eq!(carol.envelope_sender(Vec::new()), None);
+    }
+
+    #[test]
+    fn test_preferred_cipher_roundtrip_and_unknown_suite() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+
+        assert_eq!(alice.preferred_cipher(), "xchacha20-poly1305");
+        assert!(matches!(
+            alice.set_preferred_cipher("rot13".into()),
+            Err(IronCoreError::InvalidInput)
+        ));
+        alice
+            .set_preferred_cipher("chacha20-poly1305".into())
+            .unwrap();
+
+        let envelope = alice
+            .prepare_message(bob_pk, "hi bob".into(), crate::MessageType::Text, None)
+            .unwrap()
+            .envelope_data;
+        let mut drift_env = crate::drift::DriftEnvelope::from_bytes(&envelope).unwrap();
+        assert_eq!(
+            drift_env.aead_suite,
+            crate::crypto::AeadSuite::ChaCha20Poly1305.as_u8()
+        );
+        let received = bob.receive_message(envelope).unwrap();
+        assert_eq!(received.text_content().unwrap(), "hi bob");
+
+        drift_env.aead_suite = 0x7f;
+        assert!(matches!(
+            bob.receive_message(drift_env.to_bytes().unwrap()),
+            Err(IronCoreError::UnsupportedCipherSuite)
+        ));
+
+        let mut future = drift_env.to_bytes().unwrap();
+        future[0] = crate::message::codec::MAX_ENVELOPE_VERSION + 1;
+        assert!(matches!(
+            bob.receive_message(future),
+            Err(IronCoreError::UnsupportedEnvelopeVersion)
+        ));
+    }
+
+    #[test]
+    fn test_send_draft_moves_it_to_outbox() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+
+        assert!(matches!(
+            alice.save_draft("not-a-key".into(), "x".into()),
+            Err(IronCoreError::MalformedKey)
+        ));
+        alice.save_draft(bob_pk.clone(), "later".into()).unwrap();
+        assert_eq!(alice.get_draft(bob_pk.clone()).unwrap().text, "later");
+        assert_eq!(alice.list_drafts().len(), 1);
+        assert_eq!(alice.outbox_count(), 0);
+
+        let prepared = alice.send_draft(bob_pk.clone()).unwrap();
+        assert!(alice.get_draft(bob_pk.clone()).is_none());
+        assert_eq!(
+            alice.pending_for(bob_pk.clone())[0].message_id,
+            prepared.message_id
+        );
+        let received = bob.receive_message(prepared.envelope_data).unwrap();
+        assert_eq!(received.text_content().unwrap(), "later");
+
+        assert!(matches!(
+            alice.send_draft(bob_pk),
+            Err(IronCoreError::InvalidInput)
+        ));
+    }
+
+    #[test]
+    fn test_prepare_message_to_contact_resolves_recipient() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+
+        let mut contact = Contact::new("peer-bob".to_string(), bob_pk.clone());
+        contact.local_nickname = Some("Bob".to_string());
+        alice.contacts_store_manager().add(contact).unwrap();
+
+        let bob_identity_id = bob.get_identity_info().identity_id.unwrap();
+        for query in [
+            "bob",
+            "Bob",
+            bob_pk.as_str(),
+            "peer-bob",
+            bob_identity_id.as_str(),
+        ] {
+            let prepared = alice
+                .prepare_message_to_contact(query.to_string(), "hi".into())
+                .unwrap();
+            let received = bob.receive_message(prepared.envelope_data).unwrap();
+            assert_eq!(received.text_content().unwrap(), "hi");
+        }
+
+        assert!(matches!(
+            alice.prepare_message_to_contact("carol".into(), "hi".into()),
+            Err(IronCoreError::InvalidInput)
+        ));
+    }
+
+    #[test]
+    fn test_switch_identity_routes_messages_through_active_identity() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+
+        let personal = alice.get_identity_info().public_key_hex.unwrap();
+        let work = alice.add_identity().unwrap();
+        assert_eq!(
+            alice.list_identities(),
+            vec![personal.clone(), work.clone()]
+        );
+        // Adding does not switch.
+        assert_eq!(
+            alice.get_identity_info().public_key_hex.as_deref(),
+            Some(personal.as_str())
+        );
+
+        let to_work = bob
+            .prepare_message(
+                work.clone(),
+                "for work".into(),
+                crate::MessageType::Text,
+                None,
+            )
+            .unwrap()
+            .envelope_data;
+        assert!(alice.receive_message(to_work.clone()).is_err());
+
+        alice.switch_identity(work.clone()).unwrap();
+        assert_eq!(
+            alice.get_identity_info().public_key_hex.as_deref(),
+            Some(work.as_str())
+        );
+        let received = alice.receive_message(to_work).unwrap();
+        assert_eq!(received.text_content().unwrap(), "for work");
+
+        let reply = alice
+            .prepare_message(
+                bob_pk.clone(),
+                "from work".into(),
+                crate::MessageType::Text,
+                None,
+            )
+            .unwrap()
+            .envelope_data;
+        assert_eq!(alice.envelope_sender(reply.clone()), Some(work.clone()));
+        bob.receive_message(reply).unwrap();
+
+        alice.switch_identity(personal.clone()).unwrap();
+        let to_personal = bob
+            .prepare_message(
+                personal.clone(),
+                "hi".into(),
+                crate::MessageType::Text,
+                None,
+            )
+            .unwrap()
+            .envelope_data;
+        let hi = alice.receive_message(to_personal.clone()).unwrap();
+        assert_eq!(hi.text_content().unwrap(), "hi");
+        assert_eq!(alice.inbox_count(), 1);
+
+        // Each identity gets its own inbox back on switching, so a repeated
+        // envelope is recognised as already seen.
+        alice.switch_identity(work.clone()).unwrap();
+        assert_eq!(alice.inbox_count(), 1); // "for work"
+        alice.switch_identity(personal.clone()).unwrap();
+        assert!(alice.inbox.read().is_duplicate(&hi.id));
+        alice.receive_message(to_personal).unwrap();
+        assert_eq!(alice.inbox_count(), 1);
+        assert!(matches!(
+            alice.switch_identity(bob_pk),
+            Err(IronCoreError::InvalidInput)
+        ));
+    }
+
+    #[test]
+    fn test_switch_identity_keeps_stores_apart_across_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap().to_string();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+
+        let alice = IronCore::with_storage(path.clone());
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let personal = alice.get_identity_info().public_key_hex.unwrap();
+        alice
+            .contacts_store_manager()
+            .add(Contact::new("peer-bob".to_string(), bob_pk.clone()))
+            .unwrap();
+        let to_personal = bob
+            .prepare_message(
+                personal.clone(),
+                "hi".into(),
+                crate::MessageType::Text,
+                None,
+            )
+            .unwrap()
+            .envelope_data;
+        alice.receive_message(to_personal).unwrap();
+        let personal_sessions = alice.ratchet_session_count();
+        let personal_history = alice.history_store_manager().count();
+
+        let work = alice.add_identity().unwrap();
+        alice.switch_identity(work.clone()).unwrap();
+        assert_eq!(alice.contacts_store_manager().count(), 0);
+        assert_eq!(alice.history_store_manager().count(), 0);
+        assert_eq!(alice.ratchet_session_count(), 0);
+        alice
+            .contacts_store_manager()
+            .add(Contact::new("peer-carol".to_string(), "cc".repeat(32)))
+            .unwrap();
+
+        drop(alice);
+        let alice = IronCore::with_storage(path);
+        alice.grant_consent();
+        assert_eq!(
+            alice.get_identity_info().public_key_hex.as_deref(),
+            Some(work.as_str())
+        );
+        assert!(alice
+            .contacts_store_manager()
+            .get("peer-carol".to_string())
+            .unwrap()
+            .is_some());
+
+        alice.switch_identity(personal).unwrap();
+        let contacts = alice.contacts_store_manager().list().unwrap();
+        assert_eq!(contacts.len(), 1);
+        assert_eq!(contacts[0].peer_id, "peer-bob");
+        assert_eq!(alice.history_store_manager().count(), personal_history);
+        // The parked sessions survived the restart.
+        assert_eq!(alice.ratchet_session_count(), personal_sessions);
+    }
+
+    #[test]
+    fn test_receive_messages_isolates_errors_and_dedups() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+        let delegate = RecordingDelegate::default();
+        bob.set_delegate(Some(Box::new(delegate.clone())));
+
+        let first = alice
+            .prepare_message(bob_pk.clone(), "one".into(), crate::MessageType::Text, None)
+            .unwrap()
+            .envelope_data;
+        let second = alice
+            .prepare_message(bob_pk, "two".into(), crate::MessageType::Text, None)
+            .unwrap()
+            .envelope_data;
+
+        let results =
+            bob.receive_messages(vec![first.clone(), first, vec![0x20, 0xde, 0xad], second]);
+        assert_eq!(results.len(), 4);
+        let one = results[0].as_ref().unwrap();
+        assert_eq!(one.text_content().unwrap(), "one");
+        assert_eq!(results[1].as_ref().unwrap().id, one.id);
+        assert!(matches!(
+            results[2],
+            Err(IronCoreError::EnvelopeDecodeFailed)
+        ));
+        let two = results[3].as_ref().unwrap();
+        assert_eq!(two.text_content().unwrap(), "two");
+
+        // One callback per distinct message, in batch order.
+        assert_eq!(
+            *delegate.received.lock(),
+            vec![one.id.clone(), two.id.clone()]
+        );
+        assert_eq!(bob.inbox_count(), 2);
+    }
+
+    #[test]
+    fn test_typing_indicator_fires_callback_without_touching_inbox() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let alice_pk = alice.get_identity_info().public_key_hex.unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+        let delegate = RecordingDelegate::default();
+        bob.set_delegate(Some(Box::new(delegate.clone())));
+
+        let started = alice.prepare_typing(bob_pk.clone(), true).unwrap();
+        let stopped = alice.prepare_typing(bob_pk, false).unwrap();
+        let message = bob.receive_message(started.clone()).unwrap();
+        assert_eq!(message.message_type, crate::MessageType::Typing);
+        // Replays are not deduplicated: each one is a live signal.
+        bob.receive_message(started).unwrap();
+        bob.receive_message(stopped).unwrap();
+
+        assert_eq!(
+            *delegate.typing.lock(),
+            vec![
+                (alice_pk.clone(), true),
+                (alice_pk.clone(), true),
+                (alice_pk, false)
+            ]
+        );
+        assert!(delegate.received.lock().is_empty());
+        assert_eq!(bob.inbox_count(), 0);
+        assert_eq!(alice.outbox_count(), 0);
+    }
+
+    #[test]
+    fn test_unknown_message_type_reaches_fallback_callback() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let alice_info = alice.get_identity_info();
+        let alice_pk = alice_info.public_key_hex.unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_info = bob.get_identity_info();
+        let bob_pk: [u8; 32] = hex::decode(bob_info.public_key_hex.unwrap())
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let delegate = RecordingDelegate::default();
+        bob.set_delegate(Some(Box::new(delegate.clone())));
+
+        assert!(bob
+            .supported_message_types()
+            .contains(&"typing".to_string()));
+
+        // A message type from some future version of the protocol.
+        let future = crate::message::codec::RawMessage {
+            id: "future-1".to_string(),
+            sender_id: alice_info.identity_id.unwrap(),
+            recipient_id: bob_info.identity_id.unwrap(),
+            message_type: 99,
+            payload: b"poll: lunch?".to_vec(),
+            timestamp: 1,
+        };
+        let keys = alice.get_identity_keys().unwrap();
+        let envelope = crate::encrypt_message(
+            &keys.signing_key,
+            &bob_pk,
+            &bincode::serialize(&future).unwrap(),
+        )
+        .unwrap();
+        let envelope = crate::message::encode_envelope(&envelope).unwrap();
+
+        // The caller gets a text stand-in it can acknowledge, and a retry of
+        // the same message is not reported again.
+        let fallback = bob.receive_message(envelope.clone()).unwrap();
+        assert_eq!(fallback.id, "future-1");
+        assert_eq!(fallback.message_type, crate::MessageType::Text);
+        assert!(fallback.text_content().unwrap().contains("type 99"));
+        bob.receive_message(envelope).unwrap();
+        assert_eq!(
+            *delegate.unknown.lock(),
+            vec![(99, alice_pk, b"poll: lunch?".to_vec())]
+        );
+        assert!(delegate.received.lock().is_empty());
+        assert_eq!(bob.inbox_count(), 0);
+        assert!(bob.prepare_receipt(String::new(), fallback.id).is_ok());
+    }
+
+    #[test]
+    fn test_inbox_sweeper_removes_only_expired_messages() {
+        let core = IronCore::new();
+        let delegate = RecordingDelegate::default();
+        core.set_delegate(Some(Box::new(delegate.clone())));
+        let now = web_time::SystemTime::now()
+            .duration_since(web_time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let seed = |id: &str, received_at: u64| ReceivedMessage {
+            version: 1,
+            message_id: id.to_string(),
+            sender_id: "alice".to_string(),
+            payload: b"hi".to_vec(),
+            received_at,
+            sender_public_key_hex: None,
+            message_type: Some(crate::MessageType::Text),
+        };
+        {
+            let mut inbox = core.inbox.write();
+            inbox.receive(seed("old-1", now - 7_200));
+            inbox.receive(seed("old-2", now - 4_000));
+            inbox.receive(seed("new", now - 60));
+        }
+
+        assert!(matches!(
+            core.start_inbox_sweeper(0, 60),
+            Err(IronCoreError::InvalidInput)
+        ));
+        core.start_inbox_sweeper(3_600, 60).unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while delegate.pruned.lock().is_empty() {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "sweeper should report pruned messages"
+            );
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(*delegate.pruned.lock(), vec![2]);
+        let remaining = core.inbox.read().all_messages();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].message_id, "new");
+
+        core.stop_inbox_sweeper();
+        assert_eq!(core.sweep_inbox(3_600), 0);
+    }
+
+    #[test]
+    fn test_mock_clock_drives_ttl_expiry_without_sleeping() {
+        use crate::clock::{Clock, MockClock};
+
+        let start = 1_700_000_000;
+        let alice_clock = MockClock::at_secs(start);
+        let bob_clock = MockClock::at_secs(start);
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        alice.set_clock(Arc::new(alice_clock.clone()));
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        bob.set_clock(Arc::new(bob_clock.clone()));
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+
+        let prepared = alice
+            .prepare_message(bob_pk, "ttl".into(), crate::MessageType::Text, None)
+            .unwrap();
+        let received = bob.receive_message(prepared.envelope_data).unwrap();
+        assert_eq!(received.timestamp, start);
+
+        // Ack timeout fires only once the mock clock passes the window.
+        assert!(alice.check_ack_timeouts(60).is_empty());
+        alice_clock.advance_secs(60);
+        assert_eq!(alice.check_ack_timeouts(60), vec![prepared.message_id]);
+
+        let ttl = crate::TtlConfig {
+            expires_in_seconds: 3_600,
+        };
+        bob_clock.advance_secs(3_600);
+        assert!(!crate::message::ephemeral::is_expired_at(
+            received.timestamp,
+            &ttl,
+            bob_clock.now_secs()
+        ));
+        assert_eq!(bob.sweep_inbox(ttl.expires_in_seconds), 0);
+
+        bob_clock.advance_secs(2);
+        assert!(crate::message::ephemeral::is_expired_at(
+            received.timestamp,
+            &ttl,
+            bob_clock.now_secs()
+        ));
+        assert_eq!(bob.sweep_inbox(ttl.expires_in_seconds), 1);
+        assert_eq!(bob.inbox_count(), 0);
+    }
+
+    #[test]
+    fn test_panic_wipe_leaves_uninitialized_storage() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("storage");
+        let path_str = path.to_str().unwrap().to_string();
+        {
+            let core = IronCore::with_storage(path_str.clone());
+            core.grant_consent();
+            core.initialize_identity().unwrap();
+            let peer = IronCore::new();
+            peer.grant_consent();
+            peer.initialize_identity().unwrap();
+            core.contacts_store_manager()
+                .add(Contact::new(
+                    "peer".to_string(),
+                    peer.get_identity_info().public_key_hex.unwrap(),
+                ))
+                .unwrap();
+            std::fs::write(path.join("relay_network_key.pb"), b"network key").unwrap();
+
+            core.panic_wipe().unwrap();
+            assert!(!core.get_identity_info().initialized);
+            assert!(!path.join("relay_network_key.pb").exists());
+        }
+
+        let reopened = IronCore::with_storage(path_str);
+        assert!(!reopened.get_identity_info().initialized);
+        assert!(reopened.contacts_store_manager().list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_max_contacts_setting_caps_core_and_bridge_contacts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path_str = dir.path().to_str().unwrap().to_string();
+        let core = IronCore::with_storage(path_str.clone());
+        // Opened before the settings change, as a mobile UI would.
+        let bridge = core.contacts_manager().unwrap();
+
+        core.update_settings(crate::MeshSettings {
+            max_contacts: 1,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(bridge.max_contacts(), 1);
+
+        let contacts = core.contacts_store_manager();
+        contacts
+            .add(Contact::new("peer-a".to_string(), "aa".repeat(32)))
+            .unwrap();
+        contacts
+            .add(Contact::new("peer-b".to_string(), "bb".repeat(32)))
+            .unwrap();
+        assert_eq!(contacts.count(), 1);
+
+        // The cap is read back from the saved settings on reopen.
+        drop(core);
+        let reopened = IronCore::with_storage(path_str);
+        assert_eq!(reopened.contacts_store_manager().max_contacts(), 1);
+    }
+
+    #[test]
+    fn test_compact_storage_keeps_stored_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let path_str = dir.path().join("storage").to_str().unwrap().to_string();
+        let peer = IronCore::new();
+        peer.grant_consent();
+        peer.initialize_identity().unwrap();
+        let peer_pk = peer.get_identity_info().public_key_hex.unwrap();
+
+        let own_pk = {
+            let core = IronCore::with_storage(path_str.clone());
+            core.grant_consent();
+            core.initialize_identity().unwrap();
+            core.contacts_store_manager()
+                .add(Contact::new("peer".to_string(), peer_pk.clone()))
+                .unwrap();
+            let history = core.history_store_manager();
+            for i in 0..50 {
+                history
+                    .add(crate::store::MessageRecord::new_sent(
+                        "peer".into(),
+                        format!("churn {}", i),
+                    ))
+                    .unwrap();
+            }
+            let keep = crate::store::MessageRecord::new_sent("peer".into(), "kept".into());
+            let keep_id = keep.id.clone();
+            history.add(keep).unwrap();
+            for record in history.recent(None, 100).unwrap() {
+                if record.id != keep_id {
+                    history.delete(record.id).unwrap();
+                }
+            }
+
+            let report = core.compact_storage().unwrap();
+            assert!(report.records_rewritten > 0);
+            assert!(report.bytes_after > 0);
+            assert_eq!(
+                history.get(keep_id).unwrap().unwrap().content,
+                "kept".to_string()
+            );
+            core.get_identity_info().public_key_hex.unwrap()
+        };
+
+        let reopened = IronCore::with_storage(path_str);
+        assert_eq!(reopened.get_identity_info().public_key_hex, Some(own_pk));
+        assert_eq!(reopened.contacts_store_manager().list().unwrap().len(), 1);
+        assert_eq!(
+            reopened.history_store_manager().count(),
+            1,
+            "only the kept message survives"
+        );
+    }
+
+    #[test]
+    fn test_presence_heartbeats_update_last_seen() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let alice_pk = alice.get_identity_info().public_key_hex.unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+        alice
+            .contacts_store_manager()
+            .add(Contact::new("peer-bob".to_string(), bob_pk))
+            .unwrap();
+        bob.contacts_store_manager()
+            .add(Contact::new("peer-alice".to_string(), alice_pk))
+            .unwrap();
+
+        assert!(alice.presence_beacon_if_due().is_none());
+        alice.start_presence_heartbeat(60).unwrap();
+        bob.start_presence_heartbeat(60).unwrap();
+        let from_alice = alice.presence_beacon_if_due().unwrap();
+        let from_bob = bob.presence_beacon_if_due().unwrap();
+        // Not due again until the interval has passed.
+        assert!(alice.presence_beacon_if_due().is_none());
+
+        assert!(bob.receive_presence_beacon(from_alice.clone()).unwrap());
+        assert!(alice.receive_presence_beacon(from_bob).unwrap());
+        let seen = |core: &IronCore, peer: &str| {
+            core.contacts_store_manager()
+                .get(peer.to_string())
+                .unwrap()
+                .unwrap()
+                .last_seen
+        };
+        assert!(seen(&alice, "peer-bob").is_some());
+        assert!(seen(&bob, "peer-alice").is_some());
+        // Our own beacon matches no contact.
+        assert!(!alice.receive_presence_beacon(from_alice).unwrap());
+        assert!(alice.receive_presence_beacon(vec![1, 2, 3]).is_err());
+
+        // Paranoid discovery disables the heartbeat entirely.
+        let settings = crate::settings::MeshSettings {
+            discovery_mode: crate::settings::DiscoveryMode::Paranoid,
+            ..Default::default()
+        };
+        alice
+            .apply_policy_config(&serde_json::to_string(&settings).unwrap())
+            .unwrap();
+        alice.stop_presence_heartbeat();
+        alice.start_presence_heartbeat(60).unwrap();
+        assert!(alice.presence_beacon_if_due().is_none());
+    }
+
+    #[test]
+    fn test_paranoid_setting_in_memory_stops_presence_beacons() {
+        let core = IronCore::new();
+        core.grant_consent();
+        core.initialize_identity().unwrap();
+        let clock = crate::clock::MockClock::at_secs(1_700_000_000);
+        core.set_clock(Arc::new(clock.clone()));
+        let paranoid = crate::MeshSettings {
+            discovery_mode: crate::settings::DiscoveryMode::Paranoid,
+            ..Default::default()
+        };
+
+        // Set before the heartbeat starts, with no settings file to read.
+        core.update_settings(paranoid.clone()).unwrap();
+        core.start_presence_heartbeat(60).unwrap();
+        assert!(core.presence_beacon_if_due().is_none());
+
+        // Leaving Paranoid mode at runtime lets beacons out again...
+        core.update_settings(crate::MeshSettings::default())
+            .unwrap();
+        assert!(core.presence_beacon_if_due().is_some());
+        // ...and entering it stops them mid-heartbeat.
+        core.update_settings(paranoid).unwrap();
+        clock.advance_secs(3_600);
+        assert!(core.presence_beacon_if_due().is_none());
+    }
+
+    #[test]
+    fn test_verified_timestamp_brings_skewed_clock_into_replay_window() {
+        use crate::clock::{Clock, SystemClock};
+
+        // Mesh time runs two hours ahead of this device: the receiver and
+        // the relay are right, the sender's clock is behind.
+        const SKEW_SECS: i64 = 2 * 60 * 60;
+        let receiver = IronCore::new();
+        receiver.grant_consent();
+        receiver.initialize_identity().unwrap();
+        receiver.set_clock_offset(SKEW_SECS);
+        let sender = IronCore::new();
+        sender.grant_consent();
+        sender.initialize_identity().unwrap();
+        let sender_pk = sender.get_identity_info().public_key_hex.unwrap();
+        receiver
+            .contacts_store_manager()
+            .add(Contact::new("peer-sender".to_string(), sender_pk))
+            .unwrap();
+
+        sender.start_presence_heartbeat(60).unwrap();
+        let stale = sender.presence_beacon_if_due().unwrap();
+        assert!(receiver.receive_presence_beacon(stale).is_err());
+
+        // The exchange the swarm runs with a trusted bootstrap relay.
+        let relay = libp2p::identity::Keypair::generate_ed25519();
+        let relay_id = relay.public().to_peer_id();
+        let probe = crate::relay::ClockProbe::new(SystemClock::default().now_millis());
+        let stamp = crate::relay::SignedTimestamp::sign(&relay, probe.nonce, receiver.now_millis())
+            .unwrap();
+        let offset = probe
+            .offset_secs(&relay_id, &stamp, SystemClock::default().now_millis())
+            .unwrap();
+        assert!(offset.abs_diff(SKEW_SECS) <= 1);
+        sender.set_clock_offset(offset);
+
+        assert!(sender.now_secs().abs_diff(receiver.now_secs()) <= 1);
+        let corrected = sender.presence_beacon_if_due().unwrap();
+        assert!(receiver.receive_presence_beacon(corrected).unwrap());
+    }
+
+    #[test]
+    fn test_clock_correction_brings_message_timestamps_into_replay_window() {
+        use crate::transport::presence::MAX_CLOCK_SKEW_SECS;
+
+        // As above: the receiver runs on mesh time, the sender two hours
+        // behind it.
+        const SKEW_SECS: i64 = 2 * 60 * 60;
+        let receiver = IronCore::new();
+        receiver.grant_consent();
+        receiver.initialize_identity().unwrap();
+        receiver.set_clock_offset(SKEW_SECS);
+        let receiver_pk = receiver.get_identity_info().public_key_hex.unwrap();
+        let sender = IronCore::new();
+        sender.grant_consent();
+        sender.initialize_identity().unwrap();
+        let in_window =
+            |timestamp: u64| timestamp.abs_diff(receiver.now_secs()) <= MAX_CLOCK_SKEW_SECS;
+
+        let stale = sender
+            .prepare_message(
+                receiver_pk.clone(),
+                "before".into(),
+                crate::MessageType::Text,
+                None,
+            )
+            .unwrap();
+        let received = receiver.receive_message(stale.envelope_data).unwrap();
+        assert!(!in_window(received.timestamp));
+
+        sender.set_clock_offset(SKEW_SECS);
+        let corrected = sender
+            .prepare_message(receiver_pk, "after".into(), crate::MessageType::Text, None)
+            .unwrap();
+        let received = receiver.receive_message(corrected.envelope_data).unwrap();
+        assert!(in_window(received.timestamp));
+    }
+
+    #[test]
+    fn test_delivery_report_tracks_outgoing_message() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+
+        let prepared = alice
+            .prepare_message(bob_pk.clone(), "hi".into(), crate::MessageType::Text, None)
+            .unwrap();
+        let id = prepared.message_id;
+        let report = alice.delivery_report(id.clone()).unwrap();
+        assert_eq!(report.status, crate::store::DeliveryState::Queued);
+        assert_eq!(report.recipient, bob_pk);
+        assert_eq!(report.attempts, 0);
+
+        alice
+            .record_delivery_attempt(id.clone(), "direct".into(), Some("dial failure".into()))
+            .unwrap();
+        assert!(alice.mark_message_sent(id.clone()));
+        let report = alice.delivery_report(id.clone()).unwrap();
+        assert_eq!(report.status, crate::store::DeliveryState::Sent);
+        assert_eq!(report.attempts, 1);
+        assert_eq!(report.paths_tried, vec!["direct".to_string()]);
+        assert_eq!(report.last_error.as_deref(), Some("dial failure"));
+
+        alice
+            .mark_delivery_failed(id.clone(), "max attempts reached".into())
+            .unwrap();
+        assert_eq!(
+            alice.delivery_report(id).unwrap().status,
+            crate::store::DeliveryState::Failed
+        );
+        assert!(alice.delivery_report("unknown".into()).is_none());
+    }
+
+    #[test]
+    fn test_send_draft_keeps_draft_when_prepare_fails() {
+        let alice = IronCore::new();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+
+        // No identity yet: the draft can be saved but not sent.
+        alice.save_draft(bob_pk.clone(), "kept".into()).unwrap();
+        assert!(matches!(
+            alice.send_draft(bob_pk.clone()),
+            Err(IronCoreError::NotInitialized)
+        ));
+        assert_eq!(alice.get_draft(bob_pk).unwrap().text, "kept");
+    }
+
+    #[test]
+    fn test_cancel_pending_only_touches_one_recipient() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let mut keys = Vec::new();
+        for _ in 0..2 {
+            let peer = IronCore::new();
+            peer.grant_consent();
+            peer.initialize_identity().unwrap();
+            keys.push(peer.get_identity_info().public_key_hex.unwrap());
+        }
+        let (bob_pk, carol_pk) = (keys[0].clone(), keys[1].clone());
+
+        for text in ["one", "two"] {
+            alice
+                .prepare_message(bob_pk.clone(), text.into(), crate::MessageType::Text, None)
+                .unwrap();
+        }
+        let to_carol = alice
+            .prepare_message(
+                carol_pk.clone(),
+                "hi".into(),
+                crate::MessageType::Text,
+                None,
+            )
+            .unwrap();
+        assert_eq!(alice.pending_for(bob_pk.clone()).len(), 2);
+        assert_eq!(alice.outbox_count(), 3);
+
+        assert_eq!(alice.cancel_pending(bob_pk.clone()), 2);
+        assert!(alice.pending_for(bob_pk.clone()).is_empty());
+        assert_eq!(alice.cancel_pending(bob_pk), 0);
+
+        let carol_pending = alice.pending_for(carol_pk);
+        assert_eq!(carol_pending.len(), 1);
+        assert_eq!(carol_pending[0].message_id, to_carol.message_id);
+        assert_eq!(alice.outbox_count(), 1);
+    }
+
+    #[test]
+    fn test_outbox_reconciled_after_identity_swap() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+
+        // Two queued messages: one with sent history, one without.
+        let with_history = alice
+            .prepare_message(
+                bob_pk.clone(),
+                "keep me".into(),
+                crate::MessageType::Text,
+                None,
+            )
+            .unwrap();
+        alice
+            .history_store_manager()
+            .add(MessageRecord {
+                id: with_history.message_id.clone(),
+                direction: MessageDirection::Sent,
+                peer_id: bob_pk.clone(),
+                content: "keep me".into(),
+                timestamp: 1,
+                sender_timestamp: 1,
+                delivered: false,
+                hidden: false,
+            })
+            .unwrap();
+        let orphan = alice
+            .prepare_message(
+                bob_pk.clone(),
+                "lost".into(),
+                crate::MessageType::Text,
+                None,
+            )
+            .unwrap();
+        assert!(alice.outbox_contains_for_recipient(&bob_pk, &orphan.message_id));
+
+        // Nothing to do while the identity is unchanged.
+        let report: crate::store::OutboxReconcileReport =
+            serde_json::from_str(&alice.reconcile_outbox_after_import().unwrap()).unwrap();
+        assert_eq!(report.kept, 2);
+
+        // Swap in another identity via backup import.
+        let other = IronCore::new();
+        other.grant_consent();
+        other.initialize_identity().unwrap();
+        let other_pk = other.get_identity_info().public_key_hex.unwrap();
+        let backup = other
+            .export_identity_backup_fast("pw-123456".into())
+            .unwrap();
+        alice
+            .import_identity_backup(backup, "pw-123456".into())
+            .unwrap();
+        assert_eq!(alice.get_identity_info().public_key_hex.unwrap(), other_pk);
+
+        assert!(!alice.outbox_contains_for_recipient(&bob_pk, &orphan.message_id));
+        let resealed = alice
+            .outbox
+            .read()
+            .peek_for_peer(&bob_pk)
+            .into_iter()
+            .find(|m| m.message_id == with_history.message_id)
+            .expect("message with history is re-sealed, not dropped");
+        let info = alice
+            .relay_envelope_info(resealed.envelope_data.clone())
+            .unwrap();
+        assert_eq!(info.sender_public_key_hex, other_pk);
+
+        let received = bob.receive_message(resealed.envelope_data).unwrap();
+        assert_eq!(received.text_content().unwrap(), "keep me");
+        assert_eq!(received.id, with_history.message_id);
+    }
+
+    #[test]
+    fn test_specific_errors_for_bad_keys_and_envelopes() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let send = |key: &str| {
+            alice.prepare_message(key.to_string(), "x".into(), crate::MessageType::Text, None)
+        };
+        assert!(matches!(send("not-hex"), Err(IronCoreError::MalformedKey)));
+        assert!(matches!(send("abcd"), Err(IronCoreError::KeyWrongLength)));
+
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let eve = IronCore::new();
+        eve.grant_consent();
+        eve.initialize_identity().unwrap();
+        let envelope = send(&bob.get_identity_info().public_key_hex.unwrap())
+            .unwrap()
+            .envelope_data;
+
+        assert!(matches!(
+            eve.receive_message(envelope.clone()),
+            Err(IronCoreError::NotIntendedRecipient)
+        ));
+        assert!(matches!(
+            bob.receive_message(vec![0xde, 0xad, 0xbe, 0xef]),
+            Err(IronCoreError::EnvelopeDecodeFailed)
+        ));
+
+        // A flipped ciphertext bit addressed to bob fails authentication.
+        let mut corrupted = crate::drift::DriftEnvelope::from_bytes(&envelope).unwrap();
+        corrupted.ciphertext[0] ^= 0x01;
+        assert!(matches!(
+            bob.receive_message(corrupted.to_bytes().unwrap()),
+            Err(IronCoreError::AuthenticationFailed)
+        ));
+
+        // A truncated nonce never reaches the AEAD.
+        let alice_keys = crate::identity::IdentityKeys::generate();
+        let bob_keys = crate::identity::IdentityKeys::generate();
+        let bob_pk = bob_keys.signing_key.verifying_key().to_bytes();
+        let mut legacy = crate::encrypt_message(&alice_keys.signing_key, &bob_pk, b"x").unwrap();
+        legacy.nonce.truncate(12);
+        let err = decrypt_message(&bob_keys.signing_key, &legacy).unwrap_err();
+        assert!(matches!(
+            decrypt_error(&err, &[], &bob_pk),
+            IronCoreError::MalformedEnvelope
+        ));
+
+        let err: IronCoreError = anyhow::anyhow!("disk on fire").into();
+        assert_eq!(err.to_string(), "disk on fire");
+    }
+
+    #[test]
+    fn test_relay_only_mode_never_decrypts_or_stores() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let alice_pk = alice.get_identity_info().public_key_hex.unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+
+        let envelope = alice
+            .prepare_message(
+                bob_pk.clone(),
+                "hi bob".into(),
+                crate::MessageType::Text,
+                None,
+            )
+            .unwrap()
+            .envelope_data;
+
+        bob.set_relay_only(true);
+        assert!(matches!(
+            bob.receive_message(envelope.clone()),
+            Err(IronCoreError::RelayOnly)
+        ));
+        assert_eq!(bob.inbox_count(), 0);
+
+        // A relay with no identity can still read forwarding metadata.
+        let relay = IronCore::new();
+        relay.set_relay_only(true);
+        let info = relay.relay_envelope_info(envelope.clone()).unwrap();
+        assert_eq!(info.sender_public_key_hex, alice_pk);
+        assert_eq!(info.size_bytes, envelope.len() as u64);
+
+        // Onion layers wrapped for the relay are still peeled for
+        // forwarding, without reaching the inbox.
+        let carol_pk = crate::identity::IdentityKeys::generate().public_key_hex();
+        let layer = crate::Message {
+            id: uuid::Uuid::new_v4().to_string(),
+            sender_id: alice_pk.clone(),
+            recipient_id: carol_pk.clone(),
+            message_type: crate::MessageType::OnionRelay,
+            payload: b"inner envelope".to_vec(),
+            timestamp: alice.now_secs(),
+        };
+        let bob_key: [u8; 32] = hex::decode(&bob_pk).unwrap().try_into().unwrap();
+        let (onion, _) = {
+            let identity = alice.identity.read();
+            alice
+                .seal_envelope(identity.keys().unwrap(), &bob_pk, bob_key, &layer)
+                .unwrap()
+        };
+        assert_eq!(
+            bob.peel_onion_relay(onion).unwrap(),
+            (carol_pk, b"inner envelope".to_vec())
+        );
+        assert!(matches!(
+            bob.peel_onion_relay(envelope.clone()),
+            Err(IronCoreError::RelayOnly)
+        ));
+        assert_eq!(bob.inbox_count(), 0);
+
+        bob.set_relay_only(false);
+        assert_eq!(
+            bob.receive_message(envelope)
+                .unwrap()
+                .text_content()
+                .unwrap(),
+            "hi bob"
+        );
+        assert_eq!(bob.inbox_count(), 1);
+    }
+
+    #[test]
+    fn test_relay_only_ingest_reads_envelope_info() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let alice_pk = alice.get_identity_info().public_key_hex.unwrap();
+        let relay = IronCore::new();
+        relay.grant_consent();
+        relay.initialize_identity().unwrap();
+        let relay_pk = relay.get_identity_info().public_key_hex.unwrap();
+        let delegate = RecordingDelegate::default();
+        relay.set_delegate(Some(Box::new(delegate.clone())));
+        relay.set_relay_only(true);
+
+        // Even envelopes addressed to the relay are forwarded, not opened.
+        let envelopes: Vec<Vec<u8>> = ["one", "two"]
+            .iter()
+            .map(|text| {
+                alice
+                    .prepare_message(
+                        relay_pk.clone(),
+                        text.to_string(),
+                        crate::MessageType::Text,
+                        None,
+                    )
+                    .unwrap()
+                    .envelope_data
+            })
+            .collect();
+
+        for envelope in &envelopes {
+            let info = match relay.receive_message(envelope.clone()) {
+                Err(IronCoreError::RelayOnly) => {
+                    relay.relay_envelope_info(envelope.clone()).unwrap()
+                }
+                other => panic!("relay-only ingest should refuse, got {:?}", other),
+            };
+            assert_eq!(info.sender_public_key_hex, alice_pk);
+            assert_eq!(info.size_bytes, envelope.len() as u64);
+        }
+        assert!(relay
+            .receive_messages(envelopes)
+            .iter()
+            .all(|result| matches!(result, Err(IronCoreError::RelayOnly))));
+
+        assert_eq!(relay.inbox_count(), 0);
+        assert_eq!(relay.history_store_manager().count(), 0);
+        assert!(delegate.received.lock().is_empty());
+    }
+
+    #[test]
+    fn test_relay_toggle_off_blocks_prepare_and_receive() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+
+        let envelope = alice
+            .prepare_message(bob_pk.clone(), "hi".into(), crate::MessageType::Text, None)
+            .unwrap()
+            .envelope_data;
+
+        let off = crate::MeshSettings {
+            relay_enabled: false,
+            ..Default::default()
+        };
+        alice.update_settings(off.clone()).unwrap();
+        bob.update_settings(off).unwrap();
+        assert!(!alice.get_settings().relay_enabled);
+        assert!(matches!(
+            alice.prepare_message(bob_pk, "again".into(), crate::MessageType::Text, None),
+            Err(IronCoreError::MeshParticipationDisabled)
+        ));
+        assert!(matches!(
+            bob.receive_message(envelope.clone()),
+            Err(IronCoreError::MeshParticipationDisabled)
+        ));
+        assert_eq!(bob.inbox_count(), 0);
+
+        bob.update_settings(crate::MeshSettings::default()).unwrap();
+        assert_eq!(
+            bob.receive_message(envelope)
+                .unwrap()
+                .text_content()
+                .unwrap(),
+            "hi"
+        );
+    }
+
+    #[test]
+    fn test_update_settings_persists_and_rejects_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap().to_string();
+        let core = IronCore::with_storage(path.clone());
+        let settings = crate::MeshSettings {
+            relay_enabled: false,
+            ..Default::default()
+        };
+        core.update_settings(settings).unwrap();
+        drop(core);
+
+        let reopened = IronCore::with_storage(path);
+        assert!(!reopened.get_settings().relay_enabled);
+        let invalid = crate::MeshSettings {
+            max_relay_budget: 0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            reopened.update_settings(invalid),
+            Err(IronCoreError::InvalidInput)
+        ));
+        assert!(!reopened.get_settings().relay_enabled);
+    }
+
+    #[test]
+    fn test_ack_timeouts_report_and_clear() {
+        let alice = IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let bob = IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+
+        let first = alice
+            .prepare_message(bob_pk.clone(), "one".into(), crate::MessageType::Text, None)
+            .unwrap();
+        let second = alice
+            .prepare_message(bob_pk, "two".into(), crate::MessageType::Text, None)
+            .unwrap();
+        assert!(alice.pending_acks().contains_key(&first.message_id));
+        assert!(alice.check_ack_timeouts(3600).is_empty());
+
+        // Receipt for the first message arrives.
+        alice.mark_message_sent(first.message_id.clone());
+        assert!(!alice.pending_acks().contains_key(&first.message_id));
+
+        assert_eq!(alice.check_ack_timeouts(0), vec![second.message_id.clone()]);
+        assert!(alice.clear_pending_ack(second.message_id));
+        assert!(alice.check_ack_timeouts(0).is_empty());
+    }
+
+    #[test]
+    fn test_pending_acks_are_capped() {
+        let mut pending = std::collections::HashMap::new();
+        for i in 0..MAX_PENDING_ACKS as u64 {
+            track_pending_ack(&mut pending, format!("msg-{i}"), 1_000 + i);
+        }
+        assert_eq!(pending.len(), MAX_PENDING_ACKS);
+
+        track_pending_ack(&mut pending, "newest".to_string(), 1_000_000);
+        assert_eq!(pending.len(), MAX_PENDING_ACKS);
+        assert!(!pending.contains_key("msg-0"));
+        assert!(pending.contains_key("msg-1"));
+        assert!(pending.contains_key("newest"));
+    }
+
+    #[test]
+    fn test_escalation_policy_surface() {
+        use crate::transport::abstraction::TransportType;
+        use crate::transport::escalation::EscalationPolicy;
+
+        let core = IronCore::new();
+        core.set_escalation_policy(EscalationPolicy::PreferLowPower);
+        assert!(core.escalation_state().is_empty());
+
+        let peer = [7u8; 32];
+        core.escalation_engine()
+            .read()
+            .init_peer(peer, vec![TransportType::BLE, TransportType::Internet])
+            .unwrap();
+        let state = core.escalation_state();
+        assert_eq!(
+            state[&hex::encode(peer)].current_transport,
+            TransportType::BLE
+        );
+
+        let settings = crate::settings::MeshSettings {
+            internet_enabled: false,
+            battery_floor: 35,
+            ..Default::default()
+        };
+        core.apply_policy_config(&serde_json::to_string(&settings).unwrap())
+            .unwrap();
+        let thresholds = core.escalation_engine().read().thresholds();
+        assert!(!thresholds.internet_enabled);
+        assert_eq!(thresholds.battery_floor_percent, 35);
+        assert_eq!(
+            core.escalation_engine().read().policy(),
+            EscalationPolicy::PreferLowPower
+        );
+    }
+
+    #[test]
+    fn test_contacts_bundle_round_trip_and_tamper_detection() {
+        let exporter = IronCore::new();
+        assert!(matches!(
+            exporter.export_contacts_bundle(),
+            Err(IronCoreError::NotInitialized)
+        ));
+        exporter.grant_consent();
+        exporter.initialize_identity().unwrap();
+
+        let friend_pk = crate::identity::IdentityKeys::generate().public_key_hex();
+        let mut friend = Contact::new("peer-friend".to_string(), friend_pk.clone());
+        friend.local_nickname = Some("Friend".to_string());
+        exporter.contacts_store_manager().add(friend).unwrap();
+        let bundle = exporter.export_contacts_bundle().unwrap();
+
+        let importer = IronCore::new();
+        let report: crate::store::ContactImportReport = serde_json::from_str(
+            &importer
+                .import_contacts_bundle(bundle.clone(), true, false)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(report.added, 1);
+        let imported = importer
+            .contacts_store_manager()
+            .get("peer-friend".to_string())
+            .unwrap()
+            .unwrap();
+        assert_eq!(imported.public_key, friend_pk);
+        assert_eq!(imported.local_nickname.as_deref(), Some("Friend"));
+
+        let tampered = bundle.replace("Friend", "Impostor");
+        assert!(matches!(
+            importer.import_contacts_bundle(tampered.clone(), true, true),
+            Err(IronCoreError::CryptoError)
+        ));
+        // Explicitly skipping verification accepts it.
+        assert!(importer
+            .import_contacts_bundle(tampered, false, false)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_file_receive_reports_missing_chunks() {
+        let core = IronCore::new();
+        core.grant_consent();
+        core.initialize_identity().unwrap();
+        let recipient: [u8; 32] = hex::decode(core.get_identity_info().public_key_hex.unwrap())
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let sender = crate::identity::IdentityKeys::generate();
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i % 253) as u8).collect();
+        let chunks =
+            crate::crypto::seal_file_chunks(&sender.signing_key, &recipient, "f", &data, 4096)
+                .unwrap();
+        assert_eq!(chunks.len(), 3);
+
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("received.bin");
+        let out_path = out.to_string_lossy().to_string();
+        core.start_file_receive("f".into(), out_path.clone())
+            .unwrap();
+        assert!(matches!(
+            core.start_file_receive("f".into(), out_path),
+            Err(IronCoreError::InvalidInput)
+        ));
+        core.receive_file_chunk("f".into(), chunks[2].clone())
+            .unwrap();
+        let progress = core
+            .receive_file_chunk("f".into(), chunks[0].clone())
+            .unwrap();
+        assert_eq!(progress.missing_chunks, vec![1]);
+        assert!(matches!(
+            core.receive_file_chunk("f".into(), b"garbage".to_vec()),
+            Err(IronCoreError::InvalidInput | IronCoreError::CryptoError)
+        ));
+
+        let finished = core.finish_file_receive("f".into()).unwrap();
+        assert!(!finished.complete);
+        assert_eq!(finished.missing_chunks, vec![1]);
+        assert_eq!(finished.bytes_written, 4096 + (10_000 - 8192));
+        let written = std::fs::read(&out).unwrap();
+        assert_eq!(&written[..4096], &data[..4096]);
+        assert_eq!(&written[8192..], &data[8192..]);
+        assert!(matches!(
+            core.receive_file_chunk("f".into(), chunks[1].clone()),
+            Err(IronCoreError::InvalidInput)
+        ));
+    }
+
     #[test]
     fn test_manager_fallback_does_not_panic() {
         // Construct with a guaranteed invalid path to force the fallback to fire
@@ -7,6 +7,7 @@
 pub mod abuse;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod blocked_bridge;
+pub mod clock;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod contacts_bridge;
 pub mod crypto;
@@ -31,7 +32,7 @@ pub mod wasm_support;
 
 // Re-export critical types from core modules
 pub use error::MeshError;
-pub use iron_core::{CoreDelegate, IronCore};
+pub use iron_core::{CoreDelegate, DeliveryOutcome, IronCore};
 
 // IronCoreError — defined in Rust rather than generated from UDL
 // because the UDL-based scaffolding requires interface types to have
@@ -70,6 +71,84 @@ pub enum IronCoreError {
     IoError,
     #[error("Onion routing disabled")]
     OnionRoutingDisabled,
+    #[error("Storage path is not writable")]
+    StorageUnwritable,
+    #[error("Not enough free disk space")]
+    StorageFull,
+    #[error("Relay-only mode: payloads are not decrypted")]
+    RelayOnly,
+    #[error("Malformed public key")]
+    MalformedKey,
+    #[error("Public key must be 32 bytes")]
+    KeyWrongLength,
+    #[error("Decryption failed: ciphertext or sender authentication is invalid")]
+    AuthenticationFailed,
+    #[error("Envelope is addressed to a different identity")]
+    NotIntendedRecipient,
+    #[error("Envelope fields are malformed and cannot be decrypted")]
+    MalformedEnvelope,
+    #[error("Envelope could not be decoded")]
+    EnvelopeDecodeFailed,
+    #[error("Message too long to fit in a portable (QR) envelope")]
+    PortableTooLarge,
+    #[error("Envelope uses an unsupported AEAD cipher suite")]
+    UnsupportedCipherSuite,
+    #[error("Nickname is too long or contains control or bidi-override characters")]
+    InvalidNickname,
+    #[error("Envelope was made by a newer version; upgrade to read it")]
+    UnsupportedEnvelopeVersion,
+    #[error("Mesh participation is disabled (relay toggle off)")]
+    MeshParticipationDisabled,
+    /// Catch-all that keeps the underlying cause, e.g. from `anyhow`.
+    #[error("{0}")]
+    Other(String),
+}
+
+impl IronCoreError {
+    /// Stable, locale-independent code for this error, e.g. `"NOT_INITIALIZED"`.
+    /// Codes never change once published, so clients branch on these and
+    /// localize the message themselves; the Display text may be reworded.
+    pub fn code(&self) -> &'static str {
+        match self {
+            IronCoreError::NotInitialized => "NOT_INITIALIZED",
+            IronCoreError::AlreadyRunning => "ALREADY_RUNNING",
+            IronCoreError::StorageError => "STORAGE_ERROR",
+            IronCoreError::CryptoError => "CRYPTO_ERROR",
+            IronCoreError::NetworkError => "NETWORK_ERROR",
+            IronCoreError::InvalidInput => "INVALID_INPUT",
+            IronCoreError::Blocked => "BLOCKED",
+            IronCoreError::ConsentRequired => "CONSENT_REQUIRED",
+            IronCoreError::Internal => "INTERNAL",
+            IronCoreError::CorruptionDetected => "CORRUPTION_DETECTED",
+            IronCoreError::DialSelf => "DIAL_SELF",
+            IronCoreError::NoAddresses => "NO_ADDRESSES",
+            IronCoreError::ConnectionLimit => "CONNECTION_LIMIT",
+            IronCoreError::MultiaddrNotSupported => "MULTIADDR_NOT_SUPPORTED",
+            IronCoreError::IoError => "IO_ERROR",
+            IronCoreError::OnionRoutingDisabled => "ONION_ROUTING_DISABLED",
+            IronCoreError::StorageUnwritable => "STORAGE_UNWRITABLE",
+            IronCoreError::StorageFull => "STORAGE_FULL",
+            IronCoreError::RelayOnly => "RELAY_ONLY",
+            IronCoreError::MalformedKey => "MALFORMED_KEY",
+            IronCoreError::KeyWrongLength => "KEY_WRONG_LENGTH",
+            IronCoreError::AuthenticationFailed => "AUTHENTICATION_FAILED",
+            IronCoreError::NotIntendedRecipient => "NOT_INTENDED_RECIPIENT",
+            IronCoreError::MalformedEnvelope => "MALFORMED_ENVELOPE",
+            IronCoreError::EnvelopeDecodeFailed => "ENVELOPE_DECODE_FAILED",
+            IronCoreError::PortableTooLarge => "PORTABLE_TOO_LARGE",
+            IronCoreError::UnsupportedCipherSuite => "UNSUPPORTED_CIPHER_SUITE",
+            IronCoreError::InvalidNickname => "INVALID_NICKNAME",
+            IronCoreError::UnsupportedEnvelopeVersion => "UNSUPPORTED_ENVELOPE_VERSION",
+            IronCoreError::MeshParticipationDisabled => "MESH_PARTICIPATION_DISABLED",
+            IronCoreError::Other(_) => "OTHER",
+        }
+    }
+}
+
+impl From<anyhow::Error> for IronCoreError {
+    fn from(err: anyhow::Error) -> Self {
+        IronCoreError::Other(format!("{:#}", err))
+    }
 }
 
 pub use crypto::{decrypt_message, encrypt_message};
@@ -94,7 +173,9 @@ pub use notification::{
     NotificationPlatform, NotificationUiState,
 };
 pub use observability::{AuditEvent, AuditEventType};
-pub use settings::{DiscoveryMode, MeshSettings};
+pub use settings::{
+    AdvertiseScope, DiscoveryMode, GossipProfile, KadMode, MeshSettings, SettingsError,
+};
 pub use store::ledger_entry::LedgerEntry;
 pub use store::outbox::RetryPolicy;
 pub use transport::{start_swarm, start_swarm_with_config, SwarmCommand, SwarmEvent, SwarmHandle};
@@ -106,7 +187,7 @@ pub use blocked_bridge::{
     blocked_identity_with_reason, BlockedIdentity, BlockedManager,
 };
 #[cfg(not(target_arch = "wasm32"))]
-pub use contacts_bridge::{Contact, ContactManager};
+pub use contacts_bridge::{Contact, ContactManager, SyncResult};
 #[cfg(not(target_arch = "wasm32"))]
 pub use mobile_bridge::*;
 
@@ -131,6 +212,57 @@ pub struct PreparedMessage {
     pub envelope_data: Vec<u8>,
 }
 
+/// Result of `IronCore::prepare_receipts_bulk`: one sealed receipt envelope
+/// per accepted item, plus the message ids that were skipped.
+#[derive(Default)]
+pub struct BulkReceipts {
+    pub envelopes: Vec<PreparedMessage>,
+    pub skipped: Vec<String>,
+}
+
+/// Result of `IronCore::compact_storage`: on-disk size before and after, and
+/// how many records were rewritten.
+#[derive(Debug, Clone, Default)]
+pub struct CompactionReport {
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    pub records_rewritten: u64,
+}
+
+/// Progress of a chunked file receive (see `IronCore::start_file_receive`).
+#[derive(Debug, Clone, Default)]
+pub struct FileReceiveProgress {
+    pub file_id: String,
+    /// 0 until the first chunk arrives.
+    pub total_chunks: u32,
+    pub received_chunks: u32,
+    /// Indices not yet received, ascending.
+    pub missing_chunks: Vec<u32>,
+    pub bytes_written: u64,
+    pub complete: bool,
+}
+
+impl FileReceiveProgress {
+    pub(crate) fn new(file_id: String, status: crypto::FileReassemblyStatus) -> Self {
+        Self {
+            file_id,
+            total_chunks: status.total_chunks,
+            received_chunks: status.received_chunks,
+            missing_chunks: status.missing_chunks,
+            bytes_written: status.bytes_written,
+            complete: status.complete,
+        }
+    }
+}
+
+/// Envelope metadata a relay-only node may inspect for forwarding, read
+/// without decrypting anything (`IronCore::relay_envelope_info`).
+pub struct RelayEnvelopeInfo {
+    pub sender_public_key_hex: String,
+    pub size_bytes: u64,
+    pub wire_version: u8,
+}
+
 pub struct PeelResult {
     pub next_hop: Option<Vec<u8>>,
     pub remaining_data: Vec<u8>,
@@ -159,10 +291,75 @@ pub fn get_build_provenance() -> String {
 
 #[cfg(test)]
 mod tests {
+    use super::IronCoreError;
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn test_error_codes_are_stable() {
+        let documented = [
+            (IronCoreError::NotInitialized, "NOT_INITIALIZED"),
+            (IronCoreError::AlreadyRunning, "ALREADY_RUNNING"),
+            (IronCoreError::StorageError, "STORAGE_ERROR"),
+            (IronCoreError::CryptoError, "CRYPTO_ERROR"),
+            (IronCoreError::NetworkError, "NETWORK_ERROR"),
+            (IronCoreError::InvalidInput, "INVALID_INPUT"),
+            (IronCoreError::Blocked, "BLOCKED"),
+            (IronCoreError::ConsentRequired, "CONSENT_REQUIRED"),
+            (IronCoreError::Internal, "INTERNAL"),
+            (IronCoreError::CorruptionDetected, "CORRUPTION_DETECTED"),
+            (IronCoreError::DialSelf, "DIAL_SELF"),
+            (IronCoreError::NoAddresses, "NO_ADDRESSES"),
+            (IronCoreError::ConnectionLimit, "CONNECTION_LIMIT"),
+            (
+                IronCoreError::MultiaddrNotSupported,
+                "MULTIADDR_NOT_SUPPORTED",
+            ),
+            (IronCoreError::IoError, "IO_ERROR"),
+            (
+                IronCoreError::OnionRoutingDisabled,
+                "ONION_ROUTING_DISABLED",
+            ),
+            (IronCoreError::StorageUnwritable, "STORAGE_UNWRITABLE"),
+            (IronCoreError::StorageFull, "STORAGE_FULL"),
+            (IronCoreError::RelayOnly, "RELAY_ONLY"),
+            (IronCoreError::MalformedKey, "MALFORMED_KEY"),
+            (IronCoreError::KeyWrongLength, "KEY_WRONG_LENGTH"),
+            (IronCoreError::AuthenticationFailed, "AUTHENTICATION_FAILED"),
+            (
+                IronCoreError::NotIntendedRecipient,
+                "NOT_INTENDED_RECIPIENT",
+            ),
+            (IronCoreError::MalformedEnvelope, "MALFORMED_ENVELOPE"),
+            (
+                IronCoreError::EnvelopeDecodeFailed,
+                "ENVELOPE_DECODE_FAILED",
+            ),
+            (IronCoreError::PortableTooLarge, "PORTABLE_TOO_LARGE"),
+            (
+                IronCoreError::UnsupportedCipherSuite,
+                "UNSUPPORTED_CIPHER_SUITE",
+            ),
+            (IronCoreError::InvalidNickname, "INVALID_NICKNAME"),
+            (
+                IronCoreError::UnsupportedEnvelopeVersion,
+                "UNSUPPORTED_ENVELOPE_VERSION",
+            ),
+            (
+                IronCoreError::MeshParticipationDisabled,
+                "MESH_PARTICIPATION_DISABLED",
+            ),
+            (IronCoreError::Other("x".into()), "OTHER"),
+        ];
+        let mut seen = std::collections::HashSet::new();
+        for (err, code) in documented {
+            assert_eq!(err.code(), code, "{:?}", err);
+            assert!(seen.insert(code), "duplicate code {}", code);
+        }
+    }
 }
 
 // Retry policy module - centralized retry logic for all platforms
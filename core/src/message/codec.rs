@@ -6,7 +6,7 @@
 // it falls back to legacy bincode for backward compatibility with older nodes.
 
 use super::types::{
-    Envelope, EnvelopeV2, Message, SignedEnvelope, SignedEnvelopeV2, WireEnvelope,
+    Envelope, EnvelopeV2, Message, MessageType, SignedEnvelope, SignedEnvelopeV2, WireEnvelope,
     WireSignedEnvelope, WIRE_TAG_V2,
 };
 use crate::drift::envelope::COMPRESSION_THRESHOLD;
@@ -21,6 +21,43 @@ pub const MAX_MESSAGE_SIZE: usize = 256 * 1024;
 /// Maximum text payload: 8 KB (8192 bytes)
 pub const MAX_PAYLOAD_SIZE: usize = 8 * 1024;
 
+/// Newest envelope format this build understands. The first byte of an
+/// encoded envelope names its format: `DRIFT_VERSION` (0x01) for Drift
+/// frames, `WIRE_TAG_V2` (0x02) for hybrid post-quantum envelopes. Legacy
+/// bincode envelopes start with the sender key's length prefix (0x20).
+pub const MAX_ENVELOPE_VERSION: u8 = WIRE_TAG_V2;
+
+/// First byte of a legacy bincode envelope (32-byte sender key length).
+const LEGACY_BINCODE_LEAD: u8 = 0x20;
+
+/// An envelope was made by a newer client in a format this build cannot
+/// parse. Reported instead of a generic decode failure so the user can be
+/// told to upgrade rather than that the message is corrupt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "Envelope format version {found} is newer than this client supports (up to {supported}); upgrade to read it"
+)]
+pub struct UnsupportedEnvelopeVersion {
+    pub found: u8,
+    pub supported: u8,
+}
+
+/// Reject envelopes whose leading version byte is newer than
+/// `MAX_ENVELOPE_VERSION`. Bytes from 0x20 up are left to the legacy
+/// bincode decoder.
+fn check_envelope_version(bytes: &[u8]) -> Result<()> {
+    match bytes.first() {
+        Some(&version) if version > MAX_ENVELOPE_VERSION && version < LEGACY_BINCODE_LEAD => {
+            Err(UnsupportedEnvelopeVersion {
+                found: version,
+                supported: MAX_ENVELOPE_VERSION,
+            }
+            .into())
+        }
+        _ => Ok(()),
+    }
+}
+
 /// Validate plaintext payload size against the messaging contract.
 pub fn validate_payload_size(payload: &[u8]) -> Result<()> {
     if payload.len() > MAX_PAYLOAD_SIZE {
@@ -64,6 +101,70 @@ pub fn decode_message(bytes: &[u8]) -> Result<Message> {
     Ok(msg)
 }
 
+/// A decrypted message whose `MessageType` this build does not know, e.g.
+/// one sent by a newer client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownMessage {
+    /// The sender's wire index for the message type.
+    pub type_id: u32,
+    pub id: String,
+    pub sender_id: String,
+    pub recipient_id: String,
+    pub payload: Vec<u8>,
+    pub timestamp: u64,
+}
+
+impl UnknownMessage {
+    /// A text stand-in carrying this message's id and sender, for callers
+    /// that can only show or acknowledge a `Message`.
+    pub fn fallback(&self) -> Message {
+        Message {
+            id: self.id.clone(),
+            sender_id: self.sender_id.clone(),
+            recipient_id: self.recipient_id.clone(),
+            message_type: MessageType::Text,
+            payload: format!(
+                "Unsupported message (type {}): update to view it",
+                self.type_id
+            )
+            .into_bytes(),
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+/// Wire layout of `Message` with the type left as its raw variant index.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct RawMessage {
+    pub id: String,
+    pub sender_id: String,
+    pub recipient_id: String,
+    pub message_type: u32,
+    pub payload: Vec<u8>,
+    pub timestamp: u64,
+}
+
+/// Recover a message that `decode_message` rejected only because its type
+/// is newer than this build. `None` for anything otherwise malformed.
+pub fn decode_unknown_message(bytes: &[u8]) -> Option<UnknownMessage> {
+    if bytes.len() > MAX_MESSAGE_SIZE {
+        return None;
+    }
+    let raw: RawMessage = bincode::deserialize(bytes).ok()?;
+    let known = bincode::deserialize::<MessageType>(&raw.message_type.to_le_bytes()).is_ok();
+    if known {
+        return None;
+    }
+    Some(UnknownMessage {
+        type_id: raw.message_type,
+        id: raw.id,
+        sender_id: raw.sender_id,
+        recipient_id: raw.recipient_id,
+        payload: raw.payload,
+        timestamp: raw.timestamp,
+    })
+}
+
 /// Serialize an Envelope to bytes using the Drift Protocol binary format.
 ///
 /// The legacy Envelope is converted to a DriftEnvelope with:
@@ -108,6 +209,7 @@ pub fn decode_envelope(bytes: &[u8]) -> Result<Envelope> {
             MAX_MESSAGE_SIZE
         );
     }
+    check_envelope_version(bytes)?;
 
     // Try Drift Protocol binary format first
     if !bytes.is_empty() && bytes[0] == DRIFT_VERSION {
@@ -185,6 +287,7 @@ fn encode_drift_envelope(envelope: &Envelope) -> Result<Vec<u8>> {
         pq_kem_ciphertext: None,
         pq_encaps_key: None,
         transcript_hash: None,
+        aead_suite: envelope.aead_suite,
     };
 
     Ok(drift_env.to_bytes()?)
@@ -221,6 +324,7 @@ pub fn decode_wire_envelope(buf: &[u8]) -> Result<WireEnvelope> {
     if buf.len() > MAX_MESSAGE_SIZE {
         bail!("Envelope buffer too large");
     }
+    check_envelope_version(buf)?;
 
     // Check for Drift binary format first
     if !buf.is_empty() && buf[0] == crate::drift::DRIFT_VERSION {
@@ -322,6 +426,7 @@ pub fn decode_wire_signed_envelope(buf: &[u8]) -> Result<WireSignedEnvelope> {
     if buf.len() > MAX_MESSAGE_SIZE {
         bail!("Signed envelope buffer too large");
     }
+    check_envelope_version(buf)?;
 
     if buf[0] == WIRE_TAG_V2 {
         // Try V2 decode first
@@ -390,11 +495,104 @@ pub fn decode_wire_signed_envelope(buf: &[u8]) -> Result<WireSignedEnvelope> {
     Ok(WireSignedEnvelope::V1(env))
 }
 
+/// One envelope encoding test vector. Keys, ephemeral secret and nonce are
+/// fixed, so sealing `plaintext` must give exactly `envelope`: a V1 wire
+/// envelope (legacy bincode `Envelope`). All byte fields are hex; secret
+/// keys are 32-byte Ed25519 seeds.
+#[cfg(any(test, feature = "test-vectors"))]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EnvelopeTestVector {
+    pub name: String,
+    pub sender_secret_key: String,
+    pub recipient_secret_key: String,
+    pub ephemeral_secret: String,
+    pub nonce: String,
+    pub aead_suite: u8,
+    pub plaintext: String,
+    pub envelope: String,
+}
+
+/// The committed envelope test vectors, for checking another
+/// implementation's codec byte for byte.
+#[cfg(any(test, feature = "test-vectors"))]
+pub fn test_vectors() -> Vec<EnvelopeTestVector> {
+    serde_json::from_str(include_str!("test_vectors.json"))
+        .expect("committed test vectors are valid JSON")
+}
+
+/// Check that this codec reproduces `vector` exactly: sealing its plaintext
+/// gives its envelope bytes, and decoding and opening those bytes gives the
+/// plaintext back.
+#[cfg(any(test, feature = "test-vectors"))]
+pub fn verify_test_vector(vector: &EnvelopeTestVector) -> Result<()> {
+    use crate::crypto::aead::AeadSuite;
+    use ed25519_dalek::SigningKey;
+
+    fn hex_array<const N: usize>(field: &str, value: &str) -> Result<[u8; N]> {
+        hex::decode(value)?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("{} must be {} bytes", field, N))
+    }
+
+    let sender =
+        SigningKey::from_bytes(&hex_array("sender_secret_key", &vector.sender_secret_key)?);
+    let recipient = SigningKey::from_bytes(&hex_array(
+        "recipient_secret_key",
+        &vector.recipient_secret_key,
+    )?);
+    let suite = AeadSuite::from_u8(vector.aead_suite).map_err(anyhow::Error::new)?;
+    let plaintext = hex::decode(&vector.plaintext)?;
+    let expected = hex::decode(&vector.envelope)?;
+
+    let envelope = crate::crypto::encrypt::encrypt_message_with_ephemeral(
+        &sender,
+        &recipient.verifying_key().to_bytes(),
+        &plaintext,
+        suite,
+        hex_array("ephemeral_secret", &vector.ephemeral_secret)?,
+        hex_array("nonce", &vector.nonce)?,
+    )?;
+    let encoded = encode_wire_envelope(&WireEnvelope::V1(envelope))?;
+    if encoded != expected {
+        bail!(
+            "Test vector {}: encoded envelope differs from the committed bytes",
+            vector.name
+        );
+    }
+
+    let opened = crate::crypto::decrypt_message(&recipient, &decode_envelope(&expected)?)?;
+    if opened != plaintext {
+        bail!(
+            "Test vector {}: decoded envelope opens to different plaintext",
+            vector.name
+        );
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::message::types::Message;
 
+    #[test]
+    fn test_codec_reproduces_committed_test_vectors() {
+        let vectors = test_vectors();
+        assert!(!vectors.is_empty());
+        for vector in &vectors {
+            verify_test_vector(vector).unwrap();
+        }
+
+        // A flipped ciphertext byte (just before the two `None` ratchet
+        // fields and the suite byte) must not verify.
+        let mut tampered = vectors[0].clone();
+        let mut bytes = hex::decode(&tampered.envelope).unwrap();
+        let last_ciphertext_byte = bytes.len() - 4;
+        bytes[last_ciphertext_byte] ^= 0x01;
+        tampered.envelope = hex::encode(bytes);
+        assert!(verify_test_vector(&tampered).is_err());
+    }
+
     #[test]
     fn test_message_roundtrip() {
         let msg = Message::text("sender".into(), "recipient".into(), "hello world");
@@ -484,6 +682,7 @@ mod tests {
             pq_kem_ciphertext: None,
             pq_encaps_key: None,
             transcript_hash: None,
+            aead_suite: crate::crypto::aead::DEFAULT_AEAD_SUITE,
         };
 
         // Test roundtrip
@@ -506,6 +705,7 @@ mod tests {
             ciphertext: vec![4u8; 100],
             ratchet_dh_public: None,
             ratchet_message_number: None,
+            aead_suite: crate::crypto::aead::DEFAULT_AEAD_SUITE,
         };
 
         let bytes = encode_envelope(&envelope).unwrap();
@@ -538,6 +738,7 @@ mod tests {
             pq_kem_ciphertext: None,
             pq_encaps_key: None,
             transcript_hash: None,
+            aead_suite: crate::crypto::aead::DEFAULT_AEAD_SUITE,
         };
 
         let bytes = drift_env.to_bytes().unwrap();
@@ -560,6 +761,7 @@ mod tests {
             ciphertext: vec![4u8; 50],
             ratchet_dh_public: None,
             ratchet_message_number: None,
+            aead_suite: crate::crypto::aead::DEFAULT_AEAD_SUITE,
         };
 
         let bytes = bincode::serialize(&envelope).unwrap();
@@ -573,6 +775,36 @@ mod tests {
         assert_eq!(restored.ciphertext, vec![4u8; 50]);
     }
 
+    #[test]
+    fn test_decode_rejects_future_envelope_version() {
+        let envelope = Envelope {
+            sender_public_key: vec![1u8; 32],
+            ephemeral_public_key: vec![2u8; 32],
+            nonce: vec![3u8; 24],
+            ciphertext: vec![4u8; 50],
+            ratchet_dh_public: None,
+            ratchet_message_number: None,
+            aead_suite: crate::crypto::aead::DEFAULT_AEAD_SUITE,
+        };
+        let mut bytes = encode_envelope(&envelope).unwrap();
+        assert!(decode_envelope(&bytes).is_ok());
+        assert!(decode_wire_envelope(&bytes).is_ok());
+
+        // Same frame, stamped with a version from a hypothetical newer client.
+        bytes[0] = MAX_ENVELOPE_VERSION + 1;
+        for err in [
+            decode_envelope(&bytes).unwrap_err(),
+            decode_wire_envelope(&bytes).unwrap_err(),
+        ] {
+            let version = err
+                .downcast_ref::<UnsupportedEnvelopeVersion>()
+                .expect("future version should be reported as such");
+            assert_eq!(version.found, MAX_ENVELOPE_VERSION + 1);
+            assert_eq!(version.supported, MAX_ENVELOPE_VERSION);
+            assert!(err.to_string().contains("upgrade"));
+        }
+    }
+
     #[test]
     fn test_envelope_compression_threshold() {
         // Envelopes with large ciphertext should have compressed flag set
@@ -583,6 +815,7 @@ mod tests {
             ciphertext: vec![0xABu8; 512], // Above COMPRESSION_THRESHOLD
             ratchet_dh_public: None,
             ratchet_message_number: None,
+            aead_suite: crate::crypto::aead::DEFAULT_AEAD_SUITE,
         };
 
         let bytes = encode_envelope(&envelope).unwrap();
@@ -705,6 +938,7 @@ mod tests {
             ciphertext: vec![4u8; 100],
             ratchet_dh_public: None,
             ratchet_message_number: None,
+            aead_suite: crate::crypto::aead::DEFAULT_AEAD_SUITE,
         };
 
         let wire = WireEnvelope::V1(envelope.clone());
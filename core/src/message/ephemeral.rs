@@ -25,7 +25,13 @@ pub fn is_expired(creation_timestamp: u64, ttl: &TtlConfig) -> bool {
         .expect("Time went backwards")
         .as_secs();
 
-    current_time > creation_timestamp + ttl.expires_in_seconds
+    is_expired_at(creation_timestamp, ttl, current_time)
+}
+
+/// Same as [`is_expired`], against an explicit `now` (e.g. from a
+/// [`crate::clock::Clock`]).
+pub fn is_expired_at(creation_timestamp: u64, ttl: &TtlConfig, now: u64) -> bool {
+    now > creation_timestamp.saturating_add(ttl.expires_in_seconds)
 }
 
 #[cfg(test)]
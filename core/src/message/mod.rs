@@ -5,9 +5,12 @@ pub mod ephemeral;
 pub mod types;
 
 pub use codec::{
-    decode_envelope, decode_message, decode_wire_envelope, decode_wire_signed_envelope,
-    encode_envelope, encode_message, encode_wire_envelope, encode_wire_signed_envelope,
+    decode_envelope, decode_message, decode_unknown_message, decode_wire_envelope,
+    decode_wire_signed_envelope, encode_envelope, encode_message, encode_wire_envelope,
+    encode_wire_signed_envelope, UnknownMessage,
 };
+#[cfg(any(test, feature = "test-vectors"))]
+pub use codec::{test_vectors, verify_test_vector, EnvelopeTestVector};
 pub use ephemeral::*;
 pub use types::{
     DeliveryStatus, Envelope, EnvelopeV2, Message, MessageType, Receipt, SignedEnvelope,
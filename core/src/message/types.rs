@@ -11,6 +11,12 @@ pub enum MessageType {
     Receipt,
     /// Onion relay packet (internal use for forwarding)
     OnionRelay,
+    /// Signed, unencrypted payload published to a gossipsub topic
+    /// (announcements, presence). `recipient_id` carries the topic.
+    Broadcast,
+    /// Typing indicator: payload is one byte, 1 = typing, 0 = stopped.
+    /// Encrypted like any message but never stored or deduplicated.
+    Typing,
 }
 
 /// Delivery status of a message
@@ -77,7 +83,7 @@ pub struct Envelope {
     /// Ephemeral X25519 public key (32 bytes) — for ECDH key agreement.
     /// In ratcheted mode, this carries the DH ratchet public key.
     pub ephemeral_public_key: Vec<u8>,
-    /// XChaCha20-Poly1305 nonce (24 bytes)
+    /// AEAD nonce (24 bytes; suites with shorter nonces use the prefix)
     pub nonce: Vec<u8>,
     /// Encrypted + authenticated ciphertext
     pub ciphertext: Vec<u8>,
@@ -89,6 +95,14 @@ pub struct Envelope {
     /// `None` for legacy per-message ECDH envelopes.
     #[serde(default)]
     pub ratchet_message_number: Option<u32>,
+    /// AEAD cipher suite the ciphertext is sealed with (see `crypto::aead`).
+    /// Envelopes without it are XChaCha20-Poly1305.
+    #[serde(default = "default_aead_suite")]
+    pub aead_suite: u8,
+}
+
+fn default_aead_suite() -> u8 {
+    crate::crypto::aead::DEFAULT_AEAD_SUITE
 }
 
 /// A signed envelope — adds Ed25519 signature for relay verification.
@@ -191,6 +205,46 @@ impl Message {
         })
     }
 
+    /// Create a broadcast for `topic`. The payload is not encrypted; it is
+    /// authenticated by the signed gossip frame it is published in.
+    pub fn broadcast(sender_id: String, topic: &str, data: Vec<u8>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            sender_id,
+            recipient_id: topic.to_string(),
+            message_type: MessageType::Broadcast,
+            payload: data,
+            timestamp: web_time::SystemTime::now()
+                .duration_since(web_time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+
+    /// Create a typing indicator
+    pub fn typing(sender_id: String, recipient_id: String, is_typing: bool) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            sender_id,
+            recipient_id,
+            message_type: MessageType::Typing,
+            payload: vec![is_typing as u8],
+            timestamp: web_time::SystemTime::now()
+                .duration_since(web_time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+
+    /// Typing state (only valid for Typing messages)
+    pub fn typing_state(&self) -> Option<bool> {
+        if self.message_type == MessageType::Typing {
+            self.payload.first().map(|b| *b != 0)
+        } else {
+            None
+        }
+    }
+
     /// Get text content (only valid for Text messages)
     pub fn text_content(&self) -> Option<String> {
         if self.message_type == MessageType::Text {
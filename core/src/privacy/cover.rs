@@ -3,8 +3,10 @@
 // Generates fake messages that are indistinguishable from real traffic
 // to prevent attackers from observing when actual communication occurs.
 
-use rand::RngCore;
+use parking_lot::Mutex;
+use rand::{Rng, RngCore};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use thiserror::Error;
 use web_time::{Duration, SystemTime};
 
@@ -16,15 +18,83 @@ pub enum CoverTrafficError {
     InvalidMessageSize,
 }
 
+/// Largest cover payload accepted, in bytes.
+const MAX_COVER_SIZE: usize = 65536;
+
+/// Default quiet period after real outbound traffic before cover resumes.
+pub const DEFAULT_REAL_ACTIVITY_COOLDOWN_MS: u64 = 30_000;
+
+/// How many recent real message sizes `SizeDistribution::Observed` samples from.
+const OBSERVED_SIZE_WINDOW: usize = 256;
+
+/// One weighted payload size in a `SizeDistribution::Weighted`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeBucket {
+    pub size: usize,
+    pub weight: u32,
+}
+
+/// How cover payload sizes are chosen. A constant size stands out against
+/// variable-length real traffic, so anything but `Fixed` is preferred.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SizeDistribution {
+    /// Always `CoverConfig::message_size`.
+    #[default]
+    Fixed,
+    /// Pick a bucket with probability proportional to its weight.
+    Weighted(Vec<SizeBucket>),
+    /// Replay the sizes of recently sent real messages (see
+    /// `CoverTrafficGenerator::observe_real_size`); `message_size` until
+    /// any have been seen.
+    Observed,
+}
+
+impl SizeDistribution {
+    /// Rough shape of mesh chat traffic: mostly short texts, some longer
+    /// messages and the occasional large payload.
+    pub fn chat() -> Self {
+        Self::Weighted(vec![
+            SizeBucket {
+                size: 256,
+                weight: 45,
+            },
+            SizeBucket {
+                size: 512,
+                weight: 30,
+            },
+            SizeBucket {
+                size: 1024,
+                weight: 15,
+            },
+            SizeBucket {
+                size: 4096,
+                weight: 10,
+            },
+        ])
+    }
+}
+
 /// Configuration for cover traffic generation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CoverConfig {
     /// Number of cover messages to generate per minute
     pub rate_per_minute: u32,
-    /// Target message size in bytes
+    /// Message size in bytes for `SizeDistribution::Fixed`, and the fallback
+    /// for `Observed` before any real message has been seen
     pub message_size: usize,
     /// Whether to generate cover traffic (can be disabled)
     pub enabled: bool,
+    /// How each cover message's payload size is chosen
+    #[serde(default)]
+    pub size_distribution: SizeDistribution,
+    /// Cover is suppressed for this long after each real outbound message,
+    /// since real traffic already masks the pattern. 0 never suppresses.
+    #[serde(default = "default_real_activity_cooldown_ms")]
+    pub real_activity_cooldown_ms: u64,
+}
+
+fn default_real_activity_cooldown_ms() -> u64 {
+    DEFAULT_REAL_ACTIVITY_COOLDOWN_MS
 }
 
 impl Default for CoverConfig {
@@ -33,6 +103,8 @@ impl Default for CoverConfig {
             rate_per_minute: 10,
             message_size: 1024,
             enabled: true,
+            size_distribution: SizeDistribution::default(),
+            real_activity_cooldown_ms: DEFAULT_REAL_ACTIVITY_COOLDOWN_MS,
         }
     }
 }
@@ -50,11 +122,26 @@ impl CoverConfig {
                 "message_size must be > 0".to_string(),
             ));
         }
-        if self.message_size > 65536 {
+        if self.message_size > MAX_COVER_SIZE {
             return Err(CoverTrafficError::InvalidConfig(
                 "message_size exceeds maximum (65536 bytes)".to_string(),
             ));
         }
+        if let SizeDistribution::Weighted(buckets) = &self.size_distribution {
+            if buckets.iter().all(|b| b.weight == 0) {
+                return Err(CoverTrafficError::InvalidConfig(
+                    "size_distribution needs at least one bucket with weight > 0".to_string(),
+                ));
+            }
+            if buckets
+                .iter()
+                .any(|b| b.size == 0 || b.size > MAX_COVER_SIZE)
+            {
+                return Err(CoverTrafficError::InvalidConfig(
+                    "size_distribution bucket sizes must be 1..=65536 bytes".to_string(),
+                ));
+            }
+        }
         Ok(())
     }
 
@@ -94,13 +181,58 @@ impl CoverMessage {
 /// Generates cover traffic messages
 pub struct CoverTrafficGenerator {
     config: CoverConfig,
+    /// Recent real message sizes, for `SizeDistribution::Observed`
+    observed_sizes: Mutex<VecDeque<usize>>,
 }
 
 impl CoverTrafficGenerator {
     /// Create a new cover traffic generator
     pub fn new(config: CoverConfig) -> Result<Self, CoverTrafficError> {
         config.validate()?;
-        Ok(Self { config })
+        Ok(Self {
+            config,
+            observed_sizes: Mutex::new(VecDeque::with_capacity(OBSERVED_SIZE_WINDOW)),
+        })
+    }
+
+    /// Record the size of a real outgoing message. Only used by
+    /// `SizeDistribution::Observed`; keeps the most recent sizes.
+    pub fn observe_real_size(&self, size: usize) {
+        if size == 0 {
+            return;
+        }
+        let mut observed = self.observed_sizes.lock();
+        if observed.len() == OBSERVED_SIZE_WINDOW {
+            observed.pop_front();
+        }
+        observed.push_back(size.min(MAX_COVER_SIZE));
+    }
+
+    /// Draw a payload size from the configured distribution.
+    pub fn sample_size(&self) -> usize {
+        let mut rng = rand::thread_rng();
+        match &self.config.size_distribution {
+            SizeDistribution::Fixed => self.config.message_size,
+            SizeDistribution::Weighted(buckets) => {
+                let total: u64 = buckets.iter().map(|b| b.weight as u64).sum();
+                let mut pick = rng.gen_range(0..total);
+                for bucket in buckets {
+                    if pick < bucket.weight as u64 {
+                        return bucket.size;
+                    }
+                    pick -= bucket.weight as u64;
+                }
+                self.config.message_size
+            }
+            SizeDistribution::Observed => {
+                let observed = self.observed_sizes.lock();
+                if observed.is_empty() {
+                    self.config.message_size
+                } else {
+                    observed[rng.gen_range(0..observed.len())]
+                }
+            }
+        }
     }
 
     /// Generate a single cover traffic message
@@ -118,7 +250,7 @@ impl CoverTrafficGenerator {
         rng.fill_bytes(&mut recipient_hint);
 
         // Generate random encrypted payload
-        let mut encrypted_payload = vec![0u8; self.config.message_size];
+        let mut encrypted_payload = vec![0u8; self.sample_size()];
         rng.fill_bytes(&mut encrypted_payload);
 
         // Generate random ephemeral key
@@ -148,6 +280,8 @@ impl CoverTrafficGenerator {
 pub struct CoverTrafficScheduler {
     config: CoverConfig,
     last_generation_time: SystemTime,
+    /// When a real message last went out, if ever
+    last_real_activity: Option<SystemTime>,
 }
 
 impl CoverTrafficScheduler {
@@ -157,16 +291,40 @@ impl CoverTrafficScheduler {
         Ok(Self {
             config,
             last_generation_time: SystemTime::UNIX_EPOCH,
+            last_real_activity: None,
         })
     }
 
+    /// Record that a real message was just sent. Cover is held back until
+    /// `real_activity_cooldown_ms` passes with no further real traffic.
+    pub fn note_real_activity(&mut self) {
+        self.last_real_activity = Some(SystemTime::now());
+    }
+
+    /// Whether recent real traffic is currently suppressing cover
+    pub fn in_real_activity_cooldown(&self) -> bool {
+        let Some(last) = self.last_real_activity else {
+            return false;
+        };
+        let cooldown = Duration::from_millis(self.config.real_activity_cooldown_ms);
+        match last.elapsed() {
+            Ok(elapsed) => elapsed < cooldown,
+            // Clock went backwards; don't let it pause cover indefinitely.
+            Err(_) => false,
+        }
+    }
+
     /// Check if it's time to generate cover traffic
     ///
     /// Returns true if enough time has elapsed since the last generation
+    /// and no real traffic has gone out within the cooldown
     pub fn should_generate_cover_traffic(&self) -> bool {
         if !self.config.enabled || self.config.rate_per_minute == 0 {
             return false;
         }
+        if self.in_real_activity_cooldown() {
+            return false;
+        }
 
         match self.last_generation_time.elapsed() {
             Ok(elapsed) => {
@@ -196,8 +354,13 @@ impl CoverTrafficScheduler {
             return None;
         }
 
-        self.last_generation_time
-            .checked_add(Duration::from_millis(self.config.message_interval_ms()))
+        let scheduled = self
+            .last_generation_time
+            .checked_add(Duration::from_millis(self.config.message_interval_ms()))?;
+        let quiet_from = self.last_real_activity.and_then(|last| {
+            last.checked_add(Duration::from_millis(self.config.real_activity_cooldown_ms))
+        });
+        Some(quiet_from.map_or(scheduled, |quiet| quiet.max(scheduled)))
     }
 
     /// Get the configuration
@@ -234,6 +397,7 @@ mod tests {
             rate_per_minute: 5,
             message_size: 512,
             enabled: true,
+            ..Default::default()
         };
         assert!(config.validate().is_ok());
     }
@@ -244,6 +408,7 @@ mod tests {
             rate_per_minute: 0,
             message_size: 512,
             enabled: true,
+            ..Default::default()
         };
         assert!(config.validate().is_err());
     }
@@ -254,6 +419,7 @@ mod tests {
             rate_per_minute: 0,
             message_size: 512,
             enabled: false,
+            ..Default::default()
         };
         assert!(config.validate().is_ok());
     }
@@ -264,6 +430,7 @@ mod tests {
             rate_per_minute: 10,
             message_size: 0,
             enabled: true,
+            ..Default::default()
         };
         assert!(config.validate().is_err());
     }
@@ -274,6 +441,7 @@ mod tests {
             rate_per_minute: 10,
             message_size: 100000,
             enabled: true,
+            ..Default::default()
         };
         assert!(config.validate().is_err());
     }
@@ -284,6 +452,7 @@ mod tests {
             rate_per_minute: 60,
             message_size: 512,
             enabled: true,
+            ..Default::default()
         };
         assert_eq!(config.message_interval_ms(), 1000); // 1 per second
     }
@@ -294,6 +463,7 @@ mod tests {
             rate_per_minute: 1,
             message_size: 512,
             enabled: true,
+            ..Default::default()
         };
         assert_eq!(config.message_interval_ms(), 60000); // 1 per minute
     }
@@ -325,6 +495,7 @@ mod tests {
             rate_per_minute: 0,
             message_size: 512,
             enabled: true,
+            ..Default::default()
         };
         let result = CoverTrafficGenerator::new(config);
         assert!(result.is_err());
@@ -336,6 +507,7 @@ mod tests {
             rate_per_minute: 10,
             message_size: 512,
             enabled: false,
+            ..Default::default()
         };
         let generator = CoverTrafficGenerator::new(config).unwrap();
         let result = generator.generate_cover_message();
@@ -348,6 +520,7 @@ mod tests {
             rate_per_minute: 10,
             message_size: 1024,
             enabled: true,
+            ..Default::default()
         };
         let generator = CoverTrafficGenerator::new(config).unwrap();
         let message = generator.generate_cover_message().unwrap();
@@ -364,6 +537,7 @@ mod tests {
             rate_per_minute: 10,
             message_size: 512,
             enabled: true,
+            ..Default::default()
         };
         let generator = CoverTrafficGenerator::new(config).unwrap();
 
@@ -381,6 +555,7 @@ mod tests {
             rate_per_minute: 10,
             message_size: 256,
             enabled: true,
+            ..Default::default()
         };
         let generator = CoverTrafficGenerator::new(config).unwrap();
         let batch = generator.generate_batch(5).unwrap();
@@ -405,6 +580,7 @@ mod tests {
             rate_per_minute: 60,
             message_size: 512,
             enabled: true,
+            ..Default::default()
         };
         let scheduler = CoverTrafficScheduler::new(config).unwrap();
         // Initially, should always return true (very short elapsed time)
@@ -417,6 +593,7 @@ mod tests {
             rate_per_minute: 10,
             message_size: 512,
             enabled: false,
+            ..Default::default()
         };
         let scheduler = CoverTrafficScheduler::new(config).unwrap();
         assert!(!scheduler.should_generate_cover_traffic());
@@ -428,6 +605,7 @@ mod tests {
             rate_per_minute: 10,
             message_size: 512,
             enabled: true,
+            ..Default::default()
         };
         let mut scheduler = CoverTrafficScheduler::new(config).unwrap();
 
@@ -454,6 +632,7 @@ mod tests {
             rate_per_minute: 60, // 1 per second
             message_size: 512,
             enabled: true,
+            ..Default::default()
         };
         let scheduler = CoverTrafficScheduler::new(config).unwrap();
         let next_time = scheduler.next_generation_time();
@@ -466,12 +645,33 @@ mod tests {
             rate_per_minute: 10,
             message_size: 512,
             enabled: false,
+            ..Default::default()
         };
         let scheduler = CoverTrafficScheduler::new(config).unwrap();
         let next_time = scheduler.next_generation_time();
         assert!(next_time.is_none());
     }
 
+    #[test]
+    fn test_real_activity_suppresses_cover_until_cooldown_ends() {
+        let config = CoverConfig {
+            rate_per_minute: 60_000, // due every millisecond
+            real_activity_cooldown_ms: 100,
+            ..Default::default()
+        };
+        let mut scheduler = CoverTrafficScheduler::new(config).unwrap();
+        assert!(scheduler.should_generate_cover_traffic());
+
+        scheduler.note_real_activity();
+        assert!(scheduler.in_real_activity_cooldown());
+        assert!(!scheduler.should_generate_cover_traffic());
+        assert!(scheduler.next_generation_time().unwrap() > SystemTime::now());
+
+        std::thread::sleep(Duration::from_millis(150));
+        assert!(!scheduler.in_real_activity_cooldown());
+        assert!(scheduler.should_generate_cover_traffic());
+    }
+
     #[test]
     fn test_is_cover_traffic() {
         assert!(is_cover_traffic(true)); // Failed decryption suggests cover traffic
@@ -484,6 +684,7 @@ mod tests {
             rate_per_minute: 15,
             message_size: 2048,
             enabled: true,
+            ..Default::default()
         };
 
         let serialized = bincode::serialize(&config).unwrap();
@@ -511,6 +712,66 @@ mod tests {
         assert!(deserialized.is_cover);
     }
 
+    #[test]
+    fn test_weighted_sizes_follow_distribution() {
+        let config = CoverConfig {
+            size_distribution: SizeDistribution::chat(),
+            ..Default::default()
+        };
+        let generator = CoverTrafficGenerator::new(config).unwrap();
+        const SAMPLES: usize = 20_000;
+        let mut counts = std::collections::HashMap::new();
+        for _ in 0..SAMPLES {
+            *counts.entry(generator.sample_size()).or_insert(0usize) += 1;
+        }
+        let SizeDistribution::Weighted(buckets) = SizeDistribution::chat() else {
+            unreachable!()
+        };
+        let total: u32 = buckets.iter().map(|b| b.weight).sum();
+        assert_eq!(counts.len(), buckets.len());
+        for bucket in buckets {
+            let expected = bucket.weight as f64 / total as f64;
+            let actual = counts[&bucket.size] as f64 / SAMPLES as f64;
+            // ~5 standard deviations at this sample count
+            assert!(
+                (actual - expected).abs() < 0.02,
+                "size {}: expected {:.3}, got {:.3}",
+                bucket.size,
+                expected,
+                actual
+            );
+        }
+        let message = generator.generate_cover_message().unwrap();
+        assert!(counts.contains_key(&message.encrypted_payload.len()));
+    }
+
+    #[test]
+    fn test_observed_sizes_replay_real_traffic() {
+        let config = CoverConfig {
+            message_size: 300,
+            size_distribution: SizeDistribution::Observed,
+            ..Default::default()
+        };
+        let generator = CoverTrafficGenerator::new(config).unwrap();
+        assert_eq!(generator.sample_size(), 300);
+
+        for size in [180, 700, 2_000] {
+            generator.observe_real_size(size);
+        }
+        for _ in 0..100 {
+            assert!([180, 700, 2_000].contains(&generator.sample_size()));
+        }
+
+        let empty = CoverConfig {
+            size_distribution: SizeDistribution::Weighted(vec![SizeBucket {
+                size: 64,
+                weight: 0,
+            }]),
+            ..Default::default()
+        };
+        assert!(empty.validate().is_err());
+    }
+
     #[test]
     fn test_cover_traffic_various_sizes() {
         for size in [256, 512, 1024, 2048, 4096].iter() {
@@ -518,6 +779,7 @@ mod tests {
                 rate_per_minute: 10,
                 message_size: *size,
                 enabled: true,
+                ..Default::default()
             };
             let generator = CoverTrafficGenerator::new(config).unwrap();
             let msg = generator.generate_cover_message().unwrap();
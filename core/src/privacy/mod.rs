@@ -10,7 +10,9 @@ pub mod padding;
 pub mod timing;
 
 pub use circuit::{CircuitBuilder, CircuitConfig, CircuitId, CircuitPath};
-pub use cover::{CoverConfig, CoverTrafficGenerator, CoverTrafficScheduler};
+pub use cover::{
+    CoverConfig, CoverTrafficGenerator, CoverTrafficScheduler, SizeBucket, SizeDistribution,
+};
 pub use onion::{
     construct_onion, peel_layer, ClassicalOnionLayer, HopAddress, HybridOnionLayer,
     OnionConstructionResult, OnionEnvelope, MAX_ONION_HOPS,
@@ -12,11 +12,10 @@ pub mod delegate_prewarm;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod findmy;
 pub mod invite;
-#[cfg(not(target_arch = "wasm32"))]
 pub mod peer_exchange;
 pub mod protocol;
-#[cfg(not(target_arch = "wasm32"))]
 pub mod server;
+pub mod timestamp;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub use bootstrap::{BootstrapManager, BootstrapMethod, InvitePayload, SeedPeer};
@@ -30,8 +29,7 @@ pub use delegate_prewarm::{
 #[cfg(not(target_arch = "wasm32"))]
 pub use findmy::{FindMyBeaconManager, FindMyConfig, WakeUpPayload};
 pub use invite::{InviteChain, InviteSystem, InviteToken};
-#[cfg(not(target_arch = "wasm32"))]
 pub use peer_exchange::{PeerExchangeManager, RelayPeerInfo};
 pub use protocol::{RelayCapability, RelayMessage};
-#[cfg(not(target_arch = "wasm32"))]
 pub use server::{RelayServer, RelayServerConfig, RelayServerStats};
+pub use timestamp::{ClockProbe, SignedTimestamp};
@@ -130,6 +130,16 @@ pub enum RelayMessage {
         /// Reason for disconnect
         reason: String,
     },
+    /// Timestamp request: client asks a relay for its clock
+    TimestampRequest {
+        /// Echoed in the response so it can be matched to this request
+        nonce: u64,
+    },
+    /// Timestamp response: the relay's clock, signed with its identity key
+    TimestampResponse {
+        /// Signed reading (see `relay::timestamp`)
+        stamp: crate::relay::timestamp::SignedTimestamp,
+    },
 }
 
 /// Serialized peer info for inclusion in PeerExchange
@@ -200,6 +210,8 @@ impl RelayMessage {
             RelayMessage::Ping => "Ping",
             RelayMessage::Pong => "Pong",
             RelayMessage::Disconnect { .. } => "Disconnect",
+            RelayMessage::TimestampRequest { .. } => "TimestampRequest",
+            RelayMessage::TimestampResponse { .. } => "TimestampResponse",
         }
     }
 }
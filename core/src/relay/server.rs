@@ -2,11 +2,13 @@
 
 use super::protocol::{RelayCapability, RelayMessage, PROTOCOL_VERSION};
 use parking_lot::RwLock;
-use std::collections::{HashMap, VecDeque};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use thiserror::Error;
-use web_time::{SystemTime, UNIX_EPOCH};
+use web_time::{Instant, SystemTime, UNIX_EPOCH};
 
 /// Relay server configuration
 #[derive(Debug, Clone)]
@@ -19,6 +21,8 @@ pub struct RelayServerConfig {
     pub store_ttl_secs: u64,
     /// Bandwidth limit in bytes per second (0 = unlimited)
     pub bandwidth_limit: usize,
+    /// Total envelope bytes the offline mailbox may hold
+    pub mailbox_max_bytes: usize,
 }
 
 impl Default for RelayServerConfig {
@@ -28,6 +32,7 @@ impl Default for RelayServerConfig {
             max_stored_per_peer: 10000,
             store_ttl_secs: 24 * 3600, // 24 hours
             bandwidth_limit: 0,
+            mailbox_max_bytes: 16 * 1024 * 1024, // 16 MiB
         }
     }
 }
@@ -41,6 +46,41 @@ struct StoredEnvelope {
     stored_at: u64,
 }
 
+/// Store-and-forward cache for destinations that are offline when a message
+/// arrives for them, keyed by the destination's Ed25519 public key (hex).
+/// Entries are the opaque encrypted envelopes as received; the relay holds
+/// no keys that could open them.
+#[derive(Debug, Default)]
+struct RelayMailbox {
+    queues: HashMap<String, VecDeque<StoredEnvelope>>,
+    bytes: usize,
+}
+
+impl RelayMailbox {
+    fn prune_expired(&mut self, now: u64, ttl: u64) {
+        let mut freed = 0;
+        for queue in self.queues.values_mut() {
+            queue.retain(|env| {
+                let keep = now.saturating_sub(env.stored_at) < ttl;
+                if !keep {
+                    freed += env.data.len();
+                }
+                keep
+            });
+        }
+        self.queues.retain(|_, queue| !queue.is_empty());
+        self.bytes -= freed;
+    }
+
+    fn len(&self) -> usize {
+        self.queues.values().map(|q| q.len()).sum()
+    }
+}
+
+fn mailbox_key(public_key_hex: &str) -> String {
+    public_key_hex.trim().to_lowercase()
+}
+
 /// Connection state. `Handshaking` and `Disconnected` are reserved for a future
 /// explicit handshake/teardown state machine; only `Connecting`/`Connected` are
 /// constructed today.
@@ -54,7 +94,7 @@ enum ConnectionState {
 }
 
 /// Statistics about relay server operations
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct RelayServerStats {
     /// Number of active connections
     pub connections_active: usize,
@@ -64,6 +104,14 @@ pub struct RelayServerStats {
     pub envelopes_delivered: u64,
     /// Total bytes relayed
     pub bytes_relayed: u64,
+    /// Relay circuits currently open through this node
+    pub circuits_active: usize,
+    /// Distinct peers served since start or the last `reset_stats`
+    pub unique_peers_served: usize,
+    /// Seconds since the server was created
+    pub uptime_secs: u64,
+    /// Envelopes waiting in the offline mailbox
+    pub envelopes_cached: usize,
 }
 
 /// Relay server error types
@@ -98,7 +146,9 @@ struct RelayPeerSession {
     capabilities: RelayCapability,
 }
 
-/// The relay server
+/// The relay server. Clones share the same state, so a handle can be kept
+/// for reading stats while the relay itself runs in a spawned task.
+#[derive(Clone)]
 pub struct RelayServer {
     /// Server configuration
     config: RelayServerConfig,
@@ -108,6 +158,13 @@ pub struct RelayServer {
     storage: Arc<RwLock<HashMap<String, VecDeque<StoredEnvelope>>>>,
     /// Server statistics
     stats: Arc<RwLock<RelayServerStats>>,
+    /// Peers served since start or the last `reset_stats`
+    served_peers: Arc<RwLock<HashSet<String>>>,
+    /// When the server was created, for `uptime_secs`
+    started_at: Instant,
+    /// Offline mailbox; off unless `MeshSettings::relay_mailbox_enabled`
+    mailbox_enabled: Arc<AtomicBool>,
+    mailbox: Arc<RwLock<RelayMailbox>>,
 }
 
 impl RelayServer {
@@ -122,12 +179,11 @@ impl RelayServer {
             config,
             peers: Arc::new(RwLock::new(HashMap::new())),
             storage: Arc::new(RwLock::new(HashMap::new())),
-            stats: Arc::new(RwLock::new(RelayServerStats {
-                connections_active: 0,
-                envelopes_stored: 0,
-                envelopes_delivered: 0,
-                bytes_relayed: 0,
-            })),
+            stats: Arc::new(RwLock::new(RelayServerStats::default())),
+            served_peers: Arc::new(RwLock::new(HashSet::new())),
+            started_at: Instant::now(),
+            mailbox_enabled: Arc::new(AtomicBool::new(false)),
+            mailbox: Arc::new(RwLock::new(RelayMailbox::default())),
         }
     }
 
@@ -145,6 +201,7 @@ impl RelayServer {
             return Err(RelayServerError::ConnectionLimitExceeded);
         }
 
+        self.record_peer_served(&peer_id);
         peers.insert(
             peer_id.clone(),
             RelayPeerSession {
@@ -266,14 +323,135 @@ impl RelayServer {
         // Clean up empty queues
         storage.retain(|_, queue| !queue.is_empty());
 
+        let mut mailbox = self.mailbox.write();
+        mailbox.prune_expired(now, ttl);
+
         // Update stats
         let mut stats = self.stats.write();
         stats.envelopes_stored = storage.values().map(|q| q.len()).sum();
+        stats.envelopes_cached = mailbox.len();
+    }
+
+    /// Turn the offline mailbox on or off. Turning it off drops anything
+    /// cached.
+    pub fn set_mailbox_enabled(&self, enabled: bool) {
+        self.mailbox_enabled.store(enabled, Ordering::SeqCst);
+        if !enabled {
+            *self.mailbox.write() = RelayMailbox::default();
+            self.stats.write().envelopes_cached = 0;
+        }
+    }
+
+    pub fn mailbox_enabled(&self) -> bool {
+        self.mailbox_enabled.load(Ordering::SeqCst)
+    }
+
+    /// Apply the relay-side parts of the mesh settings.
+    pub fn apply_mesh_settings(&self, settings: &crate::settings::MeshSettings) {
+        self.set_mailbox_enabled(settings.relay_mailbox_enabled);
+    }
+
+    /// Cache an envelope for a destination that is offline. Returns false
+    /// (nothing stored) when the mailbox is off, the destination's queue is
+    /// full or the mailbox is at its byte limit.
+    pub fn cache_for_offline_peer(&self, destination_public_key: &str, envelope: Vec<u8>) -> bool {
+        if !self.mailbox_enabled() {
+            return false;
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let mut mailbox = self.mailbox.write();
+        mailbox.prune_expired(now, self.config.store_ttl_secs);
+        if mailbox.bytes + envelope.len() > self.config.mailbox_max_bytes {
+            return false;
+        }
+        let queue = mailbox
+            .queues
+            .entry(mailbox_key(destination_public_key))
+            .or_default();
+        if queue.len() >= self.config.max_stored_per_peer {
+            return false;
+        }
+        let size = envelope.len();
+        queue.push_back(StoredEnvelope {
+            data: envelope,
+            stored_at: now,
+        });
+        mailbox.bytes += size;
+
+        self.stats.write().envelopes_cached = mailbox.len();
+        true
+    }
+
+    /// Remove and return the unexpired envelopes cached for a destination,
+    /// oldest first, for delivery now that it is connected.
+    pub fn take_cached_for(&self, destination_public_key: &str) -> Vec<Vec<u8>> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let mut mailbox = self.mailbox.write();
+        mailbox.prune_expired(now, self.config.store_ttl_secs);
+        let Some(queue) = mailbox.queues.remove(&mailbox_key(destination_public_key)) else {
+            return Vec::new();
+        };
+        let envelopes: Vec<Vec<u8>> = queue.into_iter().map(|env| env.data).collect();
+        let bytes: usize = envelopes.iter().map(|e| e.len()).sum();
+        mailbox.bytes -= bytes;
+
+        let mut stats = self.stats.write();
+        stats.envelopes_cached = mailbox.len();
+        stats.envelopes_delivered += envelopes.len() as u64;
+        stats.bytes_relayed += bytes as u64;
+        envelopes
     }
 
     /// Get current server statistics
     pub fn get_stats(&self) -> RelayServerStats {
-        self.stats.read().clone()
+        self.stats()
+    }
+
+    /// Snapshot of the current statistics
+    pub fn stats(&self) -> RelayServerStats {
+        let mut stats = self.stats.read().clone();
+        stats.unique_peers_served = self.served_peers.read().len();
+        stats.uptime_secs = self.started_at.elapsed().as_secs();
+        stats
+    }
+
+    /// Zero the cumulative counters (bytes relayed, envelopes delivered,
+    /// unique peers served). Gauges describing current state — active
+    /// connections and circuits, stored envelopes — and uptime are kept.
+    pub fn reset_stats(&self) {
+        let mut stats = self.stats.write();
+        stats.envelopes_delivered = 0;
+        stats.bytes_relayed = 0;
+        self.served_peers.write().clear();
+    }
+
+    /// Count `peer_id` towards `unique_peers_served`
+    pub fn record_peer_served(&self, peer_id: &str) {
+        let mut served = self.served_peers.write();
+        if !served.contains(peer_id) {
+            served.insert(peer_id.to_string());
+        }
+    }
+
+    /// A relay circuit from `src_peer_id` to `dst_peer_id` was opened
+    pub fn circuit_opened(&self, src_peer_id: &str, dst_peer_id: &str) {
+        self.record_peer_served(src_peer_id);
+        self.record_peer_served(dst_peer_id);
+        self.stats.write().circuits_active += 1;
+    }
+
+    /// A relay circuit was closed
+    pub fn circuit_closed(&self) {
+        let mut stats = self.stats.write();
+        stats.circuits_active = stats.circuits_active.saturating_sub(1);
     }
 
     /// Check if a peer is connected
@@ -531,6 +709,105 @@ mod tests {
         assert_eq!(stats.bytes_relayed, 1500);
     }
 
+    #[test]
+    fn test_mailbox_delivers_to_peer_that_was_offline() {
+        let alice = crate::IronCore::new();
+        alice.grant_consent();
+        alice.initialize_identity().unwrap();
+        let bob = crate::IronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let bob_pk = bob.get_identity_info().public_key_hex.unwrap();
+
+        let relay = test_server();
+        let prepared = alice
+            .prepare_message(
+                bob_pk.clone(),
+                "held for bob".to_string(),
+                crate::MessageType::Text,
+                None,
+            )
+            .unwrap();
+
+        // Off by default: nothing is cached.
+        assert!(!relay.cache_for_offline_peer(&bob_pk, prepared.envelope_data.clone()));
+
+        relay.set_mailbox_enabled(true);
+        assert!(relay.cache_for_offline_peer(&bob_pk, prepared.envelope_data.clone()));
+        assert_eq!(relay.stats().envelopes_cached, 1);
+
+        // Bob connects; the relay hands over exactly the opaque bytes it got.
+        relay
+            .register_peer(
+                "bob".to_string(),
+                "127.0.0.1:9000".parse().unwrap(),
+                RelayCapability::mobile(),
+            )
+            .unwrap();
+        let delivered = relay.take_cached_for(&bob_pk.to_uppercase());
+        assert_eq!(delivered, vec![prepared.envelope_data]);
+        assert!(relay.take_cached_for(&bob_pk).is_empty());
+        assert_eq!(relay.stats().envelopes_cached, 0);
+
+        let message = bob.receive_message(delivered[0].clone()).unwrap();
+        assert_eq!(message.text_content().as_deref(), Some("held for bob"));
+    }
+
+    #[test]
+    fn test_mailbox_respects_byte_limit() {
+        let relay = RelayServer::with_config(RelayServerConfig {
+            mailbox_max_bytes: 100,
+            ..Default::default()
+        });
+        relay.set_mailbox_enabled(true);
+        assert!(relay.cache_for_offline_peer("aa", vec![0; 60]));
+        assert!(!relay.cache_for_offline_peer("bb", vec![0; 60]));
+        assert_eq!(relay.take_cached_for("AA").len(), 1);
+        assert!(relay.cache_for_offline_peer("bb", vec![0; 60]));
+
+        relay.set_mailbox_enabled(false);
+        assert!(relay.take_cached_for("bb").is_empty());
+    }
+
+    #[test]
+    fn test_stats_shared_across_threads_and_reset() {
+        let server = test_server();
+        let relay = server.clone();
+        std::thread::spawn(move || {
+            relay
+                .register_peer(
+                    "peer1".to_string(),
+                    "127.0.0.1:8080".parse().unwrap(),
+                    RelayCapability::full_relay(),
+                )
+                .unwrap();
+            relay.store_for_peer("peer2", vec![vec![0; 64]]).unwrap();
+            relay.add_bytes_relayed(64);
+            relay.circuit_opened("peer1", "peer3");
+            relay.circuit_opened("peer3", "peer1");
+            relay.circuit_closed();
+            relay.get_stored_for("peer2", 0).unwrap();
+        })
+        .join()
+        .unwrap();
+
+        let stats = server.stats();
+        assert_eq!(stats.bytes_relayed, 64);
+        assert_eq!(stats.circuits_active, 1);
+        assert_eq!(stats.unique_peers_served, 2);
+        assert_eq!(stats.envelopes_delivered, 1);
+        assert_eq!(stats.connections_active, 1);
+
+        server.reset_stats();
+        let stats = server.stats();
+        assert_eq!(stats.bytes_relayed, 0);
+        assert_eq!(stats.envelopes_delivered, 0);
+        assert_eq!(stats.unique_peers_served, 0);
+        // Gauges describe live state and survive a reset.
+        assert_eq!(stats.circuits_active, 1);
+        assert_eq!(stats.connections_active, 1);
+    }
+
     #[test]
     fn test_retrieve_with_timestamp_filter() {
         let server = test_server();
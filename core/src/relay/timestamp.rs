@@ -0,0 +1,152 @@
+//! Verified timestamps for clock correction
+//!
+//! A device whose clock has drifted (one that spent weeks offline, or lost
+//! its RTC) stamps messages with the wrong time and ages TTLs wrongly. With
+//! clock sync on, the swarm asks each trusted bootstrap relay it connects to
+//! for the time, signed with the relay's identity key, and turns the answer
+//! into a correction for `IronCore::set_clock_offset`.
+
+use libp2p::identity::{ed25519, Keypair, PublicKey};
+use libp2p::PeerId;
+use serde::{Deserialize, Serialize};
+
+/// Domain separator so a timestamp signature cannot be replayed as a
+/// signature over any other message made with the same identity key.
+const TIMESTAMP_SIGNING_DOMAIN: &[u8] = b"scmessenger-timestamp-v1";
+
+/// Answers slower than this are discarded: the relay read its clock
+/// somewhere inside the round trip, so a long one makes the reading useless.
+pub const MAX_PROBE_RTT_MS: u64 = 10_000;
+
+/// A relay's clock reading, bound to the requester's nonce.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedTimestamp {
+    pub nonce: u64,
+    /// Relay time when it answered (milliseconds since the Unix epoch).
+    pub timestamp_ms: u64,
+    /// Ed25519 public key of the relay.
+    pub public_key: Vec<u8>,
+    /// Ed25519 signature over the domain, nonce and timestamp.
+    pub signature: Vec<u8>,
+}
+
+fn signing_bytes(nonce: u64, timestamp_ms: u64) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(TIMESTAMP_SIGNING_DOMAIN.len() + 16);
+    bytes.extend_from_slice(TIMESTAMP_SIGNING_DOMAIN);
+    bytes.extend_from_slice(&nonce.to_be_bytes());
+    bytes.extend_from_slice(&timestamp_ms.to_be_bytes());
+    bytes
+}
+
+impl SignedTimestamp {
+    pub fn sign(keypair: &Keypair, nonce: u64, timestamp_ms: u64) -> anyhow::Result<Self> {
+        let public = keypair
+            .clone()
+            .try_into_ed25519()
+            .map_err(|_| anyhow::anyhow!("Timestamp signing requires an Ed25519 keypair"))?
+            .public();
+        let signature = keypair.sign(&signing_bytes(nonce, timestamp_ms))?;
+        Ok(Self {
+            nonce,
+            timestamp_ms,
+            public_key: public.to_bytes().to_vec(),
+            signature,
+        })
+    }
+
+    /// Whether `relay` signed this answer to `nonce`.
+    pub fn verify(&self, relay: &PeerId, nonce: u64) -> bool {
+        if self.nonce != nonce {
+            return false;
+        }
+        let Ok(public) = ed25519::PublicKey::try_from_bytes(&self.public_key) else {
+            return false;
+        };
+        let public = PublicKey::from(public);
+        public.to_peer_id() == *relay
+            && public.verify(
+                &signing_bytes(self.nonce, self.timestamp_ms),
+                &self.signature,
+            )
+    }
+}
+
+/// A timestamp request in flight to one relay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockProbe {
+    pub nonce: u64,
+    /// Local device time the request was sent (milliseconds).
+    pub sent_ms: u64,
+}
+
+impl ClockProbe {
+    pub fn new(sent_ms: u64) -> Self {
+        Self {
+            nonce: rand::random(),
+            sent_ms,
+        }
+    }
+
+    /// The correction to add to the local clock, in seconds (positive when
+    /// it is behind), taking the relay's reading as the midpoint of the
+    /// round trip. `None` if `stamp` is not `relay`'s signed answer to this
+    /// probe, or the round trip was too long to trust.
+    pub fn offset_secs(
+        &self,
+        relay: &PeerId,
+        stamp: &SignedTimestamp,
+        received_ms: u64,
+    ) -> Option<i64> {
+        let rtt = received_ms.checked_sub(self.sent_ms)?;
+        if rtt > MAX_PROBE_RTT_MS || !stamp.verify(relay, self.nonce) {
+            return None;
+        }
+        let midpoint = self.sent_ms + rtt / 2;
+        let offset_ms = stamp.timestamp_ms as i64 - midpoint as i64;
+        Some((offset_ms + 500).div_euclid(1000))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_from_signed_timestamp() {
+        let relay = Keypair::generate_ed25519();
+        let relay_id = relay.public().to_peer_id();
+        // The device is two hours behind the relay.
+        let device_ms = 1_700_000_000_000;
+        let probe = ClockProbe::new(device_ms);
+        let stamp = SignedTimestamp::sign(&relay, probe.nonce, device_ms + 7_200_000 + 40).unwrap();
+
+        assert_eq!(
+            probe.offset_secs(&relay_id, &stamp, device_ms + 80),
+            Some(7_200)
+        );
+        // Too slow to trust.
+        assert_eq!(
+            probe.offset_secs(&relay_id, &stamp, device_ms + MAX_PROBE_RTT_MS + 1),
+            None
+        );
+    }
+
+    #[test]
+    fn test_forged_or_mismatched_timestamps_rejected() {
+        let relay = Keypair::generate_ed25519();
+        let relay_id = relay.public().to_peer_id();
+        let probe = ClockProbe::new(1_000);
+        let stamp = SignedTimestamp::sign(&relay, probe.nonce, 5_000).unwrap();
+
+        // Signed by someone other than the relay we asked.
+        let other = Keypair::generate_ed25519().public().to_peer_id();
+        assert_eq!(probe.offset_secs(&other, &stamp, 1_100), None);
+        // An answer to a different request.
+        assert!(!stamp.verify(&relay_id, probe.nonce.wrapping_add(1)));
+        // Tampered reading.
+        let mut tampered = stamp.clone();
+        tampered.timestamp_ms += 60_000;
+        assert_eq!(probe.offset_secs(&relay_id, &tampered, 1_100), None);
+        assert!(probe.offset_secs(&relay_id, &stamp, 1_100).is_some());
+    }
+}
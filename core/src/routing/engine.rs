@@ -143,7 +143,7 @@ impl RoutingEngine {
         let local_peers = self.local.peers_for_hint(recipient_hint);
         if !local_peers.is_empty() {
             // Found direct peer(s)
-            let best_peer = local_peers[0]; // Already sorted by reliability in LocalCell
+            let best_peer = local_peers[0]; // Sorted by reliability, then RTT, in LocalCell
             let transport = best_peer
                 .transports
                 .first()
@@ -490,6 +490,32 @@ mod tests {
         assert_eq!(decision.confidence, 0.0);
     }
 
+    #[test]
+    fn test_equal_hop_tie_break_prefers_lower_rtt() {
+        let mut engine = RoutingEngine::new(make_peer_id(1), make_hint(1));
+        let target_hint = make_hint(42);
+        let slow = make_peer_id(2);
+        let fast = make_peer_id(3);
+        let unmeasured = make_peer_id(4);
+
+        let cell = engine.local_cell_mut();
+        for peer in [slow, fast, unmeasured] {
+            cell.peer_seen(peer, TransportType::TCP);
+            cell.update_peer_hints(&peer, vec![target_hint]);
+        }
+        cell.record_rtt(&slow, 180);
+        cell.record_rtt(&fast, 25);
+
+        let decision = engine.route_message(&target_hint, &make_message_id(1), 50, 1000);
+        assert_eq!(decision.decided_by, RoutingLayer::Local);
+        assert!(matches!(decision.primary, NextHop::Direct { peer_id, .. } if peer_id == fast));
+
+        // Reliability still outranks latency.
+        engine.local_cell_mut().update_reliability(&slow, true);
+        let decision = engine.route_message(&target_hint, &make_message_id(2), 50, 1000);
+        assert!(matches!(decision.primary, NextHop::Direct { peer_id, .. } if peer_id == slow));
+    }
+
     #[test]
     fn test_routing_summary() {
         let local_id = make_peer_id(1);
@@ -60,6 +60,8 @@ pub struct PeerInfo {
     pub sync_count: u32,
     /// Average sync time in milliseconds
     pub avg_sync_ms: u64,
+    /// Rolling-average ping round trip in milliseconds, if measured
+    pub rtt_ms: Option<u64>,
 }
 
 /// Summary of local cell state (for gossip exchange)
@@ -155,6 +157,7 @@ impl LocalCell {
                     is_gateway: false,
                     sync_count: 0,
                     avg_sync_ms: 0,
+                    rtt_ms: None,
                 },
             );
         }
@@ -203,9 +206,18 @@ impl LocalCell {
         }
     }
 
-    /// Find peers that might be able to reach a recipient (by hint)
+    /// Update the rolling-average ping RTT for a peer
+    pub fn record_rtt(&mut self, peer_id: &PeerId, rtt_ms: u64) {
+        if let Some(peer) = self.peers.get_mut(peer_id) {
+            peer.rtt_ms = Some(rtt_ms);
+        }
+    }
+
+    /// Find peers that might be able to reach a recipient (by hint), best
+    /// first: highest reliability, then lowest RTT (unmeasured peers last)
     pub fn peers_for_hint(&self, hint: &[u8; 4]) -> Vec<&PeerInfo> {
-        self.peers
+        let mut peers: Vec<&PeerInfo> = self
+            .peers
             .values()
             .filter(|p| {
                 if let PeerStatus::Active { .. } = p.status {
@@ -214,7 +226,18 @@ impl LocalCell {
                     false
                 }
             })
-            .collect()
+            .collect();
+        peers.sort_by(|a, b| {
+            b.reliability_score
+                .partial_cmp(&a.reliability_score)
+                .expect("f64 reliability scores should always be comparable")
+                .then_with(|| {
+                    a.rtt_ms
+                        .unwrap_or(u64::MAX)
+                        .cmp(&b.rtt_ms.unwrap_or(u64::MAX))
+                })
+        });
+        peers
     }
 
     /// Get all active peers sorted by reliability (highest first)
@@ -1,12 +1,88 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DiscoveryMode {
+    #[default]
     Normal,
     Cautious,
     Paranoid,
 }
 
+impl DiscoveryMode {
+    /// Whether the node dials peers it only heard about second-hand (ledger
+    /// exchange, relay peer announcements). Paranoid nodes neither dial them
+    /// nor take part in ledger exchange; they connect only to configured
+    /// bootstrap nodes and to contacts they message.
+    pub fn dials_learned_peers(self) -> bool {
+        self != DiscoveryMode::Paranoid
+    }
+}
+
+/// Gossipsub mesh tuning, applied when the swarm's behaviour is built.
+///
+/// - `Default`: small mesh with a fast heartbeat, for desktop nodes.
+/// - `Mobile`: fewer mesh peers and a slow heartbeat, so the radio wakes less
+///   often. Messages propagate more slowly and the node depends more on
+///   gossip (IHAVE/IWANT) than on eager push.
+/// - `Relay`: a wide mesh and longer message history, so always-on nodes keep
+///   the topic connected when peers churn, at the cost of more duplicate
+///   traffic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GossipProfile {
+    #[default]
+    Default,
+    Mobile,
+    Relay,
+}
+
+/// Kademlia DHT role, applied when the swarm's behaviour is built.
+///
+/// - `Server`: answers DHT queries and advertises the kad protocol, so other
+///   nodes add it to their routing tables. Only useful when the node is
+///   publicly reachable; an unreachable server just produces failed queries
+///   for everyone who routes through it.
+/// - `Client`: issues queries (bootstrap, provider and peer lookups still
+///   work) but never answers them and is not added to other nodes' routing
+///   tables. Saves power and bandwidth on mobile, at the cost that peers can
+///   only find this node through relays or records it publishes itself.
+///
+/// Defaults to `Client` on Android and iOS builds and `Server` elsewhere;
+/// headless relays always run as servers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KadMode {
+    Client,
+    Server,
+}
+
+impl Default for KadMode {
+    fn default() -> Self {
+        if cfg!(any(target_os = "android", target_os = "ios")) {
+            KadMode::Client
+        } else {
+            KadMode::Server
+        }
+    }
+}
+
+/// Which of a peer's addresses this node feeds into Kademlia, shares with
+/// other peers and dials from discovery.
+///
+/// LAN means RFC1918, CGNAT (100.64.0.0/10) and IPv6 unique-local addresses;
+/// everything else routable is WAN. Loopback, unspecified and link-local
+/// addresses are never advertised. Relay circuit addresses count as WAN.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AdvertiseScope {
+    /// Private and public addresses (mobile and desktop nodes).
+    #[default]
+    LanAndWan,
+    /// Public addresses only, so a public relay does not spread unroutable
+    /// internal addresses across the DHT.
+    WanOnly,
+    /// Private addresses only, for an isolated local mesh.
+    LanOnly,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct MeshSettings {
@@ -30,6 +106,44 @@ pub struct MeshSettings {
     pub sound_enabled: bool,
     pub badge_enabled: bool,
     pub require_pq: bool,
+    /// Sustained inbound messages per second accepted from one peer; excess
+    /// requests are rejected in the swarm. 0 disables the limit.
+    pub inbound_messages_per_sec: u32,
+    /// Messages a peer may send back-to-back before the rate applies.
+    pub inbound_burst: u32,
+    /// Gossipsub mesh tuning; takes effect the next time the swarm starts.
+    pub gossip_profile: GossipProfile,
+    /// Kademlia DHT role; takes effect the next time the swarm starts.
+    pub kad_mode: KadMode,
+    /// Which addresses are advertised and accepted into the DHT; takes
+    /// effect the next time the swarm starts.
+    pub advertise_scope: AdvertiseScope,
+    /// When relaying, cache envelopes for offline destinations (bounded by
+    /// size and TTL) that the durable custody store could not take, and
+    /// deliver them when the destination connects. Uses memory on the relay;
+    /// envelopes stay encrypted.
+    pub relay_mailbox_enabled: bool,
+    /// When relaying, refuse envelopes that arrive without their sender's
+    /// signature. A signature that does not verify is refused either way.
+    pub require_signed_relay: bool,
+    /// Most live contacts kept in the contacts database; adding one past the
+    /// cap evicts the least recently seen unverified contact. 0 = no cap.
+    pub max_contacts: u32,
+    /// Largest gossipsub payload published or accepted on a topic, in bytes.
+    pub max_topic_payload_bytes: u32,
+    /// Per-topic overrides of `max_topic_payload_bytes`.
+    pub topic_payload_limits: HashMap<String, u32>,
+    /// Correct the local clock from signed timestamps served by trusted
+    /// bootstrap relays (see `SwarmHandle::set_clock_sync`).
+    pub clock_sync_enabled: bool,
+    /// Largest message kept on BLE before escalating to WiFi or Internet.
+    pub escalation_ble_max_bytes: u32,
+    /// Largest message kept on WiFi Aware / WiFi Direct before escalating
+    /// to Internet.
+    pub escalation_wifi_max_bytes: u32,
+    /// Expected delivery time, in milliseconds, above which a link counts as
+    /// too slow and the send escalates.
+    pub escalation_max_delivery_ms: u32,
 }
 
 impl Default for MeshSettings {
@@ -56,6 +170,257 @@ impl Default for MeshSettings {
             sound_enabled: crate::notification_defaults::sound_enabled(),
             badge_enabled: crate::notification_defaults::badge_enabled(),
             require_pq: false,
+            inbound_messages_per_sec:
+                crate::transport::rate_limit::DEFAULT_INBOUND_MESSAGES_PER_SEC,
+            inbound_burst: crate::transport::rate_limit::DEFAULT_INBOUND_BURST,
+            gossip_profile: GossipProfile::Default,
+            kad_mode: KadMode::default(),
+            advertise_scope: AdvertiseScope::default(),
+            relay_mailbox_enabled: false,
+            require_signed_relay: false,
+            max_contacts: 0,
+            max_topic_payload_bytes: crate::transport::topic_limits::DEFAULT_MAX_TOPIC_PAYLOAD
+                as u32,
+            topic_payload_limits: HashMap::new(),
+            clock_sync_enabled: false,
+            escalation_ble_max_bytes: 64 * 1024,
+            escalation_wifi_max_bytes: 8 * 1024 * 1024,
+            escalation_max_delivery_ms: 2_000,
+        }
+    }
+}
+
+/// Layout version written to `mesh_settings.json` as `settings_version`.
+/// Files without it predate versioning (version 1). Adding a field does not
+/// need a bump — missing fields take their defaults — but renaming one or
+/// changing its meaning does, together with a step in `migrate_settings_json`.
+pub const MESH_SETTINGS_VERSION: u32 = 2;
+
+const SETTINGS_VERSION_KEY: &str = "settings_version";
+
+/// Read persisted settings of any version. Fields that are missing, or whose
+/// stored value no longer parses, take their defaults; every other stored
+/// value is kept. The flag is true when the file should be re-saved (older
+/// version or fields filled in).
+pub fn migrate_settings_json(json: &str) -> Result<(MeshSettings, bool), serde_json::Error> {
+    use serde_json::Value;
+
+    let stored: serde_json::Map<String, Value> = serde_json::from_str(json)?;
+    let version = stored
+        .get(SETTINGS_VERSION_KEY)
+        .and_then(Value::as_u64)
+        .unwrap_or(1);
+
+    let mut merged = serde_json::to_value(MeshSettings::default())?
+        .as_object()
+        .cloned()
+        .unwrap_or_default();
+    let mut filled = false;
+    for (key, default) in merged.clone() {
+        let Some(value) = stored.get(&key) else {
+            filled = true;
+            continue;
+        };
+        // Take the stored value only if the settings still parse with it, so
+        // one stale field cannot reset all the others.
+        merged.insert(key.clone(), value.clone());
+        if serde_json::from_value::<MeshSettings>(Value::Object(merged.clone())).is_err() {
+            merged.insert(key, default);
+            filled = true;
+        }
+    }
+
+    let settings = serde_json::from_value(Value::Object(merged))?;
+    Ok((settings, filled || version < MESH_SETTINGS_VERSION as u64))
+}
+
+/// Settings as written to `mesh_settings.json`, tagged with the version.
+pub fn settings_to_versioned_json(settings: &MeshSettings) -> Result<String, serde_json::Error> {
+    let mut value = serde_json::to_value(settings)?;
+    if let serde_json::Value::Object(map) = &mut value {
+        map.insert(
+            SETTINGS_VERSION_KEY.to_string(),
+            MESH_SETTINGS_VERSION.into(),
+        );
+    }
+    serde_json::to_string_pretty(&value)
+}
+
+/// Highest battery floor accepted; above this the node would spend most of
+/// its time refusing to relay.
+pub const MAX_BATTERY_FLOOR: u8 = 50;
+
+/// A problem with a `MeshSettings` combination, phrased for display.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, Serialize, Deserialize)]
+pub enum SettingsError {
+    #[error("All transports are disabled; enable at least one of BLE, WiFi Aware, WiFi Direct or Internet")]
+    NoTransportEnabled,
+    #[error(
+        "Relay is enabled but only WiFi Direct is on; relaying needs BLE, WiFi Aware or Internet"
+    )]
+    RelayWithoutMeshTransport,
+    #[error("Relay is enabled with a relay budget of 0; raise the budget or disable relay")]
+    ZeroRelayBudget,
+    #[error("Battery floor {floor}% is above the {max}% maximum")]
+    BatteryFloorTooHigh { floor: u8, max: u8 },
+    #[error("Inbound rate limit is enabled with a burst of 0; no message would ever be accepted")]
+    ZeroInboundBurst,
+    #[error("Topic payload limit is 0 bytes; no gossip would ever be accepted")]
+    ZeroTopicPayloadLimit,
+    #[error("Payload limit for topic {topic} is 0 bytes; nothing could be sent on it")]
+    ZeroTopicOverride { topic: String },
+}
+
+impl MeshSettings {
+    /// Check for contradictory settings. Returns every problem found, not
+    /// just the first, so a UI can show them all at once.
+    pub fn validate(&self) -> Result<(), Vec<SettingsError>> {
+        let mut errors = Vec::new();
+
+        let any_transport = self.ble_enabled
+            || self.wifi_aware_enabled
+            || self.wifi_direct_enabled
+            || self.internet_enabled;
+        if !any_transport {
+            errors.push(SettingsError::NoTransportEnabled);
+        } else if self.relay_enabled
+            && !self.internet_enabled
+            && !self.ble_enabled
+            && !self.wifi_aware_enabled
+        {
+            errors.push(SettingsError::RelayWithoutMeshTransport);
+        }
+
+        if self.relay_enabled && self.max_relay_budget == 0 {
+            errors.push(SettingsError::ZeroRelayBudget);
+        }
+
+        if self.battery_floor > MAX_BATTERY_FLOOR {
+            errors.push(SettingsError::BatteryFloorTooHigh {
+                floor: self.battery_floor,
+                max: MAX_BATTERY_FLOOR,
+            });
+        }
+
+        if self.inbound_messages_per_sec > 0 && self.inbound_burst == 0 {
+            errors.push(SettingsError::ZeroInboundBurst);
         }
+
+        if self.max_topic_payload_bytes == 0 {
+            errors.push(SettingsError::ZeroTopicPayloadLimit);
+        }
+        let mut zero_topics: Vec<&String> = self
+            .topic_payload_limits
+            .iter()
+            .filter(|(_, max)| **max == 0)
+            .map(|(topic, _)| topic)
+            .collect();
+        zero_topics.sort();
+        for topic in zero_topics {
+            errors.push(SettingsError::ZeroTopicOverride {
+                topic: topic.clone(),
+            });
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_settings_are_valid() {
+        assert_eq!(MeshSettings::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_reports_every_problem() {
+        let settings = MeshSettings {
+            relay_enabled: true,
+            max_relay_budget: 0,
+            battery_floor: 90,
+            ble_enabled: false,
+            wifi_aware_enabled: false,
+            wifi_direct_enabled: false,
+            internet_enabled: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.validate(),
+            Err(vec![
+                SettingsError::NoTransportEnabled,
+                SettingsError::ZeroRelayBudget,
+                SettingsError::BatteryFloorTooHigh { floor: 90, max: 50 },
+            ])
+        );
+    }
+
+    #[test]
+    fn zero_topic_payload_limits_are_flagged() {
+        let mut settings = MeshSettings {
+            max_topic_payload_bytes: 0,
+            ..Default::default()
+        };
+        settings
+            .topic_payload_limits
+            .insert("sc-lobby".to_string(), 0);
+        settings
+            .topic_payload_limits
+            .insert("sc-mesh".to_string(), 4096);
+        assert_eq!(
+            settings.validate(),
+            Err(vec![
+                SettingsError::ZeroTopicPayloadLimit,
+                SettingsError::ZeroTopicOverride {
+                    topic: "sc-lobby".to_string()
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn relay_over_wifi_direct_only_is_flagged() {
+        let settings = MeshSettings {
+            relay_enabled: true,
+            ble_enabled: false,
+            wifi_aware_enabled: false,
+            wifi_direct_enabled: true,
+            internet_enabled: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.validate(),
+            Err(vec![SettingsError::RelayWithoutMeshTransport])
+        );
+
+        let no_relay = MeshSettings {
+            relay_enabled: false,
+            ..settings
+        };
+        assert_eq!(no_relay.validate(), Ok(()));
+    }
+
+    #[test]
+    fn zero_inbound_burst_is_flagged_unless_limit_disabled() {
+        let settings = MeshSettings {
+            inbound_burst: 0,
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.validate(),
+            Err(vec![SettingsError::ZeroInboundBurst])
+        );
+
+        let disabled = MeshSettings {
+            inbound_messages_per_sec: 0,
+            ..settings
+        };
+        assert_eq!(disabled.validate(), Ok(()));
     }
 }
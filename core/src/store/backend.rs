@@ -14,6 +14,28 @@ pub trait StorageBackend: Send + Sync {
     fn count_prefix(&self, prefix: &[u8]) -> Result<usize, String>;
     fn flush(&self) -> Result<(), String>;
     fn approximate_size(&self) -> Result<u64, String>;
+    /// Up to `limit` entries under `prefix` whose keys sort before `before`,
+    /// greatest key first, for paging backwards through an ordered index.
+    /// The default sorts a full prefix scan; ordered backends read the range.
+    fn scan_prefix_before(
+        &self,
+        prefix: &[u8],
+        before: &[u8],
+        limit: usize,
+    ) -> Result<ScanResult, String> {
+        let mut entries = self.scan_prefix(prefix)?;
+        entries.retain(|(key, _)| key.as_slice() < before);
+        entries.sort_unstable_by(|a, b| b.0.cmp(&a.0));
+        entries.truncate(limit);
+        Ok(entries)
+    }
+    /// Let the backend reclaim space left by deleted and overwritten records.
+    /// Must be safe while other handles keep writing. Returns the number of
+    /// records rewritten; the default just flushes.
+    fn compact(&self) -> Result<u64, String> {
+        self.flush()?;
+        Ok(0)
+    }
 }
 
 /// In-memory storage useful for testing and temporary WASM execution
@@ -101,6 +123,143 @@ impl StorageBackend for MemoryStorage {
     }
 }
 
+/// A view of another backend that keeps every key under `prefix`, so
+/// several stores of the same kind can share one database (each
+/// identity's contacts and history, see `IronCore::switch_identity`).
+#[derive(Clone)]
+pub struct PrefixedStorage {
+    inner: Arc<dyn StorageBackend>,
+    prefix: Vec<u8>,
+}
+
+impl PrefixedStorage {
+    pub fn new(inner: Arc<dyn StorageBackend>, prefix: impl Into<Vec<u8>>) -> Self {
+        Self {
+            inner,
+            prefix: prefix.into(),
+        }
+    }
+
+    fn key(&self, key: &[u8]) -> Vec<u8> {
+        [self.prefix.as_slice(), key].concat()
+    }
+}
+
+impl StorageBackend for PrefixedStorage {
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), String> {
+        self.inner.put(&self.key(key), value)
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, String> {
+        self.inner.get(&self.key(key))
+    }
+
+    fn remove(&self, key: &[u8]) -> Result<(), String> {
+        self.inner.remove(&self.key(key))
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<ScanResult, String> {
+        Ok(self
+            .inner
+            .scan_prefix(&self.key(prefix))?
+            .into_iter()
+            .map(|(key, value)| (key[self.prefix.len()..].to_vec(), value))
+            .collect())
+    }
+
+    fn count_prefix(&self, prefix: &[u8]) -> Result<usize, String> {
+        self.inner.count_prefix(&self.key(prefix))
+    }
+
+    fn scan_prefix_before(
+        &self,
+        prefix: &[u8],
+        before: &[u8],
+        limit: usize,
+    ) -> Result<ScanResult, String> {
+        Ok(self
+            .inner
+            .scan_prefix_before(&self.key(prefix), &self.key(before), limit)?
+            .into_iter()
+            .map(|(key, value)| (key[self.prefix.len()..].to_vec(), value))
+            .collect())
+    }
+
+    fn flush(&self) -> Result<(), String> {
+        self.inner.flush()
+    }
+
+    fn approximate_size(&self) -> Result<u64, String> {
+        Ok(self
+            .inner
+            .scan_prefix(&self.prefix)?
+            .iter()
+            .map(|(k, v)| (k.len() + v.len()) as u64)
+            .sum())
+    }
+
+    fn compact(&self) -> Result<u64, String> {
+        self.inner.compact()
+    }
+}
+
+/// Memory storage whose writes can be made to fail, for exercising
+/// disk-full handling in the stores.
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub(crate) struct FlakyStorage {
+    inner: MemoryStorage,
+    fail_writes: Arc<std::sync::atomic::AtomicBool>,
+}
+
+#[cfg(test)]
+impl FlakyStorage {
+    pub(crate) fn set_fail_writes(&self, fail: bool) {
+        self.fail_writes
+            .store(fail, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    fn check_write(&self) -> Result<(), String> {
+        if self.fail_writes.load(std::sync::atomic::Ordering::SeqCst) {
+            Err("No space left on device".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+impl StorageBackend for FlakyStorage {
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), String> {
+        self.check_write()?;
+        self.inner.put(key, value)
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, String> {
+        self.inner.get(key)
+    }
+
+    fn remove(&self, key: &[u8]) -> Result<(), String> {
+        self.inner.remove(key)
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<ScanResult, String> {
+        self.inner.scan_prefix(prefix)
+    }
+
+    fn count_prefix(&self, prefix: &[u8]) -> Result<usize, String> {
+        self.inner.count_prefix(prefix)
+    }
+
+    fn flush(&self) -> Result<(), String> {
+        self.check_write()
+    }
+
+    fn approximate_size(&self) -> Result<u64, String> {
+        self.inner.approximate_size()
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 pub struct SledStorage {
     db: sled::Db,
@@ -149,6 +308,15 @@ impl StorageBackend for SledStorage {
         Ok(self.db.scan_prefix(prefix).count())
     }
 
+    fn scan_prefix_before(
+        &self,
+        prefix: &[u8],
+        before: &[u8],
+        limit: usize,
+    ) -> Result<ScanResult, String> {
+        StorageBackend::scan_prefix_before(&*self.db, prefix, before, limit)
+    }
+
     fn flush(&self) -> Result<(), String> {
         self.db.flush().map_err(|e| e.to_string())?;
         Ok(())
@@ -157,6 +325,94 @@ impl StorageBackend for SledStorage {
     fn approximate_size(&self) -> Result<u64, String> {
         self.db.size_on_disk().map_err(|e| e.to_string())
     }
+
+    /// sled has no explicit GC call: its segment cleaner frees a segment once
+    /// nothing live points into it. Rewriting every live record moves it out
+    /// of fragmented segments so the cleaner can reclaim them. Each rewrite is
+    /// a compare-and-swap against the value just read, so a concurrent write
+    /// always wins and nothing is rolled back.
+    fn compact(&self) -> Result<u64, String> {
+        self.db.flush().map_err(|e| e.to_string())?;
+        let mut rewritten = 0u64;
+        for item in self.db.iter() {
+            let (key, value) = item.map_err(|e| e.to_string())?;
+            let swapped = self
+                .db
+                .compare_and_swap(&key, Some(&value), Some(&value))
+                .map_err(|e| e.to_string())?;
+            if swapped.is_ok() {
+                rewritten += 1;
+            }
+        }
+        self.db.flush().map_err(|e| e.to_string())?;
+        Ok(rewritten)
+    }
+}
+
+/// A single sled tree, for stores that keep a secondary index beside their
+/// records in the same database (see `mobile_bridge::HistoryManager`).
+#[cfg(not(target_arch = "wasm32"))]
+impl StorageBackend for sled::Tree {
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), String> {
+        self.insert(key, value).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, String> {
+        let value = sled::Tree::get(self, key).map_err(|e| e.to_string())?;
+        Ok(value.map(|ivec| ivec.to_vec()))
+    }
+
+    fn remove(&self, key: &[u8]) -> Result<(), String> {
+        sled::Tree::remove(self, key).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<ScanResult, String> {
+        sled::Tree::scan_prefix(self, prefix)
+            .map(|item| {
+                item.map(|(k, v)| (k.to_vec(), v.to_vec()))
+                    .map_err(|e| e.to_string())
+            })
+            .collect()
+    }
+
+    fn count_prefix(&self, prefix: &[u8]) -> Result<usize, String> {
+        Ok(sled::Tree::scan_prefix(self, prefix).count())
+    }
+
+    fn scan_prefix_before(
+        &self,
+        prefix: &[u8],
+        before: &[u8],
+        limit: usize,
+    ) -> Result<ScanResult, String> {
+        if before <= prefix {
+            return Ok(Vec::new());
+        }
+        self.range(prefix..before)
+            .rev()
+            .filter(|item| !matches!(item, Ok((k, _)) if !k.starts_with(prefix)))
+            .take(limit)
+            .map(|item| {
+                item.map(|(k, v)| (k.to_vec(), v.to_vec()))
+                    .map_err(|e| e.to_string())
+            })
+            .collect()
+    }
+
+    fn flush(&self) -> Result<(), String> {
+        sled::Tree::flush(self).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn approximate_size(&self) -> Result<u64, String> {
+        Ok(self
+            .iter()
+            .filter_map(Result::ok)
+            .map(|(k, v)| (k.len() + v.len()) as u64)
+            .sum())
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -7,6 +7,7 @@ use crate::store::backend::StorageBackend;
 use crate::store::history::HistoryManager;
 use crate::IronCoreError;
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +24,13 @@ pub struct Contact {
     /// Used as `intended_device_id` when sending to this contact.
     #[serde(default)]
     pub last_known_device_id: Option<String>,
+    /// Free-form user labels. Carried through contact export/import bundles.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Relays (PeerId or `/p2p/` multiaddr) to try first, right after a
+    /// direct connection, when delivering to this contact.
+    #[serde(default)]
+    pub preferred_relays: Vec<String>,
 }
 
 impl Contact {
@@ -36,12 +44,23 @@ impl Contact {
             last_seen: None,
             notes: None,
             last_known_device_id: None,
+            tags: Vec::new(),
+            preferred_relays: Vec::new(),
         }
     }
 
-    pub fn with_nickname(mut self, nickname: String) -> Self {
+    /// Set the federated nickname, rejecting over-long or display-unsafe
+    /// ones (see `identity::validate_nickname`).
+    pub fn with_nickname(
+        mut self,
+        nickname: String,
+    ) -> Result<Self, crate::identity::NicknameError> {
+        crate::identity::validate_nickname(
+            &nickname,
+            crate::identity::DEFAULT_MAX_NICKNAME_GRAPHEMES,
+        )?;
         self.nickname = Some(nickname);
-        self
+        Ok(self)
     }
 
     pub fn display_name(&self) -> &str {
@@ -58,6 +77,43 @@ impl Contact {
     }
 }
 
+/// One contact as carried in an export bundle. `public_key` is the canonical
+/// identity; `peer_id` and `nickname` are advisory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContactBundleEntry {
+    pub peer_id: String,
+    pub public_key: String,
+    pub nickname: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Signed, portable contact list produced by `IronCore::export_contacts_bundle`.
+/// `signature` covers the JSON encoding of `contacts` and is made with the
+/// exporting identity's Ed25519 key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedContactBundle {
+    pub version: u32,
+    pub contacts: Vec<ContactBundleEntry>,
+    pub signer_public_key: String,
+    pub signature: String,
+}
+
+pub const CONTACT_BUNDLE_VERSION: u32 = 1;
+
+/// Outcome of merging a contact bundle into the local store.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContactImportReport {
+    pub added: u32,
+    pub updated: u32,
+    pub unchanged: u32,
+    /// Entries whose nickname differed from an existing local one. The local
+    /// nickname was kept unless the import ran with `overwrite`.
+    pub conflicts: u32,
+    /// Entries dropped because their public key was not a valid Ed25519 key.
+    pub rejected: u32,
+}
+
 /// Key prefix namespacing contact records in the shared backend. `IronCore`
 /// hands identity, history, logs, blocked-list, and contact storage the same
 /// `Arc<dyn StorageBackend>` instance, so without a prefix, `list()`/`count()`
@@ -76,11 +132,16 @@ fn contact_bundle_key(public_key_hex: &str) -> Vec<u8> {
 #[derive(Clone)]
 pub struct ContactManager {
     backend: Arc<dyn StorageBackend>,
+    /// `MeshSettings::max_contacts`, shared by every clone; 0 = no cap.
+    max_contacts: Arc<AtomicU32>,
 }
 
 impl ContactManager {
     pub fn new(backend: Arc<dyn StorageBackend>) -> Self {
-        let manager = Self { backend };
+        let manager = Self {
+            backend,
+            max_contacts: Arc::new(AtomicU32::new(0)),
+        };
         manager.migrate_unprefixed_contacts();
         manager
     }
@@ -212,12 +273,61 @@ impl ContactManager {
         Err(IronCoreError::InvalidInput)
     }
 
+    /// Add or update a contact. When a new contact takes the store past
+    /// `max_contacts`, the least recently seen other contact is evicted.
     pub fn add(&self, contact: Contact) -> Result<(), IronCoreError> {
         let key = contact_key(&contact.peer_id);
         let value = serde_json::to_vec(&contact).map_err(|_| IronCoreError::Internal)?;
+        let is_new = self
+            .backend
+            .get(&key)
+            .map_err(|_| IronCoreError::StorageError)?
+            .is_none();
         self.backend
             .put(&key, &value)
             .map_err(|_| IronCoreError::StorageError)?;
+        if is_new {
+            self.evict_over_cap(&contact.peer_id)?;
+        }
+        Ok(())
+    }
+
+    /// Current contact cap (0 = none).
+    pub fn max_contacts(&self) -> u32 {
+        self.max_contacts.load(Ordering::Relaxed)
+    }
+
+    /// Change the contact cap for this manager and its clones. Takes effect
+    /// on the next `add`; existing contacts are not evicted until then.
+    pub fn set_max_contacts(&self, max_contacts: u32) {
+        self.max_contacts.store(max_contacts, Ordering::Relaxed);
+    }
+
+    fn evict_over_cap(&self, keep: &str) -> Result<(), IronCoreError> {
+        let cap = self.max_contacts();
+        if cap == 0 {
+            return Ok(());
+        }
+        let mut candidates = self.list()?;
+        let excess = candidates.len().saturating_sub(cap as usize);
+        if excess == 0 {
+            return Ok(());
+        }
+        candidates.retain(|c| c.peer_id != keep);
+        candidates.sort_by(|a, b| {
+            a.last_seen
+                .unwrap_or(a.added_at)
+                .cmp(&b.last_seen.unwrap_or(b.added_at))
+                .then_with(|| a.peer_id.cmp(&b.peer_id))
+        });
+        for contact in candidates.into_iter().take(excess) {
+            self.remove(contact.peer_id.clone())?;
+            tracing::info!(
+                "Evicted contact {} to stay within the {} contact cap",
+                contact.peer_id,
+                cap
+            );
+        }
         Ok(())
     }
 
@@ -321,15 +431,65 @@ impl ContactManager {
         Ok(results)
     }
 
+    /// Every contact whose nickname or local nickname equals `nickname`,
+    /// ignoring case. Nicknames are not unique, so callers resolving a
+    /// recipient must treat more than one match as ambiguous.
+    pub fn find_all_by_nickname(&self, nickname: &str) -> Result<Vec<Contact>, IronCoreError> {
+        let wanted = nickname.trim().to_lowercase();
+        if wanted.is_empty() {
+            return Ok(Vec::new());
+        }
+        let matches_name =
+            |name: &Option<String>| name.as_ref().is_some_and(|n| n.to_lowercase() == wanted);
+
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|contact| {
+                matches_name(&contact.nickname) || matches_name(&contact.local_nickname)
+            })
+            .collect())
+    }
+
+    /// The saved contact `query` names. Exact identifiers (public key, peer
+    /// ID, identity ID) win over nicknames; a nickname shared by several
+    /// contacts is ambiguous rather than guessed.
+    pub fn resolve(&self, query: &str) -> Result<ContactLookup, IronCoreError> {
+        let query = query.trim();
+        let query_lower = query.to_lowercase();
+        let contacts = self.list()?;
+        let identity_id = |c: &Contact| {
+            hex::decode(&c.public_key)
+                .map(|pk| hex::encode(blake3::hash(&pk).as_bytes()))
+                .unwrap_or_default()
+        };
+
+        if let Some(contact) = contacts.iter().find(|c| {
+            c.public_key.to_lowercase() == query_lower
+                || c.peer_id == query
+                || identity_id(c) == query_lower
+        }) {
+            return Ok(ContactLookup::Found(Box::new(contact.clone())));
+        }
+
+        let mut by_name = self.find_all_by_nickname(query)?;
+        Ok(match by_name.len() {
+            0 => ContactLookup::NotFound,
+            1 => ContactLookup::Found(Box::new(by_name.remove(0))),
+            n => ContactLookup::Ambiguous(n),
+        })
+    }
+
     pub fn set_nickname(
         &self,
         peer_id: String,
         nickname: Option<String>,
     ) -> Result<(), IronCoreError> {
         if let Some(mut contact) = self.get(peer_id)? {
-            contact.nickname = nickname
-                .map(|n| n.trim().to_string())
-                .filter(|n| !n.is_empty());
+            contact.nickname = crate::identity::normalize_nickname(nickname).map_err(|e| {
+                tracing::warn!("Rejected contact nickname: {}", e);
+                IronCoreError::InvalidNickname
+            })?;
             self.add(contact)?;
             Ok(())
         } else {
@@ -343,9 +503,28 @@ impl ContactManager {
         nickname: Option<String>,
     ) -> Result<(), IronCoreError> {
         if let Some(mut contact) = self.get(peer_id)? {
-            contact.local_nickname = nickname
-                .map(|n| n.trim().to_string())
-                .filter(|n| !n.is_empty());
+            contact.local_nickname =
+                crate::identity::normalize_nickname(nickname).map_err(|e| {
+                    tracing::warn!("Rejected contact nickname: {}", e);
+                    IronCoreError::InvalidNickname
+                })?;
+            self.add(contact)?;
+            Ok(())
+        } else {
+            Err(IronCoreError::InvalidInput)
+        }
+    }
+
+    /// Replace the contact's preferred relays. Every entry must name a relay
+    /// (see `transport::relay_peer_from_str`); an empty list clears them.
+    pub fn set_preferred_relays(
+        &self,
+        peer_id: String,
+        relays: Vec<String>,
+    ) -> Result<(), IronCoreError> {
+        let relays = normalize_preferred_relays(relays)?;
+        if let Some(mut contact) = self.get(peer_id)? {
+            contact.preferred_relays = relays;
             self.add(contact)?;
             Ok(())
         } else {
@@ -353,6 +532,27 @@ impl ContactManager {
         }
     }
 
+    /// Preferred relays of every contact that has some, keyed by the
+    /// contact's libp2p PeerId, ready for `SwarmHandle::set_preferred_relays`.
+    pub fn preferred_relay_routes(
+        &self,
+    ) -> Result<Vec<(libp2p::PeerId, Vec<libp2p::PeerId>)>, IronCoreError> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|contact| !contact.preferred_relays.is_empty())
+            .filter_map(|contact| {
+                let target = contact_libp2p_peer_id(&contact.peer_id, &contact.public_key)?;
+                let relays = contact
+                    .preferred_relays
+                    .iter()
+                    .filter_map(|relay| crate::transport::relay_peer_from_str(relay))
+                    .collect();
+                Some((target, relays))
+            })
+            .collect())
+    }
+
     pub fn update_last_seen(&self, peer_id: String) -> Result<(), IronCoreError> {
         if let Some(mut contact) = self.get(peer_id)? {
             contact.last_seen = Some(current_timestamp());
@@ -361,6 +561,20 @@ impl ContactManager {
         Ok(())
     }
 
+    /// Record that the contact was seen at `timestamp`, e.g. from a presence
+    /// beacon. `last_seen` only moves forward.
+    pub fn record_seen_at(&self, peer_id: String, timestamp: u64) -> Result<bool, IronCoreError> {
+        let Some(mut contact) = self.get(peer_id)? else {
+            return Ok(false);
+        };
+        if contact.last_seen.is_some_and(|seen| seen >= timestamp) {
+            return Ok(false);
+        }
+        contact.last_seen = Some(timestamp);
+        self.add(contact)?;
+        Ok(true)
+    }
+
     /// Update the most-recently-observed device ID for a contact (WS13 tight-pair).
     ///
     /// Called when an inbound message or ledger exchange reveals the sender's current device UUID.
@@ -391,6 +605,130 @@ impl ContactManager {
         Ok(())
     }
 
+    /// Snapshot every contact as a bundle entry. The exported nickname is the
+    /// one this user sees (local override first, then the federated one).
+    pub fn export_entries(&self) -> Result<Vec<ContactBundleEntry>, IronCoreError> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .map(|c| ContactBundleEntry {
+                nickname: c.local_nickname.clone().or(c.nickname.clone()),
+                peer_id: c.peer_id,
+                public_key: c.public_key,
+                tags: c.tags,
+            })
+            .collect())
+    }
+
+    /// Merge bundle entries into the store, matching on public key.
+    ///
+    /// Existing records keep their peer_id, timestamps, notes and device
+    /// metadata; tags are unioned. When an entry's nickname differs from the
+    /// existing local nickname the local one wins unless `overwrite` is set.
+    /// Entries with an invalid key or a display-unsafe nickname are rejected.
+    pub fn merge_bundle_entries(
+        &self,
+        entries: Vec<ContactBundleEntry>,
+        overwrite: bool,
+    ) -> Result<ContactImportReport, IronCoreError> {
+        let mut by_key: std::collections::HashMap<String, Contact> = self
+            .list()?
+            .into_iter()
+            .map(|c| (c.public_key.to_lowercase(), c))
+            .collect();
+        let mut report = ContactImportReport::default();
+
+        for entry in entries {
+            let public_key = entry.public_key.trim().to_lowercase();
+            if crate::crypto::validate_ed25519_public_key(&public_key).is_err() {
+                report.rejected += 1;
+                continue;
+            }
+            let Ok(nickname) = crate::identity::normalize_nickname(entry.nickname) else {
+                report.rejected += 1;
+                continue;
+            };
+
+            let Some(mut contact) = by_key.get(&public_key).cloned() else {
+                let peer_id = if entry.peer_id.trim().is_empty() {
+                    public_key.clone()
+                } else {
+                    entry.peer_id.trim().to_string()
+                };
+                let mut contact = Contact::new(peer_id, public_key.clone());
+                contact.local_nickname = nickname;
+                contact.tags = entry.tags;
+                self.add(contact.clone())?;
+                by_key.insert(public_key, contact);
+                report.added += 1;
+                continue;
+            };
+
+            let mut changed = false;
+            match (&contact.local_nickname, nickname) {
+                (_, None) => {}
+                (None, Some(n)) => {
+                    contact.local_nickname = Some(n);
+                    changed = true;
+                }
+                (Some(existing), Some(n)) if *existing != n => {
+                    report.conflicts += 1;
+                    if overwrite {
+                        contact.local_nickname = Some(n);
+                        changed = true;
+                    }
+                }
+                _ => {}
+            }
+            for tag in entry.tags {
+                if !contact.tags.contains(&tag) {
+                    contact.tags.push(tag);
+                    changed = true;
+                }
+            }
+
+            if changed {
+                self.add(contact.clone())?;
+                by_key.insert(public_key, contact);
+                report.updated += 1;
+            } else {
+                report.unchanged += 1;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Groups of contacts that share a public key, as `dedup` would merge
+    /// them (see `duplicate_contact_groups`). Nothing is modified.
+    pub fn find_duplicates(&self) -> Result<Vec<Vec<Contact>>, IronCoreError> {
+        Ok(duplicate_contact_groups(self.list()?))
+    }
+
+    /// Collapse contacts that share a public key into one record (see
+    /// `merge_duplicate_contacts`) and return how many were removed.
+    pub fn dedup(&self) -> Result<usize, IronCoreError> {
+        let mut removed = 0;
+        for group in self.find_duplicates()? {
+            let Some((keep, duplicates)) = merge_duplicate_contacts(group) else {
+                continue;
+            };
+            self.add(keep)?;
+            for peer_id in duplicates {
+                // Not `remove`: that would also drop the key bundle, which
+                // the kept contact shares.
+                self.backend
+                    .remove(&contact_key(&peer_id))
+                    .map_err(|_| IronCoreError::StorageError)?;
+                removed += 1;
+            }
+        }
+        if removed > 0 {
+            self.flush();
+        }
+        Ok(removed)
+    }
+
     pub fn count(&self) -> u32 {
         self.backend.count_prefix(CONTACT_KEY_PREFIX).unwrap_or(0) as u32
     }
@@ -424,6 +762,133 @@ impl ContactManager {
     }
 }
 
+/// Outcome of `ContactManager::resolve`.
+#[derive(Debug, Clone)]
+pub enum ContactLookup {
+    Found(Box<Contact>),
+    NotFound,
+    /// A nickname shared by this many contacts.
+    Ambiguous(usize),
+}
+
+/// A contact record `duplicate_contact_groups` and
+/// `merge_duplicate_contacts` work on. Implemented by this store's `Contact`
+/// and the mobile bridge's, so both stores find and merge duplicates by the
+/// same rules.
+pub(crate) trait DuplicateContact: Clone {
+    fn peer_id(&self) -> &str;
+    fn public_key(&self) -> &str;
+    fn display_name(&self) -> &str;
+    fn added_at(&self) -> u64;
+    fn last_seen(&self) -> Option<u64>;
+    /// `None` where the store does not track verification.
+    fn verified_at(&self) -> Option<u64>;
+    /// Fold a duplicate into this, the contact being kept: the earliest
+    /// `added_at` and verification, the latest `last_seen`, and whatever
+    /// nickname, notes, device id, tags or relays this one lacks.
+    fn absorb(&mut self, other: &Self);
+}
+
+/// Groups of contacts that share a public key (compared trimmed and
+/// case-insensitively), ordered by the kept contact's name. Within a group
+/// the contact to keep comes first: verified, then most recently seen, then
+/// earliest added.
+pub(crate) fn duplicate_contact_groups<C: DuplicateContact>(contacts: Vec<C>) -> Vec<Vec<C>> {
+    let mut groups: std::collections::HashMap<String, Vec<C>> = std::collections::HashMap::new();
+    for contact in contacts {
+        groups
+            .entry(contact.public_key().trim().to_lowercase())
+            .or_default()
+            .push(contact);
+    }
+    let mut duplicates: Vec<Vec<C>> = groups
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .map(|mut group| {
+            group.sort_by(|a, b| {
+                b.verified_at()
+                    .is_some()
+                    .cmp(&a.verified_at().is_some())
+                    .then(b.last_seen().cmp(&a.last_seen()))
+                    .then(a.added_at().cmp(&b.added_at()))
+            });
+            group
+        })
+        .collect();
+    duplicates.sort_by(|a, b| a[0].display_name().cmp(b[0].display_name()));
+    duplicates
+}
+
+/// Collapse a group from `duplicate_contact_groups` into its first contact.
+/// Returns the merged contact and the peer ids of the records to remove.
+pub(crate) fn merge_duplicate_contacts<C: DuplicateContact>(
+    group: Vec<C>,
+) -> Option<(C, Vec<String>)> {
+    let mut group = group.into_iter();
+    let mut keep = group.next()?;
+    let mut duplicates = Vec::new();
+    for other in group {
+        keep.absorb(&other);
+        if other.peer_id() != keep.peer_id() {
+            duplicates.push(other.peer_id().to_string());
+        }
+    }
+    Some((keep, duplicates))
+}
+
+impl DuplicateContact for Contact {
+    fn peer_id(&self) -> &str {
+        &self.peer_id
+    }
+
+    fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    fn display_name(&self) -> &str {
+        Contact::display_name(self)
+    }
+
+    fn added_at(&self) -> u64 {
+        self.added_at
+    }
+
+    fn last_seen(&self) -> Option<u64> {
+        self.last_seen
+    }
+
+    fn verified_at(&self) -> Option<u64> {
+        None
+    }
+
+    fn absorb(&mut self, other: &Self) {
+        self.added_at = self.added_at.min(other.added_at);
+        self.last_seen = self.last_seen.max(other.last_seen);
+        if self.nickname.is_none() {
+            self.nickname = other.nickname.clone();
+        }
+        if self.local_nickname.is_none() {
+            self.local_nickname = other.local_nickname.clone();
+        }
+        if self.notes.is_none() {
+            self.notes = other.notes.clone();
+        }
+        if self.last_known_device_id.is_none() {
+            self.last_known_device_id = other.last_known_device_id.clone();
+        }
+        for tag in &other.tags {
+            if !self.tags.contains(tag) {
+                self.tags.push(tag.clone());
+            }
+        }
+        for relay in &other.preferred_relays {
+            if !self.preferred_relays.contains(relay) {
+                self.preferred_relays.push(relay.clone());
+            }
+        }
+    }
+}
+
 fn current_timestamp() -> u64 {
     web_time::SystemTime::now()
         .duration_since(web_time::UNIX_EPOCH)
@@ -431,6 +896,34 @@ fn current_timestamp() -> u64 {
         .as_secs()
 }
 
+/// Trim and de-duplicate relay entries, rejecting any that name no relay.
+pub(crate) fn normalize_preferred_relays(
+    relays: Vec<String>,
+) -> Result<Vec<String>, IronCoreError> {
+    let mut normalized: Vec<String> = Vec::with_capacity(relays.len());
+    for relay in relays {
+        let relay = relay.trim().to_string();
+        if crate::transport::relay_peer_from_str(&relay).is_none() {
+            return Err(IronCoreError::InvalidInput);
+        }
+        if !normalized.contains(&relay) {
+            normalized.push(relay);
+        }
+    }
+    Ok(normalized)
+}
+
+/// A contact's libp2p PeerId: its stored peer ID when that already is one,
+/// otherwise the one derived from its Ed25519 identity key.
+fn contact_libp2p_peer_id(peer_id: &str, public_key: &str) -> Option<libp2p::PeerId> {
+    peer_id.parse().ok().or_else(|| {
+        crate::identity::peer_id_from_public_key_hex(public_key)
+            .ok()?
+            .parse()
+            .ok()
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -441,6 +934,65 @@ mod tests {
         ContactManager::new(Arc::new(MemoryStorage::new()))
     }
 
+    #[test]
+    fn find_all_by_nickname_returns_every_duplicate() {
+        let mgr = make_manager();
+        let mut first = Contact::new("peer-a".to_string(), "aa".repeat(32));
+        first.nickname = Some("Sam".to_string());
+        let mut second = Contact::new("peer-b".to_string(), "bb".repeat(32));
+        second.local_nickname = Some("sam".to_string());
+        let mut other = Contact::new("peer-c".to_string(), "cc".repeat(32));
+        other.nickname = Some("Samantha".to_string());
+        for contact in [first, second, other] {
+            mgr.add(contact).unwrap();
+        }
+
+        let mut found: Vec<String> = mgr
+            .find_all_by_nickname("SAM")
+            .unwrap()
+            .into_iter()
+            .map(|c| c.peer_id)
+            .collect();
+        found.sort();
+        assert_eq!(found, vec!["peer-a".to_string(), "peer-b".to_string()]);
+        assert_eq!(mgr.find_all_by_nickname("samantha").unwrap().len(), 1);
+        assert!(mgr.find_all_by_nickname("nobody").unwrap().is_empty());
+        assert!(mgr.find_all_by_nickname("  ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn set_preferred_relays_validates_and_yields_routes() {
+        let mgr = make_manager();
+        let contact_peer = libp2p::PeerId::random();
+        let relay = libp2p::PeerId::random();
+        mgr.add(Contact::new(contact_peer.to_string(), "pubkey".to_string()))
+            .unwrap();
+
+        assert!(mgr
+            .set_preferred_relays(contact_peer.to_string(), vec!["not-a-relay".to_string()])
+            .is_err());
+        assert!(mgr
+            .set_preferred_relays("missing".to_string(), vec![relay.to_string()])
+            .is_err());
+
+        let relay_addr = format!("/ip4/203.0.113.7/tcp/4001/p2p/{}", relay);
+        mgr.set_preferred_relays(
+            contact_peer.to_string(),
+            vec![relay_addr.clone(), format!(" {} ", relay_addr)],
+        )
+        .unwrap();
+        let stored = mgr.get(contact_peer.to_string()).unwrap().unwrap();
+        assert_eq!(stored.preferred_relays, vec![relay_addr]);
+        assert_eq!(
+            mgr.preferred_relay_routes().unwrap(),
+            vec![(contact_peer, vec![relay])]
+        );
+
+        mgr.set_preferred_relays(contact_peer.to_string(), Vec::new())
+            .unwrap();
+        assert!(mgr.preferred_relay_routes().unwrap().is_empty());
+    }
+
     #[test]
     fn contact_new_has_no_last_known_device_id() {
         let c = Contact::new("peer-1".to_string(), "pubkey-hex".to_string());
@@ -529,6 +1081,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_dedup_collapses_same_public_key() {
+        let mgr = make_manager();
+        let mut old = Contact::new("peer-cli".to_string(), "ABCD".to_string());
+        old.added_at = 100;
+        old.last_seen = Some(200);
+        old.local_nickname = Some("Ana".to_string());
+        old.tags = vec!["work".to_string()];
+        let mut recent = Contact::new("peer-qr".to_string(), "abcd ".to_string());
+        recent.added_at = 300;
+        recent.last_seen = Some(900);
+        recent.tags = vec!["family".to_string(), "work".to_string()];
+        mgr.add(old).unwrap();
+        mgr.add(recent).unwrap();
+        mgr.add(Contact::new("peer-other".to_string(), "ef01".to_string()))
+            .unwrap();
+
+        let groups = mgr.find_duplicates().unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0][0].peer_id, "peer-qr");
+        // Dry run changed nothing.
+        assert_eq!(mgr.count(), 3);
+
+        assert_eq!(mgr.dedup().unwrap(), 1);
+        assert_eq!(mgr.count(), 2);
+        assert!(mgr.get("peer-cli".to_string()).unwrap().is_none());
+        let kept = mgr.get("peer-qr".to_string()).unwrap().unwrap();
+        assert_eq!(kept.added_at, 100);
+        assert_eq!(kept.last_seen, Some(900));
+        assert_eq!(kept.local_nickname.as_deref(), Some("Ana"));
+        assert_eq!(kept.tags, vec!["family".to_string(), "work".to_string()]);
+
+        assert_eq!(mgr.dedup().unwrap(), 0);
+    }
+
     #[test]
     fn test_unprefixed_contacts_migrate_on_open() {
         let backend = Arc::new(MemoryStorage::new());
@@ -615,4 +1202,133 @@ mod tests {
             "bundle must be deleted when contact is removed"
         );
     }
+
+    fn bundle_entry(public_key: &str, nickname: Option<&str>, tags: &[&str]) -> ContactBundleEntry {
+        ContactBundleEntry {
+            peer_id: format!("peer-{}", &public_key[..8]),
+            public_key: public_key.to_string(),
+            nickname: nickname.map(str::to_string),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn merge_bundle_adds_new_and_keeps_local_nickname_on_conflict() {
+        use crate::identity::IdentityKeys;
+
+        let mgr = make_manager();
+        let known = IdentityKeys::generate().public_key_hex();
+        let fresh = IdentityKeys::generate().public_key_hex();
+        let spoofed = IdentityKeys::generate().public_key_hex();
+
+        let mut existing = Contact::new("peer-known".to_string(), known.clone());
+        existing.local_nickname = Some("Alice".to_string());
+        existing.notes = Some("met at conf".to_string());
+        mgr.add(existing).unwrap();
+
+        let report = mgr
+            .merge_bundle_entries(
+                vec![
+                    bundle_entry(&known, Some("Alicia"), &["work"]),
+                    bundle_entry(&fresh, Some("Bob"), &[]),
+                    bundle_entry("not-a-key", Some("Mallory"), &[]),
+                    bundle_entry(&spoofed, Some("admin\u{202E}txt.exe"), &[]),
+                ],
+                false,
+            )
+            .unwrap();
+        assert_eq!(report.added, 1);
+        assert_eq!(report.updated, 1);
+        assert_eq!(report.conflicts, 1);
+        assert_eq!(report.rejected, 2);
+
+        let known_contact = mgr.get("peer-known".to_string()).unwrap().unwrap();
+        assert_eq!(known_contact.local_nickname.as_deref(), Some("Alice"));
+        assert_eq!(known_contact.notes.as_deref(), Some("met at conf"));
+        assert_eq!(known_contact.tags, vec!["work".to_string()]);
+        assert_eq!(mgr.count(), 2);
+
+        // Re-importing the same entries is a no-op apart from the conflict.
+        let again = mgr
+            .merge_bundle_entries(vec![bundle_entry(&known, Some("Alicia"), &["work"])], false)
+            .unwrap();
+        assert_eq!(again.unchanged, 1);
+    }
+
+    #[test]
+    fn merge_bundle_overwrite_replaces_local_nickname() {
+        use crate::identity::IdentityKeys;
+
+        let mgr = make_manager();
+        let known = IdentityKeys::generate().public_key_hex();
+        let mut existing = Contact::new("peer-known".to_string(), known.clone());
+        existing.local_nickname = Some("Alice".to_string());
+        mgr.add(existing).unwrap();
+
+        let report = mgr
+            .merge_bundle_entries(vec![bundle_entry(&known, Some("Alicia"), &[])], true)
+            .unwrap();
+        assert_eq!(report.conflicts, 1);
+        assert_eq!(report.updated, 1);
+        let contact = mgr.get("peer-known".to_string()).unwrap().unwrap();
+        assert_eq!(contact.local_nickname.as_deref(), Some("Alicia"));
+    }
+
+    #[test]
+    fn export_entries_prefers_local_nickname() {
+        let mgr = make_manager();
+        let mut c = Contact::new("peer-1".to_string(), "pk".to_string())
+            .with_nickname("federated".to_string())
+            .unwrap();
+        c.local_nickname = Some("local".to_string());
+        c.tags = vec!["family".to_string()];
+        mgr.add(c).unwrap();
+
+        let entries = mgr.export_entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].nickname.as_deref(), Some("local"));
+        assert_eq!(entries[0].tags, vec!["family".to_string()]);
+    }
+
+    #[test]
+    fn with_nickname_rejects_display_unsafe_names() {
+        let contact = || Contact::new("peer-1".to_string(), "pk".to_string());
+        assert_eq!(
+            contact()
+                .with_nickname("Zoë 🦀".to_string())
+                .unwrap()
+                .display_name(),
+            "Zoë 🦀"
+        );
+        assert!(matches!(
+            contact().with_nickname("admin\u{202E}txt.exe".to_string()),
+            Err(crate::identity::NicknameError::BidiOverride(0x202E))
+        ));
+        assert!(matches!(
+            contact().with_nickname("y".repeat(100)),
+            Err(crate::identity::NicknameError::TooLong { .. })
+        ));
+    }
+
+    #[test]
+    fn max_contacts_evicts_least_recently_seen() {
+        let mgr = make_manager();
+        // Clones, like `IronCore::contacts_store_manager`, share the cap.
+        mgr.clone().set_max_contacts(2);
+        let seen_at = |peer_id: &str, last_seen: u64| {
+            let mut contact = Contact::new(peer_id.to_string(), format!("pk-{}", peer_id));
+            contact.last_seen = Some(last_seen);
+            contact
+        };
+        mgr.add(seen_at("peer-old", 10)).unwrap();
+        mgr.add(seen_at("peer-recent", 20)).unwrap();
+        // Updating an existing contact never evicts.
+        mgr.add(seen_at("peer-old", 11)).unwrap();
+        assert_eq!(mgr.count(), 2);
+
+        mgr.add(seen_at("peer-new", 30)).unwrap();
+        assert_eq!(mgr.count(), 2);
+        assert!(mgr.get("peer-old".to_string()).unwrap().is_none());
+        assert!(mgr.get("peer-new".to_string()).unwrap().is_some());
+    }
 }
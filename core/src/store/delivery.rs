@@ -0,0 +1,302 @@
+// Per-message delivery reports
+//
+// One record per outgoing message, pulling together what is otherwise
+// spread over the outbox (queued for an offline peer), the transport's send
+// attempts, and receipt handling (delivered). Answering "why didn't my
+// message arrive" is a single lookup (`IronCore::delivery_report`).
+//
+// Records are persisted so a report survives the process that sent the
+// message, e.g. `scm send` followed by `scm outbox report`.
+
+use crate::store::backend::StorageBackend;
+use crate::IronCoreError;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Storage key prefix for delivery reports, keyed by message ID
+const DELIVERY_PREFIX: &str = "delivery:";
+
+/// Distinct paths kept per report; further paths still count as attempts.
+const MAX_PATHS_TRIED: usize = 16;
+
+/// Reports older than this are dropped when the store is opened.
+pub const DELIVERY_REPORT_MAX_AGE_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// Where an outgoing message stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(uniffi::Enum))]
+pub enum DeliveryState {
+    /// Waiting for the recipient to become reachable.
+    Queued,
+    /// Handed to a peer or relay; no receipt yet.
+    Sent,
+    /// The recipient's receipt arrived.
+    Delivered,
+    /// Every path was tried and delivery was given up.
+    Failed,
+}
+
+impl std::fmt::Display for DeliveryState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            DeliveryState::Queued => "queued (peer offline)",
+            DeliveryState::Sent => "sent (no receipt yet)",
+            DeliveryState::Delivered => "delivered (receipt received)",
+            DeliveryState::Failed => "failed (paths exhausted)",
+        })
+    }
+}
+
+/// Delivery history of one outgoing message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(uniffi::Record))]
+pub struct DeliveryReport {
+    pub message_id: String,
+    /// Recipient identity the message was prepared for
+    pub recipient: String,
+    pub status: DeliveryState,
+    /// Send attempts made so far, successful or not
+    pub attempts: u32,
+    /// Error from the most recent failed attempt, cleared on success
+    pub last_error: Option<String>,
+    /// When the most recent attempt was made (unix timestamp)
+    pub last_attempt_at: Option<u64>,
+    /// Paths attempted, in first-use order (e.g. "direct", "relay:<peer>")
+    pub paths_tried: Vec<String>,
+    /// When the message was prepared (unix timestamp)
+    pub created_at: u64,
+}
+
+/// Persistent delivery reports, one per outgoing message.
+#[derive(Clone)]
+pub struct DeliveryLog {
+    backend: Arc<dyn StorageBackend>,
+}
+
+impl DeliveryLog {
+    pub fn new(backend: Arc<dyn StorageBackend>) -> Self {
+        Self { backend }
+    }
+
+    /// Open the store and drop reports older than
+    /// `DELIVERY_REPORT_MAX_AGE_SECS`.
+    pub fn open(backend: Arc<dyn StorageBackend>) -> Self {
+        let log = Self::new(backend);
+        let cutoff = current_timestamp().saturating_sub(DELIVERY_REPORT_MAX_AGE_SECS);
+        match log.prune_before(cutoff) {
+            Ok(0) => {}
+            Ok(pruned) => tracing::debug!("Dropped {} old delivery report(s)", pruned),
+            Err(e) => tracing::warn!("Failed to prune delivery reports: {:?}", e),
+        }
+        log
+    }
+
+    /// Remove reports for messages prepared before `before_timestamp`.
+    /// Unreadable records are removed too.
+    pub fn prune_before(&self, before_timestamp: u64) -> Result<u32, IronCoreError> {
+        let all = self
+            .backend
+            .scan_prefix(DELIVERY_PREFIX.as_bytes())
+            .map_err(|_| IronCoreError::StorageError)?;
+
+        let mut removed = 0u32;
+        for (key, value) in all {
+            let stale = serde_json::from_slice::<DeliveryReport>(&value)
+                .map(|report| report.created_at < before_timestamp)
+                .unwrap_or(true);
+            if stale {
+                self.backend
+                    .remove(&key)
+                    .map_err(|_| IronCoreError::StorageError)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn key(message_id: &str) -> String {
+        format!("{}{}", DELIVERY_PREFIX, message_id)
+    }
+
+    pub fn get(&self, message_id: &str) -> Result<Option<DeliveryReport>, IronCoreError> {
+        match self
+            .backend
+            .get(Self::key(message_id).as_bytes())
+            .map_err(|_| IronCoreError::StorageError)?
+        {
+            Some(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|_| IronCoreError::CorruptionDetected),
+            None => Ok(None),
+        }
+    }
+
+    fn put(&self, report: &DeliveryReport) -> Result<(), IronCoreError> {
+        let value = serde_json::to_vec(report).map_err(|_| IronCoreError::Internal)?;
+        self.backend
+            .put(Self::key(&report.message_id).as_bytes(), &value)
+            .map_err(|_| IronCoreError::StorageError)
+    }
+
+    /// Apply `f` to an existing report. Unknown message IDs are ignored, so
+    /// transport outcomes for messages prepared elsewhere are harmless.
+    fn update(
+        &self,
+        message_id: &str,
+        f: impl FnOnce(&mut DeliveryReport),
+    ) -> Result<bool, IronCoreError> {
+        let Some(mut report) = self.get(message_id)? else {
+            return Ok(false);
+        };
+        f(&mut report);
+        self.put(&report)?;
+        Ok(true)
+    }
+
+    /// Start the report for a newly prepared message.
+    pub fn record_queued(&self, message_id: &str, recipient: &str) -> Result<(), IronCoreError> {
+        self.put(&DeliveryReport {
+            message_id: message_id.to_string(),
+            recipient: recipient.to_string(),
+            status: DeliveryState::Queued,
+            attempts: 0,
+            last_error: None,
+            last_attempt_at: None,
+            paths_tried: Vec::new(),
+            created_at: current_timestamp(),
+        })
+    }
+
+    /// Record one send attempt over `path`. `error` is `None` when the
+    /// path accepted the message, which moves a queued message to `Sent`.
+    pub fn record_attempt(
+        &self,
+        message_id: &str,
+        path: &str,
+        error: Option<&str>,
+    ) -> Result<bool, IronCoreError> {
+        self.update(message_id, |report| {
+            report.attempts = report.attempts.saturating_add(1);
+            report.last_attempt_at = Some(current_timestamp());
+            if report.paths_tried.len() < MAX_PATHS_TRIED
+                && !report.paths_tried.iter().any(|p| p == path)
+            {
+                report.paths_tried.push(path.to_string());
+            }
+            match error {
+                Some(e) => report.last_error = Some(e.to_string()),
+                None => {
+                    report.last_error = None;
+                    if report.status == DeliveryState::Queued {
+                        report.status = DeliveryState::Sent;
+                    }
+                }
+            }
+        })
+    }
+
+    /// The message left this device without a per-attempt record, e.g.
+    /// `IronCore::mark_message_sent`.
+    pub fn mark_sent(&self, message_id: &str) -> Result<bool, IronCoreError> {
+        self.update(message_id, |report| {
+            if report.status == DeliveryState::Queued {
+                report.status = DeliveryState::Sent;
+            }
+        })
+    }
+
+    /// A receipt arrived. This wins over any earlier failure, since the
+    /// recipient evidently got the message.
+    pub fn mark_delivered(&self, message_id: &str) -> Result<bool, IronCoreError> {
+        self.update(message_id, |report| {
+            report.status = DeliveryState::Delivered;
+            report.last_error = None;
+        })
+    }
+
+    /// Delivery was given up. A message already delivered stays delivered.
+    pub fn mark_failed(&self, message_id: &str, reason: &str) -> Result<bool, IronCoreError> {
+        self.update(message_id, |report| {
+            if report.status != DeliveryState::Delivered {
+                report.status = DeliveryState::Failed;
+                report.last_error = Some(reason.to_string());
+            }
+        })
+    }
+}
+
+fn current_timestamp() -> u64 {
+    web_time::SystemTime::now()
+        .duration_since(web_time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::backend::MemoryStorage;
+
+    #[test]
+    fn test_report_follows_message_lifecycle() {
+        let log = DeliveryLog::new(Arc::new(MemoryStorage::new()));
+        log.record_queued("m1", "aa").unwrap();
+        assert_eq!(
+            log.get("m1").unwrap().unwrap().status,
+            DeliveryState::Queued
+        );
+
+        log.record_attempt("m1", "direct", Some("dial failure"))
+            .unwrap();
+        let report = log.get("m1").unwrap().unwrap();
+        assert_eq!(report.status, DeliveryState::Queued);
+        assert_eq!(report.last_error.as_deref(), Some("dial failure"));
+
+        log.record_attempt("m1", "direct", Some("timeout")).unwrap();
+        log.record_attempt("m1", "relay:r1", None).unwrap();
+        let report = log.get("m1").unwrap().unwrap();
+        assert_eq!(report.status, DeliveryState::Sent);
+        assert_eq!(report.attempts, 3);
+        assert_eq!(report.paths_tried, vec!["direct", "relay:r1"]);
+        assert_eq!(report.last_error, None);
+        assert!(report.last_attempt_at.is_some());
+
+        log.mark_delivered("m1").unwrap();
+        log.mark_failed("m1", "late give-up").unwrap();
+        assert_eq!(
+            log.get("m1").unwrap().unwrap().status,
+            DeliveryState::Delivered
+        );
+    }
+
+    #[test]
+    fn test_failed_and_unknown_messages() {
+        let log = DeliveryLog::new(Arc::new(MemoryStorage::new()));
+        log.record_queued("m1", "aa").unwrap();
+        log.mark_failed("m1", "max attempts reached").unwrap();
+        let report = log.get("m1").unwrap().unwrap();
+        assert_eq!(report.status, DeliveryState::Failed);
+        assert_eq!(report.last_error.as_deref(), Some("max attempts reached"));
+        assert_eq!(report.status.to_string(), "failed (paths exhausted)");
+
+        assert!(!log.record_attempt("nope", "direct", None).unwrap());
+        assert_eq!(log.get("nope").unwrap(), None);
+    }
+
+    #[test]
+    fn test_old_reports_are_pruned() {
+        let backend: Arc<dyn StorageBackend> = Arc::new(MemoryStorage::new());
+        let log = DeliveryLog::new(backend.clone());
+        log.record_queued("fresh", "aa").unwrap();
+        log.put(&DeliveryReport {
+            message_id: "stale".to_string(),
+            created_at: current_timestamp() - DELIVERY_REPORT_MAX_AGE_SECS - 1,
+            ..log.get("fresh").unwrap().unwrap()
+        })
+        .unwrap();
+
+        let log = DeliveryLog::open(backend);
+        assert!(log.get("fresh").unwrap().is_some());
+        assert_eq!(log.get("stale").unwrap(), None);
+    }
+}
@@ -0,0 +1,162 @@
+// Message drafts
+//
+// Unsent compositions, one per recipient, kept separately from the outbox:
+// a draft is plaintext on this device only and is never sealed for the
+// recipient until it is sent, at which point it moves into the normal
+// prepare path (see `IronCore::send_draft`).
+
+use crate::store::backend::StorageBackend;
+use crate::IronCoreError;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Storage key prefix for drafts, keyed by recipient public key (hex)
+const DRAFT_PREFIX: &str = "draft:";
+
+/// A saved, unsent message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(uniffi::Record))]
+pub struct Draft {
+    /// Recipient's Ed25519 public key (hex)
+    pub recipient: String,
+    pub text: String,
+    /// When the draft was last saved (unix timestamp)
+    pub updated_at: u64,
+}
+
+/// Persistent draft store, one draft per recipient.
+#[derive(Clone)]
+pub struct Drafts {
+    backend: Arc<dyn StorageBackend>,
+}
+
+impl Drafts {
+    pub fn new(backend: Arc<dyn StorageBackend>) -> Self {
+        Self { backend }
+    }
+
+    fn key(recipient: &str) -> String {
+        format!("{}{}", DRAFT_PREFIX, recipient)
+    }
+
+    /// Save (or overwrite) the draft for `recipient`.
+    pub fn save(&self, recipient: &str, text: &str) -> Result<Draft, IronCoreError> {
+        let draft = Draft {
+            recipient: recipient.to_string(),
+            text: text.to_string(),
+            updated_at: current_timestamp(),
+        };
+        self.put(&draft)?;
+        Ok(draft)
+    }
+
+    fn put(&self, draft: &Draft) -> Result<(), IronCoreError> {
+        let value = serde_json::to_vec(draft).map_err(|_| IronCoreError::Internal)?;
+        self.backend
+            .put(Self::key(&draft.recipient).as_bytes(), &value)
+            .map_err(|_| IronCoreError::StorageError)?;
+        self.backend
+            .flush()
+            .map_err(|_| IronCoreError::StorageError)
+    }
+
+    pub fn get(&self, recipient: &str) -> Result<Option<Draft>, IronCoreError> {
+        match self
+            .backend
+            .get(Self::key(recipient).as_bytes())
+            .map_err(|_| IronCoreError::StorageError)?
+        {
+            Some(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|_| IronCoreError::CorruptionDetected),
+            None => Ok(None),
+        }
+    }
+
+    /// All drafts, most recently saved first.
+    pub fn list(&self) -> Result<Vec<Draft>, IronCoreError> {
+        let mut drafts: Vec<Draft> = self
+            .backend
+            .scan_prefix(DRAFT_PREFIX.as_bytes())
+            .map_err(|_| IronCoreError::StorageError)?
+            .into_iter()
+            .filter_map(|(_, value)| serde_json::from_slice(&value).ok())
+            .collect();
+        drafts.sort_by_key(|b| std::cmp::Reverse(b.updated_at));
+        Ok(drafts)
+    }
+
+    /// Delete the draft for `recipient`. Returns whether one existed.
+    pub fn delete(&self, recipient: &str) -> Result<bool, IronCoreError> {
+        Ok(self.take(recipient)?.is_some())
+    }
+
+    /// Remove and return the draft for `recipient`.
+    pub fn take(&self, recipient: &str) -> Result<Option<Draft>, IronCoreError> {
+        let draft = self.get(recipient)?;
+        if draft.is_some() {
+            self.backend
+                .remove(Self::key(recipient).as_bytes())
+                .map_err(|_| IronCoreError::StorageError)?;
+            self.backend
+                .flush()
+                .map_err(|_| IronCoreError::StorageError)?;
+        }
+        Ok(draft)
+    }
+
+    /// Put back a draft removed by `take`, unless a newer one was saved for
+    /// the same recipient in the meantime.
+    pub fn restore(&self, draft: &Draft) -> Result<(), IronCoreError> {
+        if self.get(&draft.recipient)?.is_some() {
+            return Ok(());
+        }
+        self.put(draft)
+    }
+}
+
+fn current_timestamp() -> u64 {
+    web_time::SystemTime::now()
+        .duration_since(web_time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::backend::MemoryStorage;
+
+    #[test]
+    fn test_save_get_list_delete() {
+        let drafts = Drafts::new(Arc::new(MemoryStorage::new()));
+        assert_eq!(drafts.get("aa").unwrap(), None);
+
+        drafts.save("aa", "first").unwrap();
+        drafts.save("bb", "other").unwrap();
+        drafts.save("aa", "second").unwrap();
+
+        assert_eq!(drafts.get("aa").unwrap().unwrap().text, "second");
+        assert_eq!(drafts.list().unwrap().len(), 2);
+
+        assert!(drafts.delete("aa").unwrap());
+        assert!(!drafts.delete("aa").unwrap());
+        assert_eq!(drafts.get("aa").unwrap(), None);
+        assert_eq!(drafts.get("bb").unwrap().unwrap().text, "other");
+    }
+
+    #[test]
+    fn test_restore_does_not_clobber_newer_draft() {
+        let drafts = Drafts::new(Arc::new(MemoryStorage::new()));
+        drafts.save("aa", "old").unwrap();
+        let taken = drafts.take("aa").unwrap().unwrap();
+
+        drafts.restore(&taken).unwrap();
+        assert_eq!(drafts.get("aa").unwrap().unwrap().text, "old");
+
+        drafts.take("aa").unwrap();
+        drafts.save("aa", "new").unwrap();
+        drafts.restore(&taken).unwrap();
+        assert_eq!(drafts.get("aa").unwrap().unwrap().text, "new");
+    }
+}
@@ -85,6 +85,94 @@ pub struct HistoryStats {
     pub undelivered_count: u32,
 }
 
+/// Opaque position in a conversation, handed back by `conversation_page`.
+/// Encodes the last returned record's timestamp plus its id as a tiebreak, so
+/// messages sharing a timestamp are neither skipped nor repeated.
+pub(crate) fn encode_history_cursor(timestamp: u64, id: &str) -> String {
+    hex::encode(format!("{}:{}", timestamp, id))
+}
+
+pub(crate) fn decode_history_cursor(cursor: &str) -> Option<(u64, String)> {
+    let raw = String::from_utf8(hex::decode(cursor).ok()?).ok()?;
+    let (timestamp, id) = raw.split_once(':')?;
+    Some((timestamp.parse().ok()?, id.to_string()))
+}
+
+fn conversation_index_prefix(peer_id: &str) -> Vec<u8> {
+    format!("conv:{}:", peer_id.to_ascii_lowercase()).into_bytes()
+}
+
+/// Key of a record in its conversation's index,
+/// `conv:<peer id, lowercased>:<zero-padded timestamp>:<id>`, mapping to the
+/// record id. A conversation's keys sort by (timestamp, id), so a page is
+/// one reverse range read rather than a load of the whole conversation.
+pub(crate) fn conversation_index_key(peer_id: &str, timestamp: u64, id: &str) -> Vec<u8> {
+    let mut key = conversation_index_prefix(peer_id);
+    key.extend_from_slice(format!("{:020}:{}", timestamp, id).as_bytes());
+    key
+}
+
+/// One page of `peer_id`'s conversation, newest first, read from its index
+/// in `index`; shared by this store and `mobile_bridge::HistoryManager`.
+/// `load` returns the visible record with an id, and `index_key` the key
+/// that record belongs under. Entries left behind by deleted, hidden or
+/// rewritten records are skipped and dropped from the index.
+pub(crate) fn read_conversation_page<R>(
+    index: &dyn StorageBackend,
+    peer_id: &str,
+    cursor: Option<&str>,
+    limit: u32,
+    load: impl Fn(&str) -> Result<Option<R>, IronCoreError>,
+    index_key: impl Fn(&R) -> Vec<u8>,
+) -> Result<(Vec<R>, Option<String>), IronCoreError> {
+    let prefix = conversation_index_prefix(peer_id);
+    let mut before = match cursor.filter(|c| !c.is_empty()) {
+        Some(c) => {
+            let (timestamp, id) = decode_history_cursor(c).ok_or(IronCoreError::InvalidInput)?;
+            conversation_index_key(peer_id, timestamp, &id)
+        }
+        None => [prefix.as_slice(), &[0xff]].concat(),
+    };
+    // One more than asked for, to learn whether there is a next page.
+    let wanted = limit as usize + 1;
+    let mut page: Vec<(Vec<u8>, R)> = Vec::new();
+    while page.len() < wanted {
+        let asked = wanted - page.len();
+        let entries = index
+            .scan_prefix_before(&prefix, &before, asked)
+            .map_err(|_| IronCoreError::StorageError)?;
+        let exhausted = entries.len() < asked;
+        for (key, id) in entries {
+            before = key.clone();
+            match load(&String::from_utf8_lossy(&id))? {
+                Some(record) if index_key(&record) == key => page.push((key, record)),
+                _ => {
+                    let _ = index.remove(&key);
+                }
+            }
+        }
+        if exhausted {
+            break;
+        }
+    }
+
+    let has_more = page.len() > limit as usize;
+    page.truncate(limit as usize);
+    let next_cursor = if has_more {
+        page.last().and_then(|(key, _)| {
+            let rest = std::str::from_utf8(&key[prefix.len()..]).ok()?;
+            let (timestamp, id) = rest.split_once(':')?;
+            Some(encode_history_cursor(timestamp.parse().ok()?, id))
+        })
+    } else {
+        None
+    };
+    Ok((
+        page.into_iter().map(|(_, record)| record).collect(),
+        next_cursor,
+    ))
+}
+
 #[derive(Clone)]
 pub struct HistoryManager {
     backend: Arc<dyn StorageBackend>,
@@ -92,7 +180,34 @@ pub struct HistoryManager {
 
 impl HistoryManager {
     pub fn new(backend: Arc<dyn StorageBackend>) -> Self {
-        Self { backend }
+        let manager = Self { backend };
+        manager.build_conversation_index();
+        manager
+    }
+
+    /// Index the records of a store written before conversations had an
+    /// index. A no-op once any index entry exists.
+    fn build_conversation_index(&self) {
+        if self.backend.count_prefix(b"conv:").unwrap_or(0) > 0 {
+            return;
+        }
+        let Ok(all) = self.backend.scan_prefix(b"msg_") else {
+            return;
+        };
+        for (_, value) in all {
+            if let Ok(record) = serde_json::from_slice::<MessageRecord>(&value) {
+                let _ = self.index(&record);
+            }
+        }
+    }
+
+    fn index(&self, record: &MessageRecord) -> Result<(), IronCoreError> {
+        self.backend
+            .put(
+                &conversation_index_key(&record.peer_id, record.timestamp, &record.id),
+                record.id.as_bytes(),
+            )
+            .map_err(|_| IronCoreError::StorageError)
     }
 
     /// P0_SECURITY_005: Expose the storage backend for audit log persistence.
@@ -106,7 +221,7 @@ impl HistoryManager {
         self.backend
             .put(key.as_bytes(), &value)
             .map_err(|_| IronCoreError::StorageError)?;
-        Ok(())
+        self.index(&record)
     }
 
     pub fn get(&self, id: String) -> Result<Option<MessageRecord>, IronCoreError> {
@@ -191,6 +306,26 @@ impl HistoryManager {
         self.recent(Some(peer_id), limit)
     }
 
+    /// One page of a conversation, newest first, for infinite scroll.
+    /// Pass `None` for the newest page, then the returned cursor to fetch the
+    /// next older page; the cursor is `None` once the conversation is
+    /// exhausted. Ordering is by timestamp, then id, so pages are stable.
+    pub fn conversation_page(
+        &self,
+        peer_id: String,
+        cursor: Option<String>,
+        limit: u32,
+    ) -> Result<(Vec<MessageRecord>, Option<String>), IronCoreError> {
+        read_conversation_page(
+            self.backend.as_ref(),
+            &peer_id,
+            cursor.as_deref(),
+            limit,
+            |id| Ok(self.get(id.to_string())?.filter(|r| !r.hidden)),
+            |r| conversation_index_key(&r.peer_id, r.timestamp, &r.id),
+        )
+    }
+
     /// Unhide all stored messages for a given peer (called on unblock).
     pub fn unhide_messages_for_peer(&self, peer_id: &str) -> Result<u32, IronCoreError> {
         let all = self
@@ -209,6 +344,8 @@ impl HistoryManager {
                 self.backend
                     .put(key.as_bytes(), &updated)
                     .map_err(|_| IronCoreError::StorageError)?;
+                // Paging dropped the entry while the record was hidden.
+                self.index(&record)?;
                 count += 1;
             }
         }
@@ -285,10 +422,36 @@ impl HistoryManager {
                     .map_err(|_| IronCoreError::StorageError)?;
             }
         }
+        self.remove_prefix(&conversation_index_prefix(&peer_id))
+    }
 
+    fn remove_prefix(&self, prefix: &[u8]) -> Result<(), IronCoreError> {
+        let all = self
+            .backend
+            .scan_prefix(prefix)
+            .map_err(|_| IronCoreError::StorageError)?;
+        for (key, _) in all {
+            self.backend
+                .remove(&key)
+                .map_err(|_| IronCoreError::StorageError)?;
+        }
         Ok(())
     }
 
+    /// Remove a record and its index entry.
+    fn remove_record(&self, key: &[u8], record: &MessageRecord) -> Result<(), IronCoreError> {
+        self.backend
+            .remove(key)
+            .map_err(|_| IronCoreError::StorageError)?;
+        self.backend
+            .remove(&conversation_index_key(
+                &record.peer_id,
+                record.timestamp,
+                &record.id,
+            ))
+            .map_err(|_| IronCoreError::StorageError)
+    }
+
     pub fn mark_delivered(&self, id: String) -> Result<(), IronCoreError> {
         tracing::info!("Attempting to mark message {} as delivered", id);
         if let Some(mut record) = self.get(id.clone())? {
@@ -305,25 +468,18 @@ impl HistoryManager {
     }
 
     pub fn clear(&self) -> Result<(), IronCoreError> {
-        let all = self
-            .backend
-            .scan_prefix(b"msg_")
-            .map_err(|_| IronCoreError::StorageError)?;
-
-        for (key, _) in all {
-            self.backend
-                .remove(&key)
-                .map_err(|_| IronCoreError::StorageError)?;
-        }
-        Ok(())
+        self.remove_prefix(b"msg_")?;
+        self.remove_prefix(b"conv:")
     }
 
     pub fn delete(&self, id: String) -> Result<(), IronCoreError> {
         let key = format!("msg_{}", id);
+        if let Ok(Some(record)) = self.get(id) {
+            return self.remove_record(key.as_bytes(), &record);
+        }
         self.backend
             .remove(key.as_bytes())
-            .map_err(|_| IronCoreError::StorageError)?;
-        Ok(())
+            .map_err(|_| IronCoreError::StorageError)
     }
 
     pub fn stats(&self) -> Result<HistoryStats, IronCoreError> {
@@ -390,10 +546,8 @@ impl HistoryManager {
         records.sort_by_key(|a| a.1.timestamp);
 
         let to_remove = records.len().saturating_sub(max_messages as usize);
-        for (key, _) in records.iter().take(to_remove) {
-            self.backend
-                .remove(key)
-                .map_err(|_| IronCoreError::StorageError)?;
+        for (key, record) in records.iter().take(to_remove) {
+            self.remove_record(key, record)?;
         }
 
         Ok(to_remove as u32)
@@ -410,9 +564,7 @@ impl HistoryManager {
             let record: MessageRecord =
                 serde_json::from_slice(&value).map_err(|_| IronCoreError::Internal)?;
             if record.timestamp < before_timestamp {
-                self.backend
-                    .remove(&key)
-                    .map_err(|_| IronCoreError::StorageError)?;
+                self.remove_record(&key, &record)?;
                 removed += 1;
             }
         }
@@ -568,4 +720,89 @@ mod tests {
         // Verify the corrupt record is skipped (meaning it was not removed)
         assert!(backend.get(corrupt_key).unwrap().is_some());
     }
+
+    #[test]
+    fn test_conversation_page_walks_history_in_stable_order() {
+        let history = HistoryManager::new(Arc::new(MemoryStorage::new()));
+        // Pairs of messages share a timestamp to exercise the id tiebreak.
+        for i in 0..100u64 {
+            history
+                .add(MessageRecord {
+                    id: format!("msg{:03}", i),
+                    peer_id: "peer_a".to_string(),
+                    direction: MessageDirection::Received,
+                    content: format!("message {}", i),
+                    timestamp: 1000 + i / 2,
+                    sender_timestamp: 1000 + i / 2,
+                    delivered: true,
+                    hidden: false,
+                })
+                .unwrap();
+        }
+        history
+            .add(MessageRecord::new_sent("peer_b".into(), "other".into()))
+            .unwrap();
+
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        let mut pages = 0;
+        loop {
+            let (page, next) = history
+                .conversation_page("peer_a".into(), cursor, 20)
+                .unwrap();
+            assert_eq!(page.len(), 20);
+            seen.extend(page.into_iter().map(|r| r.id));
+            pages += 1;
+            match next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(pages, 5);
+        let expected: Vec<String> = (0..100).rev().map(|i| format!("msg{:03}", i)).collect();
+        assert_eq!(seen, expected);
+
+        assert!(matches!(
+            history.conversation_page("peer_a".into(), Some("not-a-cursor".into()), 20),
+            Err(IronCoreError::InvalidInput)
+        ));
+    }
+
+    #[test]
+    fn test_conversation_page_indexes_existing_records_and_skips_hidden() {
+        let backend = Arc::new(MemoryStorage::new());
+        // Records written before conversations had an index.
+        for i in 1..=3u64 {
+            let record = MessageRecord {
+                id: format!("m{}", i),
+                peer_id: "peer_a".to_string(),
+                direction: MessageDirection::Received,
+                content: format!("message {}", i),
+                timestamp: i,
+                sender_timestamp: i,
+                delivered: true,
+                hidden: i == 2,
+            };
+            backend
+                .put(
+                    format!("msg_{}", record.id).as_bytes(),
+                    &serde_json::to_vec(&record).unwrap(),
+                )
+                .unwrap();
+        }
+        let history = HistoryManager::new(backend.clone());
+        let page_ids = || -> Vec<String> {
+            let (page, _) = history
+                .conversation_page("PEER_A".into(), None, 10)
+                .unwrap();
+            page.into_iter().map(|r| r.id).collect()
+        };
+
+        assert_eq!(page_ids(), vec!["m3", "m1"]);
+        history.unhide_messages_for_peer("peer_a").unwrap();
+        assert_eq!(page_ids(), vec!["m3", "m2", "m1"]);
+        history.delete("m3".to_string()).unwrap();
+        assert_eq!(page_ids(), vec!["m2", "m1"]);
+        assert_eq!(backend.count_prefix(b"conv:").unwrap(), 2);
+    }
 }
@@ -2,7 +2,9 @@
 //
 // Tracks seen message IDs to prevent replay attacks and duplicate delivery.
 
+use crate::message::MessageType;
 use crate::store::backend::StorageBackend;
+use crate::store::seen_bloom::SeenBloom;
 use crate::store::storage::StorageManager;
 
 use rustc_hash::FxHashSet;
@@ -10,6 +12,20 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 
+/// Record layout written before `message_type` existed (version byte 1).
+#[derive(Serialize, Deserialize)]
+struct ReceivedMessageV1 {
+    version: u8,
+    message_id: String,
+    sender_id: String,
+    payload: Vec<u8>,
+    received_at: u64,
+    sender_public_key_hex: Option<String>,
+}
+
+/// Version byte of records written by `persist`.
+const RECEIVED_MESSAGE_VERSION: u8 = 2;
+
 #[derive(Serialize, Deserialize)]
 struct LegacyReceivedMessage {
     pub message_id: String,
@@ -25,11 +41,22 @@ fn deserialize_received_message(data: &[u8]) -> Result<ReceivedMessage, bincode:
             "empty",
         ))));
     }
-    // If the first byte is 1, it's the new versioned format.
+    // A first byte of 1 or 2 is the versioned format.
     // Legacy format starts with a String length. Since string lengths are usually 36 (for UUIDs),
     // their first byte is 36, not 1.
-    if data[0] == 1 {
+    if data[0] == RECEIVED_MESSAGE_VERSION {
         bincode::deserialize(data)
+    } else if data[0] == 1 {
+        let v1: ReceivedMessageV1 = bincode::deserialize(data)?;
+        Ok(ReceivedMessage {
+            version: v1.version,
+            message_id: v1.message_id,
+            sender_id: v1.sender_id,
+            payload: v1.payload,
+            received_at: v1.received_at,
+            sender_public_key_hex: v1.sender_public_key_hex,
+            message_type: None,
+        })
     } else {
         let legacy: LegacyReceivedMessage = bincode::deserialize(data)?;
         Ok(ReceivedMessage {
@@ -39,10 +66,19 @@ fn deserialize_received_message(data: &[u8]) -> Result<ReceivedMessage, bincode:
             payload: legacy.payload,
             received_at: legacy.received_at,
             sender_public_key_hex: None,
+            message_type: None,
         })
     }
 }
 
+/// Text body of a stored payload, or `None` for receipts and binary payloads.
+fn searchable_text(payload: &[u8]) -> Option<&str> {
+    if crate::message::types::decode_receipt(payload).is_ok() {
+        return None;
+    }
+    std::str::from_utf8(payload).ok()
+}
+
 /// Maximum tracked message IDs (for deduplication)
 const MAX_SEEN_IDS: usize = 50_000;
 
@@ -72,6 +108,10 @@ pub struct ReceivedMessage {
     /// broadcast. `None` for messages received before this field existed.
     #[serde(default)]
     pub sender_public_key_hex: Option<String>,
+    /// Type of the message as it arrived. `None` for messages received
+    /// before this field existed.
+    #[serde(default)]
+    pub message_type: Option<MessageType>,
 }
 
 fn default_version() -> u8 {
@@ -93,6 +133,10 @@ enum InboxBackend {
 pub struct Inbox {
     backend: InboxBackend,
     storage_manager: Option<Arc<StorageManager>>,
+    /// Received messages whose persistent write failed (e.g. disk full),
+    /// held in memory until `retry_overflow` manages to store them.
+    /// Non-empty means the inbox is running degraded.
+    overflow: Vec<ReceivedMessage>,
 }
 
 impl Inbox {
@@ -106,6 +150,7 @@ impl Inbox {
                 total: 0,
             },
             storage_manager: None,
+            overflow: Vec::new(),
         }
     }
 
@@ -117,6 +162,7 @@ impl Inbox {
         Self {
             backend: InboxBackend::Persistent(backend),
             storage_manager: Some(storage_manager),
+            overflow: Vec::new(),
         }
     }
 
@@ -125,6 +171,7 @@ impl Inbox {
         Self {
             backend: InboxBackend::Persistent(backend),
             storage_manager: None,
+            overflow: Vec::new(),
         }
     }
 
@@ -153,18 +200,116 @@ impl Inbox {
         }
     }
 
+    fn persist(db: &Arc<dyn StorageBackend>, msg: &ReceivedMessage) -> Result<(), String> {
+        let key_str = format!(
+            "{}{}_{}",
+            String::from_utf8_lossy(MESSAGES_PREFIX),
+            msg.sender_id,
+            msg.message_id
+        );
+        let record = ReceivedMessage {
+            version: RECEIVED_MESSAGE_VERSION,
+            ..msg.clone()
+        };
+        let bytes = bincode::serialize(&record).map_err(|e| e.to_string())?;
+        db.put(key_str.as_bytes(), &bytes)?;
+        db.flush()
+    }
+
+    /// Whether any received message is held only in memory because storing
+    /// it failed. Such messages are lost if the process exits first.
+    pub fn is_degraded(&self) -> bool {
+        !self.overflow.is_empty()
+    }
+
+    /// Try again to persist messages held in memory after a failed write.
+    /// Returns how many are still held. Called on every receive and drain.
+    pub fn retry_overflow(&mut self) -> usize {
+        let InboxBackend::Persistent(db) = &self.backend else {
+            return 0;
+        };
+        if self.overflow.is_empty() {
+            return 0;
+        }
+        let before = self.overflow.len();
+        self.overflow.retain(|msg| Self::persist(db, msg).is_err());
+        if self.overflow.is_empty() {
+            tracing::info!(
+                "Inbox storage recovered: persisted {} held message(s)",
+                before
+            );
+        }
+        self.overflow.len()
+    }
+
     /// Record a received message. Returns false if duplicate.
     pub fn receive(&mut self, msg: ReceivedMessage) -> bool {
         let hash = *blake3::hash(msg.message_id.as_bytes()).as_bytes();
+        if !self.record_seen(hash) {
+            return false; // Duplicate
+        }
+        self.retry_overflow();
         let is_new = match &mut self.backend {
+            InboxBackend::Memory {
+                messages, total, ..
+            } => {
+                // Store message
+                messages
+                    .entry(msg.sender_id.clone())
+                    .or_default()
+                    .push(msg.clone());
+                *total += 1;
+
+                true // New message
+            }
+            InboxBackend::Persistent(db) => {
+                // Store message. A failed write keeps the message in memory
+                // rather than dropping it; it is persisted on a later retry.
+                if let Err(e) = Self::persist(db, &msg) {
+                    tracing::warn!(
+                        "Inbox storage write failed ({}); holding message {} in memory (degraded mode)",
+                        e,
+                        msg.message_id
+                    );
+                    self.overflow.push(msg.clone());
+                }
+
+                true // New message
+            }
+        };
+
+        if is_new {
+            tracing::info!(
+                event = "inbox_receive",
+                message_id = %msg.message_id,
+                sender_id = %msg.sender_id,
+                received_at = msg.received_at
+            );
+        }
+
+        // Trigger maintenance after successful receive
+        self.trigger_maintenance();
+
+        is_new
+    }
+
+    /// Record a message ID as seen without storing a message, for traffic
+    /// that shares the inbox's dedup window but not its storage (e.g.
+    /// broadcasts). Returns false if the ID was already seen.
+    pub fn mark_seen(&mut self, message_id: &str) -> bool {
+        self.record_seen(*blake3::hash(message_id.as_bytes()).as_bytes())
+    }
+
+    /// Add a message-ID hash to the dedup set. Returns false if present.
+    fn record_seen(&mut self, hash: [u8; 32]) -> bool {
+        match &mut self.backend {
             InboxBackend::Memory {
                 seen_ids,
                 seen_order,
-                messages,
-                total,
+                ..
             } => {
                 if seen_ids.contains(&hash) {
-                    return false; // Duplicate
+                    return false;
                 }
 
                 // Track for dedup
@@ -178,15 +323,7 @@ impl Inbox {
                         seen_ids.remove(&old_hash);
                     }
                 }
-
-                // Store message
-                messages
-                    .entry(msg.sender_id.clone())
-                    .or_default()
-                    .push(msg.clone());
-                *total += 1;
-
-                true // New message
+                true
             }
             InboxBackend::Persistent(db) => {
                 // Load seen IDs
@@ -198,7 +335,7 @@ impl Inbox {
                     .unwrap_or_default();
 
                 if seen_ids.contains(&hash) {
-                    return false; // Duplicate
+                    return false;
                 }
 
                 // Add to seen set
@@ -216,36 +353,50 @@ impl Inbox {
                 if let Ok(bytes) = bincode::serialize(&seen_ids) {
                     let _ = db.put(SEEN_IDS_KEY, &bytes);
                 }
-
-                // Store message
-                let key_str = format!(
-                    "{}{}_{}",
-                    String::from_utf8_lossy(MESSAGES_PREFIX),
-                    msg.sender_id,
-                    msg.message_id
-                );
-                if let Ok(bytes) = bincode::serialize(&msg) {
-                    let _ = db.put(key_str.as_bytes(), &bytes);
-                    let _ = db.flush();
-                }
-
-                true // New message
+                true
             }
-        };
+        }
+    }
 
-        if is_new {
-            tracing::info!(
-                event = "inbox_receive",
-                message_id = %msg.message_id,
-                sender_id = %msg.sender_id,
-                received_at = msg.received_at
-            );
+    /// Hashes of every message ID in the dedup window.
+    fn seen_hashes(&self) -> FxHashSet<[u8; 32]> {
+        match &self.backend {
+            InboxBackend::Memory { seen_ids, .. } => seen_ids.clone(),
+            InboxBackend::Persistent(db) => db
+                .get(SEEN_IDS_KEY)
+                .ok()
+                .flatten()
+                .and_then(|bytes| bincode::deserialize(&bytes).ok())
+                .unwrap_or_default(),
         }
+    }
 
-        // Trigger maintenance after successful receive
-        self.trigger_maintenance();
+    /// Bloom filter of seen message IDs, for a peer device to work out what
+    /// to push here without exchanging the IDs themselves. See
+    /// `store::seen_bloom` for the false-positive tradeoff.
+    pub fn seen_bloom(&self) -> Vec<u8> {
+        let seen = self.seen_hashes();
+        let mut bloom = SeenBloom::with_capacity(seen.len());
+        for hash in &seen {
+            bloom.insert_digest(hash);
+        }
+        bloom.to_bytes()
+    }
 
-        is_new
+    /// The subset of `candidate_ids` that the device which exported
+    /// `peer_bloom` has not seen, in input order. A false positive in the
+    /// filter means a missing message is occasionally left out; a message
+    /// the peer has seen is never returned. A malformed filter returns every
+    /// candidate, since the peer deduplicates on receipt anyway.
+    pub fn missing_from_bloom(&self, peer_bloom: &[u8], candidate_ids: &[String]) -> Vec<String> {
+        match SeenBloom::from_bytes(peer_bloom) {
+            Some(bloom) => candidate_ids
+                .iter()
+                .filter(|id| !bloom.contains(id))
+                .cloned()
+                .collect(),
+            None => candidate_ids.to_vec(),
+        }
     }
 
     /// Get all messages from a specific sender
@@ -257,14 +408,19 @@ impl Inbox {
             InboxBackend::Persistent(db) => {
                 let prefix_str =
                     format!("{}{}_", String::from_utf8_lossy(MESSAGES_PREFIX), sender_id);
-                if let Ok(results) = db.scan_prefix(prefix_str.as_bytes()) {
-                    results
-                        .into_iter()
-                        .filter_map(|(_, value)| deserialize_received_message(&value).ok())
-                        .collect()
-                } else {
-                    Vec::new()
-                }
+                let mut messages: Vec<ReceivedMessage> = db
+                    .scan_prefix(prefix_str.as_bytes())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|(_, value)| deserialize_received_message(&value).ok())
+                    .collect();
+                messages.extend(
+                    self.overflow
+                        .iter()
+                        .filter(|msg| msg.sender_id == sender_id)
+                        .cloned(),
+                );
+                messages
             }
         }
     }
@@ -276,23 +432,81 @@ impl Inbox {
                 messages.values().flat_map(|msgs| msgs.clone()).collect()
             }
             InboxBackend::Persistent(db) => {
-                if let Ok(results) = db.scan_prefix(MESSAGES_PREFIX) {
-                    results
-                        .into_iter()
-                        .filter_map(|(_, value)| deserialize_received_message(&value).ok())
-                        .collect()
-                } else {
-                    Vec::new()
-                }
+                let mut messages: Vec<ReceivedMessage> = db
+                    .scan_prefix(MESSAGES_PREFIX)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|(_, value)| deserialize_received_message(&value).ok())
+                    .collect();
+                messages.extend(self.overflow.iter().cloned());
+                messages
             }
         }
     }
 
+    /// Case-insensitive substring search over text payloads, newest first,
+    /// stopping once `limit` matches are found (0 = no limit).
+    ///
+    /// This is a linear scan that decodes every stored payload, which is fine
+    /// for inbox-sized data sets but is not an index. Receipts and payloads
+    /// that are not UTF-8 text are skipped.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<ReceivedMessage> {
+        let needle = query.to_lowercase();
+        let limit = if limit == 0 { usize::MAX } else { limit };
+        let mut messages = self.all_messages();
+        messages.sort_by_key(|b| std::cmp::Reverse(b.received_at));
+        messages
+            .into_iter()
+            .filter(|m| {
+                searchable_text(&m.payload).is_some_and(|t| t.to_lowercase().contains(&needle))
+            })
+            .take(limit)
+            .collect()
+    }
+
+    /// Messages from one sender and/or of one type, newest first, skipping
+    /// `offset` matches and returning at most `limit` (0 = no limit).
+    ///
+    /// Like `search`, a linear scan. `sender_public_key_hex` matches the key
+    /// recorded at receive time, so messages stored before that field existed
+    /// never match a sender filter. With a type filter, payloads that decode
+    /// as no known type are skipped.
+    pub fn filtered(
+        &self,
+        sender_public_key_hex: Option<&str>,
+        message_type: Option<MessageType>,
+        limit: usize,
+        offset: usize,
+    ) -> Vec<ReceivedMessage> {
+        let limit = if limit == 0 { usize::MAX } else { limit };
+        let mut messages = self.all_messages();
+        messages.sort_by_key(|b| std::cmp::Reverse(b.received_at));
+        messages
+            .into_iter()
+            .filter(|m| {
+                sender_public_key_hex.is_none_or(|want| {
+                    m.sender_public_key_hex
+                        .as_deref()
+                        .is_some_and(|have| have.eq_ignore_ascii_case(want))
+                })
+            })
+            .filter(|m| {
+                message_type
+                    .as_ref()
+                    .is_none_or(|want| m.message_type.as_ref() == Some(want))
+            })
+            .skip(offset)
+            .take(limit)
+            .collect()
+    }
+
     /// Total stored messages
     pub fn total_count(&self) -> usize {
         match &self.backend {
             InboxBackend::Memory { total, .. } => *total,
-            InboxBackend::Persistent(db) => db.count_prefix(MESSAGES_PREFIX).unwrap_or(0),
+            InboxBackend::Persistent(db) => {
+                db.count_prefix(MESSAGES_PREFIX).unwrap_or(0) + self.overflow.len()
+            }
         }
     }
 
@@ -343,11 +557,52 @@ impl Inbox {
                     let _ = db.remove(key);
                 }
                 let _ = db.flush();
+                drained.append(&mut self.overflow);
                 drained
             }
         }
     }
 
+    /// Remove messages received before `cutoff` (unix seconds), returning
+    /// how many were removed. Dedup IDs are kept, so a swept message that is
+    /// relayed again is still recognised as a duplicate.
+    ///
+    /// `received_at` is compared in seconds whichever unit it was stored in:
+    /// `IronCore::receive_message` records milliseconds.
+    pub fn prune_older_than(&mut self, cutoff: u64) -> usize {
+        let mut pruned = 0;
+        match &mut self.backend {
+            InboxBackend::Memory {
+                messages, total, ..
+            } => {
+                for msgs in messages.values_mut() {
+                    let before = msgs.len();
+                    msgs.retain(|m| received_at_secs(m) >= cutoff);
+                    pruned += before - msgs.len();
+                }
+                messages.retain(|_, msgs| !msgs.is_empty());
+                *total -= pruned;
+            }
+            InboxBackend::Persistent(db) => {
+                for (key, value) in db.scan_prefix(MESSAGES_PREFIX).unwrap_or_default() {
+                    let expired = deserialize_received_message(&value)
+                        .map(|m| received_at_secs(&m) < cutoff)
+                        .unwrap_or(false);
+                    if expired && db.remove(&key).is_ok() {
+                        pruned += 1;
+                    }
+                }
+                if pruned > 0 {
+                    let _ = db.flush();
+                }
+                let before = self.overflow.len();
+                self.overflow.retain(|m| received_at_secs(m) >= cutoff);
+                pruned += before - self.overflow.len();
+            }
+        }
+        pruned
+    }
+
     /// Clear all messages (but keep dedup IDs)
     pub fn clear_messages(&mut self) {
         match &mut self.backend {
@@ -359,6 +614,7 @@ impl Inbox {
             }
             InboxBackend::Persistent(db) => {
                 // Remove all message keys (but keep seen IDs)
+                self.overflow.clear();
                 if let Ok(results) = db.scan_prefix(MESSAGES_PREFIX) {
                     for (key, _) in results {
                         let _ = db.remove(&key);
@@ -370,6 +626,16 @@ impl Inbox {
     }
 }
 
+/// `received_at` in seconds; values past year 2286 in seconds can only be
+/// milliseconds (same heuristic as the outbox's `next_retry_at`).
+fn received_at_secs(msg: &ReceivedMessage) -> u64 {
+    if msg.received_at > 10_000_000_000 {
+        msg.received_at / 1000
+    } else {
+        msg.received_at
+    }
+}
+
 impl Default for Inbox {
     fn default() -> Self {
         Self::new()
@@ -391,7 +657,175 @@ mod tests {
                 .unwrap_or_default()
                 .as_secs(),
             sender_public_key_hex: None,
+            message_type: Some(MessageType::Text),
+        }
+    }
+
+    #[test]
+    fn test_seen_bloom_has_no_false_negatives() {
+        let mut device_a = Inbox::new();
+        let ids: Vec<String> = (0..1000).map(|i| format!("msg-{}", i)).collect();
+        for id in &ids {
+            assert!(device_a.mark_seen(id));
+        }
+        let bloom = device_a.seen_bloom();
+
+        // Device B holds everything A has seen plus 1000 more. Nothing A has
+        // seen may be pushed again.
+        let device_b = Inbox::new();
+        let extra: Vec<String> = (1000..2000).map(|i| format!("msg-{}", i)).collect();
+        let mut candidates = ids.clone();
+        candidates.extend(extra.iter().cloned());
+        let missing = device_b.missing_from_bloom(&bloom, &candidates);
+        assert!(missing.iter().all(|id| extra.contains(id)));
+
+        // False positives stay near the configured 1% rate.
+        assert!(
+            missing.len() >= 960,
+            "too many false positives: {}",
+            missing.len()
+        );
+
+        // A malformed filter falls back to pushing everything.
+        assert_eq!(device_b.missing_from_bloom(b"junk", &ids), ids);
+    }
+
+    #[test]
+    fn test_failed_write_keeps_received_message() {
+        use crate::store::backend::FlakyStorage;
+        let storage = FlakyStorage::default();
+        let mut inbox = Inbox::persistent(Arc::new(storage.clone()));
+
+        storage.set_fail_writes(true);
+        assert!(inbox.receive(make_received("m1", "alice", "hello")));
+        assert!(inbox.is_degraded());
+        assert_eq!(inbox.messages_from("alice").len(), 1);
+        assert_eq!(inbox.total_count(), 1);
+
+        storage.set_fail_writes(false);
+        assert_eq!(inbox.retry_overflow(), 0);
+        assert!(!inbox.is_degraded());
+        assert_eq!(inbox.total_count(), 1);
+        assert_eq!(inbox.all_messages()[0].message_id, "m1");
+    }
+
+    #[test]
+    fn test_filtered_by_sender_type_and_both() {
+        let alice_key = "aa".repeat(32);
+        let bob_key = "bb".repeat(32);
+        let receipt = crate::message::types::encode_receipt(&crate::Receipt {
+            message_id: "m1".into(),
+            status: crate::DeliveryStatus::Delivered,
+            timestamp: 0,
+        })
+        .unwrap();
+        let seed =
+            |id: &str, key: &str, message_type: Option<MessageType>, payload: Vec<u8>, at: u64| {
+                let mut m = make_received(id, "sender", "");
+                m.sender_public_key_hex = Some(key.to_string());
+                m.message_type = message_type;
+                m.payload = payload;
+                m.received_at = at;
+                m
+            };
+        let (text, receipt_type) = (Some(MessageType::Text), Some(MessageType::Receipt));
+
+        let mut inbox = Inbox::new();
+        inbox.receive(seed(
+            "a-text-1",
+            &alice_key,
+            text.clone(),
+            b"hi".to_vec(),
+            100,
+        ));
+        inbox.receive(seed(
+            "a-text-2",
+            &alice_key,
+            text.clone(),
+            b"again".to_vec(),
+            300,
+        ));
+        inbox.receive(seed(
+            "a-receipt",
+            &alice_key,
+            receipt_type.clone(),
+            receipt.clone(),
+            200,
+        ));
+        inbox.receive(seed("a-untyped", &alice_key, None, b"old".to_vec(), 400));
+        inbox.receive(seed("b-text", &bob_key, text, b"yo".to_vec(), 150));
+        // The stored type wins over what the payload looks like.
+        inbox.receive(seed(
+            "b-receipt",
+            &bob_key,
+            receipt_type,
+            b"not a receipt".to_vec(),
+            250,
+        ));
+
+        let ids = |msgs: Vec<ReceivedMessage>| -> Vec<String> {
+            msgs.into_iter().map(|m| m.message_id).collect()
+        };
+
+        // By sender: every alice message, newest first, key case ignored.
+        assert_eq!(
+            ids(inbox.filtered(Some(&alice_key.to_uppercase()), None, 0, 0)),
+            vec!["a-untyped", "a-text-2", "a-receipt", "a-text-1"]
+        );
+        // By type: records from before types were stored are skipped.
+        assert_eq!(
+            ids(inbox.filtered(None, Some(MessageType::Text), 0, 0)),
+            vec!["a-text-2", "b-text", "a-text-1"]
+        );
+        assert_eq!(
+            ids(inbox.filtered(None, Some(MessageType::Receipt), 0, 0)),
+            vec!["b-receipt", "a-receipt"]
+        );
+        // Combined, with paging.
+        assert_eq!(
+            ids(inbox.filtered(Some(&alice_key), Some(MessageType::Text), 0, 0)),
+            vec!["a-text-2", "a-text-1"]
+        );
+        assert_eq!(
+            ids(inbox.filtered(Some(&alice_key), Some(MessageType::Text), 1, 1)),
+            vec!["a-text-1"]
+        );
+        assert!(inbox
+            .filtered(Some(&bob_key), Some(MessageType::Broadcast), 0, 0)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_search_is_case_insensitive_newest_first_and_limited() {
+        let mut inbox = Inbox::new();
+        let mut old = make_received("msg1", "alice", "Meet at the Bridge");
+        old.received_at = 100;
+        let mut new = make_received("msg2", "bob", "bridge is closed");
+        new.received_at = 200;
+        let receipt = crate::message::types::encode_receipt(&crate::Receipt {
+            message_id: "bridge".into(),
+            status: crate::DeliveryStatus::Delivered,
+            timestamp: 300,
+        })
+        .unwrap();
+        let mut ack = make_received("msg3", "bob", "");
+        ack.payload = receipt;
+        ack.received_at = 300;
+        let mut binary = make_received("msg4", "carol", "");
+        binary.payload = vec![0xff, 0xfe, b'b'];
+
+        for m in [old, new, ack, binary] {
+            inbox.receive(m);
         }
+
+        let hits: Vec<_> = inbox
+            .search("BRIDGE", 0)
+            .into_iter()
+            .map(|m| m.message_id)
+            .collect();
+        assert_eq!(hits, vec!["msg2", "msg1"]);
+        assert_eq!(inbox.search("bridge", 1)[0].message_id, "msg2");
+        assert!(inbox.search("ferry", 0).is_empty());
     }
 
     #[test]
@@ -450,6 +884,38 @@ mod tests {
         assert!(inbox.is_duplicate("msg1"));
     }
 
+    #[test]
+    fn test_prune_older_than_keeps_recent_and_dedup_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("inbox_store").to_str().unwrap().to_string();
+        let backend = Arc::new(crate::store::backend::SledStorage::new(&path).unwrap());
+
+        for mut inbox in [Inbox::new(), Inbox::persistent(backend)] {
+            let mut old = make_received("old", "alice", "stale");
+            old.received_at = 100;
+            let mut old_bob = make_received("old-bob", "bob", "stale");
+            old_bob.received_at = 150;
+            let mut new = make_received("new", "alice", "fresh");
+            new.received_at = 500;
+            // Stored in milliseconds, as `IronCore::receive_message` does.
+            let mut new_ms = make_received("new-ms", "bob", "fresh");
+            new_ms.received_at = 1_700_000_000_000;
+            let mut old_ms = make_received("old-ms", "bob", "stale");
+            old_ms.received_at = 1_600_000_000_000;
+            for m in [old, old_bob, new, new_ms, old_ms] {
+                inbox.receive(m);
+            }
+
+            assert_eq!(inbox.prune_older_than(200), 2);
+            assert_eq!(inbox.total_count(), 3);
+            assert_eq!(inbox.sender_count(), 2);
+            assert!(inbox.is_duplicate("old"));
+            assert_eq!(inbox.prune_older_than(200), 0);
+            assert_eq!(inbox.prune_older_than(1_650_000_000), 2);
+            assert_eq!(inbox.all_messages()[0].message_id, "new-ms");
+        }
+    }
+
     #[test]
     fn test_persistent_inbox() {
         use tempfile::tempdir;
@@ -501,9 +967,27 @@ mod tests {
 
             let all = inbox.all_messages();
             assert_eq!(all.len(), 2);
+            assert!(all
+                .iter()
+                .all(|m| m.message_type == Some(MessageType::Text)));
         }
     }
 
+    #[test]
+    fn test_version_1_record_decodes_without_message_type() {
+        let v1 = ReceivedMessageV1 {
+            version: 1,
+            message_id: "msg1".to_string(),
+            sender_id: "alice".to_string(),
+            payload: b"hello".to_vec(),
+            received_at: 1_700_000_000,
+            sender_public_key_hex: Some("aa".repeat(32)),
+        };
+        let decoded = deserialize_received_message(&bincode::serialize(&v1).unwrap()).unwrap();
+        assert_eq!(decoded.sender_public_key_hex, v1.sender_public_key_hex);
+        assert_eq!(decoded.message_type, None);
+    }
+
     #[test]
     fn test_drain_received_messages() {
         let mut inbox = Inbox::new();
@@ -277,6 +277,10 @@ pub struct LedgerExchangeRequest {
     pub sender_peer_id: String,
     /// Protocol version for forward compatibility
     pub version: u32,
+    /// Pull request: ask the remote to answer with its own known peers
+    /// (`SwarmHandle::request_peers`). Absent on older nodes.
+    #[serde(default)]
+    pub want_peers: bool,
 }
 
 /// Ledger exchange response — reciprocal sharing.
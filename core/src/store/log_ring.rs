@@ -0,0 +1,107 @@
+//! In-memory ring of recent warnings and errors
+//!
+//! `LogRingLayer` keeps the last `LOG_RING_CAPACITY` WARN and ERROR events in
+//! process memory so a support bundle (`IronCore::export_support_bundle`) can
+//! show what went wrong recently without shipping the log files. The ring is
+//! process-global, like the subscriber it is installed into.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+
+/// Most entries kept; the oldest is dropped first.
+pub const LOG_RING_CAPACITY: usize = 200;
+
+static RECENT: Mutex<VecDeque<LogRingEntry>> = Mutex::new(VecDeque::new());
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogRingEntry {
+    pub timestamp_ms: u64,
+    pub level: String,
+    pub target: String,
+    /// The event's message followed by its other fields as `name=value`.
+    pub message: String,
+}
+
+/// Tracing layer that records WARN and ERROR events into the ring.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogRingLayer;
+
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0.insert_str(0, &format!("{:?}", value));
+        } else {
+            self.0.push_str(&format!(" {}={:?}", field.name(), value));
+        }
+    }
+}
+
+impl<S: Subscriber> tracing_subscriber::Layer<S> for LogRingLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        // More verbose levels compare greater.
+        if *metadata.level() > Level::WARN {
+            return;
+        }
+        let mut visitor = MessageVisitor(String::new());
+        event.record(&mut visitor);
+        push(LogRingEntry {
+            timestamp_ms: web_time::SystemTime::now()
+                .duration_since(web_time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            level: metadata.level().to_string(),
+            target: metadata.target().to_string(),
+            message: visitor.0,
+        });
+    }
+}
+
+fn push(entry: LogRingEntry) {
+    let mut recent = RECENT.lock().unwrap_or_else(|e| e.into_inner());
+    if recent.len() == LOG_RING_CAPACITY {
+        recent.pop_front();
+    }
+    recent.push_back(entry);
+}
+
+/// Recorded warnings and errors, oldest first.
+pub fn recent_log_entries() -> Vec<LogRingEntry> {
+    RECENT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_ring_keeps_warnings_and_errors_only() {
+        let subscriber = tracing_subscriber::registry().with(LogRingLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("log-ring-test info");
+            tracing::warn!(peer = "abc", "log-ring-test warning");
+            tracing::error!("log-ring-test error");
+        });
+
+        let ours: Vec<_> = recent_log_entries()
+            .into_iter()
+            .filter(|e| e.message.starts_with("log-ring-test"))
+            .collect();
+        assert_eq!(ours.len(), 2);
+        assert_eq!(ours[0].level, "WARN");
+        assert_eq!(ours[0].message, "log-ring-test warning peer=\"abc\"");
+        assert_eq!(ours[1].level, "ERROR");
+    }
+}
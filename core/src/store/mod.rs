@@ -4,30 +4,45 @@ pub mod backend;
 pub mod blocked;
 pub mod contacts;
 pub mod dedup;
+pub mod delivery;
+pub mod drafts;
 pub mod history;
 pub mod inbox;
 pub mod ledger_entry;
+pub mod log_ring;
 pub mod logs;
 pub mod outbox;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod preflight;
 pub mod relay_custody;
+pub mod seen_bloom;
 pub mod storage;
 pub mod sweeper;
 pub mod tracing_init;
 pub mod transport_memory;
+pub mod wipe;
 
 pub use backend::StorageBackend;
 // Note: BlockedIdentity/BlockedManager exported through blocked_bridge for UniFFI
-pub use contacts::{Contact, ContactManager};
+pub use contacts::{
+    Contact, ContactBundleEntry, ContactImportReport, ContactLookup, ContactManager,
+    SignedContactBundle, CONTACT_BUNDLE_VERSION,
+};
 pub use dedup::{DedupAggregateStats, DedupStats, DedupStatsTracker};
+pub use delivery::{DeliveryLog, DeliveryReport, DeliveryState};
+pub use drafts::{Draft, Drafts};
 pub use history::{HistoryManager, HistoryStats, MessageDirection, MessageRecord};
 pub use inbox::{Inbox, ReceivedMessage};
 pub use ledger_entry::*;
-pub use outbox::{Outbox, QueuedMessage};
+pub use outbox::{Outbox, OutboxReconcileReport, QueuedMessage};
+#[cfg(not(target_arch = "wasm32"))]
+pub use preflight::{preflight_storage, DEFAULT_MIN_FREE_BYTES};
 pub use relay_custody::{
     CustodyCompatMode, CustodyEnforcement, CustodyError, CustodyMessage, CustodyState,
     CustodyTransition, RegistrationState, RegistrationStateInfo, RegistrationTransition,
     RelayCustodyStore, RelayRegistry,
 };
+pub use seen_bloom::SeenBloom;
 pub use storage::{DiskStats, RetentionConfig, StorageManager};
 pub use sweeper::*;
 pub use transport_memory::*;
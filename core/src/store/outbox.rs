@@ -78,6 +78,19 @@ pub enum MessageState {
     Failed,
 }
 
+/// Outcome of `IronCore::reconcile_outbox_after_import`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutboxReconcileReport {
+    /// Already sealed by the active identity.
+    pub kept: u32,
+    /// Re-encrypted and re-signed under the active identity from sent history.
+    pub resealed: u32,
+    /// Sealed by another identity with no plaintext left to re-seal from.
+    pub dropped: u32,
+    /// Not a plain wire envelope (e.g. onion-wrapped); left untouched.
+    pub unreadable: u32,
+}
+
 /// A queued outbound message
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueuedMessage {
@@ -136,6 +149,10 @@ enum OutboxBackend {
 pub struct Outbox {
     backend: OutboxBackend,
     storage_manager: Option<Arc<StorageManager>>,
+    /// Messages whose persistent write failed (e.g. disk full), held in
+    /// memory until `retry_overflow` manages to store them. Non-empty means
+    /// the outbox is running degraded.
+    overflow: Vec<QueuedMessage>,
 }
 
 impl Outbox {
@@ -147,6 +164,7 @@ impl Outbox {
                 total: 0,
             },
             storage_manager: None,
+            overflow: Vec::new(),
         }
     }
 
@@ -158,6 +176,7 @@ impl Outbox {
         Self {
             backend: OutboxBackend::Persistent(backend),
             storage_manager: Some(storage_manager),
+            overflow: Vec::new(),
         }
     }
 
@@ -166,6 +185,7 @@ impl Outbox {
         Self {
             backend: OutboxBackend::Persistent(backend),
             storage_manager: None,
+            overflow: Vec::new(),
         }
     }
 
@@ -222,6 +242,61 @@ impl Outbox {
         }
     }
 
+    fn persist(
+        db: &Arc<dyn StorageBackend>,
+        msg: &QueuedMessage,
+    ) -> std::result::Result<(), String> {
+        let key_str = format!(
+            "{}{}_{}",
+            String::from_utf8_lossy(QUEUE_PREFIX),
+            msg.recipient_id,
+            msg.message_id
+        );
+        let bytes = bincode::serialize(msg).map_err(|e| e.to_string())?;
+        db.put(key_str.as_bytes(), &bytes)?;
+        db.flush()
+    }
+
+    /// Whether any queued message is held only in memory because storing
+    /// it failed. Such messages are lost if the process exits first.
+    pub fn is_degraded(&self) -> bool {
+        !self.overflow.is_empty()
+    }
+
+    /// Try again to persist messages held in memory after a failed write.
+    /// Returns how many are still held. Called before every enqueue and
+    /// drain, so recovery needs no separate timer.
+    pub fn retry_overflow(&mut self) -> usize {
+        let OutboxBackend::Persistent(db) = &self.backend else {
+            return 0;
+        };
+        if self.overflow.is_empty() {
+            return 0;
+        }
+        let before = self.overflow.len();
+        self.overflow.retain(|msg| Self::persist(db, msg).is_err());
+        if self.overflow.is_empty() {
+            tracing::info!(
+                "Outbox storage recovered: persisted {} held message(s)",
+                before
+            );
+        }
+        self.overflow.len()
+    }
+
+    /// Remove and return held messages for `recipient_id` that `keep` allows.
+    fn take_overflow_for(
+        &mut self,
+        recipient_id: &str,
+        keep: impl Fn(&QueuedMessage) -> bool,
+    ) -> Vec<QueuedMessage> {
+        let (taken, rest): (Vec<_>, Vec<_>) = std::mem::take(&mut self.overflow)
+            .into_iter()
+            .partition(|msg| msg.recipient_id == recipient_id && keep(msg));
+        self.overflow = rest;
+        taken
+    }
+
     /// Queue a message for delivery
     pub fn enqueue(&mut self, msg: QueuedMessage) -> std::result::Result<(), String> {
         // Structured tracing: packet lifecycle span for message correlation
@@ -241,6 +316,7 @@ impl Outbox {
             payload_size = msg.envelope_data.len()
         );
 
+        self.retry_overflow();
         match &mut self.backend {
             OutboxBackend::Memory { queues, total } => {
                 if *total >= MAX_TOTAL_QUEUED {
@@ -265,7 +341,8 @@ impl Outbox {
             }
             OutboxBackend::Persistent(db) => {
                 // Check total limit
-                let current_total = db.count_prefix(QUEUE_PREFIX).unwrap_or(0);
+                let current_total =
+                    db.count_prefix(QUEUE_PREFIX).unwrap_or(0) + self.overflow.len();
                 if current_total >= MAX_TOTAL_QUEUED {
                     return Err(format!("Outbox full ({} messages)", MAX_TOTAL_QUEUED));
                 }
@@ -284,16 +361,15 @@ impl Outbox {
                     ));
                 }
 
-                // Store message
-                let key_str = format!(
-                    "{}{}_{}",
-                    String::from_utf8_lossy(QUEUE_PREFIX),
-                    msg.recipient_id,
-                    msg.message_id
-                );
-                if let Ok(bytes) = bincode::serialize(&msg) {
-                    db.put(key_str.as_bytes(), &bytes)?;
-                    db.flush()?;
+                // Store message. A failed write keeps the message in memory
+                // rather than losing it; it is persisted on a later retry.
+                if let Err(e) = Self::persist(db, &msg) {
+                    tracing::warn!(
+                        "Outbox storage write failed ({}); holding message {} in memory (degraded mode)",
+                        e,
+                        msg.message_id
+                    );
+                    self.overflow.push(msg);
                 }
                 // Trigger maintenance on persistent outbox
                 self.trigger_maintenance();
@@ -312,14 +388,19 @@ impl Outbox {
             OutboxBackend::Persistent(db) => {
                 let prefix_str =
                     format!("{}{}_", String::from_utf8_lossy(QUEUE_PREFIX), recipient_id);
-                if let Ok(results) = db.scan_prefix(prefix_str.as_bytes()) {
-                    results
-                        .into_iter()
-                        .filter_map(|(_, value)| deserialize_queued_message(&value).ok())
-                        .collect()
-                } else {
-                    Vec::new()
-                }
+                let mut messages: Vec<QueuedMessage> = db
+                    .scan_prefix(prefix_str.as_bytes())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|(_, value)| deserialize_queued_message(&value).ok())
+                    .collect();
+                messages.extend(
+                    self.overflow
+                        .iter()
+                        .filter(|msg| msg.recipient_id == recipient_id)
+                        .cloned(),
+                );
+                messages
             }
         }
     }
@@ -349,6 +430,12 @@ impl Outbox {
                         }
                     }
                 }
+                all_pending.extend(
+                    self.overflow
+                        .iter()
+                        .filter(|msg| msg.state == MessageState::Enqueued)
+                        .cloned(),
+                );
                 all_pending
             }
         }
@@ -368,6 +455,14 @@ impl Outbox {
                 false
             }
             OutboxBackend::Persistent(db) => {
+                if let Some(pos) = self
+                    .overflow
+                    .iter()
+                    .position(|m| m.message_id == message_id)
+                {
+                    self.overflow.remove(pos);
+                    return true;
+                }
                 // Find and remove the message
                 if let Ok(results) = db.scan_prefix(QUEUE_PREFIX) {
                     for (key, value) in results {
@@ -397,18 +492,19 @@ impl Outbox {
 
     /// Drain all messages for a peer (for batch delivery)
     pub fn drain_for_peer(&mut self, recipient_id: &str) -> Vec<QueuedMessage> {
-        match &mut self.backend {
+        self.retry_overflow();
+        let held = self.take_overflow_for(recipient_id, |msg| !msg.in_custody);
+        let mut drained = match &mut self.backend {
             OutboxBackend::Memory { queues, total } => {
-                let mut drained = Vec::new();
-                if let Some(queue) = queues.remove(recipient_id) {
-                    let count = queue.len();
-                    *total -= count;
-                    // Filter out messages that are in custody and should not be delivered locally
-                    for msg in queue.into_iter() {
-                        if !msg.in_custody {
-                            drained.push(msg);
-                        }
-                    }
+                let Some(queue) = queues.remove(recipient_id) else {
+                    return Vec::new();
+                };
+                // Messages in custody stay queued; only the rest are drained
+                let (kept, drained): (Vec<_>, Vec<_>) =
+                    queue.into_iter().partition(|msg| msg.in_custody);
+                *total -= drained.len();
+                if !kept.is_empty() {
+                    queues.insert(recipient_id.to_string(), VecDeque::from(kept));
                 }
                 drained
             }
@@ -436,12 +532,19 @@ impl Outbox {
 
                 messages
             }
-        }
+        };
+        drained.extend(held);
+        drained
     }
 
     /// Flush peer messages that are due for delivery
     pub fn flush_peer_messages(&mut self, recipient_id: &str) -> Vec<QueuedMessage> {
-        match &mut self.backend {
+        self.retry_overflow();
+        // Held messages are never scheduled for a later retry, so all are due.
+        let held = self.take_overflow_for(recipient_id, |msg| {
+            !msg.in_custody && msg.state == MessageState::Enqueued
+        });
+        let mut flushed = match &mut self.backend {
             OutboxBackend::Memory { queues, total } => {
                 let now_ms = web_time::SystemTime::now()
                     .duration_since(web_time::UNIX_EPOCH)
@@ -532,7 +635,9 @@ impl Outbox {
 
                 messages
             }
-        }
+        };
+        flushed.extend(held);
+        flushed
     }
 
     /// Increment attempt count for a message.
@@ -673,7 +778,9 @@ impl Outbox {
     pub fn total_count(&self) -> usize {
         match &self.backend {
             OutboxBackend::Memory { total, .. } => *total,
-            OutboxBackend::Persistent(db) => db.count_prefix(QUEUE_PREFIX).unwrap_or(0),
+            OutboxBackend::Persistent(db) => {
+                db.count_prefix(QUEUE_PREFIX).unwrap_or(0) + self.overflow.len()
+            }
         }
     }
 
@@ -696,6 +803,30 @@ impl Outbox {
         }
     }
 
+    /// Recipients with at least one message `drain_for_peer` would return
+    /// (not in custody), sorted. Used to retry via a relay when one connects.
+    pub fn pending_recipients(&self) -> Vec<String> {
+        let mut recipients: Vec<String> = match &self.backend {
+            OutboxBackend::Memory { queues, .. } => queues
+                .iter()
+                .filter(|(_, queue)| queue.iter().any(|msg| !msg.in_custody))
+                .map(|(recipient, _)| recipient.clone())
+                .collect(),
+            OutboxBackend::Persistent(db) => db
+                .scan_prefix(QUEUE_PREFIX)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|(_, value)| deserialize_queued_message(&value).ok())
+                .chain(self.overflow.iter().cloned())
+                .filter(|msg| !msg.in_custody)
+                .map(|msg| msg.recipient_id)
+                .collect(),
+        };
+        recipients.sort();
+        recipients.dedup();
+        recipients
+    }
+
     /// Remove expired messages (older than max_age_secs)
     pub fn remove_expired(&mut self, max_age_secs: u64) -> usize {
         let now = web_time::SystemTime::now()
@@ -980,6 +1111,66 @@ mod tests {
         assert_eq!(outbox.peek_for_peer("peer_a").len(), 0);
     }
 
+    #[test]
+    fn test_pending_recipients_skips_custody_only_peers() {
+        let mut outbox = Outbox::new();
+        outbox.enqueue(make_msg("msg1", "peer_b")).unwrap();
+        outbox.enqueue(make_msg("msg2", "peer_a")).unwrap();
+        outbox.enqueue(make_msg("msg3", "peer_b")).unwrap();
+        let mut custody = make_msg("msg4", "peer_c");
+        custody.in_custody = true;
+        outbox.enqueue(custody).unwrap();
+
+        assert_eq!(outbox.pending_recipients(), vec!["peer_a", "peer_b"]);
+        outbox.drain_for_peer("peer_a");
+        assert_eq!(outbox.pending_recipients(), vec!["peer_b"]);
+    }
+
+    #[test]
+    fn test_drain_for_peer_keeps_custody_messages() {
+        let mut outbox = Outbox::new();
+        let mut custody = make_msg("msg1", "peer_a");
+        custody.in_custody = true;
+        outbox.enqueue(custody).unwrap();
+        outbox.enqueue(make_msg("msg2", "peer_a")).unwrap();
+
+        let drained = outbox.drain_for_peer("peer_a");
+        assert_eq!(drained.len(), 1);
+        assert_eq!(drained[0].message_id, "msg2");
+        assert_eq!(outbox.total_count(), 1);
+        assert_eq!(outbox.peek_for_peer("peer_a")[0].message_id, "msg1");
+    }
+
+    #[test]
+    fn test_failed_write_holds_message_in_memory() {
+        use crate::store::backend::FlakyStorage;
+        let storage = FlakyStorage::default();
+        let mut outbox = Outbox::persistent(Arc::new(storage.clone()));
+
+        storage.set_fail_writes(true);
+        outbox.enqueue(make_msg("m1", "peer-a")).unwrap();
+        assert!(outbox.is_degraded());
+        assert_eq!(outbox.total_count(), 1);
+        assert_eq!(outbox.peek_for_peer("peer-a").len(), 1);
+        assert_eq!(outbox.pending_recipients(), vec!["peer-a".to_string()]);
+        assert_eq!(outbox.retry_overflow(), 1);
+
+        // Once storage recovers the held message is persisted, not lost.
+        storage.set_fail_writes(false);
+        assert_eq!(outbox.retry_overflow(), 0);
+        assert!(!outbox.is_degraded());
+        let reopened = Outbox::persistent(Arc::new(storage.clone()));
+        assert_eq!(reopened.peek_for_peer("peer-a")[0].message_id, "m1");
+
+        // Still degraded: draining hands the held message to the caller.
+        storage.set_fail_writes(true);
+        outbox.enqueue(make_msg("m2", "peer-b")).unwrap();
+        let drained = outbox.drain_for_peer("peer-b");
+        assert_eq!(drained.len(), 1);
+        assert_eq!(drained[0].message_id, "m2");
+        assert!(!outbox.is_degraded());
+    }
+
     #[test]
     fn test_record_attempt() {
         let mut outbox = Outbox::new();
@@ -1738,7 +1738,7 @@ fn custody_base_dir() -> PathBuf {
 
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(unix)]
-fn filesystem_usage_bytes(path: &std::path::Path) -> Option<(u64, u64)> {
+pub(crate) fn filesystem_usage_bytes(path: &std::path::Path) -> Option<(u64, u64)> {
     use std::os::unix::ffi::OsStrExt;
 
     let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
@@ -1775,7 +1775,7 @@ fn filesystem_usage_bytes(path: &std::path::Path) -> Option<(u64, u64)> {
 
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(not(unix))]
-fn filesystem_usage_bytes(_path: &std::path::Path) -> Option<(u64, u64)> {
+pub(crate) fn filesystem_usage_bytes(_path: &std::path::Path) -> Option<(u64, u64)> {
     None
 }
 
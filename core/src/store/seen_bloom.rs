@@ -0,0 +1,124 @@
+// Seen-message bloom filter for one-directional dedup sync
+//
+// When catching another device up, sending every seen message ID is
+// wasteful. Instead the receiving side exports a bloom filter of what it has
+// seen (`Inbox::seen_bloom`) and the sending side pushes only candidates the
+// filter says are missing (`Inbox::missing_from_bloom`).
+//
+// Tradeoff: a bloom filter has no false negatives but does have false
+// positives. A missing message is never reported as seen *unless* it
+// collides, so at the default 1% rate roughly one in a hundred genuinely
+// missing messages is skipped on a given round. That is acceptable for
+// opportunistic catch-up (a later full sync or the IBLT reconciliation in
+// `drift::sketch` recovers them) but not where every message must land.
+//
+// Wire format, hashing over the inbox's blake3 message-ID digests so the
+// filter is stable across processes and versions:
+// `version (1) || k (1) || bit_count (u32 LE) || bits`
+
+/// Current encoding version.
+pub const SEEN_BLOOM_VERSION: u8 = 1;
+/// Target false-positive rate for exported filters.
+pub const SEEN_BLOOM_FALSE_POSITIVE_RATE: f64 = 0.01;
+
+const HEADER_LEN: usize = 6;
+/// Bounds decoding work for filters received from a peer (8 MiB of bits).
+const MAX_BITS: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeenBloom {
+    bits: Vec<u8>,
+    bit_count: usize,
+    k: u8,
+}
+
+impl SeenBloom {
+    /// Filter sized for `expected_items` at `SEEN_BLOOM_FALSE_POSITIVE_RATE`.
+    pub fn with_capacity(expected_items: usize) -> Self {
+        let n = expected_items.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let m = (-(n * SEEN_BLOOM_FALSE_POSITIVE_RATE.ln()) / (ln2 * ln2)).ceil() as usize;
+        let bit_count = m.max(8);
+        let k = ((bit_count as f64 / n) * ln2).ceil().clamp(1.0, 16.0) as u8;
+        Self {
+            bits: vec![0; bit_count.div_ceil(8)],
+            bit_count,
+            k,
+        }
+    }
+
+    /// Double hashing over the digest: h(i) = h1 + i * h2 (mod m).
+    fn positions(&self, digest: &[u8; 32]) -> impl Iterator<Item = usize> + '_ {
+        let h1 = u64::from_le_bytes(digest[0..8].try_into().unwrap_or_default());
+        let h2 = u64::from_le_bytes(digest[8..16].try_into().unwrap_or_default()) | 1;
+        let m = self.bit_count as u64;
+        (0..self.k as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % m) as usize)
+    }
+
+    pub fn insert_digest(&mut self, digest: &[u8; 32]) {
+        let positions: Vec<usize> = self.positions(digest).collect();
+        for pos in positions {
+            self.bits[pos / 8] |= 1 << (pos % 8);
+        }
+    }
+
+    /// False means definitely never inserted; true means probably inserted.
+    pub fn contains_digest(&self, digest: &[u8; 32]) -> bool {
+        self.positions(digest)
+            .all(|pos| self.bits[pos / 8] & (1 << (pos % 8)) != 0)
+    }
+
+    pub fn contains(&self, message_id: &str) -> bool {
+        self.contains_digest(blake3::hash(message_id.as_bytes()).as_bytes())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_LEN + self.bits.len());
+        out.push(SEEN_BLOOM_VERSION);
+        out.push(self.k);
+        out.extend_from_slice(&(self.bit_count as u32).to_le_bytes());
+        out.extend_from_slice(&self.bits);
+        out
+    }
+
+    /// Parse a filter exported by `to_bytes`. `None` if the bytes are not a
+    /// well-formed filter of a known version.
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        if data.len() < HEADER_LEN || data[0] != SEEN_BLOOM_VERSION {
+            return None;
+        }
+        let k = data[1];
+        let bit_count = u32::from_le_bytes(data[2..6].try_into().ok()?) as usize;
+        if k == 0 || bit_count == 0 || bit_count > MAX_BITS {
+            return None;
+        }
+        let bits = &data[HEADER_LEN..];
+        if bits.len() != bit_count.div_ceil(8) {
+            return None;
+        }
+        Some(Self {
+            bits: bits.to_vec(),
+            bit_count,
+            k,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_and_malformed_input() {
+        let mut bloom = SeenBloom::with_capacity(10);
+        bloom.insert_digest(blake3::hash(b"a").as_bytes());
+        let decoded = SeenBloom::from_bytes(&bloom.to_bytes()).unwrap();
+        assert_eq!(decoded, bloom);
+        assert!(decoded.contains("a"));
+
+        let bytes = bloom.to_bytes();
+        assert!(SeenBloom::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+        assert!(SeenBloom::from_bytes(&[2, 1, 8, 0, 0, 0, 0]).is_none());
+        assert!(SeenBloom::from_bytes(b"").is_none());
+    }
+}
@@ -64,6 +64,7 @@ mod tests {
             payload: vec![1, 2, 3],
             received_at,
             sender_public_key_hex: None,
+            message_type: None,
         }
     }
 
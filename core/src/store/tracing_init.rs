@@ -54,6 +54,7 @@ pub fn init_file_tracing(log_directory: &str) -> Result<(), Box<dyn std::error::
     tracing_subscriber::registry()
         .with(env_filter)
         .with(file_layer)
+        .with(crate::store::log_ring::LogRingLayer)
         .try_init()
         .map_err(|e| format!("Tracing init failed: {}", e))?;
 
@@ -0,0 +1,129 @@
+// Best-effort secure erase (`IronCore::panic_wipe`, `scm wipe`)
+//
+// Every stored record and file is overwritten with random bytes before it is
+// deleted, so the live database and files hold nothing recoverable.
+//
+// What this cannot promise, stated plainly:
+// - SSDs, SD cards and phone flash remap writes for wear levelling, so the
+//   overwrite usually lands on different physical cells than the original.
+// - Copy-on-write and journaling filesystems (APFS, btrfs, ZFS, ext4 data
+//   journaling) may keep older blocks around.
+// - sled is log-structured: an overwritten record's previous value can stay
+//   in an unreclaimed segment until the database compacts.
+// - Backups and snapshots (iCloud, Android auto-backup, Time Machine) are
+//   out of reach.
+// On such media, only full-disk encryption makes deleted data truly
+// unrecoverable; this wipe removes everything the app itself can reach.
+
+use super::backend::StorageBackend;
+use rand::RngCore;
+
+/// Overwrite every record in `backend` with random bytes of the same length,
+/// then remove it. Returns the number of records erased.
+pub fn overwrite_backend(backend: &dyn StorageBackend) -> Result<usize, String> {
+    let entries = backend.scan_prefix(b"")?;
+    let mut rng = rand::thread_rng();
+    for (key, value) in &entries {
+        let mut noise = vec![0u8; value.len()];
+        rng.fill_bytes(&mut noise);
+        backend.put(key, &noise)?;
+    }
+    backend.flush()?;
+    for (key, _) in &entries {
+        backend.remove(key)?;
+    }
+    backend.flush()?;
+    Ok(entries.len())
+}
+
+/// Overwrite a file's contents with random bytes, sync, then delete it.
+/// A missing file is not an error.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn shred_file(path: &std::path::Path) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let meta = match std::fs::symlink_metadata(path) {
+        Ok(meta) => meta,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    // Never follow a link out of the data directory; just unlink it.
+    if meta.is_file() {
+        let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+        let mut rng = rand::thread_rng();
+        let mut chunk = vec![0u8; 64 * 1024];
+        let mut remaining = meta.len();
+        while remaining > 0 {
+            let n = remaining.min(chunk.len() as u64) as usize;
+            rng.fill_bytes(&mut chunk[..n]);
+            file.write_all(&chunk[..n])?;
+            remaining -= n as u64;
+        }
+        file.sync_all()?;
+    }
+    std::fs::remove_file(path)
+}
+
+/// `shred_file` every file under `dir`, then remove the directory tree.
+/// Keeps going past files it cannot overwrite and reports the first error.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn shred_dir(dir: &std::path::Path) -> std::io::Result<()> {
+    fn shred_tree(dir: &std::path::Path, first_error: &mut Option<std::io::Error>) {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                first_error.get_or_insert(e);
+                return;
+            }
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_dir = std::fs::symlink_metadata(&path)
+                .map(|m| m.is_dir())
+                .unwrap_or(false);
+            let result = if is_dir {
+                shred_tree(&path, first_error);
+                Ok(())
+            } else {
+                shred_file(&path)
+            };
+            if let Err(e) = result {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+
+    if !dir.exists() {
+        return Ok(());
+    }
+    let mut first_error = None;
+    shred_tree(dir, &mut first_error);
+    if let Err(e) = std::fs::remove_dir_all(dir) {
+        first_error.get_or_insert(e);
+    }
+    first_error.map_or(Ok(()), Err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::backend::MemoryStorage;
+
+    #[test]
+    fn test_overwrite_backend_and_shred_dir() {
+        let backend = MemoryStorage::new();
+        backend.put(b"identity_keys", b"secret").unwrap();
+        backend.put(b"contact:alice", b"pk").unwrap();
+        assert_eq!(overwrite_backend(&backend).unwrap(), 2);
+        assert!(backend.scan_prefix(b"").unwrap().is_empty());
+
+        let dir = tempfile::tempdir().unwrap();
+        let data = dir.path().join("data");
+        std::fs::create_dir_all(data.join("nested")).unwrap();
+        std::fs::write(data.join("relay_network_key.pb"), b"key").unwrap();
+        std::fs::write(data.join("nested").join("conf"), b"conf").unwrap();
+        shred_dir(&data).unwrap();
+        assert!(!data.exists());
+        shred_file(&data.join("gone")).unwrap();
+    }
+}
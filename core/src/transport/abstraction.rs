@@ -33,6 +33,20 @@ impl fmt::Display for TransportType {
     }
 }
 
+impl TransportType {
+    /// Deadline for reading one complete drift frame off this transport.
+    /// Slow links get longer so honest peers are not cut off mid-frame.
+    pub fn frame_read_timeout(&self) -> web_time::Duration {
+        match self {
+            TransportType::BLE => crate::drift::BLE_FRAME_READ_TIMEOUT,
+            TransportType::WiFiAware
+            | TransportType::WiFiDirect
+            | TransportType::Internet
+            | TransportType::Local => crate::drift::FRAME_READ_TIMEOUT,
+        }
+    }
+}
+
 /// Capabilities of a transport type
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransportCapabilities {
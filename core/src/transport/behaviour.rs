@@ -13,12 +13,13 @@
 // - ledger_exchange: automatic peer list sharing for aggressive discovery
 
 use super::discovery::DiscoveryConfig;
+use super::nat::NatConfig;
 use super::reflection::{AddressReflectionRequest, AddressReflectionResponse};
 use crate::identity::IdentityKeys;
+use crate::settings::{GossipProfile, KadMode};
 use crate::store::ledger_entry::{LedgerExchangeRequest, LedgerExchangeResponse};
 #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
 use libp2p::mdns;
-#[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
 use libp2p::swarm::behaviour::toggle::Toggle;
 #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
 use libp2p::upnp;
@@ -38,8 +39,9 @@ pub struct IronCoreBehaviour {
     pub relay_client: relay::client::Behaviour,
     /// Circuit Relay v2 server - all nodes act as relays for NAT traversal.
     pub relay_server: relay::Behaviour,
-    /// Direct connection upgrade through relay (hole punching).
-    pub dcutr: dcutr::Behaviour,
+    /// Direct connection upgrade through relay (hole punching); off when
+    /// `NatConfig::enable_hole_punch` is false.
+    pub dcutr: Toggle<dcutr::Behaviour>,
     /// NAT status probing via observed reachability.
     pub autonat: autonat::Behaviour,
     /// Keepalive and round-trip telemetry.
@@ -67,9 +69,9 @@ pub struct IronCoreBehaviour {
     pub mdns: Toggle<mdns::tokio::Behaviour>,
     /// Peer identification — advertises relay capability
     pub identify: identify::Behaviour,
-    /// UPnP port mapping
+    /// UPnP port mapping; off when `NatConfig::enable_upnp` is false.
     #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
-    pub upnp: upnp::tokio::Behaviour,
+    pub upnp: Toggle<upnp::tokio::Behaviour>,
     /// Connection limits to prevent resource exhaustion
     pub connection_limits: connection_limits::Behaviour,
 }
@@ -109,6 +111,11 @@ pub struct RelayRequest {
     /// None for legacy senders or when the sender has no device record for the recipient.
     #[serde(default)]
     pub intended_device_id: Option<String>,
+    /// Sender's detached Ed25519 signature over the envelope (as `SignedEnvelope`
+    /// would carry it), so the relay can reject forged frames without decrypting.
+    /// None for legacy senders and for onion-wrapped or forwarded envelopes.
+    #[serde(default)]
+    pub envelope_signature: Option<Vec<u8>>,
 }
 
 /// Response to a relay request
@@ -332,28 +339,36 @@ impl IronCoreBehaviour {
     ///
     /// Key design decisions for Aggressive Discovery:
     /// - Gossipsub uses PERMISSIVE validation (accept messages from any topic)
-    /// - Identify advertises this node as a relay
-    /// - Kademlia set to Server mode by default
+    /// - Identify advertises this node's role and capabilities (`AgentInfo`)
+    /// - Kademlia mode follows `discovery_config.kad_mode` (see `KadMode`)
     /// - Ledger exchange for automatic peer list sharing
     /// - All timeouts are generous to survive flaky networks
+    /// - Gossipsub mesh size and heartbeat follow `gossip_profile`
+    /// - Hole punching, UPnP and AutoNAT pacing follow `nat_config`
     #[allow(unused_variables)]
     pub fn new(
         keypair: &libp2p::identity::Keypair,
         relay_client: relay::client::Behaviour,
         headless: bool,
         discovery_config: Option<DiscoveryConfig>,
+        gossip_profile: GossipProfile,
+        nat_config: &NatConfig,
     ) -> anyhow::Result<Self> {
         let peer_id = keypair.public().to_peer_id();
-        let dcutr = dcutr::Behaviour::new(peer_id);
+        let dcutr = Toggle::from(
+            nat_config
+                .enable_hole_punch
+                .then(|| dcutr::Behaviour::new(peer_id)),
+        );
         let autonat = autonat::Behaviour::new(
             peer_id,
             autonat::Config {
-                // Use a longer retry_interval (300s) instead of the default 90s.
+                // `probe_interval` defaults to 300s instead of libp2p's 90s.
                 // This reduces spurious "NoServer" probe errors when isolated
                 // (no connected peers to use as probe servers). The probe will
                 // still fire, but less aggressively — and the swarm event loop
                 // gates the log on connected_peers being non-empty.
-                retry_interval: Duration::from_secs(300),
+                retry_interval: Duration::from_secs(nat_config.probe_interval),
                 ..autonat::Config::default()
             },
         );
@@ -440,18 +455,7 @@ impl IronCoreBehaviour {
         //
         // This enables dynamic topic negotiation: when a peer advertises
         // a different topic, we can see it and auto-subscribe.
-        let gossipsub_config = gossipsub::ConfigBuilder::default()
-            .heartbeat_interval(Duration::from_secs(5)) // Faster heartbeat for quicker discovery
-            .validation_mode(gossipsub::ValidationMode::Permissive) // PERMISSIVE: accept everything
-            .mesh_outbound_min(1) // Must be <= mesh_n_low
-            .mesh_n_low(1) // Accept mesh with just 1 peer
-            .mesh_n(3) // Target 3 peers in mesh
-            .mesh_n_high(12) // Allow up to 12
-            .gossip_lazy(3) // Gossip to at least 3 non-mesh peers
-            .history_length(5) // Keep 5 heartbeat windows of message history
-            .history_gossip(3) // Gossip about last 3 windows
-            .build()
-            .map_err(|e| anyhow::anyhow!("Gossipsub config error: {}", e))?;
+        let gossipsub_config = gossipsub_config(gossip_profile)?;
 
         let gossipsub = gossipsub::Behaviour::new(
             gossipsub::MessageAuthenticity::Signed(keypair.clone()),
@@ -469,8 +473,14 @@ impl IronCoreBehaviour {
         );
         let mut kademlia =
             kad::Behaviour::with_config(peer_id, kad::store::MemoryStore::new(peer_id), kad_config);
-        // Set server mode immediately — we want to be discoverable
-        kademlia.set_mode(Some(kad::Mode::Server));
+        // Pin the configured mode rather than letting libp2p infer it from
+        // confirmed external addresses, which flips mobile nodes to Server
+        // whenever a NAT mapping happens to be confirmed.
+        let kad_mode = discovery_config
+            .as_ref()
+            .map(|c| c.kad_mode)
+            .unwrap_or_default();
+        kademlia.set_mode(Some(kad_mode_to_libp2p(kad_mode)));
 
         // mDNS for LAN discovery — gracefully disabled in environments without
         // multicast support (Docker containers, cloud VMs, CI runners).
@@ -499,25 +509,37 @@ impl IronCoreBehaviour {
             Toggle::from(None)
         };
 
-        // Identify protocol — advertise this node as a relay
+        // Identify protocol
         //
-        // agent_version includes "relay" to signal we're a mandatory relay.
-        // We also distinguish "headless" (infrastructure) vs "full" (human) nodes.
+        // agent_version is structured (`scm/<version> (<relay|node>) caps=...`,
+        // see `AgentInfo`): headless infrastructure nodes advertise the relay
+        // role unless the discovery config overrides it, and the trailing
+        // segment advertises supported message types and features.
         // push_listen_addr_updates ensures peers learn our addresses quickly.
-        let type_str = if headless { "headless" } else { "full" };
+        let mut agent = super::capability::AgentInfo::local(
+            discovery_config
+                .as_ref()
+                .and_then(|c| c.node_role)
+                .unwrap_or(super::capability::NodeRole::for_headless(headless)),
+        );
+        if let Some(version) = discovery_config
+            .as_ref()
+            .and_then(|c| c.agent_version.clone())
+        {
+            agent.version = version;
+        }
         let identify = identify::Behaviour::new(
             identify::Config::new("/sc/id/1.0.0".to_string(), keypair.public())
                 .with_push_listen_addr_updates(true)
+                // Listen addresses include loopback and, for a public relay,
+                // private ones; the swarm adds the ones the advertise scope
+                // permits as external addresses instead.
+                .with_hide_listen_addrs(true)
                 .with_interval(Duration::from_secs(60)) // Reduced frequency to prevent identify storms
-                .with_agent_version(format!(
-                    "scmessenger/{}/{}/relay/{}",
-                    env!("CARGO_PKG_VERSION"),
-                    type_str,
-                    peer_id
-                )),
+                .with_agent_version(agent.to_agent_version()),
         );
         #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
-        let upnp = upnp::tokio::Behaviour::default();
+        let upnp = Toggle::from(nat_config.enable_upnp.then(upnp::tokio::Behaviour::default));
 
         // Relay server - all nodes act as relays for NAT traversal
         let relay_server = relay::Behaviour::new(peer_id, relay::Config::default());
@@ -554,11 +576,130 @@ impl IronCoreBehaviour {
     }
 }
 
+/// libp2p Kademlia mode for a configured DHT role.
+pub fn kad_mode_to_libp2p(mode: KadMode) -> kad::Mode {
+    match mode {
+        KadMode::Client => kad::Mode::Client,
+        KadMode::Server => kad::Mode::Server,
+    }
+}
+
+/// Gossipsub configuration for a mesh profile (see `GossipProfile` for the
+/// tradeoffs). Validation is always permissive.
+pub fn gossipsub_config(profile: GossipProfile) -> anyhow::Result<gossipsub::Config> {
+    let mut builder = gossipsub::ConfigBuilder::default();
+    builder.validation_mode(gossipsub::ValidationMode::Permissive); // PERMISSIVE: accept everything
+    match profile {
+        GossipProfile::Default => builder
+            .heartbeat_interval(Duration::from_secs(5)) // Faster heartbeat for quicker discovery
+            .mesh_outbound_min(1) // Must be <= mesh_n_low
+            .mesh_n_low(1) // Accept mesh with just 1 peer
+            .mesh_n(3) // Target 3 peers in mesh
+            .mesh_n_high(12) // Allow up to 12
+            .gossip_lazy(3) // Gossip to at least 3 non-mesh peers
+            .history_length(5) // Keep 5 heartbeat windows of message history
+            .history_gossip(3), // Gossip about last 3 windows
+        // Each heartbeat is a radio wake-up; a 30s heartbeat with a smaller
+        // mesh cuts them sixfold. History still covers ~90s of messages so
+        // IWANT requests after a doze can be answered.
+        GossipProfile::Mobile => builder
+            .heartbeat_interval(Duration::from_secs(30))
+            .mesh_outbound_min(1)
+            .mesh_n_low(1)
+            .mesh_n(2)
+            .mesh_n_high(4)
+            .gossip_lazy(2)
+            .history_length(3)
+            .history_gossip(2),
+        // Relays are mains-powered and well connected: keep a wide mesh so
+        // losing a few peers never partitions the topic, and remember more
+        // windows so mobile peers that doze can catch up via gossip.
+        GossipProfile::Relay => builder
+            .heartbeat_interval(Duration::from_secs(5))
+            .mesh_outbound_min(2)
+            .mesh_n_low(4)
+            .mesh_n(8)
+            .mesh_n_high(16)
+            .gossip_lazy(8)
+            .history_length(12)
+            .history_gossip(4),
+    };
+    builder
+        .build()
+        .map_err(|e| anyhow::anyhow!("Gossipsub config error: {}", e))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::identity::IdentityKeys;
 
+    #[test]
+    fn gossip_profiles_produce_expected_mesh_parameters() {
+        let default = gossipsub_config(GossipProfile::Default).unwrap();
+        assert_eq!(default.heartbeat_interval(), Duration::from_secs(5));
+        assert_eq!(default.mesh_n(), 3);
+        assert_eq!(default.history_length(), 5);
+
+        let mobile = gossipsub_config(GossipProfile::Mobile).unwrap();
+        assert_eq!(mobile.heartbeat_interval(), Duration::from_secs(30));
+        assert_eq!(mobile.mesh_n(), 2);
+        assert_eq!(mobile.mesh_n_high(), 4);
+        assert_eq!(mobile.history_length(), 3);
+
+        let relay = gossipsub_config(GossipProfile::Relay).unwrap();
+        assert_eq!(relay.heartbeat_interval(), Duration::from_secs(5));
+        assert_eq!(relay.mesh_n(), 8);
+        assert_eq!(relay.mesh_n_low(), 4);
+        assert_eq!(relay.mesh_n_high(), 16);
+        assert_eq!(relay.history_length(), 12);
+
+        assert!(mobile.heartbeat_interval() > default.heartbeat_interval());
+        assert!(relay.mesh_n() > default.mesh_n());
+    }
+
+    // Tokio runtime needed for the mDNS and UPnP behaviours built alongside.
+    #[tokio::test]
+    async fn kad_mode_is_applied_to_behaviour() {
+        for (mode, expected) in [
+            (KadMode::Client, kad::Mode::Client),
+            (KadMode::Server, kad::Mode::Server),
+        ] {
+            let keypair = libp2p::identity::Keypair::generate_ed25519();
+            let (_transport, relay_client) = relay::client::new(keypair.public().to_peer_id());
+            let behaviour = IronCoreBehaviour::new(
+                &keypair,
+                relay_client,
+                false,
+                Some(DiscoveryConfig::default().with_kad_mode(mode)),
+                GossipProfile::Default,
+                &NatConfig::default(),
+            )
+            .unwrap();
+            assert_eq!(behaviour.kademlia.mode(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn nat_config_switches_hole_punching_and_upnp() {
+        for (config, enabled) in [(NatConfig::default(), true), (NatConfig::relay(), false)] {
+            let keypair = libp2p::identity::Keypair::generate_ed25519();
+            let (_transport, relay_client) = relay::client::new(keypair.public().to_peer_id());
+            let behaviour = IronCoreBehaviour::new(
+                &keypair,
+                relay_client,
+                false,
+                None,
+                GossipProfile::Default,
+                &config,
+            )
+            .unwrap();
+            assert_eq!(behaviour.dcutr.is_enabled(), enabled);
+            #[cfg(not(target_os = "android"))]
+            assert_eq!(behaviour.upnp.is_enabled(), enabled);
+        }
+    }
+
     #[test]
     fn relay_request_carries_ws13_metadata_when_set() {
         let req = RelayRequest {
@@ -567,6 +708,7 @@ mod tests {
             message_id: "msg-2".to_string(),
             recipient_identity_id: Some("identity-abc".to_string()),
             intended_device_id: Some("550e8400-e29b-41d4-a716-446655440000".to_string()),
+            envelope_signature: None,
         };
         assert_eq!(req.recipient_identity_id.as_deref(), Some("identity-abc"));
         assert_eq!(
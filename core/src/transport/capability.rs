@@ -5,6 +5,223 @@
 // with the CLI `TransportBridge::can_forward_for_wasm()` method.
 
 use crate::transport::health::TransportHealthMonitor;
+use crate::MessageType;
+use serde::{Deserialize, Serialize};
+
+/// Identify agent-version segment carrying a peer's capabilities, e.g.
+/// `caps=text,receipt,onion-relay;ratchet,drift`.
+pub const CAPABILITIES_AGENT_PREFIX: &str = "caps=";
+
+/// Double Ratchet sessions (forward-secret per-message keys).
+pub const FEATURE_RATCHET: &str = "ratchet";
+/// Sealed sender (sender identity hidden from relays).
+pub const FEATURE_SEALED_SENDER: &str = "sealed-sender";
+/// Drift envelopes and Drift sync sessions.
+pub const FEATURE_DRIFT: &str = "drift";
+
+/// Product name at the start of a structured agent version.
+pub const AGENT_PRODUCT: &str = "scm";
+
+/// Agent-version prefix used by nodes before the structured format:
+/// `scmessenger/<version>/<headless|full>/relay/<peer_id>/caps=...`.
+const LEGACY_AGENT_PREFIX: &str = "scmessenger/";
+
+/// Role a node advertises in its Identify agent version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeRole {
+    /// Always-on headless node that relays for others.
+    Relay,
+    /// A user's node.
+    Node,
+}
+
+impl NodeRole {
+    /// Relay for headless nodes, Node otherwise.
+    pub fn for_headless(headless: bool) -> Self {
+        if headless {
+            NodeRole::Relay
+        } else {
+            NodeRole::Node
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            NodeRole::Relay => "relay",
+            NodeRole::Node => "node",
+        }
+    }
+}
+
+/// A parsed Identify agent version, e.g.
+/// `scm/0.1.2 (relay) caps=text,receipt;ratchet,drift`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentInfo {
+    pub version: String,
+    pub role: NodeRole,
+    pub capabilities: PeerCapabilities,
+}
+
+impl AgentInfo {
+    /// This build, advertising `role`.
+    pub fn local(role: NodeRole) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            role,
+            capabilities: PeerCapabilities::local(),
+        }
+    }
+
+    pub fn to_agent_version(&self) -> String {
+        format!(
+            "{}/{} ({}) {}",
+            AGENT_PRODUCT,
+            self.version,
+            self.role.as_str(),
+            self.capabilities.to_agent_segment()
+        )
+    }
+
+    /// Parse a structured or legacy SCMessenger agent version. `None` for
+    /// any other software, so arbitrary text never reads as a relay.
+    pub fn parse(agent_version: &str) -> Option<Self> {
+        let agent_version = agent_version.trim();
+        let capabilities = PeerCapabilities::from_agent_version(agent_version);
+
+        if let Some(rest) = agent_version.strip_prefix(LEGACY_AGENT_PREFIX) {
+            let mut parts = rest.split('/');
+            let version = parts.next().filter(|v| !v.is_empty())?.to_string();
+            let role = match parts.next()? {
+                "headless" => NodeRole::Relay,
+                "full" => NodeRole::Node,
+                _ => return None,
+            };
+            return Some(Self {
+                version,
+                role,
+                capabilities,
+            });
+        }
+
+        let mut tokens = agent_version.split_whitespace();
+        let version = tokens
+            .next()?
+            .strip_prefix(AGENT_PRODUCT)?
+            .strip_prefix('/')
+            .filter(|v| !v.is_empty())?
+            .to_string();
+        let role = match tokens.next()? {
+            "(relay)" => NodeRole::Relay,
+            "(node)" => NodeRole::Node,
+            _ => return None,
+        };
+        Some(Self {
+            version,
+            role,
+            capabilities,
+        })
+    }
+}
+
+/// Whether an Identify agent version belongs to an SCMessenger relay.
+pub fn is_relay_agent(agent_version: &str) -> bool {
+    AgentInfo::parse(agent_version).is_some_and(|agent| agent.role == NodeRole::Relay)
+}
+
+/// Message types and optional features a peer advertises, so a client can
+/// check support before attempting e.g. onion routing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerCapabilities {
+    /// Wire names of the message types the peer accepts (see
+    /// `message_type_name`).
+    pub message_types: Vec<String>,
+    pub features: Vec<String>,
+}
+
+/// Wire name of a message type in a capabilities advertisement.
+pub fn message_type_name(message_type: &MessageType) -> &'static str {
+    match message_type {
+        MessageType::Text => "text",
+        MessageType::Receipt => "receipt",
+        MessageType::OnionRelay => "onion-relay",
+        MessageType::Broadcast => "broadcast",
+        MessageType::Typing => "typing",
+    }
+}
+
+impl PeerCapabilities {
+    /// What this build supports. Sealed sender is not implemented, so it is
+    /// not advertised.
+    pub fn local() -> Self {
+        Self {
+            message_types: [
+                MessageType::Text,
+                MessageType::Receipt,
+                MessageType::OnionRelay,
+                MessageType::Broadcast,
+                MessageType::Typing,
+            ]
+            .iter()
+            .map(|t| message_type_name(t).to_string())
+            .collect(),
+            features: vec![FEATURE_RATCHET.to_string(), FEATURE_DRIFT.to_string()],
+        }
+    }
+
+    /// Assumed for peers that do not advertise anything.
+    pub fn text_only() -> Self {
+        Self {
+            message_types: vec![message_type_name(&MessageType::Text).to_string()],
+            features: Vec::new(),
+        }
+    }
+
+    pub fn supports(&self, message_type: &MessageType) -> bool {
+        let name = message_type_name(message_type);
+        self.message_types.iter().any(|t| t == name)
+    }
+
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.features.iter().any(|f| f == feature)
+    }
+
+    /// Encode as an agent-version segment (`caps=<types>;<features>`).
+    pub fn to_agent_segment(&self) -> String {
+        format!(
+            "{}{};{}",
+            CAPABILITIES_AGENT_PREFIX,
+            self.message_types.join(","),
+            self.features.join(",")
+        )
+    }
+
+    /// Read capabilities from an Identify agent version, falling back to
+    /// `text_only` when the peer does not advertise any. Unknown names are
+    /// kept so newer peers' capabilities still show up.
+    pub fn from_agent_version(agent_version: &str) -> Self {
+        let Some(segment) = agent_version
+            .split(|c: char| c == '/' || c.is_whitespace())
+            .find_map(|part| part.strip_prefix(CAPABILITIES_AGENT_PREFIX))
+        else {
+            return Self::text_only();
+        };
+        let (types, features) = segment.split_once(';').unwrap_or((segment, ""));
+        let split = |list: &str| -> Vec<String> {
+            list.split(',')
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        let message_types = split(types);
+        if message_types.is_empty() {
+            return Self::text_only();
+        }
+        Self {
+            message_types,
+            features: split(features),
+        }
+    }
+}
 
 /// Determine whether the local node can forward messages for WASM thin clients.
 ///
@@ -31,4 +248,58 @@ mod tests {
         let monitor = TransportHealthMonitor::new();
         assert!(!can_forward_for_wasm(&monitor));
     }
+
+    #[test]
+    fn capable_and_incapable_peers_are_distinguished() {
+        let capable = PeerCapabilities::from_agent_version(&format!(
+            "scmessenger/0.2.0/full/relay/12D3KooWExample/{}",
+            PeerCapabilities::local().to_agent_segment()
+        ));
+        assert_eq!(capable, PeerCapabilities::local());
+        assert!(capable.supports(&MessageType::OnionRelay));
+        assert!(capable.has_feature(FEATURE_RATCHET));
+        assert!(capable.has_feature(FEATURE_DRIFT));
+        assert!(!capable.has_feature(FEATURE_SEALED_SENDER));
+
+        // Older nodes don't advertise anything: text only.
+        let legacy =
+            PeerCapabilities::from_agent_version("scmessenger/0.1.0/full/relay/12D3KooWExample");
+        assert_eq!(legacy, PeerCapabilities::text_only());
+        assert!(legacy.supports(&MessageType::Text));
+        assert!(!legacy.supports(&MessageType::OnionRelay));
+        assert!(!legacy.has_feature(FEATURE_RATCHET));
+    }
+
+    #[test]
+    fn agent_version_role_and_capabilities_parse() {
+        let relay = AgentInfo::local(NodeRole::Relay).to_agent_version();
+        let node = AgentInfo::local(NodeRole::Node).to_agent_version();
+        assert!(relay.starts_with(&format!("scm/{} (relay) ", env!("CARGO_PKG_VERSION"))));
+        assert_eq!(
+            AgentInfo::parse(&relay),
+            Some(AgentInfo::local(NodeRole::Relay))
+        );
+        assert_eq!(
+            AgentInfo::parse(&node),
+            Some(AgentInfo::local(NodeRole::Node))
+        );
+        assert!(is_relay_agent(&relay));
+        assert!(!is_relay_agent(&node));
+
+        // Legacy strings said "relay" for every node; headless is the signal.
+        let legacy_relay =
+            AgentInfo::parse("scmessenger/0.1.0/headless/relay/12D3KooWExample").unwrap();
+        assert_eq!(legacy_relay.role, NodeRole::Relay);
+        assert_eq!(legacy_relay.version, "0.1.0");
+        assert_eq!(legacy_relay.capabilities, PeerCapabilities::text_only());
+        assert!(!is_relay_agent(&format!(
+            "scmessenger/0.2.0/full/relay/12D3KooWExample/{}",
+            PeerCapabilities::local().to_agent_segment()
+        )));
+
+        // Other software never counts, whatever its text says.
+        assert!(!is_relay_agent("rust-libp2p/0.47.0 relay"));
+        assert!(!is_relay_agent("scm/ (relay)"));
+        assert_eq!(AgentInfo::parse("scm/1.0 (gateway)"), None);
+    }
 }
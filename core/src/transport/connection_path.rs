@@ -0,0 +1,141 @@
+//! Connection-path state — how this node currently reaches the mesh
+//!
+//! A single pure classification shared by the native core, the mobile
+//! `MeshService` and the WASM client, so every platform reports the same
+//! state for the same inputs.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Reachability as reported by AutoNAT (`SwarmEvent::NatStatusChanged`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NatReachability {
+    Public,
+    Private,
+    Unknown,
+}
+
+impl NatReachability {
+    /// Parse the swarm's NAT status string: `"public:<addr>"`, `"private"`
+    /// or `"unknown"`. Anything unrecognised is `Unknown`.
+    pub fn from_status_str(status: &str) -> Self {
+        let status = status.trim().to_ascii_lowercase();
+        if status.starts_with("public") {
+            NatReachability::Public
+        } else if status == "private" {
+            NatReachability::Private
+        } else {
+            NatReachability::Unknown
+        }
+    }
+}
+
+/// How the node is currently connected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConnectionPath {
+    /// Transport not running.
+    Disconnected,
+    /// Running, but no peers connected yet.
+    Bootstrapping,
+    /// Peers connected and the node is directly dialable.
+    DirectPreferred,
+    /// Peers connected, but inbound traffic has to come through relays
+    /// (private NAT or no listener).
+    RelayFallback,
+}
+
+impl ConnectionPath {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConnectionPath::Disconnected => "Disconnected",
+            ConnectionPath::Bootstrapping => "Bootstrapping",
+            ConnectionPath::DirectPreferred => "DirectPreferred",
+            ConnectionPath::RelayFallback => "RelayFallback",
+        }
+    }
+}
+
+impl fmt::Display for ConnectionPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Classify a running node. Callers whose transport is not running report
+/// `ConnectionPath::Disconnected` without calling this.
+///
+/// An unknown NAT with an active listener is treated as direct, matching
+/// what the node advertises until AutoNAT says otherwise.
+pub fn connection_path(
+    peer_count: usize,
+    listener_count: usize,
+    nat: NatReachability,
+) -> ConnectionPath {
+    if peer_count == 0 {
+        ConnectionPath::Bootstrapping
+    } else if listener_count == 0 || nat == NatReachability::Private {
+        ConnectionPath::RelayFallback
+    } else {
+        ConnectionPath::DirectPreferred
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_peers_is_bootstrapping() {
+        for nat in [
+            NatReachability::Public,
+            NatReachability::Private,
+            NatReachability::Unknown,
+        ] {
+            assert_eq!(connection_path(0, 2, nat), ConnectionPath::Bootstrapping);
+        }
+    }
+
+    #[test]
+    fn private_nat_or_no_listener_falls_back_to_relay() {
+        assert_eq!(
+            connection_path(3, 2, NatReachability::Private),
+            ConnectionPath::RelayFallback
+        );
+        assert_eq!(
+            connection_path(3, 0, NatReachability::Public),
+            ConnectionPath::RelayFallback
+        );
+    }
+
+    #[test]
+    fn public_or_unknown_with_listener_is_direct() {
+        assert_eq!(
+            connection_path(1, 1, NatReachability::Public),
+            ConnectionPath::DirectPreferred
+        );
+        assert_eq!(
+            connection_path(1, 1, NatReachability::Unknown),
+            ConnectionPath::DirectPreferred
+        );
+    }
+
+    #[test]
+    fn parses_swarm_nat_strings() {
+        assert_eq!(
+            NatReachability::from_status_str("public:/ip4/1.2.3.4/tcp/9001"),
+            NatReachability::Public
+        );
+        assert_eq!(
+            NatReachability::from_status_str("private"),
+            NatReachability::Private
+        );
+        assert_eq!(
+            NatReachability::from_status_str("unknown"),
+            NatReachability::Unknown
+        );
+        assert_eq!(
+            NatReachability::from_status_str("symmetric"),
+            NatReachability::Unknown
+        );
+    }
+}
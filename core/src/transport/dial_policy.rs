@@ -255,6 +255,61 @@ impl Default for DialPolicyManager {
     }
 }
 
+/// Throttle for app-initiated dials (`SwarmHandle::dial_with_backoff`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppDialPolicy {
+    /// Minimum time between two dials the app actually initiates to the same
+    /// address; calls inside the window are suppressed.
+    pub min_interval: Duration,
+}
+
+impl Default for AppDialPolicy {
+    fn default() -> Self {
+        Self {
+            min_interval: Duration::from_secs(5),
+        }
+    }
+}
+
+/// What `SwarmHandle::dial_with_backoff` did with a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppDialOutcome {
+    /// A dial was started.
+    Initiated,
+    /// A dial to the same address was started within the policy's
+    /// `min_interval`, so this one was coalesced into it.
+    Suppressed,
+}
+
+/// Per-address record of app-initiated dials, owned by the swarm loop.
+/// Keeps a UI retry button from turning into a dial storm.
+#[derive(Debug, Default)]
+pub struct AppDialCoalescer {
+    last_initiated: HashMap<String, Instant>,
+}
+
+impl AppDialCoalescer {
+    /// Returns true (and records the dial) if a dial to `addr_key` may start
+    /// at `now` under `policy`.
+    pub fn admit(&mut self, addr_key: &str, policy: AppDialPolicy, now: Instant) -> bool {
+        if let Some(last) = self.last_initiated.get(addr_key) {
+            if now.saturating_duration_since(*last) < policy.min_interval {
+                debug!(addr_key=%addr_key, "[DIAL-POLICY] App dial suppressed (within min interval)");
+                return false;
+            }
+        }
+        self.last_initiated.insert(addr_key.to_string(), now);
+        true
+    }
+
+    /// Forget addresses not dialed for longer than `max_age`.
+    pub fn prune(&mut self, max_age: Duration) {
+        let now = Instant::now();
+        self.last_initiated
+            .retain(|_, last| now.saturating_duration_since(*last) <= max_age);
+    }
+}
+
 /// Utility function to extract the address key from a Multiaddr (strip /p2p/ component).
 pub fn multiaddr_to_key(addr: &Multiaddr) -> String {
     use libp2p::multiaddr::Protocol;
@@ -357,6 +412,32 @@ mod tests {
     use super::*;
     use std::thread;
 
+    #[test]
+    fn test_app_dial_coalescer_suppresses_rapid_repeats() {
+        let mut coalescer = AppDialCoalescer::default();
+        let policy = AppDialPolicy {
+            min_interval: Duration::from_secs(5),
+        };
+        let start = Instant::now();
+
+        assert!(coalescer.admit("/ip4/10.0.0.1/tcp/4001", policy, start));
+        for ms in [1, 100, 4_999] {
+            assert!(!coalescer.admit(
+                "/ip4/10.0.0.1/tcp/4001",
+                policy,
+                start + Duration::from_millis(ms)
+            ));
+        }
+        // Other addresses are tracked independently.
+        assert!(coalescer.admit("/ip4/10.0.0.2/tcp/4001", policy, start));
+        // Once the window has passed the next dial goes through.
+        assert!(coalescer.admit(
+            "/ip4/10.0.0.1/tcp/4001",
+            policy,
+            start + Duration::from_secs(5)
+        ));
+    }
+
     #[test]
     fn test_backoff_state_creation() {
         let state = PerPeerBackoffState::new(None);
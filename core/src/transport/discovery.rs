@@ -4,6 +4,9 @@
 // advertises and discovers peers on the network. Each mode offers different
 // privacy/discoverability tradeoffs.
 
+pub use crate::settings::AdvertiseScope;
+use crate::settings::{DiscoveryMode as MeshDiscoveryMode, GossipProfile, KadMode};
+use crate::transport::capability::NodeRole;
 use chacha20poly1305::{
     aead::{Aead, KeyInit, Payload},
     XChaCha20Poly1305,
@@ -101,6 +104,31 @@ pub struct DiscoveryConfig {
     pub advertise_protocols: bool,
     /// Whether to accept incoming connections from unknown peers
     pub accept_unknown_peers: bool,
+    /// Browser builds only: add the libp2p WebRTC transport alongside
+    /// websocket-websys so `/webrtc-direct` peers can be dialed. It cannot
+    /// listen, so browser pairs still need a relay. Ignored on native
+    /// targets.
+    #[serde(default, alias = "enable_webrtc")]
+    pub enable_webrtc_direct: bool,
+    /// Gossipsub mesh tuning for this node
+    #[serde(default)]
+    pub gossip_profile: GossipProfile,
+    /// Kademlia DHT role for this node
+    #[serde(default)]
+    pub kad_mode: KadMode,
+    /// Which addresses are advertised and accepted into the DHT
+    #[serde(default)]
+    pub advertise_scope: AdvertiseScope,
+    /// Version in the Identify agent string; `None` uses the crate version
+    #[serde(default)]
+    pub agent_version: Option<String>,
+    /// Role in the Identify agent string; `None` follows the `headless` flag
+    #[serde(default)]
+    pub node_role: Option<NodeRole>,
+    /// Mesh discovery mode; Paranoid stops dialing second-hand peers and
+    /// answering ledger exchange
+    #[serde(default)]
+    pub mesh_discovery_mode: MeshDiscoveryMode,
 }
 
 impl Default for DiscoveryConfig {
@@ -109,6 +137,13 @@ impl Default for DiscoveryConfig {
             mode: DiscoveryMode::Open,
             advertise_protocols: true,
             accept_unknown_peers: true,
+            enable_webrtc_direct: false,
+            gossip_profile: GossipProfile::Default,
+            kad_mode: KadMode::default(),
+            advertise_scope: AdvertiseScope::default(),
+            agent_version: None,
+            node_role: None,
+            mesh_discovery_mode: MeshDiscoveryMode::default(),
         }
     }
 }
@@ -120,6 +155,13 @@ impl DiscoveryConfig {
             mode,
             advertise_protocols: true,
             accept_unknown_peers: true,
+            enable_webrtc_direct: false,
+            gossip_profile: GossipProfile::Default,
+            kad_mode: KadMode::default(),
+            advertise_scope: AdvertiseScope::default(),
+            agent_version: None,
+            node_role: None,
+            mesh_discovery_mode: MeshDiscoveryMode::default(),
         }
     }
 
@@ -134,6 +176,48 @@ impl DiscoveryConfig {
         self.accept_unknown_peers = accept;
         self
     }
+
+    /// Set whether the browser swarm can dial `/webrtc-direct` peers
+    pub fn with_webrtc_direct(mut self, enable: bool) -> Self {
+        self.enable_webrtc_direct = enable;
+        self
+    }
+
+    /// Set the gossipsub mesh profile
+    pub fn with_gossip_profile(mut self, profile: GossipProfile) -> Self {
+        self.gossip_profile = profile;
+        self
+    }
+
+    /// Set the Kademlia DHT role
+    pub fn with_kad_mode(mut self, mode: KadMode) -> Self {
+        self.kad_mode = mode;
+        self
+    }
+
+    /// Set which addresses are advertised and accepted into the DHT
+    pub fn with_advertise_scope(mut self, scope: AdvertiseScope) -> Self {
+        self.advertise_scope = scope;
+        self
+    }
+
+    /// Set the version advertised in the Identify agent string
+    pub fn with_agent_version(mut self, version: impl Into<String>) -> Self {
+        self.agent_version = Some(version.into());
+        self
+    }
+
+    /// Set the role advertised in the Identify agent string
+    pub fn with_node_role(mut self, role: NodeRole) -> Self {
+        self.node_role = Some(role);
+        self
+    }
+
+    /// Set the mesh discovery mode (see `settings::DiscoveryMode`)
+    pub fn with_mesh_discovery_mode(mut self, mode: MeshDiscoveryMode) -> Self {
+        self.mesh_discovery_mode = mode;
+        self
+    }
 }
 
 /// Beacon payload for encrypted discovery
@@ -358,6 +442,13 @@ mod tests {
             mode: DiscoveryMode::Open,
             advertise_protocols: true,
             accept_unknown_peers: false,
+            enable_webrtc_direct: true,
+            gossip_profile: GossipProfile::Relay,
+            kad_mode: KadMode::Client,
+            advertise_scope: AdvertiseScope::WanOnly,
+            agent_version: Some("9.9.9".into()),
+            node_role: Some(NodeRole::Relay),
+            mesh_discovery_mode: MeshDiscoveryMode::Paranoid,
         };
 
         let json = serde_json::to_string(&config).expect("Should serialize");
@@ -366,6 +457,25 @@ mod tests {
         assert_eq!(recovered.mode, config.mode);
         assert_eq!(recovered.advertise_protocols, config.advertise_protocols);
         assert_eq!(recovered.accept_unknown_peers, config.accept_unknown_peers);
+        assert_eq!(recovered.enable_webrtc_direct, config.enable_webrtc_direct);
+        assert_eq!(recovered.kad_mode, config.kad_mode);
+        assert_eq!(recovered.advertise_scope, config.advertise_scope);
+        assert_eq!(recovered.agent_version, config.agent_version);
+        assert_eq!(recovered.node_role, config.node_role);
+        assert_eq!(recovered.mesh_discovery_mode, config.mesh_discovery_mode);
+    }
+
+    #[test]
+    fn test_discovery_config_webrtc_defaults_off() {
+        // Configs persisted before the flag existed must keep WebRTC disabled.
+        let legacy = r#"{"mode":"Open","advertise_protocols":true,"accept_unknown_peers":true}"#;
+        let config: DiscoveryConfig = serde_json::from_str(legacy).expect("Should deserialize");
+        assert!(!config.enable_webrtc_direct);
+        assert!(
+            DiscoveryConfig::default()
+                .with_webrtc_direct(true)
+                .enable_webrtc_direct
+        );
     }
 
     #[test]
@@ -26,6 +26,83 @@ pub enum EscalationPolicy {
     Balanced,
 }
 
+/// When to move a peer's traffic up the BLE → WiFi → Internet ladder.
+///
+/// A transport is kept while the message fits under its size cap and the
+/// expected delivery time (observed latency plus transfer time at the
+/// transport's estimated bandwidth) stays within `max_delivery_ms`. Below
+/// `battery_floor_percent` only the size caps force an escalation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EscalationThresholds {
+    /// Largest message sent over BLE.
+    pub ble_max_message_bytes: usize,
+    /// Largest message sent over WiFi Aware / WiFi Direct.
+    pub wifi_max_message_bytes: usize,
+    /// Expected delivery time above which a link counts as too slow.
+    pub max_delivery_ms: u64,
+    /// Battery percentage below which latency-driven escalation is suppressed.
+    pub battery_floor_percent: u8,
+    pub ble_enabled: bool,
+    pub wifi_enabled: bool,
+    pub internet_enabled: bool,
+}
+
+impl Default for EscalationThresholds {
+    fn default() -> Self {
+        Self::from_mesh_settings(&crate::settings::MeshSettings::default())
+    }
+}
+
+impl EscalationThresholds {
+    /// Thresholds backed by the user's mesh settings.
+    pub fn from_mesh_settings(settings: &crate::settings::MeshSettings) -> Self {
+        Self {
+            ble_max_message_bytes: settings.escalation_ble_max_bytes as usize,
+            wifi_max_message_bytes: settings.escalation_wifi_max_bytes as usize,
+            max_delivery_ms: u64::from(settings.escalation_max_delivery_ms),
+            battery_floor_percent: settings.battery_floor,
+            ble_enabled: settings.ble_enabled,
+            wifi_enabled: settings.wifi_aware_enabled || settings.wifi_direct_enabled,
+            internet_enabled: settings.internet_enabled,
+        }
+    }
+
+    fn permits(&self, transport: TransportType) -> bool {
+        match transport {
+            TransportType::BLE => self.ble_enabled,
+            TransportType::WiFiAware | TransportType::WiFiDirect => self.wifi_enabled,
+            TransportType::Internet => self.internet_enabled,
+            TransportType::Local => true,
+        }
+    }
+
+    fn max_message_bytes(&self, transport: TransportType) -> usize {
+        match transport {
+            TransportType::BLE => self.ble_max_message_bytes,
+            TransportType::WiFiAware | TransportType::WiFiDirect => self.wifi_max_message_bytes,
+            TransportType::Internet | TransportType::Local => usize::MAX,
+        }
+    }
+}
+
+/// Position on the escalation ladder (higher = more capable, more power).
+fn ladder_tier(transport: TransportType) -> u8 {
+    match transport {
+        TransportType::Local | TransportType::BLE => 0,
+        TransportType::WiFiAware | TransportType::WiFiDirect => 1,
+        TransportType::Internet => 2,
+    }
+}
+
+/// Conditions for a pending send, fed to `EscalationEngine::escalate_for`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LinkConditions {
+    pub message_bytes: usize,
+    /// Measured latency of the current link, if known.
+    pub observed_latency_ms: Option<u32>,
+    pub battery_percent: Option<u8>,
+}
+
 /// Errors that can occur during escalation
 #[derive(Error, Debug, Clone)]
 pub enum EscalationError {
@@ -56,6 +133,8 @@ pub struct EscalationEngine {
     states: Arc<RwLock<HashMap<[u8; 32], EscalationState>>>,
     /// Escalation policy
     policy: EscalationPolicy,
+    /// Size / latency / battery thresholds for `escalate_for`
+    thresholds: EscalationThresholds,
     /// Transport capabilities (for scoring)
     capabilities: Arc<RwLock<HashMap<TransportType, TransportCapabilities>>>,
 }
@@ -66,10 +145,34 @@ impl EscalationEngine {
         Self {
             states: Arc::new(RwLock::new(HashMap::new())),
             policy,
+            thresholds: EscalationThresholds::default(),
             capabilities: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
+    /// Create an engine with explicit thresholds
+    pub fn with_thresholds(policy: EscalationPolicy, thresholds: EscalationThresholds) -> Self {
+        let mut engine = Self::new(policy);
+        engine.thresholds = thresholds;
+        engine
+    }
+
+    pub fn policy(&self) -> EscalationPolicy {
+        self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: EscalationPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn thresholds(&self) -> EscalationThresholds {
+        self.thresholds
+    }
+
+    pub fn set_thresholds(&mut self, thresholds: EscalationThresholds) {
+        self.thresholds = thresholds;
+    }
+
     /// Set capabilities for a transport type
     pub fn set_capabilities(&self, transport: TransportType, capabilities: TransportCapabilities) {
         let mut caps = self.capabilities.write();
@@ -155,6 +258,84 @@ impl EscalationEngine {
         Ok(fallback)
     }
 
+    /// Pick the transport for a send under `conditions`, escalating along
+    /// BLE → WiFi → Internet only as far as needed. Stays on the current
+    /// transport while it still fits; if nothing fits, uses the highest tier
+    /// available. Returns the transport now recorded for the peer.
+    pub fn escalate_for(
+        &self,
+        peer_id: [u8; 32],
+        conditions: &LinkConditions,
+    ) -> Result<TransportType, EscalationError> {
+        let mut states = self.states.write();
+        let state = states
+            .get_mut(&peer_id)
+            .ok_or(EscalationError::NotPossible)?;
+        let caps = self.capabilities.read();
+        let thresholds = self.thresholds;
+
+        let allowed: Vec<TransportType> = state
+            .available_transports
+            .iter()
+            .copied()
+            .filter(|t| thresholds.permits(*t))
+            .collect();
+        if allowed.is_empty() {
+            return Err(EscalationError::NoTransportsAvailable);
+        }
+
+        let under_cap =
+            |t: TransportType| conditions.message_bytes <= thresholds.max_message_bytes(t);
+        let fast_enough = |t: TransportType, latency_ms: Option<u32>| {
+            let cap = caps
+                .get(&t)
+                .cloned()
+                .unwrap_or(TransportCapabilities::for_transport(t));
+            let latency = u64::from(latency_ms.unwrap_or(cap.estimated_latency_ms));
+            let transfer_ms = (conditions.message_bytes as u64).saturating_mul(8_000)
+                / cap.estimated_bandwidth_bps.max(1);
+            latency.saturating_add(transfer_ms) <= thresholds.max_delivery_ms
+        };
+        let low_battery = conditions
+            .battery_percent
+            .is_some_and(|pct| pct < thresholds.battery_floor_percent);
+
+        let current = state.current_transport;
+        if allowed.contains(&current)
+            && under_cap(current)
+            && (low_battery || fast_enough(current, conditions.observed_latency_ms))
+        {
+            return Ok(current);
+        }
+
+        let floor = if allowed.contains(&current) {
+            ladder_tier(current) + 1
+        } else {
+            0
+        };
+        let target = allowed
+            .iter()
+            .copied()
+            .filter(|t| ladder_tier(*t) >= floor && under_cap(*t))
+            .filter(|t| low_battery || fast_enough(*t, None))
+            .min_by_key(|t| ladder_tier(*t))
+            .or_else(|| allowed.iter().copied().max_by_key(|t| ladder_tier(*t)))
+            .ok_or(EscalationError::NoTransportsAvailable)?;
+
+        if target != current {
+            state.current_transport = target;
+            state.last_escalation_attempt = Some(web_time::SystemTime::now());
+            debug!(
+                "Escalated peer {:x?} {} -> {} for {} byte message",
+                &peer_id[..8],
+                current,
+                target,
+                conditions.message_bytes
+            );
+        }
+        Ok(target)
+    }
+
     /// Get the current transport for a peer
     pub fn current_transport(&self, peer_id: [u8; 32]) -> Option<TransportType> {
         let states = self.states.read();
@@ -333,6 +514,12 @@ impl EscalationEngine {
     }
 }
 
+impl Default for EscalationEngine {
+    fn default() -> Self {
+        Self::new(EscalationPolicy::default())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -343,6 +530,88 @@ mod tests {
         id
     }
 
+    #[test]
+    fn test_large_message_on_degraded_ble_escalates_to_internet() {
+        let engine = EscalationEngine::new(EscalationPolicy::PreferLowPower);
+        let peer_id = create_peer_id(9);
+        engine
+            .init_peer(peer_id, vec![TransportType::BLE, TransportType::Internet])
+            .unwrap();
+        assert_eq!(engine.current_transport(peer_id), Some(TransportType::BLE));
+
+        let degraded_ble = Some(1_500);
+
+        let tiny = LinkConditions {
+            message_bytes: 120,
+            observed_latency_ms: degraded_ble,
+            battery_percent: Some(80),
+        };
+        assert_eq!(
+            engine.escalate_for(peer_id, &tiny).unwrap(),
+            TransportType::BLE
+        );
+        assert_eq!(engine.current_transport(peer_id), Some(TransportType::BLE));
+
+        let large = LinkConditions {
+            message_bytes: 256 * 1024,
+            observed_latency_ms: degraded_ble,
+            battery_percent: Some(80),
+        };
+        assert_eq!(
+            engine.escalate_for(peer_id, &large).unwrap(),
+            TransportType::Internet
+        );
+        assert_eq!(
+            engine.current_transport(peer_id),
+            Some(TransportType::Internet)
+        );
+    }
+
+    #[test]
+    fn test_escalation_respects_disabled_transports_and_battery() {
+        let settings = crate::settings::MeshSettings {
+            internet_enabled: false,
+            wifi_direct_enabled: true,
+            ..Default::default()
+        };
+        let engine = EscalationEngine::with_thresholds(
+            EscalationPolicy::PreferLowPower,
+            EscalationThresholds::from_mesh_settings(&settings),
+        );
+        let peer_id = create_peer_id(10);
+        engine
+            .init_peer(
+                peer_id,
+                vec![
+                    TransportType::BLE,
+                    TransportType::WiFiDirect,
+                    TransportType::Internet,
+                ],
+            )
+            .unwrap();
+
+        // Slow link but low battery: a message under the BLE cap stays put.
+        let low_battery = LinkConditions {
+            message_bytes: 32 * 1024,
+            observed_latency_ms: Some(1_900),
+            battery_percent: Some(5),
+        };
+        assert_eq!(
+            engine.escalate_for(peer_id, &low_battery).unwrap(),
+            TransportType::BLE
+        );
+
+        // Oversized for BLE with Internet disabled: WiFi is the ceiling.
+        let huge = LinkConditions {
+            message_bytes: 64 * 1024 * 1024,
+            ..Default::default()
+        };
+        assert_eq!(
+            engine.escalate_for(peer_id, &huge).unwrap(),
+            TransportType::WiFiDirect
+        );
+    }
+
     #[test]
     fn test_escalation_engine_creation() {
         let engine = EscalationEngine::new(EscalationPolicy::Balanced);
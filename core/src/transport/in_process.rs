@@ -0,0 +1,261 @@
+//! In-process loopback transport for tests
+//!
+//! Speaks the `TransportCommand` / `TransportEvent` vocabulary from
+//! `transport::abstraction`, but moves bytes between endpoints over channels
+//! inside one process. Two cores can exchange real envelopes with no sockets,
+//! no libp2p swarm and no async runtime, so escalation, routing and outbox
+//! flush can be exercised deterministically.
+//!
+//! Available to in-crate tests and, for integration tests and dependants,
+//! behind the `test-transport` feature.
+
+use crate::transport::abstraction::{
+    TransportCapabilities, TransportCommand, TransportError, TransportEvent, TransportType,
+};
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+
+#[derive(Default)]
+struct NetworkState {
+    /// Event inbox of every attached endpoint
+    endpoints: HashMap<[u8; 32], Sender<TransportEvent>>,
+    /// Established links, stored both ways round
+    links: HashSet<([u8; 32], [u8; 32])>,
+}
+
+/// A shared in-memory "network" that endpoints attach to.
+#[derive(Clone, Default)]
+pub struct InProcessNetwork {
+    state: Arc<Mutex<NetworkState>>,
+}
+
+impl InProcessNetwork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attach an endpoint for `peer_id` (typically an identity public key).
+    /// Re-attaching the same id replaces the previous endpoint's inbox.
+    pub fn attach(&self, peer_id: [u8; 32]) -> InProcessTransport {
+        let (tx, rx) = channel();
+        self.state.lock().endpoints.insert(peer_id, tx);
+        InProcessTransport {
+            local_id: peer_id,
+            network: self.clone(),
+            events: rx,
+        }
+    }
+
+    /// Attach two endpoints and connect them to each other.
+    pub fn pair(a: [u8; 32], b: [u8; 32]) -> (InProcessTransport, InProcessTransport) {
+        let network = Self::new();
+        let ta = network.attach(a);
+        let tb = network.attach(b);
+        ta.execute(TransportCommand::Connect {
+            peer_id: b,
+            addr: Vec::new(),
+        })
+        .expect("freshly attached peer is reachable");
+        (ta, tb)
+    }
+}
+
+/// One endpoint on an `InProcessNetwork`.
+pub struct InProcessTransport {
+    local_id: [u8; 32],
+    network: InProcessNetwork,
+    events: Receiver<TransportEvent>,
+}
+
+impl InProcessTransport {
+    pub fn local_id(&self) -> [u8; 32] {
+        self.local_id
+    }
+
+    pub fn capabilities(&self) -> TransportCapabilities {
+        TransportCapabilities::for_transport(TransportType::Local)
+    }
+
+    pub fn is_connected(&self, peer_id: &[u8; 32]) -> bool {
+        self.network
+            .state
+            .lock()
+            .links
+            .contains(&(self.local_id, *peer_id))
+    }
+
+    /// Carry out a command. Events it causes are queued on the affected
+    /// endpoints immediately, so a following `try_next_event` sees them.
+    pub fn execute(&self, command: TransportCommand) -> Result<(), TransportError> {
+        let mut state = self.network.state.lock();
+        let local = self.local_id;
+        let notify = |state: &NetworkState, to: &[u8; 32], event: TransportEvent| {
+            if let Some(tx) = state.endpoints.get(to) {
+                let _ = tx.send(event);
+            }
+        };
+
+        match command {
+            TransportCommand::SendData { peer_id, data, .. } => {
+                if !state.links.contains(&(local, peer_id)) {
+                    return Err(TransportError::PeerNotFound(hex::encode(peer_id)));
+                }
+                if data.len() > self.capabilities().max_payload_size {
+                    return Err(TransportError::InvalidPayload(format!(
+                        "{} bytes exceeds the local transport limit",
+                        data.len()
+                    )));
+                }
+                notify(
+                    &state,
+                    &peer_id,
+                    TransportEvent::DataReceived {
+                        peer_id: local,
+                        transport: TransportType::Local,
+                        data,
+                    },
+                );
+            }
+            TransportCommand::Connect { peer_id, .. } => {
+                if peer_id == local || !state.endpoints.contains_key(&peer_id) {
+                    return Err(TransportError::PeerNotFound(hex::encode(peer_id)));
+                }
+                if state.links.insert((local, peer_id)) {
+                    state.links.insert((peer_id, local));
+                    for (to, from) in [(local, peer_id), (peer_id, local)] {
+                        notify(
+                            &state,
+                            &to,
+                            TransportEvent::ConnectionEstablished {
+                                peer_id: from,
+                                transport: TransportType::Local,
+                            },
+                        );
+                    }
+                }
+            }
+            TransportCommand::Disconnect { peer_id } => {
+                if state.links.remove(&(local, peer_id)) {
+                    state.links.remove(&(peer_id, local));
+                    for (to, from) in [(local, peer_id), (peer_id, local)] {
+                        notify(
+                            &state,
+                            &to,
+                            TransportEvent::PeerDisconnected {
+                                peer_id: from,
+                                transport: TransportType::Local,
+                            },
+                        );
+                    }
+                }
+            }
+            TransportCommand::StartDiscovery => {
+                let mut peers: Vec<[u8; 32]> = state
+                    .endpoints
+                    .keys()
+                    .filter(|id| **id != local)
+                    .copied()
+                    .collect();
+                // HashMap order is random; keep discovery order reproducible.
+                peers.sort_unstable();
+                for peer_id in peers {
+                    notify(
+                        &state,
+                        &local,
+                        TransportEvent::PeerDiscovered {
+                            peer_id,
+                            transport: TransportType::Local,
+                            addr: Vec::new(),
+                        },
+                    );
+                }
+            }
+            TransportCommand::StopDiscovery => {}
+        }
+        Ok(())
+    }
+
+    /// Convenience for `execute(SendData { .. })` at default priority.
+    pub fn send(&self, peer_id: [u8; 32], data: Vec<u8>) -> Result<(), TransportError> {
+        self.execute(TransportCommand::SendData {
+            peer_id,
+            data,
+            priority: 0,
+        })
+    }
+
+    /// Next queued event, if any. Never blocks.
+    pub fn try_next_event(&self) -> Option<TransportEvent> {
+        self.events.try_recv().ok()
+    }
+
+    /// Every queued event, oldest first.
+    pub fn drain_events(&self) -> Vec<TransportEvent> {
+        self.events.try_iter().collect()
+    }
+}
+
+impl Drop for InProcessTransport {
+    fn drop(&mut self) {
+        let mut state = self.network.state.lock();
+        state.endpoints.remove(&self.local_id);
+        let local = self.local_id;
+        state.links.retain(|(a, b)| *a != local && *b != local);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connect_send_disconnect() {
+        let network = InProcessNetwork::new();
+        let a = network.attach([1; 32]);
+        let b = network.attach([2; 32]);
+
+        assert!(matches!(
+            a.send([2; 32], b"early".to_vec()),
+            Err(TransportError::PeerNotFound(_))
+        ));
+        a.execute(TransportCommand::StartDiscovery).unwrap();
+        assert!(matches!(
+            a.drain_events().as_slice(),
+            [TransportEvent::PeerDiscovered {
+                peer_id: [2, ..],
+                ..
+            }]
+        ));
+
+        a.execute(TransportCommand::Connect {
+            peer_id: [2; 32],
+            addr: Vec::new(),
+        })
+        .unwrap();
+        assert!(b.is_connected(&[1; 32]));
+        a.drain_events();
+        b.drain_events();
+
+        a.send([2; 32], b"hello".to_vec()).unwrap();
+        match b.try_next_event() {
+            Some(TransportEvent::DataReceived { peer_id, data, .. }) => {
+                assert_eq!(peer_id, [1; 32]);
+                assert_eq!(data, b"hello");
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+
+        b.execute(TransportCommand::Disconnect { peer_id: [1; 32] })
+            .unwrap();
+        assert!(matches!(
+            a.try_next_event(),
+            Some(TransportEvent::PeerDisconnected {
+                peer_id: [2, ..],
+                ..
+            })
+        ));
+        assert!(a.send([2; 32], b"late".to_vec()).is_err());
+    }
+}
@@ -21,6 +21,8 @@ pub const ROUTE_REASON_RELAY_SUCCESS_SCORE: &str = "RELAY_SUCCESS_SCORE";
 pub const ROUTE_REASON_RELAY_TIEBREAK_LAST_SUCCESS: &str = "RELAY_TIEBREAK_LAST_SUCCESS";
 /// Route reason: relay ordering fell back to deterministic peer-id tie-break.
 pub const ROUTE_REASON_RELAY_TIEBREAK_PEER_ID: &str = "RELAY_TIEBREAK_PEER_ID";
+/// Route reason: relay the user chose for this contact, tried before ranked relays.
+pub const ROUTE_REASON_RELAY_CONTACT_PREFERENCE: &str = "RELAY_CONTACT_PREFERENCE";
 
 /// Ranked route candidate with deterministic metadata for trace logging.
 #[derive(Debug, Clone)]
@@ -32,6 +34,11 @@ pub struct RankedRoute {
     pub latest_success_order: u64,
 }
 
+/// How long a relay that reported `relay_budget_exhausted` is left out of
+/// route ranking. Relay budgets are hourly, so a short cooldown lets it back
+/// in once its window has likely rolled over.
+pub const RELAY_CONGESTION_COOLDOWN_SECS: u64 = 300;
+
 /// Output of advancing to the next route candidate.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RouteCursorAdvance {
@@ -62,6 +69,20 @@ pub fn advance_route_cursor(current_index: usize, candidate_count: usize) -> Rou
     }
 }
 
+/// Relay peer named by a contact preference: either a bare PeerId or a
+/// multiaddr ending in `/p2p/<PeerId>`.
+pub fn relay_peer_from_str(relay: &str) -> Option<PeerId> {
+    let relay = relay.trim();
+    if let Ok(peer_id) = relay.parse::<PeerId>() {
+        return Some(peer_id);
+    }
+    let addr = relay.parse::<libp2p::Multiaddr>().ok()?;
+    addr.iter().find_map(|proto| match proto {
+        libp2p::multiaddr::Protocol::P2p(peer_id) => Some(peer_id),
+        _ => None,
+    })
+}
+
 fn unix_now_secs() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -303,6 +324,59 @@ impl RetryStrategy {
     }
 }
 
+/// Swarm-level backoff policy for pending message deliveries.
+///
+/// Each dispatch of a `PendingMessage` schedules its next retry
+/// `base_delay * 2^attempt` later (capped at `max_delay`), perturbed by up
+/// to `jitter` (a fraction of the delay) in either direction so that many
+/// queued messages do not retry in lockstep.
+#[derive(Debug, Clone)]
+pub struct DeliveryPolicy {
+    /// Delay before the first retry.
+    pub base_delay: Duration,
+    /// Upper bound on any single retry delay, jitter included.
+    pub max_delay: Duration,
+    /// Total dispatches allowed before giving up.
+    /// `None` keeps retrying until delivered (default).
+    pub max_attempts: Option<u32>,
+    /// Jitter fraction in `0.0..=1.0`.
+    pub jitter: f64,
+}
+
+impl Default for DeliveryPolicy {
+    fn default() -> Self {
+        Self {
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            max_attempts: None, // WS1: no terminal retry cap
+            jitter: 0.1,
+        }
+    }
+}
+
+impl DeliveryPolicy {
+    /// Delay before the retry following the given 0-indexed `attempt`.
+    pub fn next_retry_delay(&self, attempt: u32) -> Duration {
+        self.next_retry_delay_with_sample(attempt, rand::random::<f64>())
+    }
+
+    /// Deterministic core of `next_retry_delay`: `sample` in `0.0..1.0`
+    /// selects where in the jitter window the delay lands.
+    pub fn next_retry_delay_with_sample(&self, attempt: u32, sample: f64) -> Duration {
+        let max_ms = self.max_delay.as_millis() as f64;
+        let exp_ms = self.base_delay.as_millis() as f64 * 2f64.powi(attempt.min(32) as i32);
+        let capped_ms = exp_ms.min(max_ms);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let factor = 1.0 + jitter * (2.0 * sample.clamp(0.0, 1.0) - 1.0);
+        Duration::from_millis((capped_ms * factor).min(max_ms).round() as u64)
+    }
+
+    /// Whether a message dispatched `attempts` times should be given up on.
+    pub fn is_exhausted(&self, attempts: u32) -> bool {
+        self.max_attempts.is_some_and(|max| attempts >= max)
+    }
+}
+
 /// Tracks ongoing delivery attempts
 #[derive(Debug, Clone)]
 pub struct DeliveryAttempt {
@@ -369,6 +443,11 @@ pub struct MultiPathDelivery {
     latest_success_by_route: HashMap<(PeerId, PeerId), u64>,
     /// Monotonic sequence for deterministic "latest successful path" tie-breaks
     success_sequence: u64,
+    /// Relays that rejected a request for budget reasons, with the unix time
+    /// (seconds) until which they are skipped
+    congested_until: HashMap<PeerId, u64>,
+    /// Relays to try first for a recipient, in order (per-contact preference)
+    preferred_relays: HashMap<PeerId, Vec<PeerId>>,
 }
 
 impl Default for MultiPathDelivery {
@@ -385,6 +464,18 @@ impl MultiPathDelivery {
             recipient_recency_by_route: HashMap::new(),
             latest_success_by_route: HashMap::new(),
             success_sequence: 0,
+            congested_until: HashMap::new(),
+            preferred_relays: HashMap::new(),
+        }
+    }
+
+    /// Relays to try, in order, before ranked relays for messages to
+    /// `target`. An empty list clears the preference.
+    pub fn set_preferred_relays(&mut self, target: PeerId, relays: Vec<PeerId>) {
+        if relays.is_empty() {
+            self.preferred_relays.remove(&target);
+        } else {
+            self.preferred_relays.insert(target, relays);
         }
     }
 
@@ -418,12 +509,40 @@ impl MultiPathDelivery {
         self.record_recipient_seen_via_relay(relay_peer, recipient_peer, unix_now_secs());
     }
 
-    /// Deterministic ranked routes: direct-first, then relay ranking policy.
+    /// Mark a relay as out of forwarding budget. It is skipped by
+    /// `ranked_routes` for `RELAY_CONGESTION_COOLDOWN_SECS` without touching
+    /// its reputation: congestion is not unreliability.
+    pub fn record_relay_congested(&mut self, relay_peer: PeerId) {
+        self.record_relay_congested_at(relay_peer, unix_now_secs());
+    }
+
+    fn record_relay_congested_at(&mut self, relay_peer: PeerId, now: u64) {
+        self.congested_until.insert(
+            relay_peer,
+            now.saturating_add(RELAY_CONGESTION_COOLDOWN_SECS),
+        );
+    }
+
+    /// Whether a relay is still inside its congestion cooldown.
+    pub fn is_relay_congested(&self, relay_peer: &PeerId) -> bool {
+        self.is_relay_congested_at(relay_peer, unix_now_secs())
+    }
+
+    fn is_relay_congested_at(&self, relay_peer: &PeerId, now: u64) -> bool {
+        self.congested_until
+            .get(relay_peer)
+            .is_some_and(|until| now < *until)
+    }
+
+    /// Deterministic ranked routes: direct-first, then the recipient's
+    /// preferred relays in the order given, then relay ranking policy.
+    /// Relays inside their congestion cooldown are left out.
     pub fn ranked_routes(&self, target: &PeerId, count: usize) -> Vec<RankedRoute> {
         if count == 0 {
             return Vec::new();
         }
 
+        let now = unix_now_secs();
         let mut routes = Vec::with_capacity(count);
         routes.push(RankedRoute {
             path: vec![*target],
@@ -433,6 +552,29 @@ impl MultiPathDelivery {
             latest_success_order: 0,
         });
 
+        let preferred: Vec<PeerId> = self
+            .preferred_relays
+            .get(target)
+            .map(|relays| {
+                let mut seen = std::collections::HashSet::new();
+                relays
+                    .iter()
+                    .filter(|relay| **relay != *target && seen.insert(**relay))
+                    .filter(|relay| !self.is_relay_congested_at(relay, now))
+                    .copied()
+                    .collect()
+            })
+            .unwrap_or_default();
+        for relay in preferred.iter().take(count.saturating_sub(1)) {
+            routes.push(RankedRoute {
+                path: vec![*relay, *target],
+                reason_code: ROUTE_REASON_RELAY_CONTACT_PREFERENCE,
+                recipient_recency: 0,
+                relay_success_score: 0.0,
+                latest_success_order: 0,
+            });
+        }
+
         #[derive(Debug)]
         struct RelayCandidate {
             relay_peer: PeerId,
@@ -447,6 +589,8 @@ impl MultiPathDelivery {
             .reputations
             .values()
             .filter(|rep| rep.is_reliable && rep.peer_id != *target)
+            .filter(|rep| !self.is_relay_congested_at(&rep.peer_id, now))
+            .filter(|rep| !preferred.contains(&rep.peer_id))
             .map(|rep| {
                 let relay_peer = rep.peer_id;
                 RelayCandidate {
@@ -475,7 +619,7 @@ impl MultiPathDelivery {
                 .then_with(|| a.relay_key.cmp(&b.relay_key))
         });
 
-        for relay in relays.into_iter().take(count.saturating_sub(1)) {
+        for relay in relays.into_iter().take(count.saturating_sub(routes.len())) {
             let reason_code = if relay.recipient_recency > 0 {
                 ROUTE_REASON_RELAY_RECENCY_SUCCESS
             } else if relay.latest_success_order > 0 {
@@ -621,6 +765,56 @@ impl BootstrapCapability {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_delivery_policy_backoff_jitter_and_cap() {
+        let policy = DeliveryPolicy {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(2),
+            max_attempts: Some(4),
+            jitter: 0.2,
+        };
+
+        // Midpoint sample is the un-jittered exponential delay.
+        assert_eq!(
+            policy.next_retry_delay_with_sample(0, 0.5),
+            Duration::from_millis(100)
+        );
+        assert_eq!(
+            policy.next_retry_delay_with_sample(3, 0.5),
+            Duration::from_millis(800)
+        );
+
+        // Jitter stays within +/-20% of the nominal delay.
+        assert_eq!(
+            policy.next_retry_delay_with_sample(2, 0.0),
+            Duration::from_millis(320)
+        );
+        assert_eq!(
+            policy.next_retry_delay_with_sample(2, 1.0),
+            Duration::from_millis(480)
+        );
+        for attempt in 0..4 {
+            let nominal = 100u64 << attempt;
+            let delay = policy.next_retry_delay(attempt).as_millis() as u64;
+            assert!(delay >= nominal * 8 / 10 && delay <= nominal * 12 / 10);
+        }
+
+        // Large attempts are capped at max_delay, jitter included.
+        for sample in [0.0, 0.5, 1.0] {
+            let delay = policy.next_retry_delay_with_sample(40, sample);
+            assert!(delay <= Duration::from_secs(2));
+            assert!(delay >= Duration::from_millis(1600));
+        }
+        assert_eq!(
+            policy.next_retry_delay_with_sample(u32::MAX, 1.0),
+            Duration::from_secs(2)
+        );
+
+        assert!(!policy.is_exhausted(3));
+        assert!(policy.is_exhausted(4));
+        assert!(!DeliveryPolicy::default().is_exhausted(u32::MAX));
+    }
+
     #[test]
     fn test_reputation_calculation() {
         let mut rep = RelayReputation {
@@ -696,4 +890,85 @@ mod tests {
         assert!(delivery.delivery_attempt(&message_id).is_none());
         assert_eq!(delivery.pending_attempts().len(), 0);
     }
+
+    #[test]
+    fn test_preferred_relay_is_tried_first_after_direct() {
+        let mut delivery = MultiPathDelivery::new();
+        let target = PeerId::random();
+        let ranked = PeerId::random();
+        let trusted = PeerId::random();
+
+        delivery.add_relay(ranked);
+        delivery.add_relay(trusted);
+        // Without a preference the relay that last saw the recipient wins.
+        delivery.record_recipient_seen_via_relay(ranked, target, 500);
+        let routes = delivery.ranked_routes(&target, 3);
+        assert_eq!(routes[1].path, vec![ranked, target]);
+
+        delivery.set_preferred_relays(target, vec![trusted]);
+        let routes = delivery.ranked_routes(&target, 3);
+        assert_eq!(routes[0].path, vec![target]);
+        assert_eq!(routes[1].path, vec![trusted, target]);
+        assert_eq!(routes[1].reason_code, ROUTE_REASON_RELAY_CONTACT_PREFERENCE);
+        assert_eq!(routes[2].path, vec![ranked, target]);
+        assert_eq!(routes.len(), 3);
+
+        // Preferences apply per recipient, and can be cleared.
+        let other = PeerId::random();
+        assert!(delivery
+            .ranked_routes(&other, 3)
+            .iter()
+            .all(|route| route.reason_code != ROUTE_REASON_RELAY_CONTACT_PREFERENCE));
+        delivery.set_preferred_relays(target, Vec::new());
+        assert_eq!(
+            delivery.ranked_routes(&target, 3)[1].path,
+            vec![ranked, target]
+        );
+
+        let addr = format!("/ip4/203.0.113.7/tcp/4001/p2p/{}", trusted);
+        assert_eq!(relay_peer_from_str(&addr), Some(trusted));
+        assert_eq!(relay_peer_from_str(&trusted.to_string()), Some(trusted));
+        assert_eq!(relay_peer_from_str("/ip4/203.0.113.7/tcp/4001"), None);
+    }
+
+    #[test]
+    fn test_budget_exhausted_relay_is_skipped_for_next_relay() {
+        let mut delivery = MultiPathDelivery::new();
+        let target = PeerId::random();
+        let relay_busy = PeerId::random();
+        let relay_spare = PeerId::random();
+        let message_id = "congested-relay-msg".to_string();
+
+        delivery.add_relay(relay_busy);
+        delivery.add_relay(relay_spare);
+        delivery.record_recipient_seen_via_relay(relay_busy, target, 200);
+        delivery.record_recipient_seen_via_relay(relay_spare, target, 100);
+        delivery.start_delivery(message_id.clone(), target);
+
+        let routes = delivery.ranked_routes(&target, 3);
+        assert_eq!(routes[1].path, vec![relay_busy, target]);
+        assert_eq!(routes[2].path, vec![relay_spare, target]);
+
+        // The busy relay answers `relay_budget_exhausted`: the first relay
+        // route is now the other one, and delivery through it succeeds.
+        delivery.record_relay_congested(relay_busy);
+        assert!(delivery.is_relay_congested(&relay_busy));
+        let routes = delivery.ranked_routes(&target, 3);
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[1].path, vec![relay_spare, target]);
+
+        delivery.record_success(&message_id, routes[1].path.clone(), 40);
+        assert!(delivery.delivery_attempt(&message_id).is_none());
+
+        // Congestion is not a reputation hit, and it expires.
+        let busy_rep = delivery
+            .reputation()
+            .all_reputations()
+            .into_iter()
+            .find(|rep| rep.peer_id == relay_busy)
+            .unwrap();
+        assert!(busy_rep.is_reliable);
+        let later = unix_now_secs() + RELAY_CONGESTION_COOLDOWN_SECS;
+        assert!(!delivery.is_relay_congested_at(&relay_busy, later));
+    }
 }
@@ -6,11 +6,14 @@ pub mod ble;
 pub mod bootstrap;
 pub mod capability;
 pub mod circuit_breaker;
+pub mod connection_path;
 pub mod diagnostics;
 pub mod dial_policy;
 pub mod discovery;
 pub mod escalation;
 pub mod health;
+#[cfg(any(test, feature = "test-transport"))]
+pub mod in_process;
 pub mod internet;
 pub mod manager;
 pub mod mesh_routing;
@@ -18,11 +21,16 @@ pub mod multiport;
 pub mod nat;
 pub mod observation;
 pub mod peer_broadcast;
+pub mod presence;
+pub mod rate_limit;
 pub mod reflection;
 pub mod relay_health;
 pub mod reputation;
 pub mod routing;
+pub mod rtt;
+pub mod signed_gossip;
 pub mod swarm;
+pub mod topic_limits;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod websocket;
 pub mod wifi_aware;
@@ -37,29 +45,34 @@ pub use behaviour::{
     RegistrationResponse, RelayRequest, RelayResponse,
 };
 pub use bootstrap::{BootstrapConfig, BootstrapManager, BootstrapState};
-pub use capability::can_forward_for_wasm;
+pub use capability::{can_forward_for_wasm, is_relay_agent, AgentInfo, NodeRole, PeerCapabilities};
 pub use circuit_breaker::{
     CircuitBreakerConfig, CircuitBreakerManager, CircuitBreakerStats, CircuitState,
 };
+pub use connection_path::{connection_path, ConnectionPath, NatReachability};
 pub use diagnostics::{
     get_network_diagnostics_report, NetworkDiagnosticsReport, PeerConnectionSummary,
 };
 pub use dial_policy::{
-    multiaddr_to_key, CircuitRelayLadder, DialPolicyManager, PerPeerBackoffState,
+    multiaddr_to_key, AppDialCoalescer, AppDialOutcome, AppDialPolicy, CircuitRelayLadder,
+    DialPolicyManager, PerPeerBackoffState,
 };
-pub use discovery::{DiscoveryConfig, DiscoveryMode};
+pub use discovery::{AdvertiseScope, DiscoveryConfig, DiscoveryMode};
 pub use health::{
     ConnectionState, ConnectionStats, GlobalTransportMetrics, TransportHealthMonitor,
 };
 pub use mesh_routing::{
-    BootstrapCapability, DeliveryAttempt, MultiPathDelivery, RelayReputation, RelayStats,
-    ReputationTracker, RetryStrategy, ROUTE_REASON_DIRECT_FIRST,
-    ROUTE_REASON_RELAY_RECENCY_SUCCESS, ROUTE_REASON_RELAY_SUCCESS_SCORE,
-    ROUTE_REASON_RELAY_TIEBREAK_LAST_SUCCESS, ROUTE_REASON_RELAY_TIEBREAK_PEER_ID,
+    relay_peer_from_str, BootstrapCapability, DeliveryAttempt, DeliveryPolicy, MultiPathDelivery,
+    RelayReputation, RelayStats, ReputationTracker, RetryStrategy, ROUTE_REASON_DIRECT_FIRST,
+    ROUTE_REASON_RELAY_CONTACT_PREFERENCE, ROUTE_REASON_RELAY_RECENCY_SUCCESS,
+    ROUTE_REASON_RELAY_SUCCESS_SCORE, ROUTE_REASON_RELAY_TIEBREAK_LAST_SUCCESS,
+    ROUTE_REASON_RELAY_TIEBREAK_PEER_ID,
 };
 pub use multiport::{BindAnalysis, BindResult, ConnectivityStatus, MultiPortConfig};
+pub use nat::{HolePunchStatus, NatConfig, NatStatusReport, NatType};
 pub use observation::{AddressObservation, AddressObserver, ConnectionEndpoint, ConnectionTracker};
 pub use peer_broadcast::PeerBroadcaster;
+pub use rate_limit::{InboundRateLimit, InboundRateLimiter};
 pub use reflection::{
     AddressReflectionRequest, AddressReflectionResponse, AddressReflectionService,
 };
@@ -82,7 +95,11 @@ pub use routing::{
     smart_retry::{calculate_next_attempt, BackoffStrategy, DeliveryTrigger},
     timeout_budget::{BudgetSummary, DiscoveryPhase, TimeoutBudget},
 };
+pub use rtt::RttTracker;
+pub use signed_gossip::{seal_gossip, SignedGossip};
 pub use swarm::{
-    default_routing_engine_handle, start_swarm, start_swarm_with_config, SwarmCommand,
-    SwarmEvent2 as SwarmEvent, SwarmHandle,
+    default_routing_engine_handle, start_swarm, start_swarm_with_config, ProbeResult,
+    RelayReservation, RelaySignaturePolicy, SwarmCommand, SwarmEvent2 as SwarmEvent, SwarmHandle,
+    DEFAULT_PROBE_TIMEOUT_SECS,
 };
+pub use topic_limits::TopicSizeLimits;
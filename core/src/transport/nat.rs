@@ -290,21 +290,29 @@ pub struct RelayCircuit {
 // ============================================================================
 
 /// NAT traversal configuration
-#[derive(Debug, Clone)]
+///
+/// Also passed to `start_swarm_with_config`, where `enable_hole_punch`
+/// switches DCUtR, `hole_punch_attempts` bounds the DCUtR rounds per peer,
+/// `probe_interval` paces AutoNAT and `enable_upnp` switches UPnP mapping.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct NatConfig {
     /// Peer reflectors for address discovery (mesh peers, not external servers)
     /// These are libp2p peer IDs of mesh nodes that provide address reflection
     pub peer_reflectors: Vec<String>,
     /// Timeout for relay circuit establishment (seconds)
     pub relay_timeout: u64,
-    /// Maximum hole-punch attempts
-    pub max_attempts: u32,
+    /// Maximum hole-punch attempts per peer
+    pub hole_punch_attempts: u32,
     /// Timeout per attempt (seconds)
     pub attempt_timeout: u64,
     /// Enable hole-punching
     pub enable_hole_punch: bool,
     /// Enable relay fallback
     pub enable_relay_fallback: bool,
+    /// Seconds between AutoNAT reachability probes while unconfirmed
+    pub probe_interval: u64,
+    /// Ask the home router for a port mapping over UPnP (desktop targets)
+    pub enable_upnp: bool,
 }
 
 impl Default for NatConfig {
@@ -314,10 +322,126 @@ impl Default for NatConfig {
             // Bootstrap nodes and web deploys are prime candidates
             peer_reflectors: vec![],
             relay_timeout: 30,
-            max_attempts: 5,
+            hole_punch_attempts: 5,
             attempt_timeout: 10,
             enable_hole_punch: true,
             enable_relay_fallback: true,
+            probe_interval: 300,
+            enable_upnp: true,
+        }
+    }
+}
+
+impl NatConfig {
+    /// Relays are publicly reachable: nothing to punch through and no router
+    /// to map a port on.
+    pub fn relay() -> Self {
+        Self {
+            enable_hole_punch: false,
+            enable_upnp: false,
+            ..Self::default()
+        }
+    }
+
+    /// Phones sit behind carrier NAT and move between networks, so they retry
+    /// hole punches harder and re-probe reachability sooner. Carriers do not
+    /// offer UPnP.
+    pub fn mobile() -> Self {
+        Self {
+            hole_punch_attempts: 10,
+            probe_interval: 60,
+            enable_upnp: false,
+            ..Self::default()
+        }
+    }
+}
+
+/// Snapshot of the swarm's NAT state, from `SwarmHandle::get_nat_status`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NatStatusReport {
+    pub nat_type: NatType,
+    pub hole_punch_enabled: bool,
+    pub upnp_enabled: bool,
+    /// Latest DCUtR outcome per peer, ordered by peer ID
+    pub hole_punches: Vec<(PeerId, HolePunchStatus)>,
+}
+
+/// DCUtR outcomes seen by the swarm, and the NAT type they imply.
+///
+/// AutoNAT only tells public from private. A private node that has punched
+/// through at least once is behind a cone NAT; one whose punches all fail is
+/// taken to be behind a symmetric NAT.
+#[derive(Debug, Default)]
+pub struct HolePunchTracker {
+    public: Option<bool>,
+    /// peer → (latest status, failed rounds)
+    peers: HashMap<PeerId, (HolePunchStatus, u32)>,
+}
+
+impl HolePunchTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record AutoNAT reachability; `None` while unknown.
+    pub fn set_public(&mut self, public: Option<bool>) {
+        self.public = public;
+    }
+
+    pub fn record_success(&mut self, peer: PeerId) {
+        self.peers.insert(peer, (HolePunchStatus::Success, 0));
+    }
+
+    /// Record a failed DCUtR round. Returns whether another round is allowed
+    /// under `max_rounds`; once it is not, the peer is `Abandoned`.
+    pub fn record_failure(&mut self, peer: PeerId, max_rounds: u32) -> bool {
+        let entry = self
+            .peers
+            .entry(peer)
+            .or_insert((HolePunchStatus::Failed, 0));
+        entry.1 += 1;
+        let retry = entry.1 < max_rounds;
+        entry.0 = if retry {
+            HolePunchStatus::Failed
+        } else {
+            HolePunchStatus::Abandoned
+        };
+        retry
+    }
+
+    pub fn status(&self, peer: &PeerId) -> Option<HolePunchStatus> {
+        self.peers.get(peer).map(|(status, _)| *status)
+    }
+
+    pub fn nat_type(&self) -> NatType {
+        match self.public {
+            Some(true) => NatType::Open,
+            Some(false) => {
+                let statuses = || self.peers.values().map(|(status, _)| *status);
+                if statuses().any(|s| s == HolePunchStatus::Success) {
+                    NatType::PortRestrictedCone
+                } else if statuses().any(|s| s == HolePunchStatus::Abandoned) {
+                    NatType::Symmetric
+                } else {
+                    NatType::Unknown
+                }
+            }
+            None => NatType::Unknown,
+        }
+    }
+
+    pub fn report(&self, config: &NatConfig) -> NatStatusReport {
+        let mut hole_punches: Vec<(PeerId, HolePunchStatus)> = self
+            .peers
+            .iter()
+            .map(|(peer, (status, _))| (*peer, *status))
+            .collect();
+        hole_punches.sort_by_key(|(peer, _)| *peer);
+        NatStatusReport {
+            nat_type: self.nat_type(),
+            hole_punch_enabled: config.enable_hole_punch,
+            upnp_enabled: config.enable_upnp,
+            hole_punches,
         }
     }
 }
@@ -338,9 +462,9 @@ pub struct NatTraversal {
 impl NatTraversal {
     /// Create a new NAT traversal instance
     pub fn new(config: NatConfig) -> Result<Self, NatTraversalError> {
-        if config.max_attempts == 0 {
+        if config.hole_punch_attempts == 0 {
             return Err(NatTraversalError::InvalidConfig(
-                "max_attempts must be > 0".to_string(),
+                "hole_punch_attempts must be > 0".to_string(),
             ));
         }
 
@@ -668,7 +792,7 @@ mod tests {
     #[test]
     fn test_nat_traversal_invalid_config() {
         let config = NatConfig {
-            max_attempts: 0,
+            hole_punch_attempts: 0,
             ..Default::default()
         };
         assert!(NatTraversal::new(config).is_err());
@@ -853,4 +977,48 @@ mod tests {
         assert!(config.enable_hole_punch);
         assert!(config.enable_relay_fallback);
     }
+
+    #[test]
+    fn test_relay_and_mobile_presets() {
+        let relay = NatConfig::relay();
+        assert!(!relay.enable_hole_punch);
+        assert!(!relay.enable_upnp);
+
+        let mobile = NatConfig::mobile();
+        assert!(mobile.enable_hole_punch);
+        assert!(mobile.hole_punch_attempts > NatConfig::default().hole_punch_attempts);
+        assert!(mobile.probe_interval < NatConfig::default().probe_interval);
+        assert!(NatTraversal::new(mobile).is_ok());
+    }
+
+    #[test]
+    fn test_hole_punch_tracker_reports_status_and_nat_type() {
+        let config = NatConfig {
+            hole_punch_attempts: 2,
+            ..Default::default()
+        };
+        let mut tracker = HolePunchTracker::new();
+        assert_eq!(tracker.report(&config).nat_type, NatType::Unknown);
+
+        let punched = PeerId::random();
+        let stubborn = PeerId::random();
+        tracker.set_public(Some(false));
+        assert!(tracker.record_failure(stubborn, config.hole_punch_attempts));
+        assert_eq!(tracker.status(&stubborn), Some(HolePunchStatus::Failed));
+        assert!(!tracker.record_failure(stubborn, config.hole_punch_attempts));
+        assert_eq!(tracker.status(&stubborn), Some(HolePunchStatus::Abandoned));
+        assert_eq!(tracker.nat_type(), NatType::Symmetric);
+
+        tracker.record_success(punched);
+        let report = tracker.report(&config);
+        assert_eq!(report.nat_type, NatType::PortRestrictedCone);
+        assert!(report.hole_punch_enabled);
+        assert_eq!(report.hole_punches.len(), 2);
+        assert!(report
+            .hole_punches
+            .contains(&(punched, HolePunchStatus::Success)));
+
+        tracker.set_public(Some(true));
+        assert_eq!(tracker.nat_type(), NatType::Open);
+    }
 }
@@ -0,0 +1,182 @@
+// Signed presence heartbeat
+//
+// A node that opts in (`IronCore::start_presence_heartbeat`) publishes a
+// minimal beacon on the lobby topic every interval: its identity public key
+// and a timestamp, signed with the identity key. Receivers update the
+// matching contact's `last_seen`, giving "last seen" without any message
+// content. Beacons carry nothing else, so the only thing they reveal is that
+// the identity is online; `DiscoveryMode::Paranoid` turns them off entirely.
+
+use crate::identity::IdentityKeys;
+use serde::{Deserialize, Serialize};
+
+/// Marks a lobby payload as a presence beacon.
+pub const PRESENCE_BEACON_PREFIX: &[u8] = b"scm.presence.v1:";
+
+/// Domain separator so a beacon signature cannot be replayed as a signature
+/// over any other message made with the same identity key.
+const PRESENCE_SIGNING_DOMAIN: &[u8] = b"scmessenger-presence-v1";
+
+/// Shortest heartbeat interval accepted, to keep the lobby quiet.
+pub const MIN_PRESENCE_INTERVAL_SECS: u64 = 10;
+
+/// Beacons further in the future than this are rejected.
+pub(crate) const MAX_CLOCK_SKEW_SECS: u64 = 300;
+
+/// Beacons older than this are stale (e.g. replayed) and ignored.
+const MAX_BEACON_AGE_SECS: u64 = 3600;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresenceBeacon {
+    /// Hex-encoded Ed25519 identity public key of the sender.
+    pub public_key: String,
+    /// When the beacon was made (unix timestamp).
+    pub timestamp: u64,
+    /// Ed25519 signature over the domain, public key and timestamp.
+    pub signature: Vec<u8>,
+}
+
+fn signing_bytes(public_key: &str, timestamp: u64) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(PRESENCE_SIGNING_DOMAIN.len() + public_key.len() + 8);
+    bytes.extend_from_slice(PRESENCE_SIGNING_DOMAIN);
+    bytes.extend_from_slice(public_key.as_bytes());
+    bytes.extend_from_slice(&timestamp.to_be_bytes());
+    bytes
+}
+
+impl PresenceBeacon {
+    pub fn sign(keys: &IdentityKeys, timestamp: u64) -> anyhow::Result<Self> {
+        let public_key = hex::encode(keys.signing_key.verifying_key().to_bytes());
+        let signature = keys.sign(&signing_bytes(&public_key, timestamp))?;
+        Ok(Self {
+            public_key,
+            timestamp,
+            signature,
+        })
+    }
+
+    pub fn encode(&self) -> Option<Vec<u8>> {
+        let mut payload = PRESENCE_BEACON_PREFIX.to_vec();
+        payload.extend(bincode::serialize(self).ok()?);
+        Some(payload)
+    }
+
+    /// Parse and verify a beacon. `None` for anything that is not a
+    /// correctly signed beacon, or whose timestamp is outside the accepted
+    /// window around `now`.
+    pub fn open(data: &[u8], now: u64) -> Option<Self> {
+        let body = data.strip_prefix(PRESENCE_BEACON_PREFIX)?;
+        let beacon: Self = bincode::deserialize(body).ok()?;
+        if beacon.timestamp > now.saturating_add(MAX_CLOCK_SKEW_SECS)
+            || beacon.timestamp.saturating_add(MAX_BEACON_AGE_SECS) < now
+        {
+            return None;
+        }
+        let public_key = hex::decode(&beacon.public_key).ok()?;
+        let valid = IdentityKeys::verify(
+            &signing_bytes(&beacon.public_key, beacon.timestamp),
+            &beacon.signature,
+            &public_key,
+        )
+        .unwrap_or(false);
+        valid.then_some(beacon)
+    }
+}
+
+/// Whether a verified lobby payload is a presence beacon.
+pub fn is_presence_payload(payload: &[u8]) -> bool {
+    payload.starts_with(PRESENCE_BEACON_PREFIX)
+}
+
+/// Schedule for this node's own heartbeat.
+#[derive(Debug, Default)]
+pub struct PresenceHeartbeat {
+    interval_secs: Option<u64>,
+    last_sent: Option<u64>,
+    /// Set while the mesh settings are in `DiscoveryMode::Paranoid`.
+    suppressed: bool,
+}
+
+impl PresenceHeartbeat {
+    pub fn start(&mut self, interval_secs: u64) {
+        self.interval_secs = Some(interval_secs.max(MIN_PRESENCE_INTERVAL_SECS));
+        self.last_sent = None;
+    }
+
+    pub fn stop(&mut self) {
+        self.interval_secs = None;
+        self.last_sent = None;
+    }
+
+    pub fn set_suppressed(&mut self, suppressed: bool) {
+        self.suppressed = suppressed;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.interval_secs.is_some() && !self.suppressed
+    }
+
+    /// True (and the send recorded) when a beacon should go out at `now`.
+    pub fn take_due(&mut self, now: u64) -> bool {
+        let Some(interval) = self.interval_secs else {
+            return false;
+        };
+        if self.suppressed {
+            return false;
+        }
+        let due = self
+            .last_sent
+            .map(|last| now.saturating_sub(last) >= interval)
+            .unwrap_or(true);
+        if due {
+            self.last_sent = Some(now);
+        }
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_beacon_roundtrip_and_rejections() {
+        let keys = IdentityKeys::generate();
+        let bytes = PresenceBeacon::sign(&keys, 1_000)
+            .unwrap()
+            .encode()
+            .unwrap();
+        assert!(is_presence_payload(&bytes));
+        let opened = PresenceBeacon::open(&bytes, 1_010).unwrap();
+        assert_eq!(
+            opened.public_key,
+            hex::encode(keys.signing_key.verifying_key().to_bytes())
+        );
+
+        // Stale, far-future and tampered beacons are dropped.
+        assert!(PresenceBeacon::open(&bytes, 1_000 + MAX_BEACON_AGE_SECS + 1).is_none());
+        assert!(PresenceBeacon::open(&bytes, 1_000 - MAX_CLOCK_SKEW_SECS - 1).is_none());
+        let mut forged = opened.clone();
+        forged.timestamp += 1;
+        assert!(PresenceBeacon::open(&forged.encode().unwrap(), 1_010).is_none());
+    }
+
+    #[test]
+    fn test_heartbeat_schedule() {
+        let mut heartbeat = PresenceHeartbeat::default();
+        assert!(!heartbeat.take_due(0));
+
+        heartbeat.start(60);
+        assert!(heartbeat.take_due(100));
+        assert!(!heartbeat.take_due(130));
+        assert!(heartbeat.take_due(160));
+
+        heartbeat.set_suppressed(true);
+        assert!(!heartbeat.is_running());
+        assert!(!heartbeat.take_due(1_000));
+
+        heartbeat.set_suppressed(false);
+        heartbeat.stop();
+        assert!(!heartbeat.take_due(2_000));
+    }
+}
@@ -0,0 +1,250 @@
+// Inbound message rate limiting
+//
+// Token bucket per sender, checked before an inbound message request is
+// handed to the application, so a flooding peer cannot fill the inbox. Each
+// sender's bucket holds up to `burst` tokens and refills at
+// `messages_per_sec`; a request with no token left is rejected.
+//
+// The sender is the identity that sealed the envelope when its header names
+// one, so a relay forwarding for many senders is not throttled as one peer.
+// Envelope senders are unauthenticated at this point, so everything a peer
+// forwards for others also shares one bucket `RELAYED_FAN_IN` times larger.
+
+use libp2p::PeerId;
+use std::collections::HashMap;
+use web_time::Instant;
+
+/// Default sustained inbound rate per peer.
+pub const DEFAULT_INBOUND_MESSAGES_PER_SEC: u32 = 10;
+/// Default number of messages a peer may send back-to-back.
+pub const DEFAULT_INBOUND_BURST: u32 = 50;
+
+/// Buckets idle for this long are full again and can be forgotten.
+const IDLE_BUCKET_SECS: u64 = 300;
+
+/// A peer may forward this many times one sender's limit for other senders.
+pub const RELAYED_FAN_IN: u32 = 8;
+
+/// Per-peer inbound limit. A `messages_per_sec` of 0 disables limiting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InboundRateLimit {
+    pub messages_per_sec: u32,
+    pub burst: u32,
+}
+
+impl Default for InboundRateLimit {
+    fn default() -> Self {
+        Self {
+            messages_per_sec: DEFAULT_INBOUND_MESSAGES_PER_SEC,
+            burst: DEFAULT_INBOUND_BURST,
+        }
+    }
+}
+
+impl InboundRateLimit {
+    pub fn from_mesh_settings(settings: &crate::settings::MeshSettings) -> Self {
+        Self {
+            messages_per_sec: settings.inbound_messages_per_sec,
+            burst: settings.inbound_burst,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.messages_per_sec > 0
+    }
+}
+
+/// Outcome of checking one inbound message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateCheck {
+    Allowed,
+    /// Dropped. `first` is true for the first drop since the peer was last
+    /// allowed through, so callers can report a flood once, not per message.
+    Limited {
+        first: bool,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    limited: bool,
+}
+
+impl Bucket {
+    fn take(
+        buckets: &mut HashMap<PeerId, Bucket>,
+        key: PeerId,
+        rate: f64,
+        burst: f64,
+        now: Instant,
+    ) -> RateCheck {
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: burst,
+            refilled_at: now,
+            limited: false,
+        });
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * rate).min(burst);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            bucket.limited = false;
+            RateCheck::Allowed
+        } else {
+            let first = !bucket.limited;
+            bucket.limited = true;
+            RateCheck::Limited { first }
+        }
+    }
+}
+
+/// Token buckets for inbound messages, keyed by sender.
+#[derive(Debug, Clone, Default)]
+pub struct InboundRateLimiter {
+    limit: InboundRateLimit,
+    buckets: HashMap<PeerId, Bucket>,
+    /// Per transport peer, for envelopes it forwards from other senders.
+    relayed: HashMap<PeerId, Bucket>,
+}
+
+impl InboundRateLimiter {
+    pub fn new(limit: InboundRateLimit) -> Self {
+        Self {
+            limit,
+            buckets: HashMap::new(),
+            relayed: HashMap::new(),
+        }
+    }
+
+    pub fn limit(&self) -> InboundRateLimit {
+        self.limit
+    }
+
+    /// Change the limit. Existing buckets are reset so a lowered burst takes
+    /// effect immediately.
+    pub fn set_limit(&mut self, limit: InboundRateLimit) {
+        self.limit = limit;
+        self.buckets.clear();
+        self.relayed.clear();
+    }
+
+    /// Check one message from transport peer `peer`. `sender` is the
+    /// identity (as a PeerId) named in the envelope header, if it has one.
+    pub fn check(&mut self, peer: PeerId, sender: Option<PeerId>) -> RateCheck {
+        self.check_at(peer, sender, Instant::now())
+    }
+
+    fn check_at(&mut self, peer: PeerId, sender: Option<PeerId>, now: Instant) -> RateCheck {
+        if !self.limit.is_enabled() {
+            return RateCheck::Allowed;
+        }
+        let burst = self.limit.burst as f64;
+        let rate = self.limit.messages_per_sec as f64;
+        match sender.filter(|sender| *sender != peer) {
+            None => Bucket::take(&mut self.buckets, peer, rate, burst, now),
+            Some(sender) => {
+                let fan_in = RELAYED_FAN_IN as f64;
+                match Bucket::take(&mut self.relayed, peer, rate * fan_in, burst * fan_in, now) {
+                    RateCheck::Allowed => Bucket::take(&mut self.buckets, sender, rate, burst, now),
+                    limited => limited,
+                }
+            }
+        }
+    }
+
+    /// Forget buckets that have been idle long enough to be full again.
+    pub fn prune_idle(&mut self) {
+        let now = Instant::now();
+        let active =
+            |b: &Bucket| now.saturating_duration_since(b.refilled_at).as_secs() < IDLE_BUCKET_SECS;
+        self.buckets.retain(|_, b| active(b));
+        self.relayed.retain(|_, b| active(b));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use web_time::Duration;
+
+    #[test]
+    fn test_burst_of_100_only_delivers_configured_number() {
+        let limit = InboundRateLimit {
+            messages_per_sec: 5,
+            burst: 20,
+        };
+        let mut limiter = InboundRateLimiter::new(limit);
+        let flooder = PeerId::random();
+        let quiet = PeerId::random();
+        let start = Instant::now();
+
+        let results: Vec<_> = (0..100)
+            .map(|_| limiter.check_at(flooder, None, start))
+            .collect();
+        let delivered = results.iter().filter(|r| **r == RateCheck::Allowed).count();
+        assert_eq!(delivered, 20);
+        assert_eq!(results[20], RateCheck::Limited { first: true });
+        assert_eq!(results[21], RateCheck::Limited { first: false });
+
+        // Other peers have their own bucket.
+        assert_eq!(limiter.check_at(quiet, None, start), RateCheck::Allowed);
+
+        // One second later the flooder has earned `messages_per_sec` more.
+        let later = start + Duration::from_secs(1);
+        let delivered = (0..100)
+            .filter(|_| limiter.check_at(flooder, None, later) == RateCheck::Allowed)
+            .count();
+        assert_eq!(delivered, 5);
+    }
+
+    #[test]
+    fn test_relayed_senders_get_their_own_buckets() {
+        let mut limiter = InboundRateLimiter::new(InboundRateLimit {
+            messages_per_sec: 1,
+            burst: 2,
+        });
+        let relay = PeerId::random();
+        let (alice, bob) = (PeerId::random(), PeerId::random());
+        let now = Instant::now();
+
+        // Two senders behind one relay each get the full burst.
+        for sender in [alice, bob] {
+            assert_eq!(
+                limiter.check_at(relay, Some(sender), now),
+                RateCheck::Allowed
+            );
+            assert_eq!(
+                limiter.check_at(relay, Some(sender), now),
+                RateCheck::Allowed
+            );
+            assert_eq!(
+                limiter.check_at(relay, Some(sender), now),
+                RateCheck::Limited { first: true }
+            );
+        }
+        // Alice's bucket follows her identity, whichever peer delivers.
+        assert_eq!(
+            limiter.check_at(alice, Some(alice), now),
+            RateCheck::Limited { first: false }
+        );
+
+        // Fresh sender keys cannot get past the relay's aggregate bucket.
+        let forwarded = (0..100)
+            .filter(|_| limiter.check_at(relay, Some(PeerId::random()), now) == RateCheck::Allowed)
+            .count();
+        assert_eq!(forwarded as u32, 2 * RELAYED_FAN_IN - 6);
+    }
+
+    #[test]
+    fn test_zero_rate_disables_limiting() {
+        let mut limiter = InboundRateLimiter::new(InboundRateLimit {
+            messages_per_sec: 0,
+            burst: 0,
+        });
+        let peer = PeerId::random();
+        assert!((0..1000).all(|_| limiter.check(peer, None) == RateCheck::Allowed));
+    }
+}
@@ -0,0 +1,82 @@
+// Peer RTT Tracking
+//
+// A cheap "how close is this peer" signal without GPS: keeps a rolling
+// average of libp2p Ping round-trip times per connected peer. The swarm feeds
+// the average into the routing engine's local cell so next-hop selection can
+// break ties toward lower-latency peers.
+
+use libp2p::PeerId;
+use std::collections::{HashMap, VecDeque};
+use web_time::Duration;
+
+/// Number of recent samples the rolling average is taken over.
+pub const RTT_WINDOW: usize = 8;
+
+/// Rolling-average RTT per peer.
+#[derive(Debug, Clone, Default)]
+pub struct RttTracker {
+    samples: HashMap<PeerId, VecDeque<Duration>>,
+}
+
+impl RttTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a ping sample and return the updated average for the peer.
+    pub fn record(&mut self, peer: PeerId, rtt: Duration) -> Duration {
+        let window = self.samples.entry(peer).or_default();
+        if window.len() == RTT_WINDOW {
+            window.pop_front();
+        }
+        window.push_back(rtt);
+        rolling_average(window)
+    }
+
+    /// Average over the last `RTT_WINDOW` samples, if any were recorded.
+    pub fn average(&self, peer: &PeerId) -> Option<Duration> {
+        self.samples
+            .get(peer)
+            .filter(|w| !w.is_empty())
+            .map(rolling_average)
+    }
+
+    /// Forget a peer, e.g. once its last connection closes.
+    pub fn remove(&mut self, peer: &PeerId) {
+        self.samples.remove(peer);
+    }
+}
+
+fn rolling_average(window: &VecDeque<Duration>) -> Duration {
+    window.iter().sum::<Duration>() / window.len() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolling_average_over_window() {
+        let peer = PeerId::random();
+        let mut tracker = RttTracker::new();
+        assert_eq!(tracker.average(&peer), None);
+
+        assert_eq!(
+            tracker.record(peer, Duration::from_millis(10)),
+            Duration::from_millis(10)
+        );
+        assert_eq!(
+            tracker.record(peer, Duration::from_millis(30)),
+            Duration::from_millis(20)
+        );
+
+        // Fill the window with 100ms samples: the early fast ones age out.
+        for _ in 0..RTT_WINDOW {
+            tracker.record(peer, Duration::from_millis(100));
+        }
+        assert_eq!(tracker.average(&peer), Some(Duration::from_millis(100)));
+
+        tracker.remove(&peer);
+        assert_eq!(tracker.average(&peer), None);
+    }
+}
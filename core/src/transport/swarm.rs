@@ -13,18 +13,25 @@
 // - Mandatory relay for all connections
 // - All behaviours from behaviour.rs
 
-#[cfg(not(target_arch = "wasm32"))]
-use super::behaviour::RelayRequest;
 use super::behaviour::{
     DeregistrationRequest, IronCoreBehaviour, Libp2pMessageRequest, Libp2pMessageResponse,
-    RegistrationMessage, RegistrationRequest, RegistrationResponse, RelayResponse,
+    RegistrationMessage, RegistrationRequest, RegistrationResponse, RelayRequest, RelayResponse,
+};
+use super::capability::PeerCapabilities;
+use super::dial_policy::{
+    multiaddr_to_key, AppDialCoalescer, AppDialOutcome, AppDialPolicy, CircuitRelayLadder,
+    DialPolicyManager,
 };
-use super::dial_policy::{multiaddr_to_key, CircuitRelayLadder, DialPolicyManager};
-use super::discovery::DiscoveryConfig;
+use super::discovery::{AdvertiseScope, DiscoveryConfig};
+use super::mesh_routing::DeliveryPolicy;
 #[cfg(not(target_arch = "wasm32"))]
 use super::mesh_routing::{
     advance_route_cursor, BootstrapCapability, MultiPathDelivery, RankedRoute,
 };
+use super::nat::{HolePunchTracker, NatConfig, NatStatusReport};
+use super::rate_limit::{InboundRateLimit, InboundRateLimiter, RateCheck};
+use super::signed_gossip::{seal_gossip, SignedGossip};
+use super::topic_limits::TopicSizeLimits;
 use crate::store::ledger_entry::{LedgerExchangeRequest, LedgerExchangeResponse, SharedPeerEntry};
 // Import mycorrhizal routing modules
 #[cfg(target_arch = "wasm32")]
@@ -42,6 +49,7 @@ use super::routing::{
     smart_retry::{calculate_next_attempt, BackoffStrategy},
 };
 use crate::drift::{DriftFrame, SyncSession};
+use crate::relay::{RelayCapability, RelayPeerInfo};
 use crate::store::relay_custody::{CustodyCompatMode, CustodyEnforcement, RelayCustodyStore};
 use anyhow::Result;
 use bincode;
@@ -60,22 +68,23 @@ use web_time::{Duration, Instant};
 #[cfg(not(target_arch = "wasm32"))]
 use web_time::{Duration, Instant, UNIX_EPOCH};
 
-/// Returns true if a Multiaddr is suitable for discovery (local or global).
+/// Returns true if a Multiaddr is suitable for discovery under `scope`.
 ///
-/// We exclude:
+/// We always exclude:
 /// - Loopback (127.x.x.x, ::1)
 /// - Unspecified (0.0.0.0, ::)
-/// - Relay circuit addresses (/p2p-circuit) — handled separately
-///
-/// We now ALLOW (previously blocked):
-/// - RFC1918 private ranges (10.x, 172.16-31.x, 192.168.x)
-/// - CGNAT (100.64.0.0/10)
+/// - Link-local (169.254.0.0/16, fe80::/10)
+/// - 192.0.0.x (mobile/VPN internal NAT)
 ///
-/// Allowing private IPs is essential for local WiFi mesh discovery via DHT.
-fn is_discoverable_multiaddr(addr: &Multiaddr) -> bool {
+/// `AdvertiseScope::LanAndWan` allows the rest: private ranges (10.x,
+/// 172.16-31.x, 192.168.x, CGNAT 100.64.0.0/10, IPv6 fc00::/7) for local
+/// WiFi mesh discovery via DHT, plus public addresses and relay circuits.
+/// `WanOnly` drops the private ranges; `LanOnly` keeps only them.
+fn is_discoverable_multiaddr(addr: &Multiaddr, scope: AdvertiseScope) -> bool {
     use libp2p::multiaddr::Protocol;
     let mut is_p2p_circuit = false;
     let mut ip_is_restricted = false;
+    let mut ip_is_lan = false;
     let mut has_ip = false;
 
     for proto in addr.iter() {
@@ -90,12 +99,22 @@ fn is_discoverable_multiaddr(addr: &Multiaddr) -> bool {
                 if ip.octets()[0] == 192 && ip.octets()[1] == 0 && ip.octets()[2] == 0 {
                     ip_is_restricted = true;
                 }
+                let cgnat = ip.octets()[0] == 100 && (ip.octets()[1] & 0xc0) == 64;
+                if ip.is_private() || cgnat {
+                    ip_is_lan = true;
+                }
             }
             Protocol::Ip6(ip) => {
                 has_ip = true;
-                if ip.is_loopback() || ip.is_unspecified() {
+                // fe80::/10 is link-local: only reachable on the same link,
+                // and meaningless without the interface it was seen on.
+                if ip.is_loopback() || ip.is_unspecified() || (ip.segments()[0] & 0xffc0) == 0xfe80
+                {
                     ip_is_restricted = true;
                 }
+                if (ip.segments()[0] & 0xfe00) == 0xfc00 {
+                    ip_is_lan = true;
+                }
             }
             Protocol::P2pCircuit => {
                 is_p2p_circuit = true;
@@ -107,11 +126,31 @@ fn is_discoverable_multiaddr(addr: &Multiaddr) -> bool {
     // Allow ANY P2P circuit address, even if it traverses a restricted IP (like 192.0.0.x)
     // as it's the ONLY way to reach the peer via that relay.
     if is_p2p_circuit {
-        return true;
+        return scope != AdvertiseScope::LanOnly;
     }
 
     // Otherwise, require an IP and it must not be restricted.
-    has_ip && !ip_is_restricted
+    if !has_ip || ip_is_restricted {
+        return false;
+    }
+    match scope {
+        AdvertiseScope::LanAndWan => true,
+        AdvertiseScope::WanOnly => !ip_is_lan,
+        AdvertiseScope::LanOnly => ip_is_lan,
+    }
+}
+
+/// Identify only sends our external addresses (its listen addresses are
+/// hidden, see `IronCoreBehaviour::new`), so a new listen address reaches
+/// peers only if `scope` permits it and it is added here.
+fn advertise_listen_addr(
+    swarm: &mut libp2p::Swarm<IronCoreBehaviour>,
+    addr: &Multiaddr,
+    scope: AdvertiseScope,
+) {
+    if is_discoverable_multiaddr(addr, scope) {
+        swarm.add_external_address(addr.clone());
+    }
 }
 
 /// Filter mDNS-advertised addresses to exclude circuit addresses that are too long for TXT records.
@@ -165,6 +204,20 @@ const ROUTE_ATTEMPT_REASON_INITIAL_SEND: &str = "INITIAL_SEND";
 const ROUTE_ATTEMPT_REASON_RETRY_NEXT: &str = "RETRY_NEXT_CANDIDATE";
 #[cfg(not(target_arch = "wasm32"))]
 const ROUTE_ATTEMPT_REASON_RETRY_CYCLE: &str = "RETRY_CYCLE_RESTART";
+#[cfg(not(target_arch = "wasm32"))]
+const ROUTE_ATTEMPT_REASON_RELAY_CONGESTED: &str = "RELAY_CONGESTED_REROUTE";
+/// Relay rejection sent when the relay's hourly forwarding budget is spent.
+const RELAY_BUDGET_EXHAUSTED: &str = "relay_budget_exhausted";
+
+/// Relay rejection sent when the device policy refuses to relay (e.g. low
+/// battery on cellular).
+const RELAY_DEVICE_POLICY: &str = "relay_device_policy";
+/// Rejection sent to a peer over its inbound message rate limit.
+const INBOUND_RATE_LIMITED: &str = "inbound_rate_limited";
+/// Relay rejection for an unsigned request under `require_signed_relay`.
+const RELAY_UNSIGNED: &str = "relay_unsigned";
+/// Relay rejection for a signature that does not match the envelope's sender.
+const RELAY_BAD_SIGNATURE: &str = "relay_bad_signature";
 const DELIVERY_CONVERGENCE_TOPIC: &str = "sc-receipt-convergence";
 const DELIVERY_CONVERGENCE_PREFIX: &[u8] = b"scm.delivery.convergence.v1:";
 const RELAY_MAX_INFLIGHT_DISPATCHES: usize = 256;
@@ -315,6 +368,54 @@ struct PendingDialEntry {
     candidate_addrs: Vec<Multiaddr>,
 }
 
+/// Default time a `SwarmCommand::ProbePeer` waits for Identify to complete
+/// before reporting the peer as unreachable.
+pub const DEFAULT_PROBE_TIMEOUT_SECS: u64 = 10;
+
+/// Outcome of a `SwarmCommand::ProbePeer` health check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbeResult {
+    /// The address that was probed.
+    pub addr: String,
+    /// True if the peer completed the Identify exchange before the timeout.
+    pub reachable: bool,
+    /// Milliseconds from dial to Identify completion for a reachable peer;
+    /// for an unreachable peer, the time elapsed before the probe gave up.
+    pub latency_ms: u64,
+    /// The PeerId reported by the remote (reachable peers only).
+    pub peer_id: Option<String>,
+    /// The agent version reported via Identify (reachable peers only).
+    pub agent_version: Option<String>,
+    /// Why the probe failed (dial error, early close, or timeout).
+    pub error: Option<String>,
+}
+
+impl ProbeResult {
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    fn unreachable(addr: &Multiaddr, elapsed: Duration, error: String) -> Self {
+        Self {
+            addr: addr.to_string(),
+            reachable: false,
+            latency_ms: elapsed.as_millis() as u64,
+            peer_id: None,
+            agent_version: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// Tracks an in-flight `SwarmCommand::ProbePeer`. Keyed in `pending_probes`
+/// by the `ConnectionId` of the probe dial so Identify, dial failures and
+/// early closes on that specific connection resolve it — never an unrelated
+/// connection to the same peer.
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+struct PendingProbeEntry {
+    addr: Multiaddr,
+    reply: mpsc::Sender<ProbeResult>,
+    started_at: web_time::Instant,
+    timeout: Duration,
+}
+
 struct RelayAbuseGuardrails {
     per_peer_buckets: HashMap<String, TokenBucketState>,
     recent_duplicates: HashMap<String, u64>,
@@ -445,6 +546,85 @@ impl RelayAbuseGuardrails {
     }
 }
 
+/// Minimum spacing between peer-list pulls to (and served to) the same peer,
+/// so `scm peer discover` fan-outs cannot turn into request storms.
+pub const PEER_PULL_MIN_INTERVAL_SECS: u64 = 30;
+
+/// Cap on entries returned to a single peer-list pull.
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+const PEER_PULL_MAX_ENTRIES: usize = 64;
+
+/// Group ledger entries by PeerId into `RelayPeerInfo` records, preserving
+/// first-seen order. Entries without a PeerId are skipped; ledger entries
+/// carry no capability data, so the default (relay-capable) is assumed.
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+fn shared_entries_to_relay_peers(entries: &[SharedPeerEntry]) -> Vec<RelayPeerInfo> {
+    let mut peers: Vec<RelayPeerInfo> = Vec::new();
+    for entry in entries {
+        let Some(peer_id) = entry.last_peer_id.as_ref() else {
+            continue;
+        };
+        if let Some(existing) = peers.iter_mut().find(|p| &p.peer_id == peer_id) {
+            if !existing.addresses.contains(&entry.multiaddr) {
+                existing.addresses.push(entry.multiaddr.clone());
+            }
+            existing.last_seen = existing.last_seen.max(entry.last_seen);
+        } else {
+            let mut info = RelayPeerInfo::new(
+                peer_id.clone(),
+                vec![entry.multiaddr.clone()],
+                RelayCapability::default(),
+            );
+            info.last_seen = entry.last_seen;
+            peers.push(info);
+        }
+    }
+    peers
+}
+
+/// Answer a peer-list pull from the Kademlia routing table: every
+/// discoverable address we hold (except the requester's own), without the
+/// `/p2p/` suffix as `SharedPeerEntry` expects.
+#[cfg(not(target_arch = "wasm32"))]
+fn known_peer_entries(
+    swarm: &mut libp2p::Swarm<IronCoreBehaviour>,
+    requester: &PeerId,
+    advertise_scope: AdvertiseScope,
+) -> Vec<SharedPeerEntry> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let mut entries = Vec::new();
+    for bucket in swarm.behaviour_mut().kademlia.kbuckets() {
+        for entry in bucket.iter() {
+            let peer_id = *entry.node.key.preimage();
+            if peer_id == *requester {
+                continue;
+            }
+            for addr in entry.node.value.iter() {
+                if !is_discoverable_multiaddr(addr, advertise_scope) {
+                    continue;
+                }
+                let transport_addr: Multiaddr = addr
+                    .iter()
+                    .filter(|p| !matches!(p, libp2p::multiaddr::Protocol::P2p(_)))
+                    .collect();
+                entries.push(SharedPeerEntry {
+                    multiaddr: transport_addr.to_string(),
+                    last_peer_id: Some(peer_id.to_string()),
+                    last_seen: now,
+                    known_topics: Vec::new(),
+                });
+                if entries.len() >= PEER_PULL_MAX_ENTRIES {
+                    return entries;
+                }
+            }
+        }
+    }
+    entries
+}
+
 /// Check if envelope data is a valid DriftFrame and return its type
 /// Wrap envelope data in a DriftFrame::Data for transport.
 /// This adds a 7-byte transport header (2-byte length, 1-byte type, 4-byte CRC32)
@@ -464,6 +644,54 @@ fn wrap_in_drift_frame(envelope_data: &[u8]) -> Vec<u8> {
     }
 }
 
+/// Identity (as a PeerId) named as sender in the cleartext header of an
+/// inbound `/sc/message` payload, for the inbound rate limiter. Unverified;
+/// `None` for anything that is not an envelope.
+fn inbound_envelope_sender(data: &[u8]) -> Option<PeerId> {
+    let envelope = match DriftFrame::from_bytes(data) {
+        Ok(frame) => crate::message::decode_wire_envelope(&frame.payload),
+        Err(_) => crate::message::decode_wire_envelope(data),
+    };
+    let sender_public_key = match envelope.ok()? {
+        crate::message::WireEnvelope::V1(envelope) => envelope.sender_public_key,
+        crate::message::WireEnvelope::V2(envelope) => envelope.sender_public_key,
+    };
+    let key = libp2p::identity::ed25519::PublicKey::try_from_bytes(&sender_public_key).ok()?;
+    Some(libp2p::identity::PublicKey::from(key).to_peer_id())
+}
+
+/// Open a `message` span for an outbound envelope so swarm-side logs line up
+/// with the core's send/receive spans. `route_id` is the swarm's own delivery
+/// tracking id; `message_id` is filled in when the envelope is Drift-encoded
+/// (onion-wrapped envelopes are opaque here).
+#[cfg(not(target_arch = "wasm32"))]
+fn outbound_message_span(route_id: &str, envelope_data: &[u8]) -> tracing::Span {
+    let span = tracing::info_span!(
+        "message",
+        message_id = tracing::field::Empty,
+        direction = "outbound",
+        route_id = %route_id
+    );
+    if let Some(message_id) = envelope_message_id(envelope_data) {
+        span.record("message_id", message_id);
+    }
+    span
+}
+
+/// Core message id of a Drift-encoded envelope; `None` for envelopes that
+/// are opaque here (onion-wrapped).
+fn envelope_message_id(envelope_data: &[u8]) -> Option<String> {
+    crate::drift::DriftEnvelope::from_bytes(envelope_data)
+        .ok()
+        .map(|envelope| uuid::Uuid::from_bytes(envelope.message_id).to_string())
+}
+
+/// The id apps know a pending message by: the core message id where the
+/// envelope carries one, the swarm's route id otherwise.
+fn delivery_message_id(route_id: &str, pending: &PendingMessage) -> String {
+    envelope_message_id(&pending.envelope_data).unwrap_or_else(|| route_id.to_string())
+}
+
 impl DeliveryConvergenceMarker {
     fn key(&self) -> String {
         format!("{}::{}", self.destination_peer_id, self.relay_message_id)
@@ -485,13 +713,280 @@ fn decode_delivery_convergence_marker(data: &[u8]) -> Option<DeliveryConvergence
         .ok()
 }
 
+/// Whether a verified gossip payload is an application broadcast, as opposed
+/// to cover traffic or internal frames.
+fn is_broadcast_payload(payload: &[u8]) -> bool {
+    crate::message::decode_message(payload)
+        .map(|m| m.message_type == crate::message::MessageType::Broadcast)
+        .unwrap_or(false)
+}
+
+/// Wrap `data` in a `SignedGossip` frame and publish it on `topic`.
+fn publish_signed_gossip(
+    swarm: &mut libp2p::Swarm<IronCoreBehaviour>,
+    keypair: &Keypair,
+    topic: &str,
+    data: Vec<u8>,
+) -> std::result::Result<(), String> {
+    let sealed = seal_gossip(keypair, topic, data).map_err(|e| e.to_string())?;
+    swarm
+        .behaviour_mut()
+        .gossipsub
+        .publish(libp2p::gossipsub::IdentTopic::new(topic), sealed)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Like `publish_signed_gossip`, but reports how many peers gossipsub handed
+/// the frame to. With flood publishing (the default) that is every connected
+/// peer subscribed to `topic`; a topic nobody subscribes to yields `Ok(0)`
+/// rather than an error.
+fn publish_signed_gossip_counted(
+    swarm: &mut libp2p::Swarm<IronCoreBehaviour>,
+    keypair: &Keypair,
+    topic: &str,
+    data: Vec<u8>,
+) -> std::result::Result<usize, String> {
+    let sealed = seal_gossip(keypair, topic, data).map_err(|e| e.to_string())?;
+    let topic = libp2p::gossipsub::IdentTopic::new(topic);
+    let hash = topic.hash();
+    let gossipsub = &mut swarm.behaviour_mut().gossipsub;
+    let subscribers = gossipsub
+        .all_peers()
+        .filter(|(_, topics)| topics.contains(&&hash))
+        .count();
+    match gossipsub.publish(topic, sealed) {
+        Ok(_) => Ok(subscribers),
+        Err(libp2p::gossipsub::PublishError::NoPeersSubscribedToTopic) => Ok(0),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// How relay requests carry and check the sender's envelope signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RelaySignaturePolicy {
+    /// Attach a detached `SignedEnvelope` signature to envelopes we hand to a relay.
+    pub sign_outbound: bool,
+    /// As a relay, refuse requests that carry no signature. A signature that
+    /// does not verify is refused either way.
+    pub require_signed_relay: bool,
+}
+
+impl Default for RelaySignaturePolicy {
+    fn default() -> Self {
+        Self {
+            sign_outbound: true,
+            require_signed_relay: false,
+        }
+    }
+}
+
+impl RelaySignaturePolicy {
+    /// Outbound envelopes are always signed; `require_signed_relay` follows
+    /// the setting of the same name.
+    pub fn from_mesh_settings(settings: &crate::settings::MeshSettings) -> Self {
+        Self {
+            require_signed_relay: settings.require_signed_relay,
+            ..Self::default()
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn outbound_signer<'a>(
+        &self,
+        key: Option<&'a ed25519_dalek::SigningKey>,
+    ) -> Option<&'a ed25519_dalek::SigningKey> {
+        key.filter(|_| self.sign_outbound)
+    }
+}
+
+/// A circuit reservation this node has requested on a relay, as reported by
+/// `SwarmHandle::get_relay_reservations`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayReservation {
+    pub relay_peer_id: PeerId,
+    /// Routable relay address the reservation was requested through; `None`
+    /// when the relay accepted a reservation made outside that path.
+    pub relay_addr: Option<Multiaddr>,
+    /// The relay has accepted (or renewed) the reservation. Until then it is
+    /// only requested, and inbound relayed connections are not possible yet.
+    pub accepted: bool,
+}
+
+/// Current reservations from the requested relay addresses and the relays
+/// that accepted, ordered by relay peer id.
+#[cfg(not(target_arch = "wasm32"))]
+fn relay_reservations(
+    relay_peer_addrs: &HashMap<PeerId, Vec<Multiaddr>>,
+    accepted: &HashSet<PeerId>,
+) -> Vec<RelayReservation> {
+    let mut reservations: Vec<RelayReservation> = relay_peer_addrs
+        .iter()
+        .map(|(peer_id, addrs)| RelayReservation {
+            relay_peer_id: *peer_id,
+            relay_addr: addrs.first().cloned(),
+            accepted: accepted.contains(peer_id),
+        })
+        .chain(
+            accepted
+                .iter()
+                .filter(|peer_id| !relay_peer_addrs.contains_key(peer_id))
+                .map(|peer_id| RelayReservation {
+                    relay_peer_id: *peer_id,
+                    relay_addr: None,
+                    accepted: true,
+                }),
+        )
+        .collect();
+    reservations.sort_by_key(|r| r.relay_peer_id.to_string());
+    reservations
+}
+
+/// Bootstrap addresses to re-dial now: skips our own address (logged once per
+/// address), addresses still inside their backoff window, and nodes that are
+/// already connected.
+fn bootstrap_redial_targets(
+    bootstrap_addrs: &[Multiaddr],
+    local_peer_id: PeerId,
+    connected_peers: &HashSet<PeerId>,
+    bootstrap_backoff: &HashMap<Multiaddr, BootstrapBackoffEntry>,
+    self_dial_logged: &mut HashSet<Multiaddr>,
+) -> Vec<Multiaddr> {
+    let addr_peer = |addr: &Multiaddr| {
+        addr.iter().find_map(|proto| match proto {
+            libp2p::multiaddr::Protocol::P2p(pid) => Some(pid),
+            _ => None,
+        })
+    };
+    let mut targets = Vec::new();
+    for addr in bootstrap_addrs {
+        let peer = addr_peer(addr);
+        // Self-dial guard: e.g. a portproxy loopback to this node.
+        if peer == Some(local_peer_id) {
+            if self_dial_logged.insert(addr.clone()) {
+                tracing::info!(
+                    "  ⊘ Skipping self-dial bootstrap addr (matches local peer): {}",
+                    addr
+                );
+            }
+            continue;
+        }
+        if !bootstrap_backoff.get(addr).is_none_or(|e| e.is_eligible()) {
+            continue;
+        }
+        if peer.is_some_and(|pid| connected_peers.contains(&pid)) {
+            continue;
+        }
+        targets.push(addr.clone());
+    }
+    targets
+}
+
+/// Dial a bootstrap address without its `/p2p/` suffix. A dial the swarm
+/// rejects outright (e.g. already dialing) counts as a failure for backoff,
+/// to avoid retry spam. Returns whether the dial started.
+fn dial_bootstrap_addr(
+    swarm: &mut libp2p::Swarm<IronCoreBehaviour>,
+    addr: &Multiaddr,
+    bootstrap_backoff: &mut HashMap<Multiaddr, BootstrapBackoffEntry>,
+) -> bool {
+    let stripped_addr: Multiaddr = addr
+        .iter()
+        .filter(|p| !matches!(p, libp2p::multiaddr::Protocol::P2p(_)))
+        .collect();
+    match swarm.dial(stripped_addr.clone()) {
+        Ok(_) => {
+            tracing::debug!("Re-dialing bootstrap: {}", stripped_addr);
+            true
+        }
+        Err(e) => {
+            tracing::trace!("Bootstrap re-dial {} skipped: {}", stripped_addr, e);
+            bootstrap_backoff
+                .entry(addr.clone())
+                .or_insert_with(BootstrapBackoffEntry::new)
+                .on_failure();
+            false
+        }
+    }
+}
+
+/// Re-dial one bootstrap address. DNS addresses are resolved in the
+/// background and dialed through `SwarmCommand::DialResolved`; others are
+/// dialed directly. Returns whether a dial or resolution was started.
+#[cfg(not(target_arch = "wasm32"))]
+fn start_bootstrap_redial(
+    swarm: &mut libp2p::Swarm<IronCoreBehaviour>,
+    addr: &Multiaddr,
+    in_flight_dns: &mut HashSet<Multiaddr>,
+    bootstrap_backoff: &mut HashMap<Multiaddr, BootstrapBackoffEntry>,
+    command_tx: &mpsc::Sender<SwarmCommand>,
+) -> bool {
+    let stripped_addr: Multiaddr = addr
+        .iter()
+        .filter(|p| !matches!(p, libp2p::multiaddr::Protocol::P2p(_)))
+        .collect();
+    if !is_dns_multiaddr(&stripped_addr) {
+        return dial_bootstrap_addr(swarm, addr, bootstrap_backoff);
+    }
+    if !in_flight_dns.insert(addr.clone()) {
+        return false;
+    }
+    let addr_clone = addr.clone();
+    let command_tx_clone = command_tx.clone();
+    tokio::spawn(async move {
+        let resolved = resolve_dns_multiaddr(&addr_clone).await;
+        if resolved.is_empty() {
+            let _ = command_tx_clone
+                .send(SwarmCommand::ResolutionFailed {
+                    original_dns: addr_clone,
+                })
+                .await;
+        } else {
+            let _ = command_tx_clone
+                .send(SwarmCommand::DialResolved {
+                    original_dns: addr_clone,
+                    resolved_addrs: resolved,
+                })
+                .await;
+        }
+    });
+    true
+}
+
+/// The identity's Ed25519 signing key, recovered from the libp2p keypair
+/// (they are the same key) for signing relayed envelopes.
+#[cfg(not(target_arch = "wasm32"))]
+fn relay_signing_key(keypair: &Keypair) -> Option<ed25519_dalek::SigningKey> {
+    let keypair = keypair.clone().try_into_ed25519().ok()?;
+    let secret: [u8; 32] = keypair.secret().as_ref().try_into().ok()?;
+    Some(ed25519_dalek::SigningKey::from_bytes(&secret))
+}
+
+/// Why a relay request fails the signature policy, if it does. The signature
+/// covers the envelope inside the DriftFrame. Onion layers do not decode as
+/// envelopes, so they cannot satisfy `require_signed_relay`.
+fn relay_signature_rejection(
+    request: &RelayRequest,
+    require_signed_relay: bool,
+) -> Option<&'static str> {
+    let Some(signature) = request.envelope_signature.as_deref() else {
+        return require_signed_relay.then_some(RELAY_UNSIGNED);
+    };
+    let envelope = DriftFrame::from_bytes(&request.envelope_data)
+        .map(|frame| frame.payload)
+        .unwrap_or_else(|_| request.envelope_data.clone());
+    crate::crypto::verify_envelope_signature(&envelope, signature)
+        .err()
+        .map(|_| RELAY_BAD_SIGNATURE)
+}
+
 fn publish_delivery_convergence_marker(
     swarm: &mut libp2p::Swarm<IronCoreBehaviour>,
+    keypair: &Keypair,
     marker: &DeliveryConvergenceMarker,
 ) {
-    let topic = libp2p::gossipsub::IdentTopic::new(DELIVERY_CONVERGENCE_TOPIC);
     if let Some(payload) = encode_delivery_convergence_marker(marker) {
-        if let Err(e) = swarm.behaviour_mut().gossipsub.publish(topic, payload) {
+        if let Err(e) = publish_signed_gossip(swarm, keypair, DELIVERY_CONVERGENCE_TOPIC, payload) {
             tracing::warn!(
                 "Failed to publish delivery convergence marker for message {}: {}",
                 marker.relay_message_id,
@@ -830,6 +1325,7 @@ fn dispatch_ranked_route(
     pending_relay_requests: &mut HashMap<libp2p::request_response::OutboundRequestId, String>,
     recipient_identity_id: Option<&str>,
     intended_device_id: Option<&str>,
+    envelope_signature: Option<&[u8]>,
 ) {
     // Wrap envelope in DriftFrame for transport integrity (CRC32 + length prefix)
     let framed_data = wrap_in_drift_frame(envelope_data);
@@ -850,6 +1346,7 @@ fn dispatch_ranked_route(
             message_id: message_id.to_string(),
             recipient_identity_id: recipient_identity_id.map(|s| s.to_string()),
             intended_device_id: intended_device_id.map(|s| s.to_string()),
+            envelope_signature: envelope_signature.map(<[u8]>::to_vec),
         };
         let request_id = swarm
             .behaviour_mut()
@@ -1284,6 +1781,62 @@ fn dispatch_pending_custody_for_peer(
     }
 }
 
+/// Ask a trusted relay for its signed clock reading. The answer arrives as a
+/// `RelayMessage::TimestampResponse` request from the relay.
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+fn send_clock_probe(
+    swarm: &mut libp2p::Swarm<IronCoreBehaviour>,
+    clock_probes: &mut HashMap<PeerId, crate::relay::ClockProbe>,
+    relay: PeerId,
+) {
+    let probe = crate::relay::ClockProbe::new(marker_now_ms());
+    let request = crate::relay::RelayMessage::TimestampRequest { nonce: probe.nonce };
+    let Ok(bytes) = request.to_bytes() else {
+        return;
+    };
+    swarm.behaviour_mut().messaging.send_request(
+        &relay,
+        Libp2pMessageRequest {
+            envelope_data: wrap_in_drift_frame(&bytes),
+        },
+    );
+    clock_probes.insert(relay, probe);
+}
+
+/// Deliver what the relay mailbox holds for a peer that just connected.
+/// Best effort: the mailbox has already handed the envelopes over, so a
+/// failed send is not retried.
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+fn dispatch_relay_mailbox_for_peer(
+    swarm: &mut libp2p::Swarm<IronCoreBehaviour>,
+    relay_server: &crate::relay::RelayServer,
+    peer_id: PeerId,
+) {
+    if !relay_server.mailbox_enabled() {
+        return;
+    }
+    let Ok(public_key) = crate::iron_core::public_key_hex_from_peer_id(&peer_id.to_string()) else {
+        return;
+    };
+    let envelopes = relay_server.take_cached_for(&public_key);
+    if envelopes.is_empty() {
+        return;
+    }
+    tracing::info!(
+        "Delivering {} mailbox envelope(s) to reconnected peer {}",
+        envelopes.len(),
+        peer_id
+    );
+    for envelope in envelopes {
+        swarm.behaviour_mut().messaging.send_request(
+            &peer_id,
+            Libp2pMessageRequest {
+                envelope_data: wrap_in_drift_frame(&envelope),
+            },
+        );
+    }
+}
+
 /// Pending message delivery tracking
 #[derive(Debug)]
 #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
@@ -1296,10 +1849,15 @@ struct PendingMessage {
     dispatch_attempts: u32,
     pass_count: u32,
     retry_notified: bool,
+    /// Backoff delay (from `DeliveryPolicy`) before the next retry of this message.
+    retry_delay: Duration,
     /// WS13 tight-pair metadata: SCMessenger identity ID of the recipient.
     recipient_identity_id: Option<String>,
     /// WS13 tight-pair metadata: specific device UUID being targeted.
     intended_device_id: Option<String>,
+    /// Detached signature made when the message was first queued; every
+    /// relay attempt carries this same signature.
+    envelope_signature: Option<Vec<u8>>,
 }
 
 /// Commands that can be sent to the swarm task
@@ -1315,6 +1873,15 @@ pub enum SwarmCommand {
         intended_device_id: Option<String>,
         reply: mpsc::Sender<Result<(), String>>,
     },
+    /// Hand an envelope to a connected relay for forwarding to `target_peer`
+    /// over `/sc/relay`. One attempt only: replies once the relay accepts or
+    /// rejects it, without falling back to other routes.
+    SendViaRelay {
+        relay_peer: PeerId,
+        target_peer: PeerId,
+        envelope_data: Vec<u8>,
+        reply: mpsc::Sender<Result<(), String>>,
+    },
     /// Register the sender's active device for an identity on a remote peer.
     RegisterIdentity {
         peer_id: PeerId,
@@ -1341,6 +1908,13 @@ pub enum SwarmCommand {
         addr: Multiaddr,
         reply: mpsc::Sender<Result<(), String>>,
     },
+    /// Reserve an app-initiated dial to `addr` under `policy`; replies false
+    /// when a dial to the same address started within the window.
+    AdmitAppDial {
+        addr: Multiaddr,
+        policy: AppDialPolicy,
+        reply: mpsc::Sender<bool>,
+    },
     /// Dial a discovered address (with rate-limiting)
     DiscoveryDial { peer_id: PeerId, addr: Multiaddr },
     /// Dial resolved IP addresses for a DNS multiaddr
@@ -1352,6 +1926,21 @@ pub enum SwarmCommand {
     ResolutionFailed { original_dns: Multiaddr },
     /// Get list of connected peers
     GetPeers { reply: mpsc::Sender<Vec<PeerId>> },
+    /// Get the rolling-average ping RTT for a peer
+    GetPeerRtt {
+        peer_id: PeerId,
+        reply: mpsc::Sender<Option<Duration>>,
+    },
+    /// Get the capabilities a peer advertised via Identify
+    GetPeerCapabilities {
+        peer_id: PeerId,
+        reply: mpsc::Sender<Option<PeerCapabilities>>,
+    },
+    /// Get the Ed25519 public key (hex) a connected peer proved via Identify
+    ConfirmPeerPubkey {
+        peer_id: PeerId,
+        reply: mpsc::Sender<Result<String, String>>,
+    },
     /// Get bound addresses
     GetBoundAddresses { reply: mpsc::Sender<Vec<Multiaddr>> },
     /// Start listening on an address
@@ -1371,7 +1960,8 @@ pub enum SwarmCommand {
         topic: String,
         reply: mpsc::Sender<Result<(), String>>,
     },
-    /// Publish payload to a Gossipsub topic.
+    /// Publish payload to a Gossipsub topic, signed with the swarm's identity
+    /// key as a `SignedGossip` frame.
     /// The reply channel surfaces gossipsub failures (e.g. InsufficientPeers)
     /// that were previously logged and swallowed — silent message drops.
     PublishTopic {
@@ -1379,17 +1969,50 @@ pub enum SwarmCommand {
         data: Vec<u8>,
         reply: mpsc::Sender<Result<(), String>>,
     },
+    /// `PublishTopic` that replies with the number of peers the frame was
+    /// forwarded to; 0 when no peer subscribes to the topic.
+    PublishTopicCounted {
+        topic: String,
+        data: Vec<u8>,
+        reply: mpsc::Sender<Result<usize, String>>,
+    },
     /// Get currently subscribed topics
     GetTopics { reply: mpsc::Sender<Vec<String>> },
+    /// NAT type and per-peer hole-punch outcomes
+    GetNatStatus {
+        reply: mpsc::Sender<NatStatusReport>,
+    },
     /// Share our ledger with a specific peer
     ShareLedger {
         peer_id: PeerId,
         entries: Vec<SharedPeerEntry>,
     },
+    /// Ask a connected peer for its known-peer list. Learned entries are also
+    /// surfaced as `SwarmEvent2::LedgerReceived` so the app merges them into
+    /// its ledger. Rate-limited per peer by `PEER_PULL_MIN_INTERVAL_SECS`.
+    RequestPeers {
+        peer_id: PeerId,
+        reply: mpsc::Sender<Result<Vec<RelayPeerInfo>, String>>,
+    },
     /// Get listening addresses
     GetListeners { reply: mpsc::Sender<Vec<Multiaddr>> },
+    /// Get the relay circuit reservations requested or held by this node
+    GetRelayReservations {
+        reply: mpsc::Sender<Vec<RelayReservation>>,
+    },
+    /// Re-dial every bootstrap node now instead of waiting for the
+    /// reconnect timer; replies with the number of dials started
+    ReconnectBootstrap { reply: mpsc::Sender<usize> },
     /// Update the relay message budget (messages relayed per hour)
     SetRelayBudget { budget: u32 },
+    /// Update whether relayed envelopes are signed and whether signatures are required
+    SetRelaySignaturePolicy { policy: RelaySignaturePolicy },
+    /// Update the per-peer inbound message rate limit
+    SetInboundRateLimit { limit: InboundRateLimit },
+    /// Update the per-topic gossipsub payload ceilings
+    SetTopicSizeLimits { limits: TopicSizeLimits },
+    /// Turn verified clock sync with trusted bootstrap relays on or off
+    SetClockSync { enabled: bool },
     /// Get best relay peers (sorted by reputation)
     GetBestRelays {
         count: usize,
@@ -1397,6 +2020,12 @@ pub enum SwarmCommand {
     },
     /// Get bootstrap candidates (all stable peers)
     GetBootstrapCandidates { reply: mpsc::Sender<Vec<PeerId>> },
+    /// Set the relays to try first for messages to `peer_id`
+    /// (per-contact preference; empty clears it)
+    SetPreferredRelays {
+        peer_id: PeerId,
+        relays: Vec<PeerId>,
+    },
     /// Get best paths to a target (Phase 2 multipath)
     GetBestPaths {
         target: PeerId,
@@ -1432,6 +2061,14 @@ pub enum SwarmCommand {
         interval_secs: u64,
         reply: mpsc::Sender<Result<(), String>>,
     },
+    /// Dial `addr` on a fresh connection, wait for Identify, report latency
+    /// and agent version, then close that connection. Always replies, with
+    /// `reachable == false` on dial failure or after `timeout`.
+    ProbePeer {
+        addr: Multiaddr,
+        timeout: Duration,
+        reply: mpsc::Sender<ProbeResult>,
+    },
     /// Shutdown the swarm
     Shutdown,
 }
@@ -1455,6 +2092,9 @@ pub enum SwarmEvent2 {
     },
     /// We started listening on an address
     ListeningOn(Multiaddr),
+    /// A listen address went away (interface down, port released, or its
+    /// listener closed). UIs should drop it from any displayed address list.
+    ListenAddrExpired(Multiaddr),
     /// A listener failed to bind or died after binding (async bind/accept
     /// failure, or the listener was closed by the OS). Without this event a
     /// node can silently lose all inbound connectivity while the application
@@ -1491,6 +2131,42 @@ pub enum SwarmEvent2 {
     /// is not currently emitted by the native swarm event loop. It is consumed in
     /// main.rs so a future relay-client event can be wired here.
     RelayCircuitBroken,
+    /// A pending message exhausted `DeliveryPolicy::max_attempts` and was
+    /// dropped from the retry loop. The app should move it to a dead-letter
+    /// state rather than wait for a delivery receipt
+    /// (`IronCore::mark_delivery_failed`). `message_id` is the core message
+    /// id where the envelope carries one, the swarm's route id otherwise.
+    DeliveryGivenUp { message_id: String, target: PeerId },
+    /// One send attempt for a pending message finished. `path` is "direct"
+    /// or "relay:<peer_id>"; `error` is `None` when the path accepted the
+    /// message. Apps pass it to `IronCore::record_delivery_attempt`.
+    DeliveryAttempted {
+        message_id: String,
+        path: String,
+        error: Option<String>,
+    },
+    /// A relay rejected a forward with `relay_budget_exhausted`. The message
+    /// has already been rerouted via another relay where one exists; the app
+    /// should deprioritize this relay for a while.
+    RelayCongested { relay_peer: PeerId },
+    /// This node refused to relay a message for `peer_id` because the
+    /// device policy (battery, network type, profile) disallows relaying.
+    RelayRefused {
+        peer_id: PeerId,
+        message_id: String,
+        reason: crate::drift::DropReason,
+    },
+    /// A peer exceeded the inbound message rate limit and its messages are
+    /// being rejected. Emitted once per flood, not per dropped message.
+    PeerRateLimited { peer: PeerId },
+    /// A broadcast (`MessageType::Broadcast`) arrived on an application
+    /// topic. `frame` is the raw signed gossip frame, for
+    /// `IronCore::receive_broadcast` to verify and deduplicate.
+    BroadcastReceived { topic: String, frame: Vec<u8> },
+    /// A trusted bootstrap relay answered a clock sync probe with a verified
+    /// timestamp. `offset_secs` has already been applied to the swarm's
+    /// core, if it has one; other apps pass it to `IronCore::set_clock_offset`.
+    ClockCorrected { relay: PeerId, offset_secs: i64 },
 }
 
 /// Handle to communicate with the running swarm task
@@ -1500,6 +2176,9 @@ pub struct SwarmHandle {
     // Retained for API symmetry; event loop holds its own core handle.
     #[allow(dead_code)]
     core_handle: Option<Weak<crate::IronCore>>,
+    /// Counters for traffic this node relays for others (circuits and
+    /// custody forwarding), updated by the swarm task.
+    relay_server: crate::relay::RelayServer,
 }
 
 impl SwarmHandle {
@@ -1534,6 +2213,33 @@ impl SwarmHandle {
             .map_err(|e| anyhow::anyhow!(e))
     }
 
+    /// Ask `relay_peer` to forward an envelope to `target_peer`, for
+    /// recipients we are not connected to. Succeeds once the relay accepts
+    /// the envelope (forwarded or taken into custody).
+    pub async fn send_via_relay(
+        &self,
+        relay_peer: PeerId,
+        target_peer: PeerId,
+        envelope_data: Vec<u8>,
+    ) -> Result<()> {
+        let (reply_tx, mut reply_rx) = mpsc::channel(1);
+        self.command_tx
+            .send(SwarmCommand::SendViaRelay {
+                relay_peer,
+                target_peer,
+                envelope_data,
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|_| anyhow::anyhow!("Swarm task not running"))?;
+
+        reply_rx
+            .recv()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("No reply from swarm"))?
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
     pub async fn register_identity(
         &self,
         peer_id: PeerId,
@@ -1614,6 +2320,55 @@ impl SwarmHandle {
             .map_err(|e| anyhow::anyhow!(e))
     }
 
+    /// Dial for app-initiated retries (e.g. a UI "retry" button): repeated
+    /// calls for the same address within `policy.min_interval` are coalesced
+    /// into the first and report `Suppressed` instead of dialing again.
+    pub async fn dial_with_backoff(
+        &self,
+        addr: Multiaddr,
+        policy: AppDialPolicy,
+    ) -> Result<AppDialOutcome> {
+        let (reply_tx, mut reply_rx) = mpsc::channel(1);
+        self.command_tx
+            .send(SwarmCommand::AdmitAppDial {
+                addr: addr.clone(),
+                policy,
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|_| anyhow::anyhow!("Swarm task not running"))?;
+        let admitted = reply_rx
+            .recv()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("No reply from swarm"))?;
+        if !admitted {
+            return Ok(AppDialOutcome::Suppressed);
+        }
+        self.dial(addr).await?;
+        Ok(AppDialOutcome::Initiated)
+    }
+
+    /// Probe a peer for liveness: dial `addr`, wait for Identify and report
+    /// the round-trip latency and agent version. The probe connection is
+    /// closed afterwards. Unreachable peers resolve after `timeout` rather
+    /// than hanging.
+    pub async fn probe_peer(&self, addr: Multiaddr, timeout: Duration) -> Result<ProbeResult> {
+        let (reply_tx, mut reply_rx) = mpsc::channel(1);
+        self.command_tx
+            .send(SwarmCommand::ProbePeer {
+                addr,
+                timeout,
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|_| anyhow::anyhow!("Swarm task not running"))?;
+
+        reply_rx
+            .recv()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("No reply from swarm"))
+    }
+
     /// Get connected peers
     pub async fn get_bound_addresses(&self) -> Result<Vec<Multiaddr>> {
         let (reply_tx, mut reply_rx) = mpsc::channel(1);
@@ -1641,6 +2396,66 @@ impl SwarmHandle {
             .ok_or_else(|| anyhow::anyhow!("No reply from swarm"))
     }
 
+    /// Rolling-average ping round trip to a connected peer, or `None` if no
+    /// ping has completed yet.
+    pub async fn get_peer_rtt(&self, peer_id: PeerId) -> Result<Option<Duration>> {
+        let (reply_tx, mut reply_rx) = mpsc::channel(1);
+        self.command_tx
+            .send(SwarmCommand::GetPeerRtt {
+                peer_id,
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|_| anyhow::anyhow!("Swarm task not running"))?;
+
+        reply_rx
+            .recv()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("No reply from swarm"))
+    }
+
+    /// Capabilities a peer advertised in its Identify agent version, or
+    /// `None` if the peer has not been identified. Identified peers that do
+    /// not advertise anything report `PeerCapabilities::text_only()`.
+    pub async fn get_peer_capabilities(&self, peer_id: PeerId) -> Result<Option<PeerCapabilities>> {
+        let (reply_tx, mut reply_rx) = mpsc::channel(1);
+        self.command_tx
+            .send(SwarmCommand::GetPeerCapabilities {
+                peer_id,
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|_| anyhow::anyhow!("Swarm task not running"))?;
+
+        reply_rx
+            .recv()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("No reply from swarm"))
+    }
+
+    /// Ed25519 public key (hex) of a connected peer, taken from its
+    /// Identify exchange. libp2p checks that the key Identify reports hashes
+    /// to the peer's ID, and the libp2p key is the peer's identity key, so
+    /// the result is the public key to trust for that peer. Compare it with
+    /// a claimed key before saving a contact. Fails if the peer is not
+    /// connected or has not completed Identify yet.
+    pub async fn confirm_peer_pubkey(&self, peer_id: PeerId) -> Result<String> {
+        let (reply_tx, mut reply_rx) = mpsc::channel(1);
+        self.command_tx
+            .send(SwarmCommand::ConfirmPeerPubkey {
+                peer_id,
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|_| anyhow::anyhow!("Swarm task not running"))?;
+
+        reply_rx
+            .recv()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("No reply from swarm"))?
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
     /// Start listening on an address
     pub async fn listen(&self, addr: Multiaddr) -> Result<Multiaddr> {
         let (reply_tx, mut reply_rx) = mpsc::channel(1);
@@ -1695,6 +2510,38 @@ impl SwarmHandle {
             .ok_or_else(|| anyhow::anyhow!("No reply from swarm"))
     }
 
+    /// Relay circuit reservations this node has requested, and whether each
+    /// relay has accepted. Browser nodes hold none.
+    pub async fn get_relay_reservations(&self) -> Result<Vec<RelayReservation>> {
+        let (reply_tx, mut reply_rx) = mpsc::channel(1);
+        self.command_tx
+            .send(SwarmCommand::GetRelayReservations { reply: reply_tx })
+            .await
+            .map_err(|_| anyhow::anyhow!("Swarm task not running"))?;
+
+        reply_rx
+            .recv()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("No reply from swarm"))
+    }
+
+    /// Re-dial all bootstrap nodes immediately, e.g. after a network change,
+    /// rather than waiting for the 60s reconnect timer. Nodes already
+    /// connected, or still backing off after failures, are skipped. Returns
+    /// how many dials (or DNS resolutions) were started.
+    pub async fn reconnect_bootstrap(&self) -> Result<usize> {
+        let (reply_tx, mut reply_rx) = mpsc::channel(1);
+        self.command_tx
+            .send(SwarmCommand::ReconnectBootstrap { reply: reply_tx })
+            .await
+            .map_err(|_| anyhow::anyhow!("Swarm task not running"))?;
+
+        reply_rx
+            .recv()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("No reply from swarm"))
+    }
+
     /// List known endpoint addresses for a peer.
     /// Returns the set of multiaddresses observed for the peer via address tracking.
     pub async fn list_endpoints(&self, peer_id: PeerId) -> Result<Vec<Multiaddr>> {
@@ -1829,10 +2676,17 @@ impl SwarmHandle {
             .map_err(|e| anyhow::anyhow!(e))
     }
 
-    /// Publish data to a Gossipsub topic. Awaits the publish outcome so
-    /// failures like InsufficientPeers reach the caller instead of being
-    /// dropped silently.
+    /// Publish data to a Gossipsub topic. Every topic publish is wrapped in a
+    /// `SignedGossip` frame; this is equivalent to `publish_signed_topic`.
     pub async fn publish_topic(&self, topic: String, data: Vec<u8>) -> Result<()> {
+        self.publish_signed_topic(topic, data).await
+    }
+
+    /// Sign `data` with the node's identity key and publish it to a Gossipsub
+    /// topic. Peers drop frames whose signature does not verify. Awaits the
+    /// publish outcome so failures like InsufficientPeers reach the caller
+    /// instead of being dropped silently.
+    pub async fn publish_signed_topic(&self, topic: String, data: Vec<u8>) -> Result<()> {
         let (reply_tx, mut reply_rx) = mpsc::channel(1);
         self.command_tx
             .send(SwarmCommand::PublishTopic {
@@ -1849,6 +2703,39 @@ impl SwarmHandle {
             .map_err(|e| anyhow::anyhow!(e))
     }
 
+    /// Publish signed data to a Gossipsub topic and return how many peers it
+    /// was forwarded to, so callers can tell a publish into an empty topic
+    /// (`Ok(0)`) from a delivered one.
+    pub async fn publish_topic_counted(&self, topic: String, data: Vec<u8>) -> Result<usize> {
+        let (reply_tx, mut reply_rx) = mpsc::channel(1);
+        self.command_tx
+            .send(SwarmCommand::PublishTopicCounted {
+                topic,
+                data,
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|_| anyhow::anyhow!("Swarm task not running"))?;
+        reply_rx
+            .recv()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("No reply from swarm"))?
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    /// Current NAT type and the latest hole-punch outcome per peer.
+    pub async fn get_nat_status(&self) -> Result<NatStatusReport> {
+        let (reply_tx, mut reply_rx) = mpsc::channel(1);
+        self.command_tx
+            .send(SwarmCommand::GetNatStatus { reply: reply_tx })
+            .await
+            .map_err(|_| anyhow::anyhow!("Swarm task not running"))?;
+        reply_rx
+            .recv()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("No reply from swarm"))
+    }
+
     /// Get currently subscribed topics
     pub async fn get_topics(&self) -> Result<Vec<String>> {
         let (reply_tx, mut reply_rx) = mpsc::channel(1);
@@ -1856,17 +2743,46 @@ impl SwarmHandle {
             .send(SwarmCommand::GetTopics { reply: reply_tx })
             .await
             .map_err(|_| anyhow::anyhow!("Swarm task not running"))?;
-
+
+        reply_rx
+            .recv()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("No reply from swarm"))
+    }
+
+    /// Share our ledger with a specific peer
+    pub async fn share_ledger(&self, peer_id: PeerId, entries: Vec<SharedPeerEntry>) -> Result<()> {
+        self.command_tx
+            .send(SwarmCommand::ShareLedger { peer_id, entries })
+            .await
+            .map_err(|_| anyhow::anyhow!("Swarm task not running"))
+    }
+
+    /// Pull the known-peer list from a connected peer. The learned peers are
+    /// also emitted as `SwarmEvent2::LedgerReceived` for ledger merging.
+    pub async fn request_peers(&self, peer_id: PeerId) -> Result<Vec<RelayPeerInfo>> {
+        let (reply_tx, mut reply_rx) = mpsc::channel(1);
+        self.command_tx
+            .send(SwarmCommand::RequestPeers {
+                peer_id,
+                reply: reply_tx,
+            })
+            .await
+            .map_err(|_| anyhow::anyhow!("Swarm task not running"))?;
         reply_rx
             .recv()
             .await
-            .ok_or_else(|| anyhow::anyhow!("No reply from swarm"))
+            .ok_or_else(|| anyhow::anyhow!("No reply from swarm"))?
+            .map_err(|e| anyhow::anyhow!(e))
     }
 
-    /// Share our ledger with a specific peer
-    pub async fn share_ledger(&self, peer_id: PeerId, entries: Vec<SharedPeerEntry>) -> Result<()> {
+    /// Opt in to (or out of) verified clock sync. While on, every trusted
+    /// bootstrap relay is asked for a signed timestamp when it connects, and
+    /// the resulting offset is applied with `IronCore::set_clock_offset`.
+    /// Enabling also probes the bootstrap relays already connected.
+    pub async fn set_clock_sync(&self, enabled: bool) -> Result<()> {
         self.command_tx
-            .send(SwarmCommand::ShareLedger { peer_id, entries })
+            .send(SwarmCommand::SetClockSync { enabled })
             .await
             .map_err(|_| anyhow::anyhow!("Swarm task not running"))
     }
@@ -1881,6 +2797,46 @@ impl SwarmHandle {
             .map_err(|_| anyhow::anyhow!("Swarm task not running"))
     }
 
+    /// Set whether envelopes sent via a relay are signed, and whether this
+    /// node refuses to relay unsigned ones.
+    pub async fn set_relay_signature_policy(&self, policy: RelaySignaturePolicy) -> Result<()> {
+        self.command_tx
+            .send(SwarmCommand::SetRelaySignaturePolicy { policy })
+            .await
+            .map_err(|_| anyhow::anyhow!("Swarm task not running"))
+    }
+
+    /// Push the parts of `settings` the running swarm enforces itself.
+    pub async fn apply_mesh_settings(
+        &self,
+        settings: &crate::settings::MeshSettings,
+    ) -> Result<()> {
+        self.set_inbound_rate_limit(InboundRateLimit::from_mesh_settings(settings))
+            .await?;
+        self.set_topic_size_limits(TopicSizeLimits::from_mesh_settings(settings))
+            .await?;
+        self.set_relay_signature_policy(RelaySignaturePolicy::from_mesh_settings(settings))
+            .await?;
+        self.set_clock_sync(settings.clock_sync_enabled).await
+    }
+
+    /// Set the per-peer inbound message rate limit (see `MeshSettings`).
+    pub async fn set_inbound_rate_limit(&self, limit: InboundRateLimit) -> Result<()> {
+        self.command_tx
+            .send(SwarmCommand::SetInboundRateLimit { limit })
+            .await
+            .map_err(|_| anyhow::anyhow!("Swarm task not running"))
+    }
+
+    /// Set the per-topic gossipsub payload ceilings (see `MeshSettings`).
+    /// Publishing past a ceiling fails; inbound payloads past it are dropped.
+    pub async fn set_topic_size_limits(&self, limits: TopicSizeLimits) -> Result<()> {
+        self.command_tx
+            .send(SwarmCommand::SetTopicSizeLimits { limits })
+            .await
+            .map_err(|_| anyhow::anyhow!("Swarm task not running"))
+    }
+
     /// Get best relay peers (sorted by reputation)
     pub async fn get_best_relays(&self, count: usize) -> Result<Vec<PeerId>> {
         let (reply_tx, mut reply_rx) = mpsc::channel(1);
@@ -1912,6 +2868,15 @@ impl SwarmHandle {
             .ok_or_else(|| anyhow::anyhow!("No reply from swarm"))
     }
 
+    /// Try `relays`, in order, before reputation-ranked relays when a
+    /// message to `peer_id` cannot go direct. An empty list clears it.
+    pub async fn set_preferred_relays(&self, peer_id: PeerId, relays: Vec<PeerId>) -> Result<()> {
+        self.command_tx
+            .send(SwarmCommand::SetPreferredRelays { peer_id, relays })
+            .await
+            .map_err(|_| anyhow::anyhow!("Swarm task not running"))
+    }
+
     /// Get best paths to a target (Phase 2 multipath)
     pub async fn get_best_paths(&self, target: PeerId, count: usize) -> Result<Vec<Vec<PeerId>>> {
         let (reply_tx, mut reply_rx) = mpsc::channel(1);
@@ -1930,6 +2895,13 @@ impl SwarmHandle {
             .ok_or_else(|| anyhow::anyhow!("No reply from swarm"))
     }
 
+    /// Relay statistics for this node (bytes relayed, circuits active,
+    /// unique peers served, uptime). The returned server shares state with
+    /// the swarm task, so `stats()` stays live and `reset_stats()` applies.
+    pub fn relay_server(&self) -> crate::relay::RelayServer {
+        self.relay_server.clone()
+    }
+
     /// Shut down the swarm
     pub async fn shutdown(&self) -> Result<()> {
         self.command_tx
@@ -1973,6 +2945,8 @@ pub async fn start_swarm(
         headless,
         discovery_config,
         routing_engine_handle,
+        None,
+        None,
     )
     .await
 }
@@ -1982,6 +2956,13 @@ pub async fn start_swarm(
 /// `bootstrap_addrs` — Multiaddrs of well-known relay / bootstrap nodes.
 /// The swarm will auto-dial these after binding, enabling cross-network
 /// peer discovery via Kademlia DHT and relay-circuit connectivity.
+///
+/// `delivery_policy` — retry backoff for outbound messages; `None` uses
+/// `DeliveryPolicy::default()` (unbounded retries).
+///
+/// `nat_config` — hole punching, UPnP and AutoNAT pacing; `None` uses
+/// `NatConfig::default()`. Relays pass `NatConfig::relay()`, phones
+/// `NatConfig::mobile()`.
 #[allow(
     clippy::too_many_arguments,
     clippy::blocks_in_conditions,
@@ -1998,10 +2979,36 @@ pub async fn start_swarm_with_config(
     headless: bool,
     discovery_config: Option<DiscoveryConfig>,
     routing_engine_handle: Arc<parking_lot::RwLock<Option<OptimizedRoutingEngine>>>,
+    delivery_policy: Option<DeliveryPolicy>,
+    nat_config: Option<NatConfig>,
 ) -> Result<SwarmHandle> {
+    let nat_config = nat_config.unwrap_or_default();
+    anyhow::ensure!(
+        nat_config.hole_punch_attempts > 0,
+        "NatConfig::hole_punch_attempts must be > 0"
+    );
     #[cfg(not(target_arch = "wasm32"))]
     {
         let local_peer_id = keypair.public().to_peer_id();
+        // Retained for signing gossipsub payloads and relay timestamps; the
+        // builder consumes `keypair`.
+        let gossip_keypair = keypair.clone();
+        let gossip_profile = discovery_config
+            .as_ref()
+            .map(|c| c.gossip_profile)
+            .unwrap_or_default();
+        let kad_mode = discovery_config
+            .as_ref()
+            .map(|c| c.kad_mode)
+            .unwrap_or_default();
+        let advertise_scope = discovery_config
+            .as_ref()
+            .map(|c| c.advertise_scope)
+            .unwrap_or_default();
+        let dial_learned_peers = discovery_config
+            .as_ref()
+            .map(|c| c.mesh_discovery_mode.dials_learned_peers())
+            .unwrap_or(true);
 
         // libp2p's convenience WebSocket builder reads the system DNS config.
         // iOS apps have no /etc/resolv.conf, so use the explicit resolver path
@@ -2019,8 +3026,15 @@ pub async fn start_swarm_with_config(
                 .await?
                 .with_relay_client(libp2p::noise::Config::new, libp2p::yamux::Config::default)?
                 .with_behaviour(|key, relay_client| {
-                    IronCoreBehaviour::new(key, relay_client, headless, discovery_config)
-                        .expect("Failed to create network behaviour")
+                    IronCoreBehaviour::new(
+                        key,
+                        relay_client,
+                        headless,
+                        discovery_config,
+                        gossip_profile,
+                        &nat_config,
+                    )
+                    .expect("Failed to create network behaviour")
                 })?
                 .with_swarm_config(|cfg: libp2p::swarm::Config| {
                     cfg.with_idle_connection_timeout(web_time::Duration::from_secs(600))
@@ -2062,8 +3076,15 @@ pub async fn start_swarm_with_config(
                 )?
                 .with_relay_client(libp2p::noise::Config::new, libp2p::yamux::Config::default)?
                 .with_behaviour(|key, relay_client| {
-                    IronCoreBehaviour::new(key, relay_client, headless, discovery_config)
-                        .expect("Failed to create network behaviour")
+                    IronCoreBehaviour::new(
+                        key,
+                        relay_client,
+                        headless,
+                        discovery_config,
+                        gossip_profile,
+                        &nat_config,
+                    )
+                    .expect("Failed to create network behaviour")
                 })?
                 .with_swarm_config(|cfg: libp2p::swarm::Config| {
                     cfg.with_idle_connection_timeout(web_time::Duration::from_secs(600))
@@ -2152,12 +3173,14 @@ pub async fn start_swarm_with_config(
             }
         }
 
-        // Kademlia already set to Server mode in behaviour constructor,
-        // but set it again here for belt-and-suspenders:
+        // Kademlia mode already set in the behaviour constructor, but set it
+        // again here for belt-and-suspenders:
         swarm
             .behaviour_mut()
             .kademlia
-            .set_mode(Some(kad::Mode::Server));
+            .set_mode(Some(super::behaviour::kad_mode_to_libp2p(kad_mode)));
+        tracing::info!("Kademlia DHT mode: {:?}", kad_mode);
+        tracing::info!("Address advertise scope: {:?}", advertise_scope);
 
         // Subscribe to default topics immediately (lobby + mesh)
         // The lobby topic is the wildcard discovery channel
@@ -2192,9 +3215,11 @@ pub async fn start_swarm_with_config(
         }
 
         let (command_tx, mut command_rx) = mpsc::channel::<SwarmCommand>(256);
+        let relay_server = crate::relay::RelayServer::new();
         let handle = SwarmHandle {
             command_tx: command_tx.clone(),
             core_handle: core_handle.clone(),
+            relay_server: relay_server.clone(),
         };
 
         // Address reflection service
@@ -2213,6 +3238,12 @@ pub async fn start_swarm_with_config(
 
         // Track connections and address observations (Phase 1 & 2)
         let mut connection_tracker = ConnectionTracker::new();
+        let mut rtt_tracker = super::rtt::RttTracker::new();
+        let mut peer_capabilities: HashMap<PeerId, PeerCapabilities> = HashMap::new();
+        // Ed25519 public key (hex) each connected peer proved via Identify.
+        let mut peer_public_keys: HashMap<PeerId, String> = HashMap::new();
+        let mut inbound_limiter = InboundRateLimiter::new(InboundRateLimit::default());
+        let mut topic_limits = TopicSizeLimits::default();
         let mut address_observer = AddressObserver::new();
 
         // Track successful relay reservations by ListenerId
@@ -2251,6 +3282,7 @@ pub async fn start_swarm_with_config(
 
         // Track pending message deliveries
         let mut pending_messages: HashMap<String, PendingMessage> = HashMap::new();
+        let delivery_policy = delivery_policy.unwrap_or_default();
 
         // SyncSession management for Drift Protocol mesh synchronization
         let mut sync_sessions: HashMap<PeerId, SyncSession> = HashMap::new();
@@ -2264,6 +3296,11 @@ pub async fn start_swarm_with_config(
             libp2p::request_response::OutboundRequestId,
             String,
         > = HashMap::new();
+        // One-shot `SendViaRelay` requests, answered on the relay's response.
+        let mut pending_relay_sends: HashMap<
+            libp2p::request_response::OutboundRequestId,
+            mpsc::Sender<Result<(), String>>,
+        > = HashMap::new();
         let relay_custody_store = RelayCustodyStore::for_service_storage(
             storage_path.as_deref(),
             &local_peer_id.to_string(),
@@ -2282,6 +3319,15 @@ pub async fn start_swarm_with_config(
         // Track peers we've already exchanged ledgers with (avoid spamming)
         let mut ledger_exchanged_peers: HashSet<PeerId> = HashSet::new();
 
+        // Pull-based peer exchange: outstanding RequestPeers replies, plus the
+        // last time we pulled from / served a pull to each peer (rate limit).
+        let mut pending_peer_pulls: HashMap<
+            libp2p::request_response::OutboundRequestId,
+            mpsc::Sender<Result<Vec<RelayPeerInfo>, String>>,
+        > = HashMap::new();
+        let mut last_peer_pull: HashMap<PeerId, Instant> = HashMap::new();
+        let mut last_peer_pull_served: HashMap<PeerId, Instant> = HashMap::new();
+
         // Track connected peers for relay peer discovery broadcasting
         let mut peer_broadcaster = crate::transport::PeerBroadcaster::new();
 
@@ -2290,6 +3336,14 @@ pub async fn start_swarm_with_config(
         // swarm.listen_on(<relay_addr>/p2p-circuit) to register a reservation,
         // which lets the relay dial us back on behalf of other nodes.
         let mut relay_peer_addrs: HashMap<PeerId, Vec<Multiaddr>> = HashMap::new();
+        // Relays that have accepted (or renewed) our circuit reservation.
+        let mut accepted_relay_reservations: HashSet<PeerId> = HashSet::new();
+        // DCUtR outcomes per peer and the NAT type they imply.
+        let mut hole_punches = HolePunchTracker::new();
+        // Verified clock sync (opt-in): timestamp requests awaiting an answer
+        // from a bootstrap relay.
+        let mut clock_sync_enabled = false;
+        let mut clock_probes: HashMap<PeerId, crate::relay::ClockProbe> = HashMap::new();
 
         // Track relay reconnect backoff state: (peer_id, attempt_count, next_dial_at)
         let _relay_backoff: HashMap<PeerId, (u32, web_time::Instant)> = HashMap::new();
@@ -2346,8 +3400,13 @@ pub async fn start_swarm_with_config(
 
         // Spawn the swarm event loop
         tokio::spawn(async move {
-            // PHASE 6: Retry interval for failed deliveries
-            let mut retry_interval = tokio::time::interval(Duration::from_millis(500));
+            // PHASE 6: Retry interval for failed deliveries. Ticks at least as
+            // often as the policy's base delay so short backoffs are honored.
+            let mut retry_interval = tokio::time::interval(
+                delivery_policy
+                    .base_delay
+                    .clamp(Duration::from_millis(50), Duration::from_millis(500)),
+            );
 
             // Bootstrap reconnection timer — re-dial bootstrap nodes every 60s
             // to handle network changes and maintain connectivity.
@@ -2391,8 +3450,14 @@ pub async fn start_swarm_with_config(
             let mut pending_dials: HashMap<Multiaddr, PendingDialEntry> = HashMap::new();
             let mut pending_dial_sweep_interval = tokio::time::interval(Duration::from_secs(5));
 
+            // Health-check probes awaiting Identify on their own connection.
+            let mut pending_probes: HashMap<libp2p::swarm::ConnectionId, PendingProbeEntry> =
+                HashMap::new();
+            let mut probe_sweep_interval = tokio::time::interval(Duration::from_millis(250));
+
             // P1 Item 3: Per-peer backoff state machine (max 3 concurrent dials)
             let dial_policy_manager = DialPolicyManager::new();
+            let mut app_dial_coalescer = AppDialCoalescer::default();
             let mut backoff_prune_interval = tokio::time::interval(Duration::from_secs(300)); // Prune stale entries every 5 minutes
 
             // P1 Item 4: Circuit-relay preference after connection established
@@ -2400,9 +3465,26 @@ pub async fn start_swarm_with_config(
 
             // Cover traffic — 1 dummy message/min to mask real traffic patterns
             let mut cover_traffic_interval = tokio::time::interval(Duration::from_secs(60));
+            let default_cover_generator = crate::privacy::cover::CoverTrafficGenerator::new(
+                crate::privacy::cover::CoverConfig {
+                    rate_per_minute: 1,
+                    message_size: 256,
+                    enabled: true,
+                    size_distribution: crate::privacy::cover::SizeDistribution::chat(),
+                    ..Default::default()
+                },
+            )
+            .expect("default cover traffic config is valid");
+            // Cover is held back for a cooldown after each real send
+            let mut last_real_send: Option<Instant> = None;
+
+            // Presence heartbeat — core decides whether a beacon is due
+            let mut presence_interval = tokio::time::interval(Duration::from_secs(5));
 
             // Relay budget rate-limiting
             let mut relay_budget: u32 = 200;
+            let mut relay_signature_policy = RelaySignaturePolicy::default();
+            let relay_signer = relay_signing_key(&gossip_keypair);
             let mut relay_count_this_hour: u32 = 0;
             let mut relay_hour_start = web_time::Instant::now();
             let mut relay_guardrails = RelayAbuseGuardrails::new();
@@ -2426,12 +3508,13 @@ pub async fn start_swarm_with_config(
                     _ = retry_interval.tick() => {
                         // Check for messages that need retry
                         let mut to_retry = Vec::new();
+                        let mut given_up = Vec::new();
 
                         for (msg_id, pending) in pending_messages.iter() {
                             if let Some(attempt) = multi_path_delivery.delivery_attempt(msg_id) {
                                 if attempt.should_retry() {
                                     let elapsed = pending.attempt_start.elapsed().unwrap_or_default();
-                                    let retry_delay = attempt.next_retry_delay();
+                                    let retry_delay = pending.retry_delay;
                                     // Mycorrhizal routing: use smart retry backoff
                                     let smart_delay = calculate_next_attempt(
                                         pending.pass_count,
@@ -2446,12 +3529,40 @@ pub async fn start_swarm_with_config(
                                     );
 
                                     if elapsed >= retry_delay.max(smart_delay_dur) {
-                                        to_retry.push(msg_id.clone());
+                                        if delivery_policy.is_exhausted(pending.dispatch_attempts) {
+                                            given_up.push(msg_id.clone());
+                                        } else {
+                                            to_retry.push(msg_id.clone());
+                                        }
                                     }
                                 }
                             }
                         }
 
+                        for msg_id in given_up {
+                            if let Some(pending) = pending_messages.remove(&msg_id) {
+                                multi_path_delivery.converge_delivery(&msg_id);
+                                tracing::warn!(
+                                    message_id = %msg_id,
+                                    target = %pending.target_peer,
+                                    attempts = pending.dispatch_attempts,
+                                    "Delivery given up after exhausting retry policy"
+                                );
+                                if !pending.retry_notified {
+                                    let _ = pending
+                                        .reply_tx
+                                        .send(Err("Delivery given up".to_string()))
+                                        .await;
+                                }
+                                let _ = event_tx
+                                    .send(SwarmEvent2::DeliveryGivenUp {
+                                        message_id: delivery_message_id(&msg_id, &pending),
+                                        target: pending.target_peer,
+                                    })
+                                    .await;
+                            }
+                        }
+
                         // Process retries
                         for msg_id in to_retry {
                             if let Some(mut pending) = pending_messages.remove(&msg_id) {
@@ -2485,6 +3596,8 @@ pub async fn start_swarm_with_config(
                                 let route = &routes[pending.current_path_index];
                                 pending.attempt_start = SystemTime::now();
                                 pending.dispatch_attempts = pending.dispatch_attempts.saturating_add(1);
+                                pending.retry_delay = delivery_policy
+                                    .next_retry_delay(pending.dispatch_attempts.saturating_sub(1));
                                 let attempt_reason = if cursor.wrapped_pass {
                                     ROUTE_ATTEMPT_REASON_RETRY_CYCLE
                                 } else {
@@ -2509,6 +3622,7 @@ pub async fn start_swarm_with_config(
                                     &mut pending_relay_requests,
                                     pending.recipient_identity_id.as_deref(),
                                     pending.intended_device_id.as_deref(),
+                                    pending.envelope_signature.as_deref(),
                                 );
 
                                 pending_messages.insert(msg_id, pending);
@@ -2554,10 +3668,29 @@ pub async fn start_swarm_with_config(
                         }
                     }
 
+                    _ = probe_sweep_interval.tick() => {
+                        let expired: Vec<libp2p::swarm::ConnectionId> = pending_probes
+                            .iter()
+                            .filter(|(_, probe)| probe.started_at.elapsed() >= probe.timeout)
+                            .map(|(connection_id, _)| *connection_id)
+                            .collect();
+                        for connection_id in expired {
+                            if let Some(probe) = pending_probes.remove(&connection_id) {
+                                let elapsed = probe.started_at.elapsed();
+                                tracing::debug!("Probe of {} timed out after {}ms", probe.addr, elapsed.as_millis());
+                                let error = format!("Timed out after {}ms waiting for Identify", elapsed.as_millis());
+                                let _ = probe.reply.send(ProbeResult::unreachable(&probe.addr, elapsed, error)).await;
+                                swarm.close_connection(connection_id);
+                            }
+                        }
+                    }
+
                     _ = backoff_prune_interval.tick() => {
                         // P1 Item 3: Periodically prune old backoff entries to prevent memory leak
                         dial_policy_manager.prune_old_entries(Duration::from_secs(3600)); // Prune entries older than 1 hour
+                        app_dial_coalescer.prune(Duration::from_secs(3600));
                         tracing::debug!("[DIAL-POLICY] Pruned stale backoff entries");
+                        inbound_limiter.prune_idle();
                     }
 
                     // Mycorrhizal routing: periodic optimization tick
@@ -2648,96 +3781,61 @@ pub async fn start_swarm_with_config(
                             bootstrap_addrs_clone.contains(original_dns)
                         });
                         tracing::info!("Relay custody audit log count: {}", relay_custody_store.audit_count());
-                        if !bootstrap_addrs_clone.is_empty() {
-                            let connected_peers: HashSet<PeerId> = swarm.connected_peers().cloned().collect();
-                            for addr in &bootstrap_addrs_clone {
-                                // Self-dial guard: skip bootstrap addrs that resolve to
-                                // our own peer ID (e.g. portproxy loopback) to avoid
-                                // the "tried to dial local peer id" warning every 60s.
-                                let is_self = addr.iter().any(|proto| {
-                                    if let libp2p::multiaddr::Protocol::P2p(pid) = proto {
-                                        pid == local_peer_id
-                                    } else {
-                                        false
-                                    }
-                                });
-                                if is_self {
-                                    if !self_dial_logged.contains(addr) {
-                                        tracing::info!(
-                                            "  ⊘ Skipping self-dial bootstrap addr (matches local peer): {}",
-                                            addr
-                                        );
-                                        self_dial_logged.insert(addr.clone());
-                                    }
-                                    continue;
-                                }
-
-                                // Exponential backoff gate: skip this addr if it's still
-                                // within its backoff window after a recent failure.
-                                if !bootstrap_backoff.get(addr).is_none_or(|e| e.is_eligible()) {
-                                    continue;
-                                }
-
-                                // Extract peer ID from multiaddr if present to avoid
-                                // re-dialing already-connected bootstrap nodes
-                                let already_connected = addr.iter().any(|proto| {
-                                    if let libp2p::multiaddr::Protocol::P2p(pid) = proto {
-                                        connected_peers.contains(&pid)
-                                    } else {
-                                        false
-                                    }
-                                });
-
-                                if !already_connected {
-                                    let stripped_addr: Multiaddr = addr.iter().filter(|p| !matches!(p, libp2p::multiaddr::Protocol::P2p(_))).collect();
-                                    if is_dns_multiaddr(&stripped_addr) {
-                                        if !in_flight_dns.contains(addr) {
-                                            in_flight_dns.insert(addr.clone());
-                                            let addr_clone = addr.clone();
-                                            let command_tx_clone = command_tx.clone();
-                                            tokio::spawn(async move {
-                                                let resolved = resolve_dns_multiaddr(&addr_clone).await;
-                                                if resolved.is_empty() {
-                                                    let _ = command_tx_clone.send(SwarmCommand::ResolutionFailed {
-                                                        original_dns: addr_clone,
-                                                    }).await;
-                                                } else {
-                                                    let _ = command_tx_clone.send(SwarmCommand::DialResolved {
-                                                        original_dns: addr_clone,
-                                                        resolved_addrs: resolved,
-                                                    }).await;
-                                                }
-                                            });
-                                        }
-                                    } else {
-                                        match swarm.dial(stripped_addr.clone()) {
-                                            Ok(_) => tracing::debug!("Re-dialing bootstrap: {}", stripped_addr),
-                                            Err(e) => {
-                                                // Dial was rejected internally (e.g. already dialing).
-                                                // Treat as a failure and apply backoff to avoid retry spam.
-                                                tracing::trace!("Bootstrap re-dial {} skipped: {}", stripped_addr, e);
-                                                bootstrap_backoff.entry(addr.clone()).or_insert_with(BootstrapBackoffEntry::new).on_failure();
-                                            }
-                                        }
-                                    }
-                                }
-                            }
+                        let connected_peers: HashSet<PeerId> = swarm.connected_peers().cloned().collect();
+                        for addr in bootstrap_redial_targets(
+                            &bootstrap_addrs_clone,
+                            local_peer_id,
+                            &connected_peers,
+                            &bootstrap_backoff,
+                            &mut self_dial_logged,
+                        ) {
+                            start_bootstrap_redial(
+                                &mut swarm,
+                                &addr,
+                                &mut in_flight_dns,
+                                &mut bootstrap_backoff,
+                                &command_tx,
+                            );
                         }
                     }
 
                     // Cover traffic — publish a dummy gossipsub message to mask real traffic
                     _ = cover_traffic_interval.tick() => {
-                        use crate::privacy::cover::{CoverConfig, CoverTrafficGenerator};
-                        if let Ok(gen) = CoverTrafficGenerator::new(CoverConfig {
-                            rate_per_minute: 1,
-                            message_size: 256,
-                            enabled: true,
-                        }) {
-                            if let Ok(cover_msg) = gen.generate_cover_message() {
-                                if let Ok(bytes) = bincode::serialize(&cover_msg) {
-                                    let topic = libp2p::gossipsub::IdentTopic::new("sc-mesh");
-                                    let _ = swarm.behaviour_mut().gossipsub.publish(topic, bytes);
-                                }
+                        let core = core_handle.as_ref().and_then(|w| w.upgrade());
+                        // Real traffic already masks the pattern for a while.
+                        let cooldown = core
+                            .as_ref()
+                            .and_then(|core| core.cover_real_activity_cooldown_ms())
+                            .unwrap_or(crate::privacy::cover::DEFAULT_REAL_ACTIVITY_COOLDOWN_MS);
+                        let in_cooldown = last_real_send
+                            .is_some_and(|at| at.elapsed() < Duration::from_millis(cooldown));
+                        // The core's configured generator (which also learns real
+                        // message sizes) wins; otherwise sizes follow typical chat traffic.
+                        let cover_msg = if in_cooldown {
+                            None
+                        } else {
+                            match core.and_then(|core| core.generate_configured_cover_message()) {
+                                Some(result) => result.ok(),
+                                None => default_cover_generator.generate_cover_message().ok(),
+                            }
+                        };
+                        if let Some(cover_msg) = cover_msg {
+                            if let Ok(bytes) = bincode::serialize(&cover_msg) {
+                                // Signed like real frames so cover is indistinguishable.
+                                let _ = publish_signed_gossip(&mut swarm, &gossip_keypair, "sc-mesh", bytes);
+                            }
+                        }
+                    }
+
+                    // Presence heartbeat — signed beacon on the lobby topic when due
+                    _ = presence_interval.tick() => {
+                        if let Some(beacon) = core_handle
+                            .as_ref()
+                            .and_then(|w| w.upgrade())
+                            .and_then(|core| core.presence_beacon_if_due())
+                        {
+                            if let Err(e) = publish_signed_gossip(&mut swarm, &gossip_keypair, crate::TOPIC_LOBBY, beacon) {
+                                tracing::debug!("Presence beacon not published: {}", e);
                             }
                         }
                     }
@@ -2770,6 +3868,20 @@ pub async fn start_swarm_with_config(
                                             continue;
                                         }
 
+                                        // Flood protection, ahead of any other handling; only the
+                                        // envelope header is read, to find its sender.
+                                        if let RateCheck::Limited { first } = inbound_limiter.check(peer, inbound_envelope_sender(&request.envelope_data)) {
+                                            if first {
+                                                tracing::warn!("Peer {} exceeded the inbound rate limit; rejecting its messages", peer);
+                                                let _ = event_tx.send(SwarmEvent2::PeerRateLimited { peer }).await;
+                                            }
+                                            let _ = swarm.behaviour_mut().messaging.send_response(
+                                                channel,
+                                                Libp2pMessageResponse { accepted: false, error: Some(INBOUND_RATE_LIMITED.to_string()) },
+                                            );
+                                            continue;
+                                        }
+
                                         // Unwrap DriftFrame FIRST: relay peer-discovery messages
                                         // (PeerJoined/PeerListResponse/PeerLeft) arrive DriftFrame-wrapped
                                         // like all /sc/message traffic. Probing RelayMessage on the raw
@@ -2817,13 +3929,21 @@ pub async fn start_swarm_with_config(
                                                         );
                                                         continue;
                                                     }
+                                                    if !dial_learned_peers {
+                                                        tracing::debug!("Not dialing peers announced by {} (paranoid discovery)", peer);
+                                                        let _ = swarm.behaviour_mut().messaging.send_response(
+                                                            channel,
+                                                            Libp2pMessageResponse { accepted: true, error: None },
+                                                        );
+                                                        continue;
+                                                    }
                                                     tracing::info!("Received PeerJoined: {} with {} addresses", peer_info.peer_id, peer_info.addresses.len());
                                                     let mut dialed = 0usize;
                                                     let connected_peers: HashSet<PeerId> = swarm.connected_peers().cloned().collect();
                                                     let mut already_dialed = HashSet::new();
                                                     for addr_str in peer_info.addresses.iter().take(MAX_DISCOVERY_DIALS) {
                                                         if let Ok(addr) = addr_str.parse::<Multiaddr>() {
-                                                            if is_discoverable_multiaddr(&addr) {
+                                                            if is_discoverable_multiaddr(&addr, advertise_scope) {
                                                                 let mut target_peer = None;
                                                                 for p in addr.iter() {
                                                                     if let libp2p::multiaddr::Protocol::P2p(pid) = p {
@@ -2863,6 +3983,14 @@ pub async fn start_swarm_with_config(
                                                         );
                                                         continue;
                                                     }
+                                                    if !dial_learned_peers {
+                                                        tracing::debug!("Not dialing peers announced by {} (paranoid discovery)", peer);
+                                                        let _ = swarm.behaviour_mut().messaging.send_response(
+                                                            channel,
+                                                            Libp2pMessageResponse { accepted: true, error: None },
+                                                        );
+                                                        continue;
+                                                    }
                                                     tracing::info!("Received peer list: {} peers", peers.len());
                                                     // Cap TOTAL dials across all peers, not per-peer, so a
                                                     // large peer list cannot amplify into thousands of dials.
@@ -2877,7 +4005,7 @@ pub async fn start_swarm_with_config(
                                                                 break 'outer;
                                                             }
                                                             if let Ok(addr) = addr_str.parse::<Multiaddr>() {
-                                                                if is_discoverable_multiaddr(&addr) {
+                                                                if is_discoverable_multiaddr(&addr, advertise_scope) {
                                                                     let mut target_peer = None;
                                                                     for p in addr.iter() {
                                                                         if let libp2p::multiaddr::Protocol::P2p(pid) = p {
@@ -2913,6 +4041,51 @@ pub async fn start_swarm_with_config(
                                                     );
                                                     continue;
                                                 }
+                                                crate::relay::protocol::RelayMessage::TimestampRequest { nonce } => {
+                                                    let _ = swarm.behaviour_mut().messaging.send_response(
+                                                        channel,
+                                                        Libp2pMessageResponse { accepted: true, error: None },
+                                                    );
+                                                    match crate::relay::SignedTimestamp::sign(&gossip_keypair, nonce, marker_now_ms()) {
+                                                        Ok(stamp) => {
+                                                            let reply = crate::relay::protocol::RelayMessage::TimestampResponse { stamp };
+                                                            if let Ok(bytes) = reply.to_bytes() {
+                                                                swarm.behaviour_mut().messaging.send_request(
+                                                                    &peer,
+                                                                    Libp2pMessageRequest { envelope_data: wrap_in_drift_frame(&bytes) },
+                                                                );
+                                                            }
+                                                        }
+                                                        Err(e) => tracing::warn!("Failed to sign timestamp for {}: {}", peer, e),
+                                                    }
+                                                    continue;
+                                                }
+                                                crate::relay::protocol::RelayMessage::TimestampResponse { stamp } => {
+                                                    let _ = swarm.behaviour_mut().messaging.send_response(
+                                                        channel,
+                                                        Libp2pMessageResponse { accepted: true, error: None },
+                                                    );
+                                                    // Only answers to our own probes count; those only go
+                                                    // to trusted bootstrap relays.
+                                                    let Some(probe) = clock_probes.remove(&peer) else {
+                                                        tracing::debug!("Discarding unsolicited timestamp from {}", peer);
+                                                        continue;
+                                                    };
+                                                    match probe.offset_secs(&peer, &stamp, marker_now_ms()) {
+                                                        Some(offset_secs) => {
+                                                            tracing::info!("Clock offset from relay {}: {}s", peer, offset_secs);
+                                                            if let Some(core) = core_handle.as_ref().and_then(|w| w.upgrade()) {
+                                                                core.set_clock_offset(offset_secs);
+                                                            }
+                                                            let _ = event_tx.send(SwarmEvent2::ClockCorrected { relay: peer, offset_secs }).await;
+                                                        }
+                                                        None => tracing::warn!(
+                                                            "Rejected timestamp from relay {}: bad signature, nonce or round trip",
+                                                            peer
+                                                        ),
+                                                    }
+                                                    continue;
+                                                }
                                                 _ => {
                                                     // Other relay messages, fall through to normal handling
                                                 }
@@ -2985,6 +4158,7 @@ pub async fn start_swarm_with_config(
                                                     .await;
                                                     publish_delivery_convergence_marker(
                                                         &mut swarm,
+                                                        &gossip_keypair,
                                                         &marker,
                                                     );
                                                 }
@@ -3011,6 +4185,13 @@ pub async fn start_swarm_with_config(
                                         {
                                             // Response to our outbound message request
                                             if let Some(pending) = pending_messages.remove(&message_id) {
+                                                let _ = event_tx.send(SwarmEvent2::DeliveryAttempted {
+                                                    message_id: delivery_message_id(&message_id, &pending),
+                                                    path: "direct".to_string(),
+                                                    error: (!response.accepted).then(|| {
+                                                        response.error.clone().unwrap_or_else(|| "rejected".to_string())
+                                                    }),
+                                                }).await;
                                                 if response.accepted {
                                                     // PHASE 5: Track successful delivery
                                                     let latency_ms = pending.attempt_start.elapsed().unwrap_or_default().as_millis() as u64;
@@ -3062,6 +4243,11 @@ pub async fn start_swarm_with_config(
                                             pending.target_peer,
                                             error
                                         );
+                                        let _ = event_tx.send(SwarmEvent2::DeliveryAttempted {
+                                            message_id: delivery_message_id(&message_id, &pending),
+                                            path: "direct".to_string(),
+                                            error: Some(error.to_string()),
+                                        }).await;
                                         multi_path_delivery
                                             .record_failure(&message_id, vec![pending.target_peer]);
                                         // Mycorrhizal routing: record unreachable in negative cache and downgrade reliability
@@ -3186,8 +4372,9 @@ pub async fn start_swarm_with_config(
                             }
 
                             // PHASE 3: Relay Protocol Handler — MANDATORY RELAY
-                            // All nodes MUST relay. We never refuse a relay request
-                            // (except for invalid destination).
+                            // All nodes MUST relay. Requests are refused only by the
+                            // guardrails, the hourly budget, or the device policy
+                            // (e.g. low battery on cellular).
                             SwarmEvent::Behaviour(super::behaviour::IronCoreBehaviourEvent::Relay(
                                 request_response::Event::Message { peer, message, .. }
                             )) => {
@@ -3235,6 +4422,27 @@ pub async fn start_swarm_with_config(
                                                 error: Some(reason.to_string()),
                                                 message_id: request.message_id.clone(),
                                             }
+                                        } else if let Some(reason) = relay_signature_rejection(
+                                            &request,
+                                            relay_signature_policy.require_signed_relay,
+                                        ) {
+                                            tracing::warn!(
+                                                "Relay request from {} (message {}) failed signature check: {}",
+                                                peer,
+                                                request.message_id,
+                                                reason
+                                            );
+                                            if reason == RELAY_BAD_SIGNATURE {
+                                                let _ = event_tx.send(SwarmEvent2::AbuseSignalDetected {
+                                                    peer_id: peer,
+                                                    signal: "InvalidFormat".to_string(),
+                                                }).await;
+                                            }
+                                            RelayResponse {
+                                                accepted: false,
+                                                error: Some(reason.to_string()),
+                                                message_id: request.message_id.clone(),
+                                            }
                                         } else if relay_budget > 0 && relay_count_this_hour >= relay_budget {
                                             tracing::warn!(
                                                 "Relay budget ({}/hr) exhausted — dropping relay request {}",
@@ -3243,7 +4451,28 @@ pub async fn start_swarm_with_config(
                                             );
                                             RelayResponse {
                                                 accepted: false,
-                                                error: Some("relay_budget_exhausted".to_string()),
+                                                error: Some(RELAY_BUDGET_EXHAUSTED.to_string()),
+                                                message_id: request.message_id.clone(),
+                                            }
+                                        } else if let Some(reason) = core_handle
+                                            .as_ref()
+                                            .and_then(|w| w.upgrade())
+                                            .and_then(|core| core.relay_policy_refusal())
+                                        {
+                                            tracing::info!(
+                                                "Device policy refused relay request {} from {}: {:?}",
+                                                request.message_id,
+                                                peer,
+                                                reason
+                                            );
+                                            let _ = event_tx.send(SwarmEvent2::RelayRefused {
+                                                peer_id: peer,
+                                                message_id: request.message_id.clone(),
+                                                reason,
+                                            }).await;
+                                            RelayResponse {
+                                                accepted: false,
+                                                error: Some(RELAY_DEVICE_POLICY.to_string()),
                                                 message_id: request.message_id.clone(),
                                             }
                                         } else if pending_custody_dispatches.len()
@@ -3347,6 +4576,10 @@ pub async fn start_swarm_with_config(
                                                                     resolved_device_id,
                                                                 ) {
                                                                     Ok(custody) => {
+                                                                        relay_server.record_peer_served(&peer.to_string());
+                                                                        relay_server.add_bytes_relayed(
+                                                                            request.envelope_data.len() as u64,
+                                                                        );
                                                                         relay_guardrails.record_accepted(
                                                                             &peer.to_string(),
                                                                             &destination.to_string(),
@@ -3376,14 +4609,41 @@ pub async fn start_swarm_with_config(
                                                                             message_id: relay_message_id,
                                                                         }
                                                                     }
-                                                                    Err(e) => RelayResponse {
-                                                                        accepted: false,
-                                                                        error: Some(format!(
-                                                                            "custody_store_failed: {}",
-                                                                            e
-                                                                        )),
-                                                                        message_id: relay_message_id,
-                                                                    },
+                                                                    Err(e) => {
+                                                                        // Custody is the durable path; the mailbox is a
+                                                                        // bounded fallback when it can't take a message
+                                                                        // for an offline destination.
+                                                                        let cached = !swarm.is_connected(&destination)
+                                                                            && crate::iron_core::public_key_hex_from_peer_id(&destination.to_string())
+                                                                                .map(|pk| relay_server.cache_for_offline_peer(&pk, request.envelope_data.clone()))
+                                                                                .unwrap_or(false);
+                                                                        if cached {
+                                                                            relay_guardrails.record_accepted(
+                                                                                &peer.to_string(),
+                                                                                &destination.to_string(),
+                                                                                &relay_message_id,
+                                                                                now_ms,
+                                                                            );
+                                                                            relay_server.record_peer_served(&peer.to_string());
+                                                                            tracing::info!(
+                                                                                "Custody store failed ({}); cached relay message {} for offline destination {} in relay mailbox",
+                                                                                e,
+                                                                                relay_message_id,
+                                                                                destination
+                                                                            );
+                                                                            RelayResponse {
+                                                                                accepted: true,
+                                                                                error: None,
+                                                                                message_id: relay_message_id,
+                                                                            }
+                                                                        } else {
+                                                                            RelayResponse {
+                                                                                accepted: false,
+                                                                                error: Some(format!("custody_store_failed: {}", e)),
+                                                                                message_id: relay_message_id,
+                                                                            }
+                                                                        }
+                                                                    }
                                                                 }
                                                             }
                                                         }
@@ -3402,8 +4662,30 @@ pub async fn start_swarm_with_config(
                                         let _ = swarm.behaviour_mut().relay.send_response(channel, relay_response);
                                     }
                                     request_response::Message::Response { request_id, response } => {
-                                        if let Some(message_id) = pending_relay_requests.remove(&request_id) {
+                                        if let Some(reply) = pending_relay_sends.remove(&request_id) {
+                                            let result = if response.accepted {
+                                                tracing::info!("[OK] Relay {} accepted message {}", peer, response.message_id);
+                                                Ok(())
+                                            } else {
+                                                let error = response
+                                                    .error
+                                                    .unwrap_or_else(|| "relay rejected".to_string());
+                                                if error == RELAY_BUDGET_EXHAUSTED {
+                                                    multi_path_delivery.record_relay_congested(peer);
+                                                    let _ = event_tx.send(SwarmEvent2::RelayCongested { relay_peer: peer }).await;
+                                                }
+                                                Err(error)
+                                            };
+                                            let _ = reply.send(result).await;
+                                        } else if let Some(message_id) = pending_relay_requests.remove(&request_id) {
                                             if let Some(pending) = pending_messages.remove(&message_id) {
+                                                let _ = event_tx.send(SwarmEvent2::DeliveryAttempted {
+                                                    message_id: delivery_message_id(&message_id, &pending),
+                                                    path: format!("relay:{}", peer),
+                                                    error: (!response.accepted).then(|| {
+                                                        response.error.clone().unwrap_or_else(|| "relay rejected".to_string())
+                                                    }),
+                                                }).await;
                                                 if response.accepted {
                                                     let latency_ms = pending.attempt_start.elapsed().unwrap_or_default().as_millis() as u64;
                                                     multi_path_delivery.record_success(&message_id, vec![peer, pending.target_peer], latency_ms);
@@ -3414,11 +4696,52 @@ pub async fn start_swarm_with_config(
                                                         .error
                                                         .unwrap_or_else(|| "relay rejected".to_string());
                                                     tracing::warn!("[FAIL] Relay via {} failed: {}", peer, error);
-                                                    multi_path_delivery.record_failure(&message_id, vec![peer, pending.target_peer]);
-                                                    if is_terminal_identity_rejection(&error) {
-                                                        let _ = pending.reply_tx.send(Err(error)).await;
-                                                    } else {
+                                                    if error == RELAY_BUDGET_EXHAUSTED {
+                                                        // Not a path failure: skip this relay for a
+                                                        // while and go straight to the next one
+                                                        // instead of waiting out the retry backoff.
+                                                        multi_path_delivery.record_relay_congested(peer);
+                                                        let _ = event_tx.send(SwarmEvent2::RelayCongested { relay_peer: peer }).await;
+                                                        let mut pending = pending;
+                                                        let routes = multi_path_delivery.ranked_routes(&pending.target_peer, 3);
+                                                        if let Some((index, route)) = routes
+                                                            .iter()
+                                                            .enumerate()
+                                                            .find(|(_, route)| route.path.len() > 1)
+                                                        {
+                                                            pending.current_path_index = index;
+                                                            pending.attempt_start = SystemTime::now();
+                                                            pending.dispatch_attempts = pending.dispatch_attempts.saturating_add(1);
+                                                            log_route_decision(
+                                                                &message_id,
+                                                                route,
+                                                                pending.dispatch_attempts,
+                                                                pending.pass_count,
+                                                                index,
+                                                                routes.len(),
+                                                                ROUTE_ATTEMPT_REASON_RELAY_CONGESTED,
+                                                            );
+                                                            dispatch_ranked_route(
+                                                                &mut swarm,
+                                                                route,
+                                                                &message_id,
+                                                                pending.target_peer,
+                                                                &pending.envelope_data,
+                                                                &mut request_to_message,
+                                                                &mut pending_relay_requests,
+                                                                pending.recipient_identity_id.as_deref(),
+                                                                pending.intended_device_id.as_deref(),
+                                                                pending.envelope_signature.as_deref(),
+                                                            );
+                                                        }
                                                         pending_messages.insert(message_id, pending);
+                                                    } else {
+                                                        multi_path_delivery.record_failure(&message_id, vec![peer, pending.target_peer]);
+                                                        if is_terminal_identity_rejection(&error) {
+                                                            let _ = pending.reply_tx.send(Err(error)).await;
+                                                        } else {
+                                                            pending_messages.insert(message_id, pending);
+                                                        }
                                                     }
                                                 }
                                             }
@@ -3429,7 +4752,10 @@ pub async fn start_swarm_with_config(
                             SwarmEvent::Behaviour(super::behaviour::IronCoreBehaviourEvent::Relay(
                                 request_response::Event::OutboundFailure { peer, request_id, error, .. }
                             )) => {
-                                if let Some(message_id) = pending_relay_requests.remove(&request_id) {
+                                if let Some(reply) = pending_relay_sends.remove(&request_id) {
+                                    tracing::warn!("[FAIL] Relay outbound failure via {}: {}", peer, error);
+                                    let _ = reply.send(Err(error.to_string())).await;
+                                } else if let Some(message_id) = pending_relay_requests.remove(&request_id) {
                                     if let Some(pending) = pending_messages.remove(&message_id) {
                                         tracing::warn!(
                                             "[FAIL] Relay outbound failure via {} to {}: {}",
@@ -3437,6 +4763,11 @@ pub async fn start_swarm_with_config(
                                             pending.target_peer,
                                             error
                                         );
+                                        let _ = event_tx.send(SwarmEvent2::DeliveryAttempted {
+                                            message_id: delivery_message_id(&message_id, &pending),
+                                            path: format!("relay:{}", peer),
+                                            error: Some(error.to_string()),
+                                        }).await;
                                         multi_path_delivery.record_failure(
                                             &message_id,
                                             vec![peer, pending.target_peer],
@@ -3456,6 +4787,21 @@ pub async fn start_swarm_with_config(
                             )) => {
                                 match message {
                                     request_response::Message::Request { request, channel, .. } => {
+                                        if !dial_learned_peers {
+                                            // Paranoid discovery takes no part in ledger
+                                            // exchange: learn nothing, share nothing.
+                                            tracing::debug!("Ignoring ledger exchange from {} (paranoid discovery)", peer);
+                                            let _ = swarm.behaviour_mut().ledger_exchange.send_response(
+                                                channel,
+                                                LedgerExchangeResponse {
+                                                    version_tag: 1,
+                                                    peers: Vec::new(),
+                                                    new_peers_learned: 0,
+                                                    version: 1,
+                                                },
+                                            );
+                                            continue;
+                                        }
                                         tracing::info!(
                                             "Ledger exchange from {}: received {} peer entries (v{})",
                                             peer,
@@ -3482,7 +4828,7 @@ pub async fn start_swarm_with_config(
                                                         entry.last_seen,
                                                     );
                                                     if let Ok(addr) = entry.multiaddr.parse::<Multiaddr>() {
-                                                        if is_discoverable_multiaddr(&addr) {
+                                                        if is_discoverable_multiaddr(&addr, advertise_scope) {
                                                             swarm.behaviour_mut().kademlia.add_address(&pid, addr);
                                                             new_count += 1;
                                                         }
@@ -3504,12 +4850,24 @@ pub async fn start_swarm_with_config(
 
                                         // Respond with an empty list — the application layer
                                         // will send our full ledger via ShareLedger command
-                                        // after processing the received entries.
+                                        // after processing the received entries. Explicit pulls
+                                        // (RequestPeers) are answered from the routing table,
+                                        // at most once per PEER_PULL_MIN_INTERVAL_SECS per peer.
+                                        let serve_pull = request.want_peers
+                                            && !last_peer_pull_served.get(&peer).is_some_and(|at| {
+                                                at.elapsed() < Duration::from_secs(PEER_PULL_MIN_INTERVAL_SECS)
+                                            });
+                                        let peers_back = if serve_pull {
+                                            last_peer_pull_served.insert(peer, Instant::now());
+                                            known_peer_entries(&mut swarm, &peer, advertise_scope)
+                                        } else {
+                                            Vec::new() // App layer fills this via ShareLedger
+                                        };
                                         let _ = swarm.behaviour_mut().ledger_exchange.send_response(
                                             channel,
                                             LedgerExchangeResponse {
                                                 version_tag: 1,
-                                                peers: Vec::new(), // App layer fills this via ShareLedger
+                                                peers: peers_back,
                                                 new_peers_learned: new_count,
                                                 version: 1,
                                             },
@@ -3517,16 +4875,21 @@ pub async fn start_swarm_with_config(
 
                                         ledger_exchanged_peers.insert(peer);
                                     }
-                                    request_response::Message::Response { response, .. } => {
+                                    request_response::Message::Response { request_id, response } => {
                                         tracing::info!(
                                             "Ledger exchange response from {}: they learned {} new peers, sent {} back",
                                             peer,
                                             response.new_peers_learned,
                                             response.peers.len(),
                                         );
+                                        if let Some(reply) = pending_peer_pulls.remove(&request_id) {
+                                            let _ = reply
+                                                .send(Ok(shared_entries_to_relay_peers(&response.peers)))
+                                                .await;
+                                        }
 
                                         // If they sent peers back in the response, merge those too
-                                        if !response.peers.is_empty() {
+                                        if dial_learned_peers && !response.peers.is_empty() {
                                             let _ = event_tx.send(SwarmEvent2::LedgerReceived {
                                                 from_peer: peer,
                                                 entries: response.peers.clone(),
@@ -3542,7 +4905,7 @@ pub async fn start_swarm_with_config(
                                                             entry.last_seen,
                                                         );
                                                         if let Ok(addr) = entry.multiaddr.parse::<Multiaddr>() {
-                                                            if is_discoverable_multiaddr(&addr) {
+                                                            if is_discoverable_multiaddr(&addr, advertise_scope) {
                                                                 swarm.behaviour_mut().kademlia.add_address(&pid, addr);
                                                             }
                                                         }
@@ -3554,6 +4917,15 @@ pub async fn start_swarm_with_config(
                                 }
                             }
 
+                            SwarmEvent::Behaviour(super::behaviour::IronCoreBehaviourEvent::LedgerExchange(
+                                request_response::Event::OutboundFailure { peer, request_id, error, .. }
+                            )) => {
+                                if let Some(reply) = pending_peer_pulls.remove(&request_id) {
+                                    tracing::debug!("Peer list request to {} failed: {}", peer, error);
+                                    let _ = reply.send(Err(error.to_string())).await;
+                                }
+                            }
+
                             // Gossipsub events — Dynamic Topic Negotiation
                             SwarmEvent::Behaviour(super::behaviour::IronCoreBehaviourEvent::Gossipsub(
                                 gossipsub::Event::Subscribed { peer_id, topic }
@@ -3582,16 +4954,34 @@ pub async fn start_swarm_with_config(
                             SwarmEvent::Behaviour(super::behaviour::IronCoreBehaviourEvent::Gossipsub(
                                 gossipsub::Event::Message { propagation_source, message, .. }
                             )) => {
-                                // Accept all gossipsub messages — log and forward
+                                // Gossipsub validation is permissive; authenticate the
+                                // payload ourselves and drop anything unsigned or tampered.
+                                let Some(signed) = SignedGossip::open(&message.data, message.topic.as_str()) else {
+                                    tracing::warn!(
+                                        "Dropping unsigned or tampered gossipsub frame from {} on topic {:?}",
+                                        propagation_source,
+                                        message.topic
+                                    );
+                                    continue;
+                                };
+                                if let Err(reason) = topic_limits.check(message.topic.as_str(), signed.payload.len()) {
+                                    tracing::warn!(
+                                        "Dropping gossipsub frame from {}: {}",
+                                        propagation_source,
+                                        reason
+                                    );
+                                    continue;
+                                }
                                 tracing::debug!(
-                                    "Gossipsub message from {} on topic {:?} ({} bytes)",
+                                    "Gossipsub message from {} on topic {:?} ({} bytes, signer {})",
                                     propagation_source,
                                     message.topic,
-                                    message.data.len()
+                                    signed.payload.len(),
+                                    signed.sender_pubkey
                                 );
                                 if message.topic.as_str() == DELIVERY_CONVERGENCE_TOPIC {
                                     if let Some(marker) =
-                                        decode_delivery_convergence_marker(&message.data)
+                                        decode_delivery_convergence_marker(&signed.payload)
                                     {
                                         if let Err(reason) = should_apply_delivery_convergence_marker(
                                             &marker,
@@ -3627,11 +5017,29 @@ pub async fn start_swarm_with_config(
                                             if propagation_source != local_peer_id {
                                                 publish_delivery_convergence_marker(
                                                     &mut swarm,
+                                                    &gossip_keypair,
                                                     &marker,
                                                 );
                                             }
                                         }
                                     }
+                                } else if message.topic.as_str() == crate::TOPIC_LOBBY
+                                    && super::presence::is_presence_payload(&signed.payload)
+                                {
+                                    if let Some(core) = core_handle.as_ref().and_then(|w| w.upgrade()) {
+                                        if let Err(e) = core.receive_presence_beacon(signed.payload) {
+                                            tracing::debug!(
+                                                "Ignoring presence beacon from {}: {:?}",
+                                                propagation_source,
+                                                e
+                                            );
+                                        }
+                                    }
+                                } else if is_broadcast_payload(&signed.payload) {
+                                    let _ = event_tx.send(SwarmEvent2::BroadcastReceived {
+                                        topic: message.topic.to_string(),
+                                        frame: message.data,
+                                    }).await;
                                 }
                             }
 
@@ -3643,6 +5051,11 @@ pub async fn start_swarm_with_config(
                                             "AutoNAT status: {:?} → {:?}",
                                             old, new
                                         );
+                                        hole_punches.set_public(match new {
+                                            autonat::NatStatus::Public(_) => Some(true),
+                                            autonat::NatStatus::Private => Some(false),
+                                            autonat::NatStatus::Unknown => None,
+                                        });
                                         // Update NAT status for the application layer.
                                         // This determines whether relay fallback is required.
                                         let status_str = match new {
@@ -3702,6 +5115,7 @@ pub async fn start_swarm_with_config(
                                             "DCUtR hole-punch SUCCESS with {} (attempts: {})",
                                             remote_peer_id, num_attempts
                                         );
+                                        hole_punches.record_success(remote_peer_id);
                                         // Hole-punch succeeded — direct connection established.
                                         // Add this peer's direct addresses to Kademlia so the
                                         // DHT knows how to reach them without the relay.
@@ -3733,6 +5147,22 @@ pub async fn start_swarm_with_config(
                                         );
                                         // Hole-punch failed — this is OK; our application-layer
                                         // relay (/sc/relay/1.0.0) handles the fallback.
+                                        // While rounds remain, dial the peer's known direct
+                                        // addresses again: a fresh attempt from our side
+                                        // often lands once the NAT mapping is warm.
+                                        if hole_punches.record_failure(remote_peer_id, nat_config.hole_punch_attempts) {
+                                            let opts = libp2p::swarm::dial_opts::DialOpts::peer_id(remote_peer_id)
+                                                .condition(libp2p::swarm::dial_opts::PeerCondition::Always)
+                                                .build();
+                                            if let Err(e) = swarm.dial(opts) {
+                                                tracing::debug!("Direct redial of {} failed: {}", remote_peer_id, e);
+                                            }
+                                        } else {
+                                            tracing::info!(
+                                                "Giving up hole-punching {} after {} rounds",
+                                                remote_peer_id, nat_config.hole_punch_attempts
+                                            );
+                                        }
                                     }
                                 }
                             }
@@ -3745,6 +5175,7 @@ pub async fn start_swarm_with_config(
                                         renewal,
                                         ..
                                     } => {
+                                        accepted_relay_reservations.insert(relay_peer_id);
                                         if renewal {
                                             tracing::debug!(
                                                 "Relay circuit reservation RENEWED via {}",
@@ -3786,12 +5217,17 @@ pub async fn start_swarm_with_config(
                                 #[allow(deprecated)]
                                 match event {
                                     RelayServerEvent::ReservationReqAccepted { src_peer_id, .. } => {
+                                        relay_server.record_peer_served(&src_peer_id.to_string());
                                         tracing::info!(
                                             "[OK] Relay server: accepted reservation from {} — acting as relay for this peer",
                                             src_peer_id
                                         );
                                     }
                                     RelayServerEvent::CircuitReqAccepted { src_peer_id, dst_peer_id } => {
+                                        relay_server.circuit_opened(
+                                            &src_peer_id.to_string(),
+                                            &dst_peer_id.to_string(),
+                                        );
                                         tracing::info!(
                                             "Relay server: circuit established {} -> {} — relaying traffic",
                                             src_peer_id,
@@ -3799,6 +5235,7 @@ pub async fn start_swarm_with_config(
                                         );
                                     }
                                     RelayServerEvent::CircuitClosed { src_peer_id, dst_peer_id, .. } => {
+                                        relay_server.circuit_closed();
                                         tracing::debug!(
                                             "Circuit closed: {} -> {}",
                                             src_peer_id,
@@ -3821,6 +5258,18 @@ pub async fn start_swarm_with_config(
 
                             SwarmEvent::Behaviour(super::behaviour::IronCoreBehaviourEvent::Ping(event)) => {
                                 tracing::trace!("Ping event: {:?}", event);
+                                if let Ok(rtt) = event.result {
+                                    // Proximity signal for routing: lower RTT wins equal-hop ties.
+                                    let avg = rtt_tracker.record(event.peer, rtt);
+                                    let peer_bytes = extract_peer_id_bytes(&event.peer.to_bytes());
+                                    let mut guard = routing_engine_handle.write();
+                                    if let Some(ref mut engine) = guard.as_mut() {
+                                        engine
+                                            .base_engine_mut()
+                                            .local_cell_mut()
+                                            .record_rtt(&peer_bytes, avg.as_millis() as u64);
+                                    }
+                                }
                             }
 
                             #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
@@ -3829,7 +5278,7 @@ pub async fn start_swarm_with_config(
                             )) => {
                                 for (peer_id, addr) in peers {
                                     tracing::info!("mDNS discovered peer: {} at {}", peer_id, addr);
-                                    if is_discoverable_multiaddr(&addr) {
+                                    if is_discoverable_multiaddr(&addr, advertise_scope) {
                                         swarm.behaviour_mut().kademlia.add_address(&peer_id, addr);
                                     }
 
@@ -3861,8 +5310,35 @@ pub async fn start_swarm_with_config(
                             // Accept ANY peer identity, regardless of expected PeerID.
                             // Log the identity and add all addresses to Kademlia.
                             SwarmEvent::Behaviour(super::behaviour::IronCoreBehaviourEvent::Identify(
-                                identify::Event::Received { peer_id, info, .. }
+                                identify::Event::Received { connection_id, peer_id, info, .. }
                             )) => {
+                                // Health-check probe: report and tear down the probe
+                                // connection without treating it as a mesh peer.
+                                if let Some(probe) = pending_probes.remove(&connection_id) {
+                                    let latency_ms = probe.started_at.elapsed().as_millis() as u64;
+                                    tracing::info!("Probe of {} answered by {} in {}ms ({})", probe.addr, peer_id, latency_ms, info.agent_version);
+                                    let _ = probe.reply.send(ProbeResult {
+                                        addr: probe.addr.to_string(),
+                                        reachable: true,
+                                        latency_ms,
+                                        peer_id: Some(peer_id.to_string()),
+                                        agent_version: Some(info.agent_version.clone()),
+                                        error: None,
+                                    }).await;
+                                    swarm.close_connection(connection_id);
+                                    continue;
+                                }
+
+                                // Recorded before the log dedup below, which skips the rest
+                                // of this handler for repeat identifies.
+                                peer_capabilities.insert(
+                                    peer_id,
+                                    PeerCapabilities::from_agent_version(&info.agent_version),
+                                );
+                                if let Ok(pk) = info.public_key.clone().try_into_ed25519() {
+                                    peer_public_keys.insert(peer_id, hex::encode(pk.to_bytes()));
+                                }
+
                                 // Dedup: suppress "Identified peer" logs for same peer within TTL window
                                 {
                                     let now = Instant::now();
@@ -3880,7 +5356,7 @@ pub async fn start_swarm_with_config(
                                 // Consolidate multi-address logging: build single summary line per peer
                                 let discoverable_addrs: Vec<&Multiaddr> = info.listen_addrs
                                     .iter()
-                                    .filter(|a| is_discoverable_multiaddr(a))
+                                    .filter(|a| is_discoverable_multiaddr(a, advertise_scope))
                                     .collect();
                                 tracing::info!(
                                     "🆔 Identified peer {} — agent: {}, protocols: {}, discoverable_addrs: {}",
@@ -3946,7 +5422,7 @@ pub async fn start_swarm_with_config(
                                 // Loopback/unspecified addresses are excluded.
                                 // Private/RFC1918/CGNAT are NOW allowed for local mesh.
                                 for addr in &info.listen_addrs {
-                                    if is_discoverable_multiaddr(addr) {
+                                    if is_discoverable_multiaddr(addr, advertise_scope) {
                                         swarm.behaviour_mut().kademlia.add_address(&peer_id, addr.clone());
                                     } else {
                                         tracing::debug!("Skipping non-discoverable Kademlia addr for {}: {}", peer_id, addr);
@@ -3954,7 +5430,7 @@ pub async fn start_swarm_with_config(
                                 }
 
                                 // Check if peer advertises relay capability
-                                let is_relay = info.agent_version.contains("relay");
+                                let is_relay = super::capability::is_relay_agent(&info.agent_version);
                                 if is_relay {
                                     tracing::info!("Peer {} is identified as a RELAY node (agent: {})", peer_id, info.agent_version);
                                     bootstrap_capability.add_peer(peer_id);
@@ -3977,7 +5453,7 @@ pub async fn start_swarm_with_config(
                                     if !already_reserved {
                                         let routable_relay_addrs: Vec<Multiaddr> = info.listen_addrs
                                             .iter()
-                                            .filter(|a| is_discoverable_multiaddr(a))
+                                            .filter(|a| is_discoverable_multiaddr(a, advertise_scope))
                                             .cloned()
                                             .collect();
 
@@ -4076,10 +5552,18 @@ pub async fn start_swarm_with_config(
 
                             SwarmEvent::NewListenAddr { address, .. } => {
                                 tracing::info!("Listening on {}", address);
+                                advertise_listen_addr(&mut swarm, &address, advertise_scope);
                                 bound_addresses.push(address.clone());
                                 let _ = event_tx.send(SwarmEvent2::ListeningOn(address)).await;
                             }
 
+                            SwarmEvent::ExpiredListenAddr { address, .. } => {
+                                tracing::info!("No longer listening on {}", address);
+                                swarm.remove_external_address(&address);
+                                bound_addresses.retain(|a| a != &address);
+                                let _ = event_tx.send(SwarmEvent2::ListenAddrExpired(address)).await;
+                            }
+
                             SwarmEvent::ConnectionEstablished { peer_id, endpoint, connection_id, .. } => {
                                 let remote_addr = endpoint.get_remote_address().clone();
 
@@ -4179,6 +5663,10 @@ pub async fn start_swarm_with_config(
                                     RELAY_MAX_INFLIGHT_DISPATCHES,
                                     "peer_reconnect",
                                 );
+                                dispatch_relay_mailbox_for_peer(&mut swarm, &relay_server, peer_id);
+                                if clock_sync_enabled && known_relays.contains(&peer_id) {
+                                    send_clock_probe(&mut swarm, &mut clock_probes, peer_id);
+                                }
 
                                 // RELAY PEER DISCOVERY: Track peer and broadcast to others
                                 // Start with the observed remote address.
@@ -4260,15 +5748,33 @@ pub async fn start_swarm_with_config(
                                 }
                             }
 
-                            SwarmEvent::ConnectionClosed { peer_id, .. } => {
+                            SwarmEvent::ConnectionClosed { peer_id, connection_id, num_established, .. } => {
+                                if num_established == 0 {
+                                    rtt_tracker.remove(&peer_id);
+                                    peer_capabilities.remove(&peer_id);
+                                    peer_public_keys.remove(&peer_id);
+                                }
+                                if let Some(probe) = pending_probes.remove(&connection_id) {
+                                    let elapsed = probe.started_at.elapsed();
+                                    let _ = probe.reply.send(ProbeResult::unreachable(
+                                        &probe.addr,
+                                        elapsed,
+                                        "Connection closed before Identify completed".to_string(),
+                                    )).await;
+                                }
                                 tracing::info!("[ERROR] Disconnected from {}", peer_id);
                                 connection_tracker.remove_connection(&peer_id);
                                 // Allow re-exchange if they reconnect
                                 ledger_exchanged_peers.remove(&peer_id);
                                 reported_peer_discoveries.remove(&peer_id);
                                 reported_peer_info.remove(&peer_id);
+                                // Pull rate-limit entries only matter inside the window.
+                                let pull_window = Duration::from_secs(PEER_PULL_MIN_INTERVAL_SECS);
+                                last_peer_pull.retain(|_, at| at.elapsed() < pull_window);
+                                last_peer_pull_served.retain(|_, at| at.elapsed() < pull_window);
 
                                 // P0.13: Clear relay tracking so we can re-reserve on reconnect
+                                accepted_relay_reservations.remove(&peer_id);
                                 if let Some(listener_id) = successful_relay_reservations.remove(&peer_id) {
                                     tracing::debug!("Clearing stale relay reservation for {}: {:?}", peer_id, listener_id);
                                     // Note: libp2p usually kills circuit listeners on connection close,
@@ -4352,7 +5858,11 @@ pub async fn start_swarm_with_config(
                             }
 
                             // Handle outgoing connection errors gracefully — don't panic
-                            SwarmEvent::OutgoingConnectionError { peer_id, error, .. } => {
+                            SwarmEvent::OutgoingConnectionError { peer_id, connection_id, error, .. } => {
+                                if let Some(probe) = pending_probes.remove(&connection_id) {
+                                    let elapsed = probe.started_at.elapsed();
+                                    let _ = probe.reply.send(ProbeResult::unreachable(&probe.addr, elapsed, error.to_string())).await;
+                                }
                                 // Downgraded to debug: Kademlia DHT explores many stale addresses
                                 // from the routing table; timeouts here are expected churn, not
                                 // actionable errors. Relay/identity failures surface at info/warn.
@@ -4527,6 +6037,13 @@ pub async fn start_swarm_with_config(
                                         error: format!("listener closed for {:?}: {:?}", addresses, reason),
                                     }).await;
                                 }
+                                // libp2p does not emit ExpiredListenAddr for the
+                                // addresses of a closed listener, so report them here.
+                                for address in addresses {
+                                    swarm.remove_external_address(&address);
+                                    bound_addresses.retain(|a| a != &address);
+                                    let _ = event_tx.send(SwarmEvent2::ListenAddrExpired(address)).await;
+                                }
                             }
 
                             _ => {}
@@ -4538,8 +6055,10 @@ pub async fn start_swarm_with_config(
                         match command {
                             #[cfg(not(target_arch = "wasm32"))]
                             SwarmCommand::SendMessage { peer_id, envelope_data, recipient_identity_id, intended_device_id, reply } => {
+                                last_real_send = Some(Instant::now());
                                 // PHASE 6: Multi-path delivery with routing engine integration
                                 let message_id = format!("{}-{}", peer_id, SystemTime::now().duration_since(UNIX_EPOCH).expect("system clock before UNIX_EPOCH").as_millis());
+                                let span_guard = outbound_message_span(&message_id, &envelope_data).entered();
 
                                 // Start delivery tracking
                                 multi_path_delivery.start_delivery(message_id.clone(), peer_id);
@@ -4613,12 +6132,18 @@ pub async fn start_swarm_with_config(
                                 );
 
                                 if routes.is_empty() {
+                                    tracing::warn!("No paths available for {}", peer_id);
+                                    // Never hold an entered span across an await.
+                                    drop(span_guard);
                                     let _ = reply.send(Err("No paths available".to_string())).await;
                                     continue;
                                 }
 
                                 let initial_route = &routes[0];
                                 let attempt_start = SystemTime::now();
+                                let envelope_signature = relay_signature_policy
+                                    .outbound_signer(relay_signer.as_ref())
+                                    .and_then(|key| crate::crypto::sign_relay_envelope(&envelope_data, key).ok());
                                 log_route_decision(
                                     &message_id,
                                     initial_route,
@@ -4638,6 +6163,7 @@ pub async fn start_swarm_with_config(
                                     &mut pending_relay_requests,
                                     recipient_identity_id.as_deref(),
                                     intended_device_id.as_deref(),
+                                    envelope_signature.as_deref(),
                                 );
 
                                 // Store pending message for retry handling
@@ -4650,14 +6176,18 @@ pub async fn start_swarm_with_config(
                                     dispatch_attempts: 1,
                                     pass_count: 0,
                                     retry_notified: false,
+                                    retry_delay: delivery_policy.next_retry_delay(0),
                                     recipient_identity_id,
                                     intended_device_id,
+                                    envelope_signature,
                                 });
                             }
                             #[cfg(target_arch = "wasm32")]
                             SwarmCommand::SendMessage { peer_id, envelope_data, recipient_identity_id, intended_device_id, reply } => {
+                                last_real_send = Some(Instant::now());
                                 // WASM: Simple direct send without complex routing
                                 let message_id = format!("{}-{}", peer_id, SystemTime::now().duration_since(UNIX_EPOCH).expect("system clock before UNIX_EPOCH").as_millis());
+                                let _span = outbound_message_span(&message_id, &envelope_data).entered();
                                 let framed = wrap_in_drift_frame(&envelope_data);
                                 let request_id = swarm.behaviour_mut().messaging.send_request(
                                     &peer_id,
@@ -4674,12 +6204,41 @@ pub async fn start_swarm_with_config(
                                     dispatch_attempts: 1,
                                     pass_count: 0,
                                     retry_notified: false,
+                                    retry_delay: delivery_policy.next_retry_delay(0),
                                     recipient_identity_id,
                                     intended_device_id,
+                                    envelope_signature: None,
                                 });
                                 request_to_message.insert(request_id, message_id);
                             }
 
+                            #[cfg(not(target_arch = "wasm32"))]
+                            SwarmCommand::SendViaRelay { relay_peer, target_peer, envelope_data, reply } => {
+                                if !swarm.is_connected(&relay_peer) {
+                                    let _ = reply.send(Err(format!("Relay {} is not connected", relay_peer))).await;
+                                    continue;
+                                }
+                                let message_id = format!("{}-via-{}-{}", target_peer, relay_peer, SystemTime::now().duration_since(UNIX_EPOCH).expect("system clock before UNIX_EPOCH").as_millis());
+                                let request_id = swarm.behaviour_mut().relay.send_request(
+                                    &relay_peer,
+                                    RelayRequest {
+                                        destination_peer: target_peer.to_bytes(),
+                                        envelope_data: wrap_in_drift_frame(&envelope_data),
+                                        message_id,
+                                        recipient_identity_id: None,
+                                        intended_device_id: None,
+                                        envelope_signature: relay_signature_policy
+                                            .outbound_signer(relay_signer.as_ref())
+                                            .and_then(|key| crate::crypto::sign_relay_envelope(&envelope_data, key).ok()),
+                                    },
+                                );
+                                pending_relay_sends.insert(request_id, reply);
+                            }
+                            #[cfg(target_arch = "wasm32")]
+                            SwarmCommand::SendViaRelay { reply, .. } => {
+                                let _ = reply.send(Err("Relayed send is not supported on wasm32".to_string())).await;
+                            }
+
                             SwarmCommand::RegisterIdentity { peer_id, request, reply } => {
                                 let request_id = swarm
                                     .behaviour_mut()
@@ -4722,6 +6281,11 @@ pub async fn start_swarm_with_config(
                                                 let _ = reply.send(addresses).await;
                                             }
 
+                                            SwarmCommand::AdmitAppDial { addr, policy, reply } => {
+                                                let admitted = app_dial_coalescer.admit(&multiaddr_to_key(&addr), policy, web_time::Instant::now());
+                                                let _ = reply.send(admitted).await;
+                                            }
+
                                             SwarmCommand::DiscoveryDial { peer_id, addr } => {
                                                 tracing::debug!("Processing off-loop discovery dial to {} for peer {}", addr, peer_id);
                                                 let _ = swarm.dial(addr);
@@ -4919,6 +6483,24 @@ pub async fn start_swarm_with_config(
                                 let _ = reply.send(peers).await;
                             }
 
+                            SwarmCommand::GetPeerRtt { peer_id, reply } => {
+                                let _ = reply.send(rtt_tracker.average(&peer_id)).await;
+                            }
+                            SwarmCommand::GetPeerCapabilities { peer_id, reply } => {
+                                let _ = reply.send(peer_capabilities.get(&peer_id).cloned()).await;
+                            }
+
+                            SwarmCommand::ConfirmPeerPubkey { peer_id, reply } => {
+                                let result = if !swarm.is_connected(&peer_id) {
+                                    Err(format!("Not connected to {}", peer_id))
+                                } else {
+                                    peer_public_keys.get(&peer_id).cloned().ok_or_else(|| {
+                                        format!("{} has not completed Identify yet", peer_id)
+                                    })
+                                };
+                                let _ = reply.send(result).await;
+                            }
+
                             SwarmCommand::Listen { addr, reply } => {
                                 match swarm.listen_on(addr) {
                                     Ok(_) => {
@@ -4931,7 +6513,7 @@ pub async fn start_swarm_with_config(
                             }
 
                             SwarmCommand::AddKadAddress { peer_id, addr } => {
-                                if is_discoverable_multiaddr(&addr) {
+                                if is_discoverable_multiaddr(&addr, advertise_scope) {
                                     swarm.behaviour_mut().kademlia.add_address(&peer_id, addr);
                                 }
                             }
@@ -4976,25 +6558,51 @@ pub async fn start_swarm_with_config(
                             }
 
                             SwarmCommand::PublishTopic { topic, data, reply } => {
-                                let ident_topic = libp2p::gossipsub::IdentTopic::new(topic.clone());
-                                match swarm.behaviour_mut().gossipsub.publish(ident_topic, data) {
-                                    Ok(_) => {
+                                if let Err(reason) = topic_limits.check(&topic, data.len()) {
+                                    tracing::warn!("Refusing to publish: {}", reason);
+                                    let _ = reply.send(Err(reason)).await;
+                                    continue;
+                                }
+                                match publish_signed_gossip(&mut swarm, &gossip_keypair, &topic, data) {
+                                    Ok(()) => {
                                         tracing::debug!("Published payload to topic {}", topic);
                                         let _ = reply.send(Ok(())).await;
                                     }
                                     Err(e) => {
                                         tracing::warn!("Failed to publish to topic {}: {}", topic, e);
-                                        let _ = reply.send(Err(e.to_string())).await;
+                                        let _ = reply.send(Err(e)).await;
                                     }
                                 }
                             }
 
+                            SwarmCommand::PublishTopicCounted { topic, data, reply } => {
+                                if let Err(reason) = topic_limits.check(&topic, data.len()) {
+                                    tracing::warn!("Refusing to publish: {}", reason);
+                                    let _ = reply.send(Err(reason)).await;
+                                    continue;
+                                }
+                                let result = publish_signed_gossip_counted(&mut swarm, &gossip_keypair, &topic, data);
+                                match &result {
+                                    Ok(peers) => tracing::debug!("Published payload to topic {} ({} peers)", topic, peers),
+                                    Err(e) => tracing::warn!("Failed to publish to topic {}: {}", topic, e),
+                                }
+                                let _ = reply.send(result).await;
+                            }
+
                             SwarmCommand::GetTopics { reply } => {
                                 let topics: Vec<String> = subscribed_topics.iter().cloned().collect();
                                 let _ = reply.send(topics).await;
                             }
 
+                            SwarmCommand::GetNatStatus { reply } => {
+                                let _ = reply.send(hole_punches.report(&nat_config)).await;
+                            }
+
                             SwarmCommand::ShareLedger { peer_id, entries } => {
+                                if !dial_learned_peers {
+                                    tracing::debug!("Not sharing ledger with {} (paranoid discovery)", peer_id);
+                                    continue;
+                                }
                                 // Send our known peer list to the specified peer
                                 if !ledger_exchanged_peers.contains(&peer_id) {
                                     tracing::info!(
@@ -5008,6 +6616,7 @@ pub async fn start_swarm_with_config(
                                         peers: entries,
                                         sender_peer_id: local_peer_id.to_string(),
                                         version: 1,
+                                        want_peers: false,
                                     };
 
                                     let _request_id = swarm.behaviour_mut().ledger_exchange.send_request(
@@ -5021,10 +6630,69 @@ pub async fn start_swarm_with_config(
                                 }
                             }
 
+                            SwarmCommand::RequestPeers { peer_id, reply } => {
+                                if !swarm.is_connected(&peer_id) {
+                                    let _ = reply.send(Err(format!("Not connected to {}", peer_id))).await;
+                                    continue;
+                                }
+                                let recently_pulled = last_peer_pull.get(&peer_id).is_some_and(|at| {
+                                    at.elapsed() < Duration::from_secs(PEER_PULL_MIN_INTERVAL_SECS)
+                                });
+                                if recently_pulled {
+                                    let _ = reply
+                                        .send(Err(format!("Peer list from {} requested too recently", peer_id)))
+                                        .await;
+                                    continue;
+                                }
+                                last_peer_pull.insert(peer_id, Instant::now());
+                                tracing::info!("Requesting known-peer list from {}", peer_id);
+                                let request_id = swarm.behaviour_mut().ledger_exchange.send_request(
+                                    &peer_id,
+                                    LedgerExchangeRequest {
+                                        version_tag: 1,
+                                        peers: Vec::new(),
+                                        sender_peer_id: local_peer_id.to_string(),
+                                        version: 1,
+                                        want_peers: true,
+                                    },
+                                );
+                                pending_peer_pulls.insert(request_id, reply);
+                            }
+
                             SwarmCommand::GetListeners { reply } => {
                     let listeners: Vec<Multiaddr> = swarm.listeners().cloned().collect();
                     let _ = reply.send(listeners).await;
                 }
+                            SwarmCommand::GetRelayReservations { reply } => {
+                                let reservations = relay_reservations(
+                                    &relay_peer_addrs,
+                                    &accepted_relay_reservations,
+                                );
+                                let _ = reply.send(reservations).await;
+                            }
+                            SwarmCommand::ReconnectBootstrap { reply } => {
+                                let connected_peers: HashSet<PeerId> = swarm.connected_peers().cloned().collect();
+                                let mut started = 0;
+                                for addr in bootstrap_redial_targets(
+                                    &bootstrap_addrs_clone,
+                                    local_peer_id,
+                                    &connected_peers,
+                                    &bootstrap_backoff,
+                                    &mut self_dial_logged,
+                                ) {
+                                    if start_bootstrap_redial(
+                                        &mut swarm,
+                                        &addr,
+                                        &mut in_flight_dns,
+                                        &mut bootstrap_backoff,
+                                        &command_tx,
+                                    ) {
+                                        started += 1;
+                                    }
+                                }
+                                tracing::info!("Reconnect requested: {} bootstrap dial(s) started", started);
+                                let _ = reply.send(started).await;
+                            }
                             SwarmCommand::ListEndpoints { peer_id: _, reply } => {
                                 // Return our own listening addresses as the endpoint list.
                                 // The Kademlia DHT doesn't expose a direct address lookup per peer
@@ -5061,6 +6729,45 @@ pub async fn start_swarm_with_config(
                                 relay_budget = budget;
                                 tracing::info!("Relay budget updated: {} msgs/hour", budget);
                             }
+                            SwarmCommand::SetRelaySignaturePolicy { policy } => {
+                                relay_signature_policy = policy;
+                                tracing::info!("Relay signature policy updated: {:?}", policy);
+                            }
+                            SwarmCommand::SetInboundRateLimit { limit } => {
+                                inbound_limiter.set_limit(limit);
+                                tracing::info!(
+                                    "Inbound rate limit updated: {} msgs/sec, burst {}",
+                                    limit.messages_per_sec,
+                                    limit.burst
+                                );
+                            }
+                            SwarmCommand::SetClockSync { enabled } => {
+                                // Settings are re-applied on every update;
+                                // only a change from off to on re-probes.
+                                let newly_enabled = enabled && !clock_sync_enabled;
+                                clock_sync_enabled = enabled;
+                                tracing::info!("Verified clock sync {}", if enabled { "enabled" } else { "disabled" });
+                                if newly_enabled {
+                                    let relays: Vec<PeerId> = known_relays
+                                        .iter()
+                                        .filter(|peer| swarm.is_connected(peer))
+                                        .copied()
+                                        .collect();
+                                    for relay in relays {
+                                        send_clock_probe(&mut swarm, &mut clock_probes, relay);
+                                    }
+                                } else if !enabled {
+                                    clock_probes.clear();
+                                }
+                            }
+                            SwarmCommand::SetTopicSizeLimits { limits } => {
+                                tracing::info!(
+                                    "Topic payload limits updated: {} bytes default, {} topic override(s)",
+                                    limits.default_max,
+                                    limits.per_topic.len()
+                                );
+                                topic_limits = limits;
+                            }
 
                             SwarmCommand::GetBestRelays { count, reply } => {
                                 let relays = multi_path_delivery.best_relays(count);
@@ -5070,10 +6777,35 @@ pub async fn start_swarm_with_config(
                                 let candidates = bootstrap_capability.get_bootstrap_candidates().to_vec();
                                 let _ = reply.send(candidates).await;
                             }
+                            SwarmCommand::SetPreferredRelays { peer_id, relays } => {
+                                tracing::debug!("Preferred relays for {}: {:?}", peer_id, relays);
+                                multi_path_delivery.set_preferred_relays(peer_id, relays);
+                            }
                             SwarmCommand::GetBestPaths { target, count, reply } => {
                                 let paths = multi_path_delivery.get_best_paths(&target, count);
                                 let _ = reply.send(paths).await;
                             }
+                            SwarmCommand::ProbePeer { addr, timeout, reply } => {
+                                let opts = libp2p::swarm::dial_opts::DialOpts::unknown_peer_id()
+                                    .address(addr.clone())
+                                    .build();
+                                let connection_id = opts.connection_id();
+                                let started_at = web_time::Instant::now();
+                                match swarm.dial(opts) {
+                                    Ok(()) => {
+                                        tracing::debug!("Probing {} (timeout {}ms)", addr, timeout.as_millis());
+                                        pending_probes.insert(connection_id, PendingProbeEntry {
+                                            addr,
+                                            reply,
+                                            started_at,
+                                            timeout,
+                                        });
+                                    }
+                                    Err(e) => {
+                                        let _ = reply.send(ProbeResult::unreachable(&addr, started_at.elapsed(), e.to_string())).await;
+                                    }
+                                }
+                            }
                             SwarmCommand::Shutdown => {
                                 tracing::info!("Swarm shutting down");
                                 break;
@@ -5099,25 +6831,74 @@ pub async fn start_swarm_with_config(
 
         let _ = routing_engine_handle;
         let local_peer_id = keypair.public().to_peer_id();
+        // Retained for signing gossipsub payloads; the builder consumes `keypair`.
+        let gossip_keypair = keypair.clone();
+        let enable_webrtc_direct = discovery_config
+            .as_ref()
+            .map(|c| c.enable_webrtc_direct)
+            .unwrap_or(false);
+        let gossip_profile = discovery_config
+            .as_ref()
+            .map(|c| c.gossip_profile)
+            .unwrap_or_default();
+        let kad_mode = discovery_config
+            .as_ref()
+            .map(|c| c.kad_mode)
+            .unwrap_or_default();
+        let advertise_scope = discovery_config
+            .as_ref()
+            .map(|c| c.advertise_scope)
+            .unwrap_or_default();
+        let dial_learned_peers = discovery_config
+            .as_ref()
+            .map(|c| c.mesh_discovery_mode.dials_learned_peers())
+            .unwrap_or(true);
 
         // Browser transport: websocket-websys + Noise + Yamux, then relay client support.
         // This keeps protocol-level parity with native swarm behaviour.
+        //
+        // When enabled, libp2p WebRTC is added alongside the websocket path,
+        // dial-only: `/webrtc-direct` peers need no signaling server (the
+        // certhash in the multiaddr stands in for the SDP exchange).
+        // libp2p-webrtc-websys cannot listen or dial browser-to-browser
+        // `/webrtc`, so browser pairs keep talking through the relay.
         let mut swarm = libp2p::SwarmBuilder::with_existing_identity(keypair)
             .with_wasm_bindgen()
             .with_other_transport(
                 |id_keys| -> std::result::Result<_, Box<dyn std::error::Error + Send + Sync>> {
                     let noise = libp2p::noise::Config::new(id_keys)?;
-                    Ok(libp2p::websocket_websys::Transport::default()
+                    let websocket = libp2p::websocket_websys::Transport::default()
                         .upgrade(Version::V1Lazy)
                         .authenticate(noise)
                         .multiplex(libp2p::yamux::Config::default())
-                        .map(|(peer_id, conn), _| (peer_id, StreamMuxerBox::new(conn))))
+                        .map(|(peer_id, conn), _| (peer_id, StreamMuxerBox::new(conn)));
+                    let webrtc = if enable_webrtc_direct {
+                        tracing::info!("WebRTC browser transport: enabled");
+                        libp2p::core::transport::OptionalTransport::some(
+                            libp2p::webrtc_websys::Transport::new(
+                                libp2p::webrtc_websys::Config::new(id_keys),
+                            )
+                            .map(|(peer_id, conn), _| (peer_id, StreamMuxerBox::new(conn))),
+                        )
+                    } else {
+                        libp2p::core::transport::OptionalTransport::none()
+                    };
+                    Ok(websocket
+                        .or_transport(webrtc)
+                        .map(|either, _| either.into_inner()))
                 },
             )?
             .with_relay_client(libp2p::noise::Config::new, libp2p::yamux::Config::default)?
             .with_behaviour(|key, relay_client| {
-                IronCoreBehaviour::new(key, relay_client, headless, discovery_config)
-                    .expect("Failed to create network behaviour")
+                IronCoreBehaviour::new(
+                    key,
+                    relay_client,
+                    headless,
+                    discovery_config,
+                    gossip_profile,
+                    &nat_config,
+                )
+                .expect("Failed to create network behaviour")
             })?
             .with_swarm_config(|cfg| {
                 cfg.with_idle_connection_timeout(web_time::Duration::from_secs(600))
@@ -5152,11 +6933,11 @@ pub async fn start_swarm_with_config(
             tracing::warn!("Failed to subscribe to delivery convergence topic: {}", e);
         }
 
-        // Kademlia server mode parity with native.
+        // Kademlia mode parity with native.
         swarm
             .behaviour_mut()
             .kademlia
-            .set_mode(Some(kad::Mode::Server));
+            .set_mode(Some(super::behaviour::kad_mode_to_libp2p(kad_mode)));
 
         // Auto-dial bootstrap nodes for internet connectivity.
         // Self-dial guard: track bootstrap addrs that resolve to our own peer
@@ -5201,9 +6982,11 @@ pub async fn start_swarm_with_config(
         }
 
         let (command_tx, mut command_rx) = mpsc::channel::<SwarmCommand>(256);
+        let relay_server = crate::relay::RelayServer::new();
         let handle = SwarmHandle {
             command_tx: command_tx.clone(),
             core_handle: core_handle.clone(),
+            relay_server: relay_server.clone(),
         };
 
         let mut pending_direct_replies: HashMap<
@@ -5243,8 +7026,16 @@ pub async fn start_swarm_with_config(
         // Keep observational parity where possible on wasm.
         let reflection_service = AddressReflectionService::new();
         let mut connection_tracker = ConnectionTracker::new();
+        let mut rtt_tracker = super::rtt::RttTracker::new();
+        let mut peer_capabilities: HashMap<PeerId, PeerCapabilities> = HashMap::new();
+        // Ed25519 public key (hex) each connected peer proved via Identify.
+        let mut peer_public_keys: HashMap<PeerId, String> = HashMap::new();
+        let mut inbound_limiter = InboundRateLimiter::new(InboundRateLimit::default());
+        let mut topic_limits = TopicSizeLimits::default();
+        let mut app_dial_coalescer = AppDialCoalescer::default();
         let mut address_observer = AddressObserver::new();
         let mut relay_budget: u32 = 200;
+        let mut relay_signature_policy = RelaySignaturePolicy::default();
         let mut relay_count_this_hour: u32 = 0;
         let mut relay_guardrails = RelayAbuseGuardrails::new();
         // This WASM-only event loop uses js_sys::Date::now() (f64 ms since
@@ -5261,6 +7052,8 @@ pub async fn start_swarm_with_config(
         let mut bootstrap_backoff: HashMap<Multiaddr, BootstrapBackoffEntry> = HashMap::new();
         let mut reported_peer_discoveries: HashSet<PeerId> = HashSet::new();
         let mut sync_sessions: HashMap<PeerId, SyncSession> = HashMap::new();
+        // Browsers get no DCUtR or AutoNAT events; the report stays Unknown.
+        let hole_punches = HolePunchTracker::new();
 
         wasm_bindgen_futures::spawn_local(async move {
             loop {
@@ -5284,6 +7077,9 @@ pub async fn start_swarm_with_config(
                                 );
                                 pending_direct_replies.insert(request_id, reply);
                             }
+                            SwarmCommand::SendViaRelay { reply, .. } => {
+                                let _ = reply.send(Err("Relayed send is not supported on wasm32/browser transport".to_string())).await;
+                            }
                             SwarmCommand::RegisterIdentity { peer_id, request, reply } => {
                                 let request_id = swarm
                                     .behaviour_mut()
@@ -5321,6 +7117,10 @@ pub async fn start_swarm_with_config(
                                 let addresses = address_observer.external_addresses().to_vec();
                                 let _ = reply.send(addresses).await;
                             }
+                            SwarmCommand::AdmitAppDial { addr, policy, reply } => {
+                                let admitted = app_dial_coalescer.admit(&multiaddr_to_key(&addr), policy, web_time::Instant::now());
+                                let _ = reply.send(admitted).await;
+                            }
                             SwarmCommand::DiscoveryDial { peer_id, addr } => {
                                 tracing::debug!("Processing off-loop discovery dial to {} for peer {}", addr, peer_id);
                                 let _ = swarm.dial(addr);
@@ -5340,13 +7140,29 @@ pub async fn start_swarm_with_config(
                                 let peers: Vec<PeerId> = swarm.connected_peers().cloned().collect();
                                 let _ = reply.send(peers).await;
                             }
+                            SwarmCommand::GetPeerRtt { peer_id, reply } => {
+                                let _ = reply.send(rtt_tracker.average(&peer_id)).await;
+                            }
+                            SwarmCommand::GetPeerCapabilities { peer_id, reply } => {
+                                let _ = reply.send(peer_capabilities.get(&peer_id).cloned()).await;
+                            }
+                            SwarmCommand::ConfirmPeerPubkey { peer_id, reply } => {
+                                let result = if !swarm.is_connected(&peer_id) {
+                                    Err(format!("Not connected to {}", peer_id))
+                                } else {
+                                    peer_public_keys.get(&peer_id).cloned().ok_or_else(|| {
+                                        format!("{} has not completed Identify yet", peer_id)
+                                    })
+                                };
+                                let _ = reply.send(result).await;
+                            }
                             SwarmCommand::Listen { reply, .. } => {
                                 let _ = reply
                                     .send(Err("listen is unsupported on wasm32/browser transport".to_string()))
                                     .await;
                             }
                             SwarmCommand::AddKadAddress { peer_id, addr } => {
-                                if is_discoverable_multiaddr(&addr) {
+                                if is_discoverable_multiaddr(&addr, advertise_scope) {
                                     swarm.behaviour_mut().kademlia.add_address(&peer_id, addr);
                                 }
                             }
@@ -5384,37 +7200,81 @@ pub async fn start_swarm_with_config(
                                 }
                             }
                             SwarmCommand::PublishTopic { topic, data, reply } => {
-                                let ident_topic = libp2p::gossipsub::IdentTopic::new(topic);
-                                match swarm.behaviour_mut().gossipsub.publish(ident_topic, data) {
-                                    Ok(_) => {
+                                if let Err(reason) = topic_limits.check(&topic, data.len()) {
+                                    tracing::warn!("(wasm) refusing to publish: {}", reason);
+                                    let _ = reply.send(Err(reason)).await;
+                                    continue;
+                                }
+                                match publish_signed_gossip(&mut swarm, &gossip_keypair, &topic, data) {
+                                    Ok(()) => {
                                         let _ = reply.send(Ok(())).await;
                                     }
                                     Err(e) => {
                                         tracing::warn!("Failed to publish topic payload: {}", e);
-                                        let _ = reply.send(Err(e.to_string())).await;
+                                        let _ = reply.send(Err(e)).await;
                                     }
                                 }
                             }
+                            SwarmCommand::PublishTopicCounted { topic, data, reply } => {
+                                if let Err(reason) = topic_limits.check(&topic, data.len()) {
+                                    tracing::warn!("(wasm) refusing to publish: {}", reason);
+                                    let _ = reply.send(Err(reason)).await;
+                                    continue;
+                                }
+                                let result = publish_signed_gossip_counted(&mut swarm, &gossip_keypair, &topic, data);
+                                if let Err(e) = &result {
+                                    tracing::warn!("Failed to publish topic payload: {}", e);
+                                }
+                                let _ = reply.send(result).await;
+                            }
                             SwarmCommand::GetTopics { reply } => {
                                 let topics: Vec<String> = subscribed_topics.iter().cloned().collect();
                                 let _ = reply.send(topics).await;
                             }
+                            SwarmCommand::GetNatStatus { reply } => {
+                                let _ = reply.send(hole_punches.report(&nat_config)).await;
+                            }
                             SwarmCommand::ShareLedger { peer_id, entries } => {
-                                if !ledger_exchanged_peers.contains(&peer_id) {
+                                if dial_learned_peers && !ledger_exchanged_peers.contains(&peer_id) {
                                     let request = LedgerExchangeRequest {
                                         version_tag: 1,
                                         peers: entries,
                                         sender_peer_id: local_peer_id.to_string(),
                                         version: 1,
+                                        want_peers: false,
                                     };
                                     let _ = swarm.behaviour_mut().ledger_exchange.send_request(&peer_id, request);
                                     ledger_exchanged_peers.insert(peer_id);
                                 }
                             }
+                            SwarmCommand::RequestPeers { reply, .. } => {
+                                let _ = reply
+                                    .send(Err("Peer list requests are not supported in the browser swarm".to_string()))
+                                    .await;
+                            }
                             SwarmCommand::GetListeners { reply } => {
                                 // Browser nodes do not expose listen addresses.
                                 let _ = reply.send(Vec::new()).await;
                             }
+                            SwarmCommand::GetRelayReservations { reply } => {
+                                // Browser nodes make no circuit reservations.
+                                let _ = reply.send(Vec::new()).await;
+                            }
+                            SwarmCommand::ReconnectBootstrap { reply } => {
+                                let connected_peers: HashSet<PeerId> =
+                                    swarm.connected_peers().cloned().collect();
+                                let started = bootstrap_redial_targets(
+                                    &bootstrap_addrs_clone,
+                                    local_peer_id,
+                                    &connected_peers,
+                                    &bootstrap_backoff,
+                                    &mut self_dial_logged,
+                                )
+                                .iter()
+                                .filter(|addr| dial_bootstrap_addr(&mut swarm, addr, &mut bootstrap_backoff))
+                                .count();
+                                let _ = reply.send(started).await;
+                            }
                             SwarmCommand::ListEndpoints { peer_id: _, reply } => {
                                 // WASM nodes do not track endpoint addresses locally.
                                 let _ = reply.send(Vec::new()).await;
@@ -5437,15 +7297,48 @@ pub async fn start_swarm_with_config(
                                 relay_budget = budget;
                                 tracing::info!("Relay budget updated: {} msgs/hour", budget);
                             }
+                            SwarmCommand::SetRelaySignaturePolicy { policy } => {
+                                relay_signature_policy = policy;
+                                tracing::info!("Relay signature policy updated: {:?}", policy);
+                            }
+                            SwarmCommand::SetInboundRateLimit { limit } => {
+                                inbound_limiter.set_limit(limit);
+                                tracing::info!(
+                                    "Inbound rate limit updated: {} msgs/sec, burst {}",
+                                    limit.messages_per_sec,
+                                    limit.burst
+                                );
+                            }
+                            SwarmCommand::SetClockSync { enabled } => {
+                                tracing::debug!("WASM: verified clock sync is not available (requested {})", enabled);
+                            }
+                            SwarmCommand::SetTopicSizeLimits { limits } => {
+                                tracing::info!(
+                                    "Topic payload limits updated: {} bytes default, {} topic override(s)",
+                                    limits.default_max,
+                                    limits.per_topic.len()
+                                );
+                                topic_limits = limits;
+                            }
                             SwarmCommand::GetBestRelays { reply, .. } => {
                                 let _ = reply.send(Vec::new()).await;
                             }
                             SwarmCommand::GetBootstrapCandidates { reply } => {
                                 let _ = reply.send(Vec::new()).await;
                             }
+                            SwarmCommand::SetPreferredRelays { .. } => {
+                                // Browser nodes do not rank relay routes.
+                            }
                             SwarmCommand::GetBestPaths { reply, .. } => {
                                 let _ = reply.send(Vec::new()).await;
                             }
+                            SwarmCommand::ProbePeer { addr, reply, .. } => {
+                                let _ = reply.send(ProbeResult::unreachable(
+                                    &addr,
+                                    Duration::ZERO,
+                                    "probing is unsupported on wasm32/browser transport".to_string(),
+                                )).await;
+                            }
                             SwarmCommand::Shutdown => {
                                 tracing::info!("WASM swarm shutting down");
                                 break;
@@ -5475,6 +7368,18 @@ pub async fn start_swarm_with_config(
                                                 continue;
                                             }
 
+                                            if let RateCheck::Limited { first } = inbound_limiter.check(peer, inbound_envelope_sender(&request.envelope_data)) {
+                                                if first {
+                                                    tracing::warn!("Peer {} exceeded the inbound rate limit; rejecting its messages", peer);
+                                                    let _ = event_tx.send(SwarmEvent2::PeerRateLimited { peer }).await;
+                                                }
+                                                let _ = swarm.behaviour_mut().messaging.send_response(
+                                                    channel,
+                                                    Libp2pMessageResponse { accepted: false, error: Some(INBOUND_RATE_LIMITED.to_string()) },
+                                                );
+                                                continue;
+                                            }
+
                                             // Unwrap DriftFrame if present, otherwise use raw data (legacy)
                                             let envelope_payload = match DriftFrame::from_bytes(&request.envelope_data) {
                                                 Ok(frame) => {
@@ -5528,6 +7433,7 @@ pub async fn start_swarm_with_config(
                                                         .await;
                                                         publish_delivery_convergence_marker(
                                                             &mut swarm,
+                                                            &gossip_keypair,
                                                             &marker,
                                                         );
                                                     }
@@ -5690,10 +7596,46 @@ pub async fn start_swarm_with_config(
                                                     error: Some(reason.to_string()),
                                                     message_id: request.message_id.clone(),
                                                 }
+                                            } else if let Some(reason) = relay_signature_rejection(
+                                                &request,
+                                                relay_signature_policy.require_signed_relay,
+                                            ) {
+                                                tracing::warn!(
+                                                    "Relay request from {} (message {}) failed signature check: {}",
+                                                    peer,
+                                                    request.message_id,
+                                                    reason
+                                                );
+                                                if reason == RELAY_BAD_SIGNATURE {
+                                                    let _ = event_tx.send(SwarmEvent2::AbuseSignalDetected {
+                                                        peer_id: peer,
+                                                        signal: "InvalidFormat".to_string(),
+                                                    }).await;
+                                                }
+                                                RelayResponse {
+                                                    accepted: false,
+                                                    error: Some(reason.to_string()),
+                                                    message_id: request.message_id.clone(),
+                                                }
                                             } else if relay_budget > 0 && relay_count_this_hour >= relay_budget {
                                                 RelayResponse {
                                                     accepted: false,
-                                                    error: Some("relay_budget_exhausted".to_string()),
+                                                    error: Some(RELAY_BUDGET_EXHAUSTED.to_string()),
+                                                    message_id: request.message_id.clone(),
+                                                }
+                                            } else if let Some(reason) = core_handle
+                                                .as_ref()
+                                                .and_then(|w| w.upgrade())
+                                                .and_then(|core| core.relay_policy_refusal())
+                                            {
+                                                let _ = event_tx.send(SwarmEvent2::RelayRefused {
+                                                    peer_id: peer,
+                                                    message_id: request.message_id.clone(),
+                                                    reason,
+                                                }).await;
+                                                RelayResponse {
+                                                    accepted: false,
+                                                    error: Some(RELAY_DEVICE_POLICY.to_string()),
                                                     message_id: request.message_id.clone(),
                                                 }
                                             } else if pending_custody_dispatches.len()
@@ -5819,12 +7761,22 @@ pub async fn start_swarm_with_config(
                                         request_response::Message::Response { request_id, response } => {
                                             if let Some(message_id) = pending_relay_requests.remove(&request_id) {
                                                 if let Some(pending) = pending_messages.remove(&message_id) {
+                                                    let _ = event_tx.send(SwarmEvent2::DeliveryAttempted {
+                                                        message_id: delivery_message_id(&message_id, &pending),
+                                                        path: format!("relay:{}", peer),
+                                                        error: (!response.accepted).then(|| {
+                                                            response.error.clone().unwrap_or_else(|| "relay rejected".to_string())
+                                                        }),
+                                                    }).await;
                                                     if response.accepted {
                                                         let _ = pending.reply_tx.send(Ok(())).await;
                                                     } else {
                                                         let error = response
                                                             .error
                                                             .unwrap_or_else(|| "relay rejected".to_string());
+                                                        if error == RELAY_BUDGET_EXHAUSTED {
+                                                            let _ = event_tx.send(SwarmEvent2::RelayCongested { relay_peer: peer }).await;
+                                                        }
                                                         if is_terminal_identity_rejection(&error) {
                                                             let _ = pending.reply_tx.send(Err(error)).await;
                                                         } else {
@@ -5842,10 +7794,13 @@ pub async fn start_swarm_with_config(
                                 if let request_response::Event::Message { peer, message, .. } = ev {
                                     match message {
                                         request_response::Message::Request { request, channel, .. } => {
-                                            let _ = event_tx.send(SwarmEvent2::LedgerReceived {
-                                                from_peer: peer,
-                                                entries: request.peers.clone(),
-                                            }).await;
+                                            // Paranoid discovery learns nothing from ledger exchange.
+                                            if dial_learned_peers {
+                                                let _ = event_tx.send(SwarmEvent2::LedgerReceived {
+                                                    from_peer: peer,
+                                                    entries: request.peers.clone(),
+                                                }).await;
+                                            }
                                             let _ = swarm.behaviour_mut().ledger_exchange.send_response(
                                                 channel,
                                                 LedgerExchangeResponse {
@@ -5858,7 +7813,7 @@ pub async fn start_swarm_with_config(
                                             ledger_exchanged_peers.insert(peer);
                                         }
                                         request_response::Message::Response { response, .. } => {
-                                            if !response.peers.is_empty() {
+                                            if dial_learned_peers && !response.peers.is_empty() {
                                                 let _ = event_tx.send(SwarmEvent2::LedgerReceived {
                                                     from_peer: peer,
                                                     entries: response.peers,
@@ -5886,9 +7841,24 @@ pub async fn start_swarm_with_config(
                             SwarmEvent::Behaviour(super::behaviour::IronCoreBehaviourEvent::Gossipsub(
                                 gossipsub::Event::Message { propagation_source, message, .. }
                             )) => {
+                                let Some(signed) = SignedGossip::open(&message.data, message.topic.as_str()) else {
+                                    tracing::warn!(
+                                        "(wasm) dropping unsigned or tampered gossipsub frame from {}",
+                                        propagation_source
+                                    );
+                                    continue;
+                                };
+                                if let Err(reason) = topic_limits.check(message.topic.as_str(), signed.payload.len()) {
+                                    tracing::warn!(
+                                        "(wasm) dropping gossipsub frame from {}: {}",
+                                        propagation_source,
+                                        reason
+                                    );
+                                    continue;
+                                }
                                 if message.topic.as_str() == DELIVERY_CONVERGENCE_TOPIC {
                                     if let Some(marker) =
-                                        decode_delivery_convergence_marker(&message.data)
+                                        decode_delivery_convergence_marker(&signed.payload)
                                     {
                                         if let Err(reason) = should_apply_delivery_convergence_marker(
                                             &marker,
@@ -5918,18 +7888,36 @@ pub async fn start_swarm_with_config(
                                             if propagation_source != local_peer_id {
                                                 publish_delivery_convergence_marker(
                                                     &mut swarm,
+                                                    &gossip_keypair,
                                                     &marker,
                                                 );
                                             }
                                         }
                                     }
+                                } else if message.topic.as_str() == crate::TOPIC_LOBBY
+                                    && super::presence::is_presence_payload(&signed.payload)
+                                {
+                                    if let Some(core) = core_handle.as_ref().and_then(|w| w.upgrade()) {
+                                        if let Err(e) = core.receive_presence_beacon(signed.payload) {
+                                            tracing::debug!(
+                                                "Ignoring presence beacon from {}: {:?}",
+                                                propagation_source,
+                                                e
+                                            );
+                                        }
+                                    }
+                                } else if is_broadcast_payload(&signed.payload) {
+                                    let _ = event_tx.send(SwarmEvent2::BroadcastReceived {
+                                        topic: message.topic.to_string(),
+                                        frame: message.data,
+                                    }).await;
                                 }
                             }
                             SwarmEvent::Behaviour(super::behaviour::IronCoreBehaviourEvent::Identify(
                                 identify::Event::Received { peer_id, info, .. }
                             )) => {
                                 for addr in &info.listen_addrs {
-                                    if is_discoverable_multiaddr(addr) {
+                                    if is_discoverable_multiaddr(addr, advertise_scope) {
                                         swarm.behaviour_mut().kademlia.add_address(&peer_id, addr.clone());
                                     }
                                 }
@@ -5938,8 +7926,15 @@ pub async fn start_swarm_with_config(
                                 {
                                     address_observer.record_observation(peer_id, observed_addr);
                                 }
+                                peer_capabilities.insert(
+                                    peer_id,
+                                    PeerCapabilities::from_agent_version(&info.agent_version),
+                                );
 
                                 let public_key_hex = info.public_key.clone().try_into_ed25519().map(|pk| hex::encode(pk.to_bytes())).ok();
+                                if let Some(pk) = &public_key_hex {
+                                    peer_public_keys.insert(peer_id, pk.clone());
+                                }
                                 let _ = event_tx.send(SwarmEvent2::PeerIdentified {
                                     peer_id,
                                     public_key: public_key_hex,
@@ -5996,8 +7991,13 @@ pub async fn start_swarm_with_config(
                                     }
                                 }
                             }
-                            SwarmEvent::ConnectionClosed { peer_id, .. } => {
+                            SwarmEvent::ConnectionClosed { peer_id, num_established, .. } => {
                                 tracing::info!("[ERROR] Disconnected from {} (WASM)", peer_id);
+                                if num_established == 0 {
+                                    rtt_tracker.remove(&peer_id);
+                                    peer_capabilities.remove(&peer_id);
+                                    peer_public_keys.remove(&peer_id);
+                                }
                                 connection_tracker.remove_connection(&peer_id);
                                 ledger_exchanged_peers.remove(&peer_id);
                                 let stale_dispatches: Vec<libp2p::request_response::OutboundRequestId> =
@@ -6145,6 +8145,24 @@ pub async fn start_swarm_with_config(
                                         error: format!("listener closed for {:?}: {:?}", addresses, reason),
                                     }).await;
                                 }
+                                for address in addresses {
+                                    swarm.remove_external_address(&address);
+                                    bound_addresses.retain(|a| a != &address);
+                                    let _ = event_tx.send(SwarmEvent2::ListenAddrExpired(address)).await;
+                                }
+                            }
+                            SwarmEvent::NewListenAddr { address, .. } => {
+                                advertise_listen_addr(&mut swarm, &address, advertise_scope);
+                            }
+                            SwarmEvent::ExpiredListenAddr { address, .. } => {
+                                swarm.remove_external_address(&address);
+                                bound_addresses.retain(|a| a != &address);
+                                let _ = event_tx.send(SwarmEvent2::ListenAddrExpired(address)).await;
+                            }
+                            SwarmEvent::Behaviour(super::behaviour::IronCoreBehaviourEvent::Ping(event)) => {
+                                if let Ok(rtt) = event.result {
+                                    rtt_tracker.record(event.peer, rtt);
+                                }
                             }
                             _ => {}
                         }
@@ -6170,64 +8188,14 @@ pub async fn start_swarm_with_config(
                 if js_sys::Date::now() - last_bootstrap_redial >= 60_000.0 {
                     let connected_peers: HashSet<PeerId> =
                         swarm.connected_peers().cloned().collect();
-                    for addr in &bootstrap_addrs_clone {
-                        // Self-dial guard: skip bootstrap addrs that resolve to
-                        // our own peer ID (e.g. portproxy loopback) to avoid
-                        // the "tried to dial local peer id" warning every 60s.
-                        let is_self = addr.iter().any(|proto| {
-                            if let libp2p::multiaddr::Protocol::P2p(pid) = proto {
-                                pid == local_peer_id
-                            } else {
-                                false
-                            }
-                        });
-                        if is_self {
-                            if !self_dial_logged.contains(addr) {
-                                tracing::info!(
-                                    "  ⊘ Skipping self-dial bootstrap addr (matches local peer): {}",
-                                    addr
-                                );
-                                self_dial_logged.insert(addr.clone());
-                            }
-                            continue;
-                        }
-
-                        // Exponential backoff gate: skip this addr if it is still
-                        // within its backoff window after a recent failure.
-                        if !bootstrap_backoff
-                            .get(addr)
-                            .map_or(true, |e| e.is_eligible())
-                        {
-                            continue;
-                        }
-
-                        let already_connected = addr.iter().any(|proto| {
-                            if let libp2p::multiaddr::Protocol::P2p(pid) = proto {
-                                connected_peers.contains(&pid)
-                            } else {
-                                false
-                            }
-                        });
-
-                        if !already_connected {
-                            let stripped_addr: Multiaddr = addr
-                                .iter()
-                                .filter(|p| !matches!(p, libp2p::multiaddr::Protocol::P2p(_)))
-                                .collect();
-                            if let Err(e) = swarm.dial(stripped_addr.clone()) {
-                                // Dial was rejected internally (e.g. already dialing).
-                                // Treat as a failure and apply backoff to avoid retry spam.
-                                tracing::trace!(
-                                    "Bootstrap re-dial {} skipped: {}",
-                                    stripped_addr,
-                                    e
-                                );
-                                bootstrap_backoff
-                                    .entry(addr.clone())
-                                    .or_insert_with(BootstrapBackoffEntry::new)
-                                    .on_failure();
-                            }
-                        }
+                    for addr in bootstrap_redial_targets(
+                        &bootstrap_addrs_clone,
+                        local_peer_id,
+                        &connected_peers,
+                        &bootstrap_backoff,
+                        &mut self_dial_logged,
+                    ) {
+                        dial_bootstrap_addr(&mut swarm, &addr, &mut bootstrap_backoff);
                     }
                     last_bootstrap_redial = js_sys::Date::now();
                 }
@@ -6248,17 +8216,169 @@ use libp2p::{gossipsub, request_response};
 #[cfg(test)]
 mod tests {
     use super::{
-        extract_ed25519_public_key_from_peer_id, should_apply_delivery_convergence_marker,
-        validate_delivery_convergence_marker_shape, verify_registration_message,
-        DeliveryConvergenceMarker, PendingCustodyDispatch, PendingMessage, RelayAbuseGuardrails,
+        bootstrap_redial_targets, extract_ed25519_public_key_from_peer_id,
+        is_discoverable_multiaddr, relay_reservations, shared_entries_to_relay_peers,
+        should_apply_delivery_convergence_marker, validate_delivery_convergence_marker_shape,
+        verify_registration_message, BootstrapBackoffEntry, DeliveryConvergenceMarker,
+        PendingCustodyDispatch, PendingMessage, RelayAbuseGuardrails, SharedPeerEntry,
         RELAY_DUPLICATE_WINDOW_MS, RELAY_PEER_BUCKET_BURST_CAPACITY,
         RELAY_PEER_BUCKET_REFILL_PER_SEC,
     };
     use crate::identity::IdentityKeys;
     use crate::store::relay_custody::RelayCustodyStore;
-    use crate::transport::RegistrationMessage;
+    use crate::transport::{AdvertiseScope, RegistrationMessage};
     use libp2p::{Multiaddr, PeerId};
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
+
+    #[test]
+    fn relay_reservations_report_address_and_acceptance() {
+        let requested = PeerId::random();
+        let accepted = PeerId::random();
+        let elsewhere = PeerId::random();
+        let requested_addr: Multiaddr = "/ip4/203.0.113.7/tcp/4001".parse().unwrap();
+        let accepted_addr: Multiaddr = "/ip4/198.51.100.2/tcp/4001".parse().unwrap();
+        let relay_peer_addrs = HashMap::from([
+            (requested, vec![requested_addr.clone()]),
+            (accepted, vec![accepted_addr.clone()]),
+        ]);
+        let accepted_set = HashSet::from([accepted, elsewhere]);
+
+        let reservations = relay_reservations(&relay_peer_addrs, &accepted_set);
+        assert_eq!(reservations.len(), 3);
+        let find = |peer: PeerId| {
+            reservations
+                .iter()
+                .find(|r| r.relay_peer_id == peer)
+                .unwrap()
+        };
+        assert_eq!(find(requested).relay_addr, Some(requested_addr));
+        assert!(!find(requested).accepted);
+        assert_eq!(find(accepted).relay_addr, Some(accepted_addr));
+        assert!(find(accepted).accepted);
+        assert_eq!(find(elsewhere).relay_addr, None);
+        assert!(find(elsewhere).accepted);
+        let ids: Vec<String> = reservations
+            .iter()
+            .map(|r| r.relay_peer_id.to_string())
+            .collect();
+        let mut sorted = ids.clone();
+        sorted.sort();
+        assert_eq!(ids, sorted);
+    }
+
+    #[test]
+    fn reconnect_targets_skip_self_connected_and_backed_off_bootstraps() {
+        let local = PeerId::random();
+        let connected = PeerId::random();
+        let failing = PeerId::random();
+        let idle = PeerId::random();
+        let addr = |peer: PeerId, port: u16| -> Multiaddr {
+            format!("/ip4/203.0.113.7/tcp/{}/p2p/{}", port, peer)
+                .parse()
+                .unwrap()
+        };
+        let bootstrap = vec![
+            addr(local, 4001),
+            addr(connected, 4002),
+            addr(failing, 4003),
+            addr(idle, 4004),
+            "/ip4/198.51.100.2/tcp/4001".parse().unwrap(),
+        ];
+        let mut failed = BootstrapBackoffEntry::new();
+        failed.on_failure();
+        let backoff = HashMap::from([(bootstrap[2].clone(), failed)]);
+        let mut self_dial_logged = HashSet::new();
+
+        let targets = bootstrap_redial_targets(
+            &bootstrap,
+            local,
+            &HashSet::from([connected]),
+            &backoff,
+            &mut self_dial_logged,
+        );
+        assert_eq!(targets, vec![bootstrap[3].clone(), bootstrap[4].clone()]);
+        assert!(self_dial_logged.contains(&bootstrap[0]));
+
+        // Once nothing is connected or backing off, everything but self is due.
+        let targets = bootstrap_redial_targets(
+            &bootstrap,
+            local,
+            &HashSet::new(),
+            &HashMap::new(),
+            &mut self_dial_logged,
+        );
+        assert_eq!(targets.len(), 4);
+    }
+
+    #[test]
+    fn discoverable_multiaddr_follows_advertise_scope() {
+        let loopback: Multiaddr = "/ip4/127.0.0.1/tcp/4001".parse().unwrap();
+        let private: Multiaddr = "/ip4/192.168.1.20/tcp/4001".parse().unwrap();
+        let cgnat: Multiaddr = "/ip4/100.72.3.4/tcp/4001".parse().unwrap();
+        let ula: Multiaddr = "/ip6/fd12:3456::1/tcp/4001".parse().unwrap();
+        let link_local: Multiaddr = "/ip6/fe80::1c2a:3bff:fe4d:5e6f/tcp/4001".parse().unwrap();
+        let public: Multiaddr = "/ip4/203.0.113.7/tcp/4001".parse().unwrap();
+        let circuit: Multiaddr = format!(
+            "/ip4/203.0.113.7/tcp/4001/p2p/{}/p2p-circuit",
+            PeerId::random()
+        )
+        .parse()
+        .unwrap();
+
+        let permits = |scope| {
+            [
+                &loopback,
+                &link_local,
+                &private,
+                &cgnat,
+                &ula,
+                &public,
+                &circuit,
+            ]
+            .map(|addr| is_discoverable_multiaddr(addr, scope))
+        };
+        assert_eq!(
+            permits(AdvertiseScope::LanAndWan),
+            [false, false, true, true, true, true, true]
+        );
+        assert_eq!(
+            permits(AdvertiseScope::WanOnly),
+            [false, false, false, false, false, true, true]
+        );
+        assert_eq!(
+            permits(AdvertiseScope::LanOnly),
+            [false, false, true, true, true, false, false]
+        );
+    }
+
+    #[test]
+    fn pulled_ledger_entries_group_into_relay_peers_by_peer_id() {
+        let alice = PeerId::random().to_string();
+        let bob = PeerId::random().to_string();
+        let entry = |addr: &str, peer: Option<&String>, last_seen: u64| SharedPeerEntry {
+            multiaddr: addr.to_string(),
+            last_peer_id: peer.cloned(),
+            last_seen,
+            known_topics: Vec::new(),
+        };
+        let peers = shared_entries_to_relay_peers(&[
+            entry("/ip4/10.0.0.1/tcp/4001", Some(&alice), 100),
+            entry("/ip4/10.0.0.2/tcp/4001", Some(&bob), 50),
+            entry("/ip4/10.0.0.1/udp/4001/quic-v1", Some(&alice), 200),
+            entry("/ip4/10.0.0.1/tcp/4001", Some(&alice), 10),
+            entry("/ip4/10.0.0.9/tcp/4001", None, 300),
+        ]);
+
+        assert_eq!(peers.len(), 2);
+        assert_eq!(peers[0].peer_id, alice);
+        assert_eq!(
+            peers[0].addresses,
+            vec!["/ip4/10.0.0.1/tcp/4001", "/ip4/10.0.0.1/udp/4001/quic-v1"]
+        );
+        assert_eq!(peers[0].last_seen, 200);
+        assert_eq!(peers[1].peer_id, bob);
+        assert_eq!(peers[1].last_seen, 50);
+    }
 
     #[test]
     fn abusive_peer_burst_is_rate_limited_but_other_peer_still_passes() {
@@ -6507,6 +8627,68 @@ mod tests {
             .to_string()
             .starts_with("/ip4/192.168.0.230/tcp/9101"));
     }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn relay_signature_check_rejects_forged_and_forwards_legitimate() {
+        use super::{RelayRequest, RELAY_BAD_SIGNATURE, RELAY_UNSIGNED};
+
+        let sender = IdentityKeys::generate();
+        let recipient = IdentityKeys::generate();
+        let envelope = crate::crypto::encrypt_message(
+            &sender.signing_key,
+            &recipient.signing_key.verifying_key().to_bytes(),
+            b"via relay",
+        )
+        .unwrap();
+        let envelope_data = crate::message::encode_envelope(&envelope).unwrap();
+
+        // The key recovered from the libp2p identity signs as the identity.
+        let signer = super::relay_signing_key(&sender.to_libp2p_keypair().unwrap()).unwrap();
+        assert_eq!(signer.to_bytes(), sender.signing_key.to_bytes());
+
+        let request = |signature: Option<Vec<u8>>| RelayRequest {
+            destination_peer: PeerId::random().to_bytes(),
+            envelope_data: super::wrap_in_drift_frame(&envelope_data),
+            message_id: "relay-sig".to_string(),
+            recipient_identity_id: None,
+            intended_device_id: None,
+            envelope_signature: signature,
+        };
+
+        let legitimate = request(crate::crypto::sign_relay_envelope(&envelope_data, &signer).ok());
+        assert_eq!(super::relay_signature_rejection(&legitimate, true), None);
+
+        let attacker = IdentityKeys::generate();
+        let forged_signature = {
+            use ed25519_dalek::Signer;
+            attacker
+                .signing_key
+                .sign(&envelope_data)
+                .to_bytes()
+                .to_vec()
+        };
+        let forged = request(Some(forged_signature));
+        assert_eq!(
+            super::relay_signature_rejection(&forged, false),
+            Some(RELAY_BAD_SIGNATURE)
+        );
+
+        let unsigned = request(None);
+        assert_eq!(super::relay_signature_rejection(&unsigned, false), None);
+        assert_eq!(
+            super::relay_signature_rejection(&unsigned, true),
+            Some(RELAY_UNSIGNED)
+        );
+
+        // The relay-side requirement comes from the mesh settings.
+        let settings = crate::settings::MeshSettings {
+            require_signed_relay: true,
+            ..Default::default()
+        };
+        let policy = super::RelaySignaturePolicy::from_mesh_settings(&settings);
+        assert!(policy.require_signed_relay && policy.sign_outbound);
+    }
 }
 
 /// Extract the TCP port from a Multiaddr.
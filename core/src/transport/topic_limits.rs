@@ -0,0 +1,91 @@
+// Per-topic gossipsub payload limits
+//
+// Gossipsub only bounds frames by its transmit size, far above anything the
+// app publishes, so a peer could push oversized payloads at every subscriber.
+// Each topic has a payload ceiling, applied to the payload inside the
+// `SignedGossip` frame: larger payloads are refused at publish and dropped on
+// receipt before they are acted on.
+
+use std::collections::HashMap;
+
+/// Default payload ceiling for any topic without its own limit.
+pub const DEFAULT_MAX_TOPIC_PAYLOAD: usize = crate::drift::FRAME_MAX_PAYLOAD;
+
+/// Payload ceilings for gossipsub topics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopicSizeLimits {
+    /// Ceiling for topics not listed in `per_topic`.
+    pub default_max: usize,
+    /// Topic name → ceiling, overriding `default_max`.
+    pub per_topic: HashMap<String, usize>,
+}
+
+impl Default for TopicSizeLimits {
+    fn default() -> Self {
+        Self {
+            default_max: DEFAULT_MAX_TOPIC_PAYLOAD,
+            per_topic: HashMap::new(),
+        }
+    }
+}
+
+impl TopicSizeLimits {
+    pub fn from_mesh_settings(settings: &crate::settings::MeshSettings) -> Self {
+        Self {
+            default_max: settings.max_topic_payload_bytes as usize,
+            per_topic: settings
+                .topic_payload_limits
+                .iter()
+                .map(|(topic, max)| (topic.clone(), *max as usize))
+                .collect(),
+        }
+    }
+
+    pub fn limit_for(&self, topic: &str) -> usize {
+        self.per_topic
+            .get(topic)
+            .copied()
+            .unwrap_or(self.default_max)
+    }
+
+    /// Why a `len`-byte payload may not travel on `topic`, if it may not.
+    pub fn check(&self, topic: &str, len: usize) -> Result<(), String> {
+        let max = self.limit_for(topic);
+        if len > max {
+            return Err(format!(
+                "payload of {} bytes exceeds the {} byte limit for topic {}",
+                len, max, topic
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oversized_payload_rejected_and_in_bounds_accepted() {
+        let mut limits = TopicSizeLimits::default();
+        limits.per_topic.insert("sc-lobby".to_string(), 1024);
+
+        assert!(limits.check("sc-lobby", 1024).is_ok());
+        let reason = limits.check("sc-lobby", 1025).unwrap_err();
+        assert!(reason.contains("1024 byte limit"));
+
+        // Other topics fall back to the default ceiling.
+        assert!(limits.check("sc-mesh", 4096).is_ok());
+        assert!(limits
+            .check("sc-mesh", DEFAULT_MAX_TOPIC_PAYLOAD + 1)
+            .is_err());
+
+        let settings = crate::settings::MeshSettings {
+            max_topic_payload_bytes: 512,
+            ..Default::default()
+        };
+        let from_settings = TopicSizeLimits::from_mesh_settings(&settings);
+        assert!(from_settings.check("sc-mesh", 512).is_ok());
+        assert!(from_settings.check("sc-mesh", 513).is_err());
+    }
+}
@@ -62,6 +62,8 @@ async fn test_all_six_phases_integrated() {
         false,
         None, // discovery_config
         scmessenger_core::transport::default_routing_engine_handle(),
+        None,
+        None,
     )
     .await
     .expect("Failed to start Alice");
@@ -99,6 +101,8 @@ async fn test_all_six_phases_integrated() {
         false,
         None, // discovery_config
         scmessenger_core::transport::default_routing_engine_handle(),
+        None,
+        None,
     )
     .await
     .expect("Failed to start Bob");
@@ -291,6 +295,8 @@ async fn test_message_retry_on_failure() {
         false,
         None, // discovery_config
         scmessenger_core::transport::default_routing_engine_handle(),
+        None,
+        None,
     )
     .await
     .expect("Failed to start Alice");
@@ -379,6 +385,8 @@ async fn test_relay_protocol() {
         false,
         None, // discovery_config
         scmessenger_core::transport::default_routing_engine_handle(),
+        None,
+        None,
     )
     .await
     .expect("Failed to start Alice");
@@ -395,6 +403,8 @@ async fn test_relay_protocol() {
         false,
         None, // discovery_config
         scmessenger_core::transport::default_routing_engine_handle(),
+        None,
+        None,
     )
     .await
     .expect("Failed to start Bob");
@@ -411,6 +421,8 @@ async fn test_relay_protocol() {
         false,
         None, // discovery_config
         scmessenger_core::transport::default_routing_engine_handle(),
+        None,
+        None,
     )
     .await
     .expect("Failed to start Charlie");
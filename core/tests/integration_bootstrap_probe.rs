@@ -0,0 +1,78 @@
+// Integration test for SwarmCommand::ProbePeer (bootstrap health check)
+//
+// A probe against a live node must report it reachable with its agent
+// version; a probe against a closed port must resolve as unreachable within
+// the timeout instead of hanging.
+//
+// Test is #[ignore] by default (real networking) - run with:
+//   cargo test -p scmessenger-core --test integration_bootstrap_probe -- --include-ignored
+
+use libp2p::identity::Keypair;
+use libp2p::Multiaddr;
+use scmessenger_core::transport::swarm::{start_swarm, SwarmEvent2, SwarmHandle};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+async fn spawn_node() -> (SwarmHandle, mpsc::Receiver<SwarmEvent2>) {
+    let (event_tx, event_rx) = mpsc::channel(256);
+    let handle = start_swarm(
+        Keypair::generate_ed25519(),
+        None,
+        event_tx,
+        None,
+        true,
+        None,
+        scmessenger_core::transport::default_routing_engine_handle(),
+    )
+    .await
+    .expect("Failed to start swarm");
+    (handle, event_rx)
+}
+
+#[tokio::test]
+#[ignore = "requires real networking; run with --include-ignored"]
+async fn test_probe_reports_live_and_dead_peers() {
+    let (target, mut target_rx) = spawn_node().await;
+
+    let mut target_addr: Option<Multiaddr> = None;
+    tokio::time::timeout(Duration::from_secs(3), async {
+        while let Some(event) = target_rx.recv().await {
+            if let SwarmEvent2::ListeningOn(addr) = event {
+                let s = addr.to_string();
+                if s.contains("/127.0.0.1/") && s.contains("/tcp/") && !s.contains("/ws") {
+                    target_addr = Some(addr);
+                    break;
+                }
+            }
+        }
+    })
+    .await
+    .ok();
+    let target_addr = target_addr.expect("target node should listen on loopback TCP");
+    tokio::spawn(async move { while target_rx.recv().await.is_some() {} });
+
+    let (prober, mut prober_rx) = spawn_node().await;
+    tokio::spawn(async move { while prober_rx.recv().await.is_some() {} });
+
+    let live = prober
+        .probe_peer(target_addr.clone(), Duration::from_secs(5))
+        .await
+        .expect("probe should reply");
+    assert!(live.reachable, "live node should be reachable: {:?}", live);
+    assert!(live.agent_version.is_some());
+    assert_eq!(live.addr, target_addr.to_string());
+
+    let dead_addr: Multiaddr = "/ip4/127.0.0.1/tcp/1".parse().unwrap();
+    let dead = tokio::time::timeout(
+        Duration::from_secs(5),
+        prober.probe_peer(dead_addr, Duration::from_secs(2)),
+    )
+    .await
+    .expect("probe must not hang past its timeout")
+    .expect("probe should reply");
+    assert!(!dead.reachable);
+    assert!(dead.error.is_some());
+
+    let _ = prober.shutdown().await;
+    let _ = target.shutdown().await;
+}
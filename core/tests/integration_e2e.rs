@@ -114,6 +114,7 @@ fn test_e2e_message_flow_two_peers() {
             .unwrap()
             .as_secs(),
         sender_public_key_hex: None,
+        message_type: Some(received_message.message_type.clone()),
     };
 
     assert!(bob_inbox.receive(received_msg), "Failed to receive message");
@@ -136,6 +137,7 @@ fn test_e2e_message_flow_two_peers() {
             .unwrap()
             .as_secs(),
         sender_public_key_hex: None,
+        message_type: Some(received_message.message_type.clone()),
     };
 
     assert!(!bob_inbox.receive(duplicate_msg), "Deduplication failed");
@@ -263,6 +265,7 @@ fn test_e2e_persistent_message_flow() {
             payload: received_message.payload.clone(),
             received_at: 1,
             sender_public_key_hex: None,
+            message_type: Some(received_message.message_type.clone()),
         };
 
         inbox.receive(received_msg);
@@ -0,0 +1,78 @@
+//! Integration test: message delivery over the in-process loopback transport.
+//!
+//! Two `IronCore` nodes exchange a real sealed envelope through
+//! `transport::in_process`, covering prepare → outbox → transport send →
+//! receive → decrypt without any sockets or libp2p swarm.
+//!
+//! Run with:
+//!   cargo test -p scmessenger-core --features test-transport --test integration_in_process_transport
+
+use scmessenger_core::transport::abstraction::{TransportEvent, TransportType};
+use scmessenger_core::transport::in_process::InProcessNetwork;
+use scmessenger_core::{IronCore, MessageType};
+
+fn make_node() -> IronCore {
+    let node = IronCore::new();
+    node.grant_consent();
+    node.initialize_identity()
+        .expect("identity initialization must succeed");
+    node
+}
+
+fn pubkey(node: &IronCore) -> String {
+    node.get_identity_info()
+        .public_key_hex
+        .expect("node must be initialized before calling pubkey()")
+}
+
+fn pubkey_bytes(node: &IronCore) -> [u8; 32] {
+    hex::decode(pubkey(node))
+        .expect("public key is hex")
+        .try_into()
+        .expect("public key is 32 bytes")
+}
+
+#[test]
+fn test_message_delivered_end_to_end_over_in_process_transport() {
+    let alice = make_node();
+    let bob = make_node();
+    let (alice_link, bob_link) = InProcessNetwork::pair(pubkey_bytes(&alice), pubkey_bytes(&bob));
+    assert!(matches!(
+        bob_link.try_next_event(),
+        Some(TransportEvent::ConnectionEstablished { .. })
+    ));
+
+    let prepared = alice
+        .prepare_message(
+            pubkey(&bob),
+            "over the loopback".to_string(),
+            MessageType::Text,
+            None,
+        )
+        .expect("prepare_message");
+    assert_eq!(alice.outbox_count(), 1);
+
+    alice_link
+        .send(pubkey_bytes(&bob), prepared.envelope_data)
+        .expect("send over in-process transport");
+    assert!(alice.mark_message_sent(prepared.message_id.clone()));
+    assert_eq!(alice.outbox_count(), 0);
+
+    let (from, data) = match bob_link.try_next_event() {
+        Some(TransportEvent::DataReceived {
+            peer_id,
+            transport,
+            data,
+        }) => {
+            assert_eq!(transport, TransportType::Local);
+            (peer_id, data)
+        }
+        other => panic!("expected DataReceived, got {:?}", other),
+    };
+    assert_eq!(from, pubkey_bytes(&alice));
+
+    let message = bob.receive_message(data).expect("bob decrypts");
+    assert_eq!(message.id, prepared.message_id);
+    assert_eq!(message.text_content().as_deref(), Some("over the loopback"));
+    assert!(bob_link.try_next_event().is_none());
+}
@@ -0,0 +1,109 @@
+use libp2p::{Multiaddr, PeerId};
+use scmessenger_core::identity::IdentityKeys;
+use scmessenger_core::transport::{start_swarm, SwarmEvent};
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+async fn wait_for_tcp_listener(
+    rx: &mut mpsc::Receiver<SwarmEvent>,
+    max_wait: Duration,
+) -> Multiaddr {
+    timeout(max_wait, async {
+        loop {
+            match rx.recv().await {
+                Some(SwarmEvent::ListeningOn(addr)) if addr.to_string().contains("/tcp/") => {
+                    return addr;
+                }
+                Some(_) => {}
+                None => panic!("event channel closed while waiting for listener"),
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for listener")
+}
+
+async fn wait_for_identified(
+    rx: &mut mpsc::Receiver<SwarmEvent>,
+    expected_peer: PeerId,
+    max_wait: Duration,
+) {
+    timeout(max_wait, async {
+        loop {
+            match rx.recv().await {
+                Some(SwarmEvent::PeerIdentified { peer_id, .. }) if peer_id == expected_peer => {
+                    return;
+                }
+                Some(_) => {}
+                None => panic!("event channel closed while waiting for identify"),
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for identify");
+}
+
+#[tokio::test]
+#[ignore = "requires real networking (TCP bind); run with --include-ignored"]
+async fn confirm_peer_pubkey_returns_connected_peers_identity_key() {
+    let receiver_identity = IdentityKeys::generate();
+    let receiver_keypair = receiver_identity.to_libp2p_keypair().unwrap();
+    let receiver_peer_id = receiver_keypair.public().to_peer_id();
+    let receiver_pk_hex = hex::encode(receiver_identity.signing_key.verifying_key().to_bytes());
+
+    let sender_keypair = IdentityKeys::generate().to_libp2p_keypair().unwrap();
+
+    let (receiver_tx, mut receiver_rx) = mpsc::channel(256);
+    let receiver_handle = start_swarm(
+        receiver_keypair,
+        Some("/ip4/127.0.0.1/tcp/0".parse().unwrap()),
+        receiver_tx,
+        None,
+        false,
+        None, // discovery_config
+        scmessenger_core::transport::default_routing_engine_handle(),
+    )
+    .await
+    .expect("failed to start receiver swarm");
+    let receiver_addr = wait_for_tcp_listener(&mut receiver_rx, Duration::from_secs(10)).await;
+
+    let (sender_tx, mut sender_rx) = mpsc::channel(256);
+    let sender_handle = start_swarm(
+        sender_keypair,
+        Some("/ip4/127.0.0.1/tcp/0".parse().unwrap()),
+        sender_tx,
+        None,
+        false,
+        None, // discovery_config
+        scmessenger_core::transport::default_routing_engine_handle(),
+    )
+    .await
+    .expect("failed to start sender swarm");
+    let _sender_addr = wait_for_tcp_listener(&mut sender_rx, Duration::from_secs(10)).await;
+
+    // Not connected yet: nothing to confirm against.
+    assert!(sender_handle
+        .confirm_peer_pubkey(receiver_peer_id)
+        .await
+        .is_err());
+
+    sender_handle
+        .dial(receiver_addr)
+        .await
+        .expect("sender failed to dial receiver");
+    wait_for_identified(&mut sender_rx, receiver_peer_id, Duration::from_secs(15)).await;
+
+    let confirmed = sender_handle
+        .confirm_peer_pubkey(receiver_peer_id)
+        .await
+        .expect("identified peer should have a confirmed key");
+    assert_eq!(confirmed, receiver_pk_hex);
+
+    assert!(sender_handle
+        .confirm_peer_pubkey(PeerId::random())
+        .await
+        .is_err());
+
+    sender_handle.shutdown().await.ok();
+    receiver_handle.shutdown().await.ok();
+}
@@ -168,3 +168,113 @@ async fn offline_recipient_receives_after_reconnect_without_sender_resend() {
     relay_handle.shutdown().await.ok();
     recipient_handle.shutdown().await.ok();
 }
+
+#[tokio::test]
+#[ignore = "requires libp2p socket permissions; run with --include-ignored"]
+async fn queued_message_reaches_undiscovered_recipient_via_relay() {
+    use scmessenger_core::store::{Outbox, QueuedMessage};
+    use scmessenger_core::transport::{DiscoveryConfig, DiscoveryMode};
+
+    let manual = || Some(DiscoveryConfig::new(DiscoveryMode::Manual));
+    let relay_key = Keypair::generate_ed25519();
+    let alice_key = Keypair::generate_ed25519();
+    let bob_key = Keypair::generate_ed25519();
+    let relay_peer_id = relay_key.public().to_peer_id();
+    let bob_peer_id = bob_key.public().to_peer_id();
+
+    let (relay_tx, mut relay_rx) = mpsc::channel(256);
+    let relay_handle = start_swarm(
+        relay_key,
+        None,
+        relay_tx,
+        None,
+        false,
+        manual(),
+        scmessenger_core::transport::default_routing_engine_handle(),
+    )
+    .await
+    .expect("failed to start relay");
+    let relay_addr = wait_for_tcp_listener(&mut relay_rx, Duration::from_secs(10)).await;
+    let relay_full_addr: Multiaddr = format!("{}/p2p/{}", relay_addr, relay_peer_id)
+        .parse()
+        .expect("invalid relay multiaddr");
+
+    // Bob is only ever connected to the relay.
+    let (bob_tx, mut bob_rx) = mpsc::channel(256);
+    let bob_handle = start_swarm(
+        bob_key,
+        None,
+        bob_tx,
+        None,
+        false,
+        manual(),
+        scmessenger_core::transport::default_routing_engine_handle(),
+    )
+    .await
+    .expect("failed to start bob");
+    let _ = wait_for_tcp_listener(&mut bob_rx, Duration::from_secs(10)).await;
+    bob_handle
+        .dial(relay_full_addr.clone())
+        .await
+        .expect("bob failed to dial relay");
+    wait_for_peer_ready(&mut bob_rx, relay_peer_id, Duration::from_secs(15)).await;
+
+    // Alice queued a message for Bob while offline.
+    let payload = b"queued-for-bob-via-relay".to_vec();
+    let mut outbox = Outbox::new();
+    outbox
+        .enqueue(QueuedMessage {
+            version: 1,
+            message_id: "m1".to_string(),
+            recipient_id: bob_peer_id.to_string(),
+            envelope_data: payload.clone(),
+            queued_at: 0,
+            attempts: 0,
+            next_retry_at: None,
+            in_custody: false,
+            custody_established_at: 0,
+            state: scmessenger_core::store::outbox::MessageState::Enqueued,
+        })
+        .unwrap();
+
+    let (alice_tx, mut alice_rx) = mpsc::channel(256);
+    let alice_handle = start_swarm(
+        alice_key,
+        None,
+        alice_tx,
+        None,
+        false,
+        manual(),
+        scmessenger_core::transport::default_routing_engine_handle(),
+    )
+    .await
+    .expect("failed to start alice");
+    let _ = wait_for_tcp_listener(&mut alice_rx, Duration::from_secs(10)).await;
+    alice_handle
+        .dial(relay_full_addr)
+        .await
+        .expect("alice failed to dial relay");
+    wait_for_peer_ready(&mut alice_rx, relay_peer_id, Duration::from_secs(15)).await;
+
+    // The relay connecting is what flushes the queue, not Bob appearing.
+    assert_eq!(outbox.pending_recipients(), vec![bob_peer_id.to_string()]);
+    for msg in outbox.drain_for_peer(&bob_peer_id.to_string()) {
+        timeout(
+            Duration::from_secs(15),
+            alice_handle.send_via_relay(relay_peer_id, bob_peer_id, msg.envelope_data),
+        )
+        .await
+        .expect("relayed send timed out")
+        .expect("relay rejected the queued message");
+    }
+    assert_eq!(outbox.total_count(), 0);
+
+    let delivered_from = wait_for_envelope(&mut bob_rx, &payload, Duration::from_secs(15)).await;
+    assert_eq!(delivered_from, relay_peer_id);
+    let alice_peers = alice_handle.get_peers().await.unwrap();
+    assert!(!alice_peers.contains(&bob_peer_id));
+
+    alice_handle.shutdown().await.ok();
+    bob_handle.shutdown().await.ok();
+    relay_handle.shutdown().await.ok();
+}
@@ -0,0 +1,90 @@
+//! Integration test: `publish_topic_counted` reports how many subscribers a
+//! gossipsub publish reached.
+
+use libp2p::{Multiaddr, PeerId};
+use scmessenger_core::identity::IdentityKeys;
+use scmessenger_core::transport::{start_swarm, SwarmEvent, SwarmHandle};
+use std::collections::HashSet;
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+
+const TOPIC: &str = "sc-publish-count-test";
+
+async fn spawn_node() -> (SwarmHandle, mpsc::Receiver<SwarmEvent>, Multiaddr, PeerId) {
+    let keypair = IdentityKeys::generate().to_libp2p_keypair().unwrap();
+    let peer_id = keypair.public().to_peer_id();
+    let (tx, mut rx) = mpsc::channel(256);
+    let handle = start_swarm(
+        keypair,
+        Some("/ip4/127.0.0.1/tcp/0".parse().unwrap()),
+        tx,
+        None,
+        false,
+        None, // discovery_config
+        scmessenger_core::transport::default_routing_engine_handle(),
+    )
+    .await
+    .expect("failed to start swarm");
+    let addr = timeout(Duration::from_secs(10), async {
+        loop {
+            match rx.recv().await {
+                Some(SwarmEvent::ListeningOn(addr)) if addr.to_string().contains("/tcp/") => {
+                    return addr;
+                }
+                Some(_) => {}
+                None => panic!("event channel closed while waiting for listener"),
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for listener");
+    (handle, rx, addr, peer_id)
+}
+
+#[tokio::test]
+#[ignore = "requires real networking (TCP bind); run with --include-ignored"]
+async fn publish_counts_known_subscribers() {
+    let (publisher, mut events, _, _) = spawn_node().await;
+    let (sub_a, _sub_a_events, sub_a_addr, sub_a_peer) = spawn_node().await;
+    let (sub_b, _sub_b_events, sub_b_addr, sub_b_peer) = spawn_node().await;
+
+    // Nobody subscribes yet: the publish goes nowhere, and says so.
+    assert_eq!(
+        publisher
+            .publish_topic_counted("sc-publish-count-empty".to_string(), b"void".to_vec())
+            .await
+            .unwrap(),
+        0
+    );
+
+    sub_a.subscribe_topic(TOPIC.to_string()).await.unwrap();
+    sub_b.subscribe_topic(TOPIC.to_string()).await.unwrap();
+    publisher.dial(sub_a_addr).await.unwrap();
+    publisher.dial(sub_b_addr).await.unwrap();
+
+    let expected: HashSet<PeerId> = [sub_a_peer, sub_b_peer].into_iter().collect();
+    timeout(Duration::from_secs(15), async {
+        let mut seen = HashSet::new();
+        while seen != expected {
+            match events.recv().await {
+                Some(SwarmEvent::TopicDiscovered { peer_id, topic }) if topic == TOPIC => {
+                    seen.insert(peer_id);
+                }
+                Some(_) => {}
+                None => panic!("event channel closed while waiting for subscriptions"),
+            }
+        }
+    })
+    .await
+    .expect("timed out waiting for both subscriptions");
+
+    let peers = publisher
+        .publish_topic_counted(TOPIC.to_string(), b"hello subscribers".to_vec())
+        .await
+        .unwrap();
+    assert_eq!(peers, 2);
+
+    publisher.shutdown().await.ok();
+    sub_a.shutdown().await.ok();
+    sub_b.shutdown().await.ok();
+}
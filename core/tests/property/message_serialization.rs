@@ -2,6 +2,7 @@
 // Validates: Requirements 3.5, 13.1
 
 use proptest::prelude::*;
+use scmessenger_core::crypto::AeadSuite;
 use scmessenger_core::message::types::{
     DeliveryStatus, Envelope, Message, MessageType, Receipt, SignedEnvelope,
 };
@@ -70,6 +71,7 @@ fn arb_envelope() -> impl Strategy<Value = Envelope> {
         prop::collection::vec(any::<u8>(), 0..2048), // ciphertext (0-2048 bytes)
         prop::option::of(prop::collection::vec(any::<u8>(), 32..33)), // ratchet_dh_public
         prop::option::of(any::<u32>()),             // ratchet_message_number
+        prop::sample::select(AeadSuite::ALL.to_vec()), // aead_suite
     )
         .prop_map(
             |(
@@ -79,6 +81,7 @@ fn arb_envelope() -> impl Strategy<Value = Envelope> {
                 ciphertext,
                 ratchet_dh_public,
                 ratchet_message_number,
+                aead_suite,
             )| Envelope {
                 sender_public_key,
                 ephemeral_public_key,
@@ -86,6 +89,7 @@ fn arb_envelope() -> impl Strategy<Value = Envelope> {
                 ciphertext,
                 ratchet_dh_public,
                 ratchet_message_number,
+                aead_suite: aead_suite.as_u8(),
             },
         )
 }
@@ -175,6 +179,7 @@ proptest! {
         prop_assert_eq!(envelope.ciphertext, decoded.ciphertext);
         prop_assert_eq!(envelope.ratchet_dh_public, decoded.ratchet_dh_public);
         prop_assert_eq!(envelope.ratchet_message_number, decoded.ratchet_message_number);
+        prop_assert_eq!(envelope.aead_suite, decoded.aead_suite);
     }
 
     /// Property 4: SignedEnvelope serialization round-trip consistency
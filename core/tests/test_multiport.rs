@@ -287,6 +287,8 @@ async fn test_multiport_swarm_integration() {
         false,      // headless
         None,       // discovery_config
         scmessenger_core::transport::default_routing_engine_handle(),
+        None,
+        None,
     )
     .await;
 
@@ -12,7 +12,8 @@ fn test_contact_persistence_across_restarts() {
             "test-peer-001".to_string(),
             "a".repeat(64),
         )
-        .with_nickname("Alice".to_string());
+        .with_nickname("Alice".to_string())
+        .unwrap();
         manager.add(contact).unwrap();
         assert_eq!(manager.count(), 1);
     }
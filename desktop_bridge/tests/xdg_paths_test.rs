@@ -52,6 +52,7 @@ fn test_xdg_config_dir_contains_scmessenger() {
 /// Verify that overriding XDG_DATA_HOME is respected.
 #[test]
 #[cfg(target_os = "linux")]
+#[allow(clippy::disallowed_methods)] // the env override is what this test covers
 fn test_xdg_data_home_env_override() {
     // Save original value
     let original = std::env::var("XDG_DATA_HOME").ok();
@@ -3078,7 +3078,13 @@ final class MeshRepository {
         guard let settingsManager = settingsManager else {
             throw MeshError.notInitialized("SettingsManager not initialized")
         }
-        try settingsManager.save(settings: settings)
+        // Through the service when it exists so the running core picks the
+        // change up (e.g. the relay toggle) instead of waiting for a restart.
+        if let meshService = meshService {
+            try meshService.updateSettings(settings: settings)
+        } else {
+            try settingsManager.save(settings: settings)
+        }
         if !settings.notificationsEnabled {
             NotificationManager.shared.clearBadge()
         }
@@ -4063,7 +4069,7 @@ final class MeshRepository {
         )
 
         var syncPeerIds: [String] = [peerId]
-        let isHeadless = agentVersion.contains("/headless/")
+        let isHeadless = isRelayAgentVersion(agentVersion: agentVersion)
         let transportIdentity = resolveTransportIdentity(libp2pPeerId: peerId)
         let shouldTreatAsHeadless = isBootstrapRelayPeer(peerId) || (isHeadless && transportIdentity == nil)
         if shouldTreatAsHeadless {
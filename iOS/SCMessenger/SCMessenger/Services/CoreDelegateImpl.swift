@@ -195,6 +195,42 @@ final class CoreDelegateImpl: CoreDelegate {
         }
     }
 
+    func onBroadcastReceived(senderPublicKeyHex: String, topic: String, data: Data) {
+        logger.info("Broadcast received on \(topic) from \(String(senderPublicKeyHex.prefix(12))) (\(data.count) bytes)")
+        DispatchQueue.main.async {
+            self.eventBus.messageEvents.send(.broadcastReceived(
+                senderPublicKeyHex: senderPublicKeyHex,
+                topic: topic,
+                data: data
+            ))
+        }
+    }
+
+    func onInboxPruned(count: UInt32) {
+        logger.info("Inbox sweeper removed \(count) expired message(s)")
+        DispatchQueue.main.async {
+            self.eventBus.messageEvents.send(.inboxPruned(count: count))
+        }
+    }
+
+    func onTyping(senderPublicKeyHex: String, isTyping: Bool) {
+        logger.debug("Typing from \(String(senderPublicKeyHex.prefix(12))): \(isTyping)")
+        DispatchQueue.main.async {
+            self.eventBus.messageEvents.send(.typing(senderPublicKeyHex: senderPublicKeyHex, isTyping: isTyping))
+        }
+    }
+
+    func onUnknownMessage(typeId: UInt32, senderPublicKeyHex: String, payload: Data) {
+        logger.warning("Message of unsupported type \(typeId) from \(String(senderPublicKeyHex.prefix(12))) (\(payload.count) bytes)")
+        DispatchQueue.main.async {
+            self.eventBus.messageEvents.send(.unknownType(
+                typeId: typeId,
+                senderPublicKeyHex: senderPublicKeyHex,
+                payload: payload
+            ))
+        }
+    }
+
     func onServiceStateChanged(state: ServiceState) {
         logger.info("Service state changed: \(String(describing: state))")
         DispatchQueue.main.async {
@@ -53,6 +53,10 @@ final class MeshEventBus {
         case sent(messageId: String)
         case delivered(messageId: String)
         case failed(messageId: String, error: String)
+        case broadcastReceived(senderPublicKeyHex: String, topic: String, data: Data)
+        case inboxPruned(count: UInt32)
+        case typing(senderPublicKeyHex: String, isTyping: Bool)
+        case unknownType(typeId: UInt32, senderPublicKeyHex: String, payload: Data)
     }
 
     enum StatusEvent: Equatable {
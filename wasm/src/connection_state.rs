@@ -4,7 +4,10 @@
 // Solves the memory leak problem caused by .forget() on closures by storing
 // callbacks and cleaning them up on disconnect.
 
+use libp2p::{Multiaddr, PeerId};
+use scmessenger_core::transport::{connection_path, ConnectionPath, NatReachability};
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::rc::Rc;
 
 #[cfg(target_arch = "wasm32")]
@@ -334,6 +337,123 @@ impl Drop for ConnectionManager {
     }
 }
 
+// ============================================================================
+// CONNECTION PATH NOTIFICATIONS
+// ============================================================================
+
+/// A connection-path change is only reported once it has held for this long,
+/// so a peer flapping in and out does not spam the UI.
+pub const CONNECTION_STATE_DEBOUNCE_MS: u32 = 500;
+
+/// Inputs to `connection_path` as observed from swarm events, the last state
+/// reported to JS and the `onConnectionStateChanged` callback.
+///
+/// Every input change returns a generation number; the caller waits out the
+/// debounce window and then calls `settle` with it, which only reports if no
+/// newer change arrived in the meantime.
+pub struct ConnectionStateTracker {
+    running: bool,
+    peers: HashSet<PeerId>,
+    listeners: HashSet<Multiaddr>,
+    nat: NatReachability,
+    reported: ConnectionPath,
+    generation: u64,
+    callback: Option<js_sys::Function>,
+}
+
+impl ConnectionStateTracker {
+    pub fn new() -> Self {
+        Self {
+            running: false,
+            peers: HashSet::new(),
+            listeners: HashSet::new(),
+            nat: NatReachability::Unknown,
+            reported: ConnectionPath::Disconnected,
+            generation: 0,
+            callback: None,
+        }
+    }
+
+    pub fn set_callback(&mut self, callback: Option<js_sys::Function>) {
+        self.callback = callback;
+    }
+
+    pub fn callback(&self) -> Option<js_sys::Function> {
+        self.callback.clone()
+    }
+
+    fn changed(&mut self) -> u64 {
+        self.generation += 1;
+        self.generation
+    }
+
+    /// Swarm started or stopped. Stopping forgets peers and listeners.
+    pub fn set_running(&mut self, running: bool) -> u64 {
+        self.running = running;
+        if !running {
+            self.peers.clear();
+            self.listeners.clear();
+            self.nat = NatReachability::Unknown;
+        }
+        self.changed()
+    }
+
+    pub fn peer_connected(&mut self, peer: PeerId) -> u64 {
+        self.peers.insert(peer);
+        self.changed()
+    }
+
+    pub fn peer_disconnected(&mut self, peer: &PeerId) -> u64 {
+        self.peers.remove(peer);
+        self.changed()
+    }
+
+    pub fn listener_added(&mut self, address: Multiaddr) -> u64 {
+        self.listeners.insert(address);
+        self.changed()
+    }
+
+    /// An address can be reported gone twice (its listener closed and the
+    /// address expired); only the first removal counts.
+    pub fn listener_removed(&mut self, address: &Multiaddr) -> u64 {
+        self.listeners.remove(address);
+        self.changed()
+    }
+
+    pub fn set_nat(&mut self, nat: NatReachability) -> u64 {
+        self.nat = nat;
+        self.changed()
+    }
+
+    pub fn current(&self) -> ConnectionPath {
+        if !self.running {
+            return ConnectionPath::Disconnected;
+        }
+        connection_path(self.peers.len(), self.listeners.len(), self.nat)
+    }
+
+    /// Called once the debounce window after change `generation` has passed.
+    /// Returns the state to report, if nothing changed since and it differs
+    /// from the last report.
+    pub fn settle(&mut self, generation: u64) -> Option<ConnectionPath> {
+        if generation != self.generation {
+            return None;
+        }
+        let current = self.current();
+        if current == self.reported {
+            return None;
+        }
+        self.reported = current;
+        Some(current)
+    }
+}
+
+impl Default for ConnectionStateTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -361,6 +481,45 @@ mod tests {
         assert_eq!(manager.connection_count(), 0);
     }
 
+    #[test]
+    fn test_connection_state_settles_only_on_latest_change() {
+        let mut tracker = ConnectionStateTracker::new();
+        let peer = PeerId::random();
+        let tcp: Multiaddr = "/ip4/192.168.1.5/tcp/4001".parse().unwrap();
+        let quic: Multiaddr = "/ip4/192.168.1.5/udp/4001/quic-v1".parse().unwrap();
+
+        let g = tracker.set_running(true);
+        assert_eq!(tracker.settle(g), Some(ConnectionPath::Bootstrapping));
+
+        tracker.listener_added(tcp.clone());
+        let stale = tracker.peer_connected(peer);
+        let g = tracker.peer_disconnected(&peer);
+        // A newer change supersedes the earlier one, and a flap that ends
+        // where it started reports nothing.
+        assert_eq!(tracker.settle(stale), None);
+        assert_eq!(tracker.settle(g), None);
+
+        let g = tracker.peer_connected(peer);
+        assert_eq!(tracker.settle(g), Some(ConnectionPath::DirectPreferred));
+
+        // The same address reported twice is one listener, and the second
+        // report of it going away (listener closed, then address expired)
+        // doesn't take another listener with it.
+        tracker.listener_added(tcp.clone());
+        tracker.listener_added(quic.clone());
+        tracker.listener_removed(&tcp);
+        let g = tracker.listener_removed(&tcp);
+        assert_eq!(tracker.settle(g), None);
+        // Losing the last listen address leaves us reachable via relays only.
+        let g = tracker.listener_removed(&quic);
+        assert_eq!(tracker.settle(g), Some(ConnectionPath::RelayFallback));
+        let g = tracker.listener_added(quic);
+        assert_eq!(tracker.settle(g), Some(ConnectionPath::DirectPreferred));
+
+        let g = tracker.set_running(false);
+        assert_eq!(tracker.settle(g), Some(ConnectionPath::Disconnected));
+    }
+
     // Note: WebSocket and RTC connection tests require WASM target
     // and are tested in browser environment
 }
@@ -6,6 +6,7 @@ pub mod notification_manager;
 pub mod transport;
 
 use anyhow::Error;
+use connection_state::{ConnectionStateTracker, CONNECTION_STATE_DEBOUNCE_MS};
 use libp2p::{Multiaddr, PeerId};
 use scmessenger_core::{
     IdentityInfo, IronCore as RustIronCore, NotificationDecision, NotificationMessageContext,
@@ -13,6 +14,7 @@ use scmessenger_core::{
 };
 use serde_json::{Map, Value};
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 
@@ -45,6 +47,19 @@ pub struct MeshSettings {
     pub notify_dm_request_in_foreground: bool,
     pub sound_enabled: bool,
     pub badge_enabled: bool,
+    /// Let the browser swarm dial `/webrtc-direct` peers (see
+    /// `swarm_discovery_config`). Browser-to-browser `/webrtc` is not
+    /// supported; browser pairs still talk through a relay.
+    pub webrtc_direct_enabled: bool,
+    /// Sustained inbound messages per second accepted from one sender; 0
+    /// disables the limit.
+    pub inbound_messages_per_sec: u32,
+    /// Messages a sender may send back-to-back before the rate applies.
+    pub inbound_burst: u32,
+    /// Largest gossipsub payload published or accepted on a topic, in bytes.
+    pub max_topic_payload_bytes: u32,
+    /// Per-topic overrides of `max_topic_payload_bytes`.
+    pub topic_payload_limits: HashMap<String, u32>,
 }
 
 impl Default for MeshSettings {
@@ -69,10 +84,27 @@ impl Default for MeshSettings {
             notify_dm_request_in_foreground: true,
             sound_enabled: true,
             badge_enabled: true,
+            webrtc_direct_enabled: false,
+            inbound_messages_per_sec: default_inbound_messages_per_sec(),
+            inbound_burst: default_inbound_burst(),
+            max_topic_payload_bytes: default_max_topic_payload_bytes(),
+            topic_payload_limits: HashMap::new(),
         }
     }
 }
 
+fn default_inbound_messages_per_sec() -> u32 {
+    scmessenger_core::transport::rate_limit::DEFAULT_INBOUND_MESSAGES_PER_SEC
+}
+
+fn default_inbound_burst() -> u32 {
+    scmessenger_core::transport::rate_limit::DEFAULT_INBOUND_BURST
+}
+
+fn default_max_topic_payload_bytes() -> u32 {
+    scmessenger_core::transport::topic_limits::DEFAULT_MAX_TOPIC_PAYLOAD as u32
+}
+
 impl From<MeshSettings> for scmessenger_core::MeshSettings {
     fn from(wasm: MeshSettings) -> Self {
         scmessenger_core::MeshSettings {
@@ -100,6 +132,18 @@ impl From<MeshSettings> for scmessenger_core::MeshSettings {
             notify_dm_request_in_foreground: wasm.notify_dm_request_in_foreground,
             sound_enabled: wasm.sound_enabled,
             badge_enabled: wasm.badge_enabled,
+            inbound_messages_per_sec: wasm.inbound_messages_per_sec,
+            inbound_burst: wasm.inbound_burst,
+            gossip_profile: scmessenger_core::GossipProfile::Default,
+            kad_mode: scmessenger_core::KadMode::default(),
+            advertise_scope: scmessenger_core::AdvertiseScope::default(),
+            relay_mailbox_enabled: false,
+            require_signed_relay: false,
+            max_contacts: 0,
+            max_topic_payload_bytes: wasm.max_topic_payload_bytes,
+            topic_payload_limits: wasm.topic_payload_limits,
+            clock_sync_enabled: false,
+            ..scmessenger_core::MeshSettings::default()
         }
     }
 }
@@ -153,23 +197,9 @@ impl MeshSettingsManager {
     }
 
     pub fn validate(&self, settings: MeshSettings) -> Result<(), scmessenger_core::IronCoreError> {
-        if settings.relay_enabled && settings.max_relay_budget == 0 {
-            return Err(scmessenger_core::IronCoreError::InvalidInput);
-        }
-
-        if !settings.ble_enabled
-            && !settings.wifi_aware_enabled
-            && !settings.wifi_direct_enabled
-            && !settings.internet_enabled
-        {
-            return Err(scmessenger_core::IronCoreError::InvalidInput);
-        }
-
-        if settings.battery_floor > 50 {
-            return Err(scmessenger_core::IronCoreError::InvalidInput);
-        }
-
-        Ok(())
+        scmessenger_core::MeshSettings::from(settings)
+            .validate()
+            .map_err(|_| scmessenger_core::IronCoreError::InvalidInput)
     }
 }
 
@@ -200,9 +230,11 @@ pub enum IronCoreMode {
 pub struct IronCore {
     inner: std::sync::Arc<RustIronCore>,
     /// Buffer of successfully decoded messages waiting to be drained by JS.
-    rx_messages: Rc<RefCell<Vec<WasmMessage>>>,
+    rx_messages: Rc<RefCell<ReceivedMessages>>,
     /// Active libp2p swarm handle for browser networking.
     swarm_handle: Rc<RefCell<Option<scmessenger_core::transport::SwarmHandle>>>,
+    /// Ends the running swarm's event loop task; taken by `stopSwarm`.
+    swarm_stop: Rc<RefCell<Option<tokio::sync::oneshot::Sender<()>>>>,
     /// Settings manager for persistence (uses localStorage path or in-memory).
     settings_manager: Option<MeshSettingsManager>,
     /// Cached in-memory settings for the current session.
@@ -211,6 +243,8 @@ pub struct IronCore {
     mode: Rc<RefCell<IronCoreMode>>,
     /// Daemon socket URL for JSON-RPC communication (when in Daemon mode)
     daemon_socket_url: Rc<RefCell<Option<String>>>,
+    /// Connection-path tracking for `onConnectionStateChanged`.
+    connection_state: Rc<RefCell<ConnectionStateTracker>>,
 }
 
 #[wasm_bindgen]
@@ -230,12 +264,14 @@ impl IronCore {
         };
         let core = Self {
             inner: std::sync::Arc::new(RustIronCore::new()),
-            rx_messages: Rc::new(RefCell::new(Vec::new())),
+            rx_messages: Rc::new(RefCell::new(ReceivedMessages::default())),
             swarm_handle: Rc::new(RefCell::new(None)),
+            swarm_stop: Rc::new(RefCell::new(None)),
             settings_manager: None,
             settings: Rc::new(RefCell::new(defaults.clone())),
             mode: Rc::new(RefCell::new(IronCoreMode::Full)),
             daemon_socket_url: Rc::new(RefCell::new(None)),
+            connection_state: Rc::new(RefCell::new(ConnectionStateTracker::new())),
         };
 
         // P1_CORE_001: Sync drift state
@@ -261,12 +297,14 @@ impl IronCore {
         });
         let core = Self {
             inner: std::sync::Arc::new(RustIronCore::with_storage(storage_path)),
-            rx_messages: Rc::new(RefCell::new(Vec::new())),
+            rx_messages: Rc::new(RefCell::new(ReceivedMessages::default())),
             swarm_handle: Rc::new(RefCell::new(None)),
+            swarm_stop: Rc::new(RefCell::new(None)),
             settings_manager: Some(manager),
             settings: Rc::new(RefCell::new(loaded.clone())),
             mode: Rc::new(RefCell::new(IronCoreMode::Full)),
             daemon_socket_url: Rc::new(RefCell::new(None)),
+            connection_state: Rc::new(RefCell::new(ConnectionStateTracker::new())),
         };
 
         // P1_CORE_001: Sync drift state
@@ -292,12 +330,14 @@ impl IronCore {
         });
         let core = Self {
             inner: std::sync::Arc::new(RustIronCore::new()),
-            rx_messages: Rc::new(RefCell::new(Vec::new())),
+            rx_messages: Rc::new(RefCell::new(ReceivedMessages::default())),
             swarm_handle: Rc::new(RefCell::new(None)),
+            swarm_stop: Rc::new(RefCell::new(None)),
             settings_manager: Some(manager),
             settings: Rc::new(RefCell::new(loaded.clone())),
             mode: Rc::new(RefCell::new(IronCoreMode::Full)),
             daemon_socket_url: Rc::new(RefCell::new(None)),
+            connection_state: Rc::new(RefCell::new(ConnectionStateTracker::new())),
         };
 
         // P1_CORE_001: Sync drift state
@@ -323,19 +363,19 @@ impl IronCore {
         });
         Self {
             inner: std::sync::Arc::new(RustIronCore::new()),
-            rx_messages: Rc::new(RefCell::new(Vec::new())),
+            rx_messages: Rc::new(RefCell::new(ReceivedMessages::default())),
             swarm_handle: Rc::new(RefCell::new(None)),
+            swarm_stop: Rc::new(RefCell::new(None)),
             settings_manager: Some(manager),
             settings: Rc::new(RefCell::new(loaded)),
             mode: Rc::new(RefCell::new(IronCoreMode::Full)),
             daemon_socket_url: Rc::new(RefCell::new(None)),
+            connection_state: Rc::new(RefCell::new(ConnectionStateTracker::new())),
         }
     }
 
     pub fn start(&self) -> Result<(), JsValue> {
-        self.inner
-            .start()
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+        self.inner.start().map_err(js_core_error)
     }
 
     pub fn stop(&self) {
@@ -407,11 +447,9 @@ impl IronCore {
 
         self.inner
             .start() // Ensure core is running first
-            .map_err(|e| js_value_from_str(&format!("{}", e)))?;
+            .map_err(js_core_error)?;
 
-        self.inner
-            .initialize_identity()
-            .map_err(|e| js_value_from_str(&format!("{}", e)))?;
+        self.inner.initialize_identity().map_err(js_core_error)?;
 
         Ok(())
     }
@@ -481,7 +519,7 @@ impl IronCore {
         self.inner
             .sign_data(data)
             .map(|sig| to_js_value_safe(&WasmSignatureResult::from(sig)))
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+            .map_err(js_core_error)
     }
 
     #[wasm_bindgen(js_name = verifySignature)]
@@ -493,7 +531,7 @@ impl IronCore {
     ) -> Result<bool, JsValue> {
         self.inner
             .verify_signature(data, signature, public_key_hex)
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+            .map_err(js_core_error)
     }
 
     /// Prepare an encrypted message envelope.
@@ -534,7 +572,7 @@ impl IronCore {
                 None,
             )
             .map(|pm| pm.envelope_data)
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+            .map_err(js_core_error)
     }
 
     #[wasm_bindgen(js_name = receiveMessage)]
@@ -549,9 +587,10 @@ impl IronCore {
                     sender_peer_id: None,
                     text: msg.text_content(),
                     timestamp: msg.timestamp,
+                    receive_seq: None,
                 })
             })
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+            .map_err(js_core_error)
     }
 
     #[wasm_bindgen(js_name = outboxCount)]
@@ -574,22 +613,42 @@ impl IronCore {
     /// Start libp2p swarm networking for the browser client.
     ///
     /// `bootstrapAddrs` must be a JS array of libp2p multiaddr strings.
+    ///
+    /// With no identity the swarm normally starts relay-only under a
+    /// throwaway key. Pass `requireIdentity = true` to fail instead, with an
+    /// error starting `IdentityRequired`, so the UI can route to onboarding.
     #[wasm_bindgen(js_name = startSwarm)]
-    pub async fn start_swarm(&self, bootstrap_addrs: JsValue) -> Result<(), JsValue> {
+    pub async fn start_swarm(
+        &self,
+        bootstrap_addrs: JsValue,
+        require_identity: Option<bool>,
+    ) -> Result<(), JsValue> {
         let bootstrap_addrs = parse_bootstrap_addrs(bootstrap_addrs)?;
-        start_swarm_runtime(
+        let stop = start_swarm_runtime(
             std::sync::Arc::clone(&self.inner),
             Rc::clone(&self.rx_messages),
             Rc::clone(&self.settings),
             Rc::clone(&self.swarm_handle),
+            Rc::clone(&self.connection_state),
             bootstrap_addrs,
+            require_identity.unwrap_or(false),
         )
-        .await
+        .await?;
+        *self.swarm_stop.borrow_mut() = Some(stop);
+        Ok(())
     }
 
     /// Stop libp2p swarm networking for the browser client.
+    ///
+    /// The event loop task exits on its next poll; the handle and the
+    /// connection state are cleared here, so `startSwarm` can be called
+    /// again straight away.
     #[wasm_bindgen(js_name = stopSwarm)]
     pub async fn stop_swarm(&self) -> Result<(), JsValue> {
+        if let Some(stop) = self.swarm_stop.borrow_mut().take() {
+            let _ = stop.send(());
+            note_connection_change(&self.connection_state, |s| s.set_running(false));
+        }
         let maybe_handle = self.swarm_handle.borrow_mut().take();
         if let Some(handle) = maybe_handle {
             handle
@@ -675,34 +734,58 @@ impl IronCore {
             .get_peers()
             .await
             .map_err(|e| js_value_from_str(&format!("Failed to get peers: {}", e)))?;
-        if peers.is_empty() {
-            return Ok("Bootstrapping".to_string());
-        }
-
         let listeners = handle
             .get_listeners()
             .await
             .map_err(|e| js_value_from_str(&format!("Failed to get listeners: {}", e)))?;
-        if listeners.is_empty() {
-            Ok("RelayFallback".to_string())
-        } else {
-            Ok("DirectPreferred".to_string())
-        }
+
+        // The browser swarm runs no AutoNAT, so reachability is unknown.
+        Ok(scmessenger_core::transport::connection_path(
+            peers.len(),
+            listeners.len(),
+            scmessenger_core::transport::NatReachability::Unknown,
+        )
+        .to_string())
+    }
+
+    /// Register `callback(state)` to be called whenever the connection path
+    /// (the value `getConnectionPathState` returns) changes, so UIs need not
+    /// poll. A change is reported once it has held for a short debounce
+    /// window, so flapping peers do not produce a burst of calls. Pass
+    /// `null` to unregister.
+    #[wasm_bindgen(js_name = onConnectionStateChanged)]
+    pub fn on_connection_state_changed(&self, callback: Option<js_sys::Function>) {
+        self.connection_state.borrow_mut().set_callback(callback);
     }
 
     #[wasm_bindgen(js_name = exportDiagnostics)]
+    #[allow(clippy::disallowed_methods)] // serde_json::json! expands to unwrap() calls internally
     pub async fn export_diagnostics(&self) -> Result<String, JsValue> {
         // Clone the handle out of the lock before any await so the RefCell borrow
         // is not held across the suspension point.
         let handle_opt = self.swarm_handle.borrow().clone();
-        let peers = if let Some(handle) = handle_opt {
-            handle
+        let (peers, relay_reservations) = if let Some(handle) = handle_opt {
+            let peers = handle
                 .get_peers()
                 .await
                 .map(|p| p.into_iter().map(|id| id.to_string()).collect::<Vec<_>>())
+                .unwrap_or_default();
+            let reservations: Vec<Value> = handle
+                .get_relay_reservations()
+                .await
                 .unwrap_or_default()
+                .into_iter()
+                .map(|r| {
+                    serde_json::json!({
+                        "relay_peer_id": r.relay_peer_id.to_string(),
+                        "relay_addr": r.relay_addr.map(|addr| addr.to_string()),
+                        "accepted": r.accepted,
+                    })
+                })
+                .collect();
+            (peers, reservations)
         } else {
-            Vec::new()
+            (Vec::new(), Vec::new())
         };
 
         let connection_path = self.get_connection_path_state().await?;
@@ -710,6 +793,7 @@ impl IronCore {
         m.insert("running".to_string(), self.is_running().into());
         m.insert("connection_path_state".to_string(), connection_path.into());
         m.insert("peers".to_string(), peers.into());
+        m.insert("relay_reservations".to_string(), relay_reservations.into());
         m.insert("inbox_count".to_string(), self.inbox_count().into());
         m.insert("outbox_count".to_string(), self.outbox_count().into());
         m.insert(
@@ -800,6 +884,26 @@ impl IronCore {
         Ok(())
     }
 
+    /// Re-dial bootstrap nodes now instead of waiting for the 60s timer,
+    /// e.g. after the browser comes back online. Nodes already connected or
+    /// backing off are skipped. Resolves to the number of dials started.
+    #[wasm_bindgen(js_name = reconnect)]
+    pub async fn reconnect(&self) -> Result<u32, JsValue> {
+        let handle = self
+            .swarm_handle
+            .borrow()
+            .clone()
+            .ok_or_else(|| js_value_from_str("Swarm is not running"))?;
+
+        let started = handle
+            .reconnect_bootstrap()
+            .await
+            .map_err(|e: anyhow::Error| {
+                js_value_from_str(&format!("Failed to reconnect: {}", e))
+            })?;
+        Ok(started as u32)
+    }
+
     /// Send data to all currently connected peers.
     #[wasm_bindgen(js_name = sendToAllPeers)]
     pub async fn send_to_all_peers(&self, data: Vec<u8>) -> Result<(), JsValue> {
@@ -961,29 +1065,33 @@ impl IronCore {
         let relay_multiaddr = relay_url_to_multiaddr(&relay_url)
             .map_err(|e| js_value_from_str(&format!("Invalid relay URL: {}", e)))?;
 
-        start_swarm_runtime(
+        let stop = start_swarm_runtime(
             std::sync::Arc::clone(&self.inner),
             Rc::clone(&self.rx_messages),
             Rc::clone(&self.settings),
             Rc::clone(&self.swarm_handle),
+            Rc::clone(&self.connection_state),
             vec![relay_multiaddr],
+            false,
         )
-        .await
+        .await?;
+        *self.swarm_stop.borrow_mut() = Some(stop);
+        Ok(())
     }
 
     /// Drain and return all messages that have arrived since the last call.
     ///
     /// Returns a `js_sys::Array` of plain JS objects with the same shape as
     /// the object returned by `receiveMessage`:
-    /// `{ id, senderId, text, timestamp }`.
+    /// `{ id, senderId, text, timestamp }`, plus `receiveSeq`.
     ///
-    /// The internal buffer is cleared on each call; messages are not duplicated
-    /// across successive calls.
+    /// Messages come back in ascending `receiveSeq`, a counter assigned when
+    /// each message was buffered, so the order is stable even when several
+    /// share a timestamp. The internal buffer is cleared on each call;
+    /// messages are not duplicated across successive calls.
     #[wasm_bindgen(js_name = drainReceivedMessages)]
     pub fn drain_received_messages(&self) -> js_sys::Array {
-        let mut buf = self.rx_messages.borrow_mut();
-        let drained: Vec<WasmMessage> = buf.drain(..).collect();
-        drop(buf);
+        let drained = self.rx_messages.borrow_mut().drain();
 
         let array = js_sys::Array::new();
         for msg in drained {
@@ -1011,6 +1119,13 @@ impl IronCore {
             .map_err(|e| js_value_from_str(&format!("Invalid settings: {}", e)))?;
         let settings: MeshSettings = wasm_settings.into();
 
+        // Reject contradictory settings with every problem listed, so the UI
+        // can show them all (the error value is an array of strings).
+        if let Err(errors) = scmessenger_core::MeshSettings::from(settings.clone()).validate() {
+            let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+            return Err(to_js_value_safe(&messages));
+        }
+
         // Persist if we have a storage manager
         if let Some(ref mgr) = self.settings_manager {
             mgr.save(settings.clone())
@@ -1019,6 +1134,14 @@ impl IronCore {
 
         *self.settings.borrow_mut() = settings.clone();
 
+        let maybe_handle = self.swarm_handle.borrow().clone();
+        if let Some(handle) = maybe_handle {
+            let core_settings: scmessenger_core::MeshSettings = settings.clone().into();
+            wasm_bindgen_futures::spawn_local(async move {
+                let _ = handle.apply_mesh_settings(&core_settings).await;
+            });
+        }
+
         // P1_CORE_001: Sync drift protocol state with relay toggle
         if settings.relay_enabled {
             self.inner.drift_activate();
@@ -1081,9 +1204,7 @@ impl IronCore {
     /// Set the nickname for the local identity.
     #[wasm_bindgen(js_name = setNickname)]
     pub fn set_nickname(&self, nickname: String) -> Result<(), JsValue> {
-        self.inner
-            .set_nickname(nickname)
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+        self.inner.set_nickname(nickname).map_err(js_core_error)
     }
 
     /// Export the local identity as a backup string (for import on another device).
@@ -1091,7 +1212,7 @@ impl IronCore {
     pub fn export_identity_backup(&self, passphrase: String) -> Result<String, JsValue> {
         self.inner
             .export_identity_backup(passphrase)
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+            .map_err(js_core_error)
     }
 
     /// Import an identity from an encrypted backup string produced by `exportIdentityBackup`.
@@ -1104,7 +1225,7 @@ impl IronCore {
     ) -> Result<(), JsValue> {
         self.inner
             .import_identity_backup(backup, passphrase)
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+            .map_err(js_core_error)
     }
 
     /// Derive the Ed25519 public key hex from a libp2p PeerId string.
@@ -1112,7 +1233,37 @@ impl IronCore {
     pub fn extract_public_key_from_peer_id(&self, peer_id: String) -> Result<String, JsValue> {
         self.inner
             .extract_public_key_from_peer_id(peer_id)
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+            .map_err(js_core_error)
+    }
+
+    /// Parse a scanned contact QR code (`SCM:` text or the mobile JSON form),
+    /// derive its PeerId in Rust and save it as a contact if it is new.
+    /// Returns `{publicKeyHex, peerId, nickname}`; foreign QR codes and codes
+    /// whose PeerId does not match the key are rejected.
+    #[wasm_bindgen(js_name = importContactFromQr)]
+    pub fn import_contact_from_qr(&self, payload: String) -> Result<JsValue, JsValue> {
+        let parsed = scmessenger_core::identity::parse_contact_qr(&payload)
+            .map_err(|e| js_value_from_str(&format!("{}", e)))?;
+
+        let contacts = self.inner.contacts_store_manager();
+        let existing = contacts
+            .get(parsed.peer_id.clone())
+            .map_err(js_core_error)?;
+        if existing.is_none() {
+            let mut contact = scmessenger_core::store::Contact::new(
+                parsed.peer_id.clone(),
+                parsed.public_key_hex.clone(),
+            );
+            contact.nickname = parsed.nickname.clone();
+            contacts.add(contact).map_err(js_core_error)?;
+        }
+
+        serde_wasm_bindgen::to_value(&WasmImportedContact {
+            public_key_hex: parsed.public_key_hex,
+            peer_id: parsed.peer_id,
+            nickname: parsed.nickname,
+        })
+        .map_err(|e| js_value_from_str(&format!("Failed to serialize contact: {}", e)))
     }
 
     // ── Messaging (extended) ─────────────────────────────────────────────
@@ -1139,7 +1290,7 @@ impl IronCore {
                     envelope_data: p.envelope_data,
                 })
             })
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+            .map_err(js_core_error)
     }
 
     /// Prepare a delivery receipt envelope to send back to the original sender.
@@ -1152,18 +1303,70 @@ impl IronCore {
     ) -> Result<Vec<u8>, JsValue> {
         self.inner
             .prepare_receipt(recipient_public_key_hex, message_id)
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+            .map_err(js_core_error)
+    }
+
+    /// Wire names of the message types this build understands. A message of
+    /// any other type comes back from `receiveMessage` as a text stand-in.
+    #[wasm_bindgen(js_name = supportedMessageTypes)]
+    pub fn supported_message_types(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.inner.supported_message_types())
+            .map_err(|e| js_value_from_str(&format!("Failed to serialize types: {}", e)))
+    }
+
+    /// Prepare a typing indicator envelope (`isTyping` false = stopped).
+    /// Send it immediately; it is never queued or stored by the recipient.
+    #[wasm_bindgen(js_name = prepareTyping)]
+    pub fn prepare_typing(
+        &self,
+        recipient_public_key_hex: String,
+        is_typing: bool,
+    ) -> Result<Vec<u8>, JsValue> {
+        self.inner
+            .prepare_typing(recipient_public_key_hex, is_typing)
+            .map_err(js_core_error)
     }
 
     /// Generate a cover traffic payload — random bytes that look like an
-    /// encrypted message. Broadcast via `sendPreparedEnvelope` or the
-    /// swarm's send-to-all to obscure real traffic patterns.
+    /// encrypted message. Send via `sendPreparedEnvelope`, or use
+    /// `broadcastCoverTraffic` to reach every connected peer at once.
     /// `sizeBytes` is clamped to [16, 1024].
     #[wasm_bindgen(js_name = prepareCoverTraffic)]
     pub fn prepare_cover_traffic(&self, size_bytes: u32) -> Result<Vec<u8>, JsValue> {
         self.inner
             .prepare_cover_traffic(size_bytes)
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+            .map_err(js_core_error)
+    }
+
+    /// Generate and send a fresh cover traffic payload to every connected
+    /// peer in one call. Returns how many sends succeeded; 0 (not an error)
+    /// when the swarm is not running.
+    #[wasm_bindgen(js_name = broadcastCoverTraffic)]
+    pub async fn broadcast_cover_traffic(&self, size_bytes: u32) -> Result<u32, JsValue> {
+        ensure_mesh_participation_enabled(self.settings.borrow().relay_enabled)?;
+
+        let maybe_handle = self.swarm_handle.borrow().clone();
+        let Some(handle) = maybe_handle else {
+            return Ok(0);
+        };
+        let peers = handle.get_peers().await.unwrap_or_default();
+
+        let mut sent = 0u32;
+        for peer_id in peers {
+            // A distinct payload per peer, so copies cannot be correlated.
+            let payload = self
+                .inner
+                .prepare_cover_traffic(size_bytes)
+                .map_err(js_core_error)?;
+            if handle
+                .send_message(peer_id, payload, None, None)
+                .await
+                .is_ok()
+            {
+                sent += 1;
+            }
+        }
+        Ok(sent)
     }
 
     /// Remove a message from the Rust outbox after it has been delivered.
@@ -1201,7 +1404,7 @@ impl IronCore {
     pub fn get_invite_signable_data(&self, token_bytes: Vec<u8>) -> Result<Vec<u8>, JsValue> {
         self.inner
             .invite_get_signable_data(token_bytes)
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+            .map_err(js_core_error)
     }
 
     // ── Identity Resolution ──────────────────────────────────────────────
@@ -1210,9 +1413,7 @@ impl IronCore {
     /// to the canonical public_key_hex.
     #[wasm_bindgen(js_name = resolveIdentity)]
     pub fn resolve_identity(&self, any_id: String) -> Result<String, JsValue> {
-        self.inner
-            .resolve_identity(any_id)
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+        self.inner.resolve_identity(any_id).map_err(js_core_error)
     }
 
     /// Resolve any identifier format to the identity_id (Blake3 hash).
@@ -1220,7 +1421,7 @@ impl IronCore {
     pub fn resolve_to_identity_id(&self, any_id: String) -> Result<String, JsValue> {
         self.inner
             .resolve_to_identity_id(any_id)
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+            .map_err(js_core_error)
     }
 
     // ── Blocking ─────────────────────────────────────────────────────────
@@ -1230,7 +1431,7 @@ impl IronCore {
     pub fn block_peer(&self, peer_id: String, reason: Option<String>) -> Result<(), JsValue> {
         self.inner
             .block_peer(peer_id, None, reason)
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+            .map_err(js_core_error)
     }
 
     /// Unblock a previously blocked peer.
@@ -1238,7 +1439,7 @@ impl IronCore {
     pub fn unblock_peer(&self, peer_id: String) -> Result<(), JsValue> {
         self.inner
             .unblock_peer(peer_id, None)
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+            .map_err(js_core_error)
     }
 
     /// Block a peer AND delete all their stored messages (cascade purge).
@@ -1251,7 +1452,7 @@ impl IronCore {
     ) -> Result<(), JsValue> {
         self.inner
             .block_and_delete_peer(peer_id, None, reason)
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+            .map_err(js_core_error)
     }
 
     /// Check whether a peer is currently blocked.
@@ -1259,16 +1460,13 @@ impl IronCore {
     pub fn is_peer_blocked(&self, peer_id: String) -> Result<bool, JsValue> {
         self.inner
             .is_peer_blocked(peer_id, None)
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+            .map_err(js_core_error)
     }
 
     /// List all blocked peers. Returns a JS array of BlockedIdentity objects.
     #[wasm_bindgen(js_name = listBlockedPeers)]
     pub fn list_blocked_peers(&self) -> Result<js_sys::Array, JsValue> {
-        let list = self
-            .inner
-            .list_blocked_peers_raw()
-            .map_err(|e| js_value_from_str(&format!("{}", e)))?;
+        let list = self.inner.list_blocked_peers_raw().map_err(js_core_error)?;
         let array = js_sys::Array::new();
         for item in list {
             let obj = js_sys::Object::new();
@@ -1318,9 +1516,7 @@ impl IronCore {
     /// Get the count of blocked peers.
     #[wasm_bindgen(js_name = blockedCount)]
     pub fn blocked_count(&self) -> Result<u32, JsValue> {
-        self.inner
-            .blocked_count()
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+        self.inner.blocked_count().map_err(js_core_error)
     }
 
     // ── Device & Registration ────────────────────────────────────────────
@@ -1370,9 +1566,7 @@ impl IronCore {
     /// Perform storage maintenance (quota enforcement, cleanup).
     #[wasm_bindgen(js_name = performMaintenance)]
     pub fn perform_maintenance(&self) -> Result<(), JsValue> {
-        self.inner
-            .perform_maintenance()
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+        self.inner.perform_maintenance().map_err(js_core_error)
     }
 
     /// Update disk usage statistics for quota enforcement.
@@ -1416,9 +1610,7 @@ impl IronCore {
     /// Export all recorded log entries as a single string.
     #[wasm_bindgen(js_name = exportLogs)]
     pub fn export_logs(&self) -> Result<String, JsValue> {
-        self.inner
-            .export_logs()
-            .map_err(|e| js_value_from_str(&format!("{}", e)))
+        self.inner.export_logs().map_err(js_core_error)
     }
 
     // ── Peer Notifications ───────────────────────────────────────────────
@@ -1434,6 +1626,19 @@ impl IronCore {
     pub fn notify_peer_disconnected(&self, peer_id: String) {
         self.inner.notify_peer_disconnected(peer_id);
     }
+
+    /// Notify the core that a peer's identity was confirmed (Identify completed).
+    /// `listen_addrs` may be empty if the peer advertised none.
+    #[wasm_bindgen(js_name = notifyPeerIdentified)]
+    pub fn notify_peer_identified(
+        &self,
+        peer_id: String,
+        agent_version: String,
+        listen_addrs: Vec<String>,
+    ) {
+        self.inner
+            .notify_peer_identified(peer_id, agent_version, listen_addrs);
+    }
 }
 
 #[wasm_bindgen]
@@ -1710,6 +1915,16 @@ fn js_value_from_str(message: &str) -> JsValue {
     }
 }
 
+/// Error raised to JS for a core failure: the stable code, then the message,
+/// e.g. `"NOT_INITIALIZED: Identity not initialized"`.
+fn js_core_error(err: scmessenger_core::IronCoreError) -> JsValue {
+    js_value_from_str(&core_error_message(&err))
+}
+
+fn core_error_message(err: &scmessenger_core::IronCoreError) -> String {
+    format!("{}: {}", err.code(), err)
+}
+
 /// Safely convert a serializable value to JsValue, returning JsValue::NULL on error.
 /// This prevents panics from serde_wasm_bindgen::to_value() failures.
 fn to_js_value_safe<T: serde::Serialize>(value: &T) -> JsValue {
@@ -1787,13 +2002,49 @@ fn ensure_mesh_participation_enabled(relay_enabled: bool) -> Result<(), JsValue>
     ))
 }
 
+/// Apply a swarm-driven change to the connection state and, once it has held
+/// for `CONNECTION_STATE_DEBOUNCE_MS`, report the resulting path to the
+/// `onConnectionStateChanged` callback.
+fn note_connection_change(
+    state: &Rc<RefCell<ConnectionStateTracker>>,
+    change: impl FnOnce(&mut ConnectionStateTracker) -> u64,
+) {
+    let generation = change(&mut state.borrow_mut());
+    let state = Rc::clone(state);
+    wasm_bindgen_futures::spawn_local(async move {
+        gloo_timers::future::TimeoutFuture::new(CONNECTION_STATE_DEBOUNCE_MS).await;
+        let (settled, callback) = {
+            let mut state = state.borrow_mut();
+            (state.settle(generation), state.callback())
+        };
+        if let (Some(path), Some(callback)) = (settled, callback) {
+            if let Err(e) = callback.call1(&JsValue::NULL, &JsValue::from_str(path.as_str())) {
+                tracing::warn!("onConnectionStateChanged callback threw: {:?}", e);
+            }
+        }
+    });
+}
+
+/// Discovery config the browser swarm is built with. WebRTC-direct is
+/// opt-in and dial-only, so the websocket relay path stays the baseline
+/// transport.
+fn swarm_discovery_config(settings: &MeshSettings) -> scmessenger_core::transport::DiscoveryConfig {
+    scmessenger_core::transport::DiscoveryConfig::default()
+        .with_webrtc_direct(settings.webrtc_direct_enabled)
+}
+
+/// Start the swarm and spawn its event loop. The returned sender stops the
+/// loop; if the swarm instead ends on its own, the loop clears
+/// `swarm_handle` itself.
 async fn start_swarm_runtime(
     inner: std::sync::Arc<RustIronCore>,
-    rx_messages: Rc<RefCell<Vec<WasmMessage>>>,
+    rx_messages: Rc<RefCell<ReceivedMessages>>,
     settings: Rc<RefCell<MeshSettings>>,
     swarm_handle: Rc<RefCell<Option<scmessenger_core::transport::SwarmHandle>>>,
+    connection_state: Rc<RefCell<ConnectionStateTracker>>,
     bootstrap_addrs: Vec<String>,
-) -> Result<(), JsValue> {
+    require_identity: bool,
+) -> Result<tokio::sync::oneshot::Sender<()>, JsValue> {
     if swarm_handle.borrow().is_some() {
         return Err(js_value_from_str("Swarm is already running"));
     }
@@ -1804,7 +2055,8 @@ async fn start_swarm_runtime(
             .map_err(|e| js_value_from_str(&format!("Failed to start core: {}", e)))?;
     }
 
-    let (libp2p_keys, headless_mode) = resolve_swarm_keypair_and_mode(inner.as_ref())?;
+    let (libp2p_keys, headless_mode) =
+        resolve_swarm_keypair_and_mode(inner.as_ref(), require_identity)?;
 
     let bootstrap_multiaddrs: Vec<Multiaddr> = bootstrap_addrs
         .iter()
@@ -1815,6 +2067,8 @@ async fn start_swarm_runtime(
         })
         .collect::<Result<Vec<_>, _>>()?;
 
+    let discovery_config = swarm_discovery_config(&settings.borrow());
+
     let (event_tx, mut event_rx) = tokio::sync::mpsc::channel(100);
     let handle: scmessenger_core::transport::SwarmHandle =
         scmessenger_core::transport::start_swarm_with_config(
@@ -1826,18 +2080,37 @@ async fn start_swarm_runtime(
             None,
             Some(std::sync::Arc::downgrade(&inner)),
             headless_mode,
-            None, // discovery_config
+            Some(discovery_config),
             scmessenger_core::transport::default_routing_engine_handle(),
+            None,
+            None,
         )
         .await
         .map_err(|e: anyhow::Error| js_value_from_str(&format!("Failed to start swarm: {}", e)))?;
 
+    let core_settings: scmessenger_core::MeshSettings = settings.borrow().clone().into();
+    if let Err(e) = handle.apply_mesh_settings(&core_settings).await {
+        tracing::warn!("Failed to apply mesh settings to the swarm: {}", e);
+    }
+
     *swarm_handle.borrow_mut() = Some(handle);
+    note_connection_change(&connection_state, |s| s.set_running(true));
 
     let swarm_handle_for_loop: Rc<RefCell<Option<scmessenger_core::transport::SwarmHandle>>> =
         Rc::clone(&swarm_handle);
+    let (stop_tx, mut stop_rx) = tokio::sync::oneshot::channel::<()>();
     wasm_bindgen_futures::spawn_local(async move {
-        while let Some(event) = event_rx.recv().await {
+        let stopped = loop {
+            let event = tokio::select! {
+                // Checked first: once stopped, the handle slot may already
+                // hold a newer swarm and must not be touched.
+                biased;
+                _ = &mut stop_rx => break true,
+                event = event_rx.recv() => match event {
+                    Some(event) => event,
+                    None => break false,
+                },
+            };
             match event {
                 scmessenger_core::transport::SwarmEvent::MessageReceived {
                     peer_id,
@@ -1856,6 +2129,7 @@ async fn start_swarm_runtime(
                                 sender_peer_id: Some(peer_id.to_string()),
                                 text: msg.text_content(),
                                 timestamp: msg.timestamp,
+                                receive_seq: None,
                             });
                         }
                         Err(e) => {
@@ -1868,6 +2142,7 @@ async fn start_swarm_runtime(
                     }
                 }
                 scmessenger_core::transport::SwarmEvent::PeerDiscovered(peer_id) => {
+                    note_connection_change(&connection_state, |s| s.peer_connected(peer_id));
                     inner.notify_peer_discovered(peer_id.to_string());
                     // Flush any queued outbox messages for this peer
                     let pid_str = peer_id.to_string();
@@ -1881,35 +2156,106 @@ async fn start_swarm_runtime(
                     }
                 }
                 scmessenger_core::transport::SwarmEvent::PeerDisconnected(peer_id) => {
+                    note_connection_change(&connection_state, |s| s.peer_disconnected(&peer_id));
                     inner.notify_peer_disconnected(peer_id.to_string());
                 }
-                scmessenger_core::transport::SwarmEvent::PeerIdentified { peer_id, .. } => {
+                scmessenger_core::transport::SwarmEvent::PeerIdentified {
+                    peer_id,
+                    agent_version,
+                    listen_addrs,
+                    ..
+                } => {
                     tracing::info!("Swarm identified peer {}", peer_id);
+                    inner.notify_peer_identified(
+                        peer_id.to_string(),
+                        agent_version,
+                        listen_addrs.iter().map(|a| a.to_string()).collect(),
+                    );
                 }
                 scmessenger_core::transport::SwarmEvent::ListenerFailed { listener_id, error } => {
                     tracing::warn!("Swarm listener {} failed: {}", listener_id, error);
                 }
+                scmessenger_core::transport::SwarmEvent::DeliveryGivenUp { message_id, target } => {
+                    tracing::warn!("Swarm gave up delivering {} to {}", message_id, target);
+                    let _ = inner.mark_delivery_failed(
+                        message_id,
+                        format!("delivery to {} given up", target),
+                    );
+                }
+                scmessenger_core::transport::SwarmEvent::DeliveryAttempted {
+                    message_id,
+                    path,
+                    error,
+                } => {
+                    let _ = inner.record_delivery_attempt(message_id, path, error);
+                }
+                scmessenger_core::transport::SwarmEvent::RelayCongested { relay_peer } => {
+                    tracing::warn!("Relay {} is out of forwarding budget", relay_peer);
+                }
+                scmessenger_core::transport::SwarmEvent::PeerRateLimited { peer } => {
+                    tracing::warn!("Peer {} is over the inbound rate limit", peer);
+                }
+                scmessenger_core::transport::SwarmEvent::RelayRefused {
+                    peer_id,
+                    message_id,
+                    reason,
+                } => {
+                    tracing::info!(
+                        "Refused to relay {} for {} ({:?})",
+                        message_id,
+                        peer_id,
+                        reason
+                    );
+                }
+                scmessenger_core::transport::SwarmEvent::BroadcastReceived { topic, frame } => {
+                    if let Err(e) = inner.receive_broadcast(&topic, &frame) {
+                        tracing::warn!("Dropped broadcast on {}: {:?}", topic, e);
+                    }
+                }
+                scmessenger_core::transport::SwarmEvent::ListeningOn(address) => {
+                    note_connection_change(&connection_state, |s| s.listener_added(address));
+                }
+                scmessenger_core::transport::SwarmEvent::ListenAddrExpired(address) => {
+                    note_connection_change(&connection_state, |s| s.listener_removed(&address));
+                }
+                scmessenger_core::transport::SwarmEvent::NatStatusChanged(status) => {
+                    note_connection_change(&connection_state, |s| {
+                        s.set_nat(
+                            scmessenger_core::transport::NatReachability::from_status_str(&status),
+                        )
+                    });
+                }
                 scmessenger_core::transport::SwarmEvent::AddressReflected { .. }
-                | scmessenger_core::transport::SwarmEvent::ListeningOn(_)
                 | scmessenger_core::transport::SwarmEvent::PortMapping(_)
                 | scmessenger_core::transport::SwarmEvent::TopicDiscovered { .. }
                 | scmessenger_core::transport::SwarmEvent::LedgerReceived { .. }
-                | scmessenger_core::transport::SwarmEvent::NatStatusChanged(_)
                 | scmessenger_core::transport::SwarmEvent::AbuseSignalDetected { .. }
                 | scmessenger_core::transport::SwarmEvent::RelayCircuitEstablished
-                | scmessenger_core::transport::SwarmEvent::RelayCircuitBroken => {}
+                | scmessenger_core::transport::SwarmEvent::RelayCircuitBroken
+                | scmessenger_core::transport::SwarmEvent::ClockCorrected { .. } => {}
             }
-        }
+        };
 
-        *swarm_handle_for_loop.borrow_mut() = None;
+        // After `stopSwarm` both the handle slot and the connection state
+        // may already belong to a restarted swarm.
+        if !stopped {
+            *swarm_handle_for_loop.borrow_mut() = None;
+            note_connection_change(&connection_state, |s| s.set_running(false));
+        }
         tracing::info!("WASM swarm event loop terminated");
     });
 
-    Ok(())
+    Ok(stop_tx)
 }
 
+/// Prefix of the `startSwarm` error raised when `requireIdentity` is set and
+/// no identity exists yet.
+const IDENTITY_REQUIRED_ERROR: &str =
+    "IdentityRequired: create or import an identity before starting the swarm";
+
 fn resolve_swarm_keypair_and_mode(
     inner: &RustIronCore,
+    require_identity: bool,
 ) -> Result<(libp2p::identity::Keypair, bool), JsValue> {
     if let Some(identity_keys) = inner.get_identity_keys() {
         let libp2p_keys: libp2p::identity::Keypair =
@@ -1922,10 +2268,21 @@ fn resolve_swarm_keypair_and_mode(
         return Ok((libp2p_keys, false));
     }
 
+    if require_identity {
+        return Err(js_value_from_str(IDENTITY_REQUIRED_ERROR));
+    }
     tracing::info!("No identity available; starting swarm in relay-only mode");
     Ok((libp2p::identity::Keypair::generate_ed25519(), true))
 }
 
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WasmImportedContact {
+    public_key_hex: String,
+    peer_id: String,
+    nickname: Option<String>,
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct WasmIdentityInfo {
@@ -1991,6 +2348,33 @@ struct WasmMessage {
     sender_peer_id: Option<String>,
     text: Option<String>,
     timestamp: u64,
+    /// Position in receive order; only set on messages from
+    /// `drainReceivedMessages`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    receive_seq: Option<u64>,
+}
+
+/// Decoded messages waiting for `drainReceivedMessages`, each stamped with a
+/// monotonic receive sequence as it is buffered.
+#[derive(Default)]
+struct ReceivedMessages {
+    next_seq: u64,
+    pending: Vec<WasmMessage>,
+}
+
+impl ReceivedMessages {
+    fn push(&mut self, mut msg: WasmMessage) {
+        msg.receive_seq = Some(self.next_seq);
+        self.next_seq += 1;
+        self.pending.push(msg);
+    }
+
+    /// Take every buffered message, oldest receive first.
+    fn drain(&mut self) -> Vec<WasmMessage> {
+        let mut drained = std::mem::take(&mut self.pending);
+        drained.sort_by_key(|msg| msg.receive_seq);
+        drained
+    }
 }
 
 /// Web-facing MeshSettings with camelCase field names for JS interop.
@@ -2016,6 +2400,16 @@ struct WasmMeshSettings {
     notify_dm_request_in_foreground: bool,
     sound_enabled: bool,
     badge_enabled: bool,
+    #[serde(default, alias = "webrtcEnabled")]
+    webrtc_direct_enabled: bool,
+    #[serde(default = "default_inbound_messages_per_sec")]
+    inbound_messages_per_sec: u32,
+    #[serde(default = "default_inbound_burst")]
+    inbound_burst: u32,
+    #[serde(default = "default_max_topic_payload_bytes")]
+    max_topic_payload_bytes: u32,
+    #[serde(default)]
+    topic_payload_limits: HashMap<String, u32>,
 }
 
 impl From<MeshSettings> for WasmMeshSettings {
@@ -2044,6 +2438,11 @@ impl From<MeshSettings> for WasmMeshSettings {
             notify_dm_request_in_foreground: s.notify_dm_request_in_foreground,
             sound_enabled: s.sound_enabled,
             badge_enabled: s.badge_enabled,
+            webrtc_direct_enabled: s.webrtc_direct_enabled,
+            inbound_messages_per_sec: s.inbound_messages_per_sec,
+            inbound_burst: s.inbound_burst,
+            max_topic_payload_bytes: s.max_topic_payload_bytes,
+            topic_payload_limits: s.topic_payload_limits,
         }
     }
 }
@@ -2074,6 +2473,11 @@ impl From<WasmMeshSettings> for MeshSettings {
             notify_dm_request_in_foreground: w.notify_dm_request_in_foreground,
             sound_enabled: w.sound_enabled,
             badge_enabled: w.badge_enabled,
+            webrtc_direct_enabled: w.webrtc_direct_enabled,
+            inbound_messages_per_sec: w.inbound_messages_per_sec,
+            inbound_burst: w.inbound_burst,
+            max_topic_payload_bytes: w.max_topic_payload_bytes,
+            topic_payload_limits: w.topic_payload_limits,
         }
     }
 }
@@ -2216,6 +2620,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_core_errors_carry_stable_code() {
+        assert_eq!(
+            core_error_message(&scmessenger_core::IronCoreError::NotInitialized),
+            "NOT_INITIALIZED: Identity not initialized"
+        );
+        assert_eq!(
+            core_error_message(&scmessenger_core::IronCoreError::Other(
+                "disk on fire".into()
+            )),
+            "OTHER: disk on fire"
+        );
+    }
+
     #[test]
     fn test_relay_url_to_multiaddr_ws_defaults() {
         let addr = relay_url_to_multiaddr("ws://relay.example.com").unwrap();
@@ -2243,7 +2661,7 @@ mod tests {
     #[test]
     fn test_desktop_role_resolution_defaults_to_relay_only_without_identity() {
         let core = RustIronCore::new();
-        let (_, relay_only) = resolve_swarm_keypair_and_mode(&core).unwrap();
+        let (_, relay_only) = resolve_swarm_keypair_and_mode(&core, false).unwrap();
         assert!(
             relay_only,
             "desktop should start relay-only when identity is absent"
@@ -2251,7 +2669,7 @@ mod tests {
 
         core.grant_consent();
         core.initialize_identity().unwrap();
-        let (_, full_role) = resolve_swarm_keypair_and_mode(&core).unwrap();
+        let (_, full_role) = resolve_swarm_keypair_and_mode(&core, false).unwrap();
         assert!(
             !full_role,
             "desktop should switch to full role after identity initialization"
@@ -2345,6 +2763,212 @@ mod tests {
         assert_eq!(stats.undelivered_count, 1);
     }
 
+    #[test]
+    fn test_swarm_transport_follows_webrtc_direct_setting() {
+        // Settings saved before the flag existed build a websocket-only swarm.
+        let legacy = serde_json::to_value(WasmMeshSettings::from(MeshSettings::default()))
+            .unwrap()
+            .as_object()
+            .cloned()
+            .map(|mut m| {
+                m.remove("webrtcDirectEnabled");
+                m
+            })
+            .unwrap();
+        let parsed: WasmMeshSettings = serde_json::from_value(Value::Object(legacy)).unwrap();
+        assert!(!swarm_discovery_config(&parsed.into()).enable_webrtc_direct);
+
+        // Opting in survives the JS round trip and adds the WebRTC transport.
+        let enabled = WasmMeshSettings::from(MeshSettings {
+            webrtc_direct_enabled: true,
+            ..MeshSettings::default()
+        });
+        let enabled_json = serde_json::to_value(&enabled).unwrap();
+        assert!(swarm_discovery_config(&enabled.into()).enable_webrtc_direct);
+
+        // So do settings saved under the earlier `webrtcEnabled` key.
+        let mut renamed = enabled_json.as_object().cloned().unwrap();
+        let flag = renamed.remove("webrtcDirectEnabled").unwrap();
+        renamed.insert("webrtcEnabled".to_string(), flag);
+        let parsed: WasmMeshSettings = serde_json::from_value(Value::Object(renamed)).unwrap();
+        assert!(swarm_discovery_config(&parsed.into()).enable_webrtc_direct);
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_swarm_starts_with_webrtc_direct_dialing() {
+        let core = IronCore::new();
+        let settings = WasmMeshSettings::from(MeshSettings {
+            webrtc_direct_enabled: true,
+            ..MeshSettings::default()
+        });
+        core.update_settings(serde_wasm_bindgen::to_value(&settings).unwrap())
+            .unwrap();
+
+        core.start_swarm(js_sys::Array::new().into(), None)
+            .await
+            .expect("swarm should build with WebRTC-direct dialing enabled");
+        core.stop_swarm().await.unwrap();
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_swarm_restarts_after_stop() {
+        let core = IronCore::new();
+        for _ in 0..2 {
+            core.start_swarm(js_sys::Array::new().into(), None)
+                .await
+                .expect("swarm should start after the previous stop");
+            assert!(core.swarm_handle.borrow().is_some());
+            assert!(core
+                .start_swarm(js_sys::Array::new().into(), None)
+                .await
+                .is_err());
+
+            core.stop_swarm().await.unwrap();
+            assert!(core.swarm_handle.borrow().is_none());
+            assert!(core.swarm_stop.borrow().is_none());
+        }
+
+        // The stopped loops have exited without clearing the live handle or
+        // marking the restarted swarm as stopped.
+        core.start_swarm(js_sys::Array::new().into(), None)
+            .await
+            .unwrap();
+        gloo_timers::future::TimeoutFuture::new(50).await;
+        assert!(core.swarm_handle.borrow().is_some());
+        assert_ne!(
+            core.connection_state.borrow().current(),
+            scmessenger_core::transport::ConnectionPath::Disconnected
+        );
+        core.stop_swarm().await.unwrap();
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_connection_state_callback_fires_on_settled_transitions() {
+        let core = IronCore::new();
+        let seen = js_sys::Array::new();
+        let push = js_sys::Function::new_with_args("state", "this.push(state)").bind0(&seen);
+        core.on_connection_state_changed(Some(push));
+        let settle = || gloo_timers::future::TimeoutFuture::new(CONNECTION_STATE_DEBOUNCE_MS * 2);
+        let state = &core.connection_state;
+        let peer = PeerId::random();
+
+        note_connection_change(state, |s| s.set_running(true));
+        settle().await;
+        note_connection_change(state, |s| {
+            s.listener_added("/ip4/192.168.1.5/tcp/4001".parse().unwrap())
+        });
+        note_connection_change(state, |s| s.peer_connected(peer));
+        settle().await;
+        // A disconnect/reconnect inside the debounce window is not reported.
+        note_connection_change(state, |s| s.peer_disconnected(&peer));
+        note_connection_change(state, |s| s.peer_connected(peer));
+        settle().await;
+        note_connection_change(state, |s| s.peer_disconnected(&peer));
+        settle().await;
+
+        let states: Vec<String> = seen.iter().filter_map(|v| v.as_string()).collect();
+        assert_eq!(
+            states,
+            vec!["Bootstrapping", "DirectPreferred", "Bootstrapping"]
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_import_contact_from_qr_derives_peer_id() {
+        let core = IronCore::new();
+        let bob = RustIronCore::new();
+        bob.grant_consent();
+        bob.initialize_identity().unwrap();
+        let info = bob.get_identity_info();
+        let payload = scmessenger_core::identity::ContactQr::new(
+            info.public_key_hex.as_deref().unwrap(),
+            Some("Bob".to_string()),
+        )
+        .unwrap()
+        .to_payload();
+
+        let imported: WasmImportedContact =
+            serde_wasm_bindgen::from_value(core.import_contact_from_qr(payload).unwrap()).unwrap();
+        assert_eq!(Some(imported.peer_id.clone()), info.libp2p_peer_id);
+        assert_eq!(Some(imported.public_key_hex), info.public_key_hex);
+        assert_eq!(imported.nickname.as_deref(), Some("Bob"));
+        assert!(core
+            .get_contact_manager()
+            .get(imported.peer_id)
+            .map(|c| c.is_object())
+            .unwrap());
+
+        assert!(core
+            .import_contact_from_qr("WIFI:T:WPA;S:home;P:secret;;".to_string())
+            .is_err());
+    }
+
+    #[test]
+    fn test_swarm_keypair_without_identity_honours_require_identity() {
+        let core = RustIronCore::new();
+        let (_, headless) = resolve_swarm_keypair_and_mode(&core, false).unwrap();
+        assert!(headless);
+        assert!(resolve_swarm_keypair_and_mode(&core, true).is_err());
+
+        core.grant_consent();
+        core.initialize_identity().unwrap();
+        let (keys, headless) = resolve_swarm_keypair_and_mode(&core, true).unwrap();
+        assert!(!headless);
+        assert_eq!(
+            Some(keys.public().to_peer_id().to_string()),
+            core.get_identity_info().libp2p_peer_id
+        );
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_start_swarm_require_identity_reports_onboarding_error() {
+        let core = IronCore::new();
+        let err = core
+            .start_swarm(js_sys::Array::new().into(), Some(true))
+            .await
+            .unwrap_err();
+        assert!(err.as_string().unwrap().starts_with("IdentityRequired"));
+        assert!(core.swarm_handle.borrow().is_none());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_drained_messages_follow_receive_order() {
+        let core = IronCore::new();
+        for id in ["c", "a", "b"] {
+            core.rx_messages.borrow_mut().push(WasmMessage {
+                id: id.to_string(),
+                sender_id: "sender".to_string(),
+                sender_peer_id: None,
+                text: None,
+                // Same-millisecond arrivals: only the sequence orders them.
+                timestamp: 1_700_000_000_000,
+                receive_seq: None,
+            });
+        }
+        let first: Vec<WasmMessage> = core
+            .drain_received_messages()
+            .iter()
+            .map(|v| serde_wasm_bindgen::from_value(v).unwrap())
+            .collect();
+        let ids: Vec<&str> = first.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["c", "a", "b"]);
+        let seqs: Vec<Option<u64>> = first.iter().map(|m| m.receive_seq).collect();
+        assert_eq!(seqs, vec![Some(0), Some(1), Some(2)]);
+
+        core.rx_messages.borrow_mut().push(WasmMessage {
+            id: "d".to_string(),
+            sender_id: "sender".to_string(),
+            sender_peer_id: None,
+            text: None,
+            timestamp: 1_700_000_000_000,
+            receive_seq: None,
+        });
+        let next: WasmMessage =
+            serde_wasm_bindgen::from_value(core.drain_received_messages().get(0)).unwrap();
+        assert_eq!(next.receive_seq, Some(3));
+        assert_eq!(core.drain_received_messages().length(), 0);
+    }
+
     // Notification Manager Tests
     #[wasm_bindgen_test]
     fn test_notification_manager_creation() {